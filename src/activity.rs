@@ -0,0 +1,254 @@
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::claude::get_claude_sessions;
+use crate::devenv::EnvChange;
+use crate::git::last_commit_time;
+use crate::state::{PigsState, WorktreeInfo, get_config_dir};
+
+/// Agent launches kept per worktree in `agent_history`.
+const AGENT_HISTORY_LIMIT: usize = 10;
+
+/// One pigs action performed against a worktree, appended to
+/// `~/.pigs/activity.jsonl`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActivityRecord {
+    pub timestamp: DateTime<Utc>,
+    pub repo_name: String,
+    pub worktree: String,
+    pub action: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    /// Dashboard session the action started
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    /// Command an agent or task was launched with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<LaunchCommand>,
+}
+
+/// The exact command a session was launched with, so a run that behaves
+/// oddly can be traced to the model, flags, or resume id it used.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LaunchCommand {
+    pub program: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Variables changed by the worktree's direnv/mise setup, as names only
+    /// (`-NAME` when removed) since they may hold secrets, plus any
+    /// `*_MODEL` variable with its value
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub env: Vec<String>,
+}
+
+/// An agent launched in a worktree, kept in its `agent_history` so `pigs list`
+/// and the dashboard can show which agent drove it last.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AgentUse {
+    /// Program name without its directory, e.g. `claude` or `codex`
+    pub agent: String,
+    pub at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+}
+
+impl LaunchCommand {
+    pub fn new(program: &str, args: &[String], env_changes: &[EnvChange]) -> Self {
+        let is_model = |key: &str| key.ends_with("_MODEL");
+        let mut env: Vec<String> = env_changes
+            .iter()
+            .filter(|(key, _)| !is_model(key))
+            .map(|(key, value)| match value {
+                Some(_) => key.clone(),
+                None => format!("-{key}"),
+            })
+            .collect();
+        let mut models: BTreeMap<String, Option<String>> = std::env::vars()
+            .filter(|(key, _)| is_model(key))
+            .map(|(key, value)| (key, Some(value)))
+            .collect();
+        models.extend(env_changes.iter().filter(|(key, _)| is_model(key)).cloned());
+        env.extend(models.into_iter().map(|(key, value)| match value {
+            Some(value) => format!("{key}={value}"),
+            None => format!("-{key}"),
+        }));
+        Self {
+            program: program.to_string(),
+            args: args.to_vec(),
+            env,
+        }
+    }
+
+    /// Shell-style command line, e.g. `claude --model opus 'fix the tests'`.
+    pub fn command_line(&self) -> String {
+        std::iter::once(&self.program)
+            .chain(&self.args)
+            .map(|part| {
+                if !part.is_empty()
+                    && part
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || "-_./=:@%+,".contains(c))
+                {
+                    part.clone()
+                } else {
+                    format!("'{}'", part.replace('\'', "'\\''"))
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+fn activity_path() -> Result<PathBuf> {
+    Ok(get_config_dir()?.join("activity.jsonl"))
+}
+
+/// Name of the OS user running pigs, used to attribute CLI actions.
+pub fn local_user() -> String {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .ok()
+        .filter(|name| !name.trim().is_empty())
+        .unwrap_or_else(|| "local".to_string())
+}
+
+/// Like `record_as`, for an action that launched `command`.
+pub fn record_launch(
+    user: &str,
+    repo_name: &str,
+    worktree: &str,
+    action: &str,
+    detail: Option<String>,
+    session_id: Option<&str>,
+    command: &LaunchCommand,
+) {
+    write_entry(ActivityRecord {
+        timestamp: Utc::now(),
+        repo_name: repo_name.to_string(),
+        worktree: worktree.to_string(),
+        action: action.to_string(),
+        detail,
+        user: Some(user.to_string()),
+        session_id: session_id.map(str::to_string),
+        command: Some(command.clone()),
+    });
+}
+
+/// Append an action to the activity log attributed to `user`. Failures are
+/// reported but never interrupt the command that triggered them.
+pub fn record_as(
+    user: &str,
+    repo_name: &str,
+    worktree: &str,
+    action: &str,
+    detail: Option<String>,
+) {
+    let entry = ActivityRecord {
+        timestamp: Utc::now(),
+        repo_name: repo_name.to_string(),
+        worktree: worktree.to_string(),
+        action: action.to_string(),
+        detail,
+        user: Some(user.to_string()),
+        session_id: None,
+        command: None,
+    };
+    write_entry(entry);
+}
+
+/// Add a launch of `program` with `args` to the worktree's `agent_history`,
+/// dropping the oldest entries past `AGENT_HISTORY_LIMIT`. Like the activity
+/// log, failures are reported but never stop the launch.
+pub fn record_agent_use(repo_name: &str, worktree: &str, program: &str, args: &[String]) {
+    let agent = std::path::Path::new(program).file_name().map_or_else(
+        || program.to_string(),
+        |name| name.to_string_lossy().into_owned(),
+    );
+    let update = || -> Result<()> {
+        let mut state = PigsState::load()?;
+        let key = PigsState::make_key(repo_name, worktree);
+        let Some(info) = state.worktrees.get_mut(&key) else {
+            return Ok(());
+        };
+        info.agent_history.push(AgentUse {
+            agent,
+            at: Utc::now(),
+            args: args.to_vec(),
+        });
+        let excess = info.agent_history.len().saturating_sub(AGENT_HISTORY_LIMIT);
+        info.agent_history.drain(..excess);
+        state.save()
+    };
+    if let Err(err) = update() {
+        eprintln!("Warning: failed to record agent history: {err}");
+    }
+}
+
+fn write_entry(entry: ActivityRecord) {
+    if let Err(err) = append(&entry) {
+        eprintln!("Warning: failed to record activity: {err}");
+    }
+}
+
+fn append(entry: &ActivityRecord) -> Result<()> {
+    let path = activity_path()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).context("Failed to create config directory")?;
+    }
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .context("Failed to open activity log")?;
+    writeln!(file, "{}", serde_json::to_string(entry)?).context("Failed to write activity log")?;
+    Ok(())
+}
+
+/// When a worktree was last worked on: its newest commit or Claude session,
+/// or when it was created. Matches how the dashboard orders worktrees, minus
+/// Codex sessions, which take a scan of every Codex log to find.
+pub fn last_activity(info: &WorktreeInfo) -> DateTime<Utc> {
+    let sessions = get_claude_sessions(&info.path);
+    last_commit_time(&info.path)
+        .into_iter()
+        .chain(sessions.iter().filter_map(|session| session.last_timestamp))
+        .fold(info.created_at, DateTime::max)
+}
+
+/// Read recorded actions for one worktree at or after `since`, oldest first.
+/// Malformed lines are skipped.
+pub fn read_for_worktree(
+    repo_name: &str,
+    worktree: &str,
+    since: DateTime<Utc>,
+) -> Result<Vec<ActivityRecord>> {
+    Ok(read_all()?
+        .into_iter()
+        .filter(|entry| {
+            entry.repo_name == repo_name && entry.worktree == worktree && entry.timestamp >= since
+        })
+        .collect())
+}
+
+/// Read every recorded action, oldest first. Malformed lines are skipped.
+pub fn read_all() -> Result<Vec<ActivityRecord>> {
+    let path = activity_path()?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let file = fs::File::open(&path).context("Failed to open activity log")?;
+    Ok(BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str::<ActivityRecord>(&line).ok())
+        .collect())
+}
@@ -0,0 +1,340 @@
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::state::{PigsState, RepoConfig, WorktreeInfo, get_config_dir};
+
+/// Runs kept per worktree; recording a new one deletes the oldest beyond this.
+const MAX_RUNS: usize = 10;
+/// File the task's terminal output is saved to in every run.
+pub const OUTPUT_LOG: &str = "output.log";
+
+/// Outputs saved from one verification run (a repo task run from the dashboard).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArtifactRun {
+    /// Directory name under the worktree's artifacts directory
+    pub id: String,
+    pub task: String,
+    pub finished_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i64>,
+    pub success: bool,
+    #[serde(default)]
+    pub files: Vec<ArtifactFile>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArtifactFile {
+    /// Path relative to the run directory, e.g. `coverage/coverage-summary.json`
+    pub name: String,
+    pub kind: ArtifactKind,
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ArtifactKind {
+    Junit,
+    Coverage,
+    Log,
+    Other,
+}
+
+impl ArtifactKind {
+    fn detect(name: &str) -> Self {
+        let lower = name.to_ascii_lowercase();
+        if lower.ends_with(".xml") && (lower.contains("junit") || lower.contains("test")) {
+            Self::Junit
+        } else if lower.contains("coverage") || lower.ends_with("lcov.info") {
+            Self::Coverage
+        } else if lower.ends_with(".log") || lower.ends_with(".txt") {
+            Self::Log
+        } else {
+            Self::Other
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Junit => "junit",
+            Self::Coverage => "coverage",
+            Self::Log => "log",
+            Self::Other => "other",
+        }
+    }
+}
+
+/// `~/.pigs/artifacts/<repo>/<worktree>`, holding one directory per run.
+pub fn worktree_dir(info: &WorktreeInfo) -> Result<PathBuf> {
+    Ok(get_config_dir()?
+        .join("artifacts")
+        .join(&info.repo_name)
+        .join(&info.name))
+}
+
+pub fn run_dir(info: &WorktreeInfo, run: &ArtifactRun) -> Result<PathBuf> {
+    Ok(worktree_dir(info)?.join(&run.id))
+}
+
+/// Save a finished task's output plus the files listed under `artifacts` in
+/// the repo's `.pigs/settings.json`, and record the run on the worktree.
+pub fn record_run(
+    key: &str,
+    task: &str,
+    exit_code: Option<i64>,
+    output: &str,
+) -> Result<ArtifactRun> {
+    let info = PigsState::load()?
+        .worktrees
+        .get(key)
+        .cloned()
+        .with_context(|| format!("Worktree '{key}' not found"))?;
+    let finished_at = Utc::now();
+    let id = format!(
+        "{}-{}",
+        finished_at.format("%Y%m%dT%H%M%S%3fZ"),
+        task.replace(|c: char| !c.is_ascii_alphanumeric() && c != '-', "_")
+    );
+    let dir = worktree_dir(&info)?.join(&id);
+    fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+
+    fs::write(dir.join(OUTPUT_LOG), output).context("Failed to save task output")?;
+    let mut files = vec![ArtifactFile {
+        name: OUTPUT_LOG.to_string(),
+        kind: ArtifactKind::Log,
+        size: output.len() as u64,
+    }];
+    for rel_path in RepoConfig::load(&info.path)?.artifacts {
+        match artifact_source(&info.path, &rel_path) {
+            Ok(Some(source)) => collect(&source, &dir, Path::new(&rel_path), &mut files)?,
+            Ok(None) => {}
+            Err(err) => eprintln!("Warning: skipping artifact '{rel_path}': {err}"),
+        }
+    }
+
+    let run = ArtifactRun {
+        id,
+        task: task.to_string(),
+        finished_at,
+        exit_code,
+        success: exit_code == Some(0),
+        files,
+    };
+
+    // Reload so changes made while the task ran aren't overwritten
+    let mut state = PigsState::load()?;
+    if let Some(entry) = state.worktrees.get_mut(key) {
+        entry.artifacts.push(run.clone());
+        let excess = entry.artifacts.len().saturating_sub(MAX_RUNS);
+        for old in entry.artifacts.drain(..excess) {
+            let _ = fs::remove_dir_all(worktree_dir(&info)?.join(&old.id));
+        }
+        state.save()?;
+    }
+    Ok(run)
+}
+
+/// The file or directory an `artifacts` entry names inside `worktree`, or
+/// `None` if it doesn't exist. Entries must be relative paths without `..`
+/// that stay inside the worktree once symlinks are resolved, so a repo's
+/// settings can't read or overwrite files elsewhere.
+fn artifact_source(worktree: &Path, rel_path: &str) -> Result<Option<PathBuf>> {
+    let relative = Path::new(rel_path)
+        .components()
+        .all(|component| matches!(component, Component::Normal(_)));
+    if rel_path.is_empty() || !relative {
+        anyhow::bail!("artifact paths must be relative, without '..'");
+    }
+    let source = worktree.join(rel_path);
+    if !source.exists() {
+        return Ok(None);
+    }
+    let root = worktree.canonicalize()?;
+    if !source.canonicalize()?.starts_with(&root) {
+        anyhow::bail!("it resolves to a path outside the worktree");
+    }
+    Ok(Some(source))
+}
+
+/// Copy `source` (a file or directory) to `dest_root/rel_path`. Symlinks
+/// inside a directory are skipped, since they may point out of the worktree.
+fn collect(
+    source: &Path,
+    dest_root: &Path,
+    rel_path: &Path,
+    files: &mut Vec<ArtifactFile>,
+) -> Result<()> {
+    let dest = dest_root.join(rel_path);
+    if source.is_dir() {
+        for entry in fs::read_dir(source)? {
+            let entry = entry?;
+            if entry.file_type()?.is_symlink() {
+                continue;
+            }
+            collect(
+                &entry.path(),
+                dest_root,
+                &rel_path.join(entry.file_name()),
+                files,
+            )?;
+        }
+        return Ok(());
+    }
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let size = fs::copy(source, &dest)
+        .with_context(|| format!("Failed to save artifact {}", source.display()))?;
+    let name = rel_path.to_string_lossy().replace('\\', "/");
+    files.push(ArtifactFile {
+        kind: ArtifactKind::detect(&name),
+        name,
+        size,
+    });
+    Ok(())
+}
+
+/// Delete every saved run for a worktree that is going away.
+pub fn remove_all(info: &WorktreeInfo) {
+    if let Ok(dir) = worktree_dir(info)
+        && dir.exists()
+        && let Err(err) = fs::remove_dir_all(&dir)
+    {
+        eprintln!(
+            "Warning: failed to remove artifacts in {}: {err}",
+            dir.display()
+        );
+    }
+}
+
+/// Markdown section describing the latest run of each task, for PR bodies.
+pub fn markdown_summary(info: &WorktreeInfo) -> Result<Option<String>> {
+    let mut latest: Vec<&ArtifactRun> = Vec::new();
+    for run in info.artifacts.iter().rev() {
+        if !latest.iter().any(|seen| seen.task == run.task) {
+            latest.push(run);
+        }
+    }
+    if latest.is_empty() {
+        return Ok(None);
+    }
+    latest.sort_by(|a, b| a.task.cmp(&b.task));
+
+    let mut out = String::from("## Verification\n\n");
+    for run in latest {
+        let outcome = if run.success {
+            "✅ passed"
+        } else {
+            "❌ failed"
+        };
+        out.push_str(&format!(
+            "- `{}` {} ({})\n",
+            run.task,
+            outcome,
+            run.finished_at.format("%Y-%m-%d %H:%M UTC")
+        ));
+        let dir = run_dir(info, run)?;
+        for file in run.files.iter().filter(|f| f.kind != ArtifactKind::Log) {
+            let details = fs::read_to_string(dir.join(&file.name))
+                .ok()
+                .and_then(|content| match file.kind {
+                    ArtifactKind::Junit => junit_totals(&content),
+                    ArtifactKind::Coverage => coverage_totals(&content),
+                    _ => None,
+                })
+                .map(|details| format!(": {details}"))
+                .unwrap_or_default();
+            out.push_str(&format!(
+                "  - {} `{}`{}\n",
+                file.kind.as_str(),
+                file.name,
+                details
+            ));
+        }
+    }
+    Ok(Some(out))
+}
+
+/// "12 tests, 1 failed" from the first `<testsuites>` or `<testsuite>` tag.
+fn junit_totals(xml: &str) -> Option<String> {
+    let start = xml.find("<testsuites").or_else(|| xml.find("<testsuite"))?;
+    let tag = &xml[start..start + xml[start..].find('>')?];
+    let attr = |name: &str| -> u64 {
+        tag.split_once(&format!(" {name}=\""))
+            .and_then(|(_, rest)| rest.split('"').next())
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(0)
+    };
+    let tests = attr("tests");
+    let failed = attr("failures") + attr("errors");
+    Some(format!("{tests} tests, {failed} failed"))
+}
+
+/// Line coverage from an Istanbul `coverage-summary.json`.
+fn coverage_totals(json: &str) -> Option<String> {
+    let summary: serde_json::Value = serde_json::from_str(json).ok()?;
+    let pct = summary.pointer("/total/lines/pct")?.as_f64()?;
+    Some(format!("{pct:.1}% of lines covered"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn artifact_kinds_are_detected_from_names() {
+        assert_eq!(
+            ArtifactKind::detect("target/junit.xml"),
+            ArtifactKind::Junit
+        );
+        assert_eq!(
+            ArtifactKind::detect("reports/test-results.xml"),
+            ArtifactKind::Junit
+        );
+        assert_eq!(
+            ArtifactKind::detect("coverage/coverage-summary.json"),
+            ArtifactKind::Coverage
+        );
+        assert_eq!(ArtifactKind::detect("lcov.info"), ArtifactKind::Coverage);
+        assert_eq!(ArtifactKind::detect("build.log"), ArtifactKind::Log);
+        assert_eq!(ArtifactKind::detect("report.html"), ArtifactKind::Other);
+    }
+
+    #[test]
+    fn artifact_sources_stay_inside_the_worktree() {
+        let dir = tempfile::tempdir().unwrap();
+        let worktree = dir.path().join("wt");
+        fs::create_dir_all(worktree.join("target")).unwrap();
+        fs::write(worktree.join("target/junit.xml"), "<testsuites/>").unwrap();
+        let outside = dir.path().join("secret.txt");
+        fs::write(&outside, "secret").unwrap();
+        std::os::unix::fs::symlink(&outside, worktree.join("link.txt")).unwrap();
+
+        assert_eq!(
+            artifact_source(&worktree, "target/junit.xml").unwrap(),
+            Some(worktree.join("target/junit.xml"))
+        );
+        assert_eq!(artifact_source(&worktree, "missing.xml").unwrap(), None);
+        assert!(artifact_source(&worktree, outside.to_str().unwrap()).is_err());
+        assert!(artifact_source(&worktree, "../secret.txt").is_err());
+        assert!(artifact_source(&worktree, "target/../../secret.txt").is_err());
+        assert!(artifact_source(&worktree, "link.txt").is_err());
+        assert_eq!(fs::read_to_string(&outside).unwrap(), "secret");
+    }
+
+    #[test]
+    fn summaries_read_junit_and_coverage_totals() {
+        let junit = r#"<?xml version="1.0"?>
+<testsuites name="jest" tests="12" failures="1" errors="0">
+  <testsuite name="a" tests="12" failures="1"></testsuite>
+</testsuites>"#;
+        assert_eq!(junit_totals(junit).unwrap(), "12 tests, 1 failed");
+
+        let coverage = r#"{"total": {"lines": {"total": 200, "covered": 163, "pct": 81.5}}}"#;
+        assert_eq!(coverage_totals(coverage).unwrap(), "81.5% of lines covered");
+        assert!(coverage_totals("{}").is_none());
+    }
+}
@@ -1,119 +1,182 @@
 use chrono::{DateTime, Utc};
+use serde_json::Value;
 use std::fs;
 use std::io::{BufRead, BufReader};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Default)]
 pub struct SessionInfo {
+    /// Session id, the transcript's file name; empty for tailed transcripts
+    pub id: String,
     pub last_user_message: String,
+    /// Text of the agent's final reply, typically a summary of what it did
+    pub last_assistant_message: Option<String>,
+    pub first_timestamp: Option<DateTime<Utc>>,
     pub last_timestamp: Option<DateTime<Utc>>,
+    /// Timestamp of the last entry of any kind, including assistant replies
+    pub last_activity: Option<DateTime<Utc>>,
+    pub model: Option<String>,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cost_usd: f64,
 }
 
-pub fn get_claude_sessions(project_path: &Path) -> Vec<SessionInfo> {
-    // Get home directory
-    let Ok(home) = std::env::var("HOME") else {
-        return vec![];
-    };
+impl SessionInfo {
+    /// Fold one transcript entry into the summary.
+    pub fn apply(&mut self, json: &Value) {
+        if let Some(ts_str) = json.get("timestamp").and_then(|t| t.as_str())
+            && let Ok(ts) = DateTime::parse_from_rfc3339(ts_str)
+        {
+            self.last_activity = Some(ts.with_timezone(&Utc));
+        }
 
-    // Construct path to Claude projects directory
-    let claude_projects_dir = Path::new(&home).join(".claude").join("projects");
+        if json.get("type").and_then(|t| t.as_str()) == Some("assistant") {
+            if let Some(name) = json
+                .pointer("/message/model")
+                .and_then(|m| m.as_str())
+                .filter(|name| !name.starts_with('<'))
+            {
+                self.model = Some(name.to_string());
+            }
+            if let Some(usage) = json.pointer("/message/usage") {
+                let count = |key: &str| usage.get(key).and_then(|v| v.as_u64()).unwrap_or(0);
+                self.input_tokens += count("input_tokens")
+                    + count("cache_creation_input_tokens")
+                    + count("cache_read_input_tokens");
+                self.output_tokens += count("output_tokens");
+            }
+            self.cost_usd += json.get("costUSD").and_then(|c| c.as_f64()).unwrap_or(0.0);
+            let text = json
+                .pointer("/message/content")
+                .and_then(|c| c.as_array())
+                .map(|items| {
+                    items
+                        .iter()
+                        .filter(|item| item.get("type").and_then(|t| t.as_str()) == Some("text"))
+                        .filter_map(|item| item.get("text").and_then(|t| t.as_str()))
+                        .collect::<Vec<_>>()
+                        .join("\n\n")
+                })
+                .unwrap_or_default();
+            if !text.trim().is_empty() {
+                self.last_assistant_message = Some(text.trim().to_string());
+            }
+            return;
+        }
 
-    // Get canonical path of the project
-    let Ok(canonical_path) = project_path.canonicalize() else {
-        return vec![];
-    };
+        if json.get("type").and_then(|t| t.as_str()) == Some("user") {
+            // Extract timestamp
+            if let Some(ts_str) = json.get("timestamp").and_then(|t| t.as_str())
+                && let Ok(ts) = DateTime::parse_from_rfc3339(ts_str)
+            {
+                let ts = ts.with_timezone(&Utc);
+                self.first_timestamp.get_or_insert(ts);
+                self.last_timestamp = Some(ts);
+            }
 
-    // Convert path to Claude's format (replace / with -)
-    let encoded_path = canonical_path.to_string_lossy().replace('/', "-");
+            // Extract message content
+            if let Some(message) = json.get("message") {
+                let content = message.get("content").and_then(|c| c.as_str()).map_or_else(
+                    || {
+                        message
+                            .get("content")
+                            .and_then(|c| c.as_array())
+                            .map_or_else(String::new, |content_arr| {
+                                content_arr
+                                    .iter()
+                                    .filter_map(|item| item.get("text").and_then(|t| t.as_str()))
+                                    .collect::<Vec<_>>()
+                                    .join(" ")
+                            })
+                    },
+                    std::string::ToString::to_string,
+                );
 
-    let project_dir = claude_projects_dir.join(&encoded_path);
+                // Filter out system messages and empty content
+                if !content.is_empty()
+                    && !content.starts_with("<local-command")
+                    && !content.starts_with("<command-")
+                    && !content.starts_with("Caveat:")
+                    && !content.contains("[Request interrupted")
+                {
+                    self.last_user_message = content;
+                }
+            }
+        }
+    }
+}
 
-    // List session files (.jsonl files)
-    let mut sessions = vec![];
-    if let Ok(entries) = fs::read_dir(&project_dir) {
-        for entry in entries.flatten() {
-            if let Some(name) = entry.file_name().to_str()
-                && std::path::Path::new(name)
-                    .extension()
-                    .is_some_and(|ext| ext.eq_ignore_ascii_case("jsonl"))
-            {
-                // Read session data from the file
-                let mut last_user_message = String::new();
-                let mut last_timestamp = None;
+/// Where Claude keeps the transcripts of every project.
+pub fn projects_dir() -> Option<PathBuf> {
+    let home = std::env::var("HOME").ok()?;
+    Some(Path::new(&home).join(".claude").join("projects"))
+}
 
-                if let Ok(file) = fs::File::open(entry.path()) {
-                    let reader = BufReader::new(file);
-                    let mut user_messages = Vec::new();
+/// Where Claude keeps the transcripts of one project directory.
+pub fn project_dir(project_path: &Path) -> Option<PathBuf> {
+    // Get canonical path of the project
+    let canonical_path = project_path.canonicalize().ok()?;
 
-                    for line in reader.lines().map_while(Result::ok) {
-                        if let Ok(json) = serde_json::from_str::<serde_json::Value>(&line)
-                            && json.get("type").and_then(|t| t.as_str()) == Some("user")
-                        {
-                            // Extract timestamp
-                            if let Some(ts_str) = json.get("timestamp").and_then(|t| t.as_str())
-                                && let Ok(ts) = DateTime::parse_from_rfc3339(ts_str)
-                            {
-                                last_timestamp = Some(ts.with_timezone(&Utc));
-                            }
+    // Convert path to Claude's format (replace / with -)
+    let encoded_path = canonical_path.to_string_lossy().replace('/', "-");
 
-                            // Extract message content
-                            if let Some(message) = json.get("message") {
-                                let content =
-                                    message.get("content").and_then(|c| c.as_str()).map_or_else(
-                                        || {
-                                            message
-                                                .get("content")
-                                                .and_then(|c| c.as_array())
-                                                .map_or_else(String::new, |content_arr| {
-                                                    content_arr
-                                                        .iter()
-                                                        .filter_map(|item| {
-                                                            item.get("text")
-                                                                .and_then(|t| t.as_str())
-                                                        })
-                                                        .collect::<Vec<_>>()
-                                                        .join(" ")
-                                                })
-                                        },
-                                        std::string::ToString::to_string,
-                                    );
+    Some(projects_dir()?.join(encoded_path))
+}
 
-                                // Filter out system messages and empty content
-                                if !content.is_empty()
-                                    && !content.starts_with("<local-command")
-                                    && !content.starts_with("<command-")
-                                    && !content.starts_with("Caveat:")
-                                    && !content.contains("[Request interrupted")
-                                {
-                                    user_messages.push(content);
-                                }
-                            }
-                        }
-                    }
+/// Session transcripts (`.jsonl` files) Claude keeps for a project directory.
+pub fn session_files(project_path: &Path) -> Vec<PathBuf> {
+    let Some(project_dir) = project_dir(project_path) else {
+        return vec![];
+    };
 
-                    // Get the last meaningful user message
-                    if let Some(msg) = user_messages.last() {
-                        last_user_message.clone_from(msg);
-                    }
-                }
+    let Ok(entries) = fs::read_dir(&project_dir) else {
+        return vec![];
+    };
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("jsonl"))
+        })
+        .collect()
+}
 
-                // Only add sessions with user messages
-                if !last_user_message.is_empty() {
-                    sessions.push(SessionInfo {
-                        last_user_message,
-                        last_timestamp,
-                    });
+pub fn get_claude_sessions(project_path: &Path) -> Vec<SessionInfo> {
+    let mut sessions = vec![];
+    for path in session_files(project_path) {
+        // Read session data from the file
+        let mut session = SessionInfo {
+            id: path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            ..SessionInfo::default()
+        };
+        if let Ok(file) = fs::File::open(&path) {
+            let reader = BufReader::new(file);
+            for line in reader.lines().map_while(Result::ok) {
+                if let Ok(json) = serde_json::from_str::<Value>(&line) {
+                    session.apply(&json);
                 }
             }
         }
+
+        // Only add sessions with user messages
+        if !session.last_user_message.is_empty() {
+            sessions.push(session);
+        }
     }
+    sort_sessions(&mut sessions);
+    sessions
+}
 
-    // Sort by timestamp (most recent first)
+/// Sort by timestamp (most recent first)
+pub fn sort_sessions(sessions: &mut [SessionInfo]) {
     sessions.sort_by(|a, b| match (&b.last_timestamp, &a.last_timestamp) {
         (Some(b_ts), Some(a_ts)) => b_ts.cmp(a_ts),
         (Some(_), None) => std::cmp::Ordering::Less,
         (None, Some(_)) => std::cmp::Ordering::Greater,
         (None, None) => std::cmp::Ordering::Equal,
     });
-    sessions
 }
@@ -2,7 +2,6 @@ use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
 use serde_json::Value;
 use std::cmp::Ordering;
-use std::collections::{HashMap, HashSet};
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::path::{Path, PathBuf};
@@ -11,12 +10,18 @@ use std::path::{Path, PathBuf};
 pub struct CodexSession {
     pub id: String,
     pub cwd: PathBuf,
+    pub started_at: Option<DateTime<Utc>>,
     pub last_timestamp: Option<DateTime<Utc>>,
     pub last_user_message: Option<String>,
     pub is_subagent: bool,
+    pub model: Option<String>,
+    /// Cumulative token usage from the last `token_count` event
+    pub input_tokens: u64,
+    pub output_tokens: u64,
 }
 
-fn sessions_root() -> Option<PathBuf> {
+/// Where Codex keeps its session transcripts.
+pub fn sessions_root() -> Option<PathBuf> {
     if let Ok(dir) = std::env::var("PIGS_CODEX_SESSIONS_DIR") {
         return Some(PathBuf::from(dir));
     }
@@ -98,52 +103,89 @@ fn parse_session_file(path: &Path) -> Result<Option<CodexSession>> {
 
     let meta = serde_json::from_str::<Value>(&first_line)
         .with_context(|| format!("Failed to parse session meta in {}", path.display()))?;
-
-    if meta.get("type").and_then(|t| t.as_str()) != Some("session_meta") {
+    let Some(mut session) = CodexSession::from_meta(&meta, path)? else {
         return Ok(None);
+    };
+
+    for line in lines {
+        if let Ok(value) = serde_json::from_str::<Value>(&line) {
+            session.apply(&value);
+        }
     }
+    Ok(Some(session))
+}
 
-    let payload = meta
-        .get("payload")
-        .and_then(|p| p.as_object())
-        .ok_or_else(|| anyhow::anyhow!("Session payload is missing in {}", path.display()))?;
+impl CodexSession {
+    /// Start a session from the `session_meta` entry that opens every session
+    /// file. `None` when `meta` is some other entry.
+    pub fn from_meta(meta: &Value, path: &Path) -> Result<Option<Self>> {
+        if meta.get("type").and_then(|t| t.as_str()) != Some("session_meta") {
+            return Ok(None);
+        }
 
-    let id = payload
-        .get("id")
-        .and_then(|v| v.as_str())
-        .ok_or_else(|| anyhow::anyhow!("Session id missing in {}", path.display()))?
-        .to_string();
+        let payload = meta
+            .get("payload")
+            .and_then(|p| p.as_object())
+            .ok_or_else(|| anyhow::anyhow!("Session payload is missing in {}", path.display()))?;
 
-    let cwd_str = payload
-        .get("cwd")
-        .and_then(|v| v.as_str())
-        .unwrap_or_default();
-    let cwd = PathBuf::from(cwd_str);
+        let id = payload
+            .get("id")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Session id missing in {}", path.display()))?
+            .to_string();
 
-    let is_subagent = payload
-        .get("source")
-        .is_some_and(|v| v.is_object() && v.get("subagent").is_some());
+        let cwd_str = payload
+            .get("cwd")
+            .and_then(|v| v.as_str())
+            .unwrap_or_default();
+        let cwd = PathBuf::from(cwd_str);
 
-    let start_timestamp = payload
-        .get("timestamp")
-        .and_then(|v| v.as_str())
-        .and_then(|ts| DateTime::parse_from_rfc3339(ts).ok())
-        .map(|dt| dt.with_timezone(&Utc));
+        let is_subagent = payload
+            .get("source")
+            .is_some_and(|v| v.is_object() && v.get("subagent").is_some());
 
-    let mut last_user_message = None;
-    let mut last_timestamp = start_timestamp;
+        let start_timestamp = payload
+            .get("timestamp")
+            .and_then(|v| v.as_str())
+            .and_then(|ts| DateTime::parse_from_rfc3339(ts).ok())
+            .map(|dt| dt.with_timezone(&Utc));
 
-    for line in lines {
-        let Ok(value) = serde_json::from_str::<Value>(&line) else {
-            continue;
-        };
+        Ok(Some(Self {
+            id,
+            cwd,
+            started_at: start_timestamp,
+            last_timestamp: start_timestamp,
+            last_user_message: None,
+            is_subagent,
+            model: None,
+            input_tokens: 0,
+            output_tokens: 0,
+        }))
+    }
 
-        if value.get("type").and_then(|t| t.as_str()) != Some("response_item") {
-            continue;
+    /// Fold one session entry after the `session_meta` into the summary.
+    pub fn apply(&mut self, value: &Value) {
+        match value.get("type").and_then(|t| t.as_str()) {
+            Some("response_item") => {}
+            Some("turn_context") => {
+                if let Some(name) = value.pointer("/payload/model").and_then(|m| m.as_str()) {
+                    self.model = Some(name.to_string());
+                }
+                return;
+            }
+            Some("event_msg") => {
+                if let Some(usage) = value.pointer("/payload/info/total_token_usage") {
+                    let count = |key: &str| usage.get(key).and_then(|v| v.as_u64()).unwrap_or(0);
+                    self.input_tokens = count("input_tokens");
+                    self.output_tokens = count("output_tokens");
+                }
+                return;
+            }
+            _ => return,
         }
 
         let Some(payload) = value.get("payload").and_then(|p| p.as_object()) else {
-            continue;
+            return;
         };
 
         let role = payload
@@ -155,7 +197,7 @@ fn parse_session_file(path: &Path) -> Result<Option<CodexSession>> {
             .and_then(|k| k.as_str())
             .unwrap_or_default();
         if role != "user" || kind != "message" {
-            continue;
+            return;
         }
 
         let message_timestamp = value
@@ -165,25 +207,17 @@ fn parse_session_file(path: &Path) -> Result<Option<CodexSession>> {
             .map(|dt| dt.with_timezone(&Utc));
 
         if let Some(ts) = message_timestamp
-            && last_timestamp.is_none_or(|current| ts > current)
+            && self.last_timestamp.is_none_or(|current| ts > current)
         {
-            last_timestamp = Some(ts);
+            self.last_timestamp = Some(ts);
         }
 
         if let Some(msg) = extract_user_message(payload)
             && !msg.trim().is_empty()
         {
-            last_user_message = Some(msg);
+            self.last_user_message = Some(msg);
         }
     }
-
-    Ok(Some(CodexSession {
-        id,
-        cwd,
-        last_timestamp,
-        last_user_message,
-        is_subagent,
-    }))
 }
 
 fn extract_user_message(payload: &serde_json::Map<String, Value>) -> Option<String> {
@@ -210,7 +244,7 @@ fn extract_user_message(payload: &serde_json::Map<String, Value>) -> Option<Stri
     }
 }
 
-fn iterate_session_files(descending: bool) -> Result<Vec<PathBuf>> {
+pub fn iterate_session_files(descending: bool) -> Result<Vec<PathBuf>> {
     let Some(root) = sessions_root() else {
         return Ok(Vec::new());
     };
@@ -257,6 +291,17 @@ fn matches_worktree(session_path: &Path, target_canonical: &Path, fallback: &Pat
         || session_path == fallback
 }
 
+/// Whether the Codex session saved in `file` ran in one of `worktree_paths`.
+/// Unreadable files belong to none.
+pub fn session_in_worktrees(file: &Path, worktree_paths: &[PathBuf]) -> bool {
+    let Ok(Some(session)) = parse_session_file(file) else {
+        return false;
+    };
+    worktree_paths
+        .iter()
+        .any(|path| matches_worktree(&session.cwd, &normalized_path(path), path))
+}
+
 pub fn find_latest_session(worktree_path: &Path) -> Result<Option<CodexSession>> {
     let files = iterate_session_files(true)?;
     if files.is_empty() {
@@ -309,53 +354,3 @@ pub fn recent_sessions(worktree_path: &Path, limit: usize) -> Result<(Vec<CodexS
 
     Ok((sessions, total))
 }
-
-pub fn collect_recent_sessions_for_paths(
-    worktree_paths: &[PathBuf],
-    limit: usize,
-) -> Result<HashMap<PathBuf, Vec<CodexSession>>> {
-    if worktree_paths.is_empty() || limit == 0 {
-        return Ok(HashMap::new());
-    }
-
-    let files = iterate_session_files(true)?;
-    if files.is_empty() {
-        return Ok(HashMap::new());
-    }
-
-    let mut targets: HashSet<PathBuf> = HashSet::new();
-    for path in worktree_paths {
-        targets.insert(normalized_path(path));
-    }
-
-    let mut satisfied: HashSet<PathBuf> = HashSet::new();
-    let mut map: HashMap<PathBuf, Vec<CodexSession>> = HashMap::new();
-
-    for file in files {
-        if satisfied.len() == targets.len() {
-            break;
-        }
-
-        let Some(session) = parse_session_file(&file)? else {
-            continue;
-        };
-
-        let normalized = normalized_path(&session.cwd);
-        if !targets.contains(&normalized) {
-            continue;
-        }
-
-        let entry = map.entry(normalized.clone()).or_default();
-        if entry.len() >= limit {
-            satisfied.insert(normalized);
-            continue;
-        }
-
-        entry.push(session);
-        if entry.len() == limit {
-            satisfied.insert(normalized);
-        }
-    }
-
-    Ok(map)
-}
@@ -2,12 +2,20 @@ use anyhow::{Context, Result};
 use chrono::Utc;
 use colored::Colorize;
 use std::fs;
+use std::path::{Path, PathBuf};
 
-use crate::git::{get_current_branch, get_repo_name, is_in_worktree};
+use crate::git::{
+    execute_git, get_current_branch, get_repo_name, is_in_worktree, parse_worktree_list,
+};
+use crate::input::smart_multi_select;
 use crate::state::{PigsState, WorktreeInfo};
 use crate::utils::sanitize_branch_name;
 
-pub fn handle_add(name: Option<String>) -> Result<()> {
+pub fn handle_add(name: Option<String>, scan: bool) -> Result<()> {
+    if scan {
+        return handle_scan();
+    }
+
     // Check if we're in a git repository
     let repo_name = get_repo_name().context("Not in a git repository")?;
 
@@ -31,9 +39,6 @@ pub fn handle_add(name: Option<String>) -> Result<()> {
     // Load state
     let mut state = PigsState::load()?;
 
-    let normalize_path = |path: &std::path::Path| -> std::path::PathBuf {
-        fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
-    };
     let current_dir_key = normalize_path(&current_dir);
 
     // Check if this path is already managed under another worktree
@@ -69,13 +74,7 @@ pub fn handle_add(name: Option<String>) -> Result<()> {
     // Add to state
     state.worktrees.insert(
         key,
-        WorktreeInfo {
-            name: worktree_name.clone(),
-            branch: current_branch,
-            path: current_dir.clone(),
-            repo_name,
-            created_at: Utc::now(),
-        },
+        adopted_worktree(&repo_name, &worktree_name, current_branch, &current_dir),
     );
     state.save()?;
 
@@ -88,3 +87,130 @@ pub fn handle_add(name: Option<String>) -> Result<()> {
 
     Ok(())
 }
+
+fn normalize_path(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+fn adopted_worktree(repo_name: &str, name: &str, branch: String, path: &Path) -> WorktreeInfo {
+    WorktreeInfo {
+        name: name.to_string(),
+        branch,
+        path: path.to_path_buf(),
+        repo_name: repo_name.to_string(),
+        created_at: Utc::now(),
+        base_branch: None,
+        stack: None,
+        reminder: None,
+        container: None,
+        artifacts: Vec::new(),
+        note: None,
+        tags: Vec::new(),
+        agent_history: Vec::new(),
+        repo_root: crate::git::main_checkout(path),
+    }
+}
+
+/// Find worktrees of the current repository that pigs doesn't track, such as
+/// ones made with plain `git worktree add` or recorded in another state file,
+/// and adopt the ones the user picks.
+fn handle_scan() -> Result<()> {
+    let repo_name = get_repo_name().context("Not in a git repository")?;
+    let mut state = PigsState::load()?;
+    let output = execute_git(&["worktree", "list", "--porcelain"])?;
+
+    // Pooled worktrees belong to pigs too, waiting for `pigs create --fast`
+    let tracked: Vec<PathBuf> = state
+        .worktrees
+        .values()
+        .map(|info| &info.path)
+        .chain(state.pooled_worktrees.iter().map(|pooled| &pooled.path))
+        .map(|path| normalize_path(path))
+        .collect();
+    let mut candidates: Vec<(String, String, PathBuf)> = Vec::new();
+    // The first entry is the main working tree, which pigs never manages
+    for listed in parse_worktree_list(&output).into_iter().skip(1) {
+        if listed.prunable.is_some()
+            || !listed.path.exists()
+            || tracked.contains(&normalize_path(&listed.path))
+        {
+            continue;
+        }
+        let Some(branch) = listed.branch else {
+            println!(
+                "  {} Skipping {}: HEAD is detached",
+                "⚠️ ".yellow(),
+                listed.path.display()
+            );
+            continue;
+        };
+        let name = sanitize_branch_name(&branch);
+        let key = PigsState::make_key(&repo_name, &name);
+        if state.worktrees.contains_key(&key)
+            || candidates.iter().any(|(other, _, _)| *other == name)
+        {
+            println!(
+                "  {} Skipping {}: '{}' is already taken; run 'pigs add <name>' inside it",
+                "⚠️ ".yellow(),
+                listed.path.display(),
+                name
+            );
+            continue;
+        }
+        candidates.push((name, branch, listed.path));
+    }
+
+    if candidates.is_empty() {
+        println!(
+            "{} Every worktree of '{}' is already tracked",
+            "✨".green(),
+            repo_name
+        );
+        return Ok(());
+    }
+
+    println!(
+        "{} Found {} untracked worktree{}:",
+        "🔍".cyan(),
+        candidates.len(),
+        if candidates.len() == 1 { "" } else { "s" }
+    );
+    for (name, _, path) in &candidates {
+        println!(
+            "  {} {}",
+            name.cyan(),
+            path.display().to_string().bright_black()
+        );
+    }
+
+    let Some(selection) =
+        smart_multi_select("Select worktrees to adopt", &candidates, |(name, _, _)| {
+            name.clone()
+        })?
+    else {
+        anyhow::bail!(
+            "Interactive selection not available in non-interactive mode. Pipe the worktrees to adopt (numbers or names)."
+        );
+    };
+    if selection.is_empty() {
+        println!("{} Nothing selected", "ℹ️ ".blue());
+        return Ok(());
+    }
+
+    for index in &selection {
+        let (name, branch, path) = &candidates[*index];
+        state.worktrees.insert(
+            PigsState::make_key(&repo_name, name),
+            adopted_worktree(&repo_name, name, branch.clone(), path),
+        );
+        println!(
+            "{} Adopted '{}' {}",
+            "✅".green(),
+            name.cyan(),
+            path.display().to_string().bright_black()
+        );
+    }
+    state.save()?;
+
+    Ok(())
+}
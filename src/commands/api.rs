@@ -0,0 +1,51 @@
+use std::io::Read;
+
+use anyhow::{Context, Result, bail};
+use serde_json::Value;
+
+use crate::dashboard;
+use crate::jobs;
+
+/// Call a dashboard API endpoint in-process and print its JSON response.
+/// The method defaults to POST when a body is given, else GET.
+pub fn handle_api(endpoint: String, method: Option<String>, json: Option<String>) -> Result<()> {
+    let path = api_path(&endpoint);
+    let body = match json.as_deref() {
+        Some("-") => {
+            let mut body = String::new();
+            std::io::stdin()
+                .read_to_string(&mut body)
+                .context("Failed to read the request body from stdin")?;
+            Some(body)
+        }
+        _ => json,
+    };
+    if let Some(body) = &body {
+        serde_json::from_str::<Value>(body).context("--json is not valid JSON")?;
+    }
+    let method = method.unwrap_or_else(|| if body.is_some() { "POST" } else { "GET" }.to_string());
+
+    let (status, response) = dashboard::call_api(&method, &path, body)?;
+    // Jobs started by the request (create, sync) run on this process's threads
+    jobs::wait_for_spawned();
+
+    if !status.is_success() {
+        bail!(
+            "{} {path} failed with {status}: {}",
+            method.to_uppercase(),
+            response.trim()
+        );
+    }
+    match serde_json::from_str::<Value>(&response) {
+        Ok(value) => println!("{}", serde_json::to_string_pretty(&value)?),
+        Err(_) => println!("{}", response.trim_end()),
+    }
+    Ok(())
+}
+
+/// `/api/worktrees` from `worktrees`, `api/worktrees`, or `/api/worktrees`.
+fn api_path(endpoint: &str) -> String {
+    let endpoint = endpoint.trim_start_matches('/');
+    let endpoint = endpoint.strip_prefix("api/").unwrap_or(endpoint);
+    format!("/api/{endpoint}")
+}
@@ -0,0 +1,144 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use chrono::{Local, Utc};
+use colored::Colorize;
+use serde_json::json;
+use tokio::sync::broadcast;
+
+use crate::git::{execute_git, uncommitted_patch};
+use crate::jobs::{self, Job, JobEvent};
+use crate::names::resolve_worktree;
+use crate::state::{PigsState, WorktreeInfo, get_config_dir};
+
+/// Bundle file holding the branch's history in every archive.
+const BUNDLE_FILE: &str = "branch.bundle";
+/// Patch of uncommitted changes, written only when there are some.
+const PATCH_FILE: &str = "changes.patch";
+
+pub fn handle_archive(name: Option<String>) -> Result<()> {
+    let state = PigsState::load()?;
+    let info = resolve_worktree(&state, name)?;
+    archive_worktree(&info)?;
+    Ok(())
+}
+
+/// Save a worktree's branch as a git bundle, plus its uncommitted changes as
+/// a patch, under `~/.pigs/archives/<repo>/<name>-<timestamp>`. Returns the
+/// archive directory.
+pub(super) fn archive_worktree(info: &WorktreeInfo) -> Result<PathBuf> {
+    let archive = save_archive(info, &|_| Ok(()))?;
+    let bundle = archive.dir.join(BUNDLE_FILE);
+    println!(
+        "{} Archived '{}' to {}",
+        "📦".green(),
+        info.name.cyan(),
+        archive.dir.display()
+    );
+    println!(
+        "  {} Restore with: git fetch {} {}:{}{}",
+        "ℹ️".blue(),
+        bundle.display(),
+        info.branch,
+        info.branch,
+        if archive.has_changes {
+            format!(
+                ", then git apply {} in the new worktree",
+                archive.dir.join(PATCH_FILE).display()
+            )
+        } else {
+            String::new()
+        }
+    );
+    Ok(archive.dir)
+}
+
+/// Queue archiving a worktree as an `archive` job, with a `bundle` step and a
+/// `changes` step. It can be cancelled until the bundle is written.
+pub fn spawn_archive_job(info: WorktreeInfo) -> (Arc<Job>, broadcast::Receiver<JobEvent>) {
+    let description = PigsState::make_key(&info.repo_name, &info.name);
+    jobs::spawn("archive", Some(description), move |job| {
+        let progress = |step: &str| {
+            job.step(step);
+            match step {
+                "bundle" => job.check_cancelled(),
+                _ => Ok(()),
+            }
+        };
+        let archive = save_archive(&info, &progress)?;
+        Ok(json!({
+            "path": archive.dir,
+            "uncommittedChanges": archive.has_changes,
+        }))
+    })
+}
+
+struct Archive {
+    dir: PathBuf,
+    has_changes: bool,
+}
+
+/// Write the archive, calling `progress` before each phase; an error from it
+/// stops before anything more is written.
+fn save_archive(info: &WorktreeInfo, progress: &dyn Fn(&str) -> Result<()>) -> Result<Archive> {
+    if !info.path.exists() {
+        anyhow::bail!("Worktree directory {} is missing", info.path.display());
+    }
+    let path = info
+        .path
+        .to_str()
+        .context("Worktree path contains invalid UTF-8")?;
+
+    let parent = get_config_dir()?.join("archives").join(&info.repo_name);
+    let stem = format!("{}-{}", info.name, Local::now().format("%Y%m%d-%H%M%S"));
+    // Archives made within the same second get a numeric suffix
+    let dir = (1..)
+        .map(|n| match n {
+            1 => parent.join(&stem),
+            n => parent.join(format!("{stem}-{n}")),
+        })
+        .find(|dir| !dir.exists())
+        .expect("an unused archive directory name");
+    progress("bundle")?;
+    fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+
+    let bundle = dir.join(BUNDLE_FILE);
+    execute_git(&[
+        "-C",
+        path,
+        "bundle",
+        "create",
+        bundle
+            .to_str()
+            .context("Archive path contains invalid UTF-8")?,
+        &info.branch,
+    ])
+    .context("Failed to bundle the branch")?;
+
+    progress("changes")?;
+    let patch = uncommitted_patch(&info.path).context("Failed to save uncommitted changes")?;
+    let has_changes = !patch.is_empty();
+    if has_changes {
+        fs::write(dir.join(PATCH_FILE), &patch).context("Failed to save uncommitted changes")?;
+    }
+
+    let head = execute_git(&["-C", path, "rev-parse", "HEAD"])?;
+    let metadata = json!({
+        "repo_name": info.repo_name,
+        "name": info.name,
+        "branch": info.branch,
+        "path": info.path,
+        "head": head,
+        "created_at": info.created_at,
+        "archived_at": Utc::now(),
+        "uncommitted_changes": has_changes,
+    });
+    fs::write(
+        dir.join("archive.json"),
+        serde_json::to_string_pretty(&metadata)?,
+    )
+    .context("Failed to write archive metadata")?;
+    Ok(Archive { dir, has_changes })
+}
@@ -0,0 +1,113 @@
+use anyhow::Result;
+use chrono::Local;
+use colored::Colorize;
+use serde::Serialize;
+
+use crate::artifacts::{self, ArtifactFile, ArtifactRun};
+use crate::names::resolve_worktree;
+use crate::state::PigsState;
+
+#[derive(Serialize)]
+struct JsonArtifactRun<'a> {
+    #[serde(flatten)]
+    run: &'a ArtifactRun,
+    path: String,
+}
+
+/// List the saved verification runs for a worktree, newest first, optionally
+/// only those of `task`.
+pub fn handle_artifacts(
+    name: Option<String>,
+    task: Option<String>,
+    json: bool,
+    markdown: bool,
+) -> Result<()> {
+    let state = PigsState::load()?;
+    let mut info = resolve_worktree(&state, name)?;
+    if let Some(task) = &task {
+        info.artifacts.retain(|run| run.task == *task);
+    }
+
+    if markdown {
+        match artifacts::markdown_summary(&info)? {
+            Some(summary) => print!("{summary}"),
+            None => eprintln!("No verification runs recorded for '{}'", info.name),
+        }
+        return Ok(());
+    }
+
+    if json {
+        let runs = info
+            .artifacts
+            .iter()
+            .rev()
+            .map(|run| {
+                Ok(JsonArtifactRun {
+                    run,
+                    path: artifacts::run_dir(&info, run)?.display().to_string(),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&serde_json::json!({ "runs": runs }))?
+        );
+        return Ok(());
+    }
+
+    if info.artifacts.is_empty() {
+        println!(
+            "{} No verification runs recorded for '{}'",
+            "📭".yellow(),
+            info.name
+        );
+        println!(
+            "   Run a task from the dashboard; list extra files under {} in .pigs/settings.json",
+            "artifacts".cyan()
+        );
+        return Ok(());
+    }
+
+    println!(
+        "{} Verification runs for {}:",
+        "🧪".cyan(),
+        info.name.cyan()
+    );
+    for run in info.artifacts.iter().rev() {
+        let outcome = if run.success {
+            "passed".green()
+        } else {
+            "failed".red()
+        };
+        println!();
+        println!(
+            "  {} {} {} {}",
+            "•".green(),
+            run.task.bold(),
+            outcome,
+            run.finished_at
+                .with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string()
+                .bright_black()
+        );
+        let dir = artifacts::run_dir(&info, run)?;
+        for file in &run.files {
+            println!(
+                "      {} {} {}",
+                format!("{:<8}", file.kind.as_str()).bright_black(),
+                dir.join(&file.name).display(),
+                format!("({})", format_size(file)).bright_black()
+            );
+        }
+    }
+    Ok(())
+}
+
+fn format_size(file: &ArtifactFile) -> String {
+    if file.size < 1024 {
+        format!("{} B", file.size)
+    } else {
+        format!("{} KiB", file.size / 1024)
+    }
+}
@@ -0,0 +1,206 @@
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result, bail};
+use colored::Colorize;
+use serde::Serialize;
+
+use crate::git::{
+    copy_files_to_worktree, execute_git, get_repo_name, is_working_tree_clean, run_setup_commands,
+    update_submodules,
+};
+use crate::labels::detect_worktree_labels;
+use crate::state::RepoConfig;
+use crate::utils::{execute_in_dir, prepare_agent_command};
+
+/// Steps timed on every iteration, in execution order. The first four make up
+/// `pigs create`; `open` resolves the agent command without launching it.
+const STEPS: [&str; 7] = [
+    "worktree add",
+    "submodules",
+    "copy files",
+    "setup hooks",
+    "open",
+    "status",
+    "delete",
+];
+
+#[derive(Debug, Serialize)]
+struct StepSummary {
+    step: String,
+    min_ms: f64,
+    median_ms: f64,
+    mean_ms: f64,
+    max_ms: f64,
+}
+
+#[derive(Debug, Serialize)]
+struct BenchReport {
+    repo_name: String,
+    iterations: usize,
+    steps: Vec<StepSummary>,
+}
+
+pub fn handle_bench(iterations: usize, json: bool) -> Result<()> {
+    if iterations == 0 {
+        bail!("--iterations must be at least 1");
+    }
+
+    let repo_root = PathBuf::from(
+        execute_git(&["rev-parse", "--show-toplevel"]).context("Not in a git repository")?,
+    );
+    let repo_name = get_repo_name().context("Not in a git repository")?;
+    let repo_config = RepoConfig::load(&repo_root)?;
+
+    if !json {
+        println!(
+            "{} Benchmarking worktree operations on '{}' ({} iteration(s))...",
+            "⏱️".cyan(),
+            repo_name.cyan(),
+            iterations
+        );
+    }
+
+    let mut samples: Vec<Vec<Duration>> = vec![Vec::new(); STEPS.len()];
+    for iteration in 0..iterations {
+        let timings = run_iteration(&repo_root, &repo_name, &repo_config, iteration)?;
+        for (step, elapsed) in timings.into_iter().enumerate() {
+            samples[step].push(elapsed);
+        }
+    }
+
+    let report = BenchReport {
+        repo_name,
+        iterations,
+        steps: STEPS
+            .iter()
+            .zip(&samples)
+            .map(|(step, durations)| summarize(step, durations))
+            .collect(),
+    };
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+
+    println!();
+    println!(
+        "  {:<14} {:>10} {:>10} {:>10} {:>10}",
+        "step".bold(),
+        "min".bold(),
+        "median".bold(),
+        "mean".bold(),
+        "max".bold()
+    );
+    for step in &report.steps {
+        println!(
+            "  {:<14} {:>8.1}ms {:>8.1}ms {:>8.1}ms {:>8.1}ms",
+            step.step, step.min_ms, step.median_ms, step.mean_ms, step.max_ms
+        );
+    }
+
+    Ok(())
+}
+
+/// Create, open, inspect, and delete one throwaway worktree, returning the
+/// elapsed time of each entry in `STEPS`. The worktree and its branch are
+/// removed even when an intermediate step fails.
+fn run_iteration(
+    repo_root: &Path,
+    repo_name: &str,
+    repo_config: &RepoConfig,
+    iteration: usize,
+) -> Result<Vec<Duration>> {
+    let branch = format!("pigs-bench-{}-{iteration}", std::process::id());
+    let worktree_path = repo_root
+        .parent()
+        .context("Repository has no parent directory")?
+        .join(format!("{repo_name}-{branch}"));
+    let worktree_str = worktree_path
+        .to_str()
+        .context("Worktree path contains invalid UTF-8")?
+        .to_string();
+    let root_str = repo_root
+        .to_str()
+        .context("Repository path contains invalid UTF-8")?;
+
+    let mut timings = Vec::with_capacity(STEPS.len());
+    timings.push(time(|| {
+        execute_git(&[
+            "-C",
+            root_str,
+            "worktree",
+            "add",
+            "-b",
+            &branch,
+            &worktree_str,
+            "HEAD",
+        ])
+        .map(|_| ())
+    })?);
+
+    let result = (|| -> Result<()> {
+        timings.push(time(|| update_submodules(&worktree_path))?);
+        timings.push(time(|| {
+            copy_files_to_worktree(repo_root, &worktree_path, &repo_config.copy_files, true)
+        })?);
+        timings.push(time(|| {
+            run_setup_commands(&worktree_path, &repo_config.setup_commands, true)
+        })?);
+        timings.push(time(|| {
+            prepare_agent_command(&worktree_path, None).map(|_| ())
+        })?);
+        timings.push(time(|| {
+            execute_in_dir(&worktree_path, is_working_tree_clean)?;
+            detect_worktree_labels(&worktree_path);
+            Ok(())
+        })?);
+        Ok(())
+    })();
+
+    let delete = time(|| {
+        execute_git(&[
+            "-C",
+            root_str,
+            "worktree",
+            "remove",
+            "--force",
+            &worktree_str,
+        ])?;
+        execute_git(&["-C", root_str, "branch", "-D", &branch]).map(|_| ())
+    });
+
+    result?;
+    timings.push(delete?);
+    Ok(timings)
+}
+
+fn time(step: impl FnOnce() -> Result<()>) -> Result<Duration> {
+    let started = Instant::now();
+    step()?;
+    Ok(started.elapsed())
+}
+
+fn summarize(step: &str, durations: &[Duration]) -> StepSummary {
+    let mut millis: Vec<f64> = durations
+        .iter()
+        .map(|duration| duration.as_secs_f64() * 1000.0)
+        .collect();
+    millis.sort_by(f64::total_cmp);
+
+    let count = millis.len();
+    let median = if count.is_multiple_of(2) {
+        (millis[count / 2 - 1] + millis[count / 2]) / 2.0
+    } else {
+        millis[count / 2]
+    };
+
+    StepSummary {
+        step: step.to_string(),
+        min_ms: millis[0],
+        median_ms: median,
+        mean_ms: millis.iter().sum::<f64>() / count as f64,
+        max_ms: millis[count - 1],
+    }
+}
@@ -1,42 +1,60 @@
+use std::io::Write;
 use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
 
 use anyhow::{Context, Result, bail};
 use chrono::Utc;
 use colored::Colorize;
 
+use crate::commands::create::run_post_create;
 use crate::commands::open::handle_open;
 use crate::git::{
     copy_files_to_worktree, execute_git, get_repo_name, run_setup_commands, update_submodules,
 };
 use crate::input::{get_command_arg, smart_confirm};
+use crate::network;
 use crate::state::{PigsState, RepoConfig, WorktreeInfo};
 use crate::utils::sanitize_branch_name;
+use crate::webhooks::{self, WebhookEvent};
 
 pub fn handle_checkout(
     target: Option<String>,
     yes: bool,
+    sparse: bool,
     selected_agent: Option<String>,
     agent_args: Vec<String>,
 ) -> Result<()> {
     let raw_target = get_command_arg(target)?
         .map(|s| s.trim().to_string())
         .filter(|s| !s.is_empty())
-        .context("Please provide a branch name or pull request number")?;
+        .context("Please provide a branch name, pull request number, or pull request URL")?;
 
     let checkout_target = CheckoutTarget::parse(&raw_target)?;
+    let sparse_files = match (&checkout_target, sparse) {
+        (CheckoutTarget::PullRequest(pr_number), true) => Some(fetch_pr_files(*pr_number)?),
+        (CheckoutTarget::MergeRequest(_), true) => {
+            bail!("--sparse is only supported for GitHub pull requests")
+        }
+        (CheckoutTarget::Branch(_), true) => {
+            bail!("--sparse is only supported when checking out a pull request")
+        }
+        (_, false) => None,
+    };
     let repo_root_str = execute_git(&["rev-parse", "--show-toplevel"])?
         .trim()
         .to_string();
     let repo_root = PathBuf::from(&repo_root_str);
     let repo_name = get_repo_name().context("Not in a git repository")?;
 
-    // For PRs, resolve the actual branch name via `gh` CLI
-    let branch_name = match &checkout_target {
-        CheckoutTarget::PullRequest(pr_number) => {
-            resolve_pr_branch_name(*pr_number).unwrap_or_else(|| format!("pr/{pr_number}"))
-        }
-        CheckoutTarget::Branch(name) => name.clone(),
-    };
+    // For PRs and MRs, resolve the actual branch name via `gh` or `glab`
+    let branch_name =
+        match &checkout_target {
+            CheckoutTarget::PullRequest(pr_number) => resolve_pr_branch_name(*pr_number)
+                .unwrap_or_else(|| checkout_target.fallback_branch()),
+            CheckoutTarget::MergeRequest(mr_number) => resolve_mr_branch_name(*mr_number)
+                .unwrap_or_else(|| checkout_target.fallback_branch()),
+            CheckoutTarget::Branch(name) => name.clone(),
+        };
     let worktree_name = sanitize_branch_name(&branch_name);
 
     if let Some(existing) = find_existing_worktree(&repo_name, &branch_name)? {
@@ -79,7 +97,13 @@ pub fn handle_checkout(
         worktree_name.cyan()
     );
 
-    let created_path = create_worktree(&repo_root, &repo_name, &branch_name, &worktree_name)?;
+    let created_path = create_worktree(
+        &repo_root,
+        &repo_name,
+        &branch_name,
+        &worktree_name,
+        sparse_files.as_deref(),
+    )?;
 
     println!(
         "{} Worktree created at: {}",
@@ -129,15 +153,10 @@ fn find_existing_worktree(repo_name: &str, branch_name: &str) -> Result<Option<E
 fn ensure_branch_ready(target: &CheckoutTarget, branch_name: &str) -> Result<()> {
     match target {
         CheckoutTarget::Branch(_) => ensure_branch_available(branch_name),
-        CheckoutTarget::PullRequest(pr_number) => {
-            // If we resolved the real branch name, fetch it as a regular branch.
-            // Otherwise (pr/N fallback), use the PR ref fetch.
-            if branch_name == format!("pr/{pr_number}") {
-                fetch_pull_request(*pr_number, branch_name)
-            } else {
-                ensure_branch_available(branch_name)
-            }
-        }
+        // If we resolved the real branch name, fetch it as a regular branch.
+        // Otherwise (pr/N or mr/N fallback), use the PR or MR ref fetch.
+        _ if branch_name == target.fallback_branch() => fetch_pull_request(target, branch_name),
+        _ => ensure_branch_available(branch_name),
     }
 }
 
@@ -146,6 +165,10 @@ fn ensure_branch_available(branch_name: &str) -> Result<()> {
         return Ok(());
     }
 
+    if network::is_offline() {
+        bail!("Branch '{branch_name}' not found locally (fetching is disabled in offline mode)");
+    }
+
     println!(
         "{} Branch '{}' not found locally. Attempting to fetch from origin...",
         "🌐".blue(),
@@ -167,6 +190,10 @@ fn ensure_branch_available(branch_name: &str) -> Result<()> {
 /// Try to resolve the actual branch name for a PR via `gh pr view`.
 /// Returns `None` if `gh` is not available or the lookup fails.
 fn resolve_pr_branch_name(pr_number: u64) -> Option<String> {
+    if network::is_offline() {
+        return None;
+    }
+
     std::process::Command::new("gh")
         .args([
             "pr",
@@ -190,17 +217,139 @@ fn resolve_pr_branch_name(pr_number: u64) -> Option<String> {
         })
 }
 
-fn fetch_pull_request(pr_number: u64, branch_name: &str) -> Result<()> {
-    ensure_origin_remote()?;
-    println!(
-        "{} Fetching pull request #{} from origin...",
-        "🌐".blue(),
-        pr_number
+/// Try to resolve the source branch of a GitLab merge request via `glab mr view`.
+/// Returns `None` if `glab` is not available or the lookup fails.
+fn resolve_mr_branch_name(mr_number: u64) -> Option<String> {
+    if network::is_offline() {
+        return None;
+    }
+
+    let output = Command::new("glab")
+        .args(["mr", "view", &mr_number.to_string(), "--output", "json"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    let view: serde_json::Value = serde_json::from_slice(&output.stdout).ok()?;
+    view["source_branch"]
+        .as_str()
+        .filter(|branch| !branch.is_empty())
+        .map(str::to_string)
+}
+
+/// List the files a PR touches via `gh pr diff --name-only`.
+fn fetch_pr_files(pr_number: u64) -> Result<Vec<String>> {
+    network::ensure_online("Sparse pull request checkout")?;
+
+    let output = Command::new("gh")
+        .args(["pr", "diff", &pr_number.to_string(), "--name-only"])
+        .output()
+        .context("Failed to run 'gh'. Install the GitHub CLI to use --sparse")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!(
+            "Failed to list files for pull request #{pr_number}: {}",
+            stderr.trim()
+        );
+    }
+
+    let files: Vec<String> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect();
+
+    if files.is_empty() {
+        bail!("Pull request #{pr_number} does not touch any files");
+    }
+
+    Ok(files)
+}
+
+/// Build non-cone sparse-checkout patterns: every top-level file (so manifests
+/// and agent instructions stay available) plus each file the PR touches.
+fn sparse_patterns(files: &[String]) -> Vec<String> {
+    let mut patterns = vec!["/*".to_string(), "!/*/".to_string()];
+    patterns.extend(
+        files
+            .iter()
+            .map(|file| format!("/{}", file.trim_start_matches('/'))),
     );
+    patterns
+}
+
+fn apply_sparse_checkout(worktree_path: &Path, files: &[String]) -> Result<()> {
+    let worktree_str = worktree_path
+        .to_str()
+        .context("Worktree path contains invalid UTF-8")?;
+
+    // Patterns go through stdin so large PRs don't hit argument length limits
+    let mut child = Command::new("git")
+        .args([
+            "-C",
+            worktree_str,
+            "sparse-checkout",
+            "set",
+            "--no-cone",
+            "--stdin",
+        ])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("Failed to execute git sparse-checkout")?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(sparse_patterns(files).join("\n").as_bytes())
+            .context("Failed to write sparse-checkout patterns")?;
+    }
+    let output = child
+        .wait_with_output()
+        .context("Failed to wait for git sparse-checkout")?;
+    if !output.status.success() {
+        bail!(
+            "Failed to configure sparse-checkout: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    // The worktree was added with --no-checkout; populate only the sparse paths
+    execute_git(&["-C", worktree_str, "read-tree", "-mu", "HEAD"])
+        .context("Failed to populate sparse worktree")?;
+
+    Ok(())
+}
+
+fn fetch_pull_request(target: &CheckoutTarget, branch_name: &str) -> Result<()> {
+    let head_ref = match target {
+        CheckoutTarget::PullRequest(number) => format!("pull/{number}/head"),
+        CheckoutTarget::MergeRequest(number) => format!("merge-requests/{number}/head"),
+        CheckoutTarget::Branch(name) => name.clone(),
+    };
+    let described = target.describe();
 
-    let fetch_ref = format!("pull/{pr_number}/head:refs/heads/{branch_name}");
+    if network::is_offline() {
+        if branch_exists(branch_name) {
+            println!(
+                "{} Offline: using local '{}' without fetching {}",
+                "✈️".yellow(),
+                branch_name.cyan(),
+                described
+            );
+            return Ok(());
+        }
+        bail!(
+            "Cannot check out {described}: it has not been fetched yet (fetching is disabled in offline mode)"
+        );
+    }
+
+    ensure_origin_remote()?;
+    println!("{} Fetching {} from origin...", "🌐".blue(), described);
+
+    let fetch_ref = format!("{head_ref}:refs/heads/{branch_name}");
     execute_git(&["fetch", "origin", &fetch_ref])
-        .with_context(|| format!("Failed to fetch pull request #{pr_number} from origin"))?;
+        .with_context(|| format!("Failed to fetch {described} from origin"))?;
 
     Ok(())
 }
@@ -220,15 +369,13 @@ fn create_worktree(
     repo_name: &str,
     branch_name: &str,
     worktree_name: &str,
+    sparse_files: Option<&[String]>,
 ) -> Result<PathBuf> {
     let repo_root_str = repo_root
         .to_str()
         .context("Repository path contains invalid UTF-8")?;
 
-    let worktree_parent = repo_root
-        .parent()
-        .context("Repository root has no parent directory for worktrees")?;
-    let worktree_path = worktree_parent.join(format!("{repo_name}-{worktree_name}"));
+    let worktree_path = PigsState::load()?.worktree_dir(repo_root, repo_name, worktree_name)?;
 
     if worktree_path.exists() {
         bail!(
@@ -245,9 +392,8 @@ fn create_worktree(
         );
     }
 
-    let mut state = PigsState::load()?;
     let key = PigsState::make_key(repo_name, worktree_name);
-    if state.worktrees.contains_key(&key) {
+    if PigsState::load()?.worktrees.contains_key(&key) {
         bail!(
             "A worktree named '{}' is already tracked for '{}'.",
             worktree_name,
@@ -259,15 +405,21 @@ fn create_worktree(
         .to_str()
         .context("Worktree path contains invalid UTF-8")?;
 
-    execute_git(&[
-        "-C",
-        repo_root_str,
-        "worktree",
-        "add",
-        worktree_arg,
-        branch_name,
-    ])
-    .context("Failed to create worktree")?;
+    let mut add_args = vec!["-C", repo_root_str, "worktree", "add"];
+    if sparse_files.is_some() {
+        add_args.push("--no-checkout");
+    }
+    add_args.extend([worktree_arg, branch_name]);
+    execute_git(&add_args).context("Failed to create worktree")?;
+
+    if let Some(files) = sparse_files {
+        apply_sparse_checkout(&worktree_path, files)?;
+        println!(
+            "{} Sparse checkout limited to {} file(s) touched by the PR",
+            "🪶".green(),
+            files.len()
+        );
+    }
 
     if let Err(e) = update_submodules(&worktree_path) {
         println!(
@@ -286,17 +438,28 @@ fn create_worktree(
     copy_files_to_worktree(repo_root, &worktree_path, &repo_config.copy_files, false)?;
     run_setup_commands(&worktree_path, &repo_config.setup_commands, false)?;
 
-    state.worktrees.insert(
-        key,
-        WorktreeInfo {
-            name: worktree_name.to_string(),
-            branch: branch_name.to_string(),
-            path: worktree_path.clone(),
-            repo_name: repo_name.to_string(),
-            created_at: Utc::now(),
-        },
-    );
+    // Reload: trusting the repo settings above may have saved state
+    let mut state = PigsState::load()?;
+    let info = WorktreeInfo {
+        name: worktree_name.to_string(),
+        branch: branch_name.to_string(),
+        path: worktree_path.clone(),
+        repo_name: repo_name.to_string(),
+        created_at: Utc::now(),
+        base_branch: None,
+        stack: None,
+        reminder: None,
+        container: None,
+        artifacts: Vec::new(),
+        note: None,
+        tags: Vec::new(),
+        agent_history: Vec::new(),
+        repo_root: crate::git::main_checkout(&worktree_path),
+    };
+    state.worktrees.insert(key, info.clone());
     state.save()?;
+    webhooks::emit(WebhookEvent::new("worktree.created", &info));
+    run_post_create(&info, &repo_config.hooks, false)?;
 
     Ok(worktree_path)
 }
@@ -331,6 +494,7 @@ impl std::ops::Deref for ExistingWorktree {
 enum CheckoutTarget {
     Branch(String),
     PullRequest(u64),
+    MergeRequest(u64),
 }
 
 impl CheckoutTarget {
@@ -340,6 +504,21 @@ impl CheckoutTarget {
             bail!("Target cannot be empty");
         }
 
+        if let Some(url) = RequestUrl::parse(trimmed) {
+            url.ensure_matches_origin()?;
+            return Ok(if url.merge_request {
+                Self::MergeRequest(url.number)
+            } else {
+                Self::PullRequest(url.number)
+            });
+        }
+        if trimmed.contains("://") {
+            bail!(
+                "'{trimmed}' is not a pull request or merge request URL \
+                 (expected .../pull/<n> or .../-/merge_requests/<n>)"
+            );
+        }
+
         let digits_only = trimmed.trim_start_matches('#');
         if !digits_only.is_empty() && digits_only.chars().all(|c| c.is_ascii_digit()) {
             let value = digits_only
@@ -355,6 +534,124 @@ impl CheckoutTarget {
         match self {
             Self::Branch(name) => format!("branch '{name}'"),
             Self::PullRequest(number) => format!("pull request #{number}"),
+            Self::MergeRequest(number) => format!("merge request !{number}"),
+        }
+    }
+
+    /// Local branch used when the PR's or MR's own branch name can't be resolved.
+    fn fallback_branch(&self) -> String {
+        match self {
+            Self::Branch(name) => name.clone(),
+            Self::PullRequest(number) => format!("pr/{number}"),
+            Self::MergeRequest(number) => format!("mr/{number}"),
+        }
+    }
+}
+
+/// A pull request (GitHub) or merge request (GitLab) link copied from the
+/// browser, e.g. `https://github.com/org/repo/pull/123`.
+#[derive(Debug, PartialEq, Eq)]
+struct RequestUrl {
+    host: String,
+    /// `org/repo`, or `group/subgroup/repo` on GitLab
+    repo: String,
+    number: u64,
+    merge_request: bool,
+}
+
+impl RequestUrl {
+    fn parse(input: &str) -> Option<Self> {
+        let rest = input
+            .strip_prefix("https://")
+            .or_else(|| input.strip_prefix("http://"))?;
+        let (host, path) = rest.split_once('/')?;
+        let path = path.split(['?', '#']).next()?;
+        let (repo, number, merge_request) =
+            if let Some((repo, number)) = path.split_once("/-/merge_requests/") {
+                (repo, number, true)
+            } else {
+                let (repo, number) = path.split_once("/pull/")?;
+                (repo, number, false)
+            };
+        let number = number.split('/').next()?.parse().ok()?;
+        Some(Self {
+            host: host.to_ascii_lowercase(),
+            repo: repo.trim_matches('/').to_string(),
+            number,
+            merge_request,
+        })
+    }
+
+    /// Refuse links to another repository: the PR's head would be fetched
+    /// from the wrong origin.
+    fn ensure_matches_origin(&self) -> Result<()> {
+        let origin = execute_git(&["config", "--get", "remote.origin.url"]).context(
+            "Remote 'origin' is not configured. Please add a remote before using checkout.",
+        )?;
+        let matches = remote_location(&origin)
+            .is_some_and(|(host, repo)| host == self.host && repo.eq_ignore_ascii_case(&self.repo));
+        if !matches {
+            bail!(
+                "{}/{} is not this repository (origin is {})",
+                self.host,
+                self.repo,
+                origin.trim()
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Host and repository path of a remote URL: `git@host:org/repo.git`,
+/// `ssh://git@host:22/org/repo`, or `https://host/org/repo.git`.
+fn remote_location(url: &str) -> Option<(String, String)> {
+    let url = url.trim();
+    let url = url.strip_suffix(".git").unwrap_or(url);
+    let (host, path) = match url.split_once("://") {
+        Some((_, rest)) => rest.split_once('/')?,
+        None => url.split_once(':')?,
+    };
+    let host = host.rsplit('@').next()?.split(':').next()?;
+    Some((
+        host.to_ascii_lowercase(),
+        path.trim_matches('/').to_string(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RequestUrl, remote_location};
+
+    #[test]
+    fn request_urls_match_their_origin() {
+        let pr = RequestUrl::parse("https://github.com/Acme/App/pull/123/files?w=1").unwrap();
+        assert_eq!(
+            pr,
+            RequestUrl {
+                host: "github.com".to_string(),
+                repo: "Acme/App".to_string(),
+                number: 123,
+                merge_request: false,
+            }
+        );
+        let mr = RequestUrl::parse("https://gitlab.com/group/sub/app/-/merge_requests/9#note_1")
+            .unwrap();
+        assert_eq!(
+            (mr.repo.as_str(), mr.number, mr.merge_request),
+            ("group/sub/app", 9, true)
+        );
+        assert!(RequestUrl::parse("https://github.com/acme/app/issues/5").is_none());
+
+        for origin in [
+            "git@github.com:acme/app.git",
+            "ssh://git@github.com:22/acme/app",
+            "https://github.com/acme/app.git",
+        ] {
+            assert_eq!(
+                remote_location(origin),
+                Some(("github.com".to_string(), "acme/app".to_string())),
+                "{origin}"
+            );
         }
     }
 }
@@ -0,0 +1,140 @@
+use std::path::PathBuf;
+
+use anyhow::{Context, Result, bail};
+use colored::Colorize;
+
+use crate::git::execute_git;
+use crate::names::resolve_worktree;
+use crate::repo_lock::lock_repo;
+use crate::state::PigsState;
+
+/// Apply commits from one worktree's branch onto another worktree, or onto
+/// the main checkout when no destination is given.
+pub fn handle_cherry_pick(source: String, commits: String, dest: Option<String>) -> Result<()> {
+    let state = PigsState::load()?;
+    let source_info = resolve_worktree(&state, Some(source.clone()))?;
+
+    let (dest_label, dest_path): (String, PathBuf) = match &dest {
+        Some(name) => {
+            // A name used in several repositories means the source's one
+            let key = PigsState::make_key(&source_info.repo_name, name);
+            let info = match state.worktrees.get(&key) {
+                Some(info) => info.clone(),
+                None => resolve_worktree(&state, Some(name.clone()))?,
+            };
+            if info.repo_name != source_info.repo_name {
+                bail!(
+                    "Worktree '{name}' is not in repository '{}'",
+                    source_info.repo_name
+                );
+            }
+            if info.path == source_info.path {
+                bail!("Source and destination are the same worktree");
+            }
+            (info.name, info.path)
+        }
+        None => {
+            let main = source_info.main_repo_path()?;
+            let branch = execute_git(&[
+                "-C",
+                main.to_str()
+                    .context("Repository path contains invalid UTF-8")?,
+                "rev-parse",
+                "--abbrev-ref",
+                "HEAD",
+            ])
+            .context("Failed to read the main checkout's branch")?;
+            (format!("{} ({branch})", source_info.repo_name), main)
+        }
+    };
+
+    let source_path = source_info
+        .path
+        .to_str()
+        .context("Worktree path contains invalid UTF-8")?;
+    let dest_str = dest_path
+        .to_str()
+        .context("Worktree path contains invalid UTF-8")?;
+
+    // Resolve in the source worktree so HEAD and relative refs mean its branch
+    let shas = resolve_commits(source_path, &commits)?;
+    if shas.is_empty() {
+        bail!("No commits in '{commits}' on '{source}'");
+    }
+
+    let _guard = lock_repo(
+        &source_info.repo_name,
+        &format!("cherry-pick from '{source}'"),
+    )?;
+
+    if !execute_git(&["-C", dest_str, "status", "--porcelain"])?.is_empty() {
+        bail!("'{dest_label}' has uncommitted changes; commit or stash them first");
+    }
+
+    println!(
+        "{} Applying {} commit(s) from '{}' onto '{}'...",
+        "🍒".green(),
+        shas.len(),
+        source.cyan(),
+        dest_label.cyan()
+    );
+
+    let mut args = vec!["-C", dest_str, "cherry-pick"];
+    args.extend(shas.iter().map(String::as_str));
+    if let Err(err) = execute_git(&args) {
+        let conflicts = execute_git(&["-C", dest_str, "diff", "--name-only", "--diff-filter=U"])
+            .unwrap_or_default();
+        if conflicts.is_empty() {
+            let _ = execute_git(&["-C", dest_str, "cherry-pick", "--abort"]);
+            return Err(err.context("Cherry-pick failed"));
+        }
+        eprintln!("{} Conflicts in '{}':", "⚠️".yellow(), dest_label.cyan());
+        for file in conflicts.lines() {
+            eprintln!("  {file}");
+        }
+        let resolve_hint = match &dest {
+            Some(name) => format!(" (or `pigs open {name}` to have the agent resolve them)"),
+            None => String::new(),
+        };
+        bail!(
+            "Resolve the conflicts in {}{}, then run `git cherry-pick --continue` (or `git cherry-pick --abort`)",
+            dest_path.display(),
+            resolve_hint
+        );
+    }
+
+    println!(
+        "{} Applied {} commit(s) onto '{}'",
+        "✅".green(),
+        shas.len(),
+        dest_label.cyan()
+    );
+    Ok(())
+}
+
+/// Commits named by `spec`, oldest first: a range such as `main..HEAD` or
+/// `abc123~2..abc123`, or a single commit.
+fn resolve_commits(worktree_path: &str, spec: &str) -> Result<Vec<String>> {
+    let spec = spec.trim();
+    if spec.contains("..") {
+        let list = execute_git(&[
+            "-C",
+            worktree_path,
+            "rev-list",
+            "--reverse",
+            "--no-merges",
+            spec,
+        ])
+        .with_context(|| format!("Invalid commit range '{spec}'"))?;
+        return Ok(list.lines().map(str::to_string).collect());
+    }
+    let sha = execute_git(&[
+        "-C",
+        worktree_path,
+        "rev-parse",
+        "--verify",
+        &format!("{spec}^{{commit}}"),
+    ])
+    .with_context(|| format!("Unknown commit '{spec}'"))?;
+    Ok(vec![sha])
+}
@@ -1,49 +1,126 @@
-use anyhow::Result;
+use anyhow::{Context, Result, bail};
 use colored::Colorize;
-use std::collections::HashSet;
-use std::path::PathBuf;
+use serde_json::json;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::broadcast;
 
-use crate::git::list_worktrees;
-use crate::state::PigsState;
+use super::delete::{branch_head, delete_worktree_unattended};
+use crate::artifacts;
+use crate::git::{ListedWorktree, execute_git, parse_worktree_list, resolve_base_ref};
+use crate::input::smart_confirm;
+use crate::jobs::{self, Job, JobEvent};
+use crate::network;
+use crate::relink;
+use crate::state::{PigsState, WorktreeInfo};
 use crate::utils::execute_in_dir;
 
-pub fn handle_clean() -> Result<()> {
+/// Drop state entries whose worktree is gone, after compacting expired
+/// tombstones and offering to re-link moved repositories. With `dry_run`,
+/// report what would be removed and why without touching state or git.
+/// `prune_git` also runs `git worktree prune` in each repository.
+pub fn handle_clean(
+    merged: bool,
+    base: Option<String>,
+    yes: bool,
+    dry_run: bool,
+    prune_git: bool,
+) -> Result<()> {
+    if merged {
+        return clean_merged(base, yes, dry_run);
+    }
+
     let mut state = PigsState::load()?;
 
+    let compacted = state.compact_tombstones();
+    if compacted > 0 {
+        if !dry_run {
+            state.save()?;
+        }
+        println!(
+            "{} {} {} expired tombstone{}",
+            "🪦".cyan(),
+            if dry_run {
+                "Would compact"
+            } else {
+                "Compacted"
+            },
+            compacted,
+            if compacted == 1 { "" } else { "s" }
+        );
+    }
+
     if state.worktrees.is_empty() {
         println!("{} No worktrees in state", "✨".green());
         return Ok(());
     }
 
+    // A dry run can't know whether the user would re-link, so worktrees of
+    // moved repositories are left out of the report
+    let relinkable: HashSet<String> = if dry_run {
+        report_moved_repos(&state)
+    } else {
+        relink_moved_repos(&mut state, yes)?;
+        HashSet::new()
+    };
+
     println!("{} Checking for invalid worktrees...", "🔍".cyan());
 
-    // Collect all actual worktrees from all repositories
-    let actual_worktrees = collect_all_worktrees(&state)?;
+    let registrations = collect_all_worktrees(&state);
+    report_stale_registrations(&registrations, prune_git, dry_run);
+    if prune_git && !dry_run {
+        prune_git_worktrees(&registrations);
+    }
+
+    // Stale registrations only keep a worktree valid while git still has them
+    let actual_worktrees: HashSet<&Path> = registrations
+        .values()
+        .flatten()
+        .filter(|listed| !prune_git || listed.prunable.is_none())
+        .map(|listed| listed.path.as_path())
+        .collect();
 
-    // Find and remove invalid worktrees
-    let mut removed_count = 0;
-    let worktrees_to_remove: Vec<_> = state
+    let mut invalid: Vec<(&String, &WorktreeInfo)> = state
         .worktrees
         .iter()
-        .filter_map(|(name, info)| {
-            if !actual_worktrees.contains(&info.path) {
-                println!(
-                    "  {} Found invalid worktree: {} ({})",
-                    "❌".red(),
-                    name.yellow(),
-                    info.path.display()
-                );
-                removed_count += 1;
-                Some(name.clone())
-            } else {
-                None
-            }
+        .filter(|(key, info)| {
+            !relinkable.contains(*key) && !actual_worktrees.contains(info.path.as_path())
         })
         .collect();
+    invalid.sort_by_key(|(key, _)| *key);
+    for (key, info) in &invalid {
+        println!(
+            "  {} Found invalid worktree: {} ({}): {}",
+            "❌".red(),
+            key.yellow(),
+            info.path.display(),
+            invalid_reason(info)
+        );
+    }
+    let worktrees_to_remove: Vec<String> =
+        invalid.into_iter().map(|(key, _)| key.clone()).collect();
+    let removed_count = worktrees_to_remove.len();
+
+    if dry_run {
+        println!(
+            "{} Dry run: would remove {} invalid worktree{} from state",
+            "📝".cyan(),
+            removed_count,
+            if removed_count == 1 { "" } else { "s" }
+        );
+        return Ok(());
+    }
 
     // Remove invalid worktrees from state
     for name in worktrees_to_remove {
-        state.worktrees.remove(&name);
+        let head = state.worktrees.get(&name).and_then(|info| {
+            let repo = info.main_repo_path().ok()?;
+            branch_head(info, Some(repo.to_str()?))
+        });
+        if let Some(info) = state.remove_worktree(&name, head) {
+            artifacts::remove_all(&info);
+        }
     }
 
     if removed_count > 0 {
@@ -61,24 +138,431 @@ pub fn handle_clean() -> Result<()> {
     Ok(())
 }
 
-fn collect_all_worktrees(state: &PigsState) -> Result<HashSet<PathBuf>> {
-    let mut all_worktrees = HashSet::new();
+/// Why a worktree that git doesn't list is invalid.
+fn invalid_reason(info: &WorktreeInfo) -> String {
+    match info.main_repo_path() {
+        Ok(repo) if !repo.exists() => format!("repository {} is missing", repo.display()),
+        _ if !info.path.exists() => "directory is missing".to_string(),
+        Ok(repo) => format!("not a worktree of {}", repo.display()),
+        Err(_) => "not a worktree of any repository".to_string(),
+    }
+}
+
+/// Offer to re-link repositories that moved, before their worktrees would be
+/// reported as invalid.
+fn relink_moved_repos(state: &mut PigsState, yes: bool) -> Result<()> {
+    for moved in relink::find_moved_repos(state) {
+        println!(
+            "{} Repository '{}' is no longer at {}, but was found at {}",
+            "📦".yellow(),
+            moved.repo_name.cyan(),
+            moved.old_root.display(),
+            moved.new_root.display()
+        );
+        let count = moved.worktrees.len();
+        let prompt = format!(
+            "Re-link it and its {count} worktree{}?",
+            if count == 1 { "" } else { "s" }
+        );
+        if !yes && !smart_confirm(&prompt, true)? {
+            continue;
+        }
+        let relinked = relink::relink(state, &moved)?;
+        state.save()?;
+        println!(
+            "  {} Re-linked {}/{} worktrees",
+            "🔗".green(),
+            relinked,
+            count
+        );
+    }
+    Ok(())
+}
+
+/// Report repositories a real run would offer to re-link, returning the keys
+/// of their worktrees.
+fn report_moved_repos(state: &PigsState) -> HashSet<String> {
+    let mut keys = HashSet::new();
+    for moved in relink::find_moved_repos(state) {
+        let count = moved.worktrees.len();
+        println!(
+            "{} Would offer to re-link '{}' and its {} worktree{}: no longer at {}, but found at {}",
+            "📦".yellow(),
+            moved.repo_name.cyan(),
+            count,
+            if count == 1 { "" } else { "s" },
+            moved.old_root.display(),
+            moved.new_root.display()
+        );
+        keys.extend(moved.worktrees);
+    }
+    keys
+}
 
+/// Worktrees git has registered, by repository path, for every repository
+/// with a worktree in state. Pooled worktrees are left out; the pool skips
+/// and replaces its own missing ones.
+fn collect_all_worktrees(state: &PigsState) -> BTreeMap<PathBuf, Vec<ListedWorktree>> {
     // Get unique repository paths
-    let repo_paths: HashSet<_> = state
+    let repo_paths: BTreeSet<_> = state
         .worktrees
         .values()
-        .filter_map(|info| info.path.parent().map(|p| p.join(&info.repo_name)))
+        .filter_map(|info| info.main_repo_path().ok())
         .collect();
 
-    // Collect worktrees from each repository
+    let mut registrations = BTreeMap::new();
     for repo_path in repo_paths {
         if repo_path.exists()
-            && let Ok(worktrees) = execute_in_dir(&repo_path, list_worktrees)
+            && let Ok(output) = execute_in_dir(&repo_path, || {
+                execute_git(&["worktree", "list", "--porcelain"])
+            })
         {
-            all_worktrees.extend(worktrees);
+            let mut listed = parse_worktree_list(&output);
+            listed.retain(|listed| {
+                !state
+                    .pooled_worktrees
+                    .iter()
+                    .any(|pooled| pooled.path == listed.path)
+            });
+            registrations.insert(repo_path, listed);
         }
     }
+    registrations
+}
+
+/// List worktrees git still has registered although their directory is gone.
+fn report_stale_registrations(
+    registrations: &BTreeMap<PathBuf, Vec<ListedWorktree>>,
+    prune_git: bool,
+    dry_run: bool,
+) {
+    let mut stale = 0;
+    for (repo_path, listed) in registrations {
+        for listed in listed {
+            let Some(reason) = &listed.prunable else {
+                continue;
+            };
+            stale += 1;
+            println!(
+                "  {} Stale git worktree registration in {}: {}{}",
+                "🗑️".yellow(),
+                repo_path.display(),
+                listed.path.display(),
+                if reason.is_empty() {
+                    String::new()
+                } else {
+                    format!(" ({reason})")
+                }
+            );
+        }
+    }
+    if stale > 0 && !prune_git {
+        println!(
+            "  {} Pass --prune-git to run 'git worktree prune'",
+            "💡".cyan()
+        );
+    } else if stale > 0 && dry_run {
+        println!(
+            "  {} Would run 'git worktree prune' to drop {} stale registration{}",
+            "📝".cyan(),
+            stale,
+            if stale == 1 { "" } else { "s" }
+        );
+    }
+}
+
+fn prune_git_worktrees(registrations: &BTreeMap<PathBuf, Vec<ListedWorktree>>) {
+    for repo_path in registrations.keys() {
+        let Some(repo) = repo_path.to_str() else {
+            continue;
+        };
+        match execute_git(&["-C", repo, "worktree", "prune"]) {
+            Ok(_) => println!(
+                "  {} Ran 'git worktree prune' in {}",
+                "🧹".green(),
+                repo_path.display()
+            ),
+            Err(err) => println!(
+                "  {} 'git worktree prune' failed in {}: {err:#}",
+                "⚠️".yellow(),
+                repo_path.display()
+            ),
+        }
+    }
+}
+
+/// A worktree whose branch has landed on its base branch.
+struct MergedWorktree {
+    info: WorktreeInfo,
+    repo_path: PathBuf,
+    /// Merged through a GitHub PR (e.g. squash-merged) rather than by git history
+    via_pr: bool,
+    base_ref: String,
+}
+
+impl MergedWorktree {
+    fn label(&self) -> String {
+        format!("{}/{}", self.info.repo_name, self.info.name)
+    }
+
+    fn merged_how(&self) -> String {
+        if self.via_pr {
+            "via PR".to_string()
+        } else {
+            format!("into {}", self.base_ref)
+        }
+    }
+}
+
+/// Outcome of checking one worktree for `pigs clean --merged`.
+enum MergedCheck {
+    Merged(Box<MergedWorktree>),
+    /// Merged or undecidable, but kept; the message says why
+    Skipped(String),
+}
+
+/// Check every tracked worktree, in repository and name order, for a branch
+/// merged into `base` (default: each repository's base branch). Merged
+/// worktrees with uncommitted changes, or that are the current directory,
+/// are skipped.
+fn check_merged(state: &PigsState, base: Option<&str>) -> Result<Vec<MergedCheck>> {
+    let current_dir = std::env::current_dir().ok();
+    let mut worktrees: Vec<&WorktreeInfo> = state.worktrees.values().collect();
+    worktrees.sort_by(|a, b| (&a.repo_name, &a.name).cmp(&(&b.repo_name, &b.name)));
+
+    let mut checks = Vec::new();
+    for info in worktrees {
+        let repo_path = info.main_repo_path()?;
+        if !repo_path.exists() {
+            continue;
+        }
+        let Some(base_ref) = base
+            .map(str::to_string)
+            .or_else(|| resolve_base_ref(&repo_path))
+        else {
+            checks.push(MergedCheck::Skipped(format!(
+                "{}: no base branch found, pass --base",
+                info.name
+            )));
+            continue;
+        };
+
+        let via_pr = if merged_into(&repo_path, &info.branch, &base_ref) {
+            false
+        } else if merged_via_pr(&repo_path, &info.branch) {
+            true
+        } else {
+            continue;
+        };
+
+        let label = format!("{}/{}", info.repo_name, info.name);
+        if current_dir.as_deref() == Some(info.path.as_path()) {
+            checks.push(MergedCheck::Skipped(format!(
+                "{label} is merged but is the current directory; skipping"
+            )));
+            continue;
+        }
+        if info.path.exists() && has_uncommitted_changes(&info.path) {
+            checks.push(MergedCheck::Skipped(format!(
+                "{label} is merged but has uncommitted changes; skipping"
+            )));
+            continue;
+        }
+
+        checks.push(MergedCheck::Merged(Box::new(MergedWorktree {
+            info: info.clone(),
+            repo_path,
+            via_pr,
+            base_ref,
+        })));
+    }
+    Ok(checks)
+}
+
+/// Remove every worktree whose branch is merged into its base branch, along
+/// with the branch and its state entry, after one confirmation. With
+/// `dry_run`, only list them.
+fn clean_merged(base: Option<String>, yes: bool, dry_run: bool) -> Result<()> {
+    let state = PigsState::load()?;
+    if state.worktrees.is_empty() {
+        println!("{} No worktrees in state", "✨".green());
+        return Ok(());
+    }
+
+    println!("{} Checking for merged worktrees...", "🔍".cyan());
+
+    let mut candidates = Vec::new();
+    for check in check_merged(&state, base.as_deref())? {
+        match check {
+            MergedCheck::Skipped(reason) => println!("  {} {}", "⚠️".yellow(), reason),
+            MergedCheck::Merged(candidate) => {
+                println!(
+                    "  {} {} ({} merged {})",
+                    "✔".green(),
+                    candidate.label().cyan(),
+                    candidate.info.branch,
+                    candidate.merged_how()
+                );
+                candidates.push(*candidate);
+            }
+        }
+    }
+
+    if candidates.is_empty() {
+        println!("{} No merged worktrees", "✨".green());
+        return Ok(());
+    }
+
+    if dry_run {
+        println!(
+            "{} Dry run: would remove {} merged worktree{} and their branches",
+            "📝".cyan(),
+            candidates.len(),
+            if candidates.len() == 1 { "" } else { "s" }
+        );
+        return Ok(());
+    }
+
+    let prompt = format!(
+        "Remove {} merged worktree{} and their branches?",
+        candidates.len(),
+        if candidates.len() == 1 { "" } else { "s" }
+    );
+    if !yes && !smart_confirm(&prompt, true)? {
+        println!("{} Cancelled", "❌".red());
+        return Ok(());
+    }
+
+    let mut removed = 0;
+    for candidate in &candidates {
+        let info = &candidate.info;
+        match remove_merged(candidate) {
+            Ok(()) => {
+                removed += 1;
+                println!(
+                    "{} Removed {} and branch {}",
+                    "✅".green(),
+                    info.name.cyan(),
+                    info.branch
+                );
+            }
+            Err(err) => println!("{} Failed to remove {}: {err:#}", "❌".red(), info.name),
+        }
+    }
+
+    println!(
+        "{} Removed {}/{} merged worktrees",
+        "✨".green(),
+        removed,
+        candidates.len()
+    );
+    Ok(())
+}
+
+/// Queue `pigs clean --merged --yes` as a `clean` job, with one step per
+/// removed worktree. Cancelling stops before the next worktree.
+pub fn spawn_clean_merged_job(base: Option<String>) -> (Arc<Job>, broadcast::Receiver<JobEvent>) {
+    jobs::spawn("clean", base.clone(), move |job| {
+        job.step("check");
+        let state = PigsState::load()?;
+        let mut removed = Vec::new();
+        let mut skipped = Vec::new();
+        let mut failed = Vec::new();
+        for check in check_merged(&state, base.as_deref())? {
+            let candidate = match check {
+                MergedCheck::Skipped(reason) => {
+                    skipped.push(reason);
+                    continue;
+                }
+                MergedCheck::Merged(candidate) => candidate,
+            };
+            job.check_cancelled()?;
+            let label = candidate.label();
+            job.step(&format!("remove {label}"));
+            match remove_merged(&candidate) {
+                Ok(()) => removed.push(label),
+                Err(err) => failed.push(json!({ "name": label, "error": format!("{err:#}") })),
+            }
+        }
+        if !failed.is_empty() {
+            bail!("{} merged worktree(s) could not be removed", failed.len());
+        }
+        Ok(json!({ "removed": removed, "skipped": skipped, "failed": failed }))
+    })
+}
+
+fn remove_merged(candidate: &MergedWorktree) -> Result<()> {
+    let branch_deleted = delete_worktree_unattended(&candidate.info, false)?;
+    // Squash merges leave the branch unmerged as far as `git branch -d` knows
+    if !branch_deleted && candidate.via_pr {
+        let repo = candidate
+            .repo_path
+            .to_str()
+            .context("Repository path contains invalid UTF-8")?;
+        execute_git(&["-C", repo, "branch", "-D", &candidate.info.branch])
+            .context("Failed to delete branch")?;
+    }
+    Ok(())
+}
+
+/// Whether `branch` has commits of its own that are all contained in `base`.
+/// A branch nobody has committed to yet is not considered merged.
+fn merged_into(repo_path: &Path, branch: &str, base: &str) -> bool {
+    let Some(repo) = repo_path.to_str() else {
+        return false;
+    };
+    let branch_ref = format!("refs/heads/{branch}");
+    let contained =
+        execute_git(&["-C", repo, "merge-base", "--is-ancestor", &branch_ref, base]).is_ok();
+    contained && has_own_commits(repo, &branch_ref)
+}
+
+/// The branch reflog has an entry per update; one means it was only created.
+fn has_own_commits(repo: &str, branch_ref: &str) -> bool {
+    execute_git(&["-C", repo, "reflog", "show", "--format=%H", branch_ref])
+        .map(|log| log.lines().count() > 1)
+        .unwrap_or(false)
+}
+
+/// Whether a merged GitHub PR has exactly this branch's local head, so commits
+/// made after the merge are never thrown away.
+fn merged_via_pr(repo_path: &Path, branch: &str) -> bool {
+    if network::is_offline() {
+        return false;
+    }
+    let Some(repo) = repo_path.to_str() else {
+        return false;
+    };
+    let Ok(local_head) = execute_git(&["-C", repo, "rev-parse", &format!("refs/heads/{branch}")])
+    else {
+        return false;
+    };
+
+    std::process::Command::new("gh")
+        .args([
+            "pr",
+            "list",
+            "--state",
+            "merged",
+            "--head",
+            branch,
+            "--json",
+            "headRefOid",
+        ])
+        .current_dir(repo_path)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| serde_json::from_slice::<Vec<serde_json::Value>>(&output.stdout).ok())
+        .is_some_and(|prs| {
+            prs.iter()
+                .any(|pr| pr["headRefOid"].as_str() == Some(local_head.trim()))
+        })
+}
 
-    Ok(all_worktrees)
+fn has_uncommitted_changes(worktree_path: &Path) -> bool {
+    worktree_path
+        .to_str()
+        .and_then(|path| execute_git(&["-C", path, "status", "--porcelain"]).ok())
+        .is_none_or(|status| !status.is_empty())
 }
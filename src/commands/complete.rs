@@ -4,7 +4,7 @@ use std::path::Path;
 use std::process::Command;
 
 use crate::claude::get_claude_sessions;
-use crate::state::{PigsState, WorktreeInfo};
+use crate::state::{PigsState, RepoConfig, WorktreeInfo};
 
 pub fn handle_complete_worktrees(format: &str) -> Result<()> {
     // Silently load state, return empty on any error
@@ -13,50 +13,26 @@ pub fn handle_complete_worktrees(format: &str) -> Result<()> {
         Err(_) => return Ok(()), // Silent failure for completions
     };
 
-    if state.worktrees.is_empty() {
-        return Ok(());
-    }
-
-    // Collect all worktrees and sort them
-    // Primary sort: by repository name
-    // Secondary sort: by worktree name within same repository
-    let mut all_worktrees: Vec<&WorktreeInfo> = state.worktrees.values().collect();
-    all_worktrees.sort_by(|a, b| match a.repo_name.cmp(&b.repo_name) {
-        std::cmp::Ordering::Equal => a.name.cmp(&b.name),
-        other => other,
-    });
-
     match format {
-        "simple" => {
-            // Simple format: just worktree names, one per line, sorted
-            for info in &all_worktrees {
-                println!("{}", info.name);
-            }
-        }
         "detailed" => {
-            // Detailed format: name<TAB>repo<TAB>path<TAB>sessions
+            // Detailed format: name<TAB>repo<TAB>path<TAB>sessions<TAB>note
             // Used by shell completions for rich descriptions
-            for info in &all_worktrees {
-                let session_count = count_sessions_safe(&info.path);
-                let session_text = match session_count {
-                    0 => "no sessions".to_string(),
-                    1 => "1 session".to_string(),
-                    n => format!("{} sessions", n),
-                };
-
+            for info in sorted_worktrees(&state) {
                 // Use tab separator for easy parsing
                 println!(
-                    "{}\t{}\t{}\t{}",
+                    "{}\t{}\t{}\t{}\t{}",
                     info.name,
                     info.repo_name,
                     info.path.display(),
-                    session_text
+                    session_summary(info),
+                    one_line_note(info)
                 );
             }
         }
         _ => {
-            // Unknown format, fall back to simple
-            for info in &all_worktrees {
+            // Simple format (and fallback for unknown formats): just worktree
+            // names, one per line, sorted
+            for info in sorted_worktrees(&state) {
                 println!("{}", info.name);
             }
         }
@@ -65,8 +41,66 @@ pub fn handle_complete_worktrees(format: &str) -> Result<()> {
     Ok(())
 }
 
+/// Worktree names with a `[repo] N sessions - note` description, for the
+/// completions pigs answers itself.
+pub fn worktree_candidates() -> Vec<(String, String)> {
+    let Ok(state) = PigsState::load() else {
+        return Vec::new();
+    };
+    sorted_worktrees(&state)
+        .into_iter()
+        .map(|info| {
+            let mut help = format!("[{}] {}", info.repo_name, session_summary(info));
+            let note = one_line_note(info);
+            if !note.is_empty() {
+                help.push_str(" - ");
+                help.push_str(&note);
+            }
+            (info.name.clone(), help)
+        })
+        .collect()
+}
+
+/// Worktrees sorted by repository name, then by worktree name within the same
+/// repository.
+fn sorted_worktrees(state: &PigsState) -> Vec<&WorktreeInfo> {
+    let mut all_worktrees: Vec<&WorktreeInfo> = state.worktrees.values().collect();
+    all_worktrees.sort_by(|a, b| match a.repo_name.cmp(&b.repo_name) {
+        std::cmp::Ordering::Equal => a.name.cmp(&b.name),
+        other => other,
+    });
+    all_worktrees
+}
+
+fn session_summary(info: &WorktreeInfo) -> String {
+    match count_sessions_safe(&info.path) {
+        0 => "no sessions".to_string(),
+        1 => "1 session".to_string(),
+        n => format!("{} sessions", n),
+    }
+}
+
+/// Notes are free-form, so keep them to one field on one line
+fn one_line_note(info: &WorktreeInfo) -> String {
+    info.note
+        .as_deref()
+        .unwrap_or_default()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
 /// Output completion candidates for `--from`: worktree names + local branch names, deduplicated.
 pub fn handle_complete_from() -> Result<()> {
+    for name in &from_candidates() {
+        println!("{}", name);
+    }
+
+    Ok(())
+}
+
+/// Worktree names and local branch names, deduplicated and sorted.
+pub fn from_candidates() -> BTreeSet<String> {
     let mut candidates = BTreeSet::new();
 
     // Add worktree names
@@ -80,43 +114,66 @@ pub fn handle_complete_from() -> Result<()> {
     if let Ok(output) = Command::new("git")
         .args(["branch", "--format=%(refname:short)"])
         .output()
+        && output.status.success()
     {
-        if output.status.success() {
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            for line in stdout.lines() {
-                let branch = line.trim();
-                if !branch.is_empty() {
-                    candidates.insert(branch.to_string());
-                }
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        for line in stdout.lines() {
+            let branch = line.trim();
+            if !branch.is_empty() {
+                candidates.insert(branch.to_string());
             }
         }
     }
 
-    for name in &candidates {
-        println!("{}", name);
-    }
-
-    Ok(())
+    candidates
 }
 
 /// Output configured agent names for `--agent` completions.
 pub fn handle_complete_agents() -> Result<()> {
-    if let Ok(state) = PigsState::load_with_local_overrides() {
-        if let Some(options) = state.agent {
-            for option in options {
-                let name = option.name.trim();
-                if !name.is_empty() {
-                    println!("{name}");
-                }
-            }
-            return Ok(());
-        }
+    for name in agent_names() {
+        println!("{name}");
+    }
+    Ok(())
+}
+
+/// Configured agent names, or the default agent's when none are configured.
+pub fn agent_names() -> Vec<String> {
+    if let Ok(state) = PigsState::load_with_local_overrides()
+        && let Some(options) = state.agent
+    {
+        return options
+            .into_iter()
+            .map(|option| option.name.trim().to_string())
+            .filter(|name| !name.is_empty())
+            .collect();
+    }
+
+    vec![crate::state::get_default_agent().name]
+}
+
+/// Output the current repository's configured task names and their commands,
+/// tab separated, for completing task names.
+pub fn handle_complete_tasks() -> Result<()> {
+    for (name, command) in task_candidates() {
+        println!("{name}\t{command}");
     }
 
-    println!("{}", crate::state::get_default_agent().name);
     Ok(())
 }
 
+/// The current repository's configured task names and their commands.
+pub fn task_candidates() -> Vec<(String, String)> {
+    let Ok(root) = crate::git::execute_git(&["rev-parse", "--show-toplevel"]) else {
+        return Vec::new();
+    };
+    // Completions must not prompt; untrusted tasks are simply left out
+    crate::trust::disable_prompts();
+    let Ok(config) = RepoConfig::load(Path::new(&root)) else {
+        return Vec::new();
+    };
+    config.tasks.into_iter().collect()
+}
+
 // Safe wrapper for counting sessions that won't fail
 fn count_sessions_safe(worktree_path: &Path) -> usize {
     get_claude_sessions(worktree_path).len()
@@ -1,16 +1,28 @@
 use anyhow::Result;
 
 use crate::linear;
+use crate::network;
 
 pub fn handle_complete_linear() -> Result<()> {
-    let issues = match linear::fetch_my_issues() {
-        Ok(issues) => issues,
-        Err(_) => return Ok(()),
-    };
-
-    for issue in issues {
-        println!("{}\t{}", issue.identifier, issue.title);
+    for (identifier, title) in linear_candidates() {
+        println!("{}\t{}", identifier, title);
     }
 
     Ok(())
 }
+
+/// Identifiers and titles of the current user's Linear issues; empty when
+/// offline or when Linear can't be reached.
+pub fn linear_candidates() -> Vec<(String, String)> {
+    if network::is_offline() {
+        return Vec::new();
+    }
+
+    match linear::fetch_my_issues() {
+        Ok(issues) => issues
+            .into_iter()
+            .map(|issue| (issue.identifier, issue.title))
+            .collect(),
+        Err(_) => Vec::new(),
+    }
+}
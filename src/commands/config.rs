@@ -1,7 +1,14 @@
 use std::fs;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
 use anyhow::{Context, Result, anyhow, bail};
+use colored::Colorize;
+use serde_json::{Map, Value};
+
+use crate::git::execute_git;
+use crate::state::{AgentOption, PigsState, RepoConfig, get_default_agent, get_state_path};
+use crate::trust::set_trusted;
 
 pub fn handle_config() -> Result<()> {
     let editor = std::env::var("EDITOR")
@@ -14,7 +21,7 @@ pub fn handle_config() -> Result<()> {
         bail!("EDITOR command is empty");
     }
 
-    let state_path = crate::state::get_state_path()?;
+    let state_path = get_state_path()?;
     if let Some(parent) = state_path.parent() {
         fs::create_dir_all(parent)
             .with_context(|| format!("Failed to create config directory: {}", parent.display()))?;
@@ -42,3 +49,170 @@ pub fn handle_config() -> Result<()> {
 
     Ok(())
 }
+
+/// Keys pigs maintains itself; other commands change them.
+const MANAGED_KEYS: &[&str] = &["worktrees", "tombstones", "trusted_repos"];
+
+/// Print one setting: strings as-is, anything else as JSON.
+pub fn handle_config_get(key: String, local: bool) -> Result<()> {
+    let (_, settings) = load_settings(local)?;
+    let value = settings
+        .get(&key)
+        .with_context(|| format!("'{key}' is not set"))?;
+    match value {
+        Value::String(text) => println!("{text}"),
+        other => println!("{}", serde_json::to_string_pretty(other)?),
+    }
+    Ok(())
+}
+
+/// Set one setting. `value` is parsed as JSON when it can be, else taken as a
+/// string. For `agent`, the name of a configured agent makes it the default,
+/// and a command line adds (or replaces) the agent named after its program.
+pub fn handle_config_set(key: String, value: String, local: bool) -> Result<()> {
+    ensure_not_managed(&key)?;
+    let (path, mut settings) = load_settings(local)?;
+
+    let parsed = serde_json::from_str::<Value>(&value).unwrap_or(Value::String(value.clone()));
+    let parsed = match (key.as_str(), parsed) {
+        ("agent", Value::String(_)) => agent_list_with_default(&settings, &value, local)?,
+        (_, parsed) => parsed,
+    };
+    settings.insert(key.clone(), parsed);
+    save_settings(&path, settings, local)?;
+
+    println!("{} Set {} in {}", "✅".green(), key.cyan(), path.display());
+    Ok(())
+}
+
+/// Remove one setting, falling back to its default (or the global value, for
+/// a local file).
+pub fn handle_config_unset(key: String, local: bool) -> Result<()> {
+    ensure_not_managed(&key)?;
+    let (path, mut settings) = load_settings(local)?;
+    if settings.remove(&key).is_none() {
+        println!("{} '{}' is not set", "ℹ️".blue(), key);
+        return Ok(());
+    }
+    save_settings(&path, settings, local)?;
+    println!(
+        "{} Unset {} in {}",
+        "🗑️".green(),
+        key.cyan(),
+        path.display()
+    );
+    Ok(())
+}
+
+/// Print every setting as `key = value`, leaving out the ones pigs maintains.
+pub fn handle_config_list(local: bool) -> Result<()> {
+    let (_, settings) = load_settings(local)?;
+    for (key, value) in &settings {
+        if MANAGED_KEYS.contains(&key.as_str()) {
+            continue;
+        }
+        let value = match value {
+            Value::String(text) => text.clone(),
+            other => serde_json::to_string(other)?,
+        };
+        println!("{} = {}", key.cyan(), value);
+    }
+    Ok(())
+}
+
+fn ensure_not_managed(key: &str) -> Result<()> {
+    if MANAGED_KEYS.contains(&key) {
+        bail!("'{key}' is maintained by pigs and can't be changed with pigs config");
+    }
+    Ok(())
+}
+
+/// The settings file to change: `~/.pigs/settings.json`, or with `local` the
+/// repository's `.pigs/settings.json`. A missing file reads as empty.
+fn load_settings(local: bool) -> Result<(PathBuf, Map<String, Value>)> {
+    let path = if local {
+        let root = execute_git(&["rev-parse", "--show-toplevel"])
+            .context("--local must be used inside a git repository")?;
+        PathBuf::from(root).join(".pigs/settings.json")
+    } else {
+        get_state_path()?
+    };
+    if !path.exists() {
+        return Ok((path, Map::new()));
+    }
+    let content =
+        fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let settings = serde_json::from_str(&content)
+        .with_context(|| format!("Failed to parse {}", path.display()))?;
+    Ok((path, settings))
+}
+
+/// Check the new settings still load, then write them. Privileged settings the
+/// user sets in a local file are trusted, as with `pigs init`.
+fn save_settings(path: &Path, settings: Map<String, Value>, local: bool) -> Result<()> {
+    let settings = Value::Object(settings);
+    serde_json::from_value::<PigsState>(settings.clone())
+        .map_err(|err| anyhow!("Invalid value: {err}"))?;
+    if local {
+        serde_json::from_value::<RepoConfig>(settings.clone())
+            .map_err(|err| anyhow!("Invalid value: {err}"))?;
+    }
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    fs::write(
+        path,
+        format!("{}\n", serde_json::to_string_pretty(&settings)?),
+    )
+    .with_context(|| format!("Failed to write {}", path.display()))?;
+
+    if local {
+        set_trusted(path, &settings, true)?;
+    }
+    Ok(())
+}
+
+/// The agent list with `value` first: an agent already configured under that
+/// name, or a new agent running the `value` command line.
+fn agent_list_with_default(
+    settings: &Map<String, Value>,
+    value: &str,
+    local: bool,
+) -> Result<Value> {
+    let mut agents: Vec<AgentOption> = match settings.get("agent") {
+        Some(agents) => serde_json::from_value(agents.clone()).context("Invalid 'agent' list")?,
+        // A local file starts from the agents it would otherwise inherit
+        None if local => PigsState::load()?.agent.unwrap_or_default(),
+        None => Vec::new(),
+    };
+    if agents.is_empty() {
+        agents.push(get_default_agent());
+    }
+
+    let existing = agents
+        .iter()
+        .position(|agent| agent.name.eq_ignore_ascii_case(value));
+    let agent = match existing {
+        Some(index) => agents.remove(index),
+        None => {
+            let program = shell_words::split(value)
+                .map_err(|err| anyhow!("Invalid agent command: {value} ({err})"))?
+                .into_iter()
+                .next()
+                .context("Agent command is empty")?;
+            let name = Path::new(&program)
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or(program);
+            agents.retain(|agent| agent.name != name);
+            AgentOption {
+                name,
+                command: value.to_string(),
+            }
+        }
+    };
+    agents.insert(0, agent);
+    Ok(serde_json::to_value(agents)?)
+}
@@ -1,57 +1,495 @@
 use anyhow::{Context, Result};
 use chrono::Utc;
 use colored::Colorize;
-use std::path::PathBuf;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::broadcast;
 
 use crate::commands::open::handle_open;
+use crate::container::{self, ContainerImage};
+use crate::detect::detect;
 use crate::git::{
-    copy_files_to_worktree, execute_git, extract_repo_name_from_url, get_repo_name, list_worktrees,
-    run_setup_commands, update_submodules,
+    apply_patch, copy_files_to_worktree, execute_git, extract_repo_name_from_url, get_repo_name,
+    list_worktrees, run_setup_commands, update_submodules,
 };
+use crate::hooks::{self, Hook};
 use crate::input::{get_command_arg, smart_confirm};
-use crate::state::{PigsState, RepoConfig, WorktreeInfo};
-use crate::utils::{generate_random_name, sanitize_branch_name};
+use crate::jobs;
+use crate::names;
+use crate::pool::{self, PooledWorktree};
+use crate::repo_lock::lock_repo;
+use crate::stack::StackLink;
+use crate::state::{HookConfig, PigsState, RepoConfig, WorktreeInfo};
+use crate::utils::sanitize_branch_name;
+use crate::webhooks::{self, WebhookEvent};
 
+/// Per-invocation override of the repository's `copy_files`, from
+/// `--no-copy` and `--copy <file>`.
+#[derive(Debug, Clone, Default)]
+pub struct CopyOverride {
+    /// Skip the files listed in `copy_files`
+    pub skip_configured: bool,
+    /// Files to copy in addition, relative to the repository root
+    pub extra: Vec<String>,
+}
+
+impl CopyOverride {
+    fn files(&self, configured: &[String]) -> Vec<String> {
+        let mut files = if self.skip_configured {
+            Vec::new()
+        } else {
+            configured.to_vec()
+        };
+        for file in &self.extra {
+            if !files.contains(file) {
+                files.push(file.clone());
+            }
+        }
+        files
+    }
+
+    /// Fail before anything is created when a `--copy` file doesn't exist.
+    fn check(&self, source_root: &Path) -> Result<()> {
+        for file in &self.extra {
+            if Path::new(file).is_absolute() || !source_root.join(file).is_file() {
+                anyhow::bail!(
+                    "--copy '{}' is not a file in {}",
+                    file,
+                    source_root.display()
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Long-running phases of worktree creation, reported as job steps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CreateStep {
+    Branching,
+    WorktreeAdd,
+    Submodules,
+    Copy,
+    Hooks,
+}
+
+impl CreateStep {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Branching => "branching",
+            Self::WorktreeAdd => "worktree-add",
+            Self::Submodules => "submodules",
+            Self::Copy => "copy",
+            Self::Hooks => "hooks",
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn handle_create(
-    name: Option<String>,
+    names: Vec<String>,
+    count: Option<usize>,
     from: Option<String>,
+    stack_on: Option<String>,
+    track: Option<String>,
     yes: bool,
+    json: bool,
+    container: bool,
+    fast: bool,
     selected_agent: Option<String>,
     agent_args: Vec<String>,
+    patch_file: Option<PathBuf>,
+    copy: CopyOverride,
+    path: Option<PathBuf>,
+) -> Result<()> {
+    copy.check(&std::env::current_dir()?)?;
+    let batch = count.is_some() || names.len() > 1;
+    if batch
+        && (yes
+            || json
+            || track.is_some()
+            || path.is_some()
+            || selected_agent.is_some()
+            || !agent_args.is_empty())
+    {
+        anyhow::bail!(
+            "-y, --json, --track, --path, --agent, and agent arguments only work when creating a single worktree"
+        );
+    }
+    if count == Some(0) {
+        anyhow::bail!("--count must be at least 1");
+    }
+    let name = if batch { None } else { names.first().cloned() };
+    if json {
+        if track.is_some() {
+            anyhow::bail!("--track cannot be used with --json");
+        }
+        return handle_create_json(name, from, stack_on);
+    }
+    // Read the patch before anything else can consume stdin
+    let patch = patch_file.as_deref().map(read_patch).transpose()?;
+    let container = if container {
+        Some(pull_prebuild_image()?)
+    } else {
+        None
+    };
+    if batch {
+        return handle_create_batch(
+            names,
+            count,
+            from,
+            stack_on,
+            container,
+            fast,
+            patch.as_deref(),
+            &copy,
+        );
+    }
+    handle_create_in_dir(
+        name,
+        None,
+        from,
+        stack_on,
+        track,
+        yes,
+        container,
+        fast,
+        selected_agent,
+        agent_args,
+        patch.as_deref(),
+        &copy,
+        path,
+    )
+}
+
+/// Create several worktrees in one go, e.g. to hand the same task to several
+/// agents for comparison: the given names, or `count` generated ones. Each is
+/// created like a single worktree, without offering to open it, and a failure
+/// doesn't stop the rest. Ends with a summary table.
+#[allow(clippy::too_many_arguments)]
+fn handle_create_batch(
+    names: Vec<String>,
+    count: Option<usize>,
+    from: Option<String>,
+    stack_on: Option<String>,
+    container: Option<ContainerImage>,
+    fast: bool,
+    patch: Option<&[u8]>,
+    copy: &CopyOverride,
+) -> Result<()> {
+    let repo_name = resolve_repo_name(None)?;
+    for (index, name) in names.iter().enumerate() {
+        let sanitized = sanitize_branch_name(name);
+        if names[..index]
+            .iter()
+            .any(|other| sanitize_branch_name(other) == sanitized)
+        {
+            anyhow::bail!("'{name}' is given more than once");
+        }
+    }
+
+    let total = count.unwrap_or(names.len());
+    let mut names = names.into_iter();
+    let mut created = Vec::new();
+    let mut failed = Vec::new();
+    for index in 1..=total {
+        // Generate each name after the previous worktree exists, so they differ
+        let name = match names.next() {
+            Some(name) => name,
+            None => names::generate(&repo_name, None)?,
+        };
+        println!(
+            "{} Creating worktree {}/{}: '{}'...",
+            "✨".green(),
+            index,
+            total,
+            name.cyan()
+        );
+        match create_worktree(
+            Some(name.clone()),
+            None,
+            from.clone(),
+            stack_on.clone(),
+            None,
+            true,
+            false,
+            container.clone(),
+            fast,
+            None,
+            Vec::new(),
+            patch,
+            copy,
+            None,
+            &|_| Ok(()),
+        ) {
+            Ok(info) => created.push(info),
+            Err(e) => {
+                eprintln!("{} Failed to create '{}': {:#}", "❌".red(), name, e);
+                failed.push(name);
+            }
+        }
+    }
+
+    if !created.is_empty() {
+        let name_width = created
+            .iter()
+            .map(|info| info.name.len())
+            .max()
+            .unwrap_or(0);
+        let branch_width = created
+            .iter()
+            .map(|info| info.branch.len())
+            .max()
+            .unwrap_or(0);
+        println!();
+        println!(
+            "{} Created {}/{} worktrees:",
+            "✅".green(),
+            created.len(),
+            total
+        );
+        println!(
+            "  {:<name_width$}  {:<branch_width$}  {}",
+            "name".bold(),
+            "branch".bold(),
+            "path".bold()
+        );
+        for info in &created {
+            println!(
+                "  {:<name_width$}  {:<branch_width$}  {}",
+                info.name.cyan(),
+                info.branch,
+                info.path.display().to_string().dimmed()
+            );
+        }
+        println!(
+            "  {} To open one, run: {} <name>",
+            "💡".cyan(),
+            "pigs open".cyan()
+        );
+    }
+
+    if !failed.is_empty() {
+        anyhow::bail!(
+            "Failed to create {} of {} worktrees: {}",
+            failed.len(),
+            total,
+            failed.join(", ")
+        );
+    }
+    Ok(())
+}
+
+/// Read the patch for `--apply-patch`: a file, or stdin for `-`.
+fn read_patch(source: &Path) -> Result<Vec<u8>> {
+    let patch = if source == Path::new("-") {
+        let mut patch = Vec::new();
+        std::io::stdin()
+            .read_to_end(&mut patch)
+            .context("Failed to read the patch from stdin")?;
+        patch
+    } else {
+        std::fs::read(source)
+            .with_context(|| format!("Failed to read patch {}", source.display()))?
+    };
+    if patch.iter().all(u8::is_ascii_whitespace) {
+        anyhow::bail!("The patch is empty");
+    }
+    Ok(patch)
+}
+
+/// Pull the repository's prebuild image before anything is created, so a
+/// missing or unreachable image fails fast.
+fn pull_prebuild_image() -> Result<ContainerImage> {
+    let image = container::configured_image(&std::env::current_dir()?)?;
+    println!("{} Pulling prebuild image {}...", "🐳".blue(), image.cyan());
+    let pulled = container::pull(&image)?;
+    println!("  {} Pinned {}", "📌".green(), pulled.digest);
+    Ok(pulled)
+}
+
+/// Run creation as a job and print its progress events as JSON lines, ending
+/// with a `succeeded` or `failed` event.
+fn handle_create_json(
+    name: Option<String>,
+    from: Option<String>,
+    stack_on: Option<String>,
 ) -> Result<()> {
-    handle_create_in_dir(name, None, from, yes, selected_agent, agent_args)
+    // Resolve the name here: the job thread must not read it from stdin
+    let name = get_command_arg(name)?;
+    let (job, mut events) = spawn_create_job(name, None, from, stack_on);
+
+    while let Ok(event) = events.blocking_recv() {
+        println!("{}", serde_json::to_string(&event)?);
+        if event.is_terminal() {
+            break;
+        }
+    }
+
+    match job.snapshot().error {
+        Some(error) => anyhow::bail!(error),
+        None => Ok(()),
+    }
+}
+
+/// Queue worktree creation as a `create` job. Each phase is reported as a job
+/// step, and the result holds the new worktree's key, name, branch, and path.
+/// The job can be cancelled until it starts changing the repository.
+pub fn spawn_create_job(
+    name: Option<String>,
+    repo_path: Option<PathBuf>,
+    from: Option<String>,
+    stack_on: Option<String>,
+) -> (Arc<jobs::Job>, broadcast::Receiver<jobs::JobEvent>) {
+    let description = name.clone();
+    jobs::spawn("create", description, move |job| {
+        let name = match name {
+            Some(name) => name,
+            None => names::generate(
+                &resolve_repo_name(repo_path.as_ref())?,
+                repo_path.as_deref(),
+            )?,
+        };
+        let progress = |step: CreateStep| {
+            job.step(step.as_str());
+            match step {
+                CreateStep::Branching => job.check_cancelled(),
+                _ => Ok(()),
+            }
+        };
+        let info = create_worktree(
+            Some(name),
+            repo_path,
+            from,
+            stack_on,
+            None,
+            true,
+            false,
+            None,
+            false,
+            None,
+            Vec::new(),
+            None,
+            &CopyOverride::default(),
+            None,
+            &progress,
+        )?;
+        Ok(serde_json::json!({
+            "key": PigsState::make_key(&info.repo_name, &info.name),
+            "name": info.name,
+            "branch": info.branch,
+            "path": info.path,
+        }))
+    })
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn handle_create_in_dir(
     name: Option<String>,
     repo_path: Option<PathBuf>,
     from: Option<String>,
+    stack_on: Option<String>,
+    track: Option<String>,
     yes: bool,
+    container: Option<ContainerImage>,
+    fast: bool,
     selected_agent: Option<String>,
     agent_args: Vec<String>,
+    patch: Option<&[u8]>,
+    copy: &CopyOverride,
+    path: Option<PathBuf>,
 ) -> Result<()> {
-    handle_create_in_dir_quiet(
+    create_worktree(
         name,
         repo_path,
         from,
+        stack_on,
+        track,
         false,
         yes,
+        container,
+        fast,
         selected_agent,
         agent_args,
+        patch,
+        copy,
+        path,
+        &|_| Ok(()),
     )?;
     Ok(())
 }
 
 // Create worktree quietly without prompting for open, returns the created worktree name
+#[allow(clippy::too_many_arguments)]
 pub fn handle_create_in_dir_quiet(
     name: Option<String>,
     repo_path: Option<PathBuf>,
     from: Option<String>,
+    stack_on: Option<String>,
     quiet: bool,
     yes: bool,
+    container: Option<ContainerImage>,
     selected_agent: Option<String>,
     agent_args: Vec<String>,
 ) -> Result<String> {
+    create_worktree(
+        name,
+        repo_path,
+        from,
+        stack_on,
+        None,
+        quiet,
+        yes,
+        container,
+        false,
+        selected_agent,
+        agent_args,
+        None,
+        &CopyOverride::default(),
+        None,
+        &|_| Ok(()),
+    )
+    .map(|info| info.name)
+}
+
+/// Get repo name from the target directory
+pub fn resolve_repo_name(repo_path: Option<&PathBuf>) -> Result<String> {
+    if let Some(path) = repo_path {
+        // Get repo name from the specified path using git -C
+        let output = execute_git(&["-C", path.to_str().unwrap(), "remote", "get-url", "origin"])?;
+        if let Some(name) = extract_repo_name_from_url(&output) {
+            Ok(name)
+        } else {
+            // Fallback to directory name
+            path.file_name()
+                .and_then(|n| n.to_str())
+                .map(String::from)
+                .context("Failed to get repository name")
+        }
+    } else {
+        get_repo_name().context("Not in a git repository")
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn create_worktree(
+    name: Option<String>,
+    repo_path: Option<PathBuf>,
+    from: Option<String>,
+    stack_on: Option<String>,
+    track: Option<String>,
+    quiet: bool,
+    yes: bool,
+    container: Option<ContainerImage>,
+    fast: bool,
+    selected_agent: Option<String>,
+    agent_args: Vec<String>,
+    patch: Option<&[u8]>,
+    copy: &CopyOverride,
+    path: Option<PathBuf>,
+    progress: &dyn Fn(CreateStep) -> Result<()>,
+) -> Result<WorktreeInfo> {
     // Helper to execute git in the right directory using git -C
     let exec_git = |args: &[&str]| -> Result<String> {
         if let Some(ref path) = repo_path {
@@ -64,25 +502,30 @@ pub fn handle_create_in_dir_quiet(
         }
     };
 
-    // Get repo name from the target directory
-    let repo_name = if let Some(ref path) = repo_path {
-        // Get repo name from the specified path using git -C
-        let output = execute_git(&["-C", path.to_str().unwrap(), "remote", "get-url", "origin"])?;
-        if let Some(name) = extract_repo_name_from_url(&output) {
-            name
-        } else {
-            // Fallback to directory name
-            path.file_name()
-                .and_then(|n| n.to_str())
-                .map(String::from)
-                .context("Failed to get repository name")?
+    let repo_name = resolve_repo_name(repo_path.as_ref())?;
+
+    // A stacked worktree branches from its parent worktree's branch
+    let stack_parent = match stack_on {
+        Some(ref parent) => {
+            if from.is_some() {
+                anyhow::bail!("--stack-on and --from cannot be used together");
+            }
+            Some(resolve_stack_parent(parent, &repo_name)?)
         }
-    } else {
-        get_repo_name().context("Not in a git repository")?
+        None => None,
     };
+    let from = stack_parent
+        .as_ref()
+        .map(|parent| parent.branch.clone())
+        .or(from);
 
     // Resolve --from target to a source branch if provided
-    let source_branch = if let Some(ref from_target) = from {
+    let source_branch = if let Some(ref track) = track {
+        if from.is_some() {
+            anyhow::bail!("--track and --from/--stack-on cannot be used together");
+        }
+        Some(resolve_track_target(track, quiet, &exec_git)?)
+    } else if let Some(ref from_target) = from {
         Some(resolve_from_target(from_target, &repo_name, &exec_git)?)
     } else {
         None
@@ -108,12 +551,29 @@ pub fn handle_create_in_dir_quiet(
         }
     }
 
-    // Get name from CLI args or pipe, generate if not provided
+    // Get name from CLI args or pipe, generate if not provided. A tracked
+    // branch defaults to the remote branch's own name
+    let name = match (name, &track) {
+        (None, Some(track)) => track.split_once('/').map(|(_, branch)| branch.to_string()),
+        (name, _) => name,
+    };
     let branch_name = match get_command_arg(name)? {
         Some(n) => n,
-        None => generate_random_name()?,
+        None => names::generate(&repo_name, repo_path.as_deref())?,
     };
 
+    // The remote branch the new branch tracks: the --track target, or a
+    // remote-only --from branch checked out under its own name
+    let upstream = track.clone().or_else(|| {
+        source_branch
+            .as_ref()
+            .filter(|src| {
+                src.strip_prefix("origin/") == Some(branch_name.as_str())
+                    && exec_git(&["show-ref", "--verify", &format!("refs/remotes/{src}")]).is_ok()
+            })
+            .cloned()
+    });
+
     // Sanitize the branch name for use in directory names
     let worktree_name = sanitize_branch_name(&branch_name);
 
@@ -128,23 +588,21 @@ pub fn handle_create_in_dir_quiet(
         );
     }
 
-    // Check if the worktree directory will be created
-    let worktree_dir_path = if let Some(ref path) = repo_path {
-        path.parent()
-            .unwrap()
-            .join(format!("{repo_name}-{worktree_name}"))
-    } else {
-        std::env::current_dir()?
-            .parent()
-            .unwrap()
-            .join(format!("{repo_name}-{worktree_name}"))
+    // Where the worktree goes: --path, else the configured or default location
+    let source_root = match repo_path {
+        Some(ref path) => path.clone(),
+        None => PathBuf::from(exec_git(&["rev-parse", "--show-toplevel"])?),
+    };
+    let worktree_path = match path {
+        Some(path) => std::env::current_dir()?.join(path),
+        None => state.worktree_dir(&source_root, &repo_name, &worktree_name)?,
     };
 
     // Check if the directory already exists
-    if worktree_dir_path.exists() {
+    if worktree_path.exists() {
         anyhow::bail!(
             "Directory '{}' already exists. Please choose a different name or remove the existing directory.",
-            worktree_dir_path.display()
+            worktree_path.display()
         );
     }
 
@@ -170,13 +628,16 @@ pub fn handle_create_in_dir_quiet(
         list_worktrees()?
     };
 
-    if existing_worktrees.iter().any(|w| w == &worktree_dir_path) {
+    if existing_worktrees.iter().any(|w| w == &worktree_path) {
         anyhow::bail!(
             "A git worktree already exists at '{}'. Please choose a different name or remove the existing worktree.",
-            worktree_dir_path.display()
+            worktree_path.display()
         );
     }
 
+    progress(CreateStep::Branching)?;
+    let repo_guard = lock_repo(&repo_name, &format!("create worktree '{worktree_name}'"))?;
+
     // Check if the branch already exists
     let branch_already_exists = exec_git(&[
         "show-ref",
@@ -185,7 +646,48 @@ pub fn handle_create_in_dir_quiet(
     ])
     .is_ok();
 
-    if branch_already_exists {
+    // Undo the branch and worktree if anything below fails before state is saved
+    let mut rollback = CreateRollback {
+        exec_git: &exec_git,
+        branch: None,
+        worktree_dir: None,
+        pooled: None,
+    };
+
+    // The branch a new branch starts from, which `pigs sync` keeps it current with
+    let mut base_branch = None;
+
+    // A pooled worktree only stands in for a new branch off the current base branch
+    let claimed =
+        if fast && !branch_already_exists && source_branch.is_none() && repo_path.is_none() {
+            let current_branch = exec_git(&["branch", "--show-current"])?;
+            let claimed = pool::claim(&repo_name, &current_branch)?;
+            if claimed.is_none() && !quiet {
+                println!(
+                    "{} No pooled worktree ready for '{}', creating one from scratch",
+                    "⚠️".yellow(),
+                    current_branch
+                );
+            }
+            claimed
+        } else {
+            None
+        };
+    rollback.pooled = claimed.clone();
+
+    if let Some(ref pooled) = claimed {
+        if !quiet {
+            println!(
+                "{} Claiming a pooled worktree for '{}'...",
+                "⚡".green(),
+                worktree_name.cyan()
+            );
+        }
+        exec_git(&["branch", "-m", &pooled.branch, &branch_name])
+            .context("Failed to rename the pooled worktree's branch")?;
+        base_branch = Some(pooled.base_branch.clone());
+        rollback.branch = Some(branch_name.clone());
+    } else if branch_already_exists {
         if !quiet {
             println!(
                 "{} Creating worktree '{}' from existing branch '{}'...",
@@ -194,6 +696,14 @@ pub fn handle_create_in_dir_quiet(
                 branch_name.cyan()
             );
         }
+        if let Some(ref upstream) = upstream {
+            exec_git(&[
+                "branch",
+                &format!("--set-upstream-to={upstream}"),
+                &branch_name,
+            ])
+            .with_context(|| format!("Failed to set '{upstream}' as the upstream"))?;
+        }
     } else {
         if !quiet {
             if let Some(ref src) = source_branch {
@@ -214,10 +724,15 @@ pub fn handle_create_in_dir_quiet(
             }
         }
 
-        if let Some(ref src) = source_branch {
+        if let Some(ref upstream) = upstream {
+            // Track the remote branch so `git pull` and `git push` work as usual
+            exec_git(&["branch", "--track", &branch_name, upstream])
+                .context("Failed to create branch tracking the remote branch")?;
+        } else if let Some(ref src) = source_branch {
             // Create branch from the resolved --from target
             exec_git(&["branch", &branch_name, src])
                 .context("Failed to create branch from source")?;
+            base_branch = Some(src.clone());
         } else if repo_path.is_some() {
             // When repo_path is provided, create branch from the default branch
             let default_branch = exec_git(&["symbolic-ref", "refs/remotes/origin/HEAD"])
@@ -231,30 +746,34 @@ pub fn handle_create_in_dir_quiet(
                 &format!("origin/{}", default_branch),
             ])
             .context("Failed to create branch from default branch")?;
+            base_branch = Some(default_branch);
         } else {
             // Create branch from current branch (original behavior for CLI)
             exec_git(&["branch", &branch_name]).context("Failed to create branch")?;
+            base_branch = exec_git(&["branch", "--show-current"])
+                .ok()
+                .filter(|branch| !branch.is_empty());
         }
+        rollback.branch = Some(branch_name.clone());
     }
 
     // Create worktree with sanitized directory name
-    let worktree_dir = format!("../{repo_name}-{worktree_name}");
-    exec_git(&["worktree", "add", &worktree_dir, &branch_name])
-        .context("Failed to create worktree")?;
-
-    // Get absolute path
-    let worktree_path = if let Some(ref path) = repo_path {
-        path.parent()
-            .unwrap()
-            .join(format!("{repo_name}-{worktree_name}"))
-    } else {
-        std::env::current_dir()?
-            .parent()
-            .unwrap()
-            .join(format!("{repo_name}-{worktree_name}"))
-    };
+    progress(CreateStep::WorktreeAdd)?;
+    let worktree_dir = worktree_path
+        .to_str()
+        .context("Worktree path contains invalid UTF-8")?
+        .to_string();
+    match claimed {
+        Some(ref pooled) => claim_pooled(&mut rollback, pooled, &worktree_dir)?,
+        None => {
+            exec_git(&["worktree", "add", &worktree_dir, &branch_name])
+                .context("Failed to create worktree")?;
+            rollback.worktree_dir = Some(worktree_dir.clone());
+        }
+    }
 
     // Update submodules if they exist
+    progress(CreateStep::Submodules)?;
     if let Err(e) = update_submodules(&worktree_path) {
         if !quiet {
             println!(
@@ -271,30 +790,65 @@ pub fn handle_create_in_dir_quiet(
         }
     }
 
+    drop(repo_guard);
+
+    // A seed change to start from; failing to apply it undoes the worktree
+    if let Some(patch) = patch {
+        apply_patch(&worktree_path, patch).context("Failed to apply the patch")?;
+        if !quiet {
+            println!("{} Applied the patch", "🩹".green());
+        }
+    }
+
     // Copy CLAUDE.local.md and any repo-configured extra files
-    let source_root = if let Some(ref path) = repo_path {
-        path.clone()
-    } else {
-        PathBuf::from(std::env::current_dir()?)
-    };
     let repo_config = RepoConfig::load(&source_root)?;
-    copy_files_to_worktree(&source_root, &worktree_path, &repo_config.copy_files, quiet)?;
-    run_setup_commands(&worktree_path, &repo_config.setup_commands, quiet)?;
+    progress(CreateStep::Copy)?;
+    copy_files_to_worktree(
+        &source_root,
+        &worktree_path,
+        &copy.files(&repo_config.copy_files),
+        quiet,
+    )?;
+    progress(CreateStep::Hooks)?;
+    // Pooled worktrees ran their setup commands when the pool was filled
+    if claimed.is_none() {
+        run_setup_commands(&worktree_path, &repo_config.setup_commands, quiet)?;
+    }
+
+    // Remember which parent commit the branch starts from so `pigs sync` can restack it
+    let stack = match stack_parent {
+        Some(parent) => Some(StackLink {
+            base: exec_git(&["merge-base", &parent.branch, &branch_name])
+                .context("Failed to find the parent's base commit")?,
+            parent: parent.name,
+        }),
+        None => None,
+    };
 
     // Save state
     let mut state = PigsState::load()?;
     let key = PigsState::make_key(&repo_name, &worktree_name);
-    state.worktrees.insert(
-        key,
-        WorktreeInfo {
-            name: worktree_name.clone(),
-            branch: branch_name.clone(),
-            path: worktree_path.clone(),
-            repo_name,
-            created_at: Utc::now(),
-        },
-    );
+    let info = WorktreeInfo {
+        name: worktree_name.clone(),
+        branch: branch_name.clone(),
+        path: worktree_path.clone(),
+        repo_name,
+        created_at: Utc::now(),
+        base_branch,
+        stack,
+        reminder: None,
+        container,
+        artifacts: Vec::new(),
+        note: None,
+        tags: Vec::new(),
+        agent_history: Vec::new(),
+        repo_root: crate::git::main_checkout(&worktree_path),
+    };
+    state.tombstones.remove(&key);
+    state.worktrees.insert(key, info.clone());
     state.save()?;
+    rollback.disarm();
+    webhooks::emit(WebhookEvent::new("worktree.created", &info));
 
     if !quiet {
         println!(
@@ -303,6 +857,19 @@ pub fn handle_create_in_dir_quiet(
             worktree_path.display()
         );
     }
+    run_post_create(&info, &repo_config.hooks, quiet)?;
+
+    if !quiet && !source_root.join(".pigs/settings.json").exists() {
+        let detected = detect(&source_root);
+        if !detected.is_empty() {
+            println!(
+                "  {} Detected {}. Run {} to save them to .pigs/settings.json",
+                "💡".cyan(),
+                detected.summary(),
+                "pigs init".cyan()
+            );
+        }
+    }
 
     // Ask if user wants to open the worktree (skip in quiet mode)
     if !quiet {
@@ -339,7 +906,176 @@ pub fn handle_create_in_dir_quiet(
         }
     }
 
-    Ok(worktree_name)
+    Ok(info)
+}
+
+/// Run the `post_create` hooks in a new worktree. The worktree is already
+/// recorded, so it is kept when a hook fails.
+pub(super) fn run_post_create(
+    info: &WorktreeInfo,
+    repo_hooks: &HookConfig,
+    quiet: bool,
+) -> Result<()> {
+    hooks::run(Hook::PostCreate, info, &info.path, repo_hooks, quiet).with_context(|| {
+        format!(
+            "Worktree '{}' was created, but its setup is incomplete",
+            info.name
+        )
+    })
+}
+
+/// Move a claimed pooled worktree to `worktree_dir` and bring it up to date
+/// with its base branch, which may have moved since the pool was filled.
+fn claim_pooled<F: Fn(&[&str]) -> Result<String>>(
+    rollback: &mut CreateRollback<'_, F>,
+    pooled: &PooledWorktree,
+    worktree_dir: &str,
+) -> Result<()> {
+    let exec_git = rollback.exec_git;
+    let pooled_path = pooled.path.to_string_lossy();
+    exec_git(&["worktree", "move", &pooled_path, worktree_dir])
+        .context("Failed to move the pooled worktree")?;
+    rollback.worktree_dir = Some(worktree_dir.to_string());
+    exec_git(&[
+        "-C",
+        worktree_dir,
+        "merge",
+        "--ff-only",
+        "--quiet",
+        &pooled.base_branch,
+    ])
+    .context("Failed to fast-forward the pooled worktree")?;
+    // From here on it is a worktree of its own, removed if the create fails
+    rollback.pooled = None;
+    Ok(())
+}
+
+/// Removes what a failed create left behind, so a worktree only exists once
+/// it is recorded in state.
+struct CreateRollback<'a, F: Fn(&[&str]) -> Result<String>> {
+    exec_git: &'a F,
+    /// Set once the branch has been created by this run
+    branch: Option<String>,
+    /// Set once `git worktree add` has succeeded
+    worktree_dir: Option<String>,
+    /// Set while a claimed pooled worktree can still go back to the pool,
+    /// moved back and with its placeholder branch name restored
+    pooled: Option<PooledWorktree>,
+}
+
+impl<F: Fn(&[&str]) -> Result<String>> CreateRollback<'_, F> {
+    fn disarm(&mut self) {
+        self.branch = None;
+        self.worktree_dir = None;
+        self.pooled = None;
+    }
+
+    fn undo(&mut self) {
+        if let Some(pooled) = self.pooled.take() {
+            self.return_to_pool(pooled);
+        }
+        if let Some(dir) = self.worktree_dir.take()
+            && let Err(e) = (self.exec_git)(&["worktree", "remove", "--force", &dir])
+        {
+            eprintln!(
+                "{} Failed to remove worktree {} after a failed create: {}",
+                "⚠️".yellow(),
+                dir,
+                e
+            );
+        }
+        if let Some(branch) = self.branch.take()
+            && let Err(e) = (self.exec_git)(&["branch", "-D", &branch])
+        {
+            eprintln!(
+                "{} Failed to delete branch {} after a failed create: {}",
+                "⚠️".yellow(),
+                branch,
+                e
+            );
+        }
+    }
+
+    fn return_to_pool(&mut self, pooled: PooledWorktree) {
+        let pooled_path = pooled.path.to_string_lossy().to_string();
+        let restored = self
+            .worktree_dir
+            .take()
+            .map_or(Ok(String::new()), |dir| {
+                (self.exec_git)(&["worktree", "move", &dir, &pooled_path])
+            })
+            .and_then(|_| match self.branch.take() {
+                Some(branch) => (self.exec_git)(&["branch", "-m", &branch, &pooled.branch]),
+                None => Ok(String::new()),
+            })
+            .and_then(|_| pool::unclaim(pooled));
+        if let Err(e) = restored {
+            eprintln!(
+                "{} Failed to return worktree {} to the pool after a failed create: {}",
+                "⚠️".yellow(),
+                pooled_path,
+                e
+            );
+        }
+    }
+}
+
+impl<F: Fn(&[&str]) -> Result<String>> Drop for CreateRollback<'_, F> {
+    fn drop(&mut self) {
+        crate::faults::suspended(|| self.undo());
+    }
+}
+
+/// Look up the `--stack-on` parent among the repo's pigs worktrees.
+fn resolve_stack_parent(parent: &str, repo_name: &str) -> Result<WorktreeInfo> {
+    let state = PigsState::load()?;
+    let sanitized = sanitize_branch_name(parent);
+    state
+        .worktrees
+        .values()
+        .find(|info| info.repo_name == repo_name && (info.name == parent || info.name == sanitized))
+        .cloned()
+        .with_context(|| {
+            format!("Cannot stack on '{parent}': no pigs worktree with that name in '{repo_name}'.")
+        })
+}
+
+/// Make sure the `--track` target `<remote>/<branch>` exists as a
+/// remote-tracking branch, fetching it from the remote if it isn't known yet.
+fn resolve_track_target(
+    track: &str,
+    quiet: bool,
+    exec_git: &impl Fn(&[&str]) -> Result<String>,
+) -> Result<String> {
+    let Some((remote, branch)) = track
+        .split_once('/')
+        .filter(|(r, b)| !r.is_empty() && !b.is_empty())
+    else {
+        anyhow::bail!(
+            "Invalid --track '{track}'; expected <remote>/<branch>, e.g. origin/feature-x"
+        );
+    };
+    let remote_ref = format!("refs/remotes/{track}");
+    if exec_git(&["show-ref", "--verify", &remote_ref]).is_ok() {
+        return Ok(track.to_string());
+    }
+    if exec_git(&["remote", "get-url", remote]).is_err() {
+        anyhow::bail!("Cannot track '{track}': no remote named '{remote}'");
+    }
+    if crate::network::is_offline() {
+        anyhow::bail!("Cannot track '{track}': it isn't fetched yet and pigs is offline");
+    }
+    if !quiet {
+        println!(
+            "{} Fetching '{}' from {}...",
+            "🌐".blue(),
+            branch.cyan(),
+            remote
+        );
+    }
+    exec_git(&["fetch", remote, &format!("{branch}:{remote_ref}")])
+        .with_context(|| format!("Failed to fetch '{branch}' from {remote}"))?;
+    Ok(track.to_string())
 }
 
 /// Resolve a `--from` target to a branch name.
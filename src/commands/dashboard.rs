@@ -1,7 +1,113 @@
-use anyhow::Result;
+use std::path::PathBuf;
 
-use crate::dashboard;
+use anyhow::{Result, bail};
+use chrono::Utc;
+use colored::Colorize;
+use uuid::Uuid;
 
-pub fn handle_dashboard(addr: Option<String>, no_browser: bool) -> Result<()> {
-    dashboard::run_dashboard(addr, !no_browser)
+use crate::dashboard::{self, LogOptions};
+use crate::state::{DashboardToken, PigsState, TokenScope};
+
+/// How much of a token `list` and `revoke` show.
+const TOKEN_PREFIX_LEN: usize = 8;
+
+pub fn handle_dashboard(
+    addr: Option<String>,
+    no_browser: bool,
+    notify: bool,
+    assets_dir: Option<PathBuf>,
+    log_level: String,
+    log_json: bool,
+) -> Result<()> {
+    let logging = LogOptions {
+        level: log_level,
+        json: log_json,
+    };
+    dashboard::run_dashboard(addr, !no_browser, notify, assets_dir, logging)
+}
+
+/// Mint a dashboard token with `scope` and print it. It is only shown once.
+pub fn handle_token_create(scope: TokenScope, user: Option<String>) -> Result<()> {
+    let mut state = PigsState::load()?;
+    let token = Uuid::new_v4().simple().to_string();
+    state.dashboard_tokens.insert(
+        token.clone(),
+        DashboardToken {
+            scope,
+            user: user.filter(|user| !user.trim().is_empty()),
+            created_at: Utc::now(),
+        },
+    );
+    state.save()?;
+
+    println!(
+        "{} Created a {} dashboard token:",
+        "🔑".green(),
+        scope.as_str().cyan()
+    );
+    println!("{token}");
+    println!(
+        "{} Send it as `Authorization: Bearer <token>`, or open the dashboard at /?token=<token>. Every API request now needs a token.",
+        "💡".yellow()
+    );
+    Ok(())
+}
+
+pub fn handle_token_list() -> Result<()> {
+    let state = PigsState::load()?;
+    if state.dashboard_tokens.is_empty() {
+        println!("No dashboard tokens. Create one with 'pigs dashboard token create'");
+        return Ok(());
+    }
+
+    let mut tokens: Vec<_> = state.dashboard_tokens.iter().collect();
+    tokens.sort_by_key(|(_, token)| token.created_at);
+    for (token, info) in tokens {
+        let user = info
+            .user
+            .as_deref()
+            .map(|user| format!(" as {user}"))
+            .unwrap_or_default();
+        println!(
+            "{}…  {:<8} created {}{user}",
+            &token[..token.len().min(TOKEN_PREFIX_LEN)],
+            info.scope.as_str(),
+            info.created_at.format("%Y-%m-%d %H:%M UTC")
+        );
+    }
+    Ok(())
+}
+
+/// Revoke the token equal to `token` or the only one starting with it.
+pub fn handle_token_revoke(token: String) -> Result<()> {
+    let mut state = PigsState::load()?;
+    let token = token.trim();
+    let matches: Vec<String> = if state.dashboard_tokens.contains_key(token) {
+        vec![token.to_string()]
+    } else {
+        state
+            .dashboard_tokens
+            .keys()
+            .filter(|key| !token.is_empty() && key.starts_with(token))
+            .cloned()
+            .collect()
+    };
+    let key = match matches.as_slice() {
+        [key] => key.clone(),
+        [] => bail!("No dashboard token matches '{token}'"),
+        _ => bail!("'{token}' matches several tokens; give more of it"),
+    };
+
+    let revoked = state.dashboard_tokens.remove(&key);
+    state.save()?;
+    println!(
+        "{} Revoked {} token {}…",
+        "🗑️".red(),
+        revoked.map_or("dashboard", |info| info.scope.as_str()),
+        &key[..key.len().min(TOKEN_PREFIX_LEN)]
+    );
+    if state.dashboard_tokens.is_empty() && state.dashboard_users.is_empty() {
+        println!("No scoped tokens left, so the dashboard API no longer asks for one.");
+    }
+    Ok(())
 }
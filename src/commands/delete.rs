@@ -1,10 +1,72 @@
 use anyhow::{Context, Result};
 use colored::Colorize;
 
-use crate::git::{execute_git, has_unpushed_commits, is_working_tree_clean};
-use crate::input::{get_command_arg, smart_confirm};
-use crate::state::{PigsState, WorktreeInfo};
+use super::archive::archive_worktree;
+use crate::artifacts;
+use crate::git::{execute_git, has_unpushed_commits, is_working_tree_clean, uncommitted_patch};
+use crate::hooks::{self, Hook};
+use crate::input::{get_command_arg, smart_confirm, smart_multi_select};
+use crate::names;
+use crate::network;
+use crate::repo_lock::lock_repo;
+use crate::state::{HookConfig, PigsState, RepoConfig, WorktreeInfo, get_config_dir};
 use crate::utils::execute_in_dir;
+use crate::webhooks::{self, WebhookEvent};
+
+/// What to do with uncommitted changes (staged, unstaged, or untracked) in a
+/// worktree being deleted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DirtyChanges {
+    /// Refuse to delete the worktree
+    Refuse,
+    /// Delete it anyway (`--force`)
+    Discard,
+    /// Save them to a named git stash first (`--stash`)
+    Stash,
+    /// Save them to a patch file under `~/.pigs/patches` first (`--patch`)
+    Patch,
+    /// Archive the whole worktree, changes included, first (`--archive`)
+    Archive,
+}
+
+impl DirtyChanges {
+    pub fn from_flags(force: bool, stash: bool, patch: bool, archive: bool) -> Self {
+        if archive {
+            Self::Archive
+        } else if stash {
+            Self::Stash
+        } else if patch {
+            Self::Patch
+        } else if force {
+            Self::Discard
+        } else {
+            Self::Refuse
+        }
+    }
+}
+
+/// What to do with the worktree's branch on its remote (`--remote`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RemoteBranch {
+    /// Leave it alone
+    Keep,
+    /// Delete it if the branch is merged (`--remote`)
+    DeleteMerged,
+    /// Delete it even if unmerged (`--remote-force`)
+    Delete,
+}
+
+impl RemoteBranch {
+    pub fn from_flags(remote: bool, remote_force: bool) -> Self {
+        if remote_force {
+            Self::Delete
+        } else if remote {
+            Self::DeleteMerged
+        } else {
+            Self::Keep
+        }
+    }
+}
 
 /// Represents the result of various checks performed before deletion
 struct DeletionChecks {
@@ -43,9 +105,22 @@ impl DeletionConfig {
     }
 }
 
-pub fn handle_delete(name: Option<String>, all: bool) -> Result<()> {
+pub fn handle_delete(
+    name: Option<String>,
+    all: bool,
+    repo: Option<String>,
+    select: bool,
+    dirty: DirtyChanges,
+    remote: RemoteBranch,
+) -> Result<()> {
+    if select {
+        return handle_delete_selected(repo, dirty);
+    }
+    if repo.is_some() && !all {
+        anyhow::bail!("--repo only applies with --all or --select");
+    }
     if all {
-        return handle_delete_all();
+        return handle_delete_all(repo, dirty);
     }
 
     let mut state = PigsState::load()?;
@@ -62,6 +137,7 @@ pub fn handle_delete(name: Option<String>, all: bool) -> Result<()> {
     );
 
     // Handle case where worktree directory doesn't exist
+    let mut branch_merged = None;
     if !config.worktree_exists {
         if !handle_missing_worktree(&worktree_info, &config)? {
             println!("{} Cancelled", "❌".red());
@@ -76,7 +152,26 @@ pub fn handle_delete(name: Option<String>, all: bool) -> Result<()> {
         );
 
         // Perform deletion checks
-        let checks = perform_deletion_checks(&worktree_info)?;
+        let mut checks = perform_deletion_checks(&worktree_info)?;
+
+        if checks.has_uncommitted_changes {
+            match dirty {
+                DirtyChanges::Refuse => {
+                    show_pending_work_warnings(&checks);
+                    anyhow::bail!(
+                        "Worktree '{}' has uncommitted changes. Use --stash or --patch to save them first, or --force to discard them",
+                        worktree_info.name
+                    );
+                }
+                DirtyChanges::Discard => {
+                    println!("{} Discarding uncommitted changes", "⚠️ ".yellow());
+                }
+                DirtyChanges::Stash | DirtyChanges::Patch | DirtyChanges::Archive => {}
+            }
+            // Handled by the flag, so only unpushed commits still need a warning
+            checks.has_uncommitted_changes = false;
+        }
+        branch_merged = Some(checks.branch_is_merged());
 
         if !confirm_deletion(&worktree_info, &checks, &config)? {
             println!("{} Cancelled", "❌".red());
@@ -84,49 +179,300 @@ pub fn handle_delete(name: Option<String>, all: bool) -> Result<()> {
         }
     }
 
+    // Decide about the remote branch before anything is removed, while the
+    // branch and its upstream configuration still exist
+    let remote_target = match remote {
+        RemoteBranch::Keep => None,
+        _ => prepare_remote_deletion(&worktree_info, remote, branch_merged)?,
+    };
+
+    let hooks = DeleteHooks::load(&worktree_info)?;
+    if config.worktree_exists {
+        if dirty == DirtyChanges::Archive {
+            archive_worktree(&worktree_info)?;
+        } else {
+            save_uncommitted_changes(&worktree_info, dirty)?;
+        }
+    }
+    hooks.pre_delete(false)?;
+
     // Execute deletion
-    perform_deletion(&worktree_info, &config)?;
+    let head = perform_deletion(&worktree_info, &config)?;
 
     // Update state
-    state.worktrees.remove(&key);
+    state.remove_worktree(&key, head);
     state.save()?;
+    artifacts::remove_all(&worktree_info);
+    webhooks::emit(WebhookEvent::new("worktree.deleted", &worktree_info));
 
     println!(
         "{} Worktree '{}' deleted successfully",
         "✅".green(),
         worktree_info.name.cyan()
     );
+    hooks.post_delete(false);
+
+    if let Some(target) = remote_target {
+        // The local work is already gone, so a failed push is only a warning
+        if let Err(e) = delete_remote_branch(&worktree_info, &target) {
+            eprintln!(
+                "{} Failed to delete '{}' from {}: {}",
+                "⚠️ ".yellow(),
+                target.branch,
+                target.remote,
+                e
+            );
+        }
+    }
     Ok(())
 }
 
-fn handle_delete_all() -> Result<()> {
-    let mut state = PigsState::load()?;
+/// A branch on a remote, as it was when deletion was decided.
+struct RemoteTarget {
+    remote: String,
+    branch: String,
+    /// The commit the branch pointed to, so the push only deletes that
+    sha: String,
+}
 
-    if state.worktrees.is_empty() {
-        println!("{} No worktrees to delete", "ℹ️ ".blue());
-        return Ok(());
+/// Check that the worktree's branch may be deleted from its remote and return
+/// where it is there, or `None` if there's nothing to delete. Fails when the
+/// branch isn't merged, or the remote branch has commits that are neither the
+/// local branch nor merged, and `remote` doesn't force it, so nothing is
+/// deleted locally either.
+fn prepare_remote_deletion(
+    worktree_info: &WorktreeInfo,
+    remote: RemoteBranch,
+    branch_merged: Option<bool>,
+) -> Result<Option<RemoteTarget>> {
+    network::ensure_online("Deleting the remote branch")?;
+    let main_repo_path = worktree_info.main_repo_path()?;
+    let repo = main_repo_path
+        .to_str()
+        .context("Repository path contains invalid UTF-8")?;
+
+    let (remote_name, remote_branch) = remote_branch_of(repo, &worktree_info.branch);
+    let listed = execute_git(&[
+        "-C",
+        repo,
+        "ls-remote",
+        "--heads",
+        &remote_name,
+        &format!("refs/heads/{remote_branch}"),
+    ])
+    .with_context(|| format!("Failed to look up '{remote_branch}' on {remote_name}"))?;
+    let Some(sha) = listed.split_whitespace().next().map(str::to_string) else {
+        println!(
+            "{} No branch '{}' on {}; nothing to delete there",
+            "ℹ️ ".blue(),
+            remote_branch,
+            remote_name
+        );
+        return Ok(None);
+    };
+
+    // Someone may have pushed to the branch since it was last fetched; only
+    // commits pigs can see are safe to throw away without --remote-force
+    let local_head = execute_git(&[
+        "-C",
+        repo,
+        "rev-parse",
+        "--verify",
+        "--quiet",
+        &format!("refs/heads/{}", worktree_info.branch),
+    ])
+    .ok();
+    let remote_merged =
+        execute_git(&["-C", repo, "merge-base", "--is-ancestor", &sha, "HEAD"]).is_ok();
+    if remote != RemoteBranch::Delete
+        && !remote_merged
+        && local_head.as_deref() != Some(sha.as_str())
+    {
+        anyhow::bail!(
+            "'{}' on {} has commits that aren't in the local branch '{}' or merged, so it won't be deleted. Use --remote-force to delete it anyway",
+            remote_branch,
+            remote_name,
+            worktree_info.branch
+        );
+    }
+
+    if remote == RemoteBranch::DeleteMerged && !remote_merged {
+        let merged = match branch_merged {
+            Some(merged) => merged,
+            None => {
+                let (via_git, via_pr) =
+                    check_branch_merge_status(&main_repo_path, &worktree_info.branch)?;
+                via_git || via_pr
+            }
+        };
+        if !merged {
+            anyhow::bail!(
+                "Branch '{}' is not merged, so it won't be deleted from {}. Use --remote-force to delete it anyway",
+                worktree_info.branch,
+                remote_name
+            );
+        }
     }
 
-    // Collect and display all worktrees
-    let entries: Vec<(String, WorktreeInfo)> = state
+    Ok(Some(RemoteTarget {
+        remote: remote_name,
+        branch: remote_branch,
+        sha,
+    }))
+}
+
+/// The remote and branch name `branch` tracks, or `origin` and the same name
+/// when it has no upstream.
+fn remote_branch_of(repo: &str, branch: &str) -> (String, String) {
+    let config = |key: &str| {
+        execute_git(&[
+            "-C",
+            repo,
+            "config",
+            "--get",
+            &format!("branch.{branch}.{key}"),
+        ])
+        .ok()
+        .filter(|value| !value.is_empty())
+    };
+    let remote = config("remote")
+        .filter(|remote| remote != ".")
+        .unwrap_or_else(|| "origin".to_string());
+    let merge = config("merge")
+        .and_then(|merge| merge.strip_prefix("refs/heads/").map(str::to_string))
+        .unwrap_or_else(|| branch.to_string());
+    (remote, merge)
+}
+
+/// Push the deletion of `target`, leased on the commit it was checked at so
+/// anything pushed since is kept.
+fn delete_remote_branch(worktree_info: &WorktreeInfo, target: &RemoteTarget) -> Result<()> {
+    println!(
+        "{} Deleting branch '{}' from {}...",
+        "🗑️ ".yellow(),
+        target.branch,
+        target.remote
+    );
+    let main_repo_path = worktree_info.main_repo_path()?;
+    let repo = main_repo_path
+        .to_str()
+        .context("Repository path contains invalid UTF-8")?;
+    execute_git(&[
+        "-C",
+        repo,
+        "push",
+        &format!(
+            "--force-with-lease=refs/heads/{}:{}",
+            target.branch, target.sha
+        ),
+        &target.remote,
+        "--delete",
+        &target.branch,
+    ])?;
+    println!("{} Remote branch deleted", "✅".green());
+    Ok(())
+}
+
+/// Managed worktrees, optionally limited to one repository, sorted by key.
+fn deletion_candidates(state: &PigsState, repo: Option<&str>) -> Vec<(String, WorktreeInfo)> {
+    let mut entries: Vec<(String, WorktreeInfo)> = state
         .worktrees
         .iter()
+        .filter(|(_, info)| repo.is_none_or(|repo| info.repo_name == repo))
         .map(|(k, v)| (k.clone(), v.clone()))
         .collect();
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
+    entries
+}
+
+fn handle_delete_all(repo: Option<String>, dirty: DirtyChanges) -> Result<()> {
+    let state = PigsState::load()?;
+    let entries = deletion_candidates(&state, repo.as_deref());
+
+    if entries.is_empty() {
+        match repo {
+            Some(repo) => println!("{} No worktrees to delete in '{}'", "ℹ️ ".blue(), repo),
+            None => println!("{} No worktrees to delete", "ℹ️ ".blue()),
+        }
+        return Ok(());
+    }
+
+    delete_entries(state, entries, dirty)
+}
+
+/// Pick worktrees to delete from a checkbox list, then delete them after a
+/// single confirmation.
+fn handle_delete_selected(repo: Option<String>, dirty: DirtyChanges) -> Result<()> {
+    let state = PigsState::load()?;
+    let candidates = deletion_candidates(&state, repo.as_deref());
+
+    if candidates.is_empty() {
+        println!("{} No worktrees to delete", "ℹ️ ".blue());
+        return Ok(());
+    }
+
+    let Some(selection) =
+        smart_multi_select("Select worktrees to delete", &candidates, |(key, _)| {
+            key.clone()
+        })?
+    else {
+        anyhow::bail!(
+            "Interactive selection not available in non-interactive mode. Pipe the worktrees to delete, or use --all."
+        );
+    };
+    if selection.is_empty() {
+        println!("{} Nothing selected", "ℹ️ ".blue());
+        return Ok(());
+    }
+
+    let entries = selection
+        .into_iter()
+        .map(|index| candidates[index].clone())
+        .collect();
+    delete_entries(state, entries, dirty)
+}
 
+/// List `entries`, confirm once, and delete each one, carrying on past failures.
+fn delete_entries(
+    mut state: PigsState,
+    entries: Vec<(String, WorktreeInfo)>,
+    dirty: DirtyChanges,
+) -> Result<()> {
     println!(
         "{} The following {} worktrees will be deleted:",
         "⚠️ ".yellow(),
         entries.len()
     );
+    let mut any_dirty = false;
     for (_, info) in &entries {
-        println!("  - {} ({})", info.name.cyan(), info.path.display());
+        let has_changes = has_uncommitted_changes(info);
+        any_dirty |= has_changes;
+        println!(
+            "  - {} ({}){}",
+            info.name.cyan(),
+            info.path.display(),
+            if has_changes {
+                " — uncommitted changes".red().to_string()
+            } else {
+                String::new()
+            }
+        );
+    }
+    if any_dirty {
+        let plan = match dirty {
+            DirtyChanges::Refuse => "will be skipped (use --stash, --patch, or --force)",
+            DirtyChanges::Discard => "will be discarded",
+            DirtyChanges::Stash => "will be stashed first",
+            DirtyChanges::Patch => "will be saved as patches first",
+            DirtyChanges::Archive => "will be archived first",
+        };
+        println!("  {} Uncommitted changes {}", "ℹ️".blue(), plan);
     }
     println!();
 
     if !smart_confirm(
         &format!(
-            "Delete all {} worktrees? This cannot be undone.",
+            "Delete these {} worktrees? This cannot be undone.",
             entries.len()
         ),
         false,
@@ -145,6 +491,40 @@ fn handle_delete_all() -> Result<()> {
             worktree_info.name.cyan()
         );
 
+        if dirty == DirtyChanges::Archive && worktree_info.path.exists() {
+            if let Err(e) = archive_worktree(worktree_info) {
+                eprintln!(
+                    "{} Skipping '{}': failed to archive: {}",
+                    "❌".red(),
+                    worktree_info.name,
+                    e
+                );
+                continue;
+            }
+        } else if has_uncommitted_changes(worktree_info) {
+            let saved = match dirty {
+                DirtyChanges::Refuse => Err(anyhow::anyhow!(
+                    "it has uncommitted changes (use --stash, --patch, or --force)"
+                )),
+                _ => save_uncommitted_changes(worktree_info, dirty),
+            };
+            if let Err(e) = saved {
+                eprintln!("{} Skipping '{}': {}", "❌".red(), worktree_info.name, e);
+                continue;
+            }
+        }
+
+        // After the checks above, so a worktree that is skipped isn't torn down
+        let hooks = match DeleteHooks::load(worktree_info)
+            .and_then(|hooks| hooks.pre_delete(false).map(|()| hooks))
+        {
+            Ok(hooks) => hooks,
+            Err(e) => {
+                eprintln!("{} Skipping '{}': {:#}", "❌".red(), worktree_info.name, e);
+                continue;
+            }
+        };
+
         let config = match DeletionConfig::from_env(worktree_info) {
             Ok(c) => c,
             Err(e) => {
@@ -158,29 +538,38 @@ fn handle_delete_all() -> Result<()> {
             }
         };
 
-        if let Err(e) = perform_deletion(worktree_info, &config) {
-            eprintln!(
-                "{} Failed to delete '{}': {}",
-                "❌".red(),
-                worktree_info.name,
-                e
-            );
-            continue;
-        }
+        let head = match perform_deletion(worktree_info, &config) {
+            Ok(head) => head,
+            Err(e) => {
+                eprintln!(
+                    "{} Failed to delete '{}': {}",
+                    "❌".red(),
+                    worktree_info.name,
+                    e
+                );
+                continue;
+            }
+        };
 
-        deleted_keys.push(key.clone());
+        deleted_keys.push((key.clone(), head));
         println!(
             "{} Worktree '{}' deleted successfully",
             "✅".green(),
             worktree_info.name.cyan()
         );
+        hooks.post_delete(false);
     }
 
     // Remove all successfully deleted entries from state
-    for key in &deleted_keys {
-        state.worktrees.remove(key);
+    let mut deleted = Vec::new();
+    for (key, head) in &deleted_keys {
+        deleted.extend(state.remove_worktree(key, head.clone()));
     }
     state.save()?;
+    for info in &deleted {
+        artifacts::remove_all(info);
+        webhooks::emit(WebhookEvent::new("worktree.deleted", info));
+    }
 
     println!();
     println!(
@@ -192,19 +581,79 @@ fn handle_delete_all() -> Result<()> {
     Ok(())
 }
 
+/// Whether the worktree directory has staged, unstaged, or untracked changes.
+fn has_uncommitted_changes(worktree_info: &WorktreeInfo) -> bool {
+    worktree_info.path.exists()
+        && worktree_info.path.to_str().is_some_and(|path| {
+            execute_git(&["-C", path, "status", "--porcelain"])
+                .is_ok_and(|status| !status.is_empty())
+        })
+}
+
+/// Save the worktree's uncommitted changes before it is removed, as `dirty`
+/// asks. Stashes live in the shared repository, so they outlast the worktree.
+fn save_uncommitted_changes(worktree_info: &WorktreeInfo, dirty: DirtyChanges) -> Result<()> {
+    if !matches!(dirty, DirtyChanges::Stash | DirtyChanges::Patch)
+        || !has_uncommitted_changes(worktree_info)
+    {
+        return Ok(());
+    }
+    let path = worktree_info
+        .path
+        .to_str()
+        .context("Worktree path contains invalid UTF-8")?;
+    let label = format!(
+        "pigs delete {}/{} ({})",
+        worktree_info.repo_name, worktree_info.name, worktree_info.branch
+    );
+
+    if dirty == DirtyChanges::Stash {
+        execute_git(&[
+            "-C",
+            path,
+            "stash",
+            "push",
+            "--include-untracked",
+            "-m",
+            &label,
+        ])
+        .context("Failed to stash uncommitted changes")?;
+        println!(
+            "{} Stashed uncommitted changes as '{}' (see {})",
+            "📦".green(),
+            label,
+            "git stash list".cyan()
+        );
+        return Ok(());
+    }
+
+    let patch = uncommitted_patch(&worktree_info.path).context("Failed to create patch")?;
+    let dir = get_config_dir()?
+        .join("patches")
+        .join(&worktree_info.repo_name);
+    std::fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    let file = dir.join(format!(
+        "{}-{}.patch",
+        worktree_info.name,
+        chrono::Local::now().format("%Y%m%d-%H%M%S")
+    ));
+    std::fs::write(&file, patch).with_context(|| format!("Failed to write {}", file.display()))?;
+    println!(
+        "{} Saved uncommitted changes to {} (restore with {})",
+        "📦".green(),
+        file.display(),
+        "git apply".cyan()
+    );
+    Ok(())
+}
+
 /// Find the worktree to delete based on the provided name or current directory
 fn find_worktree_to_delete(
     state: &PigsState,
     name: Option<String>,
 ) -> Result<(String, WorktreeInfo)> {
     if let Some(n) = name {
-        // Find worktree by name across all projects
-        state
-            .worktrees
-            .iter()
-            .find(|(_, w)| w.name == n)
-            .map(|(k, w)| (k.clone(), w.clone()))
-            .context(format!("Worktree '{n}' not found"))
+        names::find_worktree_exactly(state, &n)
     } else {
         // Find worktree by current directory
         find_current_worktree(state)
@@ -249,7 +698,7 @@ fn perform_deletion_checks(worktree_info: &WorktreeInfo) -> Result<DeletionCheck
         let has_unpushed_commits = has_unpushed_commits();
 
         // Check branch merge status in main repo
-        let main_repo_path = get_main_repo_path(worktree_info)?;
+        let main_repo_path = worktree_info.main_repo_path()?;
         let (branch_merged_via_git, branch_merged_via_pr) =
             check_branch_merge_status(&main_repo_path, &worktree_info.branch)?;
 
@@ -274,10 +723,7 @@ fn check_branch_merge_status(
             .output()
             .context("Failed to check merged branches")?;
 
-        let merged_branches = String::from_utf8_lossy(&output.stdout);
-        let is_merged_git = merged_branches
-            .lines()
-            .any(|line| line.trim().trim_start_matches('*').trim() == branch);
+        let is_merged_git = lists_branch(&String::from_utf8_lossy(&output.stdout), branch);
 
         // Check if merged via PR (works for squash merge)
         let is_merged_pr = check_branch_merged_via_pr(branch);
@@ -286,8 +732,22 @@ fn check_branch_merge_status(
     })
 }
 
+/// Whether `git branch` output lists `branch`. Besides `*` for the current
+/// branch, git marks branches checked out in another worktree with `+`. Every
+/// branch pigs is deleting is checked out in its worktree, so without
+/// stripping that marker no branch would ever look merged.
+fn lists_branch(output: &str, branch: &str) -> bool {
+    output
+        .lines()
+        .any(|line| line.trim().trim_start_matches(['*', '+']).trim() == branch)
+}
+
 /// Check if branch was merged via GitHub PR
 fn check_branch_merged_via_pr(branch: &str) -> bool {
+    if network::is_offline() {
+        return false;
+    }
+
     std::process::Command::new("gh")
         .args([
             "pr", "list", "--state", "merged", "--head", branch, "--json", "number",
@@ -347,8 +807,12 @@ fn show_unmerged_branch_warning(worktree_info: &WorktreeInfo) {
 }
 
 /// Perform the actual deletion of worktree and branch
-fn perform_deletion(worktree_info: &WorktreeInfo, config: &DeletionConfig) -> Result<()> {
-    let main_repo_path = get_main_repo_path(worktree_info)?;
+/// Remove the worktree and its branch, returning the commit the branch was at.
+fn perform_deletion(
+    worktree_info: &WorktreeInfo,
+    config: &DeletionConfig,
+) -> Result<Option<String>> {
+    let main_repo_path = worktree_info.main_repo_path()?;
 
     // Change to main repo if we're deleting current directory
     if config.is_current_directory {
@@ -357,16 +821,37 @@ fn perform_deletion(worktree_info: &WorktreeInfo, config: &DeletionConfig) -> Re
     }
 
     execute_in_dir(&main_repo_path, || {
+        let head = branch_head(worktree_info, None);
+
         // Remove or prune worktree
         remove_worktree(worktree_info, config)?;
 
-        // Delete branch
-        delete_branch(worktree_info, config)?;
+        // The worktree is gone, so a branch that can't be deleted must not keep
+        // its entry in state
+        if let Err(e) = delete_branch(worktree_info, config) {
+            eprintln!(
+                "{} Failed to delete branch '{}': {}",
+                "⚠️ ".yellow(),
+                worktree_info.branch,
+                e
+            );
+        }
 
-        Ok(())
+        Ok(head)
     })
 }
 
+/// The commit a worktree's branch points at, looked up in `repo` (default: the
+/// current directory).
+pub fn branch_head(worktree_info: &WorktreeInfo, repo: Option<&str>) -> Option<String> {
+    let reference = format!("refs/heads/{}", worktree_info.branch);
+    let mut args = vec!["rev-parse", "--verify", "--quiet", reference.as_str()];
+    if let Some(repo) = repo {
+        args.splice(0..0, ["-C", repo]);
+    }
+    execute_git(&args).ok()
+}
+
 /// Remove the worktree from git
 fn remove_worktree(worktree_info: &WorktreeInfo, config: &DeletionConfig) -> Result<()> {
     if config.worktree_exists {
@@ -429,12 +914,122 @@ fn delete_branch(worktree_info: &WorktreeInfo, config: &DeletionConfig) -> Resul
     Ok(())
 }
 
-/// Get the path to the main repository from worktree info
-fn get_main_repo_path(worktree_info: &WorktreeInfo) -> Result<std::path::PathBuf> {
-    let parent = worktree_info
+/// Delete a worktree without prompting or printing, for the dashboard. Refuses
+/// to discard uncommitted changes unless `force` is set, and only deletes the
+/// branch when it is fully merged. Returns whether the branch was deleted.
+pub fn delete_worktree_unattended(worktree_info: &WorktreeInfo, force: bool) -> Result<bool> {
+    let main_repo_path = worktree_info.main_repo_path()?;
+    let repo = main_repo_path
+        .to_str()
+        .context("Repository path contains invalid UTF-8")?;
+    let path = worktree_info
         .path
-        .parent()
-        .context("Failed to get parent directory")?;
+        .to_str()
+        .context("Worktree path contains invalid UTF-8")?;
+
+    let _guard = lock_repo(
+        &worktree_info.repo_name,
+        &format!("delete worktree '{}'", worktree_info.name),
+    )?;
+    let hooks = DeleteHooks::load(worktree_info)?;
+    if worktree_info.path.exists() {
+        if !force && !execute_git(&["-C", path, "status", "--porcelain"])?.is_empty() {
+            anyhow::bail!("Worktree '{}' has uncommitted changes", worktree_info.name);
+        }
+        hooks.pre_delete(true)?;
+        let mut args = vec!["-C", repo, "worktree", "remove"];
+        if force {
+            args.push("--force");
+        }
+        args.push(path);
+        execute_git(&args).context("Failed to remove worktree")?;
+    } else {
+        hooks.pre_delete(true)?;
+        execute_git(&["-C", repo, "worktree", "prune"]).context("Failed to prune worktree")?;
+    }
+
+    let head = branch_head(worktree_info, Some(repo));
+    let branch_deleted = execute_git(&["-C", repo, "branch", "-d", &worktree_info.branch]).is_ok();
+
+    let mut state = PigsState::load()?;
+    state.remove_worktree(
+        &PigsState::make_key(&worktree_info.repo_name, &worktree_info.name),
+        head,
+    );
+    state.save()?;
+    artifacts::remove_all(worktree_info);
+    webhooks::emit(WebhookEvent::new("worktree.deleted", worktree_info));
+    hooks.post_delete(true);
 
-    Ok(parent.join(&worktree_info.repo_name))
+    Ok(branch_deleted)
+}
+
+/// A worktree's `pre_delete` and `post_delete` hooks, read before it is
+/// removed: from its own settings, or the main repository's once the
+/// directory is gone.
+struct DeleteHooks {
+    worktree: WorktreeInfo,
+    main_repo: std::path::PathBuf,
+    config: HookConfig,
+}
+
+impl DeleteHooks {
+    fn load(worktree_info: &WorktreeInfo) -> Result<Self> {
+        let main_repo = worktree_info.main_repo_path()?;
+        let config = if worktree_info.path.exists() {
+            RepoConfig::load(&worktree_info.path)?.hooks
+        } else {
+            RepoConfig::load(&main_repo)?.hooks
+        };
+        Ok(Self {
+            worktree: worktree_info.clone(),
+            main_repo,
+            config,
+        })
+    }
+
+    /// Run the `pre_delete` hooks in the worktree (the main repository if it
+    /// is already gone). A failure means the worktree must be kept.
+    fn pre_delete(&self, quiet: bool) -> Result<()> {
+        let dir = if self.worktree.path.exists() {
+            &self.worktree.path
+        } else {
+            &self.main_repo
+        };
+        hooks::run(Hook::PreDelete, &self.worktree, dir, &self.config, quiet).with_context(|| {
+            format!(
+                "Not deleting worktree '{}'; its pre_delete hooks didn't finish",
+                self.worktree.name
+            )
+        })
+    }
+
+    /// Run the `post_delete` hooks in the main repository. The worktree is
+    /// already gone, so failures are only reported.
+    fn post_delete(&self, quiet: bool) {
+        if let Err(e) = hooks::run(
+            Hook::PostDelete,
+            &self.worktree,
+            &self.main_repo,
+            &self.config,
+            quiet,
+        ) {
+            eprintln!("{} {:#}", "⚠️ ".yellow(), e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merged_branch_list_ignores_current_and_worktree_markers() {
+        let output = "* main\n+ feature\n  old-fix\n+ feature-2\n";
+        assert!(lists_branch(output, "main"));
+        assert!(lists_branch(output, "feature"));
+        assert!(lists_branch(output, "old-fix"));
+        assert!(!lists_branch(output, "feature-3"));
+        assert!(!lists_branch(output, "+ feature"));
+    }
 }
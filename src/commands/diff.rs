@@ -0,0 +1,108 @@
+use std::process::Command;
+
+use anyhow::{Context, Result, bail};
+
+use crate::git::{execute_git, resolve_base_ref, resolve_session_ref, snapshot_diff_revisions};
+use crate::input::get_command_arg;
+use crate::names::resolve_worktree;
+use crate::state::PigsState;
+
+pub struct DiffOptions {
+    pub base: bool,
+    pub staged: bool,
+    pub since_session: Option<String>,
+    pub stat: bool,
+    pub no_pager: bool,
+}
+
+pub fn handle_diff(name: Option<String>, options: DiffOptions) -> Result<()> {
+    let state = PigsState::load()?;
+
+    if state.worktrees.is_empty() {
+        bail!("No worktrees found. Create one first with 'pigs create'");
+    }
+
+    let worktree = resolve_worktree(&state, get_command_arg(name)?)?;
+    if !worktree.path.exists() {
+        bail!(
+            "Worktree directory {} no longer exists. Run 'pigs clean' to prune it.",
+            worktree.path.display()
+        );
+    }
+    let path = worktree.path.to_str().context("Invalid worktree path")?;
+
+    let mut diff_args: Vec<String> = vec!["diff".to_string()];
+    if std::env::var("NO_COLOR").is_ok() {
+        diff_args.push("--no-color".to_string());
+    }
+    if options.stat {
+        diff_args.push("--stat".to_string());
+    }
+
+    if let Some(session) = options.since_session.as_deref() {
+        diff_args.push(resolve_session_ref(path, session)?);
+    } else if options.base {
+        let base_ref = resolve_base_ref(&worktree.path).context(
+            "Could not determine a base branch (tried origin/HEAD, main, master, develop)",
+        )?;
+        let merge_base = execute_git(&["-C", path, "merge-base", &base_ref, "HEAD"])
+            .with_context(|| format!("Failed to find merge-base with {base_ref}"))?;
+        diff_args.push(merge_base);
+    } else if options.staged {
+        diff_args.push("--cached".to_string());
+    } else {
+        diff_args.push("HEAD".to_string());
+    }
+
+    run_git_diff(path, &diff_args, options.no_pager)
+}
+
+/// Show what changed in a worktree between two session snapshots, so a long
+/// session can be reviewed stage by stage. Either checkpoint can be `now`.
+pub fn handle_diff_snapshots(
+    name: String,
+    from: String,
+    to: String,
+    stat: bool,
+    no_pager: bool,
+) -> Result<()> {
+    let state = PigsState::load()?;
+    let worktree = resolve_worktree(&state, Some(name))?;
+    if !worktree.path.exists() {
+        bail!(
+            "Worktree directory {} no longer exists. Run 'pigs clean' to prune it.",
+            worktree.path.display()
+        );
+    }
+    let path = worktree.path.to_str().context("Invalid worktree path")?;
+
+    let mut diff_args: Vec<String> = vec!["diff".to_string()];
+    if std::env::var("NO_COLOR").is_ok() {
+        diff_args.push("--no-color".to_string());
+    }
+    if stat {
+        diff_args.push("--stat".to_string());
+    }
+    diff_args.extend(snapshot_diff_revisions(path, &from, &to)?);
+
+    run_git_diff(path, &diff_args, no_pager)
+}
+
+fn run_git_diff(path: &str, diff_args: &[String], no_pager: bool) -> Result<()> {
+    // Let git render the diff directly so its pager and color settings apply
+    let mut command = Command::new("git");
+    command.arg("-C").arg(path);
+    if no_pager {
+        command.arg("--no-pager");
+    }
+    let status = command
+        .args(diff_args)
+        .status()
+        .context("Failed to execute git diff")?;
+
+    if !status.success() {
+        bail!("git diff exited with {}", status.code().unwrap_or(-1));
+    }
+
+    Ok(())
+}
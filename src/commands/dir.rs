@@ -1,9 +1,23 @@
-use anyhow::{Context, Result};
+use anyhow::Result;
 
+use super::focus::focused_worktree;
 use crate::input::{get_command_arg, smart_select};
+use crate::names;
 use crate::state::{PigsState, WorktreeInfo};
 
 pub fn handle_dir(name: Option<String>) -> Result<()> {
+    let worktree_info = select_worktree(name)?;
+
+    // Output only the path - no decorations, no colors
+    // This makes it easy to use in shell commands: cd $(pigs dir name)
+    println!("{}", worktree_info.path.display());
+
+    Ok(())
+}
+
+/// The worktree named on the command line or piped in, else the focused one,
+/// else one picked interactively.
+pub fn select_worktree(name: Option<String>) -> Result<WorktreeInfo> {
     let state = PigsState::load()?;
 
     if state.worktrees.is_empty() {
@@ -15,13 +29,9 @@ pub fn handle_dir(name: Option<String>) -> Result<()> {
 
     // Determine which worktree to get path for
     let (_key, worktree_info) = if let Some(n) = target_name {
-        // Find worktree by name across all projects
-        state
-            .worktrees
-            .iter()
-            .find(|(_, w)| w.name == n)
-            .map(|(k, w)| (k.clone(), w.clone()))
-            .context(format!("Worktree '{n}' not found"))?
+        names::find_worktree(&state, &n)?
+    } else if let Some(info) = focused_worktree(&state) {
+        (PigsState::make_key(&info.repo_name, &info.name), info)
     } else {
         // Interactive selection - show repo/name format
         let worktree_list: Vec<(String, WorktreeInfo)> = state
@@ -42,9 +52,5 @@ pub fn handle_dir(name: Option<String>) -> Result<()> {
         }
     };
 
-    // Output only the path - no decorations, no colors
-    // This makes it easy to use in shell commands: cd $(pigs dir name)
-    println!("{}", worktree_info.path.display());
-
-    Ok(())
+    Ok(worktree_info)
 }
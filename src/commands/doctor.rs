@@ -0,0 +1,306 @@
+use anyhow::Result;
+use colored::Colorize;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::state::{PigsState, get_default_agent, get_state_path};
+
+enum Outcome {
+    Ok,
+    Warn,
+    Fail,
+}
+
+struct Check {
+    name: String,
+    outcome: Outcome,
+    detail: String,
+    /// What to do about a warning or failure
+    fix: Option<String>,
+}
+
+impl Check {
+    fn ok(name: impl Into<String>, detail: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            outcome: Outcome::Ok,
+            detail: detail.into(),
+            fix: None,
+        }
+    }
+
+    fn warn(name: impl Into<String>, detail: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            outcome: Outcome::Warn,
+            detail: detail.into(),
+            fix: Some(fix.into()),
+        }
+    }
+
+    fn fail(name: impl Into<String>, detail: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            outcome: Outcome::Fail,
+            detail: detail.into(),
+            fix: Some(fix.into()),
+        }
+    }
+
+    fn print(&self) {
+        let icon = match self.outcome {
+            Outcome::Ok => "✅".green(),
+            Outcome::Warn => "⚠️ ".yellow(),
+            Outcome::Fail => "❌".red(),
+        };
+        println!("  {} {}: {}", icon, self.name.bold(), self.detail);
+        if let Some(fix) = &self.fix {
+            println!("     {} {}", "→".cyan(), fix);
+        }
+    }
+}
+
+/// Check the tools, state file, and tracked worktrees pigs depends on, and
+/// print a fix for anything that's wrong. Fails when a required check fails.
+pub fn handle_doctor() -> Result<()> {
+    println!("{} Checking tools...", "🩺".cyan());
+    let state = match PigsState::load_with_local_overrides() {
+        Ok(state) => Some(state),
+        Err(_) => PigsState::load().ok(),
+    };
+    let mut checks = check_tools(state.as_ref());
+    print_all(&checks);
+
+    println!("{} Checking state...", "🩺".cyan());
+    let state_checks = check_state();
+    print_all(&state_checks);
+    checks.extend(state_checks);
+
+    if let Some(state) = &state {
+        println!("{} Checking worktrees...", "🩺".cyan());
+        let worktree_checks = check_worktrees(state);
+        print_all(&worktree_checks);
+        checks.extend(worktree_checks);
+    }
+
+    let failures = checks
+        .iter()
+        .filter(|check| matches!(check.outcome, Outcome::Fail))
+        .count();
+    let warnings = checks
+        .iter()
+        .filter(|check| matches!(check.outcome, Outcome::Warn))
+        .count();
+    if failures > 0 {
+        anyhow::bail!(
+            "pigs doctor found {} problem{}",
+            failures,
+            if failures == 1 { "" } else { "s" }
+        );
+    }
+    if warnings > 0 {
+        println!(
+            "{} No problems found ({} warning{})",
+            "✨".green(),
+            warnings,
+            if warnings == 1 { "" } else { "s" }
+        );
+    } else {
+        println!("{} No problems found", "✨".green());
+    }
+    Ok(())
+}
+
+fn print_all(checks: &[Check]) {
+    for check in checks {
+        check.print();
+    }
+}
+
+fn check_tools(state: Option<&PigsState>) -> Vec<Check> {
+    let mut checks = Vec::new();
+
+    checks.push(match version_of("git") {
+        Some(version) => Check::ok("git", version),
+        None => Check::fail(
+            "git",
+            "not found on PATH",
+            "Install git; pigs can't manage worktrees without it",
+        ),
+    });
+    checks.push(match version_of("gh") {
+        Some(version) => Check::ok("gh", version),
+        None => Check::warn(
+            "gh",
+            "not found on PATH",
+            "Install the GitHub CLI (https://cli.github.com) to use `pigs pr` and pull request checkouts",
+        ),
+    });
+
+    let agents = state
+        .and_then(|state| state.agent.clone())
+        .unwrap_or_else(|| vec![get_default_agent()]);
+    for agent in agents {
+        let name = format!("agent '{}'", agent.name);
+        let program = shell_words::split(&agent.command)
+            .ok()
+            .and_then(|parts| parts.into_iter().next());
+        checks.push(match program {
+            None => Check::fail(
+                name,
+                format!("invalid command `{}`", agent.command),
+                "Fix the agent command with `pigs config`",
+            ),
+            Some(program) => match find_program(&program) {
+                Some(path) => Check::ok(name, path.display().to_string()),
+                None => Check::fail(
+                    name,
+                    format!("`{program}` not found on PATH"),
+                    format!("Install `{program}` or change the agent command in `pigs config`"),
+                ),
+            },
+        });
+    }
+
+    let editor = state
+        .and_then(|state| state.editor.clone())
+        .or_else(|| std::env::var("VISUAL").ok())
+        .or_else(|| std::env::var("EDITOR").ok())
+        .unwrap_or_else(|| "vi".to_string());
+    checks.push(command_check("editor", &editor, "editor"));
+
+    let shell = state
+        .and_then(|state| state.shell.clone())
+        .or_else(|| std::env::var("SHELL").ok())
+        .unwrap_or_else(|| "/bin/zsh".to_string());
+    checks.push(command_check("shell", &shell, "shell"));
+
+    checks
+}
+
+/// Optional tool configured as a full command line, e.g. `code --wait`.
+fn command_check(name: &str, cmdline: &str, key: &str) -> Check {
+    let program = shell_words::split(cmdline)
+        .ok()
+        .and_then(|parts| parts.into_iter().next())
+        .unwrap_or_default();
+    match find_program(&program) {
+        Some(path) => Check::ok(name, path.display().to_string()),
+        None => Check::warn(
+            name,
+            format!("`{cmdline}` not found"),
+            format!("Install it or run `pigs config set {key} <command>`"),
+        ),
+    }
+}
+
+fn check_state() -> Vec<Check> {
+    let path = get_state_path()
+        .map(|path| path.display().to_string())
+        .unwrap_or_else(|_| "~/.pigs/settings.json".to_string());
+    let mut checks = vec![match PigsState::load() {
+        Ok(state) => Check::ok(
+            "state file",
+            format!(
+                "{path} ({} worktree{})",
+                state.worktrees.len(),
+                if state.worktrees.len() == 1 { "" } else { "s" }
+            ),
+        ),
+        Err(err) => Check::fail(
+            "state file",
+            format!("{err:#}"),
+            format!("Fix the JSON in {path}, or move it aside to start over"),
+        ),
+    }];
+
+    match PigsState::find_local_settings() {
+        Ok(Some((local, _))) => {
+            if let Err(err) = PigsState::load_with_local_overrides() {
+                checks.push(Check::fail(
+                    "repo settings",
+                    format!("{err:#}"),
+                    format!("Fix {}", local.display()),
+                ));
+            }
+        }
+        Ok(None) => {}
+        Err(err) => checks.push(Check::fail(
+            "repo settings",
+            format!("{err:#}"),
+            "Fix the .pigs/settings.json in this repository",
+        )),
+    }
+    checks
+}
+
+fn check_worktrees(state: &PigsState) -> Vec<Check> {
+    let mut keys: Vec<&String> = state.worktrees.keys().collect();
+    keys.sort();
+    keys.into_iter()
+        .map(|key| {
+            let info = &state.worktrees[key];
+            if !info.path.exists() {
+                return Check::fail(
+                    key.as_str(),
+                    format!("{} is missing", info.path.display()),
+                    "Run `pigs clean` to re-link a moved repository or forget the worktree",
+                );
+            }
+            if !branch_exists(&info.path, &info.branch) {
+                return Check::fail(
+                    key.as_str(),
+                    format!("branch '{}' no longer exists", info.branch),
+                    format!(
+                        "Check out a branch in {} or run `pigs delete {}`",
+                        info.path.display(),
+                        info.name
+                    ),
+                );
+            }
+            Check::ok(
+                key.as_str(),
+                format!("{} on {}", info.path.display(), info.branch),
+            )
+        })
+        .collect()
+}
+
+fn branch_exists(worktree: &Path, branch: &str) -> bool {
+    Command::new("git")
+        .current_dir(worktree)
+        .args([
+            "show-ref",
+            "--verify",
+            "--quiet",
+            &format!("refs/heads/{branch}"),
+        ])
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+/// First line of `<program> --version`, if the program runs.
+fn version_of(program: &str) -> Option<String> {
+    let output = Command::new(program).arg("--version").output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Some(stdout.lines().next().unwrap_or(program).trim().to_string())
+}
+
+/// Resolve `program` the way a shell would: paths are checked directly, bare
+/// names are searched for on `PATH`.
+fn find_program(program: &str) -> Option<PathBuf> {
+    if program.is_empty() {
+        return None;
+    }
+    if program.contains('/') {
+        let path = PathBuf::from(program);
+        return path.is_file().then_some(path);
+    }
+    let paths = std::env::var_os("PATH")?;
+    std::env::split_paths(&paths)
+        .map(|dir| dir.join(program))
+        .find(|candidate| candidate.is_file())
+}
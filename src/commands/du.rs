@@ -0,0 +1,155 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use colored::Colorize;
+use serde::Serialize;
+
+use super::list::format_time_ago;
+use crate::activity;
+use crate::state::PigsState;
+use crate::utils::format_bytes;
+
+/// Directory names that hold generated files and are usually what makes a
+/// worktree big; reported wherever they appear in the tree.
+const GENERATED_DIRS: &[&str] = &[
+    "node_modules",
+    "target",
+    ".venv",
+    "venv",
+    "dist",
+    "build",
+    ".next",
+    ".turbo",
+    ".gradle",
+    "__pycache__",
+];
+
+/// Largest directories listed under each worktree.
+const TOP_OFFENDERS: usize = 3;
+
+#[derive(Serialize)]
+struct WorktreeUsage {
+    repo_name: String,
+    name: String,
+    path: String,
+    bytes: u64,
+    last_activity: DateTime<Utc>,
+    /// The biggest top-level or generated directories, largest first
+    largest: Vec<DirUsage>,
+}
+
+#[derive(Serialize)]
+struct DirUsage {
+    /// Relative to the worktree
+    path: String,
+    bytes: u64,
+}
+
+/// Show how much disk each worktree takes, largest first, with the
+/// directories responsible (top-level ones and generated ones such as
+/// `node_modules` or `target`), so it's clear which to delete first.
+pub fn handle_du(json: bool) -> Result<()> {
+    let state = PigsState::load()?;
+
+    let mut usages: Vec<WorktreeUsage> = state
+        .worktrees
+        .values()
+        .filter(|info| info.path.exists())
+        .map(|info| {
+            let mut dirs = Vec::new();
+            let bytes = measure(&info.path, &info.path, 0, &mut dirs);
+            dirs.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.path.cmp(&b.path)));
+            dirs.truncate(TOP_OFFENDERS);
+            WorktreeUsage {
+                repo_name: info.repo_name.clone(),
+                name: info.name.clone(),
+                path: info.path.display().to_string(),
+                bytes,
+                last_activity: activity::last_activity(info),
+                largest: dirs,
+            }
+        })
+        .collect();
+    usages.sort_by(|a, b| {
+        b.bytes
+            .cmp(&a.bytes)
+            .then_with(|| (&a.repo_name, &a.name).cmp(&(&b.repo_name, &b.name)))
+    });
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&usages)?);
+        return Ok(());
+    }
+
+    if usages.is_empty() {
+        println!("{} No worktrees on disk", "📭".yellow());
+        return Ok(());
+    }
+
+    let total: u64 = usages.iter().map(|usage| usage.bytes).sum();
+    println!(
+        "{} {} across {} worktree(s)",
+        "💾".cyan(),
+        format_bytes(total).bold(),
+        usages.len()
+    );
+    println!();
+    for usage in &usages {
+        println!(
+            "  {:>10}  {}/{} {}",
+            format_bytes(usage.bytes).bold(),
+            usage.repo_name,
+            usage.name.cyan(),
+            format!("(active {})", format_time_ago(Some(usage.last_activity))).bright_black()
+        );
+        for dir in &usage.largest {
+            println!(
+                "  {:>10}    {}",
+                format_bytes(dir.bytes).bright_black(),
+                dir.path.bright_black()
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Total size of the files under `dir`, without following symlinks. Adds the
+/// top-level directories of `root` and any generated directories to `dirs`;
+/// generated ones aren't searched for more.
+fn measure(root: &Path, dir: &Path, depth: usize, dirs: &mut Vec<DirUsage>) -> u64 {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return 0;
+    };
+    let mut total = 0;
+    for entry in entries.flatten() {
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        if !metadata.is_dir() {
+            total += metadata.len();
+            continue;
+        }
+
+        let path = entry.path();
+        let generated = GENERATED_DIRS.iter().any(|name| entry.file_name() == *name);
+        let size = if generated {
+            measure(root, &path, depth + 1, &mut Vec::new())
+        } else {
+            measure(root, &path, depth + 1, dirs)
+        };
+        total += size;
+        if generated || depth == 0 {
+            dirs.push(DirUsage {
+                path: path
+                    .strip_prefix(root)
+                    .unwrap_or(&path)
+                    .display()
+                    .to_string(),
+                bytes: size,
+            });
+        }
+    }
+    total
+}
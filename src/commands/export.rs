@@ -0,0 +1,210 @@
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use colored::Colorize;
+
+use crate::state::PigsState;
+
+/// Print pigs state as JSON, in the same format as the state file, so it can be
+/// moved to another machine with `pigs import`. Dashboard tokens, trusted repo
+/// settings, and pooled worktrees stay behind: they are secrets or only make
+/// sense on this machine. With `repo`, only that repository's worktrees and
+/// tombstones are included.
+pub fn handle_export(repo: Option<String>) -> Result<()> {
+    let mut state = PigsState::load()?;
+    strip_local(&mut state);
+
+    if let Some(repo) = repo {
+        state.worktrees.retain(|_, info| info.repo_name == repo);
+        if state.worktrees.is_empty() {
+            bail!("No worktrees found for repository '{repo}'");
+        }
+        let prefix = PigsState::make_key(&repo, "");
+        state.tombstones.retain(|key, _| key.starts_with(&prefix));
+        state.worktree_pool.clear();
+        if state
+            .focus
+            .as_ref()
+            .is_some_and(|key| !key.starts_with(&prefix))
+        {
+            state.focus = None;
+        }
+    }
+
+    println!("{}", serde_json::to_string_pretty(&state)?);
+    Ok(())
+}
+
+/// Merge state written by `pigs export` (or a copy of the state file) into this
+/// machine's state. Worktrees already tracked here win over imported ones, and
+/// settings only fill in what isn't set yet. `rewrite_prefix` is `/old:/new`
+/// and moves every imported path under `/old` to `/new`.
+pub fn handle_import(file: PathBuf, rewrite_prefix: Option<String>) -> Result<()> {
+    let rewrite = rewrite_prefix.as_deref().map(parse_rewrite).transpose()?;
+
+    let from_stdin = file == Path::new("-");
+    let source = if from_stdin {
+        "stdin".to_string()
+    } else {
+        file.display().to_string()
+    };
+    let content = if from_stdin {
+        let mut content = String::new();
+        std::io::stdin()
+            .read_to_string(&mut content)
+            .context("Failed to read the export from stdin")?;
+        content
+    } else {
+        std::fs::read_to_string(&file)
+            .with_context(|| format!("Failed to read {}", file.display()))?
+    };
+    let mut imported: PigsState =
+        serde_json::from_str(&content).with_context(|| format!("{source} is not a pigs export"))?;
+    strip_local(&mut imported);
+    if let Some((from, to)) = &rewrite {
+        rewrite_paths(&mut imported, from, to);
+    }
+
+    let mut state = PigsState::load()?;
+    let mut added = Vec::new();
+    let mut skipped = Vec::new();
+    let mut keys: Vec<String> = imported.worktrees.keys().cloned().collect();
+    keys.sort();
+    for key in keys {
+        let info = imported.worktrees.remove(&key).expect("key from map");
+        if state.worktrees.contains_key(&key) {
+            skipped.push(key);
+        } else {
+            state.tombstones.remove(&key);
+            added.push((key.clone(), info.path.clone()));
+            state.worktrees.insert(key, info);
+        }
+    }
+    for (key, tombstone) in imported.tombstones {
+        if !state.worktrees.contains_key(&key) {
+            state.tombstones.entry(key).or_insert(tombstone);
+        }
+    }
+    for (repo, count) in imported.worktree_pool {
+        state.worktree_pool.entry(repo).or_insert(count);
+    }
+    for root in imported.workspace_roots {
+        if !state.workspace_roots.contains(&root) {
+            state.workspace_roots.push(root);
+        }
+    }
+    for webhook in imported.webhooks {
+        if !state.webhooks.iter().any(|w| w.url == webhook.url) {
+            state.webhooks.push(webhook);
+        }
+    }
+    state.agent = state.agent.take().or(imported.agent);
+    state.editor = state.editor.take().or(imported.editor);
+    state.shell = state.shell.take().or(imported.shell);
+    state.name_scheme = state.name_scheme.take().or(imported.name_scheme);
+    state.tombstone_days = state.tombstone_days.or(imported.tombstone_days);
+    state.offline |= imported.offline;
+    state.record_sessions |= imported.record_sessions;
+    if state.focus.is_none() {
+        state.focus = imported
+            .focus
+            .filter(|key| state.worktrees.contains_key(key));
+    }
+    state.save()?;
+
+    let missing: Vec<&(String, PathBuf)> =
+        added.iter().filter(|(_, path)| !path.exists()).collect();
+    println!(
+        "{} Imported {} worktree(s) from {}",
+        "📥".green(),
+        added.len(),
+        source
+    );
+    for (key, path) in &added {
+        println!("   {} {}", key.cyan(), path.display().to_string().dimmed());
+    }
+    if !skipped.is_empty() {
+        println!(
+            "{} Kept {} worktree(s) already tracked here: {}",
+            "⏭️".yellow(),
+            skipped.len(),
+            skipped.join(", ")
+        );
+    }
+    if !missing.is_empty() {
+        println!(
+            "{} {} imported path(s) don't exist on this machine. Pass --rewrite-prefix /old:/new to move them, or run 'pigs clean' to drop them",
+            "⚠️".yellow(),
+            missing.len()
+        );
+    }
+    Ok(())
+}
+
+/// Drop what must not travel between machines.
+fn strip_local(state: &mut PigsState) {
+    state.dashboard_users.clear();
+    state.dashboard_tokens.clear();
+    state.trusted_repos.clear();
+    state.pooled_worktrees.clear();
+}
+
+fn parse_rewrite(value: &str) -> Result<(PathBuf, PathBuf)> {
+    match value.split_once(':') {
+        Some((from, to)) if !from.is_empty() && !to.is_empty() => {
+            Ok((PathBuf::from(from), PathBuf::from(to)))
+        }
+        _ => bail!("Invalid --rewrite-prefix '{value}'; expected /old/prefix:/new/prefix"),
+    }
+}
+
+/// `path` moved from under `from` to under `to`, or unchanged if it isn't
+/// under `from`. Whole components are compared, so `/home/al` doesn't match
+/// `/home/alice`.
+fn rewrite_path(path: &Path, from: &Path, to: &Path) -> PathBuf {
+    match path.strip_prefix(from) {
+        Ok(rest) if rest.as_os_str().is_empty() => to.to_path_buf(),
+        Ok(rest) => to.join(rest),
+        Err(_) => path.to_path_buf(),
+    }
+}
+
+fn rewrite_paths(state: &mut PigsState, from: &Path, to: &Path) {
+    for info in state.worktrees.values_mut() {
+        info.path = rewrite_path(&info.path, from, to);
+    }
+    for root in &mut state.workspace_roots {
+        *root = rewrite_path(root, from, to);
+    }
+    state.worktree_pool = std::mem::take(&mut state.worktree_pool)
+        .into_iter()
+        .map(|(repo, count)| (rewrite_path(&repo, from, to), count))
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_rewrite, rewrite_path};
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn rewrite_path_moves_whole_components() {
+        let from = Path::new("/home/al");
+        let to = Path::new("/Users/al");
+        assert_eq!(
+            rewrite_path(Path::new("/home/al/code/app-x"), from, to),
+            PathBuf::from("/Users/al/code/app-x")
+        );
+        assert_eq!(
+            rewrite_path(Path::new("/home/al"), from, to),
+            PathBuf::from("/Users/al")
+        );
+        assert_eq!(
+            rewrite_path(Path::new("/home/alice/app"), from, to),
+            PathBuf::from("/home/alice/app")
+        );
+        assert!(parse_rewrite("/home/al").is_err());
+        assert!(parse_rewrite(":/new").is_err());
+    }
+}
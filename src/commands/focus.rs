@@ -0,0 +1,83 @@
+use anyhow::Result;
+use colored::Colorize;
+
+use crate::names::resolve_worktree;
+use crate::state::{PigsState, WorktreeInfo};
+
+pub fn handle_focus(name: Option<String>, clear: bool, prompt: bool) -> Result<()> {
+    if prompt {
+        print_prompt_segment();
+        return Ok(());
+    }
+
+    let mut state = PigsState::load()?;
+
+    if clear {
+        if state.focus.take().is_none() {
+            println!("{} No worktree is focused", "ℹ️".blue());
+            return Ok(());
+        }
+        state.save()?;
+        println!("{} Cleared focus", "🗑️".green());
+        return Ok(());
+    }
+
+    let Some(name) = name else {
+        match state.focused() {
+            Some(info) => println!(
+                "{} Focused on {}/{}",
+                "🎯".green(),
+                info.repo_name,
+                info.name.cyan()
+            ),
+            None => println!("{} No worktree is focused", "ℹ️".blue()),
+        }
+        return Ok(());
+    };
+
+    let info = resolve_worktree(&state, Some(name))?;
+    state.focus = Some(PigsState::make_key(&info.repo_name, &info.name));
+    state.save()?;
+
+    println!(
+        "{} Focused on {}/{}; commands run without a worktree name now use it",
+        "🎯".green(),
+        info.repo_name,
+        info.name.cyan()
+    );
+    Ok(())
+}
+
+/// The focused worktree, for commands run without a worktree name. Notes the
+/// choice on stderr so output meant for scripts (e.g. `pigs dir`) stays clean.
+pub(crate) fn focused_worktree(state: &PigsState) -> Option<WorktreeInfo> {
+    let info = state.focused()?.clone();
+    eprintln!(
+        "{} Using focused worktree {}/{} (pigs focus --clear to unset)",
+        "🎯".green(),
+        info.repo_name,
+        info.name.cyan()
+    );
+    Some(info)
+}
+
+/// Print the focused worktree's name for a shell prompt, marked with `*` when
+/// the shell is inside it. Prints nothing when no worktree is focused, and
+/// never fails, so a broken state file can't break the prompt.
+fn print_prompt_segment() {
+    let Ok(state) = PigsState::load() else {
+        return;
+    };
+    let Some(info) = state.focused() else {
+        return;
+    };
+    let inside = std::env::current_dir()
+        .ok()
+        .zip(info.path.canonicalize().ok())
+        .is_some_and(|(cwd, path)| cwd.starts_with(path));
+    if inside {
+        println!("*{}", info.name);
+    } else {
+        println!("{}", info.name);
+    }
+}
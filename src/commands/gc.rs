@@ -0,0 +1,205 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use colored::Colorize;
+
+use crate::claude;
+use crate::codex;
+use crate::git;
+use crate::input::smart_confirm;
+use crate::reminders::parse_offset;
+use crate::state::PigsState;
+use crate::transcripts;
+use crate::utils::format_bytes;
+
+/// Files of one kind that are old enough to remove.
+struct Group {
+    label: &'static str,
+    files: Vec<(PathBuf, u64)>,
+}
+
+impl Group {
+    fn bytes(&self) -> u64 {
+        self.files.iter().map(|(_, size)| size).sum()
+    }
+}
+
+/// Remove session logs last written more than `older_than` ago (e.g. `30d`):
+/// transcripts saved by `pigs open --record` and their dashboard bookmarks,
+/// the `refs/pigs/sessions/*` snapshots the dashboard takes when a session
+/// starts, and with `agent_logs` also the Claude and Codex session files of
+/// tracked worktrees. Reports how much space was reclaimed; `dry_run` only
+/// reports it.
+pub fn handle_gc(older_than: String, agent_logs: bool, dry_run: bool, yes: bool) -> Result<()> {
+    let cutoff = Utc::now() - parse_offset(&older_than)?;
+    let state = PigsState::load()?;
+
+    let mut groups = vec![Group {
+        label: "pigs transcripts",
+        files: old_files(jsonl_files(&transcripts::transcripts_dir()?), cutoff),
+    }];
+    let snapshots = old_session_refs(&state, cutoff);
+    if agent_logs {
+        let paths: Vec<PathBuf> = state
+            .worktrees
+            .values()
+            .map(|info| info.path.clone())
+            .collect();
+        groups.push(Group {
+            label: "Claude sessions",
+            files: old_files(
+                paths.iter().flat_map(|path| claude::session_files(path)),
+                cutoff,
+            ),
+        });
+        // Check the age first; telling which worktree a session ran in means
+        // reading its file
+        let codex = old_files(codex::iterate_session_files(false)?, cutoff)
+            .into_iter()
+            .filter(|(file, _)| codex::session_in_worktrees(file, &paths))
+            .collect();
+        groups.push(Group {
+            label: "Codex sessions",
+            files: codex,
+        });
+    }
+
+    let total: u64 = groups.iter().map(Group::bytes).sum();
+    let count: usize = groups.iter().map(|group| group.files.len()).sum();
+    let ref_count: usize = snapshots.iter().map(|(_, refs)| refs.len()).sum();
+    if count == 0 && ref_count == 0 {
+        println!("{} No session logs older than {}", "✨".green(), older_than);
+        return Ok(());
+    }
+
+    for group in &groups {
+        if !group.files.is_empty() {
+            println!(
+                "  {} {} file(s), {}",
+                format!("{}:", group.label).bright_black(),
+                group.files.len(),
+                format_bytes(group.bytes())
+            );
+        }
+    }
+    if ref_count > 0 {
+        println!(
+            "  {} {} ref(s)",
+            "session snapshots:".bright_black(),
+            ref_count
+        );
+    }
+    if dry_run {
+        println!(
+            "{} Would remove {} file(s) older than {}, reclaiming {}",
+            "🔍".yellow(),
+            count,
+            older_than,
+            format_bytes(total)
+        );
+        return Ok(());
+    }
+
+    let agent_files: usize = groups[1..].iter().map(|group| group.files.len()).sum();
+    if agent_files > 0
+        && !yes
+        && !smart_confirm(
+            &format!(
+                "Delete {agent_files} Claude/Codex session file(s)? Those sessions can't be resumed afterwards"
+            ),
+            false,
+        )?
+    {
+        groups.truncate(1);
+    }
+
+    let mut removed_refs = 0;
+    for (repo, refs) in &snapshots {
+        for name in refs {
+            match git::execute_git(&["-C", &repo.to_string_lossy(), "update-ref", "-d", name]) {
+                Ok(_) => removed_refs += 1,
+                Err(err) => eprintln!("{} Failed to remove {name}: {err}", "⚠️".yellow()),
+            }
+        }
+    }
+    if removed_refs > 0 {
+        println!(
+            "{} Removed {} session snapshot ref(s)",
+            "🧹".green(),
+            removed_refs
+        );
+    }
+
+    let mut removed = 0;
+    let mut reclaimed = 0;
+    for (file, size) in groups.iter().flat_map(|group| &group.files) {
+        match fs::remove_file(file) {
+            Ok(()) => {
+                removed += 1;
+                reclaimed += size;
+            }
+            Err(err) => eprintln!(
+                "{} Failed to remove {}: {err}",
+                "⚠️".yellow(),
+                file.display()
+            ),
+        }
+    }
+    println!(
+        "{} Removed {} file(s) older than {}, reclaiming {}",
+        "🧹".green(),
+        removed,
+        older_than,
+        format_bytes(reclaimed)
+    );
+    Ok(())
+}
+
+/// Session snapshot refs taken before `cutoff`, per main checkout of the
+/// repositories pigs tracks worktrees of.
+fn old_session_refs(state: &PigsState, cutoff: DateTime<Utc>) -> Vec<(PathBuf, Vec<String>)> {
+    let repos: BTreeSet<PathBuf> = state
+        .worktrees
+        .values()
+        .filter_map(|info| info.main_repo_path().ok())
+        .filter(|repo| repo.exists())
+        .collect();
+    repos
+        .into_iter()
+        .filter_map(|repo| {
+            let refs = git::session_refs_before(&repo, cutoff).ok()?;
+            (!refs.is_empty()).then_some((repo, refs))
+        })
+        .collect()
+}
+
+fn jsonl_files(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "jsonl"))
+        .collect()
+}
+
+/// The `files` last modified before `cutoff`, with their sizes.
+fn old_files(
+    files: impl IntoIterator<Item = PathBuf>,
+    cutoff: DateTime<Utc>,
+) -> Vec<(PathBuf, u64)> {
+    let cutoff = SystemTime::from(cutoff);
+    files
+        .into_iter()
+        .filter_map(|file| {
+            let metadata = fs::metadata(&file).ok()?;
+            let old = metadata.is_file() && metadata.modified().ok()? < cutoff;
+            old.then_some((file, metadata.len()))
+        })
+        .collect()
+}
@@ -0,0 +1,123 @@
+use anyhow::Result;
+use colored::Colorize;
+
+use crate::commands::create::{CopyOverride, handle_create};
+use crate::git::get_repo_name;
+use crate::github;
+use crate::input::{get_command_arg, smart_select};
+use crate::network;
+use crate::prompts;
+use crate::utils::sanitize_branch_name;
+
+pub fn handle_github(
+    issue: Option<String>,
+    from: Option<String>,
+    yes: bool,
+    selected_agent: Option<String>,
+    mut agent_args: Vec<String>,
+) -> Result<()> {
+    let target = match get_command_arg(issue)? {
+        Some(target) => target,
+        None => {
+            // Fetch assigned issues and let the user pick one
+            network::ensure_online("GitHub issue selection")?;
+            let issues = github::fetch_my_issues()?;
+
+            if issues.is_empty() {
+                anyhow::bail!("No open GitHub issues are assigned to you in this repository");
+            }
+
+            let selection = smart_select("Select a GitHub issue", &issues, |issue| {
+                format!("#{} {}", issue.number, issue.title)
+            })?;
+
+            match selection {
+                Some(index) => issues[index].number.to_string(),
+                None => anyhow::bail!("A GitHub issue number or URL is required (e.g. 123)"),
+            }
+        }
+    };
+
+    let Some(number) = github::parse_issue_reference(&target) else {
+        anyhow::bail!(
+            "'{}' is not a GitHub issue (expected a number like 123 or an issue URL)",
+            target
+        );
+    };
+
+    if network::is_offline() {
+        // Without GitHub we can't look up the title, so fall back to a branch
+        // named after the issue number and start without a prompt.
+        let branch_name = format!("issue-{number}");
+        println!(
+            "{} Offline: skipping GitHub lookup, using branch '{}'",
+            "✈️".yellow(),
+            branch_name.cyan()
+        );
+        return handle_create(
+            vec![branch_name],
+            None,
+            from,
+            None,
+            None,
+            yes,
+            false,
+            false,
+            false,
+            selected_agent,
+            agent_args,
+            None,
+            CopyOverride::default(),
+            None,
+        );
+    }
+
+    // A URL may point at another repository; `gh` resolves it either way
+    let lookup = if target.contains("/issues/") {
+        target.trim().to_string()
+    } else {
+        number.to_string()
+    };
+    let issue = github::fetch_issue(&lookup)?;
+
+    println!(
+        "{} Found GitHub issue #{}: {}",
+        "🔗".green(),
+        issue.number,
+        issue.title.cyan()
+    );
+
+    let branch_name = github::issue_branch_name(issue.number, &issue.title);
+    let mut prompt = issue.title;
+    if !issue.body.trim().is_empty() {
+        prompt.push_str("\n\n");
+        prompt.push_str(issue.body.trim());
+    }
+    prompt.push_str(&format!("\n\n{}", issue.url));
+    if let Ok(repo_name) = get_repo_name() {
+        prompts::record(
+            &repo_name,
+            &sanitize_branch_name(&branch_name),
+            None,
+            &prompt,
+        );
+    }
+    agent_args.push(prompt);
+
+    handle_create(
+        vec![branch_name],
+        None,
+        from,
+        None,
+        None,
+        yes,
+        false,
+        false,
+        false,
+        selected_agent,
+        agent_args,
+        None,
+        CopyOverride::default(),
+        None,
+    )
+}
@@ -0,0 +1,223 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+use serde_json::{Map, Value, json};
+
+use crate::detect::detect;
+use crate::git::execute_git;
+use crate::input::smart_confirm;
+use crate::trust::{PRIVILEGED_KEYS, set_trusted};
+
+const SETTINGS_FILE: &str = ".pigs/settings.json";
+
+/// What a new `.pigs/settings.json` should do with the file once written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InitShare {
+    /// Leave it untracked
+    Keep,
+    /// Add it to `.gitignore`, for settings personal to this checkout
+    Ignore,
+    /// Commit it, so everyone working on the repository gets the same settings
+    Commit,
+}
+
+impl InitShare {
+    pub fn from_flags(gitignore: bool, commit: bool) -> Self {
+        if gitignore {
+            Self::Ignore
+        } else if commit {
+            Self::Commit
+        } else {
+            Self::Keep
+        }
+    }
+}
+
+/// Keys a new settings file starts with, empty unless detected, and what each
+/// does. JSON has no comments, so these descriptions are printed instead.
+const SCAFFOLD: &[(&str, &str)] = &[
+    (
+        "copy_files",
+        "gitignored files copied into each new worktree, e.g. \".env\"",
+    ),
+    (
+        "setup_commands",
+        "commands run in each new worktree, e.g. \"npm ci\"",
+    ),
+    (
+        "tasks",
+        "named commands the dashboard can run, e.g. {\"test\": \"cargo test\"}",
+    ),
+    (
+        "artifacts",
+        "files saved after each task run, e.g. \"target/junit.xml\"",
+    ),
+];
+
+/// Write the settings detected in the current repository to its
+/// `.pigs/settings.json`, keeping anything the file already sets. A new file
+/// also gets the standard keys, so there's something to fill in.
+pub fn handle_init(yes: bool, share: InitShare) -> Result<()> {
+    let root = PathBuf::from(
+        execute_git(&["rev-parse", "--show-toplevel"]).context("Not inside a git repository")?,
+    );
+    let settings_path = root.join(SETTINGS_FILE);
+    let is_new = !settings_path.exists();
+    let existing: Map<String, Value> = if is_new {
+        Map::new()
+    } else {
+        let content = fs::read_to_string(&settings_path)
+            .with_context(|| format!("Failed to read {}", settings_path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse {}", settings_path.display()))?
+    };
+
+    let detected = detect(&root);
+    if !detected.found.is_empty() {
+        println!("{} Found {}", "🔎".cyan(), detected.found.join(", "));
+    }
+
+    let mut settings = existing.clone();
+    let mut added = detected.merge_into(&mut settings);
+    if is_new {
+        for (key, _) in SCAFFOLD {
+            if !settings.contains_key(*key) {
+                let empty = if *key == "tasks" {
+                    json!({})
+                } else {
+                    json!([])
+                };
+                settings.insert(key.to_string(), empty);
+                added.push(key);
+            }
+        }
+    }
+
+    if added.is_empty() {
+        println!(
+            "{} Nothing to add to {}",
+            "✨".green(),
+            settings_path.display()
+        );
+        return share_settings(&root, share);
+    }
+
+    println!("{} Proposed {}:", "📝".cyan(), settings_path.display());
+    let proposed: Map<String, Value> = added
+        .iter()
+        .map(|key| (key.to_string(), settings[*key].clone()))
+        .collect();
+    println!("{}", serde_json::to_string_pretty(&proposed)?);
+
+    let prompt = if existing.is_empty() {
+        "Create these settings?"
+    } else {
+        "Add these settings?"
+    };
+    if !yes && !smart_confirm(prompt, true)? {
+        println!("{} Cancelled", "❌".red());
+        return Ok(());
+    }
+
+    fs::create_dir_all(root.join(".pigs")).context("Failed to create .pigs directory")?;
+    let settings = Value::Object(settings);
+    fs::write(
+        &settings_path,
+        format!("{}\n", serde_json::to_string_pretty(&settings)?),
+    )
+    .with_context(|| format!("Failed to write {}", settings_path.display()))?;
+
+    // The user just approved these, so don't ask again the first time they run
+    if added.iter().any(|key| PRIVILEGED_KEYS.contains(key)) {
+        set_trusted(&settings_path, &settings, true)?;
+    }
+
+    println!(
+        "{} Wrote {} ({})",
+        "✅".green(),
+        settings_path.display(),
+        added.join(", ")
+    );
+    if is_new {
+        print_key_guide();
+    }
+    share_settings(&root, share)
+}
+
+fn print_key_guide() {
+    println!("  {} What each setting does:", "💡".cyan());
+    for (key, description) in SCAFFOLD {
+        println!("    {}: {}", key.cyan(), description);
+    }
+    println!(
+        "    {}: override the agent for this repository, e.g. [{{\"name\": \"codex\", \"command\": \"codex\"}}]",
+        "agent".cyan()
+    );
+}
+
+fn share_settings(root: &Path, share: InitShare) -> Result<()> {
+    match share {
+        InitShare::Keep => Ok(()),
+        InitShare::Ignore => ignore_settings(root),
+        InitShare::Commit => commit_settings(root),
+    }
+}
+
+fn ignore_settings(root: &Path) -> Result<()> {
+    let gitignore = root.join(".gitignore");
+    let content = fs::read_to_string(&gitignore).unwrap_or_default();
+    if content.lines().any(|line| line.trim() == SETTINGS_FILE) {
+        println!(
+            "{} .gitignore already ignores {}",
+            "✨".green(),
+            SETTINGS_FILE
+        );
+        return Ok(());
+    }
+    let mut updated = content;
+    if !updated.is_empty() && !updated.ends_with('\n') {
+        updated.push('\n');
+    }
+    updated.push_str(SETTINGS_FILE);
+    updated.push('\n');
+    fs::write(&gitignore, updated).context("Failed to update .gitignore")?;
+    println!("{} Added {} to .gitignore", "🙈".green(), SETTINGS_FILE);
+    Ok(())
+}
+
+fn commit_settings(root: &Path) -> Result<()> {
+    let root = root
+        .to_str()
+        .context("Repository path is not valid UTF-8")?;
+    execute_git(&["-C", root, "add", "--", SETTINGS_FILE])
+        .context("Failed to stage .pigs/settings.json")?;
+    if execute_git(&[
+        "-C",
+        root,
+        "diff",
+        "--cached",
+        "--quiet",
+        "--",
+        SETTINGS_FILE,
+    ])
+    .is_ok()
+    {
+        println!("{} {} is already committed", "✨".green(), SETTINGS_FILE);
+        return Ok(());
+    }
+    execute_git(&[
+        "-C",
+        root,
+        "commit",
+        "--quiet",
+        "-m",
+        "Add pigs settings",
+        "--",
+        SETTINGS_FILE,
+    ])
+    .context("Failed to commit .pigs/settings.json")?;
+    println!("{} Committed {}", "📦".green(), SETTINGS_FILE);
+    Ok(())
+}
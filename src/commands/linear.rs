@@ -1,9 +1,13 @@
 use anyhow::{Context, Result};
 use colored::Colorize;
 
-use crate::commands::create::handle_create;
+use crate::commands::create::{CopyOverride, handle_create};
+use crate::git::get_repo_name;
 use crate::input::{get_command_arg, smart_confirm, smart_select};
 use crate::linear;
+use crate::network;
+use crate::prompts;
+use crate::utils::sanitize_branch_name;
 
 pub fn handle_linear(
     identifier: Option<String>,
@@ -16,6 +20,7 @@ pub fn handle_linear(
         Some(id) => id,
         None => {
             // Fetch assigned issues and let the user pick one
+            network::ensure_online("Linear issue selection")?;
             std::env::var("LINEAR_API_KEY")
                 .context("LINEAR_API_KEY environment variable is not set")?;
 
@@ -43,6 +48,33 @@ pub fn handle_linear(
         );
     }
 
+    if network::is_offline() {
+        // Without Linear we can't look up the branch or title, so fall back to
+        // a branch named after the issue and skip the status update.
+        let branch_name = identifier.to_lowercase();
+        println!(
+            "{} Offline: skipping Linear lookup, using branch '{}'",
+            "✈️".yellow(),
+            branch_name.cyan()
+        );
+        return handle_create(
+            vec![branch_name],
+            None,
+            from,
+            None,
+            None,
+            yes,
+            false,
+            false,
+            false,
+            selected_agent,
+            agent_args,
+            None,
+            CopyOverride::default(),
+            None,
+        );
+    }
+
     std::env::var("LINEAR_API_KEY").context("LINEAR_API_KEY environment variable is not set")?;
 
     let issue = linear::fetch_issue(&identifier)?;
@@ -74,13 +106,30 @@ pub fn handle_linear(
         prompt.push_str("\n\n");
         prompt.push_str(&desc);
     }
+    if let Ok(repo_name) = get_repo_name() {
+        prompts::record(
+            &repo_name,
+            &sanitize_branch_name(&issue.branch_name),
+            None,
+            &prompt,
+        );
+    }
     agent_args.push(prompt);
 
     handle_create(
-        Some(issue.branch_name),
+        vec![issue.branch_name],
+        None,
         from,
+        None,
+        None,
         yes,
+        false,
+        false,
+        false,
         selected_agent,
         agent_args,
+        None,
+        CopyOverride::default(),
+        None,
     )
 }
@@ -1,12 +1,17 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use chrono::{DateTime, Local, Utc};
 use colored::Colorize;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 
+use crate::activity::AgentUse;
 use crate::claude::get_claude_sessions;
 use crate::codex;
-use crate::state::PigsState;
+use crate::labels::{ChangeLabel, detect_worktree_labels};
+use crate::problems::{ProblemReport, detect_worktree_problems};
+use crate::reminders::ReminderReport;
+use crate::stack::{StackNode, stack_order};
+use crate::state::{PigsState, WorktreeInfo};
 
 #[derive(Debug, Serialize, Deserialize)]
 struct JsonSessionInfo {
@@ -22,6 +27,21 @@ struct JsonWorktreeInfo {
     path: String,
     repo_name: String,
     created_at: DateTime<Utc>,
+    labels: Vec<ChangeLabel>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    stack_parent: Option<String>,
+    problems: Vec<ProblemReport>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    reminder: Option<ReminderReport>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    note: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    last_agent: Option<AgentUse>,
+    /// Total Claude and Codex sessions, so consumers needn't count the arrays
+    session_count: usize,
+    codex_session_count: usize,
     sessions: Vec<JsonSessionInfo>,
     codex_sessions: Vec<JsonCodexSessionInfo>,
 }
@@ -39,7 +59,7 @@ struct JsonCodexSessionInfo {
     time_ago: String,
 }
 
-fn format_time_ago(timestamp: Option<DateTime<Utc>>) -> String {
+pub(super) fn format_time_ago(timestamp: Option<DateTime<Utc>>) -> String {
     timestamp.map_or_else(
         || "unknown".to_string(),
         |ts| {
@@ -57,7 +77,7 @@ fn format_time_ago(timestamp: Option<DateTime<Utc>>) -> String {
     )
 }
 
-fn format_message_preview(message: &str, limit: usize) -> String {
+pub(super) fn format_message_preview(message: &str, limit: usize) -> String {
     if message.len() <= limit {
         return message.to_string();
     }
@@ -74,10 +94,35 @@ fn format_message_preview(message: &str, limit: usize) -> String {
     truncated
 }
 
-pub fn handle_list(json: bool) -> Result<()> {
+fn problem_reports(info: &WorktreeInfo) -> Vec<ProblemReport> {
+    detect_worktree_problems(&info.path)
+        .into_iter()
+        .map(|kind| ProblemReport::new(kind, &info.branch))
+        .collect()
+}
+
+pub fn handle_list(json: bool, label: Option<String>, tag: Option<String>) -> Result<()> {
     let state = PigsState::load()?;
 
-    if state.worktrees.is_empty() {
+    let label_filter = match label {
+        Some(value) => Some(ChangeLabel::parse(&value).with_context(|| {
+            format!(
+                "Unknown label '{value}'. Available labels: migrations, public-api, dependencies, tests-only, docs-only"
+            )
+        })?),
+        None => None,
+    };
+
+    // Compute labels once per worktree and drop entries that don't match the filters
+    let entries: Vec<(&WorktreeInfo, Vec<ChangeLabel>)> = state
+        .worktrees
+        .values()
+        .filter(|info| tag.as_ref().is_none_or(|wanted| info.tags.contains(wanted)))
+        .map(|info| (info, detect_worktree_labels(&info.path)))
+        .filter(|(_, labels)| label_filter.is_none_or(|wanted| labels.contains(&wanted)))
+        .collect();
+
+    if entries.is_empty() {
         if json {
             let output = JsonOutput { worktrees: vec![] };
             println!("{}", serde_json::to_string_pretty(&output)?);
@@ -91,7 +136,7 @@ pub fn handle_list(json: bool) -> Result<()> {
         // JSON output
         let mut worktrees = Vec::new();
 
-        for info in state.worktrees.values() {
+        for (info, labels) in &entries {
             let claude_sessions = get_claude_sessions(&info.path);
             let json_sessions: Vec<JsonSessionInfo> = claude_sessions
                 .into_iter()
@@ -119,6 +164,18 @@ pub fn handle_list(json: bool) -> Result<()> {
                 path: info.path.display().to_string(),
                 repo_name: info.repo_name.clone(),
                 created_at: info.created_at,
+                labels: labels.clone(),
+                stack_parent: info.stack.as_ref().map(|link| link.parent.clone()),
+                problems: problem_reports(info),
+                reminder: info
+                    .reminder
+                    .as_ref()
+                    .map(|reminder| ReminderReport::new(reminder, Utc::now())),
+                note: info.note.clone(),
+                tags: info.tags.clone(),
+                last_agent: info.last_agent().cloned(),
+                session_count: json_sessions.len(),
+                codex_session_count: json_codex_sessions.len(),
                 sessions: json_sessions,
                 codex_sessions: json_codex_sessions,
             });
@@ -140,22 +197,50 @@ pub fn handle_list(json: bool) -> Result<()> {
 
         // Group worktrees by repository
         let mut grouped: BTreeMap<String, Vec<_>> = BTreeMap::new();
-        for info in state.worktrees.values() {
+        for (info, labels) in &entries {
             grouped
                 .entry(info.repo_name.clone())
                 .or_default()
-                .push(info);
+                .push((*info, labels));
         }
 
         // Display grouped by repository
         for (repo_name, mut worktrees) in grouped {
             println!("  {} {}", "📦".blue(), repo_name.bold());
 
-            // Sort worktrees within each repo by name
-            worktrees.sort_by_key(|w| &w.name);
+            // Sort worktrees within each repo by name, then nest stacked worktrees
+            // under their parents
+            worktrees.sort_by_key(|(w, _)| &w.name);
+            let nodes: Vec<StackNode> = worktrees
+                .iter()
+                .map(|(info, _)| StackNode {
+                    repo_name: &info.repo_name,
+                    name: &info.name,
+                    parent: info.stack.as_ref().map(|link| link.parent.as_str()),
+                })
+                .collect();
 
-            for info in worktrees {
-                println!("    {} {}", "•".green(), info.name.cyan());
+            for (index, depth) in stack_order(&nodes) {
+                let (info, labels) = worktrees[index];
+                if depth == 0 {
+                    println!("    {} {}", "•".green(), info.name.cyan());
+                } else {
+                    println!(
+                        "    {}{} {}",
+                        "  ".repeat(depth - 1),
+                        "└─".green(),
+                        info.name.cyan()
+                    );
+                }
+                if let Some(note) = &info.note {
+                    println!("      {} {}", "Note:".bright_black(), note);
+                }
+                if let Some(link) = &info.stack {
+                    println!("      {} {}", "Stacked on:".bright_black(), link.parent);
+                }
+                if let Some(container) = &info.container {
+                    println!("      {} {}", "Container:".bright_black(), container.digest);
+                }
                 println!("      {} {}", "Path:".bright_black(), info.path.display());
                 println!(
                     "      {} {}",
@@ -164,6 +249,49 @@ pub fn handle_list(json: bool) -> Result<()> {
                         .with_timezone(&Local)
                         .format("%Y-%m-%d %H:%M:%S")
                 );
+                if !labels.is_empty() {
+                    let names: Vec<&str> = labels.iter().map(|l| l.as_str()).collect();
+                    println!("      {} {}", "Labels:".bright_black(), names.join(", "));
+                }
+                if !info.tags.is_empty() {
+                    println!("      {} {}", "Tags:".bright_black(), info.tags.join(", "));
+                }
+                if let Some(last) = info.last_agent() {
+                    println!(
+                        "      {} {} ({})",
+                        "Last agent:".bright_black(),
+                        last.agent,
+                        format_time_ago(Some(last.at))
+                    );
+                }
+                if let Some(reminder) = &info.reminder {
+                    let now = Utc::now();
+                    if reminder.is_overdue(now) {
+                        println!(
+                            "      {} {}",
+                            "Reminder:".red().bold(),
+                            reminder.describe(now).red().bold()
+                        );
+                    } else {
+                        println!(
+                            "      {} {}",
+                            "Reminder:".bright_black(),
+                            reminder.describe(now)
+                        );
+                    }
+                }
+                for problem in problem_reports(info) {
+                    println!(
+                        "      {} {}",
+                        "⚠️ ".red(),
+                        problem.message.to_uppercase().red().bold()
+                    );
+                    println!(
+                        "        {} {}",
+                        "Resolve with:".bright_black(),
+                        problem.hint.cyan()
+                    );
+                }
 
                 // Get Claude sessions for this worktree
                 let claude_sessions = get_claude_sessions(&info.path);
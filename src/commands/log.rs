@@ -0,0 +1,61 @@
+use anyhow::Result;
+use chrono::{DateTime, Local, Utc};
+use colored::Colorize;
+
+use crate::activity;
+use crate::names::resolve_worktree;
+use crate::state::PigsState;
+
+/// Show what was done to a worktree, newest last, including the exact command
+/// each agent or task session was launched with.
+pub fn handle_log(name: Option<String>, limit: usize, json: bool) -> Result<()> {
+    let state = PigsState::load()?;
+    let info = resolve_worktree(&state, name)?;
+    let mut records =
+        activity::read_for_worktree(&info.repo_name, &info.name, DateTime::<Utc>::MIN_UTC)?;
+    let skip = records.len().saturating_sub(limit);
+    records.drain(..skip);
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&records)?);
+        return Ok(());
+    }
+
+    if records.is_empty() {
+        println!(
+            "{} No activity recorded for {}",
+            "ℹ️".blue(),
+            info.name.cyan()
+        );
+        return Ok(());
+    }
+
+    for record in &records {
+        let when = record
+            .timestamp
+            .with_timezone(&Local)
+            .format("%Y-%m-%d %H:%M:%S");
+        let user = record.user.as_deref().unwrap_or("-");
+        let summary = match (&record.command, &record.detail) {
+            (Some(command), _) => command.command_line(),
+            (None, Some(detail)) => detail.clone(),
+            (None, None) => String::new(),
+        };
+        println!(
+            "{}  {:<10} {:<13} {}",
+            when.to_string().dimmed(),
+            user,
+            record.action.cyan(),
+            summary
+        );
+        if let Some(session_id) = &record.session_id {
+            println!("{:>21}session {}", "", session_id.dimmed());
+        }
+        if let Some(command) = &record.command
+            && !command.env.is_empty()
+        {
+            println!("{:>21}env {}", "", command.env.join(", ").dimmed());
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,129 @@
+use anyhow::{Context, Result, bail};
+use colored::Colorize;
+
+use super::delete::delete_worktree_unattended;
+use crate::git::{execute_git, resolve_base_ref};
+use crate::names::resolve_worktree;
+use crate::repo_lock::lock_repo;
+use crate::state::PigsState;
+
+/// Land a worktree's branch: switch the main checkout to the base branch and
+/// merge (or squash-merge) the branch into it, then optionally delete the
+/// worktree.
+pub fn handle_merge(name: Option<String>, squash: bool, delete: bool) -> Result<()> {
+    let state = PigsState::load()?;
+    let info = resolve_worktree(&state, name)?;
+    let main = info.main_repo_path()?;
+    let repo = main
+        .to_str()
+        .context("Repository path contains invalid UTF-8")?;
+
+    let base = match &info.base_branch {
+        Some(branch) => branch.clone(),
+        None => resolve_base_ref(&main).context(
+            "Could not determine a base branch (tried origin/HEAD, main, master, develop)",
+        )?,
+    };
+    // Land on the local branch, even when the worktree was created from origin's
+    let base = base.strip_prefix("origin/").unwrap_or(&base).to_string();
+
+    let guard = lock_repo(&info.repo_name, &format!("merge '{}'", info.name))?;
+
+    if info.path.exists()
+        && !execute_git(&[
+            "-C",
+            info.path
+                .to_str()
+                .context("Worktree path contains invalid UTF-8")?,
+            "status",
+            "--porcelain",
+        ])?
+        .is_empty()
+    {
+        bail!(
+            "'{}' has uncommitted changes that would not be merged; commit or stash them first",
+            info.name
+        );
+    }
+    if !execute_git(&["-C", repo, "status", "--porcelain"])?.is_empty() {
+        bail!(
+            "The main checkout at {} has uncommitted changes; commit or stash them first",
+            main.display()
+        );
+    }
+
+    if execute_git(&["-C", repo, "branch", "--show-current"])? != base {
+        execute_git(&["-C", repo, "switch", &base])
+            .with_context(|| format!("Failed to switch the main checkout to '{base}'"))?;
+    }
+
+    println!(
+        "{} {} '{}' into '{}'...",
+        "🔀".green(),
+        if squash { "Squash-merging" } else { "Merging" },
+        info.branch.cyan(),
+        base.cyan()
+    );
+
+    let merged = if squash {
+        execute_git(&["-C", repo, "merge", "--squash", &info.branch])
+            .and_then(|_| execute_git(&["-C", repo, "commit", "--no-edit"]))
+    } else {
+        execute_git(&["-C", repo, "merge", "--no-edit", &info.branch])
+    };
+    if let Err(err) = merged {
+        let conflicts = execute_git(&["-C", repo, "diff", "--name-only", "--diff-filter=U"])
+            .unwrap_or_default();
+        if conflicts.is_empty() {
+            let _ = execute_git(&["-C", repo, "merge", "--abort"]);
+            return Err(err.context("Merge failed"));
+        }
+        eprintln!("{} Conflicts in {}:", "⚠️".yellow(), main.display());
+        for file in conflicts.lines() {
+            eprintln!("  {file}");
+        }
+        let (finish, abort) = if squash {
+            ("git commit", "git reset --merge")
+        } else {
+            ("git merge --continue", "git merge --abort")
+        };
+        bail!(
+            "Resolve the conflicts in {}, then run `{finish}` (or `{abort}`)",
+            main.display()
+        );
+    }
+
+    println!(
+        "{} Merged '{}' into '{}'",
+        "✅".green(),
+        info.branch.cyan(),
+        base.cyan()
+    );
+
+    if !delete {
+        println!(
+            "  {} Run `pigs delete {}` to remove the worktree",
+            "ℹ️".blue(),
+            info.name
+        );
+        return Ok(());
+    }
+
+    drop(guard);
+    let mut branch_deleted = delete_worktree_unattended(&info, false)?;
+    if !branch_deleted && squash {
+        // A squash merge doesn't make the branch an ancestor, but it has landed
+        branch_deleted = execute_git(&["-C", repo, "branch", "-D", &info.branch]).is_ok();
+    }
+    println!(
+        "{} Deleted worktree '{}'{}",
+        "🗑️".green(),
+        info.name.cyan(),
+        if branch_deleted {
+            " and its branch"
+        } else {
+            ""
+        }
+    );
+    Ok(())
+}
@@ -1,5 +1,10 @@
 pub mod add;
+pub mod api;
+pub mod archive;
+pub mod artifacts;
+pub mod bench;
 pub mod checkout;
+pub mod cherry_pick;
 pub mod clean;
 pub mod complete;
 pub mod complete_linear;
@@ -7,27 +12,93 @@ pub mod config;
 pub mod create;
 pub mod dashboard;
 pub mod delete;
+pub mod diff;
 pub mod dir;
+pub mod doctor;
+pub mod du;
+pub mod export;
+pub mod focus;
+pub mod gc;
+pub mod github;
+pub mod init;
 pub mod linear;
 pub mod list;
+pub mod log;
+pub mod merge;
+pub mod note;
 pub mod open;
+pub mod pool;
+pub mod pr;
+pub mod recent;
+pub mod remind;
 pub mod rename;
+pub mod replay;
+pub mod report;
+pub mod resume;
 pub mod review;
+pub mod sessions;
+pub mod stats;
+pub mod switch;
+pub mod sync;
+pub mod tag;
+pub mod tidy;
+pub mod trace;
+pub mod trust;
+pub mod tui;
+pub mod watch;
 
 pub use add::handle_add;
+pub use api::handle_api;
+pub use archive::handle_archive;
+pub use artifacts::handle_artifacts;
+pub use bench::handle_bench;
 pub use checkout::handle_checkout;
+pub use cherry_pick::handle_cherry_pick;
 pub use clean::handle_clean;
 pub use complete::handle_complete_agents;
 pub use complete::handle_complete_from;
+pub use complete::handle_complete_tasks;
 pub use complete::handle_complete_worktrees;
 pub use complete_linear::handle_complete_linear;
-pub use config::handle_config;
-pub use create::handle_create;
-pub use dashboard::handle_dashboard;
-pub use delete::handle_delete;
+pub use config::{
+    handle_config, handle_config_get, handle_config_list, handle_config_set, handle_config_unset,
+};
+pub use create::{CopyOverride, handle_create};
+pub use dashboard::{
+    handle_dashboard, handle_token_create, handle_token_list, handle_token_revoke,
+};
+pub use delete::{DirtyChanges, RemoteBranch, handle_delete};
+pub use diff::{DiffOptions, handle_diff, handle_diff_snapshots};
 pub use dir::handle_dir;
+pub use doctor::handle_doctor;
+pub use du::handle_du;
+pub use export::{handle_export, handle_import};
+pub use focus::handle_focus;
+pub use gc::handle_gc;
+pub use github::handle_github;
+pub use init::{InitShare, handle_init};
 pub use linear::handle_linear;
 pub use list::handle_list;
-pub use open::handle_open;
+pub use log::handle_log;
+pub use merge::handle_merge;
+pub use note::handle_note;
+pub use open::{OpenWith, handle_open_with};
+pub use pool::handle_pool;
+pub use pr::handle_pr;
+pub use recent::handle_recent;
+pub use remind::handle_remind;
 pub use rename::handle_rename;
+pub use replay::handle_replay_prompts;
+pub use report::handle_report;
+pub use resume::handle_resume;
 pub use review::handle_review;
+pub use sessions::handle_sessions;
+pub use stats::handle_stats;
+pub use switch::{handle_shell_init, handle_switch};
+pub use sync::handle_sync;
+pub use tag::handle_tag;
+pub use tidy::{TidyOptions, handle_tidy};
+pub use trace::handle_trace;
+pub use trust::handle_trust;
+pub use tui::handle_tui;
+pub use watch::handle_watch;
@@ -0,0 +1,42 @@
+use anyhow::{Result, bail};
+use colored::Colorize;
+
+use crate::names::resolve_worktree;
+use crate::state::PigsState;
+
+/// Show, set, or clear the free-form note on a worktree.
+pub fn handle_note(name: Option<String>, text: Option<String>, clear: bool) -> Result<()> {
+    let mut state = PigsState::load()?;
+    let info = resolve_worktree(&state, name)?;
+    let key = PigsState::make_key(&info.repo_name, &info.name);
+    let Some(entry) = state.worktrees.get_mut(&key) else {
+        bail!("Worktree '{}' not found", info.name);
+    };
+
+    if clear {
+        if entry.note.take().is_none() {
+            println!("{} No note on {}", "ℹ️".blue(), info.name.cyan());
+            return Ok(());
+        }
+        state.save()?;
+        println!("{} Cleared note on {}", "🗑️".green(), info.name.cyan());
+        return Ok(());
+    }
+
+    let Some(text) = text else {
+        match &entry.note {
+            Some(note) => println!("{} {}: {}", "📝".cyan(), info.name.cyan(), note),
+            None => println!("{} No note on {}", "ℹ️".blue(), info.name.cyan()),
+        }
+        return Ok(());
+    };
+
+    let text = text.trim();
+    if text.is_empty() {
+        bail!("The note is empty; use --clear to remove it");
+    }
+    entry.note = Some(text.to_string());
+    state.save()?;
+    println!("{} Noted on {}: {}", "📝".green(), info.name.cyan(), text);
+    Ok(())
+}
@@ -1,19 +1,68 @@
 use anyhow::{Context, Result};
 use chrono::Utc;
 use colored::Colorize;
+use std::cmp::Reverse;
+use std::io::IsTerminal;
 use std::process::{Command, Stdio};
+use uuid::Uuid;
 
-use crate::git::{get_current_branch, get_repo_name, is_base_branch, is_in_worktree};
-use crate::input::{drain_stdin, get_command_arg, is_piped_input, smart_confirm, smart_select};
-use crate::state::{PigsState, WorktreeInfo};
+use super::focus::focused_worktree;
+use crate::activity::{self, LaunchCommand};
+use crate::container;
+use crate::devenv;
+use crate::git::{get_current_branch, get_repo_name, is_base_branch, is_dirty, is_in_worktree};
+use crate::hooks::{self, Hook};
+use crate::input::{
+    drain_stdin, get_command_arg, is_piped_input, smart_confirm, smart_fuzzy_select,
+};
+use crate::launch;
+use crate::names;
+use crate::recorder;
+use crate::state::{PigsState, RepoConfig, WorktreeInfo};
 use crate::utils::{prepare_agent_command, sanitize_branch_name};
+use crate::webhooks::{self, WebhookEvent};
+
+/// What `pigs open` starts in the worktree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpenWith {
+    Agent,
+    /// An interactive shell, in the foreground
+    Shell,
+    /// The configured editor, opening the worktree directory
+    Editor,
+}
+
+impl OpenWith {
+    pub fn from_flags(shell: bool, editor: bool) -> Self {
+        if shell {
+            Self::Shell
+        } else if editor {
+            Self::Editor
+        } else {
+            Self::Agent
+        }
+    }
+}
 
 pub fn handle_open(
     name: Option<String>,
     selected_agent: Option<String>,
     agent_args: Vec<String>,
+) -> Result<()> {
+    handle_open_with(name, selected_agent, agent_args, OpenWith::Agent, false)
+}
+
+/// `record` runs the agent behind a PTY that saves its transcript; the
+/// `record_sessions` setting turns that on for every open.
+pub fn handle_open_with(
+    name: Option<String>,
+    selected_agent: Option<String>,
+    agent_args: Vec<String>,
+    open_with: OpenWith,
+    record: bool,
 ) -> Result<()> {
     let mut state = PigsState::load()?;
+    let record = record || state.record_sessions;
 
     // Check if current path is a worktree when no name is provided
     // Note: base branches (main/master/develop) are not considered worktrees
@@ -82,6 +131,15 @@ pub fn handle_open(
                         path: current_dir.clone(),
                         repo_name: repo_name.clone(),
                         created_at: Utc::now(),
+                        base_branch: None,
+                        stack: None,
+                        reminder: None,
+                        container: None,
+                        artifacts: Vec::new(),
+                        note: None,
+                        tags: Vec::new(),
+                        agent_history: Vec::new(),
+                        repo_root: crate::git::main_checkout(&current_dir),
                     },
                 );
                 state.save()?;
@@ -95,6 +153,11 @@ pub fn handle_open(
                 );
             }
 
+            let worktree_info = state.worktrees[&key].clone();
+            if open_with != OpenWith::Agent {
+                return open_in(&worktree_info, open_with);
+            }
+
             // Launch agent in current directory
             let (program, mut args) =
                 prepare_agent_command(&current_dir, selected_agent.as_deref())?;
@@ -110,9 +173,7 @@ pub fn handle_open(
                 cmd.stdin(Stdio::null());
             }
 
-            let status = cmd.status().context("Failed to launch agent")?;
-
-            if !status.success() {
+            if !run_agent(&worktree_info, &program, cmd, record, "open")? {
                 anyhow::bail!("Agent exited with error");
             }
 
@@ -129,27 +190,37 @@ pub fn handle_open(
 
     // Determine which worktree to open
     let (_key, worktree_info) = if let Some(n) = target_name {
-        // Find worktree by name across all projects
-        state
-            .worktrees
-            .iter()
-            .find(|(_, w)| w.name == n)
-            .map(|(k, w)| (k.clone(), w.clone()))
-            .context(format!("Worktree '{n}' not found"))?
+        names::find_worktree(&state, &n)?
+    } else if let Some(info) = focused_worktree(&state) {
+        (PigsState::make_key(&info.repo_name, &info.name), info)
     } else {
-        // Interactive selection - show repo/name format
-        let worktree_list: Vec<(String, WorktreeInfo)> = state
+        // Interactive selection - most recently active first, marking the
+        // worktrees with uncommitted changes
+        let mut worktree_list: Vec<(String, WorktreeInfo, bool)> = state
             .worktrees
             .iter()
-            .map(|(k, v)| (k.clone(), v.clone()))
+            .map(|(k, v)| (k.clone(), v.clone(), is_dirty(&v.path)))
             .collect();
+        worktree_list.sort_by_cached_key(|(_, info, _)| Reverse(activity::last_activity(info)));
 
-        let selection = smart_select("Select a worktree to open", &worktree_list, |(_, info)| {
-            format!("{}/{}", info.repo_name, info.name)
-        })?;
+        let selection = smart_fuzzy_select(
+            "Select a worktree to open",
+            &worktree_list,
+            |(_, info, dirty)| {
+                let label = format!("{}/{}", info.repo_name, info.name);
+                if *dirty {
+                    format!("{label} (uncommitted changes)")
+                } else {
+                    label
+                }
+            },
+        )?;
 
         match selection {
-            Some(idx) => worktree_list[idx].clone(),
+            Some(idx) => {
+                let (key, info, _) = worktree_list.swap_remove(idx);
+                (key, info)
+            }
             None => anyhow::bail!(
                 "Interactive selection not available in non-interactive mode. Please specify a worktree name."
             ),
@@ -164,6 +235,9 @@ pub fn handle_open(
         worktree_info.repo_name,
         worktree_name.cyan()
     );
+    if open_with != OpenWith::Agent {
+        return open_in(&worktree_info, open_with);
+    }
 
     // Change to worktree directory and launch Claude
     std::env::set_current_dir(&worktree_info.path).context("Failed to change directory")?;
@@ -184,11 +258,115 @@ pub fn handle_open(
         cmd.stdin(Stdio::null());
     }
 
-    let status = cmd.status().context("Failed to launch agent")?;
-
-    if !status.success() {
+    if !run_agent(&worktree_info, &program, cmd, record, "open")? {
         anyhow::bail!("Agent exited with error");
     }
 
     Ok(())
 }
+
+/// Run a shell or the editor for the worktree in the foreground, resolved the
+/// same way as the dashboard's Shell and Editor buttons.
+fn open_in(info: &WorktreeInfo, open_with: OpenWith) -> Result<()> {
+    let state = PigsState::load_with_local_overrides()?;
+    let (mut cmd, action, what) = match open_with {
+        OpenWith::Shell => (launch::shell(info, state.shell)?, "open_shell", "shell"),
+        OpenWith::Editor => (
+            launch::editor(&info.path, state.editor)?,
+            "open_editor",
+            "editor",
+        ),
+        OpenWith::Agent => unreachable!("the agent is launched by handle_open_with"),
+    };
+    activity::record_as(
+        &activity::local_user(),
+        &info.repo_name,
+        &info.name,
+        action,
+        None,
+    );
+
+    let status = cmd
+        .status()
+        .with_context(|| format!("Failed to launch {what}"))?;
+    if !status.success() {
+        anyhow::bail!("The {what} exited with error");
+    }
+    Ok(())
+}
+
+/// Run the agent in the foreground with the worktree's direnv/mise
+/// environment once the `pre_open` hooks pass, recording the command it runs
+/// and notifying webhooks when it starts and exits under `action`. With
+/// `record`, its transcript is saved too. Returns whether it exited
+/// successfully.
+pub(super) fn run_agent(
+    info: &WorktreeInfo,
+    program: &str,
+    mut cmd: Command,
+    record: bool,
+    action: &str,
+) -> Result<bool> {
+    let repo_hooks = RepoConfig::load(&info.path)?.hooks;
+    hooks::run(Hook::PreOpen, info, &info.path, &repo_hooks, false)
+        .context("Not launching the agent")?;
+    let env_changes = devenv::activation_env(&info.path);
+    let mut args: Vec<String> = cmd
+        .get_args()
+        .map(|arg| arg.to_string_lossy().into_owned())
+        .collect();
+    activity::record_agent_use(&info.repo_name, &info.name, program, &args);
+    let mut program = program.to_string();
+    if let Some(image) = &info.container {
+        // Same environment and stdin, but the agent runs inside the container
+        let tty = std::io::stdin().is_terminal();
+        (program, args) = container::wrap_command(info, image, &program, &args, &env_changes, tty)?;
+        let mut wrapped = Command::new(&program);
+        wrapped.args(&args).envs(std::env::vars());
+        if !tty {
+            wrapped.stdin(Stdio::null());
+        }
+        println!(
+            "{} Running in container {}",
+            "🐳".blue(),
+            image.digest.dimmed()
+        );
+        cmd = wrapped;
+    }
+    devenv::apply_changes(&mut cmd, &env_changes);
+    let session_id = record.then(|| Uuid::new_v4().to_string());
+    activity::record_launch(
+        &activity::local_user(),
+        &info.repo_name,
+        &info.name,
+        action,
+        Some(program.clone()),
+        session_id.as_deref(),
+        &LaunchCommand::new(&program, &args, &env_changes),
+    );
+
+    let mut started = WebhookEvent::new("session.started", info);
+    started.detail = Some(program.clone());
+    started.session_id = session_id.clone();
+    webhooks::emit(started);
+
+    let exit_code = match &session_id {
+        Some(id) => {
+            println!("{} Recording session {}", "⏺️".red(), id.dimmed());
+            let exit_code = recorder::run(info, &program, &args, &env_changes, id)?;
+            Some(i64::from(exit_code))
+        }
+        None => cmd
+            .status()
+            .context("Failed to launch agent")?
+            .code()
+            .map(i64::from),
+    };
+
+    let mut exited = WebhookEvent::new("session.exited", info);
+    exited.detail = Some(program);
+    exited.session_id = session_id;
+    exited.exit_code = exit_code;
+    webhooks::emit(exited);
+    Ok(exit_code == Some(0))
+}
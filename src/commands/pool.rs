@@ -0,0 +1,55 @@
+use anyhow::Result;
+use colored::Colorize;
+
+use crate::pool;
+use crate::state::PigsState;
+
+/// Show the worktree pool, or top it up or empty it.
+pub fn handle_pool(fill: bool, drain: bool) -> Result<()> {
+    if drain {
+        let count = pool::drain()?;
+        println!(
+            "{} Removed {} pooled worktree{}",
+            "🧹".green(),
+            count,
+            if count == 1 { "" } else { "s" }
+        );
+        return Ok(());
+    }
+
+    let state = PigsState::load()?;
+    if fill {
+        if state.worktree_pool.is_empty() {
+            println!(
+                "{} No pools configured. Set one with: {}",
+                "💡".cyan(),
+                "pigs config set worktree_pool '{\"/path/to/repo\": 2}'".cyan()
+            );
+            return Ok(());
+        }
+        for (repo, size) in &state.worktree_pool {
+            println!("{} Filling pool for {}...", "⏳".cyan(), repo.display());
+            let created = pool::fill(repo, *size)?;
+            println!("  {} Created {}", "✅".green(), created);
+        }
+        return Ok(());
+    }
+
+    for (repo, size) in &state.worktree_pool {
+        println!("{} {} (keeps {})", "📦".cyan(), repo.display(), size);
+    }
+    if state.pooled_worktrees.is_empty() {
+        println!("{} No pooled worktrees", "✨".green());
+        return Ok(());
+    }
+    for pooled in &state.pooled_worktrees {
+        println!(
+            "  {} {} from {} — {}",
+            "⚡".green(),
+            pooled.repo_name.cyan(),
+            pooled.base_branch,
+            pooled.path.display()
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,135 @@
+use std::process::Command;
+
+use anyhow::{Context, Result, bail};
+use colored::Colorize;
+
+use crate::claude::get_claude_sessions;
+use crate::git::execute_git;
+use crate::linear;
+use crate::names::resolve_worktree;
+use crate::network;
+use crate::state::{PigsState, WorktreeInfo};
+
+/// Push a worktree's branch and open a GitHub pull request for it with `gh`.
+/// The reverse of `pigs checkout <pr-number>`.
+pub fn handle_pr(
+    name: Option<String>,
+    title: Option<String>,
+    session_summary: bool,
+    draft: bool,
+) -> Result<()> {
+    network::ensure_online("GitHub")?;
+    let state = PigsState::load()?;
+    let info = resolve_worktree(&state, name)?;
+    let path = info
+        .path
+        .to_str()
+        .context("Worktree path contains invalid UTF-8")?;
+
+    let title = match title {
+        Some(title) => title,
+        None => default_title(&info.branch),
+    };
+    let body = if session_summary {
+        get_claude_sessions(&info.path)
+            .into_iter()
+            .find_map(|session| session.last_assistant_message)
+            .context("No agent session with a final reply found for this worktree")?
+    } else {
+        String::new()
+    };
+
+    println!(
+        "{} Pushing '{}' to origin...",
+        "⬆️".green(),
+        info.branch.cyan()
+    );
+    execute_git(&["-C", path, "push", "--set-upstream", "origin", &info.branch])
+        .context("Failed to push the branch")?;
+
+    let mut args = vec![
+        "pr".to_string(),
+        "create".to_string(),
+        "--head".to_string(),
+        info.branch.clone(),
+        "--title".to_string(),
+        title.clone(),
+        "--body".to_string(),
+        body,
+    ];
+    if let Some(base) = pr_base(&state, &info) {
+        args.push("--base".to_string());
+        args.push(base);
+    }
+    if draft {
+        args.push("--draft".to_string());
+    }
+
+    let output = Command::new("gh")
+        .args(&args)
+        .current_dir(&info.path)
+        .output()
+        .context("Failed to run 'gh'. Install the GitHub CLI to create pull requests")?;
+    if !output.status.success() {
+        bail!(
+            "gh pr create failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    let url = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+    println!("{} Opened pull request: {}", "✅".green(), title.cyan());
+    println!("  {url}");
+    Ok(())
+}
+
+/// The branch the pull request targets: the parent's branch for a stacked
+/// worktree, else the branch it was created from. `None` lets `gh` use the
+/// repository's default branch.
+fn pr_base(state: &PigsState, info: &WorktreeInfo) -> Option<String> {
+    if let Some(link) = &info.stack
+        && let Some(parent) = state
+            .worktrees
+            .get(&PigsState::make_key(&info.repo_name, &link.parent))
+    {
+        return Some(parent.branch.clone());
+    }
+    let base = info.base_branch.as_deref()?;
+    Some(base.strip_prefix("origin/").unwrap_or(base).to_string())
+}
+
+/// A title from the Linear issue the branch is named after, when it can be
+/// looked up, else from the branch name itself.
+fn default_title(branch: &str) -> String {
+    let slug = branch.rsplit('/').next().unwrap_or(branch);
+    let issue_id = linear_identifier(slug);
+
+    if let Some(id) = &issue_id
+        && !network::is_offline()
+        && std::env::var("LINEAR_API_KEY").is_ok()
+        && let Ok(issue) = linear::fetch_issue(id)
+    {
+        return format!("[{id}] {}", issue.title);
+    }
+
+    let words = match &issue_id {
+        Some(id) => slug[id.len()..].trim_start_matches(['-', '_']),
+        None => slug,
+    };
+    let mut title = words.replace(['-', '_'], " ");
+    if let Some(first) = title.get(..1) {
+        title.replace_range(..1, &first.to_uppercase());
+    }
+    match issue_id {
+        Some(id) if title.is_empty() => id,
+        Some(id) => format!("[{id}] {title}"),
+        None => title,
+    }
+}
+
+/// `ENG-123` from a branch slug such as `eng-123-fix-login`.
+fn linear_identifier(slug: &str) -> Option<String> {
+    let mut parts = slug.splitn(3, '-');
+    let id = format!("{}-{}", parts.next()?, parts.next()?).to_uppercase();
+    linear::is_linear_task_id(&id).then_some(id)
+}
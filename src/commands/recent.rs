@@ -0,0 +1,45 @@
+use std::cmp::Reverse;
+
+use anyhow::Result;
+use chrono::Local;
+use colored::Colorize;
+
+use super::list::format_time_ago;
+use crate::activity;
+use crate::state::PigsState;
+
+/// List the `limit` most recently worked-on worktrees across all repositories,
+/// newest first, by their latest commit or Claude session.
+pub fn handle_recent(limit: usize) -> Result<()> {
+    let state = PigsState::load()?;
+    if state.worktrees.is_empty() {
+        println!("{} No active worktrees", "📭".yellow());
+        return Ok(());
+    }
+
+    let mut worktrees: Vec<_> = state
+        .worktrees
+        .values()
+        .map(|info| (activity::last_activity(info), info))
+        .collect();
+    worktrees.sort_by_key(|(last_activity, info)| {
+        (Reverse(*last_activity), &info.repo_name, &info.name)
+    });
+
+    println!("{} Recently active worktrees:", "🕒".cyan());
+    for (last_activity, info) in worktrees.into_iter().take(limit) {
+        println!(
+            "  {} {}/{} {}",
+            "•".green(),
+            info.repo_name,
+            info.name.cyan(),
+            format!(
+                "{} ({})",
+                format_time_ago(Some(last_activity)),
+                last_activity.with_timezone(&Local).format("%Y-%m-%d %H:%M")
+            )
+            .bright_black()
+        );
+    }
+    Ok(())
+}
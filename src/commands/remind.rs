@@ -0,0 +1,74 @@
+use anyhow::{Result, bail};
+use chrono::{Local, Utc};
+use colored::Colorize;
+
+use crate::names::resolve_worktree;
+use crate::reminders::{Reminder, parse_due, parse_offset};
+use crate::state::PigsState;
+
+pub fn handle_remind(
+    name: Option<String>,
+    note: Option<String>,
+    offset: Option<String>,
+    at: Option<String>,
+    clear: bool,
+) -> Result<()> {
+    let mut state = PigsState::load()?;
+    let info = resolve_worktree(&state, name)?;
+    let key = PigsState::make_key(&info.repo_name, &info.name);
+    let now = Utc::now();
+
+    let due_at = match (offset, at) {
+        (Some(offset), _) => Some(now + parse_offset(&offset)?),
+        (None, Some(at)) => Some(parse_due(&at)?),
+        (None, None) => None,
+    };
+
+    let Some(entry) = state.worktrees.get_mut(&key) else {
+        bail!("Worktree '{}' not found", info.name);
+    };
+
+    if clear {
+        if entry.reminder.take().is_none() {
+            println!("{} No reminder set for {}", "ℹ️".blue(), info.name.cyan());
+            return Ok(());
+        }
+        state.save()?;
+        println!("{} Cleared reminder for {}", "🗑️".green(), info.name.cyan());
+        return Ok(());
+    }
+
+    let Some(due_at) = due_at else {
+        if note.is_some() {
+            bail!("Pass --in or --at to say when the reminder is due");
+        }
+        match &entry.reminder {
+            Some(reminder) => println!(
+                "{} {}: {}",
+                "⏰".yellow(),
+                info.name.cyan(),
+                reminder.describe(now)
+            ),
+            None => println!("{} No reminder set for {}", "ℹ️".blue(), info.name.cyan()),
+        }
+        return Ok(());
+    };
+
+    let reminder = Reminder {
+        due_at,
+        note: note.filter(|note| !note.trim().is_empty()),
+        notified: false,
+    };
+    let summary = reminder.describe(now);
+    entry.reminder = Some(reminder);
+    state.save()?;
+
+    println!(
+        "{} Reminder for {} set for {} ({})",
+        "⏰".green(),
+        info.name.cyan(),
+        due_at.with_timezone(&Local).format("%Y-%m-%d %H:%M"),
+        summary
+    );
+    Ok(())
+}
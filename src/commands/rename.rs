@@ -1,10 +1,19 @@
 use anyhow::{Context, Result, bail};
 use colored::Colorize;
 
-use crate::git;
-use crate::state::PigsState;
+use crate::artifacts;
+use crate::git::{self, execute_git};
+use crate::state::{PigsState, WorktreeInfo};
 
-pub fn handle_rename(old_name: String, new_name: String) -> Result<()> {
+/// Rename a worktree's entry. With `rename_branch`, its git branch is renamed
+/// to the new name too, along with the branch it tracks where possible. With
+/// `move_dir`, its directory moves to where the new name would be created.
+pub fn handle_rename(
+    old_name: String,
+    new_name: String,
+    rename_branch: bool,
+    move_dir: bool,
+) -> Result<()> {
     let repo = git::get_repo_name()?;
     let mut state = PigsState::load()?;
 
@@ -28,9 +37,39 @@ pub fn handle_rename(old_name: String, new_name: String) -> Result<()> {
         .remove(&old_key)
         .context("Failed to get worktree data")?;
 
+    if move_dir {
+        move_worktree_dir(&state, &mut worktree_data, &new_name)?;
+    }
+
+    if rename_branch && worktree_data.branch != new_name {
+        let old_branch = std::mem::replace(&mut worktree_data.branch, new_name.clone());
+        rename_git_branch(&worktree_data, &old_branch)?;
+        // Worktrees branched from the old name now sync onto the new one
+        for other in state.worktrees.values_mut() {
+            if other.repo_name == repo && other.base_branch.as_deref() == Some(old_branch.as_str())
+            {
+                other.base_branch = Some(new_name.clone());
+            }
+        }
+        println!(
+            "{} Renamed branch {} to {}",
+            "✓".green(),
+            old_branch.cyan(),
+            new_name.cyan()
+        );
+    }
+
     // Update the name field in the worktree info
+    let old_artifacts = artifacts::worktree_dir(&worktree_data)?;
     worktree_data.name = new_name.clone();
+    if old_artifacts.exists() {
+        std::fs::rename(&old_artifacts, artifacts::worktree_dir(&worktree_data)?)
+            .context("Failed to move the worktree's saved artifacts")?;
+    }
 
+    if state.focus.as_deref() == Some(old_key.as_str()) {
+        state.focus = Some(new_key.clone());
+    }
     state.worktrees.insert(new_key, worktree_data);
     state.save()?;
 
@@ -46,3 +85,96 @@ pub fn handle_rename(old_name: String, new_name: String) -> Result<()> {
 
     Ok(())
 }
+
+/// Move the worktree's directory to where `worktree_dir_template` puts
+/// `new_name`, with `git worktree move` so git keeps track of it.
+fn move_worktree_dir(state: &PigsState, info: &mut WorktreeInfo, new_name: &str) -> Result<()> {
+    let old_path = info
+        .path
+        .to_str()
+        .context("Worktree path contains invalid UTF-8")?
+        .to_string();
+    // The main checkout is always listed first
+    let listed = git::parse_worktree_list(&execute_git(&[
+        "-C",
+        &old_path,
+        "worktree",
+        "list",
+        "--porcelain",
+    ])?);
+    let repo_root = &listed
+        .first()
+        .context("Failed to find the repository's main checkout")?
+        .path;
+    let new_path = state.worktree_dir(repo_root, &info.repo_name, new_name)?;
+    if new_path == info.path {
+        return Ok(());
+    }
+    if new_path.exists() {
+        bail!("Directory '{}' already exists", new_path.display());
+    }
+    if let Some(parent) = new_path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    let new_path_str = new_path
+        .to_str()
+        .context("Worktree path contains invalid UTF-8")?;
+    execute_git(&[
+        "-C",
+        &repo_root.to_string_lossy(),
+        "worktree",
+        "move",
+        &old_path,
+        new_path_str,
+    ])
+    .context("Failed to move the worktree directory")?;
+    println!("{} Moved directory to {}", "✓".green(), new_path.display());
+    info.path = new_path;
+    Ok(())
+}
+
+/// Run `git branch -m` in the worktree, moving `old_branch` to `info.branch`.
+/// A branch that tracked a remote branch of the same name is pointed at the
+/// remote branch with the new name when one exists, and otherwise stops
+/// tracking, so it isn't pushed back to the old name.
+fn rename_git_branch(info: &WorktreeInfo, old_branch: &str) -> Result<()> {
+    let path = info
+        .path
+        .to_str()
+        .context("Worktree path is not valid UTF-8")?;
+    let remote = execute_git(&["-C", path, "config", &format!("branch.{old_branch}.remote")]).ok();
+    let merge = execute_git(&["-C", path, "config", &format!("branch.{old_branch}.merge")]).ok();
+
+    execute_git(&["-C", path, "branch", "-m", old_branch, &info.branch])
+        .with_context(|| format!("Failed to rename branch '{old_branch}'"))?;
+
+    let Some(remote) = remote.filter(|remote| remote != ".") else {
+        return Ok(());
+    };
+    if merge.as_deref() != Some(format!("refs/heads/{old_branch}").as_str()) {
+        return Ok(());
+    }
+    let renamed_upstream = format!("{remote}/{}", info.branch);
+    let has_renamed_upstream = execute_git(&[
+        "-C",
+        path,
+        "show-ref",
+        "--verify",
+        "--quiet",
+        &format!("refs/remotes/{renamed_upstream}"),
+    ])
+    .is_ok();
+    if has_renamed_upstream {
+        execute_git(&["-C", path, "branch", "-u", &renamed_upstream, &info.branch])?;
+        println!("{} Now tracking {}", "✓".green(), renamed_upstream.cyan());
+    } else {
+        execute_git(&["-C", path, "branch", "--unset-upstream", &info.branch])?;
+        println!(
+            "{} Stopped tracking {remote}/{old_branch}; publish the new name with {}",
+            "💡".yellow(),
+            format!("git push -u {remote} {}", info.branch).cyan()
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,198 @@
+use std::io::{Read, Write};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result, bail};
+use colored::Colorize;
+use portable_pty::{CommandBuilder, PtySize, native_pty_system};
+
+use crate::dashboard::{TerminalSize, scrub_terminal_queries};
+use crate::names::resolve_worktree;
+use crate::prompts;
+use crate::state::{PigsState, WorktreeInfo};
+use crate::utils::prepare_agent_command;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+type SharedWriter = Arc<Mutex<Box<dyn Write + Send>>>;
+
+/// Re-send the prompts recorded for `source` to a fresh agent in `target`.
+/// The first prompt is passed as the agent's initial argument (like
+/// `pigs linear`); each later prompt is typed once the agent has been quiet
+/// for `idle_secs`.
+pub fn handle_replay_prompts(
+    source: String,
+    target: String,
+    selected_agent: Option<String>,
+    idle_secs: u64,
+    dry_run: bool,
+) -> Result<()> {
+    let state = PigsState::load()?;
+    let source = resolve_worktree(&state, Some(source))?;
+    let target = resolve_worktree(&state, Some(target))?;
+
+    let recorded = prompts::read_for_worktree(&source.repo_name, &source.name)?;
+    if recorded.is_empty() {
+        bail!(
+            "No prompts recorded for '{}'. Prompts are recorded when sent from the dashboard or `pigs linear`.",
+            source.name
+        );
+    }
+    let sequence: Vec<String> = recorded.into_iter().map(|entry| entry.prompt).collect();
+
+    if dry_run {
+        println!(
+            "{} {} prompt(s) would be replayed from '{}' into '{}':",
+            "🔁".cyan(),
+            sequence.len(),
+            source.name.cyan(),
+            target.name.cyan()
+        );
+        for (index, prompt) in sequence.iter().enumerate() {
+            println!("  {} {}", format!("{}.", index + 1).bright_black(), prompt);
+        }
+        return Ok(());
+    }
+
+    if !target.path.exists() {
+        bail!(
+            "Worktree directory {} no longer exists. Run 'pigs clean' to prune it.",
+            target.path.display()
+        );
+    }
+
+    println!(
+        "{} Replaying {} prompt(s) from '{}' into '{}'...",
+        "🔁".cyan(),
+        sequence.len(),
+        source.name.cyan(),
+        target.name.cyan()
+    );
+
+    replay(&target, selected_agent.as_deref(), &sequence, idle_secs)?;
+
+    println!();
+    println!(
+        "{} Replayed {} prompt(s) into '{}'",
+        "✅".green(),
+        sequence.len(),
+        target.name.cyan()
+    );
+    println!(
+        "  {} To continue the conversation, run: {} {}",
+        "💡".cyan(),
+        "pigs open".cyan(),
+        target.name.cyan()
+    );
+    Ok(())
+}
+
+fn replay(
+    target: &WorktreeInfo,
+    selected_agent: Option<&str>,
+    sequence: &[String],
+    idle_secs: u64,
+) -> Result<()> {
+    let size = TerminalSize::default();
+    let pair = native_pty_system().openpty(PtySize {
+        rows: size.rows,
+        cols: size.cols,
+        pixel_width: 0,
+        pixel_height: 0,
+    })?;
+
+    let (program, mut args) = prepare_agent_command(&target.path, selected_agent)
+        .context("Failed to resolve agent command")?;
+    args.push(sequence[0].clone());
+    let mut builder = CommandBuilder::new(program);
+    for arg in args {
+        builder.arg(arg);
+    }
+    builder.cwd(target.path.clone());
+    for (key, value) in std::env::vars() {
+        builder.env(&key, value);
+    }
+
+    let mut child = pair
+        .slave
+        .spawn_command(builder)
+        .context("Failed to spawn agent")?;
+    drop(pair.slave);
+    prompts::record(&target.repo_name, &target.name, None, &sequence[0]);
+
+    let mut reader = pair
+        .master
+        .try_clone_reader()
+        .context("Failed to clone PTY reader")?;
+    let writer: SharedWriter = Arc::new(Mutex::new(
+        pair.master
+            .take_writer()
+            .context("Failed to capture PTY writer")?,
+    ));
+    let last_output = Arc::new(Mutex::new(Instant::now()));
+
+    let reader_writer = writer.clone();
+    let reader_last_output = last_output.clone();
+    std::thread::spawn(move || {
+        let mut buf = [0u8; 4096];
+        let mut stdout = std::io::stdout();
+        while let Ok(n) = reader.read(&mut buf) {
+            if n == 0 {
+                break;
+            }
+            let (cleaned, responses) = scrub_terminal_queries(&buf[..n], size);
+            for response in responses {
+                let _ = write_to_agent(&reader_writer, &response);
+            }
+            let _ = stdout.write_all(&cleaned);
+            let _ = stdout.flush();
+            *reader_last_output.lock().unwrap() = Instant::now();
+        }
+    });
+
+    let idle = Duration::from_secs(idle_secs);
+    for prompt in &sequence[1..] {
+        if wait_for_idle(&last_output, idle, &mut *child)? {
+            bail!("Agent exited before the replay finished");
+        }
+        let mut payload = prompt.clone().into_bytes();
+        payload.push(b'\n');
+        write_to_agent(&writer, &payload)?;
+        // Give the agent a full idle window to start responding
+        *last_output.lock().unwrap() = Instant::now();
+        prompts::record(&target.repo_name, &target.name, None, prompt);
+    }
+    if wait_for_idle(&last_output, idle, &mut *child)? {
+        return Ok(());
+    }
+
+    // The agent keeps its own history, so `pigs open` can pick up from here
+    let _ = child.kill();
+    let _ = child.wait();
+    Ok(())
+}
+
+fn write_to_agent(writer: &SharedWriter, payload: &[u8]) -> Result<()> {
+    let mut writer = writer.lock().unwrap();
+    writer.write_all(payload)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Block until the agent has produced no output for `idle`. Returns `true`
+/// if the agent exited instead.
+fn wait_for_idle(
+    last_output: &Mutex<Instant>,
+    idle: Duration,
+    child: &mut (dyn portable_pty::Child + Send + Sync),
+) -> Result<bool> {
+    loop {
+        if child.try_wait()?.is_some() {
+            return Ok(true);
+        }
+        if last_output.lock().unwrap().elapsed() >= idle {
+            return Ok(false);
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
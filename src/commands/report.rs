@@ -0,0 +1,294 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Duration, Utc};
+use colored::Colorize;
+
+use crate::claude::get_claude_sessions;
+use crate::codex;
+use crate::git::{execute_git, resolve_base_ref};
+use crate::prompts;
+use crate::state::{PigsState, WorktreeInfo};
+
+/// One column set per worktree in the comparison table.
+struct ReportRow {
+    worktree: String,
+    branch: String,
+    agents: Vec<String>,
+    models: Vec<String>,
+    duration: Option<Duration>,
+    diffstat: Option<String>,
+    verification: Vec<String>,
+    prompts: usize,
+    input_tokens: u64,
+    output_tokens: u64,
+    cost_usd: Option<f64>,
+}
+
+const HEADERS: [&str; 10] = [
+    "Worktree",
+    "Branch",
+    "Agent",
+    "Model",
+    "Duration",
+    "Diff",
+    "Verification",
+    "Prompts",
+    "Tokens (in/out)",
+    "Cost",
+];
+
+/// Compare the worktrees of an experiment group: the worktree named `group`
+/// plus every worktree named `group-*` (e.g. `parser-claude`, `parser-codex`).
+pub fn handle_report(group: String, html: bool, output: Option<PathBuf>) -> Result<()> {
+    let state = PigsState::load()?;
+
+    let mut members: Vec<&WorktreeInfo> = state
+        .worktrees
+        .values()
+        .filter(|info| in_group(&info.name, &group))
+        .collect();
+    if members.is_empty() {
+        bail!(
+            "No worktrees found for group '{group}'. Name worktrees '{group}-<variant>' to group them."
+        );
+    }
+    members.sort_by(|a, b| {
+        a.repo_name
+            .cmp(&b.repo_name)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+
+    let mut rows = Vec::with_capacity(members.len());
+    for info in members {
+        rows.push(collect_row(info)?);
+    }
+
+    let generated_at = Utc::now();
+    let report = if html {
+        render_html(&group, &rows, generated_at)
+    } else {
+        render_markdown(&group, &rows, generated_at)
+    };
+
+    match output {
+        Some(path) => {
+            std::fs::write(&path, report)
+                .with_context(|| format!("Failed to write report to {}", path.display()))?;
+            println!(
+                "{} Wrote report for '{}' to {}",
+                "📊".green(),
+                group.cyan(),
+                path.display()
+            );
+        }
+        None => print!("{report}"),
+    }
+
+    Ok(())
+}
+
+fn in_group(name: &str, group: &str) -> bool {
+    name == group
+        || name
+            .strip_prefix(group)
+            .is_some_and(|rest| rest.starts_with('-'))
+}
+
+fn collect_row(info: &WorktreeInfo) -> Result<ReportRow> {
+    let mut agents = Vec::new();
+    let mut models = BTreeSet::new();
+    let mut started: Option<DateTime<Utc>> = None;
+    let mut finished: Option<DateTime<Utc>> = None;
+    let mut input_tokens = 0;
+    let mut output_tokens = 0;
+    let mut cost_usd = 0.0;
+
+    let mut widen = |start: Option<DateTime<Utc>>, end: Option<DateTime<Utc>>| {
+        if let Some(start) = start {
+            started = Some(started.map_or(start, |current| current.min(start)));
+        }
+        if let Some(end) = end {
+            finished = Some(finished.map_or(end, |current| current.max(end)));
+        }
+    };
+
+    let claude_sessions = get_claude_sessions(&info.path);
+    if !claude_sessions.is_empty() {
+        agents.push("claude".to_string());
+    }
+    for session in &claude_sessions {
+        models.extend(session.model.clone());
+        widen(
+            session.first_timestamp,
+            session.last_activity.or(session.last_timestamp),
+        );
+        input_tokens += session.input_tokens;
+        output_tokens += session.output_tokens;
+        cost_usd += session.cost_usd;
+    }
+
+    let (codex_sessions, _) = codex::recent_sessions(&info.path, usize::MAX)?;
+    if !codex_sessions.is_empty() {
+        agents.push("codex".to_string());
+    }
+    for session in &codex_sessions {
+        models.extend(session.model.clone());
+        widen(session.started_at, session.last_timestamp);
+        input_tokens += session.input_tokens;
+        output_tokens += session.output_tokens;
+    }
+
+    let prompts = prompts::read_for_worktree(&info.repo_name, &info.name)?.len();
+
+    Ok(ReportRow {
+        worktree: info.name.clone(),
+        branch: info.branch.clone(),
+        agents,
+        models: models.into_iter().collect(),
+        duration: started.zip(finished).map(|(start, end)| end - start),
+        diffstat: diffstat(&info.path),
+        verification: verification(info),
+        prompts,
+        input_tokens,
+        output_tokens,
+        cost_usd: (cost_usd > 0.0).then_some(cost_usd),
+    })
+}
+
+/// Outcome of the latest recorded run of each task, e.g. `test ✅`.
+fn verification(info: &WorktreeInfo) -> Vec<String> {
+    let mut latest: BTreeMap<&str, bool> = BTreeMap::new();
+    for run in &info.artifacts {
+        latest.insert(&run.task, run.success);
+    }
+    latest
+        .into_iter()
+        .map(|(task, success)| format!("{task} {}", if success { "✅" } else { "❌" }))
+        .collect()
+}
+
+/// Summarize changes against the merge-base with the base branch, including
+/// uncommitted edits, e.g. `3 files, +40/-2`.
+fn diffstat(worktree_path: &Path) -> Option<String> {
+    let path = worktree_path.to_str()?;
+    let base_ref = resolve_base_ref(worktree_path)?;
+    let merge_base = execute_git(&["-C", path, "merge-base", &base_ref, "HEAD"]).ok()?;
+    let shortstat = execute_git(&["-C", path, "diff", "--shortstat", &merge_base]).ok()?;
+    Some(format_shortstat(&shortstat))
+}
+
+fn format_shortstat(shortstat: &str) -> String {
+    let mut files = 0;
+    let mut added = 0;
+    let mut removed = 0;
+    for part in shortstat.split(',') {
+        let mut words = part.split_whitespace();
+        let Some(count) = words.next().and_then(|n| n.parse::<u64>().ok()) else {
+            continue;
+        };
+        match words.next() {
+            Some(word) if word.starts_with("file") => files = count,
+            Some(word) if word.starts_with("insertion") => added = count,
+            Some(word) if word.starts_with("deletion") => removed = count,
+            _ => {}
+        }
+    }
+    format!("{files} files, +{added}/-{removed}")
+}
+
+fn format_duration(duration: Duration) -> String {
+    let minutes = duration.num_minutes().max(0);
+    if minutes < 60 {
+        format!("{minutes}m")
+    } else {
+        format!("{}h {:02}m", minutes / 60, minutes % 60)
+    }
+}
+
+fn format_tokens(count: u64) -> String {
+    if count >= 1_000_000 {
+        format!("{:.1}M", count as f64 / 1_000_000.0)
+    } else if count >= 1_000 {
+        format!("{:.1}k", count as f64 / 1_000.0)
+    } else {
+        count.to_string()
+    }
+}
+
+fn cells(row: &ReportRow) -> [String; 10] {
+    let or_dash = |value: String| {
+        if value.is_empty() {
+            "—".to_string()
+        } else {
+            value
+        }
+    };
+    [
+        row.worktree.clone(),
+        row.branch.clone(),
+        or_dash(row.agents.join(", ")),
+        or_dash(row.models.join(", ")),
+        row.duration
+            .map_or_else(|| "—".to_string(), format_duration),
+        row.diffstat.clone().unwrap_or_else(|| "—".to_string()),
+        or_dash(row.verification.join(", ")),
+        row.prompts.to_string(),
+        if row.input_tokens + row.output_tokens == 0 {
+            "—".to_string()
+        } else {
+            format!(
+                "{} / {}",
+                format_tokens(row.input_tokens),
+                format_tokens(row.output_tokens)
+            )
+        },
+        row.cost_usd
+            .map_or_else(|| "—".to_string(), |cost| format!("${cost:.2}")),
+    ]
+}
+
+fn render_markdown(group: &str, rows: &[ReportRow], generated_at: DateTime<Utc>) -> String {
+    let escape = |value: &str| value.replace('|', "\\|");
+    let mut out = format!(
+        "# Agent comparison: {group}\n\nGenerated {}\n\n",
+        generated_at.format("%Y-%m-%d %H:%M UTC")
+    );
+    out.push_str(&format!("| {} |\n", HEADERS.join(" | ")));
+    out.push_str(&format!("|{}\n", " --- |".repeat(HEADERS.len())));
+    for row in rows {
+        let cells: Vec<String> = cells(row).iter().map(|cell| escape(cell)).collect();
+        out.push_str(&format!("| {} |\n", cells.join(" | ")));
+    }
+    out
+}
+
+fn render_html(group: &str, rows: &[ReportRow], generated_at: DateTime<Utc>) -> String {
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Agent comparison: {group}</title>\n<style>body{{font-family:system-ui,sans-serif;margin:2rem}}table{{border-collapse:collapse}}th,td{{border:1px solid #ddd;padding:6px 10px;text-align:left}}th{{background:#f5f5f5}}</style>\n</head>\n<body>\n<h1>Agent comparison: {group}</h1>\n<p>Generated {}</p>\n<table>\n<tr>",
+        generated_at.format("%Y-%m-%d %H:%M UTC"),
+        group = escape_html(group)
+    );
+    for header in HEADERS {
+        out.push_str(&format!("<th>{header}</th>"));
+    }
+    out.push_str("</tr>\n");
+    for row in rows {
+        out.push_str("<tr>");
+        for cell in cells(row) {
+            out.push_str(&format!("<td>{}</td>", escape_html(&cell)));
+        }
+        out.push_str("</tr>\n");
+    }
+    out.push_str("</table>\n</body>\n</html>\n");
+    out
+}
+
+fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
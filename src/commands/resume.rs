@@ -0,0 +1,73 @@
+use anyhow::{Context, Result, bail};
+use colored::Colorize;
+use std::process::Command;
+
+use super::list::{format_message_preview, format_time_ago};
+use super::open::run_agent;
+use super::sessions::worktree_sessions;
+use crate::input::smart_fuzzy_select;
+use crate::names::resolve_worktree;
+use crate::state::PigsState;
+use crate::utils::resume_agent_command;
+
+/// Resume a past Claude or Codex session in a worktree: the one given by
+/// `session`, or one picked from the worktree's sessions, newest first.
+pub fn handle_resume(name: Option<String>, session: Option<String>) -> Result<()> {
+    let state = PigsState::load()?;
+    let info = resolve_worktree(&state, name)?;
+    let mut sessions = worktree_sessions(&info.path)?;
+
+    let chosen = if let Some(id) = session {
+        // A unique prefix is enough, as with git commits
+        let mut matching = sessions.iter().filter(|s| s.id.starts_with(&id));
+        match (matching.next(), matching.next()) {
+            (Some(found), None) => found.clone(),
+            (Some(_), Some(_)) => bail!("Session id '{id}' is ambiguous; give more of it"),
+            (None, _) => bail!(
+                "Session '{id}' not found for {}. Run 'pigs sessions {}' to list them",
+                info.name,
+                info.name
+            ),
+        }
+    } else {
+        if sessions.is_empty() {
+            bail!("No agent sessions found for {}", info.name);
+        }
+        let selection = smart_fuzzy_select("Select a session to resume", &sessions, |s| {
+            format!(
+                "{} {} {} {}",
+                s.provider,
+                s.id,
+                format_time_ago(s.last_timestamp),
+                s.last_user_message
+                    .as_deref()
+                    .map(|message| format_message_preview(message, 60))
+                    .unwrap_or_default()
+            )
+        })?;
+        match selection {
+            Some(index) => sessions.swap_remove(index),
+            None => bail!(
+                "Interactive selection not available in non-interactive mode. Pass --session <id>."
+            ),
+        }
+    };
+
+    println!(
+        "{} Resuming {} session {} in '{}/{}'...",
+        "🚀".green(),
+        chosen.provider,
+        chosen.id.dimmed(),
+        info.repo_name,
+        info.name.cyan()
+    );
+    std::env::set_current_dir(&info.path).context("Failed to change directory")?;
+
+    let (program, args) = resume_agent_command(chosen.provider, &chosen.id)?;
+    let mut cmd = Command::new(&program);
+    cmd.args(&args).envs(std::env::vars());
+    if !run_agent(&info, &program, cmd, false, "resume")? {
+        bail!("Agent exited with error");
+    }
+    Ok(())
+}
@@ -7,12 +7,15 @@ use chrono::Utc;
 use colored::Colorize;
 use serde::{Deserialize, Serialize};
 
+use crate::commands::create::run_post_create;
 use crate::git::{
     copy_files_to_worktree, execute_git, get_repo_name, run_setup_commands, update_submodules,
 };
 use crate::input::{get_command_arg, smart_confirm};
+use crate::network;
 use crate::state::{PigsState, RepoConfig, WorktreeInfo};
 use crate::utils::sanitize_branch_name;
+use crate::webhooks::{self, WebhookEvent};
 
 const REVIEW_STATE_FILE: &str = "pigs-review";
 
@@ -62,8 +65,9 @@ fn clear_review_state_in(worktree_path: &Path) -> Result<()> {
 /// Try to find the current directory's worktree review state for finish/abort.
 fn current_review_worktree() -> Result<(PathBuf, ReviewState)> {
     let cwd = std::env::current_dir().context("Failed to get current directory")?;
-    let state = load_review_state_in(&cwd)?
-        .context("Not currently in a review worktree. Run this from a review worktree directory.")?;
+    let state = load_review_state_in(&cwd)?.context(
+        "Not currently in a review worktree. Run this from a review worktree directory.",
+    )?;
     Ok((cwd, state))
 }
 
@@ -86,14 +90,13 @@ pub fn handle_review(target: Option<String>, base: Option<String>) -> Result<()>
     // Resolve PR number to branch name if needed
     let trimmed = raw_target.trim();
     let digits_only = trimmed.trim_start_matches('#');
-    let branch_name =
-        if !digits_only.is_empty() && digits_only.chars().all(|c| c.is_ascii_digit()) {
-            let pr_number: u64 = digits_only.parse().context("Invalid pull request number")?;
-            resolve_pr_branch_name(pr_number)
-                .unwrap_or_else(|| format!("pr/{pr_number}"))
-        } else {
-            trimmed.to_string()
-        };
+    let branch_name = if !digits_only.is_empty() && digits_only.chars().all(|c| c.is_ascii_digit())
+    {
+        let pr_number: u64 = digits_only.parse().context("Invalid pull request number")?;
+        resolve_pr_branch_name(pr_number).unwrap_or_else(|| format!("pr/{pr_number}"))
+    } else {
+        trimmed.to_string()
+    };
 
     let worktree_name = format!("review-{}", sanitize_branch_name(&branch_name));
 
@@ -104,7 +107,7 @@ pub fn handle_review(target: Option<String>, base: Option<String>) -> Result<()>
     let repo_name = get_repo_name().context("Not in a git repository")?;
 
     // Check if this review worktree already exists
-    let mut pigs_state = PigsState::load()?;
+    let pigs_state = PigsState::load()?;
     let key = PigsState::make_key(&repo_name, &worktree_name);
     if let Some(existing) = pigs_state.worktrees.get(&key) {
         println!(
@@ -118,10 +121,7 @@ pub fn handle_review(target: Option<String>, base: Option<String>) -> Result<()>
             launch_editor(&existing.path)?;
             let wt_display = existing.path.display();
             println!();
-            println!(
-                "  {} When done:",
-                "💡".cyan(),
-            );
+            println!("  {} When done:", "💡".cyan(),);
             println!(
                 "    {}",
                 format!("cd {wt_display} && pigs review finish").cyan()
@@ -144,10 +144,7 @@ pub fn handle_review(target: Option<String>, base: Option<String>) -> Result<()>
     );
 
     // Create worktree
-    let worktree_parent = repo_root
-        .parent()
-        .context("Repository root has no parent directory")?;
-    let worktree_path = worktree_parent.join(format!("{repo_name}-{worktree_name}"));
+    let worktree_path = PigsState::load()?.worktree_dir(&repo_root, &repo_name, &worktree_name)?;
 
     if worktree_path.exists() {
         bail!(
@@ -182,26 +179,36 @@ pub fn handle_review(target: Option<String>, base: Option<String>) -> Result<()>
     copy_files_to_worktree(&repo_root, &worktree_path, &repo_config.copy_files, false)?;
     run_setup_commands(&worktree_path, &repo_config.setup_commands, false)?;
 
-    // Save to pigs state
-    pigs_state.worktrees.insert(
-        key,
-        WorktreeInfo {
-            name: worktree_name.clone(),
-            branch: branch_name.clone(),
-            path: worktree_path.clone(),
-            repo_name: repo_name.clone(),
-            created_at: Utc::now(),
-        },
-    );
+    // Save to pigs state, reloading since trusting the repo settings may have saved it
+    let mut pigs_state = PigsState::load()?;
+    let info = WorktreeInfo {
+        name: worktree_name.clone(),
+        branch: branch_name.clone(),
+        path: worktree_path.clone(),
+        repo_name: repo_name.clone(),
+        created_at: Utc::now(),
+        base_branch: None,
+        stack: None,
+        reminder: None,
+        container: None,
+        artifacts: Vec::new(),
+        note: None,
+        tags: Vec::new(),
+        agent_history: Vec::new(),
+        repo_root: crate::git::main_checkout(&worktree_path),
+    };
+    pigs_state.worktrees.insert(key, info.clone());
     pigs_state.save()?;
+    webhooks::emit(WebhookEvent::new("worktree.created", &info));
+    run_post_create(&info, &repo_config.hooks, false)?;
 
     // Now set up review mode inside the worktree
-    let wt_str = worktree_path
-        .to_str()
-        .context("Invalid worktree path")?;
+    let wt_str = worktree_path.to_str().context("Invalid worktree path")?;
 
     // Fetch base branch for merge-base calculation
-    let _ = execute_git(&["-C", wt_str, "fetch", "origin", &base_branch]);
+    if !network::is_offline() {
+        let _ = execute_git(&["-C", wt_str, "fetch", "origin", &base_branch]);
+    }
 
     let base_ref = if execute_git(&[
         "-C",
@@ -217,8 +224,8 @@ pub fn handle_review(target: Option<String>, base: Option<String>) -> Result<()>
         format!("origin/{base_branch}")
     };
 
-    let merge_base = execute_git(&["-C", wt_str, "merge-base", &base_ref, "HEAD"])
-        .with_context(|| {
+    let merge_base =
+        execute_git(&["-C", wt_str, "merge-base", &base_ref, "HEAD"]).with_context(|| {
             format!(
                 "Failed to find merge base between '{}' and HEAD. \
                  Make sure the base branch '{}' exists.",
@@ -241,8 +248,7 @@ pub fn handle_review(target: Option<String>, base: Option<String>) -> Result<()>
     execute_git(&["-C", wt_str, "reset", "--soft", &merge_base])
         .context("Failed to soft reset to merge base")?;
 
-    let diff_stat =
-        execute_git(&["-C", wt_str, "diff", "--cached", "--stat"]).unwrap_or_default();
+    let diff_stat = execute_git(&["-C", wt_str, "diff", "--cached", "--stat"]).unwrap_or_default();
 
     println!(
         "{} Review worktree created at: {}",
@@ -265,16 +271,12 @@ pub fn handle_review(target: Option<String>, base: Option<String>) -> Result<()>
     );
 
     // cd into worktree and launch editor
-    std::env::set_current_dir(&worktree_path)
-        .context("Failed to change to review worktree")?;
+    std::env::set_current_dir(&worktree_path).context("Failed to change to review worktree")?;
     launch_editor(&worktree_path)?;
 
     let wt_display = worktree_path.display();
     println!();
-    println!(
-        "  {} When done:",
-        "💡".cyan(),
-    );
+    println!("  {} When done:", "💡".cyan(),);
     println!(
         "    {}",
         format!("cd {wt_display} && pigs review finish").cyan()
@@ -289,9 +291,7 @@ pub fn handle_review(target: Option<String>, base: Option<String>) -> Result<()>
 
 fn handle_review_finish() -> Result<()> {
     let (worktree_path, state) = current_review_worktree()?;
-    let wt_str = worktree_path
-        .to_str()
-        .context("Invalid worktree path")?;
+    let wt_str = worktree_path.to_str().context("Invalid worktree path")?;
 
     // Capture any unstaged changes (user's review edits)
     let user_diff = execute_git(&["-C", wt_str, "diff"])?;
@@ -349,9 +349,7 @@ fn handle_review_finish() -> Result<()> {
 
 fn handle_review_abort() -> Result<()> {
     let (worktree_path, state) = current_review_worktree()?;
-    let wt_str = worktree_path
-        .to_str()
-        .context("Invalid worktree path")?;
+    let wt_str = worktree_path.to_str().context("Invalid worktree path")?;
 
     // Discard everything and restore the branch
     execute_git(&["-C", wt_str, "reset", "--hard", &state.original_head])
@@ -377,10 +375,10 @@ fn handle_review_abort() -> Result<()> {
 
 fn resolve_editor() -> String {
     // Check pigs state for editor config
-    if let Ok(state) = PigsState::load_with_local_overrides() {
-        if let Some(editor) = state.editor {
-            return editor;
-        }
+    if let Ok(state) = PigsState::load_with_local_overrides()
+        && let Some(editor) = state.editor
+    {
+        return editor;
     }
 
     // Fall back to VISUAL, then EDITOR, then vi
@@ -421,6 +419,10 @@ fn launch_editor(worktree_path: &Path) -> Result<()> {
 }
 
 fn resolve_pr_branch_name(pr_number: u64) -> Option<String> {
+    if network::is_offline() {
+        return None;
+    }
+
     Command::new("gh")
         .args([
             "pr",
@@ -445,36 +447,27 @@ fn resolve_pr_branch_name(pr_number: u64) -> Option<String> {
 }
 
 fn ensure_branch_available(branch_name: &str) -> Result<()> {
-    if execute_git(&[
-        "show-ref",
-        "--verify",
-        &format!("refs/heads/{branch_name}"),
-    ])
-    .is_ok()
-    {
+    if execute_git(&["show-ref", "--verify", &format!("refs/heads/{branch_name}")]).is_ok() {
         return Ok(());
     }
 
+    if network::is_offline() {
+        bail!("Branch '{branch_name}' not found locally (fetching is disabled in offline mode)");
+    }
+
     println!(
         "{} Fetching branch '{}' from origin...",
         "🌐".blue(),
         branch_name.cyan()
     );
 
-    execute_git(&["remote", "get-url", "origin"])
-        .context("Remote 'origin' is not configured")?;
+    execute_git(&["remote", "get-url", "origin"]).context("Remote 'origin' is not configured")?;
 
     let fetch_spec = format!("{branch_name}:{branch_name}");
     execute_git(&["fetch", "origin", &fetch_spec])
         .with_context(|| format!("Failed to fetch branch '{branch_name}' from origin"))?;
 
-    if execute_git(&[
-        "show-ref",
-        "--verify",
-        &format!("refs/heads/{branch_name}"),
-    ])
-    .is_ok()
-    {
+    if execute_git(&["show-ref", "--verify", &format!("refs/heads/{branch_name}")]).is_ok() {
         Ok(())
     } else {
         bail!("Branch '{branch_name}' does not exist locally or on origin");
@@ -0,0 +1,114 @@
+use anyhow::Result;
+use chrono::{DateTime, Local, Utc};
+use colored::Colorize;
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::path::Path;
+
+use super::list::{format_message_preview, format_time_ago};
+use crate::claude::get_claude_sessions;
+use crate::codex;
+use crate::names::resolve_worktree;
+use crate::state::PigsState;
+
+/// A Claude or Codex session recorded for a worktree.
+#[derive(Debug, Clone, Serialize)]
+pub(super) struct AgentSession {
+    pub id: String,
+    /// `claude` or `codex`
+    pub provider: &'static str,
+    pub last_user_message: Option<String>,
+    pub last_timestamp: Option<DateTime<Utc>>,
+}
+
+#[derive(Serialize)]
+struct JsonOutput<'a> {
+    repo_name: &'a str,
+    name: &'a str,
+    sessions: &'a [AgentSession],
+}
+
+/// Claude and Codex sessions of the worktree at `path`, newest first. Codex
+/// subagent sessions are left out, since they can't be resumed on their own.
+pub(super) fn worktree_sessions(path: &Path) -> Result<Vec<AgentSession>> {
+    let mut sessions: Vec<AgentSession> = get_claude_sessions(path)
+        .into_iter()
+        .map(|session| AgentSession {
+            id: session.id,
+            provider: "claude",
+            last_user_message: Some(session.last_user_message),
+            last_timestamp: session.last_timestamp,
+        })
+        .collect();
+    let (codex_sessions, _) = codex::recent_sessions(path, usize::MAX)?;
+    sessions.extend(
+        codex_sessions
+            .into_iter()
+            .filter(|session| !session.is_subagent)
+            .map(|session| AgentSession {
+                id: session.id,
+                provider: "codex",
+                last_user_message: session.last_user_message,
+                last_timestamp: session.last_timestamp,
+            }),
+    );
+    sessions.sort_by_key(|session| Reverse(session.last_timestamp));
+    Ok(sessions)
+}
+
+/// Print one session as a line with its id and age, then its last message.
+fn print_session(session: &AgentSession) {
+    let when = session.last_timestamp.map_or_else(String::new, |ts| {
+        format!(
+            "{} ({})",
+            format_time_ago(Some(ts)),
+            ts.with_timezone(&Local).format("%Y-%m-%d %H:%M")
+        )
+    });
+    println!(
+        "  {:<6} {} {}",
+        session.provider.cyan(),
+        session.id,
+        when.bright_black()
+    );
+    if let Some(message) = &session.last_user_message {
+        println!("         {}", format_message_preview(message, 80));
+    }
+}
+
+/// List the Claude and Codex sessions recorded for a worktree, newest first.
+pub fn handle_sessions(name: Option<String>, json: bool) -> Result<()> {
+    let state = PigsState::load()?;
+    let info = resolve_worktree(&state, name)?;
+    let sessions = worktree_sessions(&info.path)?;
+
+    if json {
+        let output = JsonOutput {
+            repo_name: &info.repo_name,
+            name: &info.name,
+            sessions: &sessions,
+        };
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
+    }
+
+    if sessions.is_empty() {
+        println!(
+            "{} No agent sessions found for {}",
+            "ℹ️".blue(),
+            info.name.cyan()
+        );
+        return Ok(());
+    }
+
+    println!(
+        "{} Sessions for {}/{}:",
+        "💬".cyan(),
+        info.repo_name,
+        info.name.cyan()
+    );
+    for session in &sessions {
+        print_session(session);
+    }
+    Ok(())
+}
@@ -0,0 +1,340 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use anyhow::{Result, bail};
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, Utc};
+use colored::Colorize;
+use serde::Serialize;
+
+use super::list::format_time_ago;
+use crate::activity;
+use crate::claude::get_claude_sessions;
+use crate::codex;
+use crate::state::PigsState;
+
+/// Activity log actions that launch an agent.
+const LAUNCH_ACTIONS: [&str; 3] = ["open", "resume", "live-session"];
+
+#[derive(Serialize)]
+struct StatsReport {
+    generated_at: DateTime<Utc>,
+    totals: RepoStats,
+    repos: Vec<RepoStats>,
+    /// Worktrees created and deleted in each of the last `--weeks` weeks,
+    /// oldest first
+    weeks: Vec<WeekStats>,
+    /// Agent launches from `pigs open`, `pigs resume`, and the dashboard,
+    /// by program
+    agent_launches: BTreeMap<String, usize>,
+    /// Active worktrees with no commits or sessions for `--stale-days`
+    stale: Vec<StaleWorktree>,
+}
+
+#[derive(Serialize, Default)]
+struct RepoStats {
+    repo_name: String,
+    active: usize,
+    created: usize,
+    deleted: usize,
+    /// Mean time from creation to deletion, over deleted worktrees whose
+    /// creation time is known
+    average_lifetime_hours: Option<f64>,
+    claude_sessions: usize,
+    codex_sessions: usize,
+    #[serde(skip)]
+    lifetimes: Vec<Duration>,
+}
+
+#[derive(Serialize)]
+struct WeekStats {
+    week_of: NaiveDate,
+    created: usize,
+    deleted: usize,
+}
+
+#[derive(Serialize)]
+struct StaleWorktree {
+    repo_name: String,
+    name: String,
+    branch: String,
+    last_activity: DateTime<Utc>,
+}
+
+impl RepoStats {
+    fn add(&mut self, other: &RepoStats) {
+        self.active += other.active;
+        self.created += other.created;
+        self.deleted += other.deleted;
+        self.claude_sessions += other.claude_sessions;
+        self.codex_sessions += other.codex_sessions;
+        self.lifetimes.extend_from_slice(&other.lifetimes);
+    }
+
+    fn finish(&mut self) {
+        if !self.lifetimes.is_empty() {
+            let total: i64 = self.lifetimes.iter().map(Duration::num_minutes).sum();
+            let minutes = total as f64 / self.lifetimes.len() as f64;
+            self.average_lifetime_hours = Some((minutes / 6.0).round() / 10.0);
+        }
+    }
+}
+
+/// Summarize how worktrees have been used: how many were created and deleted
+/// per week and per repository, how long they lived, how many agent sessions
+/// they saw, and which active ones have gone quiet. Deleted worktrees are
+/// known from their tombstones, so only those within `tombstone_days` count.
+pub fn handle_stats(weeks: usize, stale_days: u32, json: bool) -> Result<()> {
+    if weeks == 0 {
+        bail!("--weeks must be at least 1");
+    }
+    let state = PigsState::load()?;
+    let now = Utc::now();
+
+    let mut repos: BTreeMap<String, RepoStats> = BTreeMap::new();
+    let mut created_at = Vec::new();
+    let mut deleted_at = Vec::new();
+    let mut stale = Vec::new();
+    let stale_cutoff = now - Duration::days(stale_days.into());
+
+    let mut worktrees: Vec<_> = state.worktrees.values().collect();
+    worktrees.sort_by(|a, b| (&a.repo_name, &a.name).cmp(&(&b.repo_name, &b.name)));
+    for info in worktrees {
+        let stats = repo_entry(&mut repos, &info.repo_name);
+        stats.active += 1;
+        stats.created += 1;
+        stats.claude_sessions += get_claude_sessions(&info.path).len();
+        stats.codex_sessions += codex_session_count(&info.path);
+        created_at.push(info.created_at);
+
+        let last_activity = activity::last_activity(info);
+        if last_activity < stale_cutoff {
+            stale.push(StaleWorktree {
+                repo_name: info.repo_name.clone(),
+                name: info.name.clone(),
+                branch: info.branch.clone(),
+                last_activity,
+            });
+        }
+    }
+
+    for (key, tombstone) in &state.tombstones {
+        let repo_name = key.split_once('/').map_or(key.as_str(), |(repo, _)| repo);
+        let stats = repo_entry(&mut repos, repo_name);
+        stats.deleted += 1;
+        deleted_at.push(tombstone.deleted_at);
+        if let Some(created) = tombstone.created_at {
+            stats.created += 1;
+            stats.lifetimes.push(tombstone.deleted_at - created);
+            created_at.push(created);
+        }
+    }
+
+    let mut agent_launches: BTreeMap<String, usize> = BTreeMap::new();
+    for record in activity::read_all()? {
+        let Some(command) = record.command.as_ref() else {
+            continue;
+        };
+        if !LAUNCH_ACTIONS.contains(&record.action.as_str()) {
+            continue;
+        }
+        let agent = Path::new(&command.program).file_name().map_or_else(
+            || command.program.clone(),
+            |name| name.to_string_lossy().into_owned(),
+        );
+        *agent_launches.entry(agent).or_default() += 1;
+    }
+
+    let mut totals = RepoStats {
+        repo_name: "all".to_string(),
+        ..Default::default()
+    };
+    for stats in repos.values_mut() {
+        stats.finish();
+        totals.add(stats);
+    }
+    totals.finish();
+    stale.sort_by_key(|worktree| worktree.last_activity);
+
+    let report = StatsReport {
+        generated_at: now,
+        totals,
+        repos: repos.into_values().collect(),
+        weeks: weekly(&created_at, &deleted_at, weeks, now),
+        agent_launches,
+        stale,
+    };
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print_report(&report, stale_days);
+    }
+    Ok(())
+}
+
+fn repo_entry<'a>(repos: &'a mut BTreeMap<String, RepoStats>, name: &str) -> &'a mut RepoStats {
+    repos.entry(name.to_string()).or_insert_with(|| RepoStats {
+        repo_name: name.to_string(),
+        ..Default::default()
+    })
+}
+
+fn codex_session_count(path: &Path) -> usize {
+    codex::recent_sessions(path, 1).map_or(0, |(_, total)| total)
+}
+
+/// Count `created` and `deleted` times into the `weeks` weeks (starting
+/// Monday, local time) up to and including the one containing `now`.
+fn weekly(
+    created: &[DateTime<Utc>],
+    deleted: &[DateTime<Utc>],
+    weeks: usize,
+    now: DateTime<Utc>,
+) -> Vec<WeekStats> {
+    let week_of = |time: DateTime<Utc>| {
+        let date = time.with_timezone(&Local).date_naive();
+        date - Duration::days(date.weekday().num_days_from_monday().into())
+    };
+    let current = week_of(now);
+    let mut buckets: Vec<WeekStats> = (0..weeks)
+        .rev()
+        .map(|back| WeekStats {
+            week_of: current - Duration::weeks(back as i64),
+            created: 0,
+            deleted: 0,
+        })
+        .collect();
+    let mut count = |time: DateTime<Utc>, deleted: bool| {
+        let week = week_of(time);
+        if let Some(bucket) = buckets.iter_mut().find(|bucket| bucket.week_of == week) {
+            if deleted {
+                bucket.deleted += 1;
+            } else {
+                bucket.created += 1;
+            }
+        }
+    };
+    for &time in created {
+        count(time, false);
+    }
+    for &time in deleted {
+        count(time, true);
+    }
+    buckets
+}
+
+fn format_lifetime(hours: Option<f64>) -> String {
+    match hours {
+        None => "-".to_string(),
+        Some(hours) if hours < 48.0 => format!("{hours:.1}h"),
+        Some(hours) => format!("{:.1}d", hours / 24.0),
+    }
+}
+
+fn print_report(report: &StatsReport, stale_days: u32) {
+    let totals = &report.totals;
+    println!("{} Worktree stats", "📊".cyan());
+    println!();
+    println!(
+        "  {} active worktree(s) in {} repo(s); {} created and {} deleted on record",
+        totals.active,
+        report.repos.len(),
+        totals.created,
+        totals.deleted
+    );
+    println!(
+        "  Average lifetime: {} (over {} deleted)",
+        format_lifetime(totals.average_lifetime_hours),
+        totals.lifetimes.len()
+    );
+    println!(
+        "  Agent sessions: {} Claude, {} Codex",
+        totals.claude_sessions, totals.codex_sessions
+    );
+    if !report.agent_launches.is_empty() {
+        let launches: Vec<String> = report
+            .agent_launches
+            .iter()
+            .map(|(agent, count)| format!("{agent} {count}"))
+            .collect();
+        println!("  Agent launches: {}", launches.join(", "));
+    }
+
+    println!();
+    println!("  {}", "WEEK OF     CREATED  DELETED".bold());
+    for week in &report.weeks {
+        println!(
+            "  {}  {:>7}  {:>7}",
+            week.week_of, week.created, week.deleted
+        );
+    }
+
+    if !report.repos.is_empty() {
+        let width = report
+            .repos
+            .iter()
+            .map(|repo| repo.repo_name.chars().count())
+            .max()
+            .unwrap_or(0)
+            .max("REPOSITORY".len());
+        println!();
+        println!(
+            "  {}",
+            format!(
+                "{:<width$}  ACTIVE  CREATED  DELETED  LIFETIME  CLAUDE  CODEX",
+                "REPOSITORY"
+            )
+            .bold()
+        );
+        for repo in &report.repos {
+            println!(
+                "  {:<width$}  {:>6}  {:>7}  {:>7}  {:>8}  {:>6}  {:>5}",
+                repo.repo_name,
+                repo.active,
+                repo.created,
+                repo.deleted,
+                format_lifetime(repo.average_lifetime_hours),
+                repo.claude_sessions,
+                repo.codex_sessions
+            );
+        }
+    }
+
+    if !report.stale.is_empty() {
+        println!();
+        println!(
+            "  {} No commits or sessions for {stale_days}+ days:",
+            "⚠️".yellow()
+        );
+        for worktree in &report.stale {
+            println!(
+                "    {}/{} ({}) last active {}",
+                worktree.repo_name,
+                worktree.name.cyan(),
+                worktree.branch,
+                format_time_ago(Some(worktree.last_activity))
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn weekly_buckets_by_monday() {
+        let now = Utc::now();
+        let weeks = weekly(
+            &[now, now - Duration::weeks(1), now - Duration::weeks(10)],
+            &[now],
+            3,
+            now,
+        );
+        assert_eq!(weeks.len(), 3);
+        assert_eq!(weeks[2].week_of.weekday(), chrono::Weekday::Mon);
+        assert_eq!(weeks[2].week_of - weeks[1].week_of, Duration::weeks(1));
+        let counts: Vec<(usize, usize)> = weeks.iter().map(|w| (w.created, w.deleted)).collect();
+        assert_eq!(counts, vec![(0, 0), (1, 0), (1, 1)]);
+    }
+}
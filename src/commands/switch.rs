@@ -0,0 +1,81 @@
+use anyhow::{Result, bail};
+use clap_complete::Shell;
+use colored::Colorize;
+
+use super::dir::select_worktree;
+
+/// Set by the `shell-init` wrapper when it runs `pigs switch`, so pigs knows
+/// the shell will change directory to the path it prints.
+const INTEGRATION_ENV: &str = "PIGS_SHELL_INTEGRATION";
+
+/// Print a worktree's path for the `shell-init` wrapper to `cd` into. A child
+/// process can't change its parent shell's directory, so without the wrapper
+/// this only prints the path and explains how to set it up.
+pub fn handle_switch(name: Option<String>) -> Result<()> {
+    let worktree_info = select_worktree(name)?;
+    println!("{}", worktree_info.path.display());
+
+    if std::env::var_os(INTEGRATION_ENV).is_none() {
+        eprintln!(
+            "{} pigs can't change your shell's directory by itself. Add this to your shell config to make `pigs switch` cd:",
+            "💡".yellow()
+        );
+        eprintln!(
+            "   {}",
+            r#"eval "$(pigs shell-init zsh)"     # or bash"#.cyan()
+        );
+        eprintln!("   {}", "pigs shell-init fish | source     # fish".cyan());
+    }
+    Ok(())
+}
+
+/// Print a `pigs` shell function that runs `pigs switch` and changes into the
+/// directory it prints, and passes every other command through unchanged.
+pub fn handle_shell_init(shell: Shell) -> Result<()> {
+    match shell {
+        Shell::Bash | Shell::Zsh => print!("{}", posix_init()),
+        Shell::Fish => print!("{}", fish_init()),
+        _ => bail!("Unsupported shell: {shell}. Supported shells: bash, zsh, fish"),
+    }
+    Ok(())
+}
+
+fn posix_init() -> String {
+    format!(
+        r#"# pigs shell integration: `pigs switch <name>` changes into the worktree
+pigs() {{
+    if [ "$1" = switch ]; then
+        shift
+        local dir
+        dir="$({INTEGRATION_ENV}=1 command pigs switch "$@")" || return
+        if [ -d "$dir" ]; then
+            cd -- "$dir"
+        elif [ -n "$dir" ]; then
+            printf '%s\n' "$dir"
+        fi
+    else
+        command pigs "$@"
+    fi
+}}
+"#
+    )
+}
+
+fn fish_init() -> String {
+    format!(
+        r#"# pigs shell integration: `pigs switch <name>` changes into the worktree
+function pigs
+    if test "$argv[1]" = switch
+        set -l dir (env {INTEGRATION_ENV}=1 pigs switch $argv[2..-1]); or return
+        if test (count $dir) -eq 1; and test -d "$dir"
+            cd $dir
+        else if test -n "$dir"
+            printf '%s\n' $dir
+        end
+    else
+        command pigs $argv
+    end
+end
+"#
+    )
+}
@@ -0,0 +1,480 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use anyhow::{Context, Result, bail};
+use colored::Colorize;
+use serde_json::json;
+use tokio::sync::broadcast;
+
+use crate::git::{execute_git, resolve_base_ref};
+use crate::hooks::{self, Hook};
+use crate::jobs::{self, Job, JobEvent};
+use crate::names::resolve_worktree;
+use crate::network;
+use crate::repo_lock::lock_repo;
+use crate::stack::{StackNode, stack_order};
+use crate::state::{PigsState, RepoConfig, WorktreeInfo};
+
+/// Outcome of restacking one worktree, reported as `pigs sync` goes.
+pub enum SyncUpdate {
+    UpToDate {
+        name: String,
+        parent: String,
+    },
+    Restacked {
+        name: String,
+        parent: String,
+    },
+    /// A named worktree that isn't stacked was brought up to date with its base
+    Updated {
+        name: String,
+        base: String,
+        merged: bool,
+    },
+    Skipped {
+        name: String,
+        reason: String,
+    },
+    Failed {
+        name: String,
+        parent: String,
+        error: String,
+    },
+}
+
+/// Restack stacked worktrees onto their parents' current heads. With a name,
+/// that worktree is first updated onto its base branch (or restacked, if it is
+/// stacked itself), then its descendants are restacked; otherwise every stack
+/// is. `merge` merges the base in instead of rebasing onto it.
+pub fn handle_sync(name: Option<String>, merge: bool) -> Result<()> {
+    let failures =
+        sync_stacks(
+            name.as_deref(),
+            merge,
+            false,
+            &|_| Ok(()),
+            &mut |update| match update {
+                SyncUpdate::UpToDate { name, parent } => println!(
+                    "{} '{}' is up to date with '{}'",
+                    "✓".green(),
+                    name.cyan(),
+                    parent.cyan()
+                ),
+                SyncUpdate::Restacked { name, parent } => println!(
+                    "{} Restacked '{}' onto '{}'",
+                    "✅".green(),
+                    name.cyan(),
+                    parent.cyan()
+                ),
+                SyncUpdate::Updated { name, base, merged } => println!(
+                    "{} {} '{}' {} '{}'",
+                    "✅".green(),
+                    if merged { "Merged" } else { "Rebased" },
+                    if merged { base.cyan() } else { name.cyan() },
+                    if merged { "into" } else { "onto" },
+                    if merged { name.cyan() } else { base.cyan() }
+                ),
+                SyncUpdate::Skipped { name, reason } => {
+                    println!("{} Skipping '{}': {}", "⏭️".yellow(), name.cyan(), reason)
+                }
+                SyncUpdate::Failed {
+                    name,
+                    parent,
+                    error,
+                } => eprintln!(
+                    "{} Failed to sync '{}' onto '{}': {}",
+                    "❌".red(),
+                    name.cyan(),
+                    parent.cyan(),
+                    error
+                ),
+            },
+        )?;
+
+    match failures {
+        None => println!("{} No stacked worktrees to sync", "📭".yellow()),
+        Some(0) => {}
+        Some(failures) => bail!("{failures} worktree(s) could not be restacked"),
+    }
+    Ok(())
+}
+
+/// Queue `pigs sync` as a `sync` job, with one step per restacked worktree.
+/// Cancelling stops before the next worktree.
+pub fn spawn_sync_job(
+    name: Option<String>,
+    merge: bool,
+) -> (Arc<Job>, broadcast::Receiver<JobEvent>) {
+    jobs::spawn("sync", name.clone(), move |job| {
+        let mut restacked = Vec::new();
+        let mut skipped = Vec::new();
+        let mut failed = Vec::new();
+        let before_each = |worktree: &str| {
+            job.check_cancelled()?;
+            job.step(&format!("restack {worktree}"));
+            Ok(())
+        };
+        let failures =
+            sync_stacks(
+                name.as_deref(),
+                merge,
+                true,
+                &before_each,
+                &mut |update| match update {
+                    SyncUpdate::UpToDate { .. } => {}
+                    SyncUpdate::Restacked { name, .. } | SyncUpdate::Updated { name, .. } => {
+                        restacked.push(name)
+                    }
+                    SyncUpdate::Skipped { name, reason } => {
+                        skipped.push(json!({ "name": name, "reason": reason }))
+                    }
+                    SyncUpdate::Failed { name, error, .. } => {
+                        failed.push(json!({ "name": name, "error": error }))
+                    }
+                },
+            )?;
+        if let Some(failures) = failures
+            && failures > 0
+        {
+            bail!("{failures} worktree(s) could not be restacked");
+        }
+        Ok(json!({ "restacked": restacked, "skipped": skipped, "failed": failed }))
+    })
+}
+
+/// Restack the selected worktrees in stack order, reporting each outcome.
+/// `before_each` runs before each worktree and can stop the sync with an error.
+/// The `pre_sync` hooks run before each update and `post_sync` after one that
+/// changed the worktree, with their output on stderr when `quiet`. Returns
+/// `None` when nothing is stacked, else the number of failures.
+fn sync_stacks(
+    name: Option<&str>,
+    merge: bool,
+    quiet: bool,
+    before_each: &dyn Fn(&str) -> Result<()>,
+    report: &mut dyn FnMut(SyncUpdate),
+) -> Result<Option<usize>> {
+    let mut state = PigsState::load()?;
+
+    let mut worktrees: Vec<WorktreeInfo> = state.worktrees.values().cloned().collect();
+    worktrees.sort_by(|a, b| {
+        a.repo_name
+            .cmp(&b.repo_name)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    let nodes: Vec<StackNode> = worktrees
+        .iter()
+        .map(|info| StackNode {
+            repo_name: &info.repo_name,
+            name: &info.name,
+            parent: info.stack.as_ref().map(|link| link.parent.as_str()),
+        })
+        .collect();
+    let ordered = stack_order(&nodes);
+
+    let selected: Vec<usize> = match name {
+        Some(name) => {
+            let found = resolve_worktree(&state, Some(name.to_string()))?;
+            let position = ordered
+                .iter()
+                .position(|&(index, _)| {
+                    worktrees[index].repo_name == found.repo_name
+                        && worktrees[index].name == found.name
+                })
+                .with_context(|| format!("Worktree '{name}' not found"))?;
+            let depth = ordered[position].1;
+            std::iter::once(&ordered[position])
+                .chain(
+                    ordered[position + 1..]
+                        .iter()
+                        .take_while(|&&(_, child_depth)| child_depth > depth),
+                )
+                .map(|&(index, _)| index)
+                .collect()
+        }
+        None => ordered
+            .iter()
+            .filter(|&&(index, _)| worktrees[index].stack.is_some())
+            .map(|&(index, _)| index)
+            .collect(),
+    };
+
+    if selected.is_empty() {
+        return Ok(None);
+    }
+
+    // Children of a worktree that failed to restack are left alone
+    let mut blocked: HashSet<String> = HashSet::new();
+    let mut failures = 0;
+    for index in selected {
+        let info = &worktrees[index];
+        before_each(&info.name)?;
+        let Some(link) = info.stack.clone() else {
+            // Only a worktree named on the command line gets here unstacked
+            let key = PigsState::make_key(&info.repo_name, &info.name);
+            let result =
+                lock_repo(&info.repo_name, &format!("sync '{}'", info.name)).and_then(|_guard| {
+                    run_sync_hook(Hook::PreSync, info, quiet)?;
+                    update_onto_base(info, merge)
+                });
+            match result {
+                Ok((base, false)) => report(SyncUpdate::UpToDate {
+                    name: info.name.clone(),
+                    parent: base,
+                }),
+                Ok((base, true)) => {
+                    report(SyncUpdate::Updated {
+                        name: info.name.clone(),
+                        base: base.clone(),
+                        merged: merge,
+                    });
+                    if let Err(err) = run_sync_hook(Hook::PostSync, info, quiet) {
+                        failures += 1;
+                        report(SyncUpdate::Failed {
+                            name: info.name.clone(),
+                            parent: base,
+                            error: format!("{err:#}"),
+                        });
+                    }
+                }
+                Err(err) => {
+                    failures += 1;
+                    report(SyncUpdate::Failed {
+                        name: info.name.clone(),
+                        parent: info
+                            .base_branch
+                            .clone()
+                            .unwrap_or_else(|| "its base branch".to_string()),
+                        error: format!("{err:#}"),
+                    });
+                    blocked.insert(key);
+                }
+            }
+            continue;
+        };
+        let parent_key = PigsState::make_key(&info.repo_name, &link.parent);
+        let key = PigsState::make_key(&info.repo_name, &info.name);
+
+        if blocked.contains(&parent_key) {
+            report(SyncUpdate::Skipped {
+                name: info.name.clone(),
+                reason: format!("parent '{}' was not restacked", link.parent),
+            });
+            blocked.insert(key);
+            continue;
+        }
+
+        let Some(parent) = state.worktrees.get(&parent_key).cloned() else {
+            report(SyncUpdate::Skipped {
+                name: info.name.clone(),
+                reason: format!("parent worktree '{}' no longer exists", link.parent),
+            });
+            blocked.insert(key);
+            continue;
+        };
+
+        let result =
+            lock_repo(&info.repo_name, &format!("restack '{}'", info.name)).and_then(|_guard| {
+                run_sync_hook(Hook::PreSync, info, quiet)?;
+                restack(info, &parent, &link.base)
+            });
+        match result {
+            Ok(None) => report(SyncUpdate::UpToDate {
+                name: info.name.clone(),
+                parent: parent.name.clone(),
+            }),
+            Ok(Some(new_base)) => {
+                if let Some(entry) = state.worktrees.get_mut(&key)
+                    && let Some(stack) = entry.stack.as_mut()
+                {
+                    stack.base = new_base;
+                }
+                state.save()?;
+                report(SyncUpdate::Restacked {
+                    name: info.name.clone(),
+                    parent: parent.name.clone(),
+                });
+                if let Err(err) = run_sync_hook(Hook::PostSync, info, quiet) {
+                    failures += 1;
+                    report(SyncUpdate::Failed {
+                        name: info.name.clone(),
+                        parent: parent.name.clone(),
+                        error: format!("{err:#}"),
+                    });
+                }
+            }
+            Err(err) => {
+                failures += 1;
+                report(SyncUpdate::Failed {
+                    name: info.name.clone(),
+                    parent: parent.name.clone(),
+                    error: err.to_string(),
+                });
+                blocked.insert(key);
+            }
+        }
+    }
+
+    Ok(Some(failures))
+}
+
+/// Run `hook` in a worktree being synced. A missing directory is left for the
+/// update itself to report.
+fn run_sync_hook(hook: Hook, info: &WorktreeInfo, quiet: bool) -> Result<()> {
+    if !info.path.exists() {
+        return Ok(());
+    }
+    let repo_hooks = RepoConfig::load(&info.path)?.hooks;
+    hooks::run(hook, info, &info.path, &repo_hooks, quiet)
+}
+
+/// Fetch origin and rebase the worktree's branch onto its base branch, or
+/// merge the base in. Returns the base ref and whether anything changed.
+fn update_onto_base(info: &WorktreeInfo, merge: bool) -> Result<(String, bool)> {
+    let path = info
+        .path
+        .to_str()
+        .context("Worktree path contains invalid UTF-8")?;
+    if !info.path.exists() {
+        bail!("worktree directory {} is missing", info.path.display());
+    }
+
+    let has_origin = execute_git(&["-C", path, "remote", "get-url", "origin"]).is_ok();
+    if has_origin && !network::is_offline() {
+        execute_git(&["-C", path, "fetch", "origin"]).context("Failed to fetch origin")?;
+    }
+
+    let base = match &info.base_branch {
+        Some(branch) => newest_of_local_and_remote(path, branch),
+        None => resolve_base_ref(&info.path).context(
+            "Could not determine a base branch (tried origin/HEAD, main, master, develop)",
+        )?,
+    };
+
+    if execute_git(&["-C", path, "merge-base", "--is-ancestor", &base, "HEAD"]).is_ok() {
+        return Ok((base, false));
+    }
+
+    let status = execute_git(&["-C", path, "status", "--porcelain"])?;
+    if !status.is_empty() {
+        bail!("worktree has uncommitted changes; commit or stash them first");
+    }
+
+    let (operation, result) = if merge {
+        (
+            "merge",
+            execute_git(&["-C", path, "merge", "--no-edit", &base]),
+        )
+    } else {
+        ("rebase", execute_git(&["-C", path, "rebase", &base]))
+    };
+    if let Err(err) = result {
+        let conflicts = execute_git(&["-C", path, "diff", "--name-only", "--diff-filter=U"])
+            .unwrap_or_default();
+        if conflicts.is_empty() {
+            let _ = execute_git(&["-C", path, operation, "--abort"]);
+            return Err(err.context(format!(
+                "git {operation} failed, so the branch was left unchanged"
+            )));
+        }
+        let files: Vec<&str> = conflicts.lines().collect();
+        bail!(
+            "conflicts in {}\n  Resolve them in {} with `git {operation} --continue` (or `git {operation} --abort`), then run `pigs sync {}` again",
+            files.join(", "),
+            info.path.display(),
+            info.name
+        );
+    }
+
+    Ok((base, true))
+}
+
+/// `origin/<branch>` when it contains everything on the local branch (or there
+/// is no local branch), else the local branch. A remote ref is used as-is.
+fn newest_of_local_and_remote(path: &str, branch: &str) -> String {
+    if branch.starts_with("origin/") {
+        return branch.to_string();
+    }
+    let remote = format!("origin/{branch}");
+    let remote_exists = execute_git(&[
+        "-C",
+        path,
+        "show-ref",
+        "--verify",
+        "--quiet",
+        &format!("refs/remotes/{remote}"),
+    ])
+    .is_ok();
+    let local_exists = execute_git(&[
+        "-C",
+        path,
+        "show-ref",
+        "--verify",
+        "--quiet",
+        &format!("refs/heads/{branch}"),
+    ])
+    .is_ok();
+    if remote_exists
+        && (!local_exists
+            || execute_git(&["-C", path, "merge-base", "--is-ancestor", branch, &remote]).is_ok())
+    {
+        remote
+    } else {
+        branch.to_string()
+    }
+}
+
+/// Rebase the commits between `old_base` and the child's head onto the
+/// parent's current head. Returns the new base, or `None` when the child is
+/// already based on it.
+fn restack(child: &WorktreeInfo, parent: &WorktreeInfo, old_base: &str) -> Result<Option<String>> {
+    let path = child
+        .path
+        .to_str()
+        .context("Worktree path contains invalid UTF-8")?;
+    if !child.path.exists() {
+        bail!("worktree directory {} is missing", child.path.display());
+    }
+
+    let new_base = execute_git(&["-C", path, "rev-parse", &parent.branch])?;
+    if new_base == old_base {
+        return Ok(None);
+    }
+
+    // Already on top of the parent, e.g. after finishing a conflicted restack by hand
+    if execute_git(&[
+        "-C",
+        path,
+        "merge-base",
+        "--is-ancestor",
+        &new_base,
+        &child.branch,
+    ])
+    .is_ok()
+    {
+        return Ok(Some(new_base));
+    }
+
+    let status = execute_git(&["-C", path, "status", "--porcelain"])?;
+    if !status.is_empty() {
+        bail!("worktree has uncommitted changes; commit or stash them first");
+    }
+
+    if let Err(err) = execute_git(&[
+        "-C",
+        path,
+        "rebase",
+        "--onto",
+        &new_base,
+        old_base,
+        &child.branch,
+    ]) {
+        bail!(
+            "{}\n  Resolve the conflicts in {} with `git rebase --continue` (or `git rebase --abort`), then run `pigs sync` again",
+            err.to_string().trim(),
+            child.path.display()
+        );
+    }
+
+    Ok(Some(new_base))
+}
@@ -0,0 +1,73 @@
+use anyhow::{Result, bail};
+use colored::Colorize;
+
+use crate::names::resolve_worktree;
+use crate::state::PigsState;
+
+/// Add (`+tag` or `tag`) and remove (`-tag`) tags on a worktree, or print its
+/// tags when none are given.
+pub fn handle_tag(name: String, changes: Vec<String>) -> Result<()> {
+    let mut state = PigsState::load()?;
+    let found = resolve_worktree(&state, Some(name))?;
+    let key = PigsState::make_key(&found.repo_name, &found.name);
+    let Some(info) = state.worktrees.get_mut(&key) else {
+        bail!("Worktree '{}' not found", found.name);
+    };
+
+    if changes.is_empty() {
+        if info.tags.is_empty() {
+            println!("{} No tags on {}", "ℹ️".blue(), info.name.cyan());
+        } else {
+            println!(
+                "{} {}: {}",
+                "🏷️".cyan(),
+                info.name.cyan(),
+                info.tags.join(", ")
+            );
+        }
+        return Ok(());
+    }
+
+    for change in &changes {
+        let (add, tag) = match change.strip_prefix('-') {
+            Some(tag) => (false, tag),
+            None => (true, change.strip_prefix('+').unwrap_or(change)),
+        };
+        validate_tag(tag)?;
+        if add {
+            if !info.tags.iter().any(|existing| existing == tag) {
+                info.tags.push(tag.to_string());
+            }
+        } else {
+            info.tags.retain(|existing| existing != tag);
+        }
+    }
+    info.tags.sort();
+
+    let summary = if info.tags.is_empty() {
+        format!("{} No tags left on {}", "🏷️".green(), info.name.cyan())
+    } else {
+        format!(
+            "{} Tagged {}: {}",
+            "🏷️".green(),
+            info.name.cyan(),
+            info.tags.join(", ")
+        )
+    };
+    state.save()?;
+    println!("{summary}");
+    Ok(())
+}
+
+/// Tags are single words so they can be typed after `+`/`-` and passed as
+/// `?tag=` without quoting.
+fn validate_tag(tag: &str) -> Result<()> {
+    if tag.is_empty()
+        || !tag
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '.' | '/'))
+    {
+        bail!("Invalid tag '{tag}': use letters, digits, '-', '_', '.', or '/'");
+    }
+    Ok(())
+}
@@ -0,0 +1,419 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{Context, Result, bail};
+use colored::Colorize;
+
+use crate::git::{execute_git, resolve_base_ref};
+use crate::input::{get_command_arg, smart_confirm};
+use crate::names::resolve_worktree;
+use crate::repo_lock::lock_repo;
+use crate::state::{PigsState, WorktreeInfo};
+use crate::utils::resolve_agent_command;
+
+pub struct TidyOptions {
+    pub base: Option<String>,
+    pub agent_message: bool,
+    pub agent: Option<String>,
+    pub dry_run: bool,
+    pub yes: bool,
+}
+
+/// One commit on the branch, oldest first.
+#[derive(Debug, Clone)]
+struct BranchCommit {
+    sha: String,
+    subject: String,
+    files: Vec<String>,
+}
+
+/// Commits that will be squashed into one, with the message to use.
+struct SquashGroup {
+    commits: Vec<BranchCommit>,
+    message: String,
+}
+
+pub fn handle_tidy(name: Option<String>, options: TidyOptions) -> Result<()> {
+    let state = PigsState::load()?;
+
+    if state.worktrees.is_empty() {
+        bail!("No worktrees found. Create one first with 'pigs create'");
+    }
+
+    let worktree = resolve_worktree(&state, get_command_arg(name)?)?;
+    if !worktree.path.exists() {
+        bail!(
+            "Worktree directory {} no longer exists. Run 'pigs clean' to prune it.",
+            worktree.path.display()
+        );
+    }
+    let path = worktree.path.to_str().context("Invalid worktree path")?;
+
+    let base_ref = match options.base.as_deref() {
+        Some(base) => base.to_string(),
+        None => resolve_base_ref(&worktree.path).context(
+            "Could not determine a base branch (tried origin/HEAD, main, master, develop); pass --base",
+        )?,
+    };
+    let merge_base = execute_git(&["-C", path, "merge-base", &base_ref, "HEAD"])
+        .with_context(|| format!("Failed to find merge-base with {base_ref}"))?;
+
+    if !execute_git(&[
+        "-C",
+        path,
+        "rev-list",
+        "--merges",
+        &format!("{merge_base}..HEAD"),
+    ])?
+    .is_empty()
+    {
+        bail!(
+            "'{}' contains merge commits; tidy only rewrites linear history",
+            worktree.name
+        );
+    }
+
+    let commits = branch_commits(path, &merge_base)?;
+    let groups = group_commits(commits.clone());
+    if groups.len() == commits.len() {
+        println!(
+            "{} Nothing to tidy: '{}' has {} commit(s) since {} that touch separate files",
+            "✨".green(),
+            worktree.name.cyan(),
+            commits.len(),
+            base_ref.cyan()
+        );
+        return Ok(());
+    }
+
+    let mut groups: Vec<SquashGroup> = groups
+        .into_iter()
+        .map(|commits| SquashGroup {
+            message: default_message(&commits),
+            commits,
+        })
+        .collect();
+
+    if options.agent_message {
+        ask_agent_for_messages(&worktree, &mut groups, options.agent.as_deref())?;
+    }
+
+    print_plan(&worktree, &base_ref, commits.len(), &groups);
+
+    if options.dry_run {
+        return Ok(());
+    }
+
+    if !execute_git(&["-C", path, "status", "--porcelain", "--untracked-files=no"])?.is_empty() {
+        bail!(
+            "'{}' has uncommitted changes; commit or stash them first",
+            worktree.name
+        );
+    }
+
+    if !options.yes && !smart_confirm("Rewrite the branch with this plan?", false)? {
+        println!("{} Tidy cancelled", "❌".red());
+        return Ok(());
+    }
+
+    let previous_head = execute_git(&["-C", path, "rev-parse", "HEAD"])?;
+    let _repo_guard = lock_repo(&worktree.repo_name, &format!("tidy '{}'", worktree.name))?;
+    run_rebase(&worktree.path, &merge_base, &groups)?;
+
+    println!(
+        "{} Tidied '{}' from {} commits into {}",
+        "✅".green(),
+        worktree.name.cyan(),
+        commits.len(),
+        groups.len()
+    );
+    println!(
+        "   Undo with: git -C {} reset --hard {}",
+        worktree.path.display(),
+        &previous_head[..previous_head.len().min(12)]
+    );
+    if state.worktrees.values().any(|info| {
+        info.stack
+            .as_ref()
+            .is_some_and(|link| link.parent == worktree.name)
+    }) {
+        println!(
+            "   Worktrees stacked on '{}' follow on the next {}",
+            worktree.name,
+            "pigs sync".cyan()
+        );
+    }
+
+    Ok(())
+}
+
+fn branch_commits(path: &str, merge_base: &str) -> Result<Vec<BranchCommit>> {
+    let log = execute_git(&[
+        "-C",
+        path,
+        "log",
+        "--reverse",
+        "--format=%H%x00%s",
+        &format!("{merge_base}..HEAD"),
+    ])?;
+
+    log.lines()
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            let (sha, subject) = line.split_once('\0').unwrap_or((line, ""));
+            let files = execute_git(&[
+                "-C",
+                path,
+                "diff-tree",
+                "--no-commit-id",
+                "--name-only",
+                "-r",
+                sha,
+            ])?
+            .lines()
+            .map(str::to_string)
+            .collect();
+            Ok(BranchCommit {
+                sha: sha.to_string(),
+                subject: subject.to_string(),
+                files,
+            })
+        })
+        .collect()
+}
+
+/// Group commits that touch overlapping files, keeping each group in commit
+/// order and ordering groups by their first commit. Groups never share a file,
+/// so moving a commit next to the rest of its group can't change the patch
+/// any file ends up with.
+fn group_commits(commits: Vec<BranchCommit>) -> Vec<Vec<BranchCommit>> {
+    let mut groups: Vec<Vec<(usize, BranchCommit)>> = Vec::new();
+    for (order, commit) in commits.into_iter().enumerate() {
+        let overlapping: Vec<usize> = groups
+            .iter()
+            .enumerate()
+            .filter(|(_, group)| {
+                group
+                    .iter()
+                    .any(|(_, other)| other.files.iter().any(|file| commit.files.contains(file)))
+            })
+            .map(|(index, _)| index)
+            .collect();
+
+        match overlapping.split_first() {
+            None => groups.push(vec![(order, commit)]),
+            Some((&first, rest)) => {
+                // A commit can bridge several groups; fold later ones into the first
+                for &index in rest.iter().rev() {
+                    let merged = groups.remove(index);
+                    groups[first].extend(merged);
+                }
+                groups[first].push((order, commit));
+                groups[first].sort_by_key(|(order, _)| *order);
+            }
+        }
+    }
+    groups
+        .into_iter()
+        .map(|group| group.into_iter().map(|(_, commit)| commit).collect())
+        .collect()
+}
+
+fn default_message(commits: &[BranchCommit]) -> String {
+    let mut message = commits[0].subject.clone();
+    if commits.len() > 1 {
+        message.push('\n');
+        for commit in &commits[1..] {
+            message.push_str(&format!("\n- {}", commit.subject));
+        }
+    }
+    message
+}
+
+fn print_plan(worktree: &WorktreeInfo, base_ref: &str, total: usize, groups: &[SquashGroup]) {
+    println!(
+        "{} Plan for '{}' ({} commits since {} → {}):",
+        "🧹".cyan(),
+        worktree.name.cyan(),
+        total,
+        base_ref,
+        groups.len()
+    );
+    for (index, group) in groups.iter().enumerate() {
+        let title = group.message.lines().next().unwrap_or_default();
+        println!(
+            "  {} {}",
+            format!("{}.", index + 1).bright_black(),
+            title.bold()
+        );
+        for commit in &group.commits {
+            println!(
+                "     {} {}",
+                commit.sha[..commit.sha.len().min(8)].bright_black(),
+                commit.subject
+            );
+        }
+    }
+}
+
+/// Write each group's default message to a file, launch the agent in the
+/// worktree asking it to rewrite them, and read them back once it exits.
+fn ask_agent_for_messages(
+    worktree: &WorktreeInfo,
+    groups: &mut [SquashGroup],
+    agent: Option<&str>,
+) -> Result<()> {
+    let path = worktree.path.to_str().context("Invalid worktree path")?;
+    let dir = PathBuf::from(execute_git(&[
+        "-C",
+        path,
+        "rev-parse",
+        "--absolute-git-dir",
+    ])?)
+    .join("pigs-tidy");
+    fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+
+    let mut prompt = String::from(
+        "I'm squashing this branch's commits. For each file below, rewrite the draft commit message it contains so it describes the combined change: a short subject line, a blank line, then a brief body. Only edit these files; don't run git commands.\n",
+    );
+    let mut files = Vec::new();
+    for (index, group) in groups.iter().enumerate() {
+        let file = dir.join(format!("{}.txt", index + 1));
+        fs::write(&file, &group.message)
+            .with_context(|| format!("Failed to write {}", file.display()))?;
+        let shas: Vec<&str> = group.commits.iter().map(|c| &c.sha[..8]).collect();
+        prompt.push_str(&format!(
+            "\n- {} (commits {})",
+            file.display(),
+            shas.join(" ")
+        ));
+        files.push(file);
+    }
+
+    let (program, mut args) = resolve_agent_command(agent)?;
+    args.push(prompt);
+    println!(
+        "{} Asking {} to write the commit messages...",
+        "🤖".cyan(),
+        program
+    );
+    let status = Command::new(&program)
+        .args(&args)
+        .current_dir(&worktree.path)
+        .status()
+        .with_context(|| format!("Failed to launch agent: {program}"))?;
+    if !status.success() {
+        bail!("Agent exited with error; the branch was not changed");
+    }
+
+    for (group, file) in groups.iter_mut().zip(&files) {
+        let message = fs::read_to_string(file).unwrap_or_default();
+        if !message.trim().is_empty() {
+            group.message = message.trim().to_string();
+        }
+    }
+    fs::remove_dir_all(&dir).ok();
+    Ok(())
+}
+
+/// Replay the plan with `git rebase -i`, feeding it a prepared todo list
+/// instead of opening an editor. Aborts and restores the branch on failure.
+fn run_rebase(worktree_path: &Path, merge_base: &str, groups: &[SquashGroup]) -> Result<()> {
+    let path = worktree_path.to_str().context("Invalid worktree path")?;
+    let dir = PathBuf::from(execute_git(&[
+        "-C",
+        path,
+        "rev-parse",
+        "--absolute-git-dir",
+    ])?)
+    .join("pigs-tidy");
+    fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+
+    let mut todo = String::new();
+    for (index, group) in groups.iter().enumerate() {
+        for (position, commit) in group.commits.iter().enumerate() {
+            let action = if position == 0 { "pick" } else { "fixup" };
+            todo.push_str(&format!("{action} {}\n", commit.sha));
+        }
+        if group.commits.len() > 1 {
+            let message_file = dir.join(format!("message-{}.txt", index + 1));
+            fs::write(&message_file, &group.message)?;
+            let quoted = shell_words::quote(&message_file.to_string_lossy()).into_owned();
+            todo.push_str(&format!(
+                "exec git commit --amend --no-verify --allow-empty --file {quoted}\n"
+            ));
+        }
+    }
+    let todo_file = dir.join("todo");
+    fs::write(&todo_file, todo)?;
+
+    let editor = format!("cp {}", shell_words::quote(&todo_file.to_string_lossy()));
+    let output = Command::new("git")
+        .args([
+            "-C",
+            path,
+            "rebase",
+            "--interactive",
+            "--no-autosquash",
+            merge_base,
+        ])
+        .env("GIT_SEQUENCE_EDITOR", editor)
+        .env("GIT_EDITOR", "true")
+        .output()
+        .context("Failed to run git rebase")?;
+    fs::remove_dir_all(&dir).ok();
+
+    if !output.status.success() {
+        let _ = execute_git(&["-C", path, "rebase", "--abort"]);
+        bail!(
+            "git rebase failed, so the branch was left unchanged: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commit(sha: &str, files: &[&str]) -> BranchCommit {
+        BranchCommit {
+            sha: sha.to_string(),
+            subject: format!("commit {sha}"),
+            files: files.iter().map(|f| f.to_string()).collect(),
+        }
+    }
+
+    fn shas(groups: &[Vec<BranchCommit>]) -> Vec<Vec<&str>> {
+        groups
+            .iter()
+            .map(|group| group.iter().map(|c| c.sha.as_str()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn commits_touching_the_same_files_are_grouped() {
+        let groups = group_commits(vec![
+            commit("1", &["src/a.rs"]),
+            commit("2", &["src/b.rs"]),
+            commit("3", &["src/a.rs", "tests/a.rs"]),
+            commit("4", &["tests/a.rs"]),
+        ]);
+
+        assert_eq!(shas(&groups), [vec!["1", "3", "4"], vec!["2"]]);
+    }
+
+    #[test]
+    fn a_commit_spanning_two_groups_merges_them() {
+        let groups = group_commits(vec![
+            commit("d", &["a"]),
+            commit("c", &["b"]),
+            commit("b", &["c"]),
+            commit("a", &["a", "b"]),
+        ]);
+
+        assert_eq!(shas(&groups), [vec!["d", "c", "a"], vec!["b"]]);
+    }
+}
@@ -0,0 +1,386 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::process::Command;
+
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Utc};
+use colored::Colorize;
+use serde::Serialize;
+
+use crate::activity::{self, ActivityRecord};
+use crate::artifacts::ArtifactRun;
+use crate::claude::get_claude_sessions;
+use crate::codex;
+use crate::git::{execute_git, resolve_base_ref};
+use crate::network;
+use crate::prompts::{self, PromptRecord};
+use crate::state::{PigsState, WorktreeInfo};
+
+/// Everything pigs recorded about the work on one issue.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TraceReport {
+    issue: String,
+    generated_at: DateTime<Utc>,
+    /// Agent session time across every worktree, in seconds
+    time_spent_secs: i64,
+    worktrees: Vec<TracedWorktree>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TracedWorktree {
+    repo_name: String,
+    name: String,
+    /// False once the worktree was deleted; only the logs remain then
+    tracked: bool,
+    branch: Option<String>,
+    base_branch: Option<String>,
+    path: Option<PathBuf>,
+    created_at: Option<DateTime<Utc>>,
+    /// Commits on the branch since it left the base branch, oldest first
+    commits: Vec<String>,
+    pull_request: Option<String>,
+    sessions: Vec<TracedSession>,
+    prompts: Vec<PromptRecord>,
+    verification: Vec<ArtifactRun>,
+    actions: Vec<ActivityRecord>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TracedSession {
+    agent: &'static str,
+    model: Option<String>,
+    started_at: Option<DateTime<Utc>>,
+    ended_at: Option<DateTime<Utc>>,
+    input_tokens: u64,
+    output_tokens: u64,
+}
+
+impl TracedSession {
+    fn duration_secs(&self) -> i64 {
+        self.started_at
+            .zip(self.ended_at)
+            .map_or(0, |(start, end)| (end - start).num_seconds().max(0))
+    }
+}
+
+/// Collect the worktrees, sessions, prompts, pull requests, and verification
+/// runs for an issue into one report. A worktree belongs to the issue when its
+/// name or branch mentions the issue id, as branches made by `pigs linear` do.
+pub fn handle_trace(issue: String, json: bool, output: Option<PathBuf>) -> Result<()> {
+    let issue = issue.trim().to_string();
+    if issue.is_empty() {
+        bail!("Issue id cannot be empty");
+    }
+
+    let state = PigsState::load()?;
+    let prompts = prompts::read_all()?;
+    let actions = activity::read_all()?;
+
+    let mut worktrees: BTreeMap<(String, String), TracedWorktree> = BTreeMap::new();
+    for info in state.worktrees.values() {
+        if mentions_issue(&info.name, &issue) || mentions_issue(&info.branch, &issue) {
+            worktrees.insert(
+                (info.repo_name.clone(), info.name.clone()),
+                trace_worktree(info),
+            );
+        }
+    }
+    // Deleted worktrees only survive in the prompt and activity logs
+    let logged = prompts
+        .iter()
+        .map(|prompt| (&prompt.repo_name, &prompt.worktree))
+        .chain(
+            actions
+                .iter()
+                .map(|action| (&action.repo_name, &action.worktree)),
+        );
+    for (repo_name, name) in logged {
+        if mentions_issue(name, &issue) {
+            worktrees
+                .entry((repo_name.clone(), name.clone()))
+                .or_insert_with(|| TracedWorktree::deleted(repo_name, name));
+        }
+    }
+    if worktrees.is_empty() {
+        bail!("Nothing recorded for '{issue}': no worktree name or branch mentions it");
+    }
+
+    for ((repo_name, name), traced) in &mut worktrees {
+        traced.prompts = prompts
+            .iter()
+            .filter(|prompt| &prompt.repo_name == repo_name && &prompt.worktree == name)
+            .cloned()
+            .collect();
+        traced.actions = actions
+            .iter()
+            .filter(|action| &action.repo_name == repo_name && &action.worktree == name)
+            .cloned()
+            .collect();
+    }
+
+    let worktrees: Vec<TracedWorktree> = worktrees.into_values().collect();
+    let report = TraceReport {
+        time_spent_secs: worktrees
+            .iter()
+            .flat_map(|traced| &traced.sessions)
+            .map(TracedSession::duration_secs)
+            .sum(),
+        issue,
+        generated_at: Utc::now(),
+        worktrees,
+    };
+
+    let rendered = if json {
+        format!("{}\n", serde_json::to_string_pretty(&report)?)
+    } else {
+        render_markdown(&report)
+    };
+    match output {
+        Some(path) => {
+            std::fs::write(&path, rendered)
+                .with_context(|| format!("Failed to write trace to {}", path.display()))?;
+            println!(
+                "{} Wrote trace for '{}' to {}",
+                "🧾".green(),
+                report.issue.cyan(),
+                path.display()
+            );
+        }
+        None => print!("{rendered}"),
+    }
+    Ok(())
+}
+
+/// Whether `text` contains the issue id as a whole token, ignoring case, so
+/// `ENG-12` matches `eng-12-fix-login` but not `eng-123`.
+fn mentions_issue(text: &str, issue: &str) -> bool {
+    let text = text.to_lowercase();
+    let issue = issue.to_lowercase();
+    text.match_indices(&issue).any(|(start, _)| {
+        let before = text[..start].chars().next_back();
+        let after = text[start + issue.len()..].chars().next();
+        !before.is_some_and(|c| c.is_ascii_alphanumeric())
+            && !after.is_some_and(|c| c.is_ascii_alphanumeric())
+    })
+}
+
+impl TracedWorktree {
+    fn deleted(repo_name: &str, name: &str) -> Self {
+        Self {
+            repo_name: repo_name.to_string(),
+            name: name.to_string(),
+            tracked: false,
+            branch: None,
+            base_branch: None,
+            path: None,
+            created_at: None,
+            commits: Vec::new(),
+            pull_request: None,
+            sessions: Vec::new(),
+            prompts: Vec::new(),
+            verification: Vec::new(),
+            actions: Vec::new(),
+        }
+    }
+}
+
+fn trace_worktree(info: &WorktreeInfo) -> TracedWorktree {
+    let mut sessions: Vec<TracedSession> = get_claude_sessions(&info.path)
+        .into_iter()
+        .map(|session| TracedSession {
+            agent: "claude",
+            model: session.model,
+            started_at: session.first_timestamp,
+            ended_at: session.last_activity.or(session.last_timestamp),
+            input_tokens: session.input_tokens,
+            output_tokens: session.output_tokens,
+        })
+        .collect();
+    if let Ok((codex_sessions, _)) = codex::recent_sessions(&info.path, usize::MAX) {
+        sessions.extend(codex_sessions.into_iter().map(|session| TracedSession {
+            agent: "codex",
+            model: session.model,
+            started_at: session.started_at,
+            ended_at: session.last_timestamp,
+            input_tokens: session.input_tokens,
+            output_tokens: session.output_tokens,
+        }));
+    }
+    sessions.sort_by_key(|session| session.started_at);
+
+    TracedWorktree {
+        repo_name: info.repo_name.clone(),
+        name: info.name.clone(),
+        tracked: true,
+        branch: Some(info.branch.clone()),
+        base_branch: info.base_branch.clone(),
+        path: Some(info.path.clone()),
+        created_at: Some(info.created_at),
+        commits: branch_commits(info),
+        pull_request: pull_request_url(info),
+        sessions,
+        prompts: Vec::new(),
+        verification: info.artifacts.clone(),
+        actions: Vec::new(),
+    }
+}
+
+fn branch_commits(info: &WorktreeInfo) -> Vec<String> {
+    let Some(path) = info.path.to_str().filter(|_| info.path.exists()) else {
+        return Vec::new();
+    };
+    let Some(base_ref) = info
+        .base_branch
+        .clone()
+        .or_else(|| resolve_base_ref(&info.path))
+    else {
+        return Vec::new();
+    };
+    execute_git(&[
+        "-C",
+        path,
+        "log",
+        "--reverse",
+        "--format=%h %s",
+        &format!("{base_ref}..HEAD"),
+    ])
+    .map(|log| log.lines().map(str::to_string).collect())
+    .unwrap_or_default()
+}
+
+fn pull_request_url(info: &WorktreeInfo) -> Option<String> {
+    if network::is_offline() || !info.path.exists() {
+        return None;
+    }
+    let output = Command::new("gh")
+        .args(["pr", "view", &info.branch, "--json", "url", "--jq", ".url"])
+        .current_dir(&info.path)
+        .output()
+        .ok()?;
+    let url = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !url.is_empty()).then_some(url)
+}
+
+fn format_duration(secs: i64) -> String {
+    let minutes = secs / 60;
+    if minutes < 60 {
+        format!("{minutes}m")
+    } else {
+        format!("{}h {:02}m", minutes / 60, minutes % 60)
+    }
+}
+
+fn format_time(ts: DateTime<Utc>) -> String {
+    ts.format("%Y-%m-%d %H:%M UTC").to_string()
+}
+
+fn render_markdown(report: &TraceReport) -> String {
+    let mut out = format!(
+        "# Trace: {}\n\nGenerated {}. Agent time: {} across {} worktree(s).\n",
+        report.issue,
+        format_time(report.generated_at),
+        format_duration(report.time_spent_secs),
+        report.worktrees.len()
+    );
+
+    for traced in &report.worktrees {
+        out.push_str(&format!("\n## {}/{}\n\n", traced.repo_name, traced.name));
+        if !traced.tracked {
+            out.push_str("Deleted; only logged prompts and actions remain.\n");
+        }
+        if let Some(branch) = &traced.branch {
+            let base = traced
+                .base_branch
+                .as_deref()
+                .map(|base| format!(" (from `{base}`)"))
+                .unwrap_or_default();
+            out.push_str(&format!("- Branch: `{branch}`{base}\n"));
+        }
+        if let Some(created_at) = traced.created_at {
+            out.push_str(&format!("- Created: {}\n", format_time(created_at)));
+        }
+        if let Some(url) = &traced.pull_request {
+            out.push_str(&format!("- Pull request: {url}\n"));
+        }
+
+        if !traced.commits.is_empty() {
+            out.push_str("\n### Commits\n\n");
+            for commit in &traced.commits {
+                out.push_str(&format!("- {commit}\n"));
+            }
+        }
+        if !traced.sessions.is_empty() {
+            out.push_str("\n### Sessions\n\n");
+            for session in &traced.sessions {
+                let started = session
+                    .started_at
+                    .map_or_else(|| "unknown start".to_string(), format_time);
+                let model = session
+                    .model
+                    .as_deref()
+                    .map(|model| format!(", {model}"))
+                    .unwrap_or_default();
+                out.push_str(&format!(
+                    "- {} {started}{model}: {}, {} in / {} out tokens\n",
+                    session.agent,
+                    format_duration(session.duration_secs()),
+                    session.input_tokens,
+                    session.output_tokens
+                ));
+            }
+        }
+        if !traced.prompts.is_empty() {
+            out.push_str("\n### Prompts\n");
+            for prompt in &traced.prompts {
+                out.push_str(&format!(
+                    "\n{}:\n\n> {}\n",
+                    format_time(prompt.timestamp),
+                    prompt.prompt.trim().replace('\n', "\n> ")
+                ));
+            }
+        }
+        if !traced.verification.is_empty() {
+            out.push_str("\n### Verification\n\n");
+            for run in &traced.verification {
+                out.push_str(&format!(
+                    "- {} {} {}\n",
+                    format_time(run.finished_at),
+                    run.task,
+                    if run.success { "✅" } else { "❌" }
+                ));
+            }
+        }
+        if !traced.actions.is_empty() {
+            out.push_str("\n### Actions\n\n");
+            for action in &traced.actions {
+                let detail = action
+                    .detail
+                    .as_deref()
+                    .map(|detail| format!(" ({detail})"))
+                    .unwrap_or_default();
+                out.push_str(&format!(
+                    "- {} {}{detail}\n",
+                    format_time(action.timestamp),
+                    action.action
+                ));
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::mentions_issue;
+
+    #[test]
+    fn mentions_issue_matches_whole_ids() {
+        assert!(mentions_issue("eng-12-fix-login", "ENG-12"));
+        assert!(mentions_issue("feature/ENG-12", "eng-12"));
+        assert!(!mentions_issue("eng-123-other", "ENG-12"));
+        assert!(!mentions_issue("xeng-12", "ENG-12"));
+    }
+}
@@ -0,0 +1,32 @@
+use anyhow::Result;
+use colored::Colorize;
+
+use crate::state::PigsState;
+use crate::trust::{PRIVILEGED_KEYS, set_trusted};
+
+pub fn handle_trust(revoke: bool) -> Result<()> {
+    let Some((path, settings)) = PigsState::find_local_settings()? else {
+        anyhow::bail!("No .pigs/settings.json found in this directory or its parents");
+    };
+
+    let keys = set_trusted(&path, &settings, !revoke)?;
+    if revoke {
+        println!("{} {} is no longer trusted", "🔒".yellow(), path.display());
+    } else if keys.is_empty() {
+        println!(
+            "{} {} doesn't set {}; nothing to trust",
+            "ℹ️".blue(),
+            path.display(),
+            PRIVILEGED_KEYS.join(", ")
+        );
+    } else {
+        println!(
+            "{} Trusted {} from {}",
+            "✅".green(),
+            keys.join(", "),
+            path.display()
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,392 @@
+use std::io::{BufRead, IsTerminal};
+use std::time::{Duration, Instant};
+
+use anyhow::{Result, bail};
+use chrono::{Local, Utc};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+
+use super::delete::{DirtyChanges, RemoteBranch, handle_delete};
+use super::list::{format_message_preview, format_time_ago};
+use super::open::handle_open;
+use super::sync::handle_sync;
+use super::watch::{ACTIVE_WINDOW, Tone, active_sessions, git_cell, needs_attention};
+use crate::dashboard::{self, DashboardPayload, WorktreeSummary};
+
+/// Sessions fetched per worktree, as many as the details pane lists.
+const SESSION_LIMIT: usize = 5;
+/// How often the worktree summaries are rebuilt while the TUI is idle.
+const REFRESH_EVERY: Duration = Duration::from_secs(5);
+const KEY_HELP: &str = "↑/↓ move  Enter/o open  s sync  d delete  r refresh  q quit";
+
+/// Something the TUI hands back to the terminal to run, since it prints or
+/// prompts like the matching `pigs` command.
+enum Action {
+    Open(String),
+    Sync(String),
+    Delete(String),
+}
+
+struct App {
+    payload: DashboardPayload,
+    list: ListState,
+    refreshed: Instant,
+    message: Option<String>,
+}
+
+impl App {
+    fn new(payload: DashboardPayload) -> Self {
+        let mut list = ListState::default();
+        if !payload.worktrees.is_empty() {
+            list.select(Some(0));
+        }
+        Self {
+            payload,
+            list,
+            refreshed: Instant::now(),
+            message: None,
+        }
+    }
+
+    /// Rebuild the summaries, keeping the same worktree selected if it is
+    /// still there.
+    fn refresh(&mut self) {
+        let selected = self.selected().map(|tree| tree.key.clone());
+        match dashboard::build_dashboard_payload(SESSION_LIMIT) {
+            Ok(payload) => {
+                self.payload = payload;
+                self.message = None;
+            }
+            Err(err) => self.message = Some(format!("Failed to refresh: {err:#}")),
+        }
+        let count = self.payload.worktrees.len();
+        let index = selected
+            .and_then(|key| self.payload.worktrees.iter().position(|t| t.key == key))
+            .or_else(|| {
+                self.list
+                    .selected()
+                    .map(|index| index.min(count.saturating_sub(1)))
+            });
+        self.list
+            .select(if count == 0 { None } else { index.or(Some(0)) });
+        self.refreshed = Instant::now();
+    }
+
+    fn selected(&self) -> Option<&WorktreeSummary> {
+        self.list
+            .selected()
+            .and_then(|index| self.payload.worktrees.get(index))
+    }
+
+    /// Draw and handle keys until the user quits (`None`) or picks an action.
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> Result<Option<Action>> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+            if !event::poll(Duration::from_millis(250))? {
+                if self.refreshed.elapsed() >= REFRESH_EVERY {
+                    self.refresh();
+                }
+                continue;
+            }
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            let name = self.selected().map(|tree| tree.name.clone());
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(None),
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    return Ok(None);
+                }
+                KeyCode::Down | KeyCode::Char('j') => self.list.select_next(),
+                KeyCode::Up | KeyCode::Char('k') => self.list.select_previous(),
+                KeyCode::Home | KeyCode::Char('g') => self.list.select_first(),
+                KeyCode::End | KeyCode::Char('G') => self.list.select_last(),
+                KeyCode::Char('r') => self.refresh(),
+                KeyCode::Enter | KeyCode::Char('o') => {
+                    if let Some(name) = name {
+                        return Ok(Some(Action::Open(name)));
+                    }
+                }
+                KeyCode::Char('s') => {
+                    if let Some(name) = name {
+                        return Ok(Some(Action::Sync(name)));
+                    }
+                }
+                KeyCode::Char('d') => {
+                    if let Some(name) = name {
+                        return Ok(Some(Action::Delete(name)));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [title, body, footer] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+
+        let refreshed = self
+            .payload
+            .generated_at
+            .with_timezone(&Local)
+            .format("%H:%M:%S");
+        frame.render_widget(
+            Line::from(vec![
+                Span::from(" pigs ").bold().cyan(),
+                Span::from(format!(
+                    "{} worktree(s), refreshed {refreshed}",
+                    self.payload.worktrees.len()
+                ))
+                .dark_gray(),
+            ]),
+            title,
+        );
+        let help = match &self.message {
+            Some(message) => Line::from(message.as_str()).red(),
+            None => Line::from(KEY_HELP).dark_gray(),
+        };
+        frame.render_widget(help, footer);
+
+        if self.payload.worktrees.is_empty() {
+            frame.render_widget(
+                Paragraph::new("No active worktrees. Create one with `pigs create`.")
+                    .block(Block::bordered().title(" Worktrees ")),
+                body,
+            );
+            return;
+        }
+
+        let [left, right] =
+            Layout::horizontal([Constraint::Percentage(35), Constraint::Percentage(65)])
+                .areas(body);
+        self.draw_list(frame, left);
+        if let Some(tree) = self.selected() {
+            frame.render_widget(
+                Paragraph::new(details(tree, self.payload.focus.as_deref()))
+                    .block(Block::bordered().title(format!(" {} ", tree.key)))
+                    .wrap(Wrap { trim: false }),
+                right,
+            );
+        }
+    }
+
+    fn draw_list(&mut self, frame: &mut Frame, area: Rect) {
+        let now = Utc::now();
+        let items: Vec<ListItem> = self
+            .payload
+            .worktrees
+            .iter()
+            .map(|tree| {
+                let name = if tree.stack_depth == 0 {
+                    tree.key.clone()
+                } else {
+                    format!("{}└─ {}", "  ".repeat(tree.stack_depth - 1), tree.name)
+                };
+                let agent = if active_sessions(tree, now) > 0 {
+                    Span::from("● ").green()
+                } else {
+                    Span::from("  ")
+                };
+                let mut spans = vec![agent, Span::from(name)];
+                if !tree.git_status.clean {
+                    spans.push(Span::from(" *").yellow());
+                }
+                if !needs_attention(tree).is_empty() {
+                    spans.push(Span::from(" !").red().bold());
+                }
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+        let list = List::new(items)
+            .block(Block::bordered().title(" Worktrees "))
+            .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, area, &mut self.list);
+    }
+}
+
+fn tone_color(tone: Tone) -> Color {
+    match tone {
+        Tone::Good => Color::Green,
+        Tone::Warn => Color::Yellow,
+        Tone::Bad => Color::Red,
+        Tone::Dim => Color::DarkGray,
+    }
+}
+
+fn field<'a>(label: &'a str, value: impl Into<Span<'a>>) -> Line<'a> {
+    Line::from(vec![
+        Span::from(format!("{label:<10}")).dark_gray(),
+        value.into(),
+    ])
+}
+
+/// Lines of the details pane for `tree`.
+fn details<'a>(tree: &'a WorktreeSummary, focus: Option<&str>) -> Vec<Line<'a>> {
+    let status = &tree.git_status;
+    let (git, tone) = git_cell(status);
+    let mut lines = vec![
+        field("Branch", tree.branch.as_str()),
+        field("Path", tree.path.as_str()),
+        field("Git", Span::from(git).fg(tone_color(tone))),
+    ];
+    if let Some(upstream) = &status.upstream {
+        lines.push(field("Upstream", upstream.as_str()));
+    }
+    if let Some(base) = &status.base_ref {
+        lines.push(field(
+            "Base",
+            format!(
+                "{base} (ahead {}, behind {})",
+                status.ahead_base.unwrap_or(0),
+                status.behind_base.unwrap_or(0)
+            ),
+        ));
+    }
+    if let Some(message) = &status.last_commit_message {
+        lines.push(field(
+            "Commit",
+            format!(
+                "{} ({})",
+                format_message_preview(message, 60),
+                format_time_ago(status.last_commit_time)
+            ),
+        ));
+    }
+    lines.push(field("Active", format_time_ago(Some(tree.last_activity))));
+    if focus == Some(tree.key.as_str()) {
+        lines.push(field("Focus", Span::from("yes").cyan()));
+    }
+    if let Some(note) = &tree.note {
+        lines.push(field("Note", note.as_str()));
+    }
+    if !tree.tags.is_empty() {
+        lines.push(field("Tags", tree.tags.join(", ")));
+    }
+
+    let attention = needs_attention(tree);
+    if !attention.is_empty() {
+        lines.push(Line::default());
+        lines.push(Line::from("Needs attention").red().bold());
+        for problem in &tree.problems {
+            lines.push(Line::from(format!("  ⚠ {}: {}", problem.message, problem.hint)).red());
+        }
+        for item in attention.iter().skip(tree.problems.len()) {
+            lines.push(Line::from(format!("  ⚠ {item}")).red());
+        }
+    }
+
+    lines.push(Line::default());
+    lines.push(Line::from("Sessions").bold());
+    if tree.sessions.is_empty() {
+        lines.push(Line::from("  none yet").dark_gray());
+    }
+    let now = Utc::now();
+    for session in &tree.sessions {
+        let active = session.timestamp.is_some_and(|ts| now - ts < ACTIVE_WINDOW);
+        lines.push(Line::from(vec![
+            if active {
+                Span::from("  ● ").green()
+            } else {
+                Span::from("    ")
+            },
+            Span::from(format!("{:<7}", session.provider)).cyan(),
+            Span::from(format!("{:<9}", format_time_ago(session.timestamp))).dark_gray(),
+            Span::from(format_message_preview(
+                session.message.as_deref().unwrap_or_default(),
+                80,
+            )),
+        ]));
+    }
+    lines
+}
+
+/// Full-screen worktree browser: the worktree list on the left, the selected
+/// worktree's git status and sessions on the right, with keys to open, sync,
+/// or delete it. Actions run in the normal terminal like their commands do,
+/// then the TUI comes back.
+pub fn handle_tui() -> Result<()> {
+    if !std::io::stdout().is_terminal() || !std::io::stdin().is_terminal() {
+        bail!(
+            "pigs tui needs an interactive terminal; use 'pigs list' or 'pigs watch --once' instead"
+        );
+    }
+    let mut app = App::new(dashboard::build_dashboard_payload(SESSION_LIMIT)?);
+
+    loop {
+        let mut terminal = ratatui::init();
+        let outcome = app.run(&mut terminal);
+        ratatui::restore();
+        let Some(action) = outcome? else {
+            return Ok(());
+        };
+
+        let (result, pause) = match action {
+            Action::Open(name) => (handle_open(Some(name), None, Vec::new()), false),
+            Action::Sync(name) => (handle_sync(Some(name), false), true),
+            Action::Delete(name) => (
+                handle_delete(
+                    Some(name),
+                    false,
+                    None,
+                    false,
+                    DirtyChanges::Refuse,
+                    RemoteBranch::Keep,
+                ),
+                true,
+            ),
+        };
+        if let Err(err) = &result {
+            eprintln!("{} {err:#}", colored::Colorize::red("❌"));
+        }
+        if pause || result.is_err() {
+            println!(
+                "{}",
+                colored::Colorize::bright_black("Press Enter to return to pigs tui")
+            );
+            std::io::stdin().lock().read_line(&mut String::new())?;
+        }
+        app.refresh();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::App;
+    use crate::dashboard::DashboardPayload;
+    use chrono::Utc;
+    use ratatui::Terminal;
+    use ratatui::backend::TestBackend;
+
+    #[test]
+    fn empty_state_points_at_create() {
+        let mut app = App::new(DashboardPayload {
+            generated_at: Utc::now(),
+            focus: None,
+            worktrees: Vec::new(),
+        });
+        let mut terminal = Terminal::new(TestBackend::new(80, 6)).unwrap();
+        terminal.draw(|frame| app.draw(frame)).unwrap();
+        let screen: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+        assert!(screen.contains("0 worktree(s)"), "{screen}");
+        assert!(screen.contains("No active worktrees"), "{screen}");
+        assert!(screen.contains("q quit"), "{screen}");
+    }
+}
@@ -0,0 +1,287 @@
+use std::time::Duration;
+
+use anyhow::{Result, bail};
+use chrono::{DateTime, Local, Utc};
+use colored::{ColoredString, Colorize};
+use console::Term;
+
+use super::list::format_time_ago;
+use crate::dashboard::{self, DashboardPayload, GitStatusSummary, WorktreeSummary};
+
+/// Sessions fetched per worktree; only the newest few matter for the table.
+const SESSION_LIMIT: usize = 5;
+/// A session whose transcript was written this recently counts as active.
+pub(super) const ACTIVE_WINDOW: chrono::Duration = chrono::Duration::minutes(2);
+const MAX_BRANCH_WIDTH: usize = 32;
+
+/// Show a table of every worktree, its git status, agent sessions, and anything
+/// that needs attention, redrawn every `interval` seconds until interrupted.
+/// The rows come from the same summaries the dashboard serves. With `once`, or
+/// when stdout isn't a terminal, frames are printed without clearing the screen.
+pub fn handle_watch(interval: u64, once: bool) -> Result<()> {
+    if interval == 0 {
+        bail!("--interval must be at least 1 second");
+    }
+    let term = Term::stdout();
+    let live = !once && term.is_term();
+
+    loop {
+        let frame = match dashboard::build_dashboard_payload(SESSION_LIMIT) {
+            Ok(payload) => render(&payload, interval, live),
+            Err(err) if !once => vec![format!(
+                "{} Failed to refresh worktrees: {err:#}",
+                "⚠️".yellow()
+            )],
+            Err(err) => return Err(err),
+        };
+
+        if live {
+            term.clear_screen()?;
+            let width = usize::from(term.size().1);
+            for line in frame {
+                term.write_line(&console::truncate_str(&line, width, "…"))?;
+            }
+        } else {
+            for line in frame {
+                println!("{line}");
+            }
+        }
+
+        if once {
+            return Ok(());
+        }
+        if !live {
+            println!();
+        }
+        std::thread::sleep(Duration::from_secs(interval));
+    }
+}
+
+struct Row {
+    name: String,
+    branch: String,
+    git: (String, Tone),
+    agent: (String, Tone),
+    activity: String,
+    attention: Vec<String>,
+}
+
+#[derive(Clone, Copy)]
+pub(super) enum Tone {
+    Good,
+    Warn,
+    Bad,
+    Dim,
+}
+
+fn paint(text: String, tone: Tone) -> ColoredString {
+    match tone {
+        Tone::Good => text.green(),
+        Tone::Warn => text.yellow(),
+        Tone::Bad => text.red(),
+        Tone::Dim => text.bright_black(),
+    }
+}
+
+fn render(payload: &DashboardPayload, interval: u64, live: bool) -> Vec<String> {
+    let now = Utc::now();
+    let rows: Vec<Row> = payload
+        .worktrees
+        .iter()
+        .map(|tree| summarize_row(tree, payload.focus.as_deref(), now))
+        .collect();
+
+    let active = rows
+        .iter()
+        .filter(|row| matches!(row.agent.1, Tone::Good))
+        .count();
+    let attention = rows.iter().filter(|row| !row.attention.is_empty()).count();
+    let refreshed = payload
+        .generated_at
+        .with_timezone(&Local)
+        .format("%H:%M:%S");
+    let mut header = format!(
+        "{} {} worktree(s), {} with active agents, {} needing attention",
+        "👀".cyan(),
+        rows.len(),
+        active,
+        attention
+    );
+    let footer = if live {
+        format!("refreshed {refreshed}, every {interval}s; Ctrl+C to quit")
+    } else {
+        format!("at {refreshed}")
+    };
+    header.push_str(&format!(" {}", footer.bright_black()));
+
+    let mut lines = vec![header, String::new()];
+    if rows.is_empty() {
+        lines.push(format!("{} No active worktrees", "📭".yellow()));
+        return lines;
+    }
+
+    let width = |column: fn(&Row) -> usize, title: &str| {
+        rows.iter()
+            .map(column)
+            .max()
+            .unwrap_or(0)
+            .max(title.chars().count())
+    };
+    let name_width = width(|row| row.name.chars().count(), "WORKTREE");
+    let branch_width = width(|row| row.branch.chars().count(), "BRANCH");
+    let git_width = width(|row| row.git.0.chars().count(), "GIT");
+    let agent_width = width(|row| row.agent.0.chars().count(), "AGENT");
+    let activity_width = width(|row| row.activity.chars().count(), "ACTIVITY");
+
+    lines.push(
+        format!(
+            "{:<name_width$}  {:<branch_width$}  {:<git_width$}  {:<agent_width$}  {:<activity_width$}  ATTENTION",
+            "WORKTREE", "BRANCH", "GIT", "AGENT", "ACTIVITY"
+        )
+        .bold()
+        .to_string(),
+    );
+    for row in rows {
+        let attention = if row.attention.is_empty() {
+            "-".bright_black()
+        } else {
+            format!("⚠ {}", row.attention.join("; ")).red()
+        };
+        lines.push(format!(
+            "{}  {:<branch_width$}  {}  {}  {}  {}",
+            format!("{:<name_width$}", row.name).cyan(),
+            row.branch,
+            paint(format!("{:<git_width$}", row.git.0), row.git.1),
+            paint(format!("{:<agent_width$}", row.agent.0), row.agent.1),
+            format!("{:<activity_width$}", row.activity).bright_black(),
+            attention
+        ));
+    }
+    lines
+}
+
+fn summarize_row(tree: &WorktreeSummary, focus: Option<&str>, now: DateTime<Utc>) -> Row {
+    let mut name = if tree.stack_depth == 0 {
+        tree.key.clone()
+    } else {
+        format!("{}└─ {}", "  ".repeat(tree.stack_depth - 1), tree.name)
+    };
+    if focus == Some(tree.key.as_str()) {
+        name.push_str(" *");
+    }
+
+    let mut branch = tree.branch.clone();
+    if branch.chars().count() > MAX_BRANCH_WIDTH {
+        branch = branch.chars().take(MAX_BRANCH_WIDTH - 1).collect();
+        branch.push('…');
+    }
+
+    let active = active_sessions(tree, now);
+    let agent = match tree.sessions.first() {
+        Some(session) if active > 0 => (
+            format!("● {} ({active} active)", session.provider),
+            Tone::Good,
+        ),
+        Some(session) => (format!("○ {} idle", session.provider), Tone::Dim),
+        None => ("-".to_string(), Tone::Dim),
+    };
+
+    Row {
+        name,
+        branch,
+        git: git_cell(&tree.git_status),
+        agent,
+        activity: format_time_ago(Some(tree.last_activity)),
+        attention: needs_attention(tree),
+    }
+}
+
+/// Sessions of `tree` whose transcript was written within `ACTIVE_WINDOW`.
+pub(super) fn active_sessions(tree: &WorktreeSummary, now: DateTime<Utc>) -> usize {
+    tree.sessions
+        .iter()
+        .filter(|session| session.timestamp.is_some_and(|ts| now - ts < ACTIVE_WINDOW))
+        .count()
+}
+
+/// What needs attention in `tree`: git problems, an overdue reminder, or a
+/// status git couldn't read.
+pub(super) fn needs_attention(tree: &WorktreeSummary) -> Vec<String> {
+    let mut attention: Vec<String> = tree
+        .problems
+        .iter()
+        .map(|problem| problem.message.clone())
+        .collect();
+    if let Some(reminder) = tree.reminder.as_ref().filter(|reminder| reminder.overdue) {
+        attention.push(match &reminder.note {
+            Some(note) => format!("reminder: {note}"),
+            None => "reminder due".to_string(),
+        });
+    }
+    if let Some(error) = &tree.git_status.error {
+        attention.push(error.clone());
+    }
+    attention
+}
+
+/// Compact git status: `clean`, or counts of staged (`+`), unstaged (`~`),
+/// untracked (`?`), and conflicted (`!`) files, then commits ahead of and
+/// behind the upstream.
+pub(super) fn git_cell(status: &GitStatusSummary) -> (String, Tone) {
+    if status.error.is_some() {
+        return ("unknown".to_string(), Tone::Bad);
+    }
+    let mut parts = Vec::new();
+    for (count, symbol) in [
+        (status.staged_files, '+'),
+        (status.unstaged_files, '~'),
+        (status.untracked_files, '?'),
+        (status.conflict_files, '!'),
+    ] {
+        if count > 0 {
+            parts.push(format!("{symbol}{count}"));
+        }
+    }
+    let tone = if status.conflict_files > 0 {
+        Tone::Bad
+    } else if parts.is_empty() {
+        Tone::Good
+    } else {
+        Tone::Warn
+    };
+    if parts.is_empty() {
+        parts.push("clean".to_string());
+    }
+    if let Some(ahead) = status.ahead_upstream.filter(|&n| n > 0) {
+        parts.push(format!("↑{ahead}"));
+    }
+    if let Some(behind) = status.behind_upstream.filter(|&n| n > 0) {
+        parts.push(format!("↓{behind}"));
+    }
+    (parts.join(" "), tone)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::git_cell;
+    use crate::dashboard::GitStatusSummary;
+
+    #[test]
+    fn git_cell_summarizes_counts_and_upstream() {
+        let clean = GitStatusSummary {
+            clean: true,
+            ahead_upstream: Some(2),
+            behind_upstream: Some(0),
+            ..Default::default()
+        };
+        assert_eq!(git_cell(&clean).0, "clean ↑2");
+
+        let dirty = GitStatusSummary {
+            staged_files: 1,
+            untracked_files: 3,
+            conflict_files: 1,
+            ..Default::default()
+        };
+        assert_eq!(git_cell(&dirty).0, "+1 ?3 !1");
+    }
+}
@@ -1,396 +1,305 @@
-use anyhow::Result;
-use clap_complete::Shell;
+use std::ffi::OsStr;
 
-pub fn handle_completions(shell: Shell) -> Result<()> {
-    match shell {
-        Shell::Bash => print_bash_completions(),
-        Shell::Zsh => print_zsh_completions(),
-        Shell::Fish => print_fish_completions(),
-        _ => {
-            eprintln!("Unsupported shell: {:?}", shell);
-            eprintln!("Supported shells: bash, zsh, fish");
-        }
-    }
-    Ok(())
-}
-
-fn print_bash_completions() {
-    println!(
-        r#"#!/bin/bash
-
-_pigs() {{
-    local cur prev words cword
-    if type _init_completion &>/dev/null; then
-        _init_completion || return
-    else
-        # Fallback for older bash-completion
-        COMPREPLY=()
-        cur="${{COMP_WORDS[COMP_CWORD]}}"
-        prev="${{COMP_WORDS[COMP_CWORD-1]}}"
-        words=("${{COMP_WORDS[@]}}")
-        cword=$COMP_CWORD
-    fi
-
-    # Main commands
-    local commands="linear create checkout review open delete add rename list clean dir completions"
+use anyhow::Result;
+use clap::{Arg, Command, ValueEnum};
+use clap_complete::engine::{ArgValueCompleter, CompletionCandidate};
+use clap_complete::env::{self, EnvCompleter};
+use clap_complete_nushell::Nushell;
 
-    # Complete main commands
-    if [[ $cword -eq 1 ]]; then
-        COMPREPLY=($(compgen -W "$commands" -- "$cur"))
-        return
-    fi
+use crate::commands::complete::{
+    agent_names, from_candidates, task_candidates, worktree_candidates,
+};
+use crate::commands::complete_linear::linear_candidates;
 
-    # Complete subcommand arguments
-    case "${{words[1]}}" in
-        linear)
-            if [[ "$prev" == "--from" ]]; then
-                local targets=$(pigs complete-from 2>/dev/null)
-                COMPREPLY=($(compgen -W "$targets" -- "$cur"))
-            elif [[ "$prev" == "--agent" || "$prev" == "-a" ]]; then
-                local agents=$(pigs complete-agents 2>/dev/null)
-                COMPREPLY=($(compgen -W "$agents" -- "$cur"))
-            elif [[ "$cur" == -* ]]; then
-                COMPREPLY=($(compgen -W "--from --agent -a -y" -- "$cur"))
-            else
-                local linear_issues=$(pigs complete-linear 2>/dev/null | cut -f1)
-                COMPREPLY=($(compgen -W "$linear_issues" -- "$cur"))
-            fi
-            ;;
-        create)
-            if [[ "$prev" == "--from" ]]; then
-                local targets=$(pigs complete-from 2>/dev/null)
-                COMPREPLY=($(compgen -W "$targets" -- "$cur"))
-            elif [[ "$prev" == "--agent" || "$prev" == "-a" ]]; then
-                local agents=$(pigs complete-agents 2>/dev/null)
-                COMPREPLY=($(compgen -W "$agents" -- "$cur"))
-            elif [[ "$cur" == -* ]]; then
-                COMPREPLY=($(compgen -W "--from --agent -a -y" -- "$cur"))
-            fi
-            ;;
-        checkout)
-            if [[ "$prev" == "--agent" || "$prev" == "-a" ]]; then
-                local agents=$(pigs complete-agents 2>/dev/null)
-                COMPREPLY=($(compgen -W "$agents" -- "$cur"))
-            elif [[ "$cur" == -* ]]; then
-                COMPREPLY=($(compgen -W "--agent -a -y" -- "$cur"))
-            fi
-            ;;
-        review)
-            if [[ "$cur" == -* ]]; then
-                COMPREPLY=($(compgen -W "--base" -- "$cur"))
-            elif [[ $cword -eq 2 ]]; then
-                COMPREPLY=($(compgen -W "finish abort" -- "$cur"))
-            fi
-            ;;
-        open)
-            if [[ "$prev" == "--agent" || "$prev" == "-a" ]]; then
-                local agents=$(pigs complete-agents 2>/dev/null)
-                COMPREPLY=($(compgen -W "$agents" -- "$cur"))
-            elif [[ "$cur" == -* ]]; then
-                COMPREPLY=($(compgen -W "--agent -a" -- "$cur"))
-            elif [[ $cword -eq 2 ]]; then
-                # Get worktree names for completion
-                local worktrees=$(pigs complete-worktrees 2>/dev/null)
-                COMPREPLY=($(compgen -W "$worktrees" -- "$cur"))
-            fi
-            ;;
-        dir|delete)
-            if [[ $cword -eq 2 ]]; then
-                # Get worktree names for completion
-                local worktrees=$(pigs complete-worktrees 2>/dev/null)
-                COMPREPLY=($(compgen -W "$worktrees" -- "$cur"))
-            fi
-            ;;
-        rename)
-            if [[ $cword -eq 2 ]]; then
-                # Complete first argument (old name)
-                local worktrees=$(pigs complete-worktrees 2>/dev/null)
-                COMPREPLY=($(compgen -W "$worktrees" -- "$cur"))
-            fi
-            ;;
-        completions)
-            if [[ $cword -eq 2 ]]; then
-                COMPREPLY=($(compgen -W "bash zsh fish" -- "$cur"))
-            fi
-            ;;
-    esac
-}}
+/// Set by the completion scripts when they call pigs back for completions;
+/// `main` answers those calls instead of running a command.
+pub const COMPLETE_VAR: &str = "PIGS_COMPLETE";
 
-complete -F _pigs pigs
-"#
-    );
+/// Shells `pigs completions` writes a script for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum CompletionShell {
+    Bash,
+    Zsh,
+    Fish,
+    #[value(name = "powershell")]
+    PowerShell,
+    Nushell,
 }
 
-fn print_zsh_completions() {
-    println!(
-        r#"#compdef pigs
-
-_pigs() {{
-    local -a commands
-    commands=(
-        'linear:Create a new git worktree from a Linear issue'
-        'create:Create a new git worktree'
-        'checkout:Checkout a branch or pull request into a worktree'
-        'review:Review a PR with all changes staged for browsing'
-        'open:Open an existing worktree and launch agent'
-        'delete:Delete a worktree and clean up'
-        'add:Add current worktree to pigs management'
-        'rename:Rename a worktree'
-        'list:List all active agent sessions'
-        'clean:Clean up invalid worktrees from state'
-        'dir:Get the directory path of a worktree'
-        'completions:Generate shell completions'
-    )
-
-    # Main command completion
-    if (( CURRENT == 2 )); then
-        _describe 'command' commands
-        return
-    fi
-
-    case "${{words[2]}}" in
-        open)
-            case "${{words[CURRENT-1]}}" in
-                --agent|-a) _pigs_agents ;;
-                *)
-                    if [[ "${{words[CURRENT]}}" == -* ]]; then
-                        local -a open_opts
-                        open_opts=('-a:Select agent at runtime' '--agent:Select agent at runtime')
-                        _describe 'option' open_opts
-                    elif (( CURRENT == 3 )); then
-                        _pigs_worktrees
-                    fi
-                    ;;
-            esac
-            ;;
-        dir|delete)
-            if (( CURRENT == 3 )); then
-                _pigs_worktrees
-            fi
-            ;;
-        rename)
-            if (( CURRENT == 3 )); then
-                _pigs_worktrees
-            elif (( CURRENT == 4 )); then
-                _message "new name"
-            fi
-            ;;
-        linear)
-            case "${{words[CURRENT-1]}}" in
-                --from) _pigs_from_targets ;;
-                --agent|-a) _pigs_agents ;;
-                *)
-                    if [[ "${{words[CURRENT]}}" == -* ]]; then
-                        local -a linear_opts
-                        linear_opts=('--from:Create from an existing worktree or branch' '-a:Select agent at runtime' '--agent:Select agent at runtime' '-y:Automatically confirm prompts')
-                        _describe 'option' linear_opts
-                    else
-                        _pigs_linear_issues
-                    fi
-                    ;;
-            esac
-            ;;
-        create)
-            case "${{words[CURRENT-1]}}" in
-                --from) _pigs_from_targets ;;
-                --agent|-a) _pigs_agents ;;
-                *)
-                    if [[ "${{words[CURRENT]}}" == -* ]]; then
-                        local -a create_opts
-                        create_opts=('--from:Create from an existing worktree or branch' '-a:Select agent at runtime' '--agent:Select agent at runtime' '-y:Automatically open after creation')
-                        _describe 'option' create_opts
-                    fi
-                    ;;
-            esac
-            ;;
-        checkout)
-            case "${{words[CURRENT-1]}}" in
-                --agent|-a) _pigs_agents ;;
-                *)
-                    if [[ "${{words[CURRENT]}}" == -* ]]; then
-                        local -a checkout_opts
-                        checkout_opts=('-a:Select agent at runtime' '--agent:Select agent at runtime' '-y:Automatically open the worktree after creation')
-                        _describe 'option' checkout_opts
-                    fi
-                    ;;
-            esac
-            ;;
-        review)
-            if [[ "${{words[CURRENT]}}" == -* ]]; then
-                local -a review_opts
-                review_opts=('--base:Base branch to diff against (default: develop)')
-                _describe 'option' review_opts
-            elif (( CURRENT == 3 )); then
-                _alternative 'args:subcommand:(finish abort)'
-            fi
-            ;;
-        add)
-            if (( CURRENT == 3 )); then
-                _message "worktree name"
-            fi
-            ;;
-        completions)
-            if (( CURRENT == 3 )); then
-                local -a shells
-                shells=(bash zsh fish)
-                _describe 'shell' shells
-            fi
-            ;;
-    esac
-}}
+/// Print the completion script for `shell`. Bash, zsh, fish, and PowerShell
+/// get a short script that asks pigs for completions as you type, so they
+/// always match the CLI. Nushell can't call back, so its module is generated
+/// from `cmd`, with helpers that call pigs for the dynamic values.
+pub fn handle_completions(shell: CompletionShell, mut cmd: Command) -> Result<()> {
+    let registration: &dyn EnvCompleter = match shell {
+        CompletionShell::Bash => &env::Bash,
+        CompletionShell::Zsh => &env::Zsh,
+        CompletionShell::Fish => &env::Fish,
+        CompletionShell::PowerShell => &env::Powershell,
+        CompletionShell::Nushell => {
+            print!("{}", nushell_completions(&mut cmd));
+            return Ok(());
+        }
+    };
+    registration.write_registration(
+        COMPLETE_VAR,
+        "pigs",
+        "pigs",
+        "pigs",
+        &mut std::io::stdout(),
+    )?;
+    Ok(())
+}
 
-_pigs_worktrees() {{
-    local -a worktrees
-    local IFS=$'\n'
-    
-    # Get detailed worktree information (sorted by repo, then by name)
-    local worktree_data
-    worktree_data=($(pigs complete-worktrees --format=detailed 2>/dev/null))
-    
-    if [[ -n "$worktree_data" ]]; then
-        for line in $worktree_data; do
-            # Parse tab-separated values: name<TAB>repo<TAB>path<TAB>sessions
-            local name=$(echo "$line" | cut -f1)
-            local repo=$(echo "$line" | cut -f2)
-            local sessions=$(echo "$line" | cut -f4)
-            
-            # Add worktree with clear repo marker and session info
-            worktrees+=("$name:[$repo] $sessions")
-        done
-        
-        # Use _describe for better presentation
-        # -V flag preserves the order (no sorting)
-        if (( ${{#worktrees[@]}} > 0 )); then
-            _describe -V -t worktrees 'worktree' worktrees
-        fi
-    else
-        # Fallback to simple completion
-        local simple_worktrees
-        simple_worktrees=($(pigs complete-worktrees 2>/dev/null))
-        if [[ -n "$simple_worktrees" ]]; then
-            compadd -a simple_worktrees
-        fi
-    fi
-}}
+/// `cmd` with a completer on every argument whose values are only known at
+/// completion time: worktree names, agents, `--from` targets, tasks, Linear
+/// issues.
+pub fn with_dynamic_values(mut cmd: Command) -> Command {
+    let hooks: Vec<(String, String, DynamicValues)> = dynamic_args(&cmd)
+        .into_iter()
+        .map(|(subcommand, arg, values)| (subcommand.to_string(), arg.get_id().to_string(), values))
+        .collect();
+    for (subcommand, id, values) in hooks {
+        // `mut_arg` re-appends the argument, which would reorder positionals.
+        cmd = cmd.mut_subcommand(subcommand, |sub| {
+            sub.mut_args(|arg| {
+                if arg.get_id() == id.as_str() {
+                    arg.add(values.completer())
+                } else {
+                    arg
+                }
+            })
+        });
+    }
+    cmd
+}
 
-_pigs_linear_issues() {{
-    local -a issues
-    local IFS=$'\n'
-    local issue_data
-    issue_data=($(pigs complete-linear 2>/dev/null))
+/// Subcommands whose positional arguments name existing worktrees.
+const WORKTREE_COMMANDS: &[&str] = &[
+    "open",
+    "dir",
+    "switch",
+    "delete",
+    "archive",
+    "diff",
+    "diff-snapshots",
+    "replay-prompts",
+    "cherry-pick",
+    "merge",
+    "pr",
+    "sync",
+    "tidy",
+    "tag",
+    "note",
+    "remind",
+    "artifacts",
+    "focus",
+    "sessions",
+    "resume",
+    "log",
+    "rename",
+];
 
-    if [[ -n "$issue_data" ]]; then
-        for line in $issue_data; do
-            local id=$(echo "$line" | cut -f1)
-            local title=$(echo "$line" | cut -f2)
-            issues+=("$id:$title")
-        done
-        _describe -V 'Linear issue' issues
-    fi
-}}
+/// Positional arguments of `WORKTREE_COMMANDS` that take a worktree name; the
+/// others are commit ranges, session ids, and the like.
+const WORKTREE_ARGS: &[&str] = &["name", "old_name", "source", "target", "dest"];
 
-_pigs_from_targets() {{
-    local -a targets
-    targets=($(pigs complete-from 2>/dev/null))
-    if [[ -n "$targets" ]]; then
-        compadd -a targets
-    fi
-}}
+/// Values only known at completion time. Nushell lists them with the hidden
+/// `pigs complete-*` commands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DynamicValues {
+    Worktrees,
+    Agents,
+    FromTargets,
+    Tasks,
+    LinearIssues,
+}
 
-_pigs_agents() {{
-    local -a agents
-    agents=($(pigs complete-agents 2>/dev/null))
-    if [[ -n "$agents" ]]; then
-        compadd -a agents
-    fi
-}}
+impl DynamicValues {
+    /// Name of the helper each script defines for these values.
+    fn helper(self) -> &'static str {
+        match self {
+            Self::Worktrees => "worktrees",
+            Self::Agents => "agents",
+            Self::FromTargets => "from",
+            Self::Tasks => "tasks",
+            Self::LinearIssues => "linear",
+        }
+    }
 
-_pigs "$@"
-"#
-    );
+    fn completer(self) -> ArgValueCompleter {
+        match self {
+            Self::Worktrees => ArgValueCompleter::new(|current: &OsStr| {
+                matching(current, worktree_candidates().into_iter().map(with_help))
+            }),
+            Self::Agents => ArgValueCompleter::new(|current: &OsStr| {
+                matching(current, agent_names().into_iter().map(|name| (name, None)))
+            }),
+            Self::FromTargets => ArgValueCompleter::new(|current: &OsStr| {
+                matching(
+                    current,
+                    from_candidates().into_iter().map(|name| (name, None)),
+                )
+            }),
+            Self::Tasks => ArgValueCompleter::new(|current: &OsStr| {
+                matching(current, task_candidates().into_iter().map(with_help))
+            }),
+            Self::LinearIssues => ArgValueCompleter::new(|current: &OsStr| {
+                matching(current, linear_candidates().into_iter().map(with_help))
+            }),
+        }
+    }
 }
 
-fn print_fish_completions() {
-    println!(
-        r#"# Fish completion for pigs
+fn with_help((value, help): (String, String)) -> (String, Option<String>) {
+    (value, Some(help))
+}
 
-# Disable file completions by default
-complete -c pigs -f
+/// Candidates whose value starts with what has been typed so far.
+fn matching(
+    current: &OsStr,
+    candidates: impl Iterator<Item = (String, Option<String>)>,
+) -> Vec<CompletionCandidate> {
+    let current = current.to_string_lossy();
+    candidates
+        .filter(|(value, _)| value.starts_with(current.as_ref()))
+        .map(|(value, help)| CompletionCandidate::new(value).help(help.map(Into::into)))
+        .collect()
+}
 
-# Main commands
-complete -c pigs -n "__fish_use_subcommand" -a linear -d "Create a new git worktree from a Linear issue"
-complete -c pigs -n "__fish_use_subcommand" -a create -d "Create a new git worktree"
-complete -c pigs -n "__fish_use_subcommand" -a checkout -d "Checkout a branch or pull request into a worktree"
-complete -c pigs -n "__fish_use_subcommand" -a review -d "Review a PR with all changes staged for browsing"
-complete -c pigs -n "__fish_use_subcommand" -a open -d "Open an existing worktree and launch agent"
-complete -c pigs -n "__fish_use_subcommand" -a delete -d "Delete a worktree and clean up"
-complete -c pigs -n "__fish_use_subcommand" -a add -d "Add current worktree to pigs management"
-complete -c pigs -n "__fish_use_subcommand" -a rename -d "Rename a worktree"
-complete -c pigs -n "__fish_use_subcommand" -a list -d "List all active agent sessions"
-complete -c pigs -n "__fish_use_subcommand" -a clean -d "Clean up invalid worktrees from state"
-complete -c pigs -n "__fish_use_subcommand" -a dir -d "Get the directory path of a worktree"
-complete -c pigs -n "__fish_use_subcommand" -a completions -d "Generate shell completions"
+fn dynamic_values(subcommand: &str, arg: &Arg) -> Option<DynamicValues> {
+    let id = arg.get_id().as_str();
+    if arg.is_positional() {
+        return match (subcommand, id) {
+            ("linear", "identifier") => Some(DynamicValues::LinearIssues),
+            _ if WORKTREE_COMMANDS.contains(&subcommand) && WORKTREE_ARGS.contains(&id) => {
+                Some(DynamicValues::Worktrees)
+            }
+            _ => None,
+        };
+    }
+    match id {
+        "agent" => Some(DynamicValues::Agents),
+        "from" => Some(DynamicValues::FromTargets),
+        "task" => Some(DynamicValues::Tasks),
+        "stack_on" => Some(DynamicValues::Worktrees),
+        _ => None,
+    }
+}
 
-# Function to get worktree completions with repo markers
-function __pigs_worktrees
-    pigs complete-worktrees --format=detailed 2>/dev/null | while read -l line
-        # Split tab-separated values: name<TAB>repo<TAB>path<TAB>sessions
-        set -l parts (string split \t $line)
-        if test (count $parts) -ge 4
-            set -l name $parts[1]
-            set -l repo $parts[2]
-            set -l sessions $parts[4]
-            echo "$name\t[$repo] $sessions"
-        end
-    end
-end
+/// Every argument of a subcommand of `cmd` whose values are dynamic.
+fn dynamic_args(cmd: &Command) -> Vec<(&str, &Arg, DynamicValues)> {
+    cmd.get_subcommands()
+        .flat_map(|sub| {
+            sub.get_arguments().filter_map(move |arg| {
+                dynamic_values(sub.get_name(), arg).map(|values| (sub.get_name(), arg, values))
+            })
+        })
+        .collect()
+}
 
-# Simple worktree names (fallback)
-function __pigs_worktrees_simple
-    pigs complete-worktrees 2>/dev/null
-end
+const NUSHELL_DYNAMIC_VALUES: &str = r#"  def "nu-complete pigs worktrees" [] {
+    (^pigs complete-worktrees --format=detailed | complete).stdout
+    | lines
+    | each {|line|
+        # name<TAB>repo<TAB>path<TAB>sessions<TAB>note
+        let parts = ($line | split row "\t")
+        let summary = $"[($parts.1)] ($parts.3)"
+        { value: $parts.0, description: ([$summary $parts.4] | compact --empty | str join " - ") }
+      }
+  }
 
-# Worktree completions for commands
-complete -c pigs -n "__fish_seen_subcommand_from open dir delete" -a "(__pigs_worktrees)"
-complete -c pigs -n "__fish_seen_subcommand_from rename" -n "not __fish_seen_argument_from (__pigs_worktrees_simple)" -a "(__pigs_worktrees)"
+  def "nu-complete pigs agents" [] {
+    (^pigs complete-agents | complete).stdout | lines
+  }
 
-# Linear issue completions
-function __pigs_linear_issues
-    pigs complete-linear 2>/dev/null | while read -l line
-        set -l parts (string split \t $line)
-        if test (count $parts) -ge 2
-            echo "$parts[1]\t$parts[2]"
-        end
-    end
-end
+  def "nu-complete pigs from" [] {
+    (^pigs complete-from | complete).stdout | lines
+  }
 
-# --from flag for create command (completes worktrees + branches)
-function __pigs_from_targets
-    pigs complete-from 2>/dev/null
-end
+  def "nu-complete pigs tasks" [] {
+    (^pigs complete-tasks | complete).stdout
+    | lines
+    | each {|line|
+        let parts = ($line | split row "\t")
+        { value: $parts.0, description: ($parts | skip 1 | str join "\t") }
+      }
+  }
 
-function __pigs_agents
-    pigs complete-agents 2>/dev/null
-end
+  def "nu-complete pigs linear" [] {
+    (^pigs complete-linear | complete).stdout
+    | lines
+    | each {|line|
+        let parts = ($line | split row "\t")
+        { value: $parts.0, description: ($parts | last) }
+      }
+  }
 
-complete -c pigs -n "__fish_seen_subcommand_from create" -l from -d "Create from an existing worktree or branch" -r -a "(__pigs_from_targets)"
-complete -c pigs -n "__fish_seen_subcommand_from create" -s a -l agent -d "Select agent at runtime" -r -a "(__pigs_agents)"
+"#;
 
-# Linear command: --from flag and issue completions
-complete -c pigs -n "__fish_seen_subcommand_from linear" -l from -d "Create from an existing worktree or branch" -r -a "(__pigs_from_targets)"
-complete -c pigs -n "__fish_seen_subcommand_from linear" -s a -l agent -d "Select agent at runtime" -r -a "(__pigs_agents)"
-complete -c pigs -n "__fish_seen_subcommand_from linear; and not __fish_seen_argument_from -l from" -a "(__pigs_linear_issues)"
+/// clap's Nushell module with `nu-complete` helpers for dynamic values, hooked
+/// up to the arguments that take them.
+fn nushell_completions(cmd: &mut Command) -> String {
+    let mut script = Vec::new();
+    clap_complete::generate(Nushell, cmd, "pigs", &mut script);
+    let script = String::from_utf8_lossy(&script);
+    let dynamic = dynamic_args(cmd);
 
-# Review command completions
-complete -c pigs -n "__fish_seen_subcommand_from review" -a finish -d "Exit review mode and preserve edits"
-complete -c pigs -n "__fish_seen_subcommand_from review" -a abort -d "Exit review mode and discard edits"
-complete -c pigs -n "__fish_seen_subcommand_from review" -l base -d "Base branch to diff against" -r
+    let mut output = String::with_capacity(script.len() + NUSHELL_DYNAMIC_VALUES.len());
+    let mut subcommand = "";
+    for line in script.split_inclusive('\n') {
+        if let Some(name) = line
+            .strip_prefix("  export extern ")
+            .and_then(|rest| rest.split('[').next())
+        {
+            subcommand = name.trim().trim_matches('"');
+            subcommand = subcommand.strip_prefix("pigs").unwrap_or(subcommand).trim();
+        }
+        let values = nushell_parameter(line).and_then(|parameter| {
+            dynamic
+                .iter()
+                .find(|(name, arg, _)| {
+                    *name == subcommand
+                        && match parameter {
+                            NuParameter::Positional(id) => {
+                                arg.is_positional() && arg.get_id().as_str() == id
+                            }
+                            NuParameter::Flag(long) => arg.get_long() == Some(long),
+                        }
+                })
+                .map(|(_, _, values)| *values)
+        });
+        match values.and_then(|values| {
+            line.find(": string")
+                .map(|at| (at + ": string".len(), values))
+        }) {
+            Some((at, values)) if !line[at..].starts_with('@') => {
+                output.push_str(&line[..at]);
+                output.push_str(&format!("@\"nu-complete pigs {}\"", values.helper()));
+                output.push_str(&line[at..]);
+            }
+            _ => output.push_str(line),
+        }
+        if line == "module completions {\n" {
+            output.push('\n');
+            output.push_str(NUSHELL_DYNAMIC_VALUES);
+        }
+    }
+    output
+}
 
-# Runtime agent flag on open/checkout
-complete -c pigs -n "__fish_seen_subcommand_from open checkout" -s a -l agent -d "Select agent at runtime" -r -a "(__pigs_agents)"
+enum NuParameter<'a> {
+    Positional(&'a str),
+    Flag(&'a str),
+}
 
-# Shell completions for completions command
-complete -c pigs -n "__fish_seen_subcommand_from completions" -a "bash zsh fish"
-"#
-    );
+/// The parameter a line of a generated `export extern` signature declares.
+fn nushell_parameter(line: &str) -> Option<NuParameter<'_>> {
+    let declaration = line.strip_prefix("    ")?.split(':').next()?;
+    if let Some(flag) = declaration.strip_prefix("--") {
+        return Some(NuParameter::Flag(flag.split('(').next()?));
+    }
+    let id = declaration.trim_start_matches("...").trim_end_matches('?');
+    (!id.is_empty() && !id.starts_with('-') && !id.starts_with('#'))
+        .then_some(NuParameter::Positional(id))
 }
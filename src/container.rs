@@ -0,0 +1,136 @@
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+
+use crate::detect::devcontainer_image;
+use crate::devenv::EnvChange;
+use crate::git::execute_git;
+use crate::state::{RepoConfig, WorktreeInfo};
+
+/// Prebuilt image a worktree's agents run in, pinned when the worktree was created.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContainerImage {
+    /// Image as configured, e.g. `ghcr.io/acme/app/devcontainer:latest`
+    pub image: String,
+    /// Digest it resolved to, e.g. `ghcr.io/acme/app/devcontainer@sha256:...`
+    pub digest: String,
+}
+
+/// Variables agents commonly need, passed into the container when set.
+const PASSTHROUGH_ENV: &[&str] = &[
+    "TERM",
+    "ANTHROPIC_API_KEY",
+    "OPENAI_API_KEY",
+    "GH_TOKEN",
+    "GITHUB_TOKEN",
+];
+
+/// Container CLI to use: `docker`, or `PIGS_CONTAINER_RUNTIME` (e.g. `podman`).
+fn runtime() -> String {
+    std::env::var("PIGS_CONTAINER_RUNTIME")
+        .ok()
+        .filter(|runtime| !runtime.is_empty())
+        .unwrap_or_else(|| "docker".to_string())
+}
+
+/// The prebuild image for a repository: `container_image` in
+/// `.pigs/settings.json`, else the `image` of its devcontainer.
+pub fn configured_image(repo_root: &Path) -> Result<String> {
+    if let Some(image) = RepoConfig::load(repo_root)?.container_image {
+        return Ok(image);
+    }
+    devcontainer_image(repo_root).context(
+        "No prebuild image found: set \"container_image\" in .pigs/settings.json or \"image\" in .devcontainer/devcontainer.json",
+    )
+}
+
+/// Pull `image` and resolve the digest it points at.
+pub fn pull(image: &str) -> Result<ContainerImage> {
+    let runtime = runtime();
+    let output = Command::new(&runtime)
+        .args(["pull", "--quiet", image])
+        .output()
+        .with_context(|| format!("Failed to run {runtime}; is it installed?"))?;
+    if !output.status.success() {
+        bail!(
+            "{runtime} pull {image} failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    // Images that were built locally have no registry digest, only an id
+    let digest = ["{{index .RepoDigests 0}}", "{{.Id}}"]
+        .iter()
+        .find_map(|format| {
+            let output = Command::new(&runtime)
+                .args(["image", "inspect", "--format", format, image])
+                .output()
+                .ok()?;
+            let digest = String::from_utf8_lossy(&output.stdout).trim().to_string();
+            (output.status.success() && !digest.is_empty()).then_some(digest)
+        })
+        .with_context(|| format!("Failed to read the digest of {image}"))?;
+
+    Ok(ContainerImage {
+        image: image.to_string(),
+        digest,
+    })
+}
+
+/// Turn `program args` into a `docker run` of the worktree's pinned image.
+/// The worktree and the repository's git directory are mounted at their host
+/// paths, so git inside the container sees the same worktree. Variables from
+/// the worktree's environment are passed through by name.
+pub fn wrap_command(
+    info: &WorktreeInfo,
+    container: &ContainerImage,
+    program: &str,
+    args: &[String],
+    env_changes: &[EnvChange],
+    tty: bool,
+) -> Result<(String, Vec<String>)> {
+    let worktree = info
+        .path
+        .to_str()
+        .context("Worktree path contains invalid UTF-8")?;
+    let git_dir = execute_git(&[
+        "-C",
+        worktree,
+        "rev-parse",
+        "--path-format=absolute",
+        "--git-common-dir",
+    ])
+    .context("Failed to find the repository's git directory")?;
+
+    let mut run = vec![
+        "run".to_string(),
+        "--rm".to_string(),
+        if tty { "-it" } else { "-i" }.to_string(),
+        "-v".to_string(),
+        format!("{worktree}:{worktree}"),
+        "-v".to_string(),
+        format!("{git_dir}:{git_dir}"),
+        "-w".to_string(),
+        worktree.to_string(),
+    ];
+    let mut names: Vec<&str> = PASSTHROUGH_ENV
+        .iter()
+        .copied()
+        .filter(|name| std::env::var_os(name).is_some())
+        .collect();
+    for (key, value) in env_changes {
+        if value.is_some() && !names.contains(&key.as_str()) {
+            names.push(key);
+        }
+    }
+    for name in names {
+        run.push("-e".to_string());
+        run.push(name.to_string());
+    }
+    run.push(container.digest.clone());
+    run.push(program.to_string());
+    run.extend(args.iter().cloned());
+    Ok((runtime(), run))
+}
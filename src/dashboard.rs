@@ -1,91 +1,383 @@
 use std::cmp::Ordering;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::io::{Read, Write};
-use std::net::SocketAddr;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 use std::path::{Path, PathBuf};
 use std::process::{Command as StdCommand, Stdio};
 use std::sync::Arc;
-use std::sync::atomic::{AtomicU64, Ordering as AtomicOrdering};
-use std::time::Duration;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering as AtomicOrdering};
+use std::time::{Duration, Instant};
 
 use anyhow::{Context, Result, anyhow};
+use axum::body::Body;
 use axum::extract::{
-    Path as AxumPath, State,
+    ConnectInfo, DefaultBodyLimit, FromRequestParts, Path as AxumPath, Query, Request, State,
     ws::{Message, WebSocket, WebSocketUpgrade},
 };
-use axum::http::StatusCode;
-use axum::response::{Html, IntoResponse};
-use axum::routing::{get, post};
+use axum::http::header::{AUTHORIZATION, CONTENT_TYPE, RETRY_AFTER};
+use axum::http::request::Parts;
+use axum::http::{HeaderMap, Method, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{Html, IntoResponse, Response};
+use axum::routing::{delete, get, post};
 use axum::{Json, Router};
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
 use futures_util::{SinkExt, StreamExt};
 use once_cell::sync::Lazy;
-use portable_pty::{CommandBuilder, PtySize, native_pty_system};
+use portable_pty::{ChildKiller, CommandBuilder, MasterPty, PtySize, native_pty_system};
+use rust_embed::RustEmbed;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use tokio::signal;
-use tokio::sync::{Mutex, RwLock, broadcast};
+use tokio::sync::{Mutex, Notify, RwLock, broadcast};
+use tower::ServiceExt;
+use tracing::{Instrument, error, info, info_span, warn};
+use tracing_subscriber::EnvFilter;
 use uuid::Uuid;
 
-use shell_words::split as shell_split;
-
+use crate::activity::{self, AgentUse, LaunchCommand};
+use crate::artifacts::{self, ArtifactKind};
 use crate::claude;
 use crate::codex;
 use crate::codex::CodexSession;
-use crate::state::{PigsState, WorktreeInfo};
+use crate::commands::archive::spawn_archive_job;
+use crate::commands::clean::spawn_clean_merged_job;
+use crate::commands::create::{handle_create_in_dir_quiet, spawn_create_job};
+use crate::commands::delete::delete_worktree_unattended;
+use crate::commands::sync::spawn_sync_job;
+use crate::container;
+use crate::devenv;
+use crate::external::{self, Snapshot};
+use crate::git::{execute_git, resolve_base_ref, snapshot_diff_revisions, snapshot_session_ref};
+use crate::github::{self, PullRequestStatus};
+use crate::hooks::{self, Hook};
+use crate::jobs;
+use crate::labels::{ChangeLabel, detect_worktree_labels};
+use crate::launch;
+use crate::linear;
+use crate::network;
+use crate::plain_text::{LineBuffer, strip_ansi};
+use crate::pool;
+use crate::problems::{ProblemReport, detect_worktree_problems};
+use crate::prompts;
+use crate::reminders::ReminderReport;
+use crate::session_tail;
+use crate::stack::{StackNode, stack_order};
+use crate::state::{
+    AgentOption, PigsState, RepoConfig, TokenScope, WorktreeInfo, get_default_agent,
+};
+use crate::transcripts::{self, Annotation, TranscriptEvent};
 use crate::utils::prepare_agent_command;
+use crate::webhooks::{self, WebhookEvent};
 
-const STATIC_INDEX: &str = include_str!("../dashboard/static/index.html");
 const DEFAULT_ADDR: &str = "127.0.0.1:5710";
 const DEFAULT_SESSION_LIMIT: usize = 5;
+/// How long a finished session stays in the registry, so its logs can still be
+/// read. Override with `PIGS_SESSION_RETENTION_SECS`.
 const SESSION_RETENTION_SECS: u64 = 300;
+/// Session transcripts keep only their newest events, up to whichever of these
+/// limits is hit first. Override with `PIGS_SESSION_LOG_MAX_EVENTS` and
+/// `PIGS_SESSION_LOG_MAX_BYTES`.
+const DEFAULT_SESSION_LOG_MAX_EVENTS: usize = 10_000;
+const DEFAULT_SESSION_LOG_MAX_BYTES: usize = 8 * 1024 * 1024;
 const PTY_ROWS: u16 = 40;
 const PTY_COLS: u16 = 120;
+const PTY_MIN_ROWS: u16 = 10;
+const PTY_MAX_ROWS: u16 = 500;
+const PTY_MIN_COLS: u16 = 40;
+const PTY_MAX_COLS: u16 = 1000;
 const CURSOR_POSITION_QUERY: &[u8] = b"\x1b[6n";
+/// How often worktrees are checked for new uncommitted changes. Override
+/// with `PIGS_DIRTY_POLL_SECS`.
+const DIRTY_POLL_SECS: u64 = 10;
+/// How often reminders set with `pigs remind` are checked for having come due.
+const REMINDER_POLL_SECS: u64 = 30;
+/// How often the state file is checked for a worktree focused with `pigs focus`.
+const FOCUS_POLL_SECS: u64 = 1;
+/// How often live sessions are checked for worktrees deleted outside the dashboard.
+const SESSION_REAP_SECS: u64 = 2;
+/// How often agent transcripts are tailed for new messages to push as previews
+/// when their directories can't be watched for changes.
+const PREVIEW_POLL_SECS: u64 = 2;
+/// While watching, how often transcripts are still tailed, to pick up agents
+/// whose transcript directory didn't exist yet when the dashboard started.
+const PREVIEW_FALLBACK_SECS: u64 = 30;
+/// How long a burst of transcript writes may settle before it is read.
+const PREVIEW_SETTLE_MS: u64 = 200;
+/// How often configured worktree pools are topped up.
+const POOL_FILL_SECS: u64 = 60;
+/// How often Linear and GitHub data is checked for being due a refresh. Each
+/// source's own refresh interval and budget decide what is actually fetched.
+const EXTERNAL_POLL_SECS: u64 = 2;
+/// Cache key of the Linear issues assigned to the dashboard's user.
+const LINEAR_ISSUES_KEY: &str = "assigned";
+const DEFAULT_TIMELINE_HOURS: i64 = 24;
+/// Longest timeline window: a year.
+const MAX_TIMELINE_HOURS: i64 = 24 * 365;
+/// Largest JSON body accepted by any API endpoint.
+const API_BODY_LIMIT: usize = 256 * 1024;
+/// Largest message a client may write to an agent's stdin in one request.
+const SEND_BODY_LIMIT: usize = 64 * 1024;
+/// Per-IP budget for API requests: burst size and steady refill per second.
+const API_RATE_BURST: f64 = 120.0;
+const API_RATE_PER_SEC: f64 = 20.0;
+/// Tighter per-IP budget for stdin writes and session spawns.
+const WRITE_RATE_BURST: f64 = 20.0;
+const WRITE_RATE_PER_SEC: f64 = 2.0;
+/// Drop idle rate-limit buckets once this many client IPs are tracked.
+const RATE_LIMIT_MAX_CLIENTS: usize = 1024;
+/// Version of the session stream protocol. Bump only for breaking changes;
+/// adding optional fields to `SessionEvent` keeps the current version.
+const SESSION_PROTOCOL_VERSION: u32 = 1;
+const SESSION_EVENT_SCHEMA_PATH: &str = "/api/schema/session-event.json";
+const USER_HEADER: &str = "x-pigs-user";
+
+/// Whether the dashboard listens only on a loopback address, so the
+/// `X-Pigs-User` header can only come from a proxy on the same host.
+static LOOPBACK_ONLY: AtomicBool = AtomicBool::new(false);
+
+/// Frontend files compiled into the binary; `--assets-dir` files take precedence.
+#[derive(RustEmbed)]
+#[folder = "dashboard/static/"]
+struct EmbeddedAssets;
 
 #[derive(Clone)]
 pub struct DashboardConfig {
     session_limit: usize,
+    desktop_notifications: bool,
+    assets_dir: Option<PathBuf>,
 }
 
 impl Default for DashboardConfig {
     fn default() -> Self {
         Self {
             session_limit: DEFAULT_SESSION_LIMIT,
+            desktop_notifications: false,
+            assets_dir: None,
         }
     }
 }
 
-pub fn run_dashboard(address: Option<String>, auto_open: bool) -> Result<()> {
+/// Where and how verbosely the dashboard logs, from `--log-level`/`--log-json`.
+pub struct LogOptions {
+    /// `tracing` filter directive, e.g. `info` or `pigs=debug`
+    pub level: String,
+    /// Emit one JSON object per line instead of human-readable text
+    pub json: bool,
+}
+
+pub fn run_dashboard(
+    address: Option<String>,
+    auto_open: bool,
+    notify: bool,
+    assets_dir: Option<PathBuf>,
+    logging: LogOptions,
+) -> Result<()> {
+    init_logging(&logging)?;
+    // Requests can't answer a trust prompt; untrusted repo settings are ignored
+    crate::trust::disable_prompts();
+
     let addr: SocketAddr = address
         .unwrap_or_else(|| DEFAULT_ADDR.to_string())
         .parse()
         .context("Invalid bind address for dashboard")?;
+    LOOPBACK_ONLY.store(addr.ip().is_loopback(), AtomicOrdering::Relaxed);
+
+    if let Some(dir) = &assets_dir
+        && !dir.is_dir()
+    {
+        anyhow::bail!("Assets directory {} does not exist", dir.display());
+    }
 
-    let config = DashboardConfig::default();
+    let config = DashboardConfig {
+        desktop_notifications: notify,
+        assets_dir,
+        ..DashboardConfig::default()
+    };
     let runtime = tokio::runtime::Runtime::new().context("Failed to start async runtime")?;
     runtime.block_on(async move { start_server(addr, config, auto_open).await })
 }
 
-async fn start_server(addr: SocketAddr, config: DashboardConfig, auto_open: bool) -> Result<()> {
-    let app = Router::new()
+fn init_logging(options: &LogOptions) -> Result<()> {
+    let filter = EnvFilter::try_new(&options.level)
+        .with_context(|| format!("Invalid --log-level '{}'", options.level))?;
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr);
+    // Ignore the error when a subscriber is already installed (e.g. in tests)
+    let _ = if options.json {
+        builder.json().try_init()
+    } else {
+        builder.try_init()
+    };
+    Ok(())
+}
+
+/// Log every request with its method, path, status, and latency inside a
+/// `request` span, so messages logged while handling it carry the same id.
+async fn log_requests(req: Request, next: Next) -> Response {
+    let span = info_span!(
+        "request",
+        id = %&Uuid::new_v4().simple().to_string()[..8],
+        method = %req.method(),
+        path = %req.uri().path(),
+    );
+    async move {
+        let started = Instant::now();
+        let response = next.run(req).await;
+        let status = response.status().as_u16();
+        let latency_ms = started.elapsed().as_millis() as u64;
+        if response.status().is_server_error() {
+            error!(status, latency_ms, "request failed");
+        } else if response.status().is_client_error() {
+            warn!(status, latency_ms, "request rejected");
+        } else {
+            info!(status, latency_ms, "request handled");
+        }
+        response
+    }
+    .instrument(span)
+    .await
+}
+
+/// Every dashboard route, without the request logging and API-wide rate limit
+/// the server adds.
+fn routes() -> Router<DashboardConfig> {
+    Router::new()
         .route("/", get(serve_index))
-        .route("/api/worktrees", get(api_worktrees))
+        .route("/assets/*path", get(serve_asset))
+        .route("/healthz", get(api_healthz))
+        .route("/readyz", get(api_readyz))
+        .route("/api/me", get(api_me))
+        .route("/api/repos", get(api_repos))
+        .route(
+            "/api/worktrees",
+            post(api_create_worktree)
+                .layer(middleware::from_fn(limit_session_writes))
+                .get(api_worktrees),
+        )
+        .route("/api/jobs", get(api_list_jobs))
+        .route("/api/jobs/:id", get(api_get_job))
+        .route("/api/jobs/:id/cancel", post(api_cancel_job))
+        .route(
+            "/api/sync",
+            post(api_start_sync).layer(middleware::from_fn(limit_session_writes)),
+        )
+        .route(
+            "/api/clean",
+            post(api_start_clean).layer(middleware::from_fn(limit_session_writes)),
+        )
+        .route("/api/jobs/:id/stream", get(api_stream_job))
+        .route(
+            "/api/worktrees/:repo/:name",
+            delete(api_delete_worktree).layer(middleware::from_fn(limit_session_writes)),
+        )
         .route(
             "/api/worktrees/:repo/:name/actions",
-            post(api_worktree_action),
+            post(api_worktree_action).layer(middleware::from_fn(limit_session_writes)),
+        )
+        .route(
+            "/api/worktrees/:repo/:name/archive",
+            post(api_archive_worktree).layer(middleware::from_fn(limit_session_writes)),
         )
         .route(
             "/api/worktrees/:repo/:name/live-session",
-            post(api_resume_session),
+            post(api_resume_session).layer(middleware::from_fn(limit_session_writes)),
         )
+        .route(
+            "/api/worktrees/:repo/:name/tasks/:task",
+            post(api_run_task).layer(middleware::from_fn(limit_session_writes)),
+        )
+        .route(
+            "/api/worktrees/:repo/:name/timeline",
+            get(api_worktree_timeline),
+        )
+        .route(
+            "/api/worktrees/:repo/:name/snapshots/diff",
+            get(api_snapshot_diff),
+        )
+        .route(
+            "/api/worktrees/:repo/:name/artifacts",
+            get(api_worktree_artifacts),
+        )
+        .route(
+            "/api/worktrees/:repo/:name/artifacts/:run/*file",
+            get(api_worktree_artifact_file),
+        )
+        .route("/api/linear/issues", get(api_linear_issues))
+        .route(
+            "/api/linear/:id/worktree",
+            post(api_linear_worktree).layer(middleware::from_fn(limit_session_writes)),
+        )
+        .route("/api/sessions/:id", delete(api_kill_session))
         .route("/api/sessions/:id/logs", get(api_get_session_logs))
-        .route("/api/sessions/:id/send", post(api_send_session_message))
+        .route(
+            "/api/sessions/:id/send",
+            post(api_send_session_message)
+                .layer::<_, std::convert::Infallible>(middleware::from_fn(limit_session_writes))
+                .layer(DefaultBodyLimit::max(SEND_BODY_LIMIT)),
+        )
+        .route(
+            "/api/sessions/:id/annotations",
+            get(api_get_session_annotations).post(api_add_session_annotation),
+        )
+        .route("/api/sessions/:id/resize", post(api_resize_session))
+        .route("/api/sessions/:id/export", get(api_export_session))
         .route("/api/sessions/:id/stream", get(api_stream_session))
+        .route("/api/events", get(api_stream_events))
+        .route(SESSION_EVENT_SCHEMA_PATH, get(api_session_event_schema))
         .route(
             "/api/settings",
             get(api_get_settings).post(api_update_settings),
         )
+}
+
+/// Answer one API request in-process with the same handlers the dashboard
+/// serves, for `pigs api`. Returns the response status and body.
+pub fn call_api(method: &str, path: &str, body: Option<String>) -> Result<(StatusCode, String)> {
+    if path == "/api/events" || path.ends_with("/stream") {
+        anyhow::bail!("{path} streams events; connect to `pigs dashboard` for it instead");
+    }
+    let method = Method::from_bytes(method.to_uppercase().as_bytes())
+        .with_context(|| format!("Invalid HTTP method '{method}'"))?;
+    let mut request = Request::builder().method(method).uri(path);
+    if body.is_some() {
+        request = request.header(CONTENT_TYPE, "application/json");
+    }
+    let request = request
+        .body(Body::from(body.unwrap_or_default()))
+        .with_context(|| format!("Invalid endpoint '{path}'"))?;
+
+    let runtime = tokio::runtime::Runtime::new().context("Failed to start async runtime")?;
+    runtime.block_on(async move {
+        let app = routes().with_state(DashboardConfig::default());
+        let Ok(response) = app.oneshot(request).await;
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .context("Failed to read the response")?;
+        Ok((status, String::from_utf8_lossy(&bytes).into_owned()))
+    })
+}
+
+async fn start_server(addr: SocketAddr, config: DashboardConfig, auto_open: bool) -> Result<()> {
+    if config.desktop_notifications {
+        tokio::spawn(forward_desktop_notifications());
+    }
+    tokio::spawn(watch_worktree_dirty_state());
+    tokio::spawn(fire_due_reminders());
+    tokio::spawn(watch_focus());
+    tokio::spawn(reap_orphaned_sessions());
+    tokio::spawn(watch_session_previews());
+    tokio::spawn(keep_worktree_pools_filled());
+    tokio::spawn(poll_external_sources());
+
+    let app = routes()
+        .layer(DefaultBodyLimit::max(API_BODY_LIMIT))
+        .layer(middleware::from_fn(require_token_scope))
+        .layer(middleware::from_fn(limit_api_requests))
+        .layer(middleware::from_fn(log_requests))
         .with_state(config);
 
     let listener = tokio::net::TcpListener::bind(addr)
@@ -100,37 +392,238 @@ async fn start_server(addr: SocketAddr, config: DashboardConfig, auto_open: bool
     if auto_open {
         let url = format!("http://{actual_addr}");
         if let Err(err) = webbrowser::open(&url) {
-            eprintln!("⚠️  Unable to open browser automatically: {err}");
+            warn!(error = %err, "unable to open browser automatically");
         }
     }
 
-    axum::serve(listener, app)
-        .with_graceful_shutdown(shutdown_signal())
-        .await
-        .context("Dashboard server exited unexpectedly")?;
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .with_graceful_shutdown(shutdown_signal())
+    .await
+    .context("Dashboard server exited unexpectedly")?;
 
     Ok(())
 }
 
+/// Per-IP token buckets: each client may burst up to `burst` requests, then
+/// gets `per_second` more every second.
+struct RateLimiter {
+    burst: f64,
+    per_second: f64,
+    buckets: std::sync::Mutex<HashMap<IpAddr, TokenBucket>>,
+}
+
+struct TokenBucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl RateLimiter {
+    fn new(burst: f64, per_second: f64) -> Self {
+        Self {
+            burst,
+            per_second,
+            buckets: std::sync::Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Take one token for `ip`, or return how long until one is available.
+    fn acquire(&self, ip: IpAddr, now: Instant) -> Result<(), Duration> {
+        let mut buckets = self
+            .buckets
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        if buckets.len() >= RATE_LIMIT_MAX_CLIENTS && !buckets.contains_key(&ip) {
+            let (burst, per_second) = (self.burst, self.per_second);
+            buckets.retain(|_, bucket| {
+                bucket.tokens + now.duration_since(bucket.updated).as_secs_f64() * per_second
+                    < burst
+            });
+            // Every client is still active; make room by dropping the one
+            // seen longest ago so the map never grows past the cap
+            if buckets.len() >= RATE_LIMIT_MAX_CLIENTS
+                && let Some(oldest) = buckets
+                    .iter()
+                    .min_by_key(|(_, bucket)| bucket.updated)
+                    .map(|(ip, _)| *ip)
+            {
+                buckets.remove(&oldest);
+            }
+        }
+
+        let bucket = buckets.entry(ip).or_insert(TokenBucket {
+            tokens: self.burst,
+            updated: now,
+        });
+        let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.per_second).min(self.burst);
+        bucket.updated = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64(
+                (1.0 - bucket.tokens) / self.per_second,
+            ))
+        }
+    }
+}
+
+static API_RATE_LIMITER: Lazy<RateLimiter> =
+    Lazy::new(|| RateLimiter::new(API_RATE_BURST, API_RATE_PER_SEC));
+static WRITE_RATE_LIMITER: Lazy<RateLimiter> =
+    Lazy::new(|| RateLimiter::new(WRITE_RATE_BURST, WRITE_RATE_PER_SEC));
+
+async fn limit_api_requests(req: Request, next: Next) -> Response {
+    if !req.uri().path().starts_with("/api/") {
+        return next.run(req).await;
+    }
+    enforce_rate_limit(&API_RATE_LIMITER, req, next).await
+}
+
+async fn limit_session_writes(req: Request, next: Next) -> Response {
+    enforce_rate_limit(&WRITE_RATE_LIMITER, req, next).await
+}
+
+async fn enforce_rate_limit(limiter: &RateLimiter, req: Request, next: Next) -> Response {
+    let ip = req
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip())
+        .unwrap_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED));
+
+    match limiter.acquire(ip, Instant::now()) {
+        Ok(()) => next.run(req).await,
+        Err(wait) => {
+            warn!(%ip, path = %req.uri().path(), "rate limited");
+            (
+                StatusCode::TOO_MANY_REQUESTS,
+                [(RETRY_AFTER, wait.as_secs().max(1).to_string())],
+                "Too many requests; slow down".to_string(),
+            )
+                .into_response()
+        }
+    }
+}
+
 async fn shutdown_signal() {
     let _ = signal::ctrl_c().await;
     println!("👋 Stopping dashboard");
 }
 
-async fn serve_index() -> Html<&'static str> {
-    Html(STATIC_INDEX)
+async fn serve_index(State(config): State<DashboardConfig>) -> Response {
+    match load_asset(config.assets_dir.as_deref(), "index.html") {
+        Some(bytes) => Html(bytes).into_response(),
+        None => (StatusCode::NOT_FOUND, "index.html not found").into_response(),
+    }
+}
+
+async fn serve_asset(
+    State(config): State<DashboardConfig>,
+    AxumPath(path): AxumPath<String>,
+) -> Response {
+    let Some(relative) = sanitize_asset_path(&path) else {
+        return (StatusCode::BAD_REQUEST, "Invalid asset path").into_response();
+    };
+
+    match load_asset(config.assets_dir.as_deref(), &relative) {
+        Some(bytes) => {
+            let mime = mime_guess::from_path(&relative).first_or_octet_stream();
+            (
+                [(axum::http::header::CONTENT_TYPE, mime.to_string())],
+                bytes,
+            )
+                .into_response()
+        }
+        None => (StatusCode::NOT_FOUND, "Asset not found").into_response(),
+    }
+}
+
+/// Read an asset from the override directory when present, falling back to
+/// the embedded copy.
+fn load_asset(assets_dir: Option<&Path>, relative: &str) -> Option<Vec<u8>> {
+    if let Some(dir) = assets_dir
+        && let Ok(bytes) = std::fs::read(dir.join(relative))
+    {
+        return Some(bytes);
+    }
+    EmbeddedAssets::get(relative).map(|file| file.data.into_owned())
+}
+
+/// Normalize a request path to a relative asset path, rejecting anything that
+/// could escape the assets directory.
+fn sanitize_asset_path(path: &str) -> Option<String> {
+    let parts: Vec<&str> = path
+        .split('/')
+        .filter(|part| !part.is_empty() && *part != ".")
+        .collect();
+    if parts.is_empty()
+        || parts
+            .iter()
+            .any(|part| *part == ".." || part.contains('\\') || part.contains(':'))
+    {
+        return None;
+    }
+    Some(parts.join("/"))
+}
+
+#[derive(Deserialize)]
+struct WorktreesQuery {
+    /// Only include worktrees with this tag
+    tag: Option<String>,
+}
+
+async fn api_worktrees(
+    State(config): State<DashboardConfig>,
+    Query(query): Query<WorktreesQuery>,
+) -> impl IntoResponse {
+    let limit = config.session_limit;
+    match tokio::task::spawn_blocking(move || build_dashboard_payload(limit)).await {
+        Ok(Ok(mut payload)) => {
+            if let Some(tag) = query.tag {
+                payload.worktrees.retain(|tree| tree.tags.contains(&tag));
+            }
+            Json(payload).into_response()
+        }
+        Ok(Err(err)) => {
+            error!(error = ?err, "failed to gather worktree info");
+            (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response()
+        }
+        Err(err) => {
+            error!(error = ?err, "worker thread panicked");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "dashboard worker panicked".to_string(),
+            )
+                .into_response()
+        }
+    }
 }
 
-async fn api_worktrees(State(config): State<DashboardConfig>) -> impl IntoResponse {
+async fn api_repos(State(config): State<DashboardConfig>) -> impl IntoResponse {
     let limit = config.session_limit;
+    let running: Vec<String> = WORKTREE_SESSION_INDEX
+        .read()
+        .await
+        .keys()
+        .cloned()
+        .collect();
     match tokio::task::spawn_blocking(move || build_dashboard_payload(limit)).await {
-        Ok(Ok(payload)) => Json(payload).into_response(),
+        Ok(Ok(payload)) => Json(RepoOverviewPayload {
+            generated_at: payload.generated_at,
+            repos: summarize_repos(&payload.worktrees, &running),
+        })
+        .into_response(),
         Ok(Err(err)) => {
-            eprintln!("[dashboard] failed to gather worktree info: {err:?}");
+            error!(error = ?err, "failed to gather repository info");
             (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response()
         }
         Err(err) => {
-            eprintln!("[dashboard] worker thread panicked: {err:?}");
+            error!(error = ?err, "worker thread panicked");
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 "dashboard worker panicked".to_string(),
@@ -140,157 +633,1343 @@ async fn api_worktrees(State(config): State<DashboardConfig>) -> impl IntoRespon
     }
 }
 
+async fn api_me(user: DashboardUser) -> impl IntoResponse {
+    Json(json!({ "name": user.0 }))
+}
+
 async fn api_worktree_action(
     AxumPath((repo, name)): AxumPath<(String, String)>,
+    user: DashboardUser,
     Json(req): Json<ActionRequest>,
 ) -> impl IntoResponse {
-    match handle_worktree_action(&repo, &name, req.action.as_str()) {
+    match handle_worktree_action(&repo, &name, req.action.as_str(), &user) {
         Ok(response) => Json(response).into_response(),
         Err((status, message)) => (status, message).into_response(),
     }
 }
 
-async fn api_resume_session(
+/// Start creating a worktree in the background. Creation can take minutes on
+/// large repositories, so this returns a job id right away; progress is
+/// available from `/api/jobs/:id` and `/api/jobs/:id/stream`.
+async fn api_create_worktree(Json(req): Json<CreateWorktreeRequest>) -> impl IntoResponse {
+    let repo = req.repo.clone();
+    let repo_root = match run_blocking(move || {
+        resolve_repo_root(repo.as_deref()).map_err(|err| (StatusCode::BAD_REQUEST, err.to_string()))
+    })
+    .await
+    {
+        Ok(root) => root,
+        Err((status, message)) => return (status, message).into_response(),
+    };
+
+    let name = req.name.filter(|name| !name.trim().is_empty());
+    let (job, _) = spawn_create_job(name, Some(repo_root), req.from, req.stack_on);
+
+    (
+        StatusCode::ACCEPTED,
+        Json(json!({ "jobId": job.id(), "job": job.snapshot() })),
+    )
+        .into_response()
+}
+
+async fn api_list_jobs() -> impl IntoResponse {
+    let jobs = tokio::task::spawn_blocking(jobs::list)
+        .await
+        .unwrap_or_default();
+    Json(json!({ "jobs": jobs }))
+}
+
+async fn api_cancel_job(AxumPath(id): AxumPath<String>) -> impl IntoResponse {
+    match jobs::get(&id) {
+        Some(job) if job.cancel() => Json(job.snapshot()).into_response(),
+        Some(_) => (StatusCode::CONFLICT, "Job has already finished").into_response(),
+        None => (StatusCode::NOT_FOUND, "Job not found").into_response(),
+    }
+}
+
+/// Restack stacked worktrees in the background, like `pigs sync [name] [--merge]`.
+async fn api_start_sync(req: Option<Json<SyncRequest>>) -> impl IntoResponse {
+    let (name, merge) = req.map_or((None, false), |Json(req)| (req.name, req.merge));
+    let (job, _) = spawn_sync_job(name, merge);
+    (
+        StatusCode::ACCEPTED,
+        Json(json!({ "jobId": job.id(), "job": job.snapshot() })),
+    )
+}
+
+/// Remove merged worktrees in the background, like `pigs clean --merged --yes`.
+async fn api_start_clean(req: Option<Json<CleanRequest>>) -> impl IntoResponse {
+    let base = req.and_then(|Json(req)| req.base);
+    let (job, _) = spawn_clean_merged_job(base);
+    (
+        StatusCode::ACCEPTED,
+        Json(json!({ "jobId": job.id(), "job": job.snapshot() })),
+    )
+}
+
+/// Archive a worktree's branch and uncommitted changes in the background,
+/// like `pigs archive`.
+async fn api_archive_worktree(
     AxumPath((repo, name)): AxumPath<(String, String)>,
 ) -> impl IntoResponse {
-    match start_live_session(&repo, &name).await {
-        Ok(runtime) => {
-            let events = runtime.snapshot().await;
-            let response = StartSessionResponse {
-                session_id: runtime.id().to_string(),
-                events,
-            };
-            Json(response).into_response()
+    let key = PigsState::make_key(&repo, &name);
+    let info = run_blocking(move || {
+        let state = PigsState::load()
+            .map_err(|err| (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()))?;
+        state
+            .worktrees
+            .get(&key)
+            .cloned()
+            .ok_or_else(|| (StatusCode::NOT_FOUND, format!("Worktree '{key}' not found")))
+    })
+    .await;
+    match info {
+        Ok(info) => {
+            let (job, _) = spawn_archive_job(info);
+            (
+                StatusCode::ACCEPTED,
+                Json(json!({ "jobId": job.id(), "job": job.snapshot() })),
+            )
+                .into_response()
         }
         Err((status, message)) => (status, message).into_response(),
     }
 }
 
-async fn api_get_session_logs(AxumPath(id): AxumPath<String>) -> impl IntoResponse {
-    match get_session_runtime(&id).await {
-        Some(runtime) => {
-            let events = runtime.snapshot().await;
-            Json(json!({ "sessionId": id, "events": events })).into_response()
-        }
-        None => (StatusCode::NOT_FOUND, "Session not found").into_response(),
+async fn api_get_job(AxumPath(id): AxumPath<String>) -> impl IntoResponse {
+    match jobs::get(&id) {
+        Some(job) => Json(job.snapshot()).into_response(),
+        None => (StatusCode::NOT_FOUND, "Job not found").into_response(),
     }
 }
 
-async fn api_send_session_message(
-    AxumPath(id): AxumPath<String>,
-    Json(req): Json<SendMessageRequest>,
-) -> impl IntoResponse {
-    let Some(runtime) = get_session_runtime(&id).await else {
-        return (StatusCode::NOT_FOUND, "Session not found").into_response();
-    };
-
-    let trimmed = req.message.trim();
-    if trimmed.is_empty() {
-        return (StatusCode::BAD_REQUEST, "Message cannot be empty").into_response();
+async fn api_stream_job(AxumPath(id): AxumPath<String>, ws: WebSocketUpgrade) -> impl IntoResponse {
+    match jobs::get(&id) {
+        Some(job) => ws.on_upgrade(move |socket| job_stream(socket, job)),
+        None => (StatusCode::NOT_FOUND, "Job not found").into_response(),
     }
+}
 
-    runtime
-        .push_message("user", "stdin", trimmed.to_string())
-        .await;
+/// Send the job's current snapshot, then each progress event until it finishes.
+async fn job_stream(socket: WebSocket, job: Arc<jobs::Job>) {
+    let (mut sender, mut receiver) = socket.split();
+    // Subscribe before taking the snapshot so no step falls in between
+    let mut rx = job.subscribe();
+    let snapshot = job.snapshot();
+    let finished = snapshot.status.is_finished();
+    let hello = json!({ "kind": "snapshot", "job": snapshot });
+    if sender.send(Message::Text(hello.to_string())).await.is_err() || finished {
+        let _ = sender.close().await;
+        return;
+    }
 
-    match runtime.write_stdin(trimmed).await {
-        Ok(()) => Json(json!({ "status": "ok" })).into_response(),
-        Err(err) => {
-            runtime
-                .push_status("error", Some(format!("stdin write failed: {err}")))
-                .await;
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "Failed to write to session".to_string(),
-            )
-                .into_response()
+    loop {
+        tokio::select! {
+            next = receiver.next() => {
+                if matches!(next, None | Some(Err(_)) | Some(Ok(Message::Close(_)))) {
+                    break;
+                }
+            }
+            event = rx.recv() => {
+                match event {
+                    Ok(ev) => {
+                        let terminal = ev.is_terminal();
+                        if sender.send(Message::Text(serde_json::to_string(&ev).unwrap_or_default())).await.is_err() || terminal {
+                            break;
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(_) => break,
+                }
+            }
         }
     }
+    let _ = sender.close().await;
 }
 
-async fn api_stream_session(
-    AxumPath(id): AxumPath<String>,
-    ws: WebSocketUpgrade,
+/// Liveness probe: answers as long as the server is accepting requests.
+async fn api_healthz() -> impl IntoResponse {
+    Json(json!({ "status": "ok" }))
+}
+
+/// Readiness probe: 200 when pigs state can be read and git is installed,
+/// otherwise 503 with the failing checks.
+async fn api_readyz() -> impl IntoResponse {
+    let checks = tokio::task::spawn_blocking(readiness_checks)
+        .await
+        .unwrap_or_else(|err| {
+            vec![ReadinessCheck::failed(
+                "worker",
+                format!("worker thread panicked: {err}"),
+            )]
+        });
+    let ready = checks.iter().all(|check| check.ok);
+    let status = if ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    let body = json!({
+        "status": if ready { "ready" } else { "not-ready" },
+        "checks": checks,
+    });
+    (status, Json(body))
+}
+
+fn readiness_checks() -> Vec<ReadinessCheck> {
+    let state = match PigsState::load() {
+        Ok(_) => ReadinessCheck::passed("state"),
+        Err(err) => ReadinessCheck::failed("state", format!("{err:#}")),
+    };
+    let git = match StdCommand::new("git").arg("--version").output() {
+        Ok(output) if output.status.success() => ReadinessCheck::passed("git"),
+        Ok(output) => ReadinessCheck::failed(
+            "git",
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ),
+        Err(err) => ReadinessCheck::failed("git", format!("git is not available: {err}")),
+    };
+    vec![state, git]
+}
+
+async fn api_delete_worktree(
+    AxumPath((repo, name)): AxumPath<(String, String)>,
+    Query(query): Query<DeleteWorktreeQuery>,
 ) -> impl IntoResponse {
-    match get_session_runtime(&id).await {
-        Some(runtime) => ws.on_upgrade(move |socket| session_stream(socket, runtime)),
-        None => (StatusCode::NOT_FOUND, "Session not found").into_response(),
+    match delete_worktree(&repo, &name, query.force).await {
+        Ok(response) => Json(response).into_response(),
+        Err((status, message)) => (status, message).into_response(),
     }
 }
 
-async fn api_get_settings() -> impl IntoResponse {
-    match load_settings_payload() {
-        Ok(payload) => Json(payload).into_response(),
-        Err(err) => {
-            eprintln!("[dashboard] failed to load settings: {err:?}");
+/// Stop the worktree's live sessions before removing its directory, so no PTY
+/// child is left running in a deleted path.
+async fn delete_worktree(
+    repo: &str,
+    name: &str,
+    force: bool,
+) -> Result<ActionResponse, (StatusCode, String)> {
+    let key = PigsState::make_key(repo, name);
+    let lookup_key = key.clone();
+    let info = run_blocking(move || {
+        let state = PigsState::load().map_err(|err| {
+            error!(error = ?err, "failed to load state");
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                "Failed to load settings".to_string(),
+                "Failed to load state".to_string(),
             )
-                .into_response()
+        })?;
+        let info = state.worktrees.get(&lookup_key).cloned().ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                format!("Worktree '{lookup_key}' not found"),
+            )
+        })?;
+        if !force && info.path.exists() && !summarize_git(&info.path).clean {
+            return Err((
+                StatusCode::CONFLICT,
+                format!(
+                    "Worktree '{lookup_key}' has uncommitted changes; pass force=true to discard them"
+                ),
+            ));
+        }
+        Ok(info)
+    })
+    .await?;
+
+    let stopped = terminate_worktree_sessions(&key, "worktree deleted").await;
+
+    let branch_deleted = run_blocking(move || {
+        delete_worktree_unattended(&info, force).map_err(|err| {
+            error!(error = ?err, "failed to delete worktree");
+            (StatusCode::INTERNAL_SERVER_ERROR, err.to_string())
+        })
+    })
+    .await?;
+
+    let mut message = format!("Deleted {key}");
+    if stopped > 0 {
+        message.push_str(&format!(" and stopped {stopped} session(s)"));
+    }
+    if !branch_deleted {
+        message.push_str("; branch kept (not fully merged)");
+    }
+    Ok(ActionResponse { message })
+}
+
+async fn run_blocking<T, F>(work: F) -> Result<T, (StatusCode, String)>
+where
+    F: FnOnce() -> Result<T, (StatusCode, String)> + Send + 'static,
+    T: Send + 'static,
+{
+    tokio::task::spawn_blocking(work)
+        .await
+        .unwrap_or_else(|err| {
+            error!(error = ?err, "worker thread panicked");
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "dashboard worker panicked".to_string(),
+            ))
+        })
+}
+
+async fn api_worktree_timeline(
+    AxumPath((repo, name)): AxumPath<(String, String)>,
+    Query(query): Query<TimelineQuery>,
+) -> impl IntoResponse {
+    let hours = timeline_hours(query.hours);
+    match tokio::task::spawn_blocking(move || build_timeline(&repo, &name, hours)).await {
+        Ok(Ok(entries)) => Json(json!({ "hours": hours, "entries": entries })).into_response(),
+        Ok(Err((status, message))) => (status, message).into_response(),
+        Err(err) => {
+            error!(error = ?err, "worker thread panicked");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "dashboard worker panicked".to_string(),
+            )
+                .into_response()
+        }
+    }
+}
+
+async fn api_snapshot_diff(
+    AxumPath((repo, name)): AxumPath<(String, String)>,
+    Query(query): Query<SnapshotDiffQuery>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    run_blocking(move || {
+        let info = load_worktree_info(&repo, &name)?;
+        let path = info.path.to_str().ok_or_else(|| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Invalid worktree path".to_string(),
+            )
+        })?;
+        let revisions = snapshot_diff_revisions(path, &query.from, &query.to)
+            .map_err(|err| (StatusCode::NOT_FOUND, err.to_string()))?;
+        let mut args = vec!["-C", path, "diff", "--no-color"];
+        if query.stat {
+            args.push("--stat");
+        }
+        args.extend(revisions.iter().map(String::as_str));
+        let diff = execute_git(&args).map_err(|err| {
+            error!(error = ?err, "failed to diff snapshots");
+            (StatusCode::INTERNAL_SERVER_ERROR, err.to_string())
+        })?;
+        Ok(Json(
+            json!({ "from": query.from, "to": query.to, "diff": diff }),
+        ))
+    })
+    .await
+}
+
+fn load_worktree_info(repo: &str, name: &str) -> Result<WorktreeInfo, (StatusCode, String)> {
+    let state = PigsState::load().map_err(|err| {
+        error!(error = ?err, "failed to load state");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to load state".to_string(),
+        )
+    })?;
+    state
+        .worktrees
+        .get(&PigsState::make_key(repo, name))
+        .cloned()
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                format!("Worktree '{repo}/{name}' not found"),
+            )
+        })
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ArtifactRunSummary {
+    id: String,
+    task: String,
+    finished_at: DateTime<Utc>,
+    exit_code: Option<i64>,
+    success: bool,
+    files: Vec<ArtifactFileSummary>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ArtifactFileSummary {
+    name: String,
+    kind: ArtifactKind,
+    size: u64,
+    url: String,
+}
+
+/// Recorded verification runs for a worktree, newest first.
+async fn api_worktree_artifacts(AxumPath((repo, name)): AxumPath<(String, String)>) -> Response {
+    let info = match load_worktree_info(&repo, &name) {
+        Ok(info) => info,
+        Err((status, message)) => return (status, message).into_response(),
+    };
+    let runs: Vec<ArtifactRunSummary> = info
+        .artifacts
+        .into_iter()
+        .rev()
+        .map(|run| ArtifactRunSummary {
+            files: run
+                .files
+                .into_iter()
+                .map(|file| ArtifactFileSummary {
+                    url: format!(
+                        "/api/worktrees/{repo}/{name}/artifacts/{}/{}",
+                        run.id, file.name
+                    ),
+                    name: file.name,
+                    kind: file.kind,
+                    size: file.size,
+                })
+                .collect(),
+            id: run.id,
+            task: run.task,
+            finished_at: run.finished_at,
+            exit_code: run.exit_code,
+            success: run.success,
+        })
+        .collect();
+    Json(json!({ "runs": runs })).into_response()
+}
+
+async fn api_worktree_artifact_file(
+    AxumPath((repo, name, run, file)): AxumPath<(String, String, String, String)>,
+) -> Response {
+    let info = match load_worktree_info(&repo, &name) {
+        Ok(info) => info,
+        Err((status, message)) => return (status, message).into_response(),
+    };
+    let Some(relative) = sanitize_asset_path(&file) else {
+        return (StatusCode::BAD_REQUEST, "Invalid artifact path").into_response();
+    };
+    let Some(run) = info.artifacts.iter().find(|candidate| candidate.id == run) else {
+        return (StatusCode::NOT_FOUND, "Artifact run not found").into_response();
+    };
+    let path = match artifacts::run_dir(&info, run) {
+        Ok(dir) => dir.join(&relative),
+        Err(err) => {
+            error!(error = ?err, "failed to locate artifacts");
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to locate artifacts",
+            )
+                .into_response();
+        }
+    };
+    match std::fs::read(&path) {
+        Ok(bytes) => {
+            let mime = mime_guess::from_path(&relative).first_or_text_plain();
+            (
+                [(axum::http::header::CONTENT_TYPE, mime.to_string())],
+                bytes,
+            )
+                .into_response()
+        }
+        Err(_) => (StatusCode::NOT_FOUND, "Artifact not found").into_response(),
+    }
+}
+
+async fn api_resume_session(
+    AxumPath((repo, name)): AxumPath<(String, String)>,
+    user: DashboardUser,
+    req: Option<Json<LiveSessionRequest>>,
+) -> impl IntoResponse {
+    let size = req
+        .map(|Json(req)| TerminalSize::requested(req.rows, req.cols))
+        .unwrap_or_default();
+    match start_live_session(&repo, &name, Vec::new(), size, &user).await {
+        Ok(runtime) => {
+            let events = runtime.snapshot().await;
+            let response = StartSessionResponse {
+                session_id: runtime.id().to_string(),
+                launched_by: runtime.launched_by().to_string(),
+                events,
+            };
+            Json(response).into_response()
+        }
+        Err((status, message)) => (status, message).into_response(),
+    }
+}
+
+async fn api_run_task(
+    AxumPath((repo, name, task)): AxumPath<(String, String, String)>,
+    user: DashboardUser,
+    req: Option<Json<LiveSessionRequest>>,
+) -> impl IntoResponse {
+    let size = req
+        .map(|Json(req)| TerminalSize::requested(req.rows, req.cols))
+        .unwrap_or_default();
+    match start_task(&repo, &name, &task, size, &user).await {
+        Ok(runtime) => {
+            let events = runtime.snapshot().await;
+            let response = StartSessionResponse {
+                session_id: runtime.id().to_string(),
+                launched_by: runtime.launched_by().to_string(),
+                events,
+            };
+            Json(response).into_response()
+        }
+        Err((status, message)) => (status, message).into_response(),
+    }
+}
+
+async fn api_linear_issues() -> impl IntoResponse {
+    if network::is_offline() {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Linear is unavailable in offline mode".to_string(),
+        )
+            .into_response();
+    }
+    // The first request fetches; after that the poller keeps the list fresh
+    let now = Instant::now();
+    LINEAR_ISSUES.read(LINEAR_ISSUES_KEY, now);
+    if LINEAR_ISSUES.claim_cold(LINEAR_ISSUES_KEY, now) {
+        refresh_external(
+            &LINEAR_ISSUES,
+            LINEAR_ISSUES_KEY.to_string(),
+            linear::fetch_my_issues,
+        )
+        .await;
+    }
+
+    let snapshot = LINEAR_ISSUES.read(LINEAR_ISSUES_KEY, Instant::now());
+    match (&snapshot.value, &snapshot.error) {
+        (Some(_), _) => Json(LinearIssuesPayload::from(snapshot)).into_response(),
+        (None, Some(error)) => (StatusCode::BAD_GATEWAY, error.clone()).into_response(),
+        (None, None) => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            [(RETRY_AFTER, EXTERNAL_POLL_SECS.to_string())],
+            "Linear issues are still loading".to_string(),
+        )
+            .into_response(),
+    }
+}
+
+async fn api_linear_worktree(
+    AxumPath(id): AxumPath<String>,
+    user: DashboardUser,
+    Json(req): Json<LinearWorktreeRequest>,
+) -> impl IntoResponse {
+    let start_session = req.start_session;
+    let size = TerminalSize::requested(req.rows, req.cols);
+    let created = match tokio::task::spawn_blocking(move || create_linear_worktree(&id, req)).await
+    {
+        Ok(Ok(created)) => created,
+        Ok(Err((status, message))) => return (status, message).into_response(),
+        Err(err) => {
+            error!(error = ?err, "worker thread panicked");
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "dashboard worker panicked".to_string(),
+            )
+                .into_response();
+        }
+    };
+
+    activity::record_as(&user.0, &created.repo_name, &created.name, "linear", None);
+
+    let mut response = LinearWorktreeResponse {
+        key: PigsState::make_key(&created.repo_name, &created.name),
+        session_id: None,
+        launched_by: None,
+        events: Vec::new(),
+    };
+
+    if start_session {
+        match start_live_session(
+            &created.repo_name,
+            &created.name,
+            vec![created.prompt.clone()],
+            size,
+            &user,
+        )
+        .await
+        {
+            Ok(runtime) => {
+                prompts::record(
+                    &created.repo_name,
+                    &created.name,
+                    Some(runtime.id()),
+                    &created.prompt,
+                );
+                response.session_id = Some(runtime.id().to_string());
+                response.launched_by = Some(runtime.launched_by().to_string());
+                response.events = runtime.snapshot().await;
+            }
+            Err((status, message)) => return (status, message).into_response(),
+        }
+    }
+
+    Json(response).into_response()
+}
+
+async fn api_get_session_logs(
+    AxumPath(id): AxumPath<String>,
+    Query(query): Query<LogsQuery>,
+) -> impl IntoResponse {
+    let format = match EventFormat::parse(query.format.as_deref()) {
+        Ok(format) => format,
+        Err(message) => return (StatusCode::BAD_REQUEST, message).into_response(),
+    };
+    let (mut events, dropped_events, launched_by, command, annotations) =
+        match get_session_runtime(&id).await {
+            Some(runtime) => {
+                let (events, dropped_events) = runtime.transcript().await;
+                (
+                    events,
+                    dropped_events,
+                    json!(runtime.launched_by()),
+                    json!(runtime.command()),
+                    runtime.annotations().await,
+                )
+            }
+            None => match recorded_session(&id) {
+                Some(recorded) => (
+                    recorded.events,
+                    0,
+                    json!(recorded.launched_by),
+                    json!(recorded.command),
+                    transcripts::read_annotations(&id),
+                ),
+                None => return (StatusCode::NOT_FOUND, "Session not found").into_response(),
+            },
+        };
+    if format == EventFormat::Plain {
+        let mut plain = PlainEvents::default();
+        let mut lines: Vec<SessionEvent> =
+            events.iter().flat_map(|event| plain.push(event)).collect();
+        lines.extend(plain.flush());
+        events = lines;
+    }
+    Json(json!({
+        "sessionId": id,
+        "launchedBy": launched_by,
+        "command": command,
+        "events": events,
+        "droppedEvents": dropped_events,
+        "annotations": annotations,
+    }))
+    .into_response()
+}
+
+/// A session `pigs open --record` saved, found by id once no live session has it.
+struct RecordedSession {
+    worktree_key: String,
+    launched_by: Option<String>,
+    command: Option<LaunchCommand>,
+    events: Vec<SessionEvent>,
+}
+
+fn recorded_session(id: &str) -> Option<RecordedSession> {
+    let events = transcripts::read(id).ok().flatten()?;
+    let launch = activity::read_all()
+        .unwrap_or_default()
+        .into_iter()
+        .find(|record| record.session_id.as_deref() == Some(id));
+    Some(RecordedSession {
+        worktree_key: launch.as_ref().map_or_else(
+            || "unknown".to_string(),
+            |record| PigsState::make_key(&record.repo_name, &record.worktree),
+        ),
+        launched_by: launch.as_ref().and_then(|record| record.user.clone()),
+        command: launch.and_then(|record| record.command),
+        events: events.into_iter().map(SessionEvent::from).collect(),
+    })
+}
+
+/// A live session's annotations, else the ones saved for a session that has
+/// ended or was taken before the dashboard restarted.
+async fn api_get_session_annotations(AxumPath(id): AxumPath<String>) -> impl IntoResponse {
+    let annotations = match get_session_runtime(&id).await {
+        Some(runtime) => runtime.annotations().await,
+        None => {
+            let lookup = id.clone();
+            let saved = tokio::task::spawn_blocking(move || {
+                let annotations = transcripts::read_annotations(&lookup);
+                let known = !annotations.is_empty() || recorded_session(&lookup).is_some();
+                known.then_some(annotations)
+            })
+            .await
+            .ok()
+            .flatten();
+            match saved {
+                Some(annotations) => annotations,
+                None => return (StatusCode::NOT_FOUND, "Session not found").into_response(),
+            }
+        }
+    };
+    Json(json!({ "sessionId": id, "annotations": annotations })).into_response()
+}
+
+async fn api_add_session_annotation(
+    AxumPath(id): AxumPath<String>,
+    user: DashboardUser,
+    Json(req): Json<AnnotationRequest>,
+) -> impl IntoResponse {
+    let annotation = match get_session_runtime(&id).await {
+        Some(runtime) => runtime.annotate(req.sequence, req.note, &user.0).await,
+        // A session `pigs open --record` saved can be annotated too
+        None => {
+            let lookup = id.clone();
+            let annotated = tokio::task::spawn_blocking(move || {
+                let recorded = recorded_session(&lookup)?;
+                let annotation = recorded
+                    .events
+                    .iter()
+                    .any(|event| event.sequence == req.sequence)
+                    .then(|| new_annotation(req.sequence, req.note, &user.0));
+                if let Some(annotation) = &annotation {
+                    save_annotation(&lookup, annotation);
+                }
+                Some(annotation)
+            })
+            .await
+            .ok()
+            .flatten();
+            match annotated {
+                Some(annotation) => annotation,
+                None => return (StatusCode::NOT_FOUND, "Session not found").into_response(),
+            }
+        }
+    };
+
+    match annotation {
+        Some(annotation) => Json(annotation).into_response(),
+        None => (
+            StatusCode::BAD_REQUEST,
+            format!("Session has no event with sequence {}", req.sequence),
+        )
+            .into_response(),
+    }
+}
+
+async fn api_export_session(AxumPath(id): AxumPath<String>) -> impl IntoResponse {
+    let transcript = match get_session_runtime(&id).await {
+        Some(runtime) => {
+            let (events, dropped_events) = runtime.transcript().await;
+            render_transcript(
+                runtime.id(),
+                runtime.worktree_key(),
+                &events,
+                dropped_events,
+                &runtime.annotations().await,
+            )
+        }
+        None => match recorded_session(&id) {
+            Some(recorded) => render_transcript(
+                &id,
+                &recorded.worktree_key,
+                &recorded.events,
+                0,
+                &transcripts::read_annotations(&id),
+            ),
+            None => return (StatusCode::NOT_FOUND, "Session not found").into_response(),
+        },
+    };
+    (
+        [(
+            axum::http::header::CONTENT_TYPE,
+            "text/markdown; charset=utf-8",
+        )],
+        transcript,
+    )
+        .into_response()
+}
+
+async fn api_send_session_message(
+    AxumPath(id): AxumPath<String>,
+    Json(req): Json<SendMessageRequest>,
+) -> impl IntoResponse {
+    let Some(runtime) = get_session_runtime(&id).await else {
+        return (StatusCode::NOT_FOUND, "Session not found").into_response();
+    };
+
+    let trimmed = req.message.trim();
+    if trimmed.is_empty() {
+        return (StatusCode::BAD_REQUEST, "Message cannot be empty").into_response();
+    }
+
+    runtime
+        .push_message("user", "stdin", trimmed.to_string())
+        .await;
+    if let Some((repo, name)) = runtime.worktree_key().split_once('/') {
+        prompts::record(repo, name, Some(runtime.id()), trimmed);
+    }
+
+    match runtime.write_stdin(trimmed).await {
+        Ok(()) => Json(json!({ "status": "ok" })).into_response(),
+        Err(err) => {
+            runtime
+                .push_status("error", Some(format!("stdin write failed: {err}")))
+                .await;
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to write to session".to_string(),
+            )
+                .into_response()
+        }
+    }
+}
+
+async fn api_resize_session(
+    AxumPath(id): AxumPath<String>,
+    Json(req): Json<LiveSessionRequest>,
+) -> impl IntoResponse {
+    let Some(runtime) = get_session_runtime(&id).await else {
+        return (StatusCode::NOT_FOUND, "Session not found").into_response();
+    };
+
+    let size = TerminalSize::requested(req.rows, req.cols);
+    match runtime.resize(size).await {
+        Ok(()) => Json(json!({ "rows": size.rows, "cols": size.cols })).into_response(),
+        Err(err) => {
+            warn!(session = %id, error = ?err, "failed to resize session");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to resize session".to_string(),
+            )
+                .into_response()
+        }
+    }
+}
+
+async fn api_kill_session(AxumPath(id): AxumPath<String>) -> impl IntoResponse {
+    if terminate_session(&id, "killed from the dashboard").await {
+        Json(json!({ "status": "ok" })).into_response()
+    } else {
+        (StatusCode::NOT_FOUND, "Session not found").into_response()
+    }
+}
+
+async fn api_stream_session(
+    AxumPath(id): AxumPath<String>,
+    Query(query): Query<StreamQuery>,
+    ws: WebSocketUpgrade,
+) -> impl IntoResponse {
+    let format = match EventFormat::parse(query.format.as_deref()) {
+        Ok(format) => format,
+        Err(message) => return (StatusCode::BAD_REQUEST, message).into_response(),
+    };
+    let requested = query.protocol_version.unwrap_or(SESSION_PROTOCOL_VERSION);
+    if requested == 0 || requested > SESSION_PROTOCOL_VERSION {
+        return (
+            StatusCode::BAD_REQUEST,
+            format!(
+                "Unsupported protocolVersion {requested}; this server supports 1..={SESSION_PROTOCOL_VERSION}"
+            ),
+        )
+            .into_response();
+    }
+
+    match get_session_runtime(&id).await {
+        Some(runtime) => {
+            ws.on_upgrade(move |socket| session_stream(socket, runtime, requested, format))
+        }
+        None => (StatusCode::NOT_FOUND, "Session not found").into_response(),
+    }
+}
+
+async fn api_session_event_schema() -> impl IntoResponse {
+    Json(schemars::schema_for!(SessionEvent))
+}
+
+async fn api_stream_events(ws: WebSocketUpgrade) -> impl IntoResponse {
+    ws.on_upgrade(events_stream)
+}
+
+async fn api_get_settings() -> impl IntoResponse {
+    match load_settings_payload() {
+        Ok(payload) => Json(payload).into_response(),
+        Err(err) => {
+            error!(error = ?err, "failed to load settings");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to load settings".to_string(),
+            )
+                .into_response()
+        }
+    }
+}
+
+async fn api_update_settings(Json(req): Json<SettingsPayload>) -> impl IntoResponse {
+    match update_settings_state(req) {
+        Ok(payload) => Json(payload).into_response(),
+        Err((status, message)) => (status, message).into_response(),
+    }
+}
+
+async fn session_stream(
+    socket: WebSocket,
+    runtime: Arc<SessionRuntime>,
+    protocol_version: u32,
+    format: EventFormat,
+) {
+    let (mut sender, mut receiver) = socket.split();
+    let (events, dropped_events) = runtime.transcript().await;
+    let handshake = StreamHandshake {
+        kind: "hello",
+        protocol_version,
+        session_id: runtime.id().to_string(),
+        schema: SESSION_EVENT_SCHEMA_PATH,
+        dropped_events,
+        format: format.name(),
+    };
+    // Raw events pass through as they are; plain ones are re-cut into lines
+    let mut plain = PlainEvents::default();
+    let mut convert = move |event: &SessionEvent| match format {
+        EventFormat::Raw => vec![event.clone()],
+        EventFormat::Plain => plain.push(event),
+    };
+    if sender
+        .send(Message::Text(
+            serde_json::to_string(&handshake).unwrap_or_default(),
+        ))
+        .await
+        .is_err()
+    {
+        return;
+    }
+
+    for event in events.iter().flat_map(&mut convert) {
+        if sender
+            .send(Message::Text(
+                serde_json::to_string(&event).unwrap_or_default(),
+            ))
+            .await
+            .is_err()
+        {
+            return;
+        }
+    }
+
+    let mut rx = runtime.subscribe();
+    loop {
+        tokio::select! {
+            next = receiver.next() => {
+                if matches!(next, None | Some(Err(_))) {
+                    break;
+                }
+                if let Some(Ok(Message::Close(_))) = next {
+                    break;
+                }
+            }
+            event = rx.recv() => {
+                match event {
+                    Ok(ev) => {
+                        for ev in convert(&ev) {
+                            if sender.send(Message::Text(serde_json::to_string(&ev).unwrap_or_default())).await.is_err() {
+                                return;
+                            }
+                        }
+                    }
+                    Err(_) => break,
+                }
+            }
+        }
+    }
+}
+
+async fn events_stream(socket: WebSocket) {
+    let (mut sender, mut receiver) = socket.split();
+    let mut rx = DASHBOARD_EVENTS.subscribe();
+    loop {
+        tokio::select! {
+            next = receiver.next() => {
+                if matches!(next, None | Some(Err(_)) | Some(Ok(Message::Close(_)))) {
+                    break;
+                }
+            }
+            event = rx.recv() => {
+                match event {
+                    Ok(ev) => {
+                        if sender.send(Message::Text(serde_json::to_string(&ev).unwrap_or_default())).await.is_err() {
+                            break;
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(_) => break,
+                }
+            }
+        }
+    }
+}
+
+/// Poll managed worktrees and publish `worktree-dirty` when one goes from clean
+/// to dirty. Polling is skipped while nobody is listening for dashboard events.
+async fn watch_worktree_dirty_state() {
+    let mut known: HashMap<String, bool> = HashMap::new();
+    let poll_secs = std::env::var("PIGS_DIRTY_POLL_SECS")
+        .ok()
+        .and_then(|value| value.trim().parse().ok())
+        .filter(|secs| *secs > 0)
+        .unwrap_or(DIRTY_POLL_SECS);
+    let mut interval = tokio::time::interval(Duration::from_secs(poll_secs));
+    loop {
+        interval.tick().await;
+        if DASHBOARD_EVENTS.receiver_count() == 0 {
+            continue;
+        }
+
+        let current = match tokio::task::spawn_blocking(collect_dirty_state).await {
+            Ok(Ok(current)) => current,
+            Ok(Err(err)) => {
+                error!(error = ?err, "failed to poll worktree status");
+                continue;
+            }
+            Err(err) => {
+                error!(error = ?err, "worker thread panicked");
+                continue;
+            }
+        };
+
+        for (key, dirty) in &current {
+            if *dirty && known.get(key) == Some(&false) {
+                publish_dashboard_event(DashboardEvent::new(
+                    "worktree-dirty",
+                    key,
+                    None,
+                    Some("Worktree has uncommitted changes".to_string()),
+                ));
+            }
+        }
+        known = current;
+    }
+}
+
+fn collect_dirty_state() -> Result<HashMap<String, bool>> {
+    let state = PigsState::load()?;
+    Ok(state
+        .worktrees
+        .into_iter()
+        .filter_map(|(key, info)| {
+            let summary = summarize_git(&info.path);
+            summary.error.is_none().then_some((key, !summary.clean))
+        })
+        .collect())
+}
+
+/// Publish a `reminder-due` event for each reminder that has passed, then mark
+/// it notified so it fires once. Like the dirty watcher this waits for a
+/// listener, so reminders that come due while nobody is connected aren't lost.
+async fn fire_due_reminders() {
+    let mut interval = tokio::time::interval(Duration::from_secs(REMINDER_POLL_SECS));
+    loop {
+        interval.tick().await;
+        if DASHBOARD_EVENTS.receiver_count() == 0 {
+            continue;
+        }
+
+        let due = match tokio::task::spawn_blocking(mark_due_reminders).await {
+            Ok(Ok(due)) => due,
+            Ok(Err(err)) => {
+                error!(error = ?err, "failed to check reminders");
+                continue;
+            }
+            Err(err) => {
+                error!(error = ?err, "worker thread panicked");
+                continue;
+            }
+        };
+
+        for (key, note) in due {
+            publish_dashboard_event(DashboardEvent::new(
+                "reminder-due",
+                &key,
+                None,
+                Some(note.unwrap_or_else(|| "Reminder is due".to_string())),
+            ));
+        }
+    }
+}
+
+fn mark_due_reminders() -> Result<Vec<(String, Option<String>)>> {
+    let mut state = PigsState::load()?;
+    let due = crate::reminders::due_unnotified(&state, Utc::now());
+    if due.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut fired = Vec::new();
+    for (key, _) in due {
+        if let Some(reminder) = state
+            .worktrees
+            .get_mut(&key)
+            .and_then(|info| info.reminder.as_mut())
+        {
+            reminder.notified = true;
+            fired.push((key, reminder.note.clone()));
+        }
+    }
+    state.save()?;
+    Ok(fired)
+}
+
+/// Tail agent transcripts of managed worktrees and publish `session-preview`
+/// with the new message whenever a worktree's latest prompt changes, so open
+/// dashboards update without a full refresh. Transcripts are read when the
+/// file watcher reports a write, and polled only when nothing can be watched.
+async fn watch_session_previews() {
+    let written = Arc::new(Notify::new());
+    let signal = written.clone();
+    let watcher = session_tail::watch_transcripts(move || signal.notify_one());
+    let poll_secs = if watcher.is_some() {
+        PREVIEW_FALLBACK_SECS
+    } else {
+        PREVIEW_POLL_SECS
+    };
+    let mut interval = tokio::time::interval(Duration::from_secs(poll_secs));
+    let mut initial = true;
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {}
+            _ = written.notified() => {
+                tokio::time::sleep(Duration::from_millis(PREVIEW_SETTLE_MS)).await;
+            }
+        }
+        if DASHBOARD_EVENTS.receiver_count() == 0 {
+            continue;
+        }
+
+        let changed = match tokio::task::spawn_blocking(|| {
+            PigsState::load().map(|state| {
+                let worktrees: Vec<(String, PathBuf)> = state
+                    .worktrees
+                    .into_iter()
+                    .map(|(key, info)| (key, info.path))
+                    .collect();
+                session_tail::changed_previews(&worktrees)
+            })
+        })
+        .await
+        {
+            Ok(Ok(changed)) => changed,
+            Ok(Err(err)) => {
+                error!(error = ?err, "failed to tail agent sessions");
+                continue;
+            }
+            Err(err) => {
+                error!(error = ?err, "worker thread panicked");
+                continue;
+            }
+        };
+
+        // The first pass reads every transcript from the start; clients
+        // already have those previews from /api/worktrees
+        if std::mem::take(&mut initial) {
+            continue;
+        }
+        for (key, message) in changed {
+            publish_dashboard_event(DashboardEvent::new(
+                "session-preview",
+                &key,
+                None,
+                Some(message),
+            ));
+        }
+    }
+}
+
+/// Pre-create worktrees for each repository in `worktree_pool`, so
+/// `pigs create --fast` can claim one instead of waiting on setup commands.
+async fn keep_worktree_pools_filled() {
+    let mut interval = tokio::time::interval(Duration::from_secs(POOL_FILL_SECS));
+    loop {
+        interval.tick().await;
+        let result = tokio::task::spawn_blocking(|| -> Result<()> {
+            let pools = PigsState::load()?.worktree_pool;
+            for (repo, size) in pools {
+                match pool::fill(&repo, size) {
+                    Ok(0) => {}
+                    Ok(created) => info!(repo = %repo.display(), created, "filled worktree pool"),
+                    Err(err) => {
+                        error!(repo = %repo.display(), error = ?err, "failed to fill worktree pool")
+                    }
+                }
+            }
+            Ok(())
+        })
+        .await;
+        match result {
+            Ok(Ok(())) => {}
+            Ok(Err(err)) => error!(error = ?err, "failed to load worktree pools"),
+            Err(err) => error!(error = ?err, "worker thread panicked"),
+        }
+    }
+}
+
+static LINEAR_ISSUES: Lazy<external::Cache<Vec<linear::LinearIssueSummary>>> =
+    Lazy::new(|| external::Cache::new(external::LINEAR));
+/// Pull requests by head branch, keyed by repository name.
+static PULL_REQUESTS: Lazy<external::Cache<HashMap<String, PullRequestStatus>>> =
+    Lazy::new(|| external::Cache::new(external::PULL_REQUESTS));
+
+/// Refresh Linear issues and pull request status that someone has read lately,
+/// so endpoints serve cached data instead of calling out on every request.
+/// Each source keeps to its own refresh interval and budget, and backs off
+/// while it's failing.
+async fn poll_external_sources() {
+    let mut interval = tokio::time::interval(Duration::from_secs(EXTERNAL_POLL_SECS));
+    loop {
+        interval.tick().await;
+        if network::is_offline() {
+            continue;
+        }
+        let now = Instant::now();
+        for key in LINEAR_ISSUES.due(now) {
+            tokio::spawn(refresh_external(
+                &LINEAR_ISSUES,
+                key,
+                linear::fetch_my_issues,
+            ));
+        }
+        for repo in PULL_REQUESTS.due(now) {
+            let key = repo.clone();
+            tokio::spawn(refresh_external(&PULL_REQUESTS, key, move || {
+                fetch_pull_requests(&repo)
+            }));
         }
     }
 }
 
-async fn api_update_settings(Json(req): Json<SettingsPayload>) -> impl IntoResponse {
-    match update_settings_state(req) {
-        Ok(payload) => Json(payload).into_response(),
-        Err(err) => {
-            eprintln!("[dashboard] failed to update settings: {err:?}");
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "Failed to update settings".to_string(),
-            )
-                .into_response()
-        }
+async fn refresh_external<T, F>(cache: &'static external::Cache<T>, key: String, fetch: F)
+where
+    T: Clone + Send + 'static,
+    F: FnOnce() -> Result<T> + Send + 'static,
+{
+    let result = match tokio::task::spawn_blocking(fetch).await {
+        Ok(result) => result,
+        Err(err) => Err(anyhow!("worker thread panicked: {err}")),
+    };
+    if let Err(err) = &result {
+        warn!(source = cache.source().name, key = %key, error = %err, "external fetch failed");
     }
+    cache.record(&key, result, Instant::now());
 }
 
-async fn session_stream(socket: WebSocket, runtime: Arc<SessionRuntime>) {
-    let (mut sender, mut receiver) = socket.split();
-    for event in runtime.snapshot().await {
-        if sender
-            .send(Message::Text(
-                serde_json::to_string(&event).unwrap_or_default(),
-            ))
-            .await
-            .is_err()
+/// Pull requests of a repository, asked of `gh` from one of its worktrees.
+fn fetch_pull_requests(repo_name: &str) -> Result<HashMap<String, PullRequestStatus>> {
+    let state = PigsState::load()?;
+    let path = state
+        .worktrees
+        .values()
+        .find(|info| info.repo_name == repo_name && info.path.exists())
+        .map(|info| info.path.clone())
+        .ok_or_else(|| anyhow!("No worktree of {repo_name} to run gh in"))?;
+    github::pull_requests_by_branch(&path)
+}
+
+/// Publish a `worktree-focused` event whenever `pigs focus` points at a
+/// different worktree, so open dashboards select it.
+async fn watch_focus() {
+    let mut interval = tokio::time::interval(Duration::from_secs(FOCUS_POLL_SECS));
+    let mut last: Option<Option<String>> = None;
+    loop {
+        interval.tick().await;
+        let focus = match tokio::task::spawn_blocking(|| {
+            PigsState::load().map(|state| {
+                state
+                    .focused()
+                    .map(|info| PigsState::make_key(&info.repo_name, &info.name))
+            })
+        })
+        .await
         {
-            return;
+            Ok(Ok(focus)) => focus,
+            Ok(Err(err)) => {
+                error!(error = ?err, "failed to check focused worktree");
+                continue;
+            }
+            Err(err) => {
+                error!(error = ?err, "worker thread panicked");
+                continue;
+            }
+        };
+        if last.as_ref() == Some(&focus) {
+            continue;
+        }
+        // The first check only records the focus the dashboard started with;
+        // clients read that from /api/worktrees
+        let initial = last.is_none();
+        last = Some(focus.clone());
+        if let Some(key) = focus
+            && !initial
+        {
+            publish_dashboard_event(DashboardEvent::new("worktree-focused", &key, None, None));
         }
     }
+}
 
-    let mut rx = runtime.subscribe();
+async fn forward_desktop_notifications() {
+    let mut rx = DASHBOARD_EVENTS.subscribe();
     loop {
-        tokio::select! {
-            next = receiver.next() => {
-                if matches!(next, None | Some(Err(_))) {
-                    break;
-                }
-                if let Some(Ok(Message::Close(_))) = next {
-                    break;
-                }
-            }
-            event = rx.recv() => {
-                match event {
-                    Ok(ev) => {
-                        if sender.send(Message::Text(serde_json::to_string(&ev).unwrap_or_default())).await.is_err() {
-                            break;
-                        }
-                    }
-                    Err(_) => break,
-                }
+        match rx.recv().await {
+            Ok(event) => {
+                let _ =
+                    tokio::task::spawn_blocking(move || send_desktop_notification(&event)).await;
             }
+            Err(broadcast::error::RecvError::Lagged(_)) => continue,
+            Err(_) => break,
         }
     }
 }
 
+/// Best-effort OS notification via `osascript` (macOS) or `notify-send` (elsewhere).
+fn send_desktop_notification(event: &DashboardEvent) {
+    let title = match event.kind.as_str() {
+        // Triggered from the terminal by the user; nothing to announce
+        "worktree-focused" => return,
+        // Fires on every prompt; the dashboard updates in place instead
+        "session-preview" => return,
+        "agent-exited" => "pigs: agent finished",
+        "agent-errored" => "pigs: agent failed",
+        "task-succeeded" => "pigs: task passed",
+        "task-failed" => "pigs: task failed",
+        "worktree-dirty" => "pigs: worktree changed",
+        "reminder-due" => "pigs: reminder due",
+        _ => "pigs",
+    };
+    let body = match &event.detail {
+        Some(detail) => format!("{} — {detail}", event.worktree_key),
+        None => event.worktree_key.clone(),
+    };
+
+    let mut command = if cfg!(target_os = "macos") {
+        let script = format!(
+            "display notification {} with title {}",
+            applescript_string(&body),
+            applescript_string(title)
+        );
+        let mut command = StdCommand::new("osascript");
+        command.args(["-e", &script]);
+        command
+    } else {
+        let mut command = StdCommand::new("notify-send");
+        command.args([title, body.as_str()]);
+        command
+    };
+
+    if let Err(err) = command.stdout(Stdio::null()).stderr(Stdio::null()).status() {
+        warn!(error = %err, "failed to send desktop notification");
+    }
+}
+
+fn applescript_string(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
 async fn start_live_session(
     repo: &str,
     name: &str,
+    agent_args: Vec<String>,
+    size: TerminalSize,
+    user: &DashboardUser,
 ) -> Result<Arc<SessionRuntime>, (StatusCode, String)> {
     let state = PigsState::load_with_local_overrides().map_err(|err| {
-        eprintln!("[dashboard] failed to load state: {err:?}");
+        error!(error = ?err, "failed to load state");
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             "Failed to load state".to_string(),
@@ -311,49 +1990,209 @@ async fn start_live_session(
         return Ok(runtime);
     }
 
-    let runtime = spawn_session(info).await.map_err(|err| {
-        eprintln!("[dashboard] failed to spawn session: {err:?}");
+    let runtime = spawn_session(info, agent_args, size, user.0.clone())
+        .await
+        .map_err(|err| {
+            error!(error = ?err, "failed to spawn session");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to launch session: {err:#}"),
+            )
+        })?;
+
+    WORKTREE_SESSION_INDEX
+        .write()
+        .await
+        .insert(key.clone(), runtime.id().to_string());
+    SESSION_REGISTRY
+        .write()
+        .await
+        .insert(runtime.id().to_string(), runtime.clone());
+    runtime.push_status("running", None).await;
+    activity::record_launch(
+        &user.0,
+        repo,
+        name,
+        "live-session",
+        Some(runtime.id().to_string()),
+        Some(runtime.id()),
+        runtime.command(),
+    );
+    Ok(runtime)
+}
+
+/// Run a task from the worktree's `.pigs/settings.json`. A task that is still
+/// running is reattached instead of started twice.
+async fn start_task(
+    repo: &str,
+    name: &str,
+    task: &str,
+    size: TerminalSize,
+    user: &DashboardUser,
+) -> Result<Arc<SessionRuntime>, (StatusCode, String)> {
+    let state = PigsState::load().map_err(|err| {
+        error!(error = ?err, "failed to load state");
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            "Failed to launch session".to_string(),
+            "Failed to load state".to_string(),
         )
     })?;
 
-    WORKTREE_SESSION_INDEX
+    let key = PigsState::make_key(repo, name);
+    let info = state.worktrees.get(&key).cloned().ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            format!("Worktree '{repo}/{name}' not found"),
+        )
+    })?;
+
+    let command = RepoConfig::load(&info.path)
+        .map_err(|err| {
+            (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                format!("Failed to read .pigs/settings.json: {err}"),
+            )
+        })?
+        .tasks
+        .remove(task)
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                format!("Task '{task}' is not defined for '{repo}/{name}'"),
+            )
+        })?;
+
+    let index_key = task_key(&key, task);
+    if let Some(existing) = TASK_SESSION_INDEX.read().await.get(&index_key).cloned()
+        && let Some(runtime) = SESSION_REGISTRY.read().await.get(&existing).cloned()
+    {
+        return Ok(runtime);
+    }
+
+    let runtime = spawn_task(
+        info,
+        task.to_string(),
+        command.clone(),
+        size,
+        user.0.clone(),
+    )
+    .await
+    .map_err(|err| {
+        error!(error = ?err, "failed to spawn task");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to launch task".to_string(),
+        )
+    })?;
+
+    TASK_SESSION_INDEX
         .write()
         .await
-        .insert(key.clone(), runtime.id().to_string());
+        .insert(index_key, runtime.id().to_string());
     SESSION_REGISTRY
         .write()
         .await
         .insert(runtime.id().to_string(), runtime.clone());
-    runtime.push_status("running", None).await;
+    runtime.push_status("running", Some(command)).await;
+    activity::record_launch(
+        &user.0,
+        repo,
+        name,
+        "task",
+        Some(task.to_string()),
+        Some(runtime.id()),
+        runtime.command(),
+    );
     Ok(runtime)
 }
 
-async fn spawn_session(info: WorktreeInfo) -> Result<Arc<SessionRuntime>> {
+async fn spawn_session(
+    info: WorktreeInfo,
+    agent_args: Vec<String>,
+    size: TerminalSize,
+    launched_by: String,
+) -> Result<Arc<SessionRuntime>> {
     let handle = tokio::runtime::Handle::current();
-    tokio::task::spawn_blocking(move || spawn_session_blocking(info, handle))
-        .await
-        .context("spawn blocking session task failed")?
+    tokio::task::spawn_blocking(move || {
+        spawn_session_blocking(info, agent_args, size, launched_by, handle)
+    })
+    .await
+    .context("spawn blocking session task failed")?
 }
 
 fn spawn_session_blocking(
     info: WorktreeInfo,
+    agent_args: Vec<String>,
+    size: TerminalSize,
+    launched_by: String,
+    handle: tokio::runtime::Handle,
+) -> Result<Arc<SessionRuntime>> {
+    let (program, mut args) =
+        prepare_agent_command(&info.path, None).context("Failed to resolve agent command")?;
+    args.extend(agent_args);
+    let repo_hooks = RepoConfig::load(&info.path)?.hooks;
+    hooks::run(Hook::PreOpen, &info, &info.path, &repo_hooks, true)
+        .context("Not launching the agent")?;
+    activity::record_agent_use(&info.repo_name, &info.name, &program, &args);
+    let runtime = spawn_pty_session(&info, program, args, None, size, launched_by, handle)?;
+    if let Err(err) = snapshot_session_ref(&info.path, runtime.id()) {
+        error!(error = ?err, "failed to snapshot worktree for session");
+    }
+    Ok(runtime)
+}
+
+async fn spawn_task(
+    info: WorktreeInfo,
+    task: String,
+    command: String,
+    size: TerminalSize,
+    launched_by: String,
+) -> Result<Arc<SessionRuntime>> {
+    let handle = tokio::runtime::Handle::current();
+    tokio::task::spawn_blocking(move || {
+        let args = vec!["-c".to_string(), command];
+        spawn_pty_session(
+            &info,
+            "sh".to_string(),
+            args,
+            Some(task),
+            size,
+            launched_by,
+            handle,
+        )
+    })
+    .await
+    .context("spawn blocking task failed")?
+}
+
+/// Run `program` in a PTY inside the worktree and stream its output through a
+/// new `SessionRuntime`. `task` is set for repo tasks, which are tracked
+/// separately from agent sessions.
+fn spawn_pty_session(
+    info: &WorktreeInfo,
+    program: String,
+    args: Vec<String>,
+    task: Option<String>,
+    size: TerminalSize,
+    launched_by: String,
     handle: tokio::runtime::Handle,
 ) -> Result<Arc<SessionRuntime>> {
     let worktree_key = PigsState::make_key(&info.repo_name, &info.name);
     let pty_system = native_pty_system();
     let pair = pty_system.openpty(PtySize {
-        rows: PTY_ROWS,
-        cols: PTY_COLS,
+        rows: size.rows,
+        cols: size.cols,
         pixel_width: 0,
         pixel_height: 0,
     })?;
 
-    let (program, args) =
-        prepare_agent_command(&info.path, None).context("Failed to resolve agent command")?;
-    let mut builder = CommandBuilder::new(program);
+    let env_changes = devenv::activation_env(&info.path);
+    let (program, args) = match &info.container {
+        Some(image) => container::wrap_command(info, image, &program, &args, &env_changes, true)?,
+        None => (program, args),
+    };
+    let command = LaunchCommand::new(&program, &args, &env_changes);
+    let mut builder = CommandBuilder::new(&program);
     for arg in args {
         builder.arg(arg);
     }
@@ -362,11 +2201,17 @@ fn spawn_session_blocking(
     for (key, value) in std::env::vars() {
         builder.env(&key, value);
     }
+    for (key, value) in env_changes {
+        match value {
+            Some(value) => builder.env(&key, value),
+            None => builder.env_remove(&key),
+        }
+    }
 
     let mut child = pair
         .slave
         .spawn_command(builder)
-        .context("Failed to spawn agent")?;
+        .with_context(|| format!("Failed to spawn {program}"))?;
     drop(pair.slave);
 
     let reader = pair
@@ -378,24 +2223,52 @@ fn spawn_session_blocking(
         .take_writer()
         .context("Failed to capture PTY writer")?;
 
-    let runtime = Arc::new(SessionRuntime::new(worktree_key.clone(), writer));
+    let runtime = Arc::new(SessionRuntime::new(
+        worktree_key.clone(),
+        launched_by,
+        task,
+        command,
+        SessionPty {
+            master: pair.master,
+            writer,
+            killer: child.clone_killer(),
+            size,
+        },
+    ));
+
+    let span = info_span!(
+        "session",
+        id = %runtime.id(),
+        worktree = %worktree_key,
+        task = runtime.task().unwrap_or_default(),
+    );
+    span.in_scope(|| info!(command = %runtime.command().command_line(), "session started"));
+    let mut started = WebhookEvent::new("session.started", info);
+    started.user = runtime.launched_by().to_string();
+    started.session_id = Some(runtime.id().to_string());
+    started.task = runtime.task().map(str::to_string);
+    started.detail = Some(program.clone());
+    webhooks::emit_in_background(started.clone());
 
     let reader_runtime = runtime.clone();
     let reader_handle = handle.clone();
+    let reader_span = span.clone();
     std::thread::spawn(move || {
+        let _entered = reader_span.enter();
         let mut reader = reader;
         let mut buf = [0u8; 4096];
         loop {
             match reader.read(&mut buf) {
                 Ok(0) => break,
                 Ok(n) => {
-                    let (cleaned, responses) = scrub_terminal_queries(&buf[..n]);
+                    let (cleaned, responses) =
+                        scrub_terminal_queries(&buf[..n], reader_runtime.size());
                     for response in responses {
                         let runtime = reader_runtime.clone();
                         let handle = reader_handle.clone();
                         handle.spawn(async move {
                             if let Err(err) = runtime.write_bytes(response).await {
-                                eprintln!("[dashboard] failed to send terminal response: {err:?}");
+                                warn!(error = ?err, "failed to send terminal response");
                             }
                         });
                     }
@@ -423,41 +2296,233 @@ fn spawn_session_blocking(
 
     let wait_runtime = runtime.clone();
     let wait_handle = handle.clone();
-    std::thread::spawn(move || match child.wait() {
-        Ok(status) => {
-            let mut detail = format!("exit code {}", status.exit_code());
-            if !status.success() {
-                detail.push_str(" (failed)");
+    std::thread::spawn(move || {
+        let _entered = span.enter();
+        let mut exited = started;
+        exited.event = "session.exited";
+        exited.timestamp = Utc::now();
+        match child.wait() {
+            Ok(status) => {
+                exited.exit_code = Some(i64::from(status.exit_code()));
+                webhooks::emit_in_background(exited);
+                let mut detail = format!("exit code {}", status.exit_code());
+                if !status.success() {
+                    detail.push_str(" (failed)");
+                }
+                info!(exit_code = status.exit_code(), "session exited");
+                let id = wait_runtime.id().to_string();
+                let key = wait_runtime.worktree_key().to_string();
+                let kind = match (wait_runtime.task(), status.success()) {
+                    (None, true) => "agent-exited",
+                    (None, false) => "agent-errored",
+                    (Some(_), true) => "task-succeeded",
+                    (Some(_), false) => "task-failed",
+                };
+                if let Some(task) = wait_runtime.task() {
+                    detail = format!("{task}: {detail}");
+                }
+                publish_dashboard_event(DashboardEvent::new(
+                    kind,
+                    &key,
+                    Some(&id),
+                    Some(detail.clone()),
+                ));
+                let exit_code = i64::from(status.exit_code());
+                wait_handle.spawn(async move {
+                    wait_runtime.push_status("stopped", Some(detail)).await;
+                    if let Some(task) = wait_runtime.task() {
+                        save_task_artifacts(&wait_runtime, task, exit_code).await;
+                    }
+                    release_session_index(&wait_runtime).await;
+                    schedule_session_cleanup(id).await;
+                });
+            }
+            Err(err) => {
+                error!(error = %err, "failed to wait for session");
+                exited.detail = Some(format!("wait error: {err}"));
+                webhooks::emit_in_background(exited);
+                let id = wait_runtime.id().to_string();
+                let key = wait_runtime.worktree_key().to_string();
+                let kind = if wait_runtime.task().is_some() {
+                    "task-failed"
+                } else {
+                    "agent-errored"
+                };
+                publish_dashboard_event(DashboardEvent::new(
+                    kind,
+                    &key,
+                    Some(&id),
+                    Some(format!("wait error: {err}")),
+                ));
+                wait_handle.spawn(async move {
+                    wait_runtime
+                        .push_status("stopped", Some(format!("wait error: {err}")))
+                        .await;
+                    release_session_index(&wait_runtime).await;
+                    schedule_session_cleanup(id).await;
+                });
             }
-            let id = wait_runtime.id().to_string();
-            let key = wait_runtime.worktree_key().to_string();
-            wait_handle.spawn(async move {
-                wait_runtime.push_status("stopped", Some(detail)).await;
-                WORKTREE_SESSION_INDEX.write().await.remove(&key);
-                schedule_session_cleanup(id).await;
-            });
-        }
-        Err(err) => {
-            let id = wait_runtime.id().to_string();
-            let key = wait_runtime.worktree_key().to_string();
-            wait_handle.spawn(async move {
-                wait_runtime
-                    .push_status("stopped", Some(format!("wait error: {err}")))
-                    .await;
-                WORKTREE_SESSION_INDEX.write().await.remove(&key);
-                schedule_session_cleanup(id).await;
-            });
         }
     });
 
     Ok(runtime)
 }
 
+/// Keep a finished task's output and the repo's configured artifacts so they
+/// can be reviewed later with `pigs artifacts`.
+async fn save_task_artifacts(runtime: &SessionRuntime, task: &str, exit_code: i64) {
+    let (events, _) = runtime.transcript().await;
+    let output: String = events
+        .iter()
+        .filter(|event| event.channel.as_deref() == Some("stdout"))
+        .filter_map(|event| event.text.as_deref())
+        .collect();
+    let key = runtime.worktree_key().to_string();
+    let task = task.to_string();
+    match tokio::task::spawn_blocking(move || {
+        artifacts::record_run(&key, &task, Some(exit_code), &output)
+    })
+    .await
+    {
+        Ok(Ok(run)) => info!(run = %run.id, files = run.files.len(), "saved task artifacts"),
+        Ok(Err(err)) => error!(error = ?err, "failed to save task artifacts"),
+        Err(err) => error!(error = ?err, "worker thread panicked"),
+    }
+}
+
+/// Forget a finished session in the index that maps worktrees (or worktree
+/// tasks) to their running session.
+async fn release_session_index(runtime: &SessionRuntime) {
+    let (mut index, key) = match runtime.task() {
+        Some(task) => (
+            TASK_SESSION_INDEX.write().await,
+            task_key(runtime.worktree_key(), task),
+        ),
+        None => (
+            WORKTREE_SESSION_INDEX.write().await,
+            runtime.worktree_key().to_string(),
+        ),
+    };
+    // A newer session may already have taken this slot
+    if index.get(&key).map(String::as_str) == Some(runtime.id()) {
+        index.remove(&key);
+    }
+}
+
+/// Stop every agent session and task running in a worktree and drop them from
+/// the registry. The indexes and registry are updated under one set of locks,
+/// taken in the same order as the lookups in `start_live_session` and
+/// `start_task`. Returns how many sessions were stopped.
+async fn terminate_worktree_sessions(worktree_key: &str, reason: &str) -> usize {
+    let runtimes: Vec<Arc<SessionRuntime>> = {
+        let mut worktree_index = WORKTREE_SESSION_INDEX.write().await;
+        let mut task_index = TASK_SESSION_INDEX.write().await;
+        let mut registry = SESSION_REGISTRY.write().await;
+
+        worktree_index.remove(worktree_key);
+        let task_prefix = task_key(worktree_key, "");
+        task_index.retain(|key, _| !key.starts_with(&task_prefix));
+
+        let ids: Vec<String> = registry
+            .iter()
+            .filter(|(_, runtime)| runtime.worktree_key() == worktree_key)
+            .map(|(id, _)| id.clone())
+            .collect();
+        ids.iter().filter_map(|id| registry.remove(id)).collect()
+    };
+
+    for runtime in &runtimes {
+        runtime.terminate(reason).await;
+    }
+    runtimes.len()
+}
+
+/// Stop one session and drop it from the registry, like
+/// `terminate_worktree_sessions`. Returns `false` when no session has that id.
+async fn terminate_session(id: &str, reason: &str) -> bool {
+    let runtime = {
+        let mut worktree_index = WORKTREE_SESSION_INDEX.write().await;
+        let mut task_index = TASK_SESSION_INDEX.write().await;
+        let mut registry = SESSION_REGISTRY.write().await;
+
+        let Some(runtime) = registry.remove(id) else {
+            return false;
+        };
+        let (index, key) = match runtime.task() {
+            Some(task) => (&mut *task_index, task_key(runtime.worktree_key(), task)),
+            None => (&mut *worktree_index, runtime.worktree_key().to_string()),
+        };
+        if index.get(&key).map(String::as_str) == Some(id) {
+            index.remove(&key);
+        }
+        runtime
+    };
+
+    runtime.terminate(reason).await;
+    true
+}
+
+/// Stop sessions whose worktree was deleted outside the dashboard (e.g. with
+/// `pigs delete`), so their processes don't keep running in a removed directory.
+async fn reap_orphaned_sessions() {
+    let mut interval = tokio::time::interval(Duration::from_secs(SESSION_REAP_SECS));
+    loop {
+        interval.tick().await;
+        let keys: HashSet<String> = SESSION_REGISTRY
+            .read()
+            .await
+            .values()
+            .map(|runtime| runtime.worktree_key().to_string())
+            .collect();
+        if keys.is_empty() {
+            continue;
+        }
+
+        let orphaned = match tokio::task::spawn_blocking(move || orphaned_worktree_keys(keys)).await
+        {
+            Ok(Ok(orphaned)) => orphaned,
+            Ok(Err(err)) => {
+                error!(error = ?err, "failed to check sessions for deleted worktrees");
+                continue;
+            }
+            Err(err) => {
+                error!(error = ?err, "worker thread panicked");
+                continue;
+            }
+        };
+
+        for key in orphaned {
+            let stopped = terminate_worktree_sessions(&key, "worktree deleted").await;
+            if stopped > 0 {
+                info!(stopped, worktree = %key, "stopped sessions for deleted worktree");
+            }
+        }
+    }
+}
+
+/// Keys whose worktree is gone from pigs state or whose directory was removed.
+fn orphaned_worktree_keys(keys: HashSet<String>) -> Result<Vec<String>> {
+    let state = PigsState::load()?;
+    Ok(keys
+        .into_iter()
+        .filter(|key| {
+            state
+                .worktrees
+                .get(key)
+                .is_none_or(|info| !info.path.exists())
+        })
+        .collect())
+}
+
+fn task_key(worktree_key: &str, task: &str) -> String {
+    format!("{worktree_key}#{task}")
+}
+
 async fn get_session_runtime(id: &str) -> Option<Arc<SessionRuntime>> {
     SESSION_REGISTRY.read().await.get(id).cloned()
 }
 
-fn build_dashboard_payload(limit: usize) -> Result<DashboardPayload> {
+pub(crate) fn build_dashboard_payload(limit: usize) -> Result<DashboardPayload> {
     let state = PigsState::load()?;
     let worktree_paths: Vec<PathBuf> = state
         .worktrees
@@ -465,14 +2530,13 @@ fn build_dashboard_payload(limit: usize) -> Result<DashboardPayload> {
         .map(|info| info.path.clone())
         .collect();
 
-    let (codex_sessions, codex_error) =
-        match codex::collect_recent_sessions_for_paths(&worktree_paths, limit) {
-            Ok(map) => (map, None),
-            Err(err) => {
-                eprintln!("[dashboard] failed to collect Codex sessions: {err:?}");
-                (HashMap::new(), Some(err.to_string()))
-            }
-        };
+    let (codex_sessions, codex_error) = match session_tail::codex_sessions(&worktree_paths, limit) {
+        Ok(map) => (map, None),
+        Err(err) => {
+            error!(error = ?err, "failed to collect Codex sessions");
+            (HashMap::new(), Some(err.to_string()))
+        }
+    };
 
     let codex_context = CodexContext {
         sessions: codex_sessions,
@@ -484,26 +2548,107 @@ fn build_dashboard_payload(limit: usize) -> Result<DashboardPayload> {
         .values()
         .map(|info| summarize_worktree(info, limit, &codex_context))
         .collect();
+    if !network::is_offline() {
+        let now = Instant::now();
+        let mut pull_requests = HashMap::new();
+        for tree in &mut worktrees {
+            let snapshot = pull_requests
+                .entry(tree.repo_name.clone())
+                .or_insert_with(|| PULL_REQUESTS.read(&tree.repo_name, now));
+            let branch = tree.branch.clone();
+            tree.pull_request = Some(
+                snapshot
+                    .clone()
+                    .map(|mut branches| branches.remove(&branch)),
+            );
+        }
+    }
 
     worktrees.sort_by(|a, b| {
         a.repo_name
             .cmp(&b.repo_name)
             .then_with(|| a.name.cmp(&b.name))
     });
+    let worktrees = nest_stacked_worktrees(worktrees);
 
     Ok(DashboardPayload {
         generated_at: Utc::now(),
+        focus: state
+            .focused()
+            .map(|info| PigsState::make_key(&info.repo_name, &info.name)),
         worktrees,
     })
 }
 
+/// Move stacked worktrees directly below their parents and record their depth
+/// so the sidebar can indent them.
+fn nest_stacked_worktrees(worktrees: Vec<WorktreeSummary>) -> Vec<WorktreeSummary> {
+    let nodes: Vec<StackNode> = worktrees
+        .iter()
+        .map(|tree| StackNode {
+            repo_name: &tree.repo_name,
+            name: &tree.name,
+            parent: tree.stack_parent.as_deref(),
+        })
+        .collect();
+    let order = stack_order(&nodes);
+
+    let mut slots: Vec<Option<WorktreeSummary>> = worktrees.into_iter().map(Some).collect();
+    order
+        .into_iter()
+        .filter_map(|(index, depth)| {
+            let mut tree = slots[index].take()?;
+            tree.stack_depth = depth;
+            Some(tree)
+        })
+        .collect()
+}
+
+/// Roll worktree summaries up into one entry per repository. `running_keys`
+/// holds the `repo/name` keys of worktrees with a live dashboard session.
+fn summarize_repos(worktrees: &[WorktreeSummary], running_keys: &[String]) -> Vec<RepoSummary> {
+    let mut repos: BTreeMap<&str, RepoSummary> = BTreeMap::new();
+    for worktree in worktrees {
+        let repo = repos
+            .entry(worktree.repo_name.as_str())
+            .or_insert_with(|| RepoSummary {
+                repo_name: worktree.repo_name.clone(),
+                worktree_count: 0,
+                dirty_files: 0,
+                last_activity: None,
+                running_sessions: 0,
+            });
+        let status = &worktree.git_status;
+        repo.worktree_count += 1;
+        repo.dirty_files += status.staged_files
+            + status.unstaged_files
+            + status.untracked_files
+            + status.conflict_files;
+        if repo
+            .last_activity
+            .is_none_or(|ts| worktree.last_activity > ts)
+        {
+            repo.last_activity = Some(worktree.last_activity);
+        }
+        if running_keys.contains(&worktree.key) {
+            repo.running_sessions += 1;
+        }
+    }
+    repos.into_values().collect()
+}
+
 fn summarize_worktree(
     info: &WorktreeInfo,
     limit: usize,
     codex_ctx: &CodexContext,
 ) -> WorktreeSummary {
     let git_status = summarize_git(&info.path);
-    let claude_sessions = claude::get_claude_sessions(&info.path);
+    let labels = detect_worktree_labels(&info.path);
+    let problems = detect_worktree_problems(&info.path)
+        .into_iter()
+        .map(|kind| ProblemReport::new(kind, &info.branch))
+        .collect();
+    let claude_sessions = session_tail::claude_sessions(&info.path);
     let mut sessions = Vec::new();
 
     for session in claude_sessions.into_iter().take(limit) {
@@ -556,28 +2701,213 @@ fn summarize_worktree(
         created_at: info.created_at,
         last_activity,
         git_status,
+        labels,
+        stack_parent: info.stack.as_ref().map(|link| link.parent.clone()),
+        stack_depth: 0,
+        tasks: RepoConfig::load(&info.path)
+            .map(|config| config.tasks.into_keys().collect())
+            .unwrap_or_default(),
+        problems,
+        reminder: info
+            .reminder
+            .as_ref()
+            .map(|reminder| ReminderReport::new(reminder, Utc::now())),
+        note: info.note.clone(),
+        tags: info.tags.clone(),
+        last_agent: info.last_agent().cloned(),
         sessions,
         session_error,
+        pull_request: None,
+    }
+}
+
+struct CreatedLinearWorktree {
+    repo_name: String,
+    name: String,
+    prompt: String,
+}
+
+/// Dashboard counterpart of `handle_linear`: fetch the issue, optionally move it
+/// to In Progress, and create a worktree on Linear's suggested branch.
+fn create_linear_worktree(
+    identifier: &str,
+    req: LinearWorktreeRequest,
+) -> Result<CreatedLinearWorktree, (StatusCode, String)> {
+    if !linear::is_linear_task_id(identifier) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("'{identifier}' is not a valid Linear task ID (expected format: ENG-123)"),
+        ));
+    }
+
+    if network::is_offline() {
+        return Err((
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Linear is unavailable in offline mode".to_string(),
+        ));
+    }
+
+    let repo_root = resolve_repo_root(req.repo.as_deref())
+        .map_err(|err| (StatusCode::BAD_REQUEST, err.to_string()))?;
+
+    let issue = linear::fetch_issue(identifier).map_err(|err| {
+        error!(error = ?err, issue = %identifier, "failed to fetch Linear issue");
+        (StatusCode::BAD_GATEWAY, err.to_string())
+    })?;
+
+    if req.start_issue
+        && let Err(err) = linear::start_issue(identifier)
+    {
+        warn!(error = ?err, issue = %identifier, "failed to update Linear issue");
+    }
+
+    let name = handle_create_in_dir_quiet(
+        Some(issue.branch_name),
+        Some(repo_root.clone()),
+        req.from,
+        None,
+        true,
+        true,
+        None,
+        None,
+        Vec::new(),
+    )
+    .map_err(|err| {
+        error!(error = ?err, issue = %identifier, "failed to create worktree for Linear issue");
+        (StatusCode::INTERNAL_SERVER_ERROR, err.to_string())
+    })?;
+
+    let state = PigsState::load().map_err(|err| {
+        error!(error = ?err, "failed to load state");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to load state".to_string(),
+        )
+    })?;
+    let main_checkout = crate::git::main_checkout(&repo_root).unwrap_or(repo_root);
+    let repo_name = state
+        .worktrees
+        .values()
+        .find(|info| {
+            info.name == name && info.main_repo_path().ok().as_ref() == Some(&main_checkout)
+        })
+        .map(|info| info.repo_name.clone())
+        .ok_or_else(|| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Worktree '{name}' was created but is missing from state"),
+            )
+        })?;
+
+    let mut prompt = issue.title;
+    if let Some(desc) = issue.description {
+        prompt.push_str("\n\n");
+        prompt.push_str(&desc);
+    }
+
+    Ok(CreatedLinearWorktree {
+        repo_name,
+        name,
+        prompt,
+    })
+}
+
+/// Locate the main repository checkout for a repo name from its managed
+/// worktrees. Falls back to the repository the dashboard was started in.
+fn resolve_repo_root(repo: Option<&str>) -> Result<PathBuf> {
+    if let Some(repo) = repo.map(str::trim).filter(|r| !r.is_empty()) {
+        let state = PigsState::load()?;
+        return state
+            .worktrees
+            .values()
+            .filter(|info| info.repo_name == repo)
+            .filter_map(|info| info.main_repo_path().ok())
+            .find(|candidate| candidate.exists())
+            .with_context(|| format!("Cannot locate the main checkout for repository '{repo}'"));
     }
+
+    let toplevel = crate::git::execute_git(&["rev-parse", "--show-toplevel"])
+        .context("Dashboard is not running inside a git repository; pass a repo name")?;
+    Ok(PathBuf::from(toplevel))
 }
 
 fn load_settings_payload() -> Result<SettingsPayload> {
     let state = PigsState::load_with_local_overrides()?;
-    Ok(SettingsPayload {
+    Ok(settings_payload(&state))
+}
+
+fn settings_payload(state: &PigsState) -> SettingsPayload {
+    let agents = configured_agents(state);
+    SettingsPayload {
         editor: state.editor.clone(),
         terminal: state.shell.clone(),
-    })
+        default_agent: agents.first().map(|agent| agent.name.clone()),
+        agents: Some(agents),
+    }
 }
 
-fn update_settings_state(req: SettingsPayload) -> Result<SettingsPayload> {
-    let mut state = PigsState::load()?;
+/// Agent options in launch order; the built-in claude command when none are set.
+fn configured_agents(state: &PigsState) -> Vec<AgentOption> {
+    match &state.agent {
+        Some(agents) if !agents.is_empty() => agents.clone(),
+        _ => vec![get_default_agent()],
+    }
+}
+
+/// Save editor and terminal, and the agent list when `agents` or
+/// `defaultAgent` is present. An empty agent list restores the built-in default.
+fn update_settings_state(req: SettingsPayload) -> Result<SettingsPayload, (StatusCode, String)> {
+    let internal = |err: anyhow::Error| {
+        error!(error = ?err, "failed to update settings");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to update settings".to_string(),
+        )
+    };
+    let mut state = PigsState::load().map_err(internal)?;
     state.editor = normalize_setting(req.editor);
     state.shell = normalize_setting(req.terminal);
-    state.save()?;
-    Ok(SettingsPayload {
-        editor: state.editor.clone(),
-        terminal: state.shell.clone(),
-    })
+
+    if let Some(agents) = req.agents {
+        let agents = normalize_agents(agents).map_err(|msg| (StatusCode::BAD_REQUEST, msg))?;
+        state.agent = (!agents.is_empty()).then_some(agents);
+    }
+    if let Some(default) = req.default_agent {
+        let mut agents = configured_agents(&state);
+        let position = agents
+            .iter()
+            .position(|agent| agent.name == default.trim())
+            .ok_or_else(|| {
+                (
+                    StatusCode::BAD_REQUEST,
+                    format!("Unknown agent '{default}'"),
+                )
+            })?;
+        let agent = agents.remove(position);
+        agents.insert(0, agent);
+        state.agent = Some(agents);
+    }
+
+    state.save().map_err(internal)?;
+    Ok(settings_payload(&state))
+}
+
+fn normalize_agents(agents: Vec<AgentOption>) -> Result<Vec<AgentOption>, String> {
+    let mut seen = HashSet::new();
+    agents
+        .into_iter()
+        .map(|agent| {
+            let name = agent.name.trim().to_string();
+            let command = agent.command.trim().to_string();
+            if name.is_empty() || command.is_empty() {
+                return Err("Every agent needs a name and a command".to_string());
+            }
+            if !seen.insert(name.clone()) {
+                return Err(format!("Agent '{name}' is listed more than once"));
+            }
+            Ok(AgentOption { name, command })
+        })
+        .collect()
 }
 
 fn normalize_setting(value: Option<String>) -> Option<String> {
@@ -622,9 +2952,81 @@ struct CodexContext {
 
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
-struct DashboardPayload {
+pub(crate) struct DashboardPayload {
+    pub(crate) generated_at: DateTime<Utc>,
+    /// Key of the worktree set with `pigs focus`
+    pub(crate) focus: Option<String>,
+    pub(crate) worktrees: Vec<WorktreeSummary>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RepoOverviewPayload {
     generated_at: DateTime<Utc>,
-    worktrees: Vec<WorktreeSummary>,
+    repos: Vec<RepoSummary>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RepoSummary {
+    repo_name: String,
+    worktree_count: usize,
+    dirty_files: usize,
+    last_activity: Option<DateTime<Utc>>,
+    running_sessions: usize,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct LinearIssuesPayload {
+    issues: Vec<linear::LinearIssueSummary>,
+    fetched_at: Option<DateTime<Utc>>,
+    /// The list is older than expected, or the last refresh failed
+    stale: bool,
+    error: Option<String>,
+}
+
+impl From<Snapshot<Vec<linear::LinearIssueSummary>>> for LinearIssuesPayload {
+    fn from(snapshot: Snapshot<Vec<linear::LinearIssueSummary>>) -> Self {
+        Self {
+            issues: snapshot.value.unwrap_or_default(),
+            fetched_at: snapshot.fetched_at,
+            stale: snapshot.stale,
+            error: snapshot.error,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct LiveSessionRequest {
+    rows: Option<u16>,
+    cols: Option<u16>,
+}
+
+/// PTY dimensions for a live session, clamped to a sane range so a bogus
+/// client value can't starve or explode the agent's terminal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct TerminalSize {
+    pub(crate) rows: u16,
+    pub(crate) cols: u16,
+}
+
+impl TerminalSize {
+    fn requested(rows: Option<u16>, cols: Option<u16>) -> Self {
+        Self {
+            rows: rows.unwrap_or(PTY_ROWS).clamp(PTY_MIN_ROWS, PTY_MAX_ROWS),
+            cols: cols.unwrap_or(PTY_COLS).clamp(PTY_MIN_COLS, PTY_MAX_COLS),
+        }
+    }
+}
+
+impl Default for TerminalSize {
+    fn default() -> Self {
+        Self {
+            rows: PTY_ROWS,
+            cols: PTY_COLS,
+        }
+    }
 }
 
 #[derive(Deserialize)]
@@ -643,12 +3045,43 @@ struct ActionResponse {
 struct SettingsPayload {
     editor: Option<String>,
     terminal: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    agents: Option<Vec<AgentOption>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    default_agent: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LinearWorktreeRequest {
+    repo: Option<String>,
+    from: Option<String>,
+    #[serde(default = "default_true")]
+    start_issue: bool,
+    #[serde(default = "default_true")]
+    start_session: bool,
+    rows: Option<u16>,
+    cols: Option<u16>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct LinearWorktreeResponse {
+    key: String,
+    session_id: Option<String>,
+    launched_by: Option<String>,
+    events: Vec<SessionEvent>,
 }
 
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 struct StartSessionResponse {
     session_id: String,
+    launched_by: String,
     events: Vec<SessionEvent>,
 }
 
@@ -657,52 +3090,412 @@ struct SendMessageRequest {
     message: String,
 }
 
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CreateWorktreeRequest {
+    repo: Option<String>,
+    name: Option<String>,
+    from: Option<String>,
+    stack_on: Option<String>,
+}
+
+#[derive(Serialize)]
+struct ReadinessCheck {
+    name: &'static str,
+    ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+impl ReadinessCheck {
+    fn passed(name: &'static str) -> Self {
+        Self {
+            name,
+            ok: true,
+            error: None,
+        }
+    }
+
+    fn failed(name: &'static str, error: String) -> Self {
+        Self {
+            name,
+            ok: false,
+            error: Some(error),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct SyncRequest {
+    name: Option<String>,
+    #[serde(default)]
+    merge: bool,
+}
+
+#[derive(Deserialize)]
+struct CleanRequest {
+    base: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct DeleteWorktreeQuery {
+    #[serde(default)]
+    force: bool,
+}
+
+#[derive(Deserialize)]
+struct SnapshotDiffQuery {
+    from: String,
+    to: String,
+    #[serde(default)]
+    stat: bool,
+}
+
+#[derive(Deserialize)]
+struct TimelineQuery {
+    hours: Option<i64>,
+}
+
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
-struct WorktreeSummary {
-    key: String,
-    repo_name: String,
-    name: String,
-    branch: String,
-    path: String,
-    created_at: DateTime<Utc>,
-    last_activity: DateTime<Utc>,
-    git_status: GitStatusSummary,
-    sessions: Vec<SessionPreview>,
-    session_error: Option<String>,
+struct TimelineEntry {
+    timestamp: DateTime<Utc>,
+    kind: String,
+    summary: String,
+    detail: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct AnnotationRequest {
+    sequence: u64,
+    #[serde(default)]
+    note: Option<String>,
+}
+
+/// Who a dashboard request acts as: the name mapped to its bearer token in
+/// `dashboard_tokens` or `dashboard_users`, else the `X-Pigs-User` header set
+/// by an authenticating reverse proxy, else the OS user running the dashboard.
+/// The header is only honored when the dashboard listens on loopback and no
+/// tokens are configured; otherwise any client could claim any name.
+#[derive(Clone)]
+struct DashboardUser(String);
+
+#[axum::async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for DashboardUser {
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(Self(resolve_dashboard_user(
+            &parts.headers,
+            parts.uri.query(),
+            LOOPBACK_ONLY.load(AtomicOrdering::Relaxed),
+        )))
+    }
+}
+
+fn resolve_dashboard_user(headers: &HeaderMap, query: Option<&str>, loopback_only: bool) -> String {
+    let state = PigsState::load().ok();
+    if let Some(state) = &state
+        && let Some(token) = request_token(headers, query)
+        && let Some(name) = state
+            .dashboard_tokens
+            .get(&token)
+            .and_then(|token| token.user.as_ref())
+            .or_else(|| state.dashboard_users.get(&token))
+    {
+        return name.clone();
+    }
+
+    let tokens_configured = state.as_ref().is_none_or(|state| {
+        !state.dashboard_tokens.is_empty() || !state.dashboard_users.is_empty()
+    });
+    if !loopback_only || tokens_configured {
+        return activity::local_user();
+    }
+    headers
+        .get(USER_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+        .unwrap_or_else(activity::local_user)
+}
+
+/// The bearer token of a request, or its `token` query parameter, which is
+/// how browsers authenticate page loads and WebSockets.
+fn request_token(headers: &HeaderMap, query: Option<&str>) -> Option<String> {
+    headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(|token| token.trim().to_string())
+        .or_else(|| {
+            query?
+                .split('&')
+                .find_map(|pair| pair.strip_prefix("token="))
+                .map(str::to_string)
+        })
+        .filter(|token| !token.is_empty())
+}
+
+/// Reject API requests whose token lacks the scope their route needs. Checks
+/// only start once a scoped token or a `dashboard_users` token exists, so a
+/// dashboard without any keeps working as before.
+async fn require_token_scope(req: Request, next: Next) -> Response {
+    if !req.uri().path().starts_with("/api/") {
+        return next.run(req).await;
+    }
+    let Ok(state) = PigsState::load() else {
+        return (StatusCode::INTERNAL_SERVER_ERROR, "Failed to load settings").into_response();
+    };
+    let token = request_token(req.headers(), req.uri().query());
+    match authorize(&state, token.as_deref(), req.method(), req.uri().path()) {
+        Ok(()) => next.run(req).await,
+        Err((status, message)) => {
+            warn!(path = %req.uri().path(), %status, "token rejected");
+            (status, message).into_response()
+        }
+    }
+}
+
+fn authorize(
+    state: &PigsState,
+    token: Option<&str>,
+    method: &Method,
+    path: &str,
+) -> Result<(), (StatusCode, String)> {
+    if state.dashboard_tokens.is_empty() && state.dashboard_users.is_empty() {
+        return Ok(());
+    }
+    let Some(token) = token else {
+        return Err((
+            StatusCode::UNAUTHORIZED,
+            "This dashboard needs a token: send `Authorization: Bearer <token>`".to_string(),
+        ));
+    };
+    // Tokens in `dashboard_users` predate scopes and keep full access
+    let scope = match state.dashboard_tokens.get(token) {
+        Some(minted) => minted.scope,
+        None if state.dashboard_users.contains_key(token) => TokenScope::Admin,
+        None => return Err((StatusCode::UNAUTHORIZED, "Unknown token".to_string())),
+    };
+    let required = required_scope(method, path);
+    if scope < required {
+        return Err((
+            StatusCode::FORBIDDEN,
+            format!(
+                "This token has the {} scope; {method} {path} needs {}",
+                scope.as_str(),
+                required.as_str()
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// The scope a route group needs: reads (including event streams) need
+/// `read`, driving agent sessions needs `sessions`, and anything else that
+/// changes state or spawns a process needs `admin`.
+fn required_scope(method: &Method, path: &str) -> TokenScope {
+    if method == Method::GET || method == Method::HEAD {
+        return TokenScope::Read;
+    }
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    match segments.as_slice() {
+        ["api", "worktrees", _, _, "live-session"]
+        | ["api", "sessions", _]
+        | ["api", "sessions", _, "send" | "annotations" | "resize"] => TokenScope::Sessions,
+        _ => TokenScope::Admin,
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WorktreeSummary {
+    pub(crate) key: String,
+    pub(crate) repo_name: String,
+    pub(crate) name: String,
+    pub(crate) branch: String,
+    pub(crate) path: String,
+    pub(crate) created_at: DateTime<Utc>,
+    pub(crate) last_activity: DateTime<Utc>,
+    pub(crate) git_status: GitStatusSummary,
+    pub(crate) labels: Vec<ChangeLabel>,
+    pub(crate) stack_parent: Option<String>,
+    pub(crate) stack_depth: usize,
+    pub(crate) tasks: Vec<String>,
+    pub(crate) problems: Vec<ProblemReport>,
+    pub(crate) reminder: Option<ReminderReport>,
+    pub(crate) note: Option<String>,
+    pub(crate) tags: Vec<String>,
+    /// The agent last launched in the worktree by `pigs open` or the dashboard
+    pub(crate) last_agent: Option<AgentUse>,
+    pub(crate) sessions: Vec<SessionPreview>,
+    pub(crate) session_error: Option<String>,
+    /// The branch's pull request as of the last poll of GitHub; `None` until the
+    /// repository was polled or when offline
+    pub(crate) pull_request: Option<Snapshot<PullRequestStatus>>,
 }
 
 #[derive(Serialize, Default, Clone)]
 #[serde(rename_all = "camelCase")]
-struct GitStatusSummary {
-    clean: bool,
-    staged_files: usize,
-    unstaged_files: usize,
-    untracked_files: usize,
-    conflict_files: usize,
-    last_commit_message: Option<String>,
-    last_commit_time: Option<DateTime<Utc>>,
-    error: Option<String>,
+pub(crate) struct GitStatusSummary {
+    pub(crate) clean: bool,
+    pub(crate) staged_files: usize,
+    pub(crate) unstaged_files: usize,
+    pub(crate) untracked_files: usize,
+    pub(crate) conflict_files: usize,
+    pub(crate) last_commit_message: Option<String>,
+    pub(crate) last_commit_time: Option<DateTime<Utc>>,
+    /// Whether the branch tracks a remote branch
+    pub(crate) has_upstream: bool,
+    pub(crate) upstream: Option<String>,
+    /// Commits on HEAD not yet on the upstream (i.e. unpushed)
+    pub(crate) ahead_upstream: Option<usize>,
+    pub(crate) behind_upstream: Option<usize>,
+    pub(crate) base_ref: Option<String>,
+    pub(crate) ahead_base: Option<usize>,
+    pub(crate) behind_base: Option<usize>,
+    pub(crate) error: Option<String>,
 }
 
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
-struct SessionPreview {
-    provider: String,
-    message: Option<String>,
-    timestamp: Option<DateTime<Utc>>,
+pub(crate) struct SessionPreview {
+    pub(crate) provider: String,
+    pub(crate) message: Option<String>,
+    pub(crate) timestamp: Option<DateTime<Utc>>,
 }
 
-#[derive(Clone, Serialize)]
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StreamQuery {
+    protocol_version: Option<u32>,
+    format: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct LogsQuery {
+    format: Option<String>,
+}
+
+/// How session events are delivered: `raw` terminal chunks, or `plain`
+/// ANSI-stripped text with one event per complete line.
+#[derive(Clone, Copy, PartialEq)]
+enum EventFormat {
+    Raw,
+    Plain,
+}
+
+impl EventFormat {
+    fn parse(value: Option<&str>) -> Result<Self, String> {
+        match value {
+            None | Some("raw") => Ok(Self::Raw),
+            Some("plain") => Ok(Self::Plain),
+            Some(other) => Err(format!(
+                "Unsupported format '{other}'; expected 'raw' or 'plain'"
+            )),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Raw => "raw",
+            Self::Plain => "plain",
+        }
+    }
+}
+
+/// Re-cuts raw session events into `format=plain` events: message text is
+/// ANSI-stripped and split into one event per complete line, per channel.
+/// A partial line is held back until it ends or a status event arrives.
+#[derive(Default)]
+struct PlainEvents {
+    /// Unfinished line per channel, with the last event seen on it
+    channels: HashMap<String, (LineBuffer, SessionEvent)>,
+}
+
+impl PlainEvents {
+    fn push(&mut self, event: &SessionEvent) -> Vec<SessionEvent> {
+        if event.kind != "message" {
+            let mut out = self.flush();
+            out.push(event.clone());
+            return out;
+        }
+        let text = event.text.as_deref().unwrap_or_default();
+        let lines = if event.channel.as_deref() == Some("stdin") {
+            // Sent messages are complete on their own
+            strip_ansi(text).lines().map(str::to_string).collect()
+        } else {
+            let channel = event.channel.clone().unwrap_or_default();
+            let (buffer, last) = self
+                .channels
+                .entry(channel)
+                .or_insert_with(|| (LineBuffer::default(), event.clone()));
+            *last = event.clone();
+            buffer.push(text)
+        };
+        lines
+            .into_iter()
+            .map(|line| SessionEvent {
+                text: Some(line),
+                ..event.clone()
+            })
+            .collect()
+    }
+
+    /// Emit every held-back partial line.
+    fn flush(&mut self) -> Vec<SessionEvent> {
+        let mut channels: Vec<_> = self.channels.drain().collect();
+        channels.sort_by_key(|(_, (_, last))| last.sequence);
+        channels
+            .into_iter()
+            .filter_map(|(_, (mut buffer, last))| {
+                buffer.flush().map(|line| SessionEvent {
+                    text: Some(line),
+                    ..last
+                })
+            })
+            .collect()
+    }
+}
+
+/// First frame on every session stream, announcing the negotiated protocol.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct StreamHandshake {
+    kind: &'static str,
+    protocol_version: u32,
+    session_id: String,
+    schema: &'static str,
+    /// Events dropped from the start of the transcript to stay within the
+    /// session log limits; the replayed events follow the gap.
+    dropped_events: u64,
+    /// `raw` or `plain`, as requested with `?format=`.
+    format: &'static str,
+}
+
+/// One entry in a live session transcript, as sent over
+/// `/api/sessions/:id/stream` and returned by `/api/sessions/:id/logs`.
+#[derive(Clone, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 struct SessionEvent {
+    /// Monotonically increasing per session, starting at 0.
     sequence: u64,
+    /// When the event was recorded (RFC 3339, UTC).
     timestamp: DateTime<Utc>,
+    /// Either `message` or `status`.
     kind: String,
+    /// For messages: `user` or `assistant`.
     role: Option<String>,
+    /// For messages: `stdin` or `stdout`.
     channel: Option<String>,
+    /// For messages: raw terminal text, may contain ANSI escapes. With
+    /// `format=plain`, one complete line with escapes stripped.
     text: Option<String>,
+    /// For status events: `running`, `stopped`, or `error`.
     status: Option<String>,
+    /// Optional human-readable detail for status events.
     detail: Option<String>,
 }
 
@@ -720,6 +3513,16 @@ impl SessionEvent {
         }
     }
 
+    /// Approximate memory held by the event, counted against the log byte limit.
+    fn size(&self) -> usize {
+        [&self.text, &self.detail]
+            .into_iter()
+            .flatten()
+            .map(String::len)
+            .sum::<usize>()
+            + std::mem::size_of::<Self>()
+    }
+
     fn status(sequence: u64, status: &str, detail: Option<String>) -> Self {
         Self {
             sequence,
@@ -734,25 +3537,118 @@ impl SessionEvent {
     }
 }
 
+impl From<TranscriptEvent> for SessionEvent {
+    fn from(event: TranscriptEvent) -> Self {
+        Self {
+            sequence: event.sequence,
+            timestamp: event.timestamp,
+            kind: event.kind,
+            role: event.role,
+            channel: event.channel,
+            text: event.text,
+            status: event.status,
+            detail: event.detail,
+        }
+    }
+}
+
+/// Dashboard-wide notification broadcast to every `/api/events` subscriber.
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DashboardEvent {
+    kind: String,
+    worktree_key: String,
+    session_id: Option<String>,
+    detail: Option<String>,
+    timestamp: DateTime<Utc>,
+}
+
+impl DashboardEvent {
+    fn new(
+        kind: &str,
+        worktree_key: &str,
+        session_id: Option<&str>,
+        detail: Option<String>,
+    ) -> Self {
+        Self {
+            kind: kind.to_string(),
+            worktree_key: worktree_key.to_string(),
+            session_id: session_id.map(str::to_string),
+            detail,
+            timestamp: Utc::now(),
+        }
+    }
+}
+
+fn new_annotation(sequence: u64, note: Option<String>, author: &str) -> Annotation {
+    Annotation {
+        id: Uuid::new_v4().to_string(),
+        sequence,
+        note: note.map(|n| n.trim().to_string()).filter(|n| !n.is_empty()),
+        author: author.to_string(),
+        created_at: Utc::now(),
+    }
+}
+
+fn save_annotation(session_id: &str, annotation: &Annotation) {
+    if let Err(err) = transcripts::append_annotation(session_id, annotation) {
+        warn!(session_id, error = ?err, "failed to save annotation");
+    }
+}
+
+fn publish_dashboard_event(event: DashboardEvent) {
+    // Sending only fails when nobody is subscribed, which is fine
+    let _ = DASHBOARD_EVENTS.send(event);
+}
+
 struct SessionRuntime {
     id: String,
     worktree_key: String,
-    log: Mutex<Vec<SessionEvent>>,
+    launched_by: String,
+    task: Option<String>,
+    command: LaunchCommand,
+    log: Mutex<SessionLog>,
+    annotations: Mutex<Vec<Annotation>>,
     counter: AtomicU64,
     tx: broadcast::Sender<SessionEvent>,
     writer: Mutex<Option<Box<dyn Write + Send>>>,
+    killer: Mutex<Box<dyn ChildKiller + Send + Sync>>,
+    master: Mutex<Box<dyn MasterPty + Send>>,
+    /// Read by the PTY reader thread to answer cursor queries
+    size: std::sync::Mutex<TerminalSize>,
+}
+
+/// The PTY a session's process runs in, handed to its `SessionRuntime`.
+struct SessionPty {
+    master: Box<dyn MasterPty + Send>,
+    writer: Box<dyn Write + Send>,
+    killer: Box<dyn ChildKiller + Send + Sync>,
+    size: TerminalSize,
 }
 
 impl SessionRuntime {
-    fn new(worktree_key: String, writer: Box<dyn Write + Send>) -> Self {
+    fn new(
+        worktree_key: String,
+        launched_by: String,
+        task: Option<String>,
+        command: LaunchCommand,
+        pty: SessionPty,
+    ) -> Self {
         let (tx, _rx) = broadcast::channel(512);
         Self {
             id: Uuid::new_v4().to_string(),
             worktree_key,
-            log: Mutex::new(Vec::new()),
+            launched_by,
+            task,
+            command,
+            log: Mutex::new(SessionLog::new(SessionLogLimits::from_env())),
+            annotations: Mutex::new(Vec::new()),
             counter: AtomicU64::new(0),
             tx,
-            writer: Mutex::new(Some(writer)),
+            writer: Mutex::new(Some(pty.writer)),
+            killer: Mutex::new(pty.killer),
+            master: Mutex::new(pty.master),
+            size: std::sync::Mutex::new(pty.size),
         }
     }
 
@@ -764,12 +3660,59 @@ impl SessionRuntime {
         &self.worktree_key
     }
 
+    fn launched_by(&self) -> &str {
+        &self.launched_by
+    }
+
+    fn task(&self) -> Option<&str> {
+        self.task.as_deref()
+    }
+
+    fn command(&self) -> &LaunchCommand {
+        &self.command
+    }
+
     fn subscribe(&self) -> broadcast::Receiver<SessionEvent> {
         self.tx.subscribe()
     }
 
     async fn snapshot(&self) -> Vec<SessionEvent> {
-        self.log.lock().await.clone()
+        self.transcript().await.0
+    }
+
+    /// Retained events plus how many older ones were dropped.
+    async fn transcript(&self) -> (Vec<SessionEvent>, u64) {
+        let log = self.log.lock().await;
+        (log.events.iter().cloned().collect(), log.dropped)
+    }
+
+    async fn annotations(&self) -> Vec<Annotation> {
+        self.annotations.lock().await.clone()
+    }
+
+    /// Attach a note to an existing event and save it next to the session's
+    /// transcript. Returns `None` when no event has that sequence number.
+    async fn annotate(
+        &self,
+        sequence: u64,
+        note: Option<String>,
+        author: &str,
+    ) -> Option<Annotation> {
+        if !self
+            .log
+            .lock()
+            .await
+            .events
+            .iter()
+            .any(|event| event.sequence == sequence)
+        {
+            return None;
+        }
+
+        let annotation = new_annotation(sequence, note, author);
+        save_annotation(&self.id, &annotation);
+        self.annotations.lock().await.push(annotation.clone());
+        Some(annotation)
     }
 
     async fn push_message(&self, role: &str, channel: &str, text: String) {
@@ -804,6 +3747,39 @@ impl SessionRuntime {
         self.write_bytes(payload).await
     }
 
+    /// Record why the session is ending, close stdin, and kill the child. The
+    /// wait thread still emits the final `stopped` event once it exits.
+    async fn terminate(&self, reason: &str) {
+        self.push_status("terminated", Some(reason.to_string()))
+            .await;
+        self.writer.lock().await.take();
+        if let Err(err) = self.killer.lock().await.kill() {
+            warn!(session = %self.id, error = %err, "failed to kill session");
+        }
+    }
+
+    fn size(&self) -> TerminalSize {
+        *self
+            .size
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Resize the session's terminal; the process gets `SIGWINCH`.
+    async fn resize(&self, size: TerminalSize) -> Result<()> {
+        self.master.lock().await.resize(PtySize {
+            rows: size.rows,
+            cols: size.cols,
+            pixel_width: 0,
+            pixel_height: 0,
+        })?;
+        *self
+            .size
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = size;
+        Ok(())
+    }
+
     async fn write_bytes(&self, payload: Vec<u8>) -> Result<()> {
         let mut guard = self.writer.lock().await;
         let writer = guard
@@ -815,10 +3791,117 @@ impl SessionRuntime {
     }
 }
 
+#[derive(Clone, Copy)]
+struct SessionLogLimits {
+    max_events: usize,
+    max_bytes: usize,
+}
+
+impl SessionLogLimits {
+    fn from_env() -> Self {
+        let read = |name: &str, default: usize| {
+            std::env::var(name)
+                .ok()
+                .and_then(|value| value.trim().parse().ok())
+                .filter(|value| *value > 0)
+                .unwrap_or(default)
+        };
+        Self {
+            max_events: read(
+                "PIGS_SESSION_LOG_MAX_EVENTS",
+                DEFAULT_SESSION_LOG_MAX_EVENTS,
+            ),
+            max_bytes: read("PIGS_SESSION_LOG_MAX_BYTES", DEFAULT_SESSION_LOG_MAX_BYTES),
+        }
+    }
+}
+
+/// Ring buffer of a session's newest events. The oldest events are dropped
+/// once either limit is exceeded, but the latest event is always kept.
+struct SessionLog {
+    events: VecDeque<SessionEvent>,
+    bytes: usize,
+    dropped: u64,
+    limits: SessionLogLimits,
+}
+
+impl SessionLog {
+    fn new(limits: SessionLogLimits) -> Self {
+        Self {
+            events: VecDeque::new(),
+            bytes: 0,
+            dropped: 0,
+            limits,
+        }
+    }
+
+    fn push(&mut self, event: SessionEvent) {
+        self.bytes += event.size();
+        self.events.push_back(event);
+        while self.events.len() > 1
+            && (self.events.len() > self.limits.max_events || self.bytes > self.limits.max_bytes)
+        {
+            if let Some(oldest) = self.events.pop_front() {
+                self.bytes -= oldest.size();
+                self.dropped += 1;
+            }
+        }
+    }
+}
+
+/// Render a session as Markdown, placing each annotation directly after the
+/// event it refers to.
+fn render_transcript(
+    session_id: &str,
+    worktree_key: &str,
+    events: &[SessionEvent],
+    dropped_events: u64,
+    annotations: &[Annotation],
+) -> String {
+    let mut out = format!("# Session {session_id}\n\nWorktree: `{worktree_key}`\n\n");
+    if dropped_events > 0 {
+        out.push_str(&format!(
+            "_{dropped_events} earlier events were dropped to stay within the session log limit._\n\n"
+        ));
+    }
+
+    for event in events {
+        let time = event.timestamp.format("%Y-%m-%d %H:%M:%S");
+        match event.kind.as_str() {
+            "status" => {
+                let status = event.status.as_deref().unwrap_or("status");
+                match &event.detail {
+                    Some(detail) => out.push_str(&format!("_[{time}] {status} · {detail}_\n\n")),
+                    None => out.push_str(&format!("_[{time}] {status}_\n\n")),
+                }
+            }
+            _ => {
+                let role = event.role.as_deref().unwrap_or("assistant");
+                let text = event.text.as_deref().unwrap_or_default().replace('\r', "");
+                out.push_str(&format!(
+                    "**{role}** [{time}]\n\n```\n{}\n```\n\n",
+                    text.trim_end()
+                ));
+            }
+        }
+
+        for annotation in annotations.iter().filter(|a| a.sequence == event.sequence) {
+            let note = annotation.note.as_deref().unwrap_or("Bookmarked");
+            out.push_str(&format!("> 🔖 {note} — {}\n\n", annotation.author));
+        }
+    }
+
+    out
+}
+
 static SESSION_REGISTRY: Lazy<RwLock<HashMap<String, Arc<SessionRuntime>>>> =
     Lazy::new(|| RwLock::new(HashMap::new()));
+static TASK_SESSION_INDEX: Lazy<RwLock<HashMap<String, String>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
 static WORKTREE_SESSION_INDEX: Lazy<RwLock<HashMap<String, String>>> =
     Lazy::new(|| RwLock::new(HashMap::new()));
+static DASHBOARD_EVENTS: Lazy<broadcast::Sender<DashboardEvent>> =
+    Lazy::new(|| broadcast::channel(256).0);
 
 fn summarize_git(path: &Path) -> GitStatusSummary {
     if !path.exists() {
@@ -860,9 +3943,55 @@ fn summarize_git(path: &Path) -> GitStatusSummary {
         summary.last_commit_time = Some(commit.timestamp);
     }
 
+    if let Some(upstream) = read_git_line(path, &["rev-parse", "--abbrev-ref", "@{upstream}"]) {
+        summary.has_upstream = true;
+        if let Some((ahead, behind)) = count_ahead_behind(path, &upstream) {
+            summary.ahead_upstream = Some(ahead);
+            summary.behind_upstream = Some(behind);
+        }
+        summary.upstream = Some(upstream);
+    }
+
+    if let Some(base_ref) = resolve_base_ref(path) {
+        if let Some((ahead, behind)) = count_ahead_behind(path, &base_ref) {
+            summary.ahead_base = Some(ahead);
+            summary.behind_base = Some(behind);
+        }
+        summary.base_ref = Some(base_ref);
+    }
+
     summary
 }
 
+/// Count commits on HEAD but not `other` (ahead) and on `other` but not HEAD
+/// (behind).
+fn count_ahead_behind(path: &Path, other: &str) -> Option<(usize, usize)> {
+    let range = format!("HEAD...{other}");
+    read_git_line(path, &["rev-list", "--left-right", "--count", &range])
+        .and_then(|line| parse_left_right_count(&line))
+}
+
+fn parse_left_right_count(line: &str) -> Option<(usize, usize)> {
+    let mut counts = line.split_whitespace().map(str::parse::<usize>);
+    match (counts.next(), counts.next()) {
+        (Some(Ok(left)), Some(Ok(right))) => Some((left, right)),
+        _ => None,
+    }
+}
+
+fn read_git_line(path: &Path, args: &[&str]) -> Option<String> {
+    let output = StdCommand::new("git")
+        .current_dir(path)
+        .args(args)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let line = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!line.is_empty()).then_some(line)
+}
+
 fn apply_status_line(line: &str, summary: &mut GitStatusSummary) {
     if line.starts_with("??") {
         summary.untracked_files += 1;
@@ -924,9 +4053,10 @@ fn handle_worktree_action(
     repo: &str,
     name: &str,
     action: &str,
+    user: &DashboardUser,
 ) -> Result<ActionResponse, (StatusCode, String)> {
     let state = PigsState::load_with_local_overrides().map_err(|err| {
-        eprintln!("[dashboard] failed to load state: {err:?}");
+        error!(error = ?err, "failed to load state");
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             "Failed to load state".to_string(),
@@ -944,7 +4074,7 @@ fn handle_worktree_action(
     let editor_override = state.editor.clone();
     let shell_override = state.shell.clone();
 
-    match action {
+    let result = match action {
         "open_agent" => launch_agent(&info).map(|_| ActionResponse {
             message: format!("Launching agent for {}/{}", info.repo_name, info.name),
         }),
@@ -958,28 +4088,147 @@ fn handle_worktree_action(
             StatusCode::BAD_REQUEST,
             format!("Unsupported action '{other}'"),
         )),
+    };
+
+    if result.is_ok() {
+        activity::record_as(&user.0, &info.repo_name, &info.name, action, None);
     }
+    result
+}
+
+/// The requested timeline window, kept between an hour and a year so the
+/// cutoff can always be computed.
+fn timeline_hours(requested: Option<i64>) -> i64 {
+    requested
+        .unwrap_or(DEFAULT_TIMELINE_HOURS)
+        .clamp(1, MAX_TIMELINE_HOURS)
+}
+
+fn build_timeline(
+    repo: &str,
+    name: &str,
+    hours: i64,
+) -> Result<Vec<TimelineEntry>, (StatusCode, String)> {
+    let state = PigsState::load().map_err(|err| {
+        error!(error = ?err, "failed to load state");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to load state".to_string(),
+        )
+    })?;
+
+    let key = PigsState::make_key(repo, name);
+    let info = state.worktrees.get(&key).cloned().ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            format!("Worktree '{repo}/{name}' not found"),
+        )
+    })?;
+
+    Ok(worktree_timeline(
+        &info,
+        Utc::now() - ChronoDuration::hours(hours),
+    ))
 }
 
-fn editor_command(override_cmd: Option<String>) -> String {
-    override_cmd
-        .filter(|s| !s.trim().is_empty())
-        .or_else(|| std::env::var("PIGS_DASHBOARD_EDITOR").ok())
-        .or_else(|| std::env::var("EDITOR").ok())
-        .unwrap_or_else(|| "code".to_string())
+/// Merge commits, agent session starts, and recorded pigs actions for one
+/// worktree since `since` into a single feed, newest first.
+fn worktree_timeline(info: &WorktreeInfo, since: DateTime<Utc>) -> Vec<TimelineEntry> {
+    let mut entries = Vec::new();
+
+    if info.created_at >= since {
+        entries.push(TimelineEntry {
+            timestamp: info.created_at,
+            kind: "created".to_string(),
+            summary: format!("Worktree created on branch {}", info.branch),
+            detail: None,
+        });
+    }
+
+    entries.extend(read_commits_since(&info.path, since));
+
+    for session in claude::get_claude_sessions(&info.path) {
+        if let Some(started) = session.first_timestamp
+            && started >= since
+        {
+            entries.push(TimelineEntry {
+                timestamp: started,
+                kind: "claude-session".to_string(),
+                summary: "Claude session started".to_string(),
+                detail: Some(session.last_user_message),
+            });
+        }
+    }
+
+    match codex::recent_sessions(&info.path, usize::MAX) {
+        Ok((sessions, _)) => {
+            for session in sessions {
+                if let Some(started) = session.started_at
+                    && started >= since
+                {
+                    entries.push(TimelineEntry {
+                        timestamp: started,
+                        kind: "codex-session".to_string(),
+                        summary: "Codex session started".to_string(),
+                        detail: session.last_user_message,
+                    });
+                }
+            }
+        }
+        Err(err) => error!(error = ?err, "failed to read Codex sessions"),
+    }
+
+    match activity::read_for_worktree(&info.repo_name, &info.name, since) {
+        Ok(records) => entries.extend(records.into_iter().map(|record| TimelineEntry {
+            timestamp: record.timestamp,
+            kind: "action".to_string(),
+            summary: match &record.user {
+                Some(user) => format!("pigs {} by {user}", record.action),
+                None => format!("pigs {}", record.action),
+            },
+            detail: record.detail,
+        })),
+        Err(err) => error!(error = ?err, "failed to read activity log"),
+    }
+
+    entries.sort_by_key(|entry| std::cmp::Reverse(entry.timestamp));
+    entries
 }
 
-fn shell_command(override_cmd: Option<String>) -> String {
-    override_cmd
-        .filter(|s| !s.trim().is_empty())
-        .or_else(|| std::env::var("PIGS_DASHBOARD_SHELL").ok())
-        .or_else(|| std::env::var("SHELL").ok())
-        .unwrap_or_else(|| "/bin/zsh".to_string())
+fn read_commits_since(path: &Path, since: DateTime<Utc>) -> Vec<TimelineEntry> {
+    let since_arg = format!("--since={}", since.to_rfc3339());
+    let output = match StdCommand::new("git")
+        .current_dir(path)
+        .args(["log", &since_arg, "--pretty=format:%h%x1f%cI%x1f%s%x1f%an"])
+        .output()
+    {
+        Ok(output) if output.status.success() => output,
+        _ => return Vec::new(),
+    };
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.split('\u{1f}');
+            let hash = parts.next()?.trim();
+            let timestamp = DateTime::parse_from_rfc3339(parts.next()?.trim())
+                .map(|dt| dt.with_timezone(&Utc))
+                .ok()?;
+            let subject = parts.next()?.trim();
+            let author = parts.next().unwrap_or_default().trim();
+            Some(TimelineEntry {
+                timestamp,
+                kind: "commit".to_string(),
+                summary: subject.to_string(),
+                detail: Some(format!("{hash} by {author}")),
+            })
+        })
+        .collect()
 }
 
 fn launch_agent(info: &WorktreeInfo) -> Result<(), (StatusCode, String)> {
     let exe = std::env::current_exe().map_err(|err| {
-        eprintln!("[dashboard] failed to locate binary: {err:?}");
+        error!(error = ?err, "failed to locate binary");
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             "Failed to locate pigs binary".to_string(),
@@ -995,7 +4244,7 @@ fn launch_agent(info: &WorktreeInfo) -> Result<(), (StatusCode, String)> {
         .spawn()
         .map(|_| ())
         .map_err(|err| {
-            eprintln!("[dashboard] failed to launch agent: {err:?}");
+            error!(error = ?err, "failed to launch agent");
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 "Failed to launch agent".to_string(),
@@ -1007,30 +4256,15 @@ fn launch_shell(
     info: &WorktreeInfo,
     shell_override: Option<String>,
 ) -> Result<(), (StatusCode, String)> {
-    let command = shell_command(shell_override);
-    let mut parts = shell_split(&command).map_err(|err| {
-        eprintln!("[dashboard] failed to parse shell command: {err:?}");
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            "Failed to parse shell command".to_string(),
-        )
+    let mut cmd = launch::shell(info, shell_override).map_err(|err| {
+        error!(error = ?err, "failed to build shell command");
+        (StatusCode::INTERNAL_SERVER_ERROR, err.to_string())
     })?;
-    if parts.is_empty() {
-        return Err((
-            StatusCode::INTERNAL_SERVER_ERROR,
-            "Shell command is empty".to_string(),
-        ));
-    }
-
-    let program = parts.remove(0);
-    let mut cmd = StdCommand::new(program);
-    cmd.args(parts);
-    cmd.current_dir(&info.path);
     cmd.stdin(Stdio::null());
     cmd.stdout(Stdio::null());
     cmd.stderr(Stdio::null());
     cmd.spawn().map(|_| ()).map_err(|err| {
-        eprintln!("[dashboard] failed to open shell: {err:?}");
+        error!(error = ?err, "failed to open shell");
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             "Failed to open shell".to_string(),
@@ -1039,30 +4273,15 @@ fn launch_shell(
 }
 
 fn launch_editor(path: &Path, editor_override: Option<String>) -> Result<(), (StatusCode, String)> {
-    let command = editor_command(editor_override);
-    let mut parts = shell_split(&command).map_err(|err| {
-        eprintln!("[dashboard] failed to parse editor command: {err:?}");
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            "Failed to parse editor command".to_string(),
-        )
+    let mut cmd = launch::editor(path, editor_override).map_err(|err| {
+        error!(error = ?err, "failed to build editor command");
+        (StatusCode::INTERNAL_SERVER_ERROR, err.to_string())
     })?;
-    if parts.is_empty() {
-        return Err((
-            StatusCode::INTERNAL_SERVER_ERROR,
-            "Editor command is empty".to_string(),
-        ));
-    }
-
-    let program = parts.remove(0);
-    let mut cmd = StdCommand::new(program);
-    cmd.args(parts);
-    cmd.arg(path);
     cmd.stdin(Stdio::null());
     cmd.stdout(Stdio::null());
     cmd.stderr(Stdio::null());
     cmd.spawn().map_err(|err| {
-        eprintln!("[dashboard] failed to spawn editor: {err:?}");
+        error!(error = ?err, "failed to spawn editor");
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             "Failed to open editor".to_string(),
@@ -1071,20 +4290,25 @@ fn launch_editor(path: &Path, editor_override: Option<String>) -> Result<(), (St
     Ok(())
 }
 async fn schedule_session_cleanup(id: String) {
-    let retention = Duration::from_secs(SESSION_RETENTION_SECS);
+    let retention = Duration::from_secs(
+        std::env::var("PIGS_SESSION_RETENTION_SECS")
+            .ok()
+            .and_then(|value| value.trim().parse().ok())
+            .unwrap_or(SESSION_RETENTION_SECS),
+    );
     tokio::spawn(async move {
         tokio::time::sleep(retention).await;
         SESSION_REGISTRY.write().await.remove(&id);
     });
 }
 
-fn scrub_terminal_queries(chunk: &[u8]) -> (Vec<u8>, Vec<Vec<u8>>) {
+pub(crate) fn scrub_terminal_queries(chunk: &[u8], size: TerminalSize) -> (Vec<u8>, Vec<Vec<u8>>) {
     let mut cleaned = Vec::with_capacity(chunk.len());
     let mut responses = Vec::new();
     let mut index = 0;
     while index < chunk.len() {
         if chunk[index..].starts_with(CURSOR_POSITION_QUERY) {
-            responses.push(cursor_position_response());
+            responses.push(cursor_position_response(size));
             index += CURSOR_POSITION_QUERY.len();
             continue;
         }
@@ -1094,6 +4318,672 @@ fn scrub_terminal_queries(chunk: &[u8]) -> (Vec<u8>, Vec<Vec<u8>>) {
     (cleaned, responses)
 }
 
-fn cursor_position_response() -> Vec<u8> {
-    format!("\x1b[{};{}R", PTY_ROWS, PTY_COLS).into_bytes()
+fn cursor_position_response(size: TerminalSize) -> Vec<u8> {
+    format!("\x1b[{};{}R", size.rows, size.cols).into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::DashboardToken;
+
+    #[test]
+    fn transcript_places_annotations_after_their_event() {
+        let events = vec![
+            SessionEvent::status(0, "running", None),
+            SessionEvent::message(1, "assistant", "stdout", "hello\r\n".to_string()),
+            SessionEvent::message(2, "user", "stdin", "next".to_string()),
+        ];
+        let annotations = vec![Annotation {
+            id: "a".to_string(),
+            sequence: 1,
+            note: Some("interesting".to_string()),
+            author: "alice".to_string(),
+            created_at: Utc::now(),
+        }];
+
+        let transcript = render_transcript("abc", "repo/wt", &events, 0, &annotations);
+
+        let hello = transcript.find("hello").unwrap();
+        let note = transcript.find("> 🔖 interesting — alice").unwrap();
+        let next = transcript.find("next").unwrap();
+        assert!(hello < note && note < next);
+        assert!(transcript.starts_with("# Session abc"));
+    }
+
+    #[test]
+    fn plain_events_are_stripped_lines() {
+        let mut plain = PlainEvents::default();
+        let raw = [
+            SessionEvent::message(0, "assistant", "stdout", "\x1b[32mbuil".to_string()),
+            SessionEvent::message(
+                1,
+                "assistant",
+                "stdout",
+                "ding\x1b[0m\r\ndone\r\n> ".to_string(),
+            ),
+            SessionEvent::message(2, "user", "stdin", "go".to_string()),
+            SessionEvent::status(3, "stopped", None),
+        ];
+        let events: Vec<SessionEvent> = raw.iter().flat_map(|event| plain.push(event)).collect();
+        let texts: Vec<(u64, Option<&str>)> = events
+            .iter()
+            .map(|event| (event.sequence, event.text.as_deref()))
+            .collect();
+        assert_eq!(
+            texts,
+            vec![
+                (1, Some("building")),
+                (1, Some("done")),
+                (2, Some("go")),
+                (1, Some("> ")),
+                (3, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn session_log_drops_oldest_events_past_its_limits() {
+        let mut log = SessionLog::new(SessionLogLimits {
+            max_events: 3,
+            max_bytes: usize::MAX,
+        });
+        for sequence in 0..5 {
+            log.push(SessionEvent::message(
+                sequence,
+                "assistant",
+                "stdout",
+                "x".to_string(),
+            ));
+        }
+        let sequences: Vec<u64> = log.events.iter().map(|event| event.sequence).collect();
+        assert_eq!(sequences, [2, 3, 4]);
+        assert_eq!(log.dropped, 2);
+
+        let mut log = SessionLog::new(SessionLogLimits {
+            max_events: usize::MAX,
+            max_bytes: 1,
+        });
+        log.push(SessionEvent::message(
+            0,
+            "assistant",
+            "stdout",
+            "a".repeat(64),
+        ));
+        log.push(SessionEvent::message(
+            1,
+            "assistant",
+            "stdout",
+            "b".repeat(64),
+        ));
+        assert_eq!(log.events.len(), 1);
+        assert_eq!(log.events[0].sequence, 1);
+        assert_eq!(log.bytes, log.events[0].size());
+        assert_eq!(log.dropped, 1);
+    }
+
+    #[test]
+    fn repo_summaries_aggregate_worktrees_per_repo() {
+        let now = Utc::now();
+        let worktree = |repo: &str, name: &str, dirty: usize, hours_ago: i64| WorktreeSummary {
+            key: format!("{repo}/{name}"),
+            repo_name: repo.to_string(),
+            name: name.to_string(),
+            branch: name.to_string(),
+            path: format!("/tmp/{repo}-{name}"),
+            created_at: now,
+            last_activity: now - chrono::Duration::hours(hours_ago),
+            git_status: GitStatusSummary {
+                unstaged_files: dirty,
+                untracked_files: 1,
+                ..Default::default()
+            },
+            labels: Vec::new(),
+            stack_parent: None,
+            stack_depth: 0,
+            tasks: Vec::new(),
+            problems: Vec::new(),
+            reminder: None,
+            note: None,
+            tags: Vec::new(),
+            last_agent: None,
+            sessions: Vec::new(),
+            session_error: None,
+            pull_request: None,
+        };
+        let worktrees = vec![
+            worktree("api", "a", 2, 5),
+            worktree("api", "b", 0, 1),
+            worktree("web", "c", 3, 2),
+        ];
+
+        let repos = summarize_repos(&worktrees, &["api/b".to_string()]);
+
+        assert_eq!(repos.len(), 2);
+        assert_eq!(repos[0].repo_name, "api");
+        assert_eq!(repos[0].worktree_count, 2);
+        assert_eq!(repos[0].dirty_files, 4);
+        assert_eq!(repos[0].last_activity, Some(worktrees[1].last_activity));
+        assert_eq!(repos[0].running_sessions, 1);
+        assert_eq!(repos[1].repo_name, "web");
+        assert_eq!(repos[1].dirty_files, 4);
+        assert_eq!(repos[1].running_sessions, 0);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn tasks_stream_output_and_report_their_exit_status() {
+        let dir = tempfile::tempdir().unwrap();
+        let info = WorktreeInfo {
+            name: "wt".to_string(),
+            branch: "wt".to_string(),
+            path: dir.path().to_path_buf(),
+            repo_name: "repo".to_string(),
+            created_at: Utc::now(),
+            base_branch: None,
+            stack: None,
+            reminder: None,
+            container: None,
+            artifacts: Vec::new(),
+            note: None,
+            tags: Vec::new(),
+            agent_history: Vec::new(),
+            repo_root: None,
+        };
+
+        let runtime = spawn_task(
+            info,
+            "check".to_string(),
+            "echo task-output; exit 3".to_string(),
+            TerminalSize::default(),
+            "tester".to_string(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(runtime.task(), Some("check"));
+
+        let deadline = Instant::now() + Duration::from_secs(10);
+        loop {
+            let events = runtime.snapshot().await;
+            let output = events.iter().any(|event| {
+                event
+                    .text
+                    .as_deref()
+                    .is_some_and(|text| text.contains("task-output"))
+            });
+            let stopped = events
+                .iter()
+                .find(|event| event.status.as_deref() == Some("stopped"));
+            if output && let Some(stopped) = stopped {
+                assert_eq!(
+                    stopped.detail.as_deref(),
+                    Some("check: exit code 3 (failed)")
+                );
+                break;
+            }
+            assert!(Instant::now() < deadline, "task never finished");
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn deleting_a_worktree_terminates_its_sessions() {
+        let dir = tempfile::tempdir().unwrap();
+        let info = WorktreeInfo {
+            name: "doomed".to_string(),
+            branch: "doomed".to_string(),
+            path: dir.path().to_path_buf(),
+            repo_name: "terminate-repo".to_string(),
+            created_at: Utc::now(),
+            base_branch: None,
+            stack: None,
+            reminder: None,
+            container: None,
+            artifacts: Vec::new(),
+            note: None,
+            tags: Vec::new(),
+            agent_history: Vec::new(),
+            repo_root: None,
+        };
+        let key = PigsState::make_key(&info.repo_name, &info.name);
+
+        let runtime = spawn_task(
+            info,
+            "serve".to_string(),
+            "sleep 30".to_string(),
+            TerminalSize::default(),
+            "tester".to_string(),
+        )
+        .await
+        .unwrap();
+        let id = runtime.id().to_string();
+        TASK_SESSION_INDEX
+            .write()
+            .await
+            .insert(task_key(&key, "serve"), id.clone());
+        SESSION_REGISTRY
+            .write()
+            .await
+            .insert(id.clone(), runtime.clone());
+
+        assert_eq!(
+            terminate_worktree_sessions(&key, "worktree deleted").await,
+            1
+        );
+        assert!(!SESSION_REGISTRY.read().await.contains_key(&id));
+        assert!(
+            !TASK_SESSION_INDEX
+                .read()
+                .await
+                .contains_key(&task_key(&key, "serve"))
+        );
+
+        let deadline = Instant::now() + Duration::from_secs(10);
+        loop {
+            let events = runtime.snapshot().await;
+            let statuses: Vec<&str> = events
+                .iter()
+                .filter_map(|event| event.status.as_deref())
+                .collect();
+            if statuses.contains(&"stopped") {
+                let terminated = events
+                    .iter()
+                    .find(|event| event.status.as_deref() == Some("terminated"))
+                    .expect("terminated status before stop");
+                assert_eq!(terminated.detail.as_deref(), Some("worktree deleted"));
+                break;
+            }
+            assert!(Instant::now() < deadline, "session was not killed");
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+    }
+
+    #[test]
+    fn timeline_hours_stay_within_a_year() {
+        assert_eq!(timeline_hours(None), DEFAULT_TIMELINE_HOURS);
+        assert_eq!(timeline_hours(Some(0)), 1);
+        assert_eq!(timeline_hours(Some(-5)), 1);
+        assert_eq!(timeline_hours(Some(i64::MAX)), MAX_TIMELINE_HOURS);
+        // The widest window still yields a valid cutoff
+        let _ = Utc::now() - ChronoDuration::hours(timeline_hours(Some(i64::MAX)));
+    }
+
+    #[test]
+    fn timeline_merges_commits_and_actions_newest_first() {
+        let config = tempfile::tempdir().unwrap();
+        let repo = tempfile::tempdir().unwrap();
+        let git = |args: &[&str]| {
+            let status = StdCommand::new("git")
+                .args(args)
+                .current_dir(repo.path())
+                .output()
+                .unwrap()
+                .status;
+            assert!(status.success(), "git {args:?} failed");
+        };
+        git(&["init", "-q", "-b", "main"]);
+        git(&[
+            "-c",
+            "user.name=Dev",
+            "-c",
+            "user.email=dev@example.com",
+            "commit",
+            "-q",
+            "--allow-empty",
+            "-m",
+            "Add feature",
+        ]);
+
+        let info = WorktreeInfo {
+            name: "wt".to_string(),
+            branch: "wt".to_string(),
+            path: repo.path().to_path_buf(),
+            repo_name: "repo".to_string(),
+            created_at: Utc::now() - ChronoDuration::hours(48),
+            base_branch: None,
+            stack: None,
+            reminder: None,
+            container: None,
+            artifacts: Vec::new(),
+            note: None,
+            tags: Vec::new(),
+            agent_history: Vec::new(),
+            repo_root: None,
+        };
+
+        temp_env::with_var("PIGS_CONFIG_DIR", Some(config.path()), || {
+            std::thread::sleep(std::time::Duration::from_millis(1100));
+            activity::record_as("alice", "repo", "wt", "open_shell", None);
+            activity::record_as("bob", "repo", "other", "open_shell", None);
+
+            let entries = worktree_timeline(&info, Utc::now() - ChronoDuration::hours(24));
+            let kinds: Vec<&str> = entries.iter().map(|entry| entry.kind.as_str()).collect();
+            // Created two days ago, so outside the window
+            assert_eq!(kinds, ["action", "commit"]);
+            assert_eq!(entries[0].summary, "pigs open_shell by alice");
+            assert_eq!(entries[1].summary, "Add feature");
+            assert!(entries[1].detail.as_deref().unwrap().ends_with("by Dev"));
+
+            let recent = worktree_timeline(&info, Utc::now() + ChronoDuration::hours(1));
+            assert!(recent.is_empty());
+        });
+    }
+
+    #[test]
+    fn readiness_fails_when_state_is_unreadable() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("settings.json"), "{ not json").unwrap();
+        let config_dir = dir.path().to_str().unwrap();
+
+        temp_env::with_var("PIGS_CONFIG_DIR", Some(config_dir), || {
+            let checks = readiness_checks();
+            let state = checks.iter().find(|check| check.name == "state").unwrap();
+            assert!(!state.ok);
+            assert!(state.error.as_deref().unwrap().contains("parse"));
+            assert!(checks.iter().find(|check| check.name == "git").unwrap().ok);
+        });
+    }
+
+    #[test]
+    fn agent_settings_are_trimmed_and_must_be_unique() {
+        let agent = |name: &str, command: &str| AgentOption {
+            name: name.to_string(),
+            command: command.to_string(),
+        };
+
+        let agents = normalize_agents(vec![agent(" codex ", " codex --full-auto ")]).unwrap();
+        assert_eq!(agents[0].name, "codex");
+        assert_eq!(agents[0].command, "codex --full-auto");
+
+        assert!(normalize_agents(vec![agent("claude", "  ")]).is_err());
+        assert!(normalize_agents(vec![agent("a", "x"), agent("a ", "y")]).is_err());
+    }
+
+    #[test]
+    fn left_right_counts_parse_as_ahead_behind() {
+        assert_eq!(parse_left_right_count("3\t1"), Some((3, 1)));
+        assert_eq!(parse_left_right_count("0\t0"), Some((0, 0)));
+        assert_eq!(parse_left_right_count("garbage"), None);
+    }
+
+    #[test]
+    fn dashboard_user_prefers_proxy_header_over_local_user() {
+        let config = tempfile::tempdir().unwrap();
+        temp_env::with_var("PIGS_CONFIG_DIR", Some(config.path()), || {
+            let mut headers = HeaderMap::new();
+            headers.insert(USER_HEADER, "  bob ".parse().unwrap());
+            assert_eq!(resolve_dashboard_user(&headers, None, true), "bob");
+
+            headers.insert(USER_HEADER, "   ".parse().unwrap());
+            assert_eq!(
+                resolve_dashboard_user(&headers, None, true),
+                activity::local_user()
+            );
+        });
+    }
+
+    #[test]
+    fn dashboard_user_ignores_proxy_header_unless_loopback_without_tokens() {
+        let config = tempfile::tempdir().unwrap();
+        temp_env::with_var("PIGS_CONFIG_DIR", Some(config.path()), || {
+            let mut headers = HeaderMap::new();
+            headers.insert(USER_HEADER, "mallory".parse().unwrap());
+            // Reachable from other hosts, so anyone could set the header
+            assert_eq!(
+                resolve_dashboard_user(&headers, None, false),
+                activity::local_user()
+            );
+
+            let mut state = PigsState::load().unwrap();
+            state
+                .dashboard_users
+                .insert("s3cr3t".to_string(), "alice".to_string());
+            state.save().unwrap();
+            assert_eq!(
+                resolve_dashboard_user(&headers, None, true),
+                activity::local_user()
+            );
+            headers.insert(AUTHORIZATION, "Bearer s3cr3t".parse().unwrap());
+            assert_eq!(resolve_dashboard_user(&headers, None, true), "alice");
+        });
+    }
+
+    #[test]
+    fn request_token_reads_bearer_header_then_query() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(
+            request_token(&headers, Some("a=1&token=abc")),
+            Some("abc".to_string())
+        );
+        assert_eq!(request_token(&headers, Some("token=")), None);
+        headers.insert(AUTHORIZATION, "Bearer xyz".parse().unwrap());
+        assert_eq!(
+            request_token(&headers, Some("token=abc")),
+            Some("xyz".to_string())
+        );
+    }
+
+    #[test]
+    fn token_scopes_are_checked_per_route_group() {
+        let mut state = PigsState::default();
+        let post = Method::POST;
+        // No scoped tokens yet: nothing is checked
+        assert!(authorize(&state, None, &post, "/api/settings").is_ok());
+
+        for (token, scope) in [
+            ("wallboard", TokenScope::Read),
+            ("pair", TokenScope::Sessions),
+        ] {
+            state.dashboard_tokens.insert(
+                token.to_string(),
+                DashboardToken {
+                    scope,
+                    user: None,
+                    created_at: Utc::now(),
+                },
+            );
+        }
+        state
+            .dashboard_users
+            .insert("legacy".to_string(), "alice".to_string());
+        let status = |token, method: &Method, path| {
+            authorize(&state, token, method, path)
+                .err()
+                .map(|(status, _)| status)
+        };
+
+        assert_eq!(
+            status(None, &Method::GET, "/api/worktrees"),
+            Some(StatusCode::UNAUTHORIZED)
+        );
+        assert_eq!(
+            status(Some("guess"), &Method::GET, "/api/worktrees"),
+            Some(StatusCode::UNAUTHORIZED)
+        );
+        assert_eq!(
+            status(Some("wallboard"), &Method::GET, "/api/sessions/a/stream"),
+            None
+        );
+        assert_eq!(
+            status(Some("wallboard"), &post, "/api/worktrees/repo/wt/actions"),
+            Some(StatusCode::FORBIDDEN)
+        );
+        assert_eq!(
+            status(Some("wallboard"), &post, "/api/sessions/a/send"),
+            Some(StatusCode::FORBIDDEN)
+        );
+        assert_eq!(status(Some("pair"), &post, "/api/sessions/a/send"), None);
+        assert_eq!(
+            status(Some("pair"), &post, "/api/worktrees/repo/wt/live-session"),
+            None
+        );
+        assert_eq!(
+            status(Some("pair"), &post, "/api/worktrees/repo/wt/actions"),
+            Some(StatusCode::FORBIDDEN)
+        );
+        assert_eq!(
+            status(Some("legacy"), &Method::DELETE, "/api/worktrees/repo/wt"),
+            None
+        );
+    }
+
+    #[test]
+    fn dashboard_users_alone_require_a_token() {
+        let mut state = PigsState::default();
+        state
+            .dashboard_users
+            .insert("s3cr3t".to_string(), "alice".to_string());
+        let status = |token, method: &Method, path| {
+            authorize(&state, token, method, path)
+                .err()
+                .map(|(status, _)| status)
+        };
+
+        assert_eq!(
+            status(None, &Method::GET, "/api/worktrees"),
+            Some(StatusCode::UNAUTHORIZED)
+        );
+        assert_eq!(
+            status(Some("guess"), &Method::POST, "/api/settings"),
+            Some(StatusCode::UNAUTHORIZED)
+        );
+        assert_eq!(status(Some("s3cr3t"), &Method::POST, "/api/settings"), None);
+    }
+
+    #[test]
+    fn terminal_size_defaults_and_clamps_client_values() {
+        assert_eq!(TerminalSize::requested(None, None), TerminalSize::default());
+        assert_eq!(
+            TerminalSize::requested(Some(60), Some(240)),
+            TerminalSize {
+                rows: 60,
+                cols: 240
+            }
+        );
+        assert_eq!(
+            TerminalSize::requested(Some(0), Some(u16::MAX)),
+            TerminalSize {
+                rows: PTY_MIN_ROWS,
+                cols: PTY_MAX_COLS
+            }
+        );
+    }
+
+    #[test]
+    fn cursor_queries_are_answered_with_the_session_size() {
+        let size = TerminalSize::requested(Some(50), Some(200));
+        let (cleaned, responses) = scrub_terminal_queries(b"a\x1b[6nb", size);
+        assert_eq!(cleaned, b"ab");
+        assert_eq!(responses, vec![b"\x1b[50;200R".to_vec()]);
+    }
+
+    #[test]
+    fn rate_limiter_allows_a_burst_then_refills_per_ip() {
+        let limiter = RateLimiter::new(2.0, 1.0);
+        let client = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+        let other = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2));
+        let start = Instant::now();
+
+        assert!(limiter.acquire(client, start).is_ok());
+        assert!(limiter.acquire(client, start).is_ok());
+        let wait = limiter.acquire(client, start).unwrap_err();
+        assert_eq!(wait, Duration::from_secs(1));
+
+        assert!(limiter.acquire(other, start).is_ok());
+        assert!(
+            limiter
+                .acquire(client, start + Duration::from_secs(1))
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn rate_limiter_stays_at_its_cap_when_every_client_is_active() {
+        // Refills too slowly for any bucket to go idle during the test
+        let limiter = RateLimiter::new(2.0, 0.001);
+        let start = Instant::now();
+        let client = |n: usize| IpAddr::V4(Ipv4Addr::from(0x0a00_0000 + n as u32));
+        for n in 0..RATE_LIMIT_MAX_CLIENTS {
+            let now = start + Duration::from_millis(n as u64);
+            assert!(limiter.acquire(client(n), now).is_ok());
+        }
+        let buckets = || limiter.buckets.lock().unwrap().len();
+        assert_eq!(buckets(), RATE_LIMIT_MAX_CLIENTS);
+
+        let now = start + Duration::from_millis(RATE_LIMIT_MAX_CLIENTS as u64);
+        assert!(limiter.acquire(client(RATE_LIMIT_MAX_CLIENTS), now).is_ok());
+        assert_eq!(buckets(), RATE_LIMIT_MAX_CLIENTS);
+        // The client seen longest ago made room
+        let tracked = limiter.buckets.lock().unwrap();
+        assert!(!tracked.contains_key(&client(0)));
+        assert!(tracked.contains_key(&client(1)));
+        assert!(tracked.contains_key(&client(RATE_LIMIT_MAX_CLIENTS)));
+    }
+
+    #[test]
+    fn write_rate_limit_leaves_listing_worktrees_alone() {
+        let config = tempfile::tempdir().unwrap();
+        temp_env::with_var("PIGS_CONFIG_DIR", Some(config.path()), || {
+            let client = SocketAddr::from(([192, 0, 2, 1], 4000));
+            while WRITE_RATE_LIMITER
+                .acquire(client.ip(), Instant::now())
+                .is_ok()
+            {}
+
+            let runtime = tokio::runtime::Runtime::new().unwrap();
+            let status = |method: Method, path: &str| {
+                let mut request = Request::builder()
+                    .method(method)
+                    .uri(path)
+                    .body(Body::empty())
+                    .unwrap();
+                request.extensions_mut().insert(ConnectInfo(client));
+                let app = routes().with_state(DashboardConfig::default());
+                let Ok(response) = runtime.block_on(app.oneshot(request));
+                response.status()
+            };
+            for _ in 0..WRITE_RATE_BURST as usize * 2 {
+                assert_eq!(status(Method::GET, "/api/worktrees"), StatusCode::OK);
+            }
+            for path in [
+                "/api/worktrees",
+                "/api/sync",
+                "/api/clean",
+                "/api/worktrees/repo/name/actions",
+                "/api/worktrees/repo/name/archive",
+            ] {
+                assert_eq!(status(Method::POST, path), StatusCode::TOO_MANY_REQUESTS);
+            }
+        });
+    }
+
+    #[test]
+    fn asset_paths_cannot_escape_the_assets_dir() {
+        assert_eq!(
+            sanitize_asset_path("js/app.js"),
+            Some("js/app.js".to_string())
+        );
+        assert_eq!(
+            sanitize_asset_path("/./fonts//a.woff2"),
+            Some("fonts/a.woff2".to_string())
+        );
+        assert_eq!(sanitize_asset_path("../secret"), None);
+        assert_eq!(sanitize_asset_path("js/../../secret"), None);
+        assert_eq!(sanitize_asset_path("C:\\windows"), None);
+        assert_eq!(sanitize_asset_path(""), None);
+    }
+
+    #[test]
+    fn session_event_schema_lists_camel_case_fields() {
+        let schema = serde_json::to_value(schemars::schema_for!(SessionEvent)).unwrap();
+        let properties = schema["properties"].as_object().unwrap();
+        for field in ["sequence", "timestamp", "kind", "role", "channel", "text"] {
+            assert!(properties.contains_key(field), "missing {field}");
+        }
+        assert!(
+            schema["required"]
+                .as_array()
+                .unwrap()
+                .contains(&json!("sequence"))
+        );
+    }
 }
@@ -0,0 +1,268 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use serde_json::{Value, json};
+
+use crate::git::execute_git;
+
+/// Local files that are usually gitignored, so a fresh worktree lacks them.
+const LOCAL_FILE_CANDIDATES: &[&str] = &[
+    ".env",
+    ".env.local",
+    ".env.development.local",
+    ".claude/settings.local.json",
+    ".cursorrules",
+    "AGENTS.local.md",
+];
+
+/// Agent instructions and editor/dev-environment config worth mentioning.
+const AGENT_FILES: &[&str] = &[
+    "CLAUDE.md",
+    "AGENTS.md",
+    ".cursorrules",
+    ".cursor/rules",
+    ".github/copilot-instructions.md",
+    ".devcontainer/devcontainer.json",
+];
+
+/// Repo-level settings suggested for a repository that has none yet.
+#[derive(Debug, Default, PartialEq)]
+pub struct DetectedConfig {
+    /// Agent and tooling files found, for the summary shown to the user
+    pub found: Vec<String>,
+    pub copy_files: Vec<String>,
+    pub setup_commands: Vec<String>,
+    pub tasks: BTreeMap<String, String>,
+    /// Default agent when the repository targets something other than Claude
+    pub agent: Option<String>,
+}
+
+impl DetectedConfig {
+    pub fn is_empty(&self) -> bool {
+        self.copy_files.is_empty()
+            && self.setup_commands.is_empty()
+            && self.tasks.is_empty()
+            && self.agent.is_none()
+    }
+
+    /// One-line summary, e.g. "CLAUDE.md, copy .env, test: cargo test".
+    pub fn summary(&self) -> String {
+        let mut parts = self.found.clone();
+        parts.extend(self.copy_files.iter().map(|file| format!("copy {file}")));
+        parts.extend(
+            self.setup_commands
+                .iter()
+                .map(|cmd| format!("setup: {cmd}")),
+        );
+        parts.extend(
+            self.tasks
+                .iter()
+                .map(|(name, cmd)| format!("{name}: {cmd}")),
+        );
+        if let Some(agent) = &self.agent {
+            parts.push(format!("agent: {agent}"));
+        }
+        parts.join(", ")
+    }
+
+    /// Fill the keys `settings` doesn't set yet. Returns the keys added.
+    pub fn merge_into(&self, settings: &mut serde_json::Map<String, Value>) -> Vec<&'static str> {
+        let mut added = Vec::new();
+        let mut fill = |key: &'static str, value: Value| {
+            let unset = match settings.get(key) {
+                None | Some(Value::Null) => true,
+                Some(Value::Array(items)) => items.is_empty(),
+                Some(Value::Object(map)) => map.is_empty(),
+                Some(_) => false,
+            };
+            if unset {
+                settings.insert(key.to_string(), value);
+                added.push(key);
+            }
+        };
+        if !self.copy_files.is_empty() {
+            fill("copy_files", json!(self.copy_files));
+        }
+        if !self.setup_commands.is_empty() {
+            fill("setup_commands", json!(self.setup_commands));
+        }
+        if !self.tasks.is_empty() {
+            fill("tasks", json!(self.tasks));
+        }
+        if let Some(agent) = &self.agent {
+            fill("agent", json!([{ "name": agent, "command": agent }]));
+        }
+        added
+    }
+}
+
+/// Look through a repository checkout for agent instructions, gitignored
+/// local files, dependency installs, and test commands.
+pub fn detect(repo_root: &Path) -> DetectedConfig {
+    let exists = |rel: &str| repo_root.join(rel).exists();
+    let mut detected = DetectedConfig {
+        found: AGENT_FILES
+            .iter()
+            .filter(|file| exists(file))
+            .map(|file| file.to_string())
+            .collect(),
+        ..DetectedConfig::default()
+    };
+
+    detected.copy_files = LOCAL_FILE_CANDIDATES
+        .iter()
+        .filter(|file| exists(file) && is_ignored(repo_root, file))
+        .map(|file| file.to_string())
+        .collect();
+
+    if exists("AGENTS.md") && !exists("CLAUDE.md") && !exists(".claude") {
+        detected.agent = Some("codex".to_string());
+    }
+
+    let package_manager = if exists("pnpm-lock.yaml") {
+        Some("pnpm")
+    } else if exists("yarn.lock") {
+        Some("yarn")
+    } else if exists("bun.lockb") || exists("bun.lock") {
+        Some("bun")
+    } else if exists("package-lock.json") {
+        Some("npm")
+    } else {
+        None
+    };
+    if let Some(manager) = package_manager {
+        detected.setup_commands.push(
+            match manager {
+                "npm" => "npm ci",
+                "pnpm" => "pnpm install --frozen-lockfile",
+                "yarn" => "yarn install --frozen-lockfile",
+                _ => "bun install",
+            }
+            .to_string(),
+        );
+    }
+    if let Some(command) = devcontainer_post_create(repo_root)
+        && !detected.setup_commands.contains(&command)
+    {
+        detected.setup_commands.push(command);
+    }
+
+    if let Some(command) = test_command(repo_root, package_manager.unwrap_or("npm")) {
+        detected.tasks.insert("test".to_string(), command);
+    }
+
+    detected
+}
+
+fn test_command(repo_root: &Path, package_manager: &str) -> Option<String> {
+    let exists = |rel: &str| repo_root.join(rel).exists();
+    if exists("Cargo.toml") {
+        return Some("cargo test".to_string());
+    }
+    if let Ok(content) = fs::read_to_string(repo_root.join("package.json"))
+        && let Ok(package) = serde_json::from_str::<Value>(&content)
+        && package.pointer("/scripts/test").is_some()
+    {
+        return Some(format!("{package_manager} test"));
+    }
+    if exists("go.mod") {
+        return Some("go test ./...".to_string());
+    }
+    if exists("pytest.ini")
+        || exists("conftest.py")
+        || fs::read_to_string(repo_root.join("pyproject.toml"))
+            .is_ok_and(|content| content.contains("[tool.pytest"))
+    {
+        return Some("pytest".to_string());
+    }
+    if fs::read_to_string(repo_root.join("Makefile"))
+        .is_ok_and(|content| content.lines().any(|line| line.starts_with("test:")))
+    {
+        return Some("make test".to_string());
+    }
+    None
+}
+
+/// `postCreateCommand` from a devcontainer, when it is a single shell string.
+fn devcontainer_post_create(repo_root: &Path) -> Option<String> {
+    devcontainer_string(repo_root, "postCreateCommand")
+}
+
+/// The prebuilt `image` a devcontainer runs, e.g. a Codespaces prebuild.
+pub fn devcontainer_image(repo_root: &Path) -> Option<String> {
+    devcontainer_string(repo_root, "image")
+}
+
+fn devcontainer_string(repo_root: &Path, key: &str) -> Option<String> {
+    let content = fs::read_to_string(repo_root.join(".devcontainer/devcontainer.json")).ok()?;
+    // devcontainer.json allows comments; drop whole-line ones before parsing
+    let content: String = content
+        .lines()
+        .filter(|line| !line.trim_start().starts_with("//"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let config: Value = serde_json::from_str(&content).ok()?;
+    config
+        .get(key)?
+        .as_str()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(str::to_string)
+}
+
+fn is_ignored(repo_root: &Path, rel: &str) -> bool {
+    repo_root
+        .to_str()
+        .is_some_and(|root| execute_git(&["-C", root, "check-ignore", "-q", rel]).is_ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_agent_files_setup_and_test_commands() {
+        let repo = tempfile::tempdir().unwrap();
+        let root = repo.path();
+        fs::write(root.join("AGENTS.md"), "# Agents\n").unwrap();
+        fs::write(root.join("package-lock.json"), "{}").unwrap();
+        fs::write(
+            root.join("package.json"),
+            r#"{"scripts": {"test": "vitest run"}}"#,
+        )
+        .unwrap();
+        fs::create_dir(root.join(".devcontainer")).unwrap();
+        fs::write(
+            root.join(".devcontainer/devcontainer.json"),
+            "{\n  // set up tools\n  \"postCreateCommand\": \"make bootstrap\"\n}",
+        )
+        .unwrap();
+
+        let detected = detect(root);
+
+        assert_eq!(
+            detected.found,
+            ["AGENTS.md", ".devcontainer/devcontainer.json"]
+        );
+        assert_eq!(detected.setup_commands, ["npm ci", "make bootstrap"]);
+        assert_eq!(detected.tasks["test"], "npm test");
+        assert_eq!(detected.agent.as_deref(), Some("codex"));
+    }
+
+    #[test]
+    fn merge_keeps_existing_settings() {
+        let detected = DetectedConfig {
+            setup_commands: vec!["npm ci".to_string()],
+            tasks: BTreeMap::from([("test".to_string(), "npm test".to_string())]),
+            ..DetectedConfig::default()
+        };
+        let mut settings = json!({ "tasks": { "lint": "npm run lint" } })
+            .as_object()
+            .cloned()
+            .unwrap();
+
+        assert_eq!(detected.merge_into(&mut settings), ["setup_commands"]);
+        assert_eq!(settings["tasks"], json!({ "lint": "npm run lint" }));
+    }
+}
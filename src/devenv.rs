@@ -0,0 +1,125 @@
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::{Context, Result, bail};
+use colored::Colorize;
+use serde_json::Value;
+
+/// An environment change from a worktree's tool setup: `None` unsets the variable.
+pub type EnvChange = (String, Option<String>);
+
+/// Variables that direnv (`.envrc`) and mise (`.mise.toml`, `mise.toml`)
+/// would set when entering `worktree_path`, so processes launched by pigs
+/// get the same tool versions as a shell in the checkout. Tools that aren't
+/// installed are skipped; failures (e.g. an `.envrc` that isn't allowed yet)
+/// print a warning and contribute nothing. `PIGS_NO_DEVENV` turns this off.
+pub fn activation_env(worktree_path: &Path) -> Vec<EnvChange> {
+    if std::env::var_os("PIGS_NO_DEVENV").is_some() {
+        return Vec::new();
+    }
+
+    let mut changes = Vec::new();
+    if worktree_path.join(".envrc").exists() {
+        match tool_env(worktree_path, "direnv", &["export", "json"]) {
+            Ok(env) => changes.extend(env),
+            Err(err) => warn(worktree_path, ".envrc", "direnv allow", &err),
+        }
+    }
+    if [".mise.toml", "mise.toml"]
+        .iter()
+        .any(|file| worktree_path.join(file).exists())
+    {
+        match tool_env(worktree_path, "mise", &["env", "--json"]) {
+            Ok(env) => changes.extend(env),
+            Err(err) => warn(worktree_path, "mise config", "mise trust", &err),
+        }
+    }
+    changes
+}
+
+/// Apply `activation_env` to a command that runs in `worktree_path`.
+pub fn apply(cmd: &mut Command, worktree_path: &Path) {
+    apply_changes(cmd, &activation_env(worktree_path));
+}
+
+pub fn apply_changes(cmd: &mut Command, changes: &[EnvChange]) {
+    for (key, value) in changes {
+        match value {
+            Some(value) => cmd.env(key, value),
+            None => cmd.env_remove(key),
+        };
+    }
+}
+
+fn tool_env(worktree_path: &Path, program: &str, args: &[&str]) -> Result<Vec<EnvChange>> {
+    let output = match Command::new(program)
+        .args(args)
+        .current_dir(worktree_path)
+        .output()
+    {
+        Ok(output) => output,
+        // Not installed: nothing to load
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err).with_context(|| format!("Failed to run {program}")),
+    };
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !output.status.success() || stderr.contains("is blocked") {
+        bail!("{program} {}: {}", args.join(" "), stderr.trim());
+    }
+    parse_env_json(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Parse the `{"NAME": "value" | null}` object printed by `direnv export json`
+/// and `mise env --json`. direnv prints nothing when there is no change.
+fn parse_env_json(output: &str) -> Result<Vec<EnvChange>> {
+    if output.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    let object: serde_json::Map<String, Value> =
+        serde_json::from_str(output).context("Unexpected environment output")?;
+    Ok(object
+        .into_iter()
+        .map(|(key, value)| {
+            let value = match value {
+                Value::Null => None,
+                Value::String(text) => Some(text),
+                other => Some(other.to_string()),
+            };
+            (key, value)
+        })
+        .collect())
+}
+
+fn warn(worktree_path: &Path, source: &str, fix: &str, err: &anyhow::Error) {
+    eprintln!(
+        "{} Not loading {} for {}: {} (run {} there to enable it)",
+        "⚠️".yellow(),
+        source,
+        worktree_path.display(),
+        err.to_string().lines().next().unwrap_or_default(),
+        fix.cyan()
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn null_values_unset_variables() {
+        let changes =
+            parse_env_json(r#"{"PATH": "/mise/node/bin:/usr/bin", "OLD_VAR": null}"#).unwrap();
+
+        assert_eq!(
+            changes,
+            [
+                ("OLD_VAR".to_string(), None),
+                (
+                    "PATH".to_string(),
+                    Some("/mise/node/bin:/usr/bin".to_string())
+                ),
+            ]
+        );
+        assert!(parse_env_json("\n").unwrap().is_empty());
+    }
+}
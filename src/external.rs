@@ -0,0 +1,293 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use serde::Serialize;
+
+/// How long a key keeps being refreshed after it was last read. Nobody looking
+/// at the data means no API calls for it.
+const IDLE_AFTER: Duration = Duration::from_secs(10 * 60);
+
+/// Refresh policy for one external service.
+#[derive(Debug, Clone, Copy)]
+pub struct Source {
+    pub name: &'static str,
+    /// How often each key is refreshed while it's being read
+    pub refresh: Duration,
+    /// Longest wait before retrying a key that keeps failing
+    pub max_backoff: Duration,
+    /// Fetches allowed per minute across all keys, with bursts up to this many
+    pub per_minute: u32,
+}
+
+pub const LINEAR: Source = Source {
+    name: "linear",
+    refresh: Duration::from_secs(60),
+    max_backoff: Duration::from_secs(15 * 60),
+    per_minute: 4,
+};
+
+pub const PULL_REQUESTS: Source = Source {
+    name: "pull-requests",
+    refresh: Duration::from_secs(120),
+    max_backoff: Duration::from_secs(15 * 60),
+    per_minute: 20,
+};
+
+/// The last value fetched for a key, with how fresh it is.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Snapshot<T> {
+    /// `None` until the first successful fetch
+    pub value: Option<T>,
+    pub fetched_at: Option<DateTime<Utc>>,
+    /// Older than twice the refresh interval, or the last fetch failed
+    pub stale: bool,
+    /// Error from the last fetch, if it failed
+    pub error: Option<String>,
+}
+
+impl<T> Snapshot<T> {
+    /// The same snapshot with `f` applied to its value.
+    pub fn map<U>(self, f: impl FnOnce(T) -> Option<U>) -> Snapshot<U> {
+        Snapshot {
+            value: self.value.and_then(f),
+            fetched_at: self.fetched_at,
+            stale: self.stale,
+            error: self.error,
+        }
+    }
+}
+
+struct Entry<T> {
+    value: Option<T>,
+    fetched_at: Option<DateTime<Utc>>,
+    error: Option<String>,
+    failures: u32,
+    next_fetch: Instant,
+    last_read: Instant,
+}
+
+struct Budget {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// Values fetched from one source, keyed by what was fetched (e.g. a repo).
+/// Readers never fetch; `due` tells the scheduler which keys to refresh and
+/// `record` stores the outcome.
+pub struct Cache<T> {
+    source: Source,
+    entries: Mutex<HashMap<String, Entry<T>>>,
+    budget: Mutex<Budget>,
+}
+
+impl<T: Clone> Cache<T> {
+    pub fn new(source: Source) -> Self {
+        Self {
+            source,
+            entries: Mutex::new(HashMap::new()),
+            budget: Mutex::new(Budget {
+                tokens: f64::from(source.per_minute),
+                updated: Instant::now(),
+            }),
+        }
+    }
+
+    pub fn source(&self) -> Source {
+        self.source
+    }
+
+    /// The current value of `key`, marking it as wanted so the scheduler keeps
+    /// it fresh. A key read for the first time is due right away.
+    pub fn read(&self, key: &str, now: Instant) -> Snapshot<T> {
+        let mut entries = self.entries.lock().unwrap_or_else(|p| p.into_inner());
+        let entry = entries.entry(key.to_string()).or_insert(Entry {
+            value: None,
+            fetched_at: None,
+            error: None,
+            failures: 0,
+            next_fetch: now,
+            last_read: now,
+        });
+        entry.last_read = now;
+        let stale_after = chrono::Duration::from_std(self.source.refresh * 2).unwrap_or_default();
+        Snapshot {
+            value: entry.value.clone(),
+            fetched_at: entry.fetched_at,
+            stale: entry.error.is_some()
+                || entry
+                    .fetched_at
+                    .is_none_or(|at| Utc::now() - at > stale_after),
+            error: entry.error.clone(),
+        }
+    }
+
+    /// Claim the first fetch of a key that has never been fetched, so a reader
+    /// with nothing to show can fetch it inline. Returns false if the key was
+    /// already fetched or is being fetched, or the budget is spent.
+    pub fn claim_cold(&self, key: &str, now: Instant) -> bool {
+        let mut entries = self.entries.lock().unwrap_or_else(|p| p.into_inner());
+        let Some(entry) = entries.get_mut(key) else {
+            return false;
+        };
+        if entry.fetched_at.is_some() || entry.failures > 0 || entry.next_fetch > now {
+            return false;
+        }
+        if !self.take_budget(now, 1) {
+            return false;
+        }
+        entry.next_fetch = now + self.source.refresh;
+        true
+    }
+
+    /// Keys to fetch now: read recently and past their refresh or backoff time,
+    /// most overdue first, limited by the source's budget. Keys nobody read for
+    /// a while are dropped.
+    pub fn due(&self, now: Instant) -> Vec<String> {
+        let mut entries = self.entries.lock().unwrap_or_else(|p| p.into_inner());
+        entries.retain(|_, entry| now.saturating_duration_since(entry.last_read) < IDLE_AFTER);
+        let mut due: Vec<(Instant, String)> = entries
+            .iter()
+            .filter(|(_, entry)| entry.next_fetch <= now)
+            .map(|(key, entry)| (entry.next_fetch, key.clone()))
+            .collect();
+        due.sort();
+
+        while !due.is_empty() && !self.take_budget(now, due.len()) {
+            due.pop();
+        }
+
+        // Don't hand the same key out again while its fetch is in flight
+        for (_, key) in &due {
+            if let Some(entry) = entries.get_mut(key) {
+                entry.next_fetch = now + self.source.refresh;
+            }
+        }
+        due.into_iter().map(|(_, key)| key).collect()
+    }
+
+    /// Take `count` fetches from the source's budget if that many are left.
+    fn take_budget(&self, now: Instant, count: usize) -> bool {
+        let mut budget = self.budget.lock().unwrap_or_else(|p| p.into_inner());
+        let limit = f64::from(self.source.per_minute);
+        let elapsed = now.saturating_duration_since(budget.updated).as_secs_f64();
+        budget.tokens = (budget.tokens + elapsed * limit / 60.0).min(limit);
+        budget.updated = now;
+        if budget.tokens < count as f64 {
+            return false;
+        }
+        budget.tokens -= count as f64;
+        true
+    }
+
+    /// Store the outcome of fetching `key` and schedule its next fetch: after
+    /// the refresh interval on success, or after a jittered exponential backoff
+    /// on failure. A failure keeps the last good value.
+    pub fn record(&self, key: &str, result: Result<T>, now: Instant) {
+        let mut entries = self.entries.lock().unwrap_or_else(|p| p.into_inner());
+        let Some(entry) = entries.get_mut(key) else {
+            return;
+        };
+        match result {
+            Ok(value) => {
+                entry.value = Some(value);
+                entry.fetched_at = Some(Utc::now());
+                entry.error = None;
+                entry.failures = 0;
+                entry.next_fetch = now + self.source.refresh;
+            }
+            Err(err) => {
+                entry.error = Some(err.to_string());
+                entry.failures += 1;
+                let jitter = rand::rng().random_range(0.75..1.25);
+                entry.next_fetch = now + backoff(self.source, entry.failures).mul_f64(jitter);
+            }
+        }
+    }
+}
+
+/// Wait before retry number `failures`: the refresh interval doubled for each
+/// consecutive failure, capped at the source's maximum.
+fn backoff(source: Source, failures: u32) -> Duration {
+    let factor = 2u32.saturating_pow(failures.min(16));
+    source
+        .refresh
+        .saturating_mul(factor)
+        .min(source.max_backoff)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST: Source = Source {
+        name: "test",
+        refresh: Duration::from_secs(60),
+        max_backoff: Duration::from_secs(600),
+        per_minute: 2,
+    };
+
+    #[test]
+    fn backoff_doubles_up_to_the_cap() {
+        assert_eq!(backoff(TEST, 1), Duration::from_secs(120));
+        assert_eq!(backoff(TEST, 2), Duration::from_secs(240));
+        assert_eq!(backoff(TEST, 4), Duration::from_secs(600));
+        assert_eq!(backoff(TEST, 100), Duration::from_secs(600));
+    }
+
+    #[test]
+    fn cache_refreshes_read_keys_within_budget() {
+        let cache: Cache<u32> = Cache::new(TEST);
+        let start = Instant::now();
+        assert!(cache.due(start).is_empty());
+
+        for key in ["a", "b", "c"] {
+            assert!(cache.read(key, start).value.is_none());
+        }
+        // Only two fetches fit the budget; the third waits for it to refill
+        assert_eq!(cache.due(start).len(), 2);
+        assert!(cache.due(start).is_empty());
+        assert_eq!(cache.due(start + Duration::from_secs(30)).len(), 1);
+    }
+
+    #[test]
+    fn cache_backs_off_after_failures() {
+        let cache: Cache<u32> = Cache::new(TEST);
+        let start = Instant::now();
+        cache.read("a", start);
+        assert_eq!(cache.due(start), vec!["a"]);
+        cache.record("a", Ok(7), start);
+        let snapshot = cache.read("a", start);
+        assert_eq!(snapshot.value, Some(7));
+        assert!(!snapshot.stale);
+        assert!(!cache.claim_cold("a", start));
+
+        // A failure keeps the old value but marks it stale and backs off
+        cache.record("a", Err(anyhow::anyhow!("rate limited")), start);
+        let snapshot = cache.read("a", start);
+        assert_eq!(snapshot.value, Some(7));
+        assert!(snapshot.stale);
+        assert_eq!(snapshot.error.as_deref(), Some("rate limited"));
+        assert!(cache.due(start + Duration::from_secs(80)).is_empty());
+        assert_eq!(cache.due(start + Duration::from_secs(160)), vec!["a"]);
+    }
+
+    #[test]
+    fn cache_forgets_keys_nobody_reads() {
+        let cache: Cache<u32> = Cache::new(TEST);
+        let start = Instant::now();
+        cache.read("a", start);
+        assert!(cache.due(start + IDLE_AFTER).is_empty());
+        assert!(!cache.claim_cold("a", start + IDLE_AFTER));
+
+        // A reader can take the first fetch itself, once
+        cache.read("b", start);
+        assert!(cache.claim_cold("b", start));
+        assert!(!cache.claim_cold("b", start));
+        assert!(cache.due(start).is_empty());
+    }
+}
@@ -0,0 +1,186 @@
+use std::cell::Cell;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use anyhow::{Context, Result, bail};
+use once_cell::sync::Lazy;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// Failure injection for robustness testing, configured with `PIGS_FAULT_INJECT`:
+/// a comma-separated list of `key=value` settings.
+///
+/// - `git_fail=<probability>`: fail git commands with this probability (0-1)
+/// - `git_fail_on=<text>`: only fail git commands whose arguments contain
+///   `text`, e.g. `worktree add` (the probability defaults to 1)
+/// - `slow_ms=<ms>`: delay every git command and state write by this long
+/// - `partial_write=<probability>`: write only half of the state file, then fail
+/// - `seed=<n>`: seed the random choices so a run can be reproduced
+///
+/// Example: `PIGS_FAULT_INJECT=git_fail=0.2,slow_ms=50,seed=7 pigs create`.
+#[derive(Debug, Default, PartialEq)]
+struct FaultConfig {
+    git_fail: f64,
+    git_fail_on: Option<String>,
+    slow: Option<Duration>,
+    partial_write: f64,
+    seed: Option<u64>,
+}
+
+impl FaultConfig {
+    fn parse(spec: &str) -> Result<Self> {
+        let mut config = FaultConfig::default();
+        let mut git_fail = None;
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let (key, value) = entry
+                .split_once('=')
+                .with_context(|| format!("expected key=value, got '{entry}'"))?;
+            let (key, value) = (key.trim(), value.trim());
+            match key {
+                "git_fail" => git_fail = Some(parse_probability(key, value)?),
+                "git_fail_on" => config.git_fail_on = Some(value.to_string()),
+                "slow_ms" => {
+                    let ms = value
+                        .parse()
+                        .with_context(|| format!("invalid slow_ms '{value}'"))?;
+                    config.slow = Some(Duration::from_millis(ms));
+                }
+                "partial_write" => config.partial_write = parse_probability(key, value)?,
+                "seed" => {
+                    config.seed = Some(
+                        value
+                            .parse()
+                            .with_context(|| format!("invalid seed '{value}'"))?,
+                    )
+                }
+                _ => bail!("unknown setting '{key}'"),
+            }
+        }
+        config.git_fail = git_fail.unwrap_or(if config.git_fail_on.is_some() {
+            1.0
+        } else {
+            0.0
+        });
+        Ok(config)
+    }
+}
+
+fn parse_probability(key: &str, value: &str) -> Result<f64> {
+    let p: f64 = value
+        .parse()
+        .with_context(|| format!("invalid {key} '{value}'"))?;
+    if !(0.0..=1.0).contains(&p) {
+        bail!("{key} must be between 0 and 1, got {value}");
+    }
+    Ok(p)
+}
+
+struct Injector {
+    config: FaultConfig,
+    rng: Mutex<StdRng>,
+}
+
+impl Injector {
+    fn roll(&self, probability: f64) -> bool {
+        probability > 0.0 && self.rng.lock().unwrap().random_bool(probability)
+    }
+
+    fn delay(&self) {
+        if let Some(slow) = self.config.slow {
+            std::thread::sleep(slow);
+        }
+    }
+}
+
+static INJECTOR: Lazy<Option<Injector>> = Lazy::new(|| {
+    let spec = std::env::var("PIGS_FAULT_INJECT").ok()?;
+    match FaultConfig::parse(&spec) {
+        Ok(config) => {
+            let rng = match config.seed {
+                Some(seed) => StdRng::seed_from_u64(seed),
+                None => StdRng::from_os_rng(),
+            };
+            Some(Injector {
+                config,
+                rng: Mutex::new(rng),
+            })
+        }
+        Err(err) => {
+            eprintln!("Warning: ignoring PIGS_FAULT_INJECT: {err}");
+            None
+        }
+    }
+});
+
+thread_local! {
+    static SUSPENDED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Run `f` with no faults injected, for rollback code whose job is to undo
+/// an injected failure.
+pub fn suspended<T>(f: impl FnOnce() -> T) -> T {
+    let was_suspended = SUSPENDED.replace(true);
+    let result = f();
+    SUSPENDED.set(was_suspended);
+    result
+}
+
+fn active() -> Option<&'static Injector> {
+    if SUSPENDED.get() {
+        return None;
+    }
+    INJECTOR.as_ref()
+}
+
+/// Called before each git command runs: may delay it or fail it outright.
+pub fn before_git(args: &[&str]) -> Result<()> {
+    let Some(injector) = active() else {
+        return Ok(());
+    };
+    injector.delay();
+    let command = args.join(" ");
+    let eligible = injector
+        .config
+        .git_fail_on
+        .as_ref()
+        .is_none_or(|pattern| command.contains(pattern.as_str()));
+    if eligible && injector.roll(injector.config.git_fail) {
+        bail!("Git command failed: injected fault (git {command})");
+    }
+    Ok(())
+}
+
+/// Called before the state file is written. Returns how many bytes of
+/// `len` to write before failing, when a partial write is injected.
+pub fn state_write_cutoff(len: usize) -> Option<usize> {
+    let injector = active()?;
+    injector.delay();
+    injector
+        .roll(injector.config.partial_write)
+        .then_some(len / 2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_fault_settings() {
+        let config =
+            FaultConfig::parse("git_fail_on=worktree add, slow_ms=20,partial_write=0.5,seed=3")
+                .unwrap();
+        assert_eq!(
+            config,
+            FaultConfig {
+                git_fail: 1.0,
+                git_fail_on: Some("worktree add".to_string()),
+                slow: Some(Duration::from_millis(20)),
+                partial_write: 0.5,
+                seed: Some(3),
+            }
+        );
+        assert_eq!(FaultConfig::parse("").unwrap(), FaultConfig::default());
+        assert!(FaultConfig::parse("git_fail=2").is_err());
+        assert!(FaultConfig::parse("explode=1").is_err());
+    }
+}
@@ -1,10 +1,12 @@
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Utc};
 use colored::Colorize;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
 pub fn execute_git(args: &[&str]) -> Result<String> {
+    crate::faults::before_git(args)?;
     let output = Command::new("git")
         .args(args)
         .output()
@@ -64,31 +66,10 @@ pub fn extract_repo_name_from_url(url: &str) -> Option<String> {
 }
 
 fn get_repo_name_from_directory() -> Result<String> {
-    // For worktrees, we need to get the main repository path
-    // Try to get the common git directory first (which points to main repo for worktrees)
-    let git_common_dir = execute_git(&["rev-parse", "--git-common-dir"])?;
-    let git_dir = execute_git(&["rev-parse", "--git-dir"])?;
-
-    let repo_path = if git_common_dir != git_dir {
-        // We're in a worktree - git-common-dir points to main repo's .git
-        let path = Path::new(&git_common_dir);
-        if path.file_name().is_some_and(|n| n == ".git") {
-            // Get the parent directory which is the main repo
-            path.parent()
-                .and_then(|p| p.to_str())
-                .map(|s| s.to_string())
-                .context("Failed to get main repository path")?
-        } else {
-            // git-common-dir doesn't end with .git, use it directly
-            git_common_dir
-        }
-    } else {
-        // Not in a worktree, use toplevel
-        execute_git(&["rev-parse", "--show-toplevel"])?
-    };
-
-    let path = Path::new(&repo_path);
-    path.file_name()
+    // The main checkout, also from inside a linked worktree or a subdirectory
+    let repo_path = main_checkout(Path::new(".")).context("Failed to get main repository path")?;
+    repo_path
+        .file_name()
         .and_then(|n| n.to_str())
         .map(std::string::ToString::to_string)
         .context("Failed to get repository name")
@@ -119,6 +100,257 @@ pub fn get_default_branch() -> Result<String> {
     Ok("main".to_string())
 }
 
+/// Resolve the ref a worktree should be compared against: the remote default
+/// branch when `origin/HEAD` is set, otherwise the first of `main`, `master`,
+/// or `develop` that exists locally or on origin.
+pub fn resolve_base_ref(worktree_path: &Path) -> Option<String> {
+    let path = worktree_path.to_str()?;
+
+    if let Ok(head) = execute_git(&["-C", path, "symbolic-ref", "refs/remotes/origin/HEAD"])
+        && let Some(branch) = head.strip_prefix("refs/remotes/")
+    {
+        return Some(branch.to_string());
+    }
+
+    for candidate in ["main", "master", "develop"] {
+        let local = format!("refs/heads/{candidate}");
+        if execute_git(&["-C", path, "show-ref", "--verify", "--quiet", &local]).is_ok() {
+            return Some(candidate.to_string());
+        }
+        let remote = format!("refs/remotes/origin/{candidate}");
+        if execute_git(&["-C", path, "show-ref", "--verify", "--quiet", &remote]).is_ok() {
+            return Some(format!("origin/{candidate}"));
+        }
+    }
+
+    None
+}
+
+/// The main checkout a worktree (or the main checkout itself) belongs to. Asks
+/// git first, then falls back to the `gitdir:` line in a linked worktree's
+/// `.git` file, which still names the old location after the main checkout
+/// has moved.
+pub fn main_checkout(worktree: &Path) -> Option<PathBuf> {
+    if let Ok(common) = execute_git(&[
+        "-C",
+        worktree.to_str()?,
+        "rev-parse",
+        "--path-format=absolute",
+        "--git-common-dir",
+    ]) {
+        return Path::new(&common).parent().map(Path::to_path_buf);
+    }
+    // `gitdir: <root>/.git/worktrees/<id>`
+    let link = fs::read_to_string(worktree.join(".git")).ok()?;
+    let gitdir = Path::new(link.strip_prefix("gitdir:")?.trim());
+    let common = gitdir.parent()?.parent()?;
+    common.parent().map(Path::to_path_buf)
+}
+
+/// Binary patch of every uncommitted change in a worktree, untracked files
+/// included, against HEAD. Uses a scratch index so the worktree's own staging
+/// area is left alone. `git apply` restores it.
+pub fn uncommitted_patch(worktree_path: &Path) -> Result<Vec<u8>> {
+    let path = worktree_path.to_str().context("Invalid worktree path")?;
+    let index = std::env::temp_dir().join(format!(
+        "pigs-index-{}-{}",
+        std::process::id(),
+        chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default()
+    ));
+    let run = |args: &[&str]| -> Result<Vec<u8>> {
+        let output = Command::new("git")
+            .args(["-C", path])
+            .args(args)
+            .env("GIT_INDEX_FILE", &index)
+            .output()
+            .context("Failed to execute git command")?;
+        if !output.status.success() {
+            anyhow::bail!(
+                "Git command failed: {}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+        }
+        Ok(output.stdout)
+    };
+    let patch = run(&["read-tree", "HEAD"])
+        .and_then(|_| run(&["add", "--all"]))
+        .and_then(|_| run(&["diff", "--cached", "--binary", "HEAD"]));
+    let _ = fs::remove_file(&index);
+    patch
+}
+
+/// Apply a patch, such as one from `uncommitted_patch` or `git diff`, to a
+/// worktree's files without staging it.
+pub fn apply_patch(worktree_path: &Path, patch: &[u8]) -> Result<()> {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let mut child = Command::new("git")
+        .arg("-C")
+        .arg(worktree_path)
+        .args(["apply", "--whitespace=nowarn", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("Failed to execute git command")?;
+    child
+        .stdin
+        .take()
+        .context("Failed to open git's stdin")?
+        .write_all(patch)?;
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        bail!(
+            "Git command failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+    Ok(())
+}
+
+/// Ref namespace used to snapshot a worktree when an agent session starts.
+pub const SESSION_REF_PREFIX: &str = "refs/pigs/sessions/";
+
+/// Record the current state of a worktree (HEAD plus tracked uncommitted
+/// changes) under `refs/pigs/sessions/<session_id>` so later diffs can show
+/// only what changed during that session.
+pub fn snapshot_session_ref(worktree_path: &Path, session_id: &str) -> Result<String> {
+    let path = worktree_path.to_str().context("Invalid worktree path")?;
+
+    // `stash create` builds a commit for dirty trees without touching the stash list;
+    // it prints nothing when the tree is clean, in which case HEAD is the snapshot.
+    let stash = execute_git(&["-C", path, "stash", "create"])?;
+    let commit = if stash.is_empty() {
+        execute_git(&["-C", path, "rev-parse", "HEAD"])?
+    } else {
+        stash
+    };
+
+    let ref_name = format!("{SESSION_REF_PREFIX}{session_id}");
+    // The reflog records when the snapshot was taken, for `pigs gc`
+    execute_git(&[
+        "-C",
+        path,
+        "update-ref",
+        "--create-reflog",
+        "-m",
+        "pigs session snapshot",
+        &ref_name,
+        &commit,
+    ])?;
+    Ok(ref_name)
+}
+
+/// Session snapshot refs of the repository at `repo` taken before `cutoff`.
+/// The time comes from the ref's reflog; snapshots without one fall back to
+/// the date of the snapshot commit.
+pub fn session_refs_before(repo: &Path, cutoff: DateTime<Utc>) -> Result<Vec<String>> {
+    let path = repo.to_str().context("Invalid repository path")?;
+    let refs = execute_git(&[
+        "-C",
+        path,
+        "for-each-ref",
+        "--format=%(refname) %(committerdate:unix)",
+        SESSION_REF_PREFIX,
+    ])?;
+    Ok(refs
+        .lines()
+        .filter_map(|line| {
+            let (name, committed) = line.rsplit_once(' ')?;
+            let taken = snapshot_time(path, name).or_else(|| committed.parse().ok())?;
+            (taken < cutoff.timestamp()).then(|| name.to_string())
+        })
+        .collect())
+}
+
+/// When `ref_name` was last updated according to its reflog, in Unix seconds.
+fn snapshot_time(path: &str, ref_name: &str) -> Option<i64> {
+    // Prints e.g. `refs/pigs/sessions/abc@{1700000000}`
+    let entry = execute_git(&[
+        "-C",
+        path,
+        "reflog",
+        "show",
+        "-n",
+        "1",
+        "--date=unix",
+        "--format=%gd",
+        ref_name,
+    ])
+    .ok()?;
+    entry.rsplit_once("@{")?.1.strip_suffix('}')?.parse().ok()
+}
+
+/// Find the snapshot ref recorded when a session started. Accepts a full
+/// session id or any unique prefix of one.
+pub fn resolve_session_ref(path: &str, session: &str) -> Result<String> {
+    let session = session.trim();
+    if session.is_empty() {
+        bail!("Session id cannot be empty");
+    }
+
+    let refs = execute_git(&[
+        "-C",
+        path,
+        "for-each-ref",
+        "--format=%(refname)",
+        SESSION_REF_PREFIX,
+    ])?;
+    let matches: Vec<&str> = refs
+        .lines()
+        .filter(|line| {
+            line.strip_prefix(SESSION_REF_PREFIX)
+                .is_some_and(|id| id.starts_with(session))
+        })
+        .collect();
+
+    match matches.as_slice() {
+        [single] => Ok((*single).to_string()),
+        [] => bail!(
+            "No snapshot found for session '{session}'. Snapshots are recorded when a session starts from the dashboard."
+        ),
+        _ => bail!("Session id '{session}' is ambiguous; use more characters"),
+    }
+}
+
+/// Checkpoint name for the worktree as it is now, rather than a snapshot.
+pub const CURRENT_CHECKPOINT: &str = "now";
+
+/// The `git diff` revisions comparing checkpoint `from` to `to`, where each is
+/// a session snapshot (full id or unique prefix) or `now`.
+pub fn snapshot_diff_revisions(path: &str, from: &str, to: &str) -> Result<Vec<String>> {
+    let resolve = |checkpoint: &str| {
+        if checkpoint.trim() == CURRENT_CHECKPOINT {
+            Ok(None)
+        } else {
+            resolve_session_ref(path, checkpoint).map(Some)
+        }
+    };
+    match (resolve(from)?, resolve(to)?) {
+        (Some(from), Some(to)) => Ok(vec![from, to]),
+        (Some(from), None) => Ok(vec![from]),
+        (None, Some(to)) => Ok(vec!["-R".to_string(), to]),
+        (None, None) => bail!("At least one checkpoint must be a session snapshot"),
+    }
+}
+
+/// Commit time of the worktree's `HEAD`.
+pub fn last_commit_time(worktree_path: &Path) -> Option<DateTime<Utc>> {
+    let path = worktree_path.to_str()?;
+    let timestamp = execute_git(&["-C", path, "log", "-1", "--format=%cI"]).ok()?;
+    DateTime::parse_from_rfc3339(&timestamp)
+        .map(|ts| ts.with_timezone(&Utc))
+        .ok()
+}
+
+/// Whether the worktree has uncommitted changes, including untracked files.
+pub fn is_dirty(worktree_path: &Path) -> bool {
+    worktree_path.to_str().is_some_and(|path| {
+        execute_git(&["-C", path, "status", "--porcelain"]).is_ok_and(|status| !status.is_empty())
+    })
+}
+
 pub fn is_base_branch() -> Result<bool> {
     let current = get_current_branch()?;
 
@@ -212,6 +444,38 @@ pub fn list_worktrees() -> Result<Vec<PathBuf>> {
     Ok(worktrees)
 }
 
+/// One entry of `git worktree list --porcelain`.
+#[derive(Debug, PartialEq)]
+pub struct ListedWorktree {
+    pub path: PathBuf,
+    /// None when HEAD is detached or the entry is the bare repository
+    pub branch: Option<String>,
+    /// Why git considers the entry stale, when it no longer finds the directory
+    pub prunable: Option<String>,
+}
+
+pub fn parse_worktree_list(output: &str) -> Vec<ListedWorktree> {
+    let mut worktrees: Vec<ListedWorktree> = Vec::new();
+    for line in output.lines() {
+        if let Some(path) = line.strip_prefix("worktree ") {
+            worktrees.push(ListedWorktree {
+                path: PathBuf::from(path),
+                branch: None,
+                prunable: None,
+            });
+        } else if let Some(current) = worktrees.last_mut() {
+            if let Some(branch) = line.strip_prefix("branch ") {
+                current.branch = Some(branch.trim_start_matches("refs/heads/").to_string());
+            } else if line == "prunable" {
+                current.prunable = Some(String::new());
+            } else if let Some(reason) = line.strip_prefix("prunable ") {
+                current.prunable = Some(reason.to_string());
+            }
+        }
+    }
+    worktrees
+}
+
 pub fn update_submodules(worktree_path: &Path) -> Result<()> {
     // Check if submodules exist
     let gitmodules = worktree_path.join(".gitmodules");
@@ -219,16 +483,20 @@ pub fn update_submodules(worktree_path: &Path) -> Result<()> {
         return Ok(());
     }
 
-    // Initialize and update submodules using git -C
-    execute_git(&[
+    // Initialize and update submodules using git -C; offline, only check out
+    // commits that are already present locally
+    let mut args = vec![
         "-C",
         worktree_path.to_str().unwrap(),
         "submodule",
         "update",
         "--init",
         "--recursive",
-    ])
-    .context("Failed to update submodules")?;
+    ];
+    if crate::network::is_offline() {
+        args.push("--no-fetch");
+    }
+    execute_git(&args).context("Failed to update submodules")?;
 
     Ok(())
 }
@@ -273,24 +541,29 @@ pub fn copy_files_to_worktree(
 
 /// Run setup commands from RepoConfig in the new worktree directory.
 pub fn run_setup_commands(worktree_path: &Path, commands: &[String], quiet: bool) -> Result<()> {
+    // Load direnv/mise once so setup runs with the worktree's tool versions
+    let env = if commands.is_empty() {
+        Vec::new()
+    } else {
+        crate::devenv::activation_env(worktree_path)
+    };
     for cmd_str in commands {
         if !quiet {
             println!("{} Running setup: {}", "⚙️".green(), cmd_str.cyan());
         }
-        let status = Command::new("sh")
-            .args(["-c", cmd_str])
-            .current_dir(worktree_path)
+        let mut command = Command::new("sh");
+        command.args(["-c", cmd_str]).current_dir(worktree_path);
+        crate::devenv::apply_changes(&mut command, &env);
+        let status = command
             .status()
             .with_context(|| format!("Failed to execute setup command: {cmd_str}"))?;
-        if !status.success() {
-            if !quiet {
-                println!(
-                    "{} Setup command failed (exit {}): {}",
-                    "⚠️".yellow(),
-                    status.code().unwrap_or(-1),
-                    cmd_str
-                );
-            }
+        if !status.success() && !quiet {
+            println!(
+                "{} Setup command failed (exit {}): {}",
+                "⚠️".yellow(),
+                status.code().unwrap_or(-1),
+                cmd_str
+            );
         }
     }
     Ok(())
@@ -300,6 +573,29 @@ pub fn run_setup_commands(worktree_path: &Path, commands: &[String], quiet: bool
 mod tests {
     use super::*;
 
+    #[test]
+    fn parses_porcelain_worktree_list() {
+        let output = "worktree /repo\nHEAD abc\nbranch refs/heads/main\n\n\
+                      worktree /repo-feature\nHEAD def\nbranch refs/heads/feature/x\n\n\
+                      worktree /repo-detached\nHEAD 123\ndetached\n\n\
+                      worktree /gone\nHEAD 456\nbranch refs/heads/gone\nprunable gitdir file points to non-existent location\n";
+        let listed = parse_worktree_list(output);
+        assert_eq!(listed.len(), 4);
+        assert_eq!(
+            listed[1],
+            ListedWorktree {
+                path: PathBuf::from("/repo-feature"),
+                branch: Some("feature/x".to_string()),
+                prunable: None,
+            }
+        );
+        assert_eq!(listed[2].branch, None);
+        assert_eq!(
+            listed[3].prunable.as_deref(),
+            Some("gitdir file points to non-existent location")
+        );
+    }
+
     #[test]
     fn test_extract_repo_name_from_url() {
         // GitHub HTTPS
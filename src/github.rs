@@ -0,0 +1,258 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+
+/// A branch's pull request and the state of its CI checks.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PullRequestStatus {
+    pub number: u64,
+    pub url: String,
+    /// `OPEN`, `CLOSED`, or `MERGED`
+    pub state: String,
+    pub is_draft: bool,
+    /// `passing`, `failing`, or `pending`; `None` when the PR has no checks
+    pub checks: Option<&'static str>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ListedPullRequest {
+    number: u64,
+    url: String,
+    state: String,
+    #[serde(default)]
+    is_draft: bool,
+    head_ref_name: String,
+    #[serde(default)]
+    status_check_rollup: Vec<CheckRollup>,
+}
+
+/// One entry of `statusCheckRollup`: a check run (`status` and `conclusion`)
+/// or a commit status (`state`).
+#[derive(Deserialize)]
+struct CheckRollup {
+    status: Option<String>,
+    conclusion: Option<String>,
+    state: Option<String>,
+}
+
+/// Pull requests of the repository at `repo_path`, by head branch, with one
+/// `gh` call. A branch with several PRs maps to its open one, else the newest.
+pub fn pull_requests_by_branch(repo_path: &Path) -> Result<HashMap<String, PullRequestStatus>> {
+    crate::network::ensure_online("GitHub")?;
+    let output = Command::new("gh")
+        .args([
+            "pr",
+            "list",
+            "--state",
+            "all",
+            "--limit",
+            "100",
+            "--json",
+            "number,url,state,isDraft,headRefName,statusCheckRollup",
+        ])
+        .current_dir(repo_path)
+        .output()
+        .context("Failed to run gh")?;
+    if !output.status.success() {
+        bail!(
+            "gh pr list failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    let listed: Vec<ListedPullRequest> =
+        serde_json::from_slice(&output.stdout).context("Failed to parse gh pr list output")?;
+    Ok(by_branch(listed))
+}
+
+fn by_branch(listed: Vec<ListedPullRequest>) -> HashMap<String, PullRequestStatus> {
+    let mut branches: HashMap<String, PullRequestStatus> = HashMap::new();
+    for pr in listed {
+        let status = PullRequestStatus {
+            number: pr.number,
+            url: pr.url,
+            checks: summarize_checks(&pr.status_check_rollup),
+            state: pr.state,
+            is_draft: pr.is_draft,
+        };
+        let rank = |status: &PullRequestStatus| (status.state == "OPEN", status.number);
+        match branches.get(&pr.head_ref_name) {
+            Some(existing) if rank(existing) >= rank(&status) => {}
+            _ => {
+                branches.insert(pr.head_ref_name, status);
+            }
+        }
+    }
+    branches
+}
+
+fn summarize_checks(checks: &[CheckRollup]) -> Option<&'static str> {
+    if checks.is_empty() {
+        return None;
+    }
+    let failed = checks.iter().any(|check| {
+        matches!(
+            check.conclusion.as_deref().or(check.state.as_deref()),
+            Some(
+                "FAILURE"
+                    | "ERROR"
+                    | "CANCELLED"
+                    | "TIMED_OUT"
+                    | "ACTION_REQUIRED"
+                    | "STARTUP_FAILURE"
+            )
+        )
+    });
+    let pending = checks.iter().any(|check| match &check.state {
+        Some(state) => matches!(state.as_str(), "PENDING" | "EXPECTED"),
+        None => check.status.as_deref() != Some("COMPLETED"),
+    });
+    Some(if failed {
+        "failing"
+    } else if pending {
+        "pending"
+    } else {
+        "passing"
+    })
+}
+
+/// A GitHub issue of the current repository.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Issue {
+    pub number: u64,
+    pub title: String,
+    #[serde(default)]
+    pub body: String,
+    pub url: String,
+}
+
+/// The issue number `target` refers to: `42`, `#42`, or an issue URL.
+pub fn parse_issue_reference(target: &str) -> Option<u64> {
+    let target = target.trim();
+    let number = match target.split_once("/issues/") {
+        Some((_, rest)) => rest.split(['/', '#', '?']).next().unwrap_or_default(),
+        None => target.strip_prefix('#').unwrap_or(target),
+    };
+    number.parse().ok().filter(|&number| number > 0)
+}
+
+/// Fetch an issue by number or URL with `gh issue view`.
+pub fn fetch_issue(target: &str) -> Result<Issue> {
+    crate::network::ensure_online("GitHub")?;
+    let output = run_gh(&["issue", "view", target, "--json", "number,title,body,url"])?;
+    serde_json::from_slice(&output).context("Failed to parse gh issue view output")
+}
+
+/// Open issues of the current repository assigned to the current user.
+pub fn fetch_my_issues() -> Result<Vec<Issue>> {
+    crate::network::ensure_online("GitHub")?;
+    let output = run_gh(&[
+        "issue",
+        "list",
+        "--assignee",
+        "@me",
+        "--limit",
+        "50",
+        "--json",
+        "number,title,url",
+    ])?;
+    serde_json::from_slice(&output).context("Failed to parse gh issue list output")
+}
+
+fn run_gh(args: &[&str]) -> Result<Vec<u8>> {
+    let output = Command::new("gh")
+        .args(args)
+        .output()
+        .context("Failed to run gh")?;
+    if !output.status.success() {
+        bail!(
+            "gh {} failed: {}",
+            args[..2].join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(output.stdout)
+}
+
+/// Branch name for an issue, like GitHub's own "Create a branch":
+/// `42-fix-the-login-bug`, with the title cut at a word boundary.
+pub fn issue_branch_name(number: u64, title: &str) -> String {
+    let mut slug = String::new();
+    for word in title
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+    {
+        if !slug.is_empty() && slug.len() + word.len() >= 50 {
+            break;
+        }
+        if !slug.is_empty() {
+            slug.push('-');
+        }
+        slug.push_str(&word.to_ascii_lowercase());
+    }
+    if slug.is_empty() {
+        format!("issue-{number}")
+    } else {
+        format!("{number}-{slug}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn issue_references_and_branch_names() {
+        assert_eq!(parse_issue_reference("42"), Some(42));
+        assert_eq!(parse_issue_reference("#42"), Some(42));
+        assert_eq!(
+            parse_issue_reference("https://github.com/acme/app/issues/42#issuecomment-1"),
+            Some(42)
+        );
+        assert_eq!(
+            parse_issue_reference("https://github.com/acme/app/pull/42"),
+            None
+        );
+        assert_eq!(parse_issue_reference("ENG-42"), None);
+
+        assert_eq!(
+            issue_branch_name(42, "Fix the login bug (Safari only!)"),
+            "42-fix-the-login-bug-safari-only"
+        );
+        assert_eq!(issue_branch_name(7, "🐛"), "issue-7");
+        let long = issue_branch_name(9, &"word ".repeat(30));
+        assert!(long.len() <= 52 && long.ends_with("word"), "{long}");
+    }
+
+    #[test]
+    fn pull_requests_prefer_open_and_summarize_checks() {
+        let listed: Vec<ListedPullRequest> = serde_json::from_str(
+            r#"[
+                {"number": 3, "url": "u3", "state": "CLOSED", "headRefName": "feature",
+                 "statusCheckRollup": []},
+                {"number": 2, "url": "u2", "state": "OPEN", "isDraft": true, "headRefName": "feature",
+                 "statusCheckRollup": [
+                    {"status": "COMPLETED", "conclusion": "SUCCESS"},
+                    {"state": "PENDING"}
+                 ]},
+                {"number": 1, "url": "u1", "state": "MERGED", "headRefName": "done",
+                 "statusCheckRollup": [
+                    {"status": "COMPLETED", "conclusion": "SUCCESS"},
+                    {"status": "COMPLETED", "conclusion": "FAILURE"}
+                 ]}
+            ]"#,
+        )
+        .unwrap();
+        let branches = by_branch(listed);
+
+        let feature = &branches["feature"];
+        assert_eq!(feature.number, 2);
+        assert!(feature.is_draft);
+        assert_eq!(feature.checks, Some("pending"));
+        assert_eq!(branches["done"].checks, Some("failing"));
+    }
+}
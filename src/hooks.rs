@@ -0,0 +1,122 @@
+use std::path::Path;
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use anyhow::{Context, Result, bail};
+use colored::Colorize;
+
+use crate::state::{HookConfig, PigsState, WorktreeInfo};
+
+/// Process-wide switch, set once at startup from `--no-hooks` or
+/// `PIGS_NO_HOOKS`.
+static DISABLED: AtomicBool = AtomicBool::new(false);
+
+/// Points in a worktree's life where hooks run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hook {
+    PostCreate,
+    PreOpen,
+    PreSync,
+    PostSync,
+    PreDelete,
+    PostDelete,
+}
+
+impl Hook {
+    /// The settings key listing this hook's commands.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::PostCreate => "post_create",
+            Self::PreOpen => "pre_open",
+            Self::PreSync => "pre_sync",
+            Self::PostSync => "post_sync",
+            Self::PreDelete => "pre_delete",
+            Self::PostDelete => "post_delete",
+        }
+    }
+
+    fn commands(self, config: &HookConfig) -> &[String] {
+        match self {
+            Self::PostCreate => &config.post_create,
+            Self::PreOpen => &config.pre_open,
+            Self::PreSync => &config.pre_sync,
+            Self::PostSync => &config.post_sync,
+            Self::PreDelete => &config.pre_delete,
+            Self::PostDelete => &config.post_delete,
+        }
+    }
+}
+
+pub fn set_disabled(disabled: bool) {
+    DISABLED.store(disabled, Ordering::Relaxed);
+}
+
+/// Run the `hook` commands for `info` one at a time in `dir`: the global ones
+/// from `~/.pigs/settings.json` first, then the repository's from `repo`. Each
+/// runs with `sh -c` in the worktree's direnv or mise environment, with
+/// `PIGS_HOOK`, `PIGS_REPO`, `PIGS_WORKTREE_NAME`, `PIGS_WORKTREE_PATH`, and
+/// `PIGS_BRANCH` set. Output streams to the terminal as they run; with `quiet`
+/// it goes to stderr so stdout stays clean for `--json` and the like. Stops at
+/// the first command that fails.
+pub fn run(
+    hook: Hook,
+    info: &WorktreeInfo,
+    dir: &Path,
+    repo: &HookConfig,
+    quiet: bool,
+) -> Result<()> {
+    if DISABLED.load(Ordering::Relaxed) {
+        return Ok(());
+    }
+    let global = PigsState::load()?.hooks;
+    let commands: Vec<&String> = hook
+        .commands(&global)
+        .iter()
+        .chain(hook.commands(repo))
+        .collect();
+    if commands.is_empty() {
+        return Ok(());
+    }
+
+    let name = hook.name();
+    let env = crate::devenv::activation_env(dir);
+    for (index, cmd_str) in commands.iter().enumerate() {
+        if !quiet {
+            println!(
+                "{} Running {} hook {}/{}: {}",
+                "🪝".green(),
+                name,
+                index + 1,
+                commands.len(),
+                cmd_str.cyan()
+            );
+        }
+        let mut command = Command::new("sh");
+        command.args(["-c", cmd_str]).current_dir(dir);
+        if quiet {
+            command.stdout(std::io::stderr());
+        }
+        crate::devenv::apply_changes(&mut command, &env);
+        command
+            .env("PIGS_HOOK", name)
+            .env("PIGS_REPO", &info.repo_name)
+            .env("PIGS_WORKTREE_NAME", &info.name)
+            .env("PIGS_WORKTREE_PATH", &info.path)
+            .env("PIGS_BRANCH", &info.branch);
+        let status = command
+            .status()
+            .with_context(|| format!("Failed to run {name} hook: {cmd_str}"))?;
+        if !status.success() {
+            let skipped = commands.len() - index - 1;
+            let exit = status.code().map_or_else(
+                || "killed by a signal".to_string(),
+                |code| format!("exit {code}"),
+            );
+            if skipped > 0 {
+                bail!("{name} hook failed ({exit}): {cmd_str}; skipped the {skipped} after it");
+            }
+            bail!("{name} hook failed ({exit}): {cmd_str}");
+        }
+    }
+    Ok(())
+}
@@ -1,6 +1,7 @@
 use anyhow::Result;
 use atty::Stream;
-use dialoguer::{Confirm, Select};
+use console::{Key, Term, style};
+use dialoguer::{Confirm, MultiSelect, Select};
 use std::io::{self, BufRead, BufReader};
 use std::sync::Mutex;
 
@@ -83,6 +84,26 @@ pub fn smart_confirm(prompt: &str, default: bool) -> Result<bool> {
         .map_err(Into::into)
 }
 
+/// Confirmation that only an answer counts for: piped `y`/`yes` or a prompt
+/// on a terminal. Unlike `smart_confirm`, `PIGS_YES` doesn't accept it and
+/// non-interactive mode declines, for guesses that could destroy work.
+pub fn explicit_confirm(prompt: &str) -> Result<bool> {
+    if let Some(input) = read_piped_line()? {
+        let input = input.to_lowercase();
+        return Ok(input == "y" || input == "yes");
+    }
+
+    if std::env::var("PIGS_NON_INTERACTIVE").is_ok() || !atty::is(Stream::Stdin) {
+        return Ok(false);
+    }
+
+    Confirm::new()
+        .with_prompt(prompt)
+        .default(false)
+        .interact()
+        .map_err(Into::into)
+}
+
 /// Smart selection that supports piped input
 pub fn smart_select<T>(
     prompt: &str,
@@ -126,6 +147,170 @@ where
     Ok(Some(selection))
 }
 
+/// Smart multi-selection that supports piped input: one line of indices or
+/// display texts separated by commas or spaces. Returns `None` in
+/// non-interactive mode without piped input.
+pub fn smart_multi_select<T>(
+    prompt: &str,
+    items: &[T],
+    display_fn: impl Fn(&T) -> String,
+) -> Result<Option<Vec<usize>>> {
+    // 1. Check for piped input
+    if let Some(input) = read_piped_line()? {
+        let mut selected = Vec::new();
+        for token in input
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|token| !token.is_empty())
+        {
+            let index = token
+                .parse::<usize>()
+                .ok()
+                .filter(|index| *index < items.len())
+                .or_else(|| items.iter().position(|item| display_fn(item) == token))
+                .ok_or_else(|| anyhow::anyhow!("Invalid selection: {}", token))?;
+            if !selected.contains(&index) {
+                selected.push(index);
+            }
+        }
+        return Ok(Some(selected));
+    }
+
+    // 2. Non-interactive mode returns None
+    if std::env::var("PIGS_NON_INTERACTIVE").is_ok() {
+        return Ok(None);
+    }
+
+    // 3. Interactive checkbox list
+    let display_items: Vec<String> = items.iter().map(display_fn).collect();
+    let selection = MultiSelect::new()
+        .with_prompt(format!("{prompt} (space to toggle, enter to confirm)"))
+        .items(&display_items)
+        .interact()?;
+
+    Ok(Some(selection))
+}
+
+/// Most entries the fuzzy picker shows at once.
+const FUZZY_VISIBLE: usize = 10;
+
+/// Like `smart_select`, but on a terminal the list narrows as you type,
+/// keeping matches in the order given.
+pub fn smart_fuzzy_select<T>(
+    prompt: &str,
+    items: &[T],
+    display_fn: impl Fn(&T) -> String,
+) -> Result<Option<usize>>
+where
+    T: Clone,
+{
+    if is_piped_input() || std::env::var("PIGS_NON_INTERACTIVE").is_ok() {
+        return smart_select(prompt, items, display_fn);
+    }
+    let term = Term::stderr();
+    if !term.is_term() {
+        return smart_select(prompt, items, display_fn);
+    }
+
+    let display_items: Vec<String> = items.iter().map(display_fn).collect();
+    let mut query = String::new();
+    let mut cursor = 0;
+    let mut drawn = 0;
+    term.hide_cursor()?;
+    let selection = loop {
+        let mut matches: Vec<(usize, usize)> = display_items
+            .iter()
+            .enumerate()
+            .filter_map(|(index, text)| fuzzy_score(&query, text).map(|score| (score, index)))
+            .collect();
+        // Stable, so equally good matches keep the caller's order
+        matches.sort_by_key(|(score, _)| *score);
+        cursor = cursor.min(matches.len().saturating_sub(1));
+
+        term.clear_last_lines(drawn)?;
+        term.write_line(&format!(
+            "{} {}",
+            style(format!("{prompt}:")).bold(),
+            style(&query).cyan()
+        ))?;
+        let start = cursor.saturating_sub(FUZZY_VISIBLE - 1);
+        for (row, (_, index)) in matches.iter().enumerate().skip(start).take(FUZZY_VISIBLE) {
+            if row == cursor {
+                term.write_line(&format!("{} {}", style(">").cyan(), display_items[*index]))?;
+            } else {
+                term.write_line(&format!("  {}", display_items[*index]))?;
+            }
+        }
+        if matches.is_empty() {
+            term.write_line(&format!("  {}", style("no matches").dim()))?;
+        }
+        drawn = 1 + matches.len().saturating_sub(start).clamp(1, FUZZY_VISIBLE);
+
+        match term.read_key()? {
+            Key::Enter => {
+                if let Some((_, index)) = matches.get(cursor) {
+                    break Some(*index);
+                }
+            }
+            Key::Escape => break None,
+            Key::ArrowUp => cursor = cursor.saturating_sub(1),
+            Key::ArrowDown => cursor += 1,
+            Key::Backspace => {
+                query.pop();
+                cursor = 0;
+            }
+            Key::Char(c) if !c.is_control() => {
+                query.push(c);
+                cursor = 0;
+            }
+            _ => {}
+        }
+    };
+    term.clear_last_lines(drawn)?;
+    term.show_cursor()?;
+
+    match selection {
+        Some(index) => Ok(Some(index)),
+        None => anyhow::bail!("Selection cancelled"),
+    }
+}
+
+/// How well `query` matches `text`, lower being better: the characters of
+/// `query` must appear in `text` in order, ignoring case, and the score is how
+/// many other characters sit between them in the tightest such match. `None`
+/// when they don't all appear.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<usize> {
+    let query: Vec<char> = query.to_lowercase().chars().collect();
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let Some((first, rest)) = query.split_first() else {
+        return Some(0);
+    };
+
+    let mut best = None;
+    for start in (0..text.len()).filter(|&i| text[i] == *first) {
+        let mut position = start + 1;
+        let mut gaps = 0;
+        let mut matched = true;
+        for wanted in rest {
+            match text[position..].iter().position(|c| c == wanted) {
+                Some(offset) => {
+                    gaps += offset;
+                    position += offset + 1;
+                }
+                None => {
+                    matched = false;
+                    break;
+                }
+            }
+        }
+        if !matched {
+            // Later starts can't fit the rest either
+            break;
+        }
+        best = Some(best.map_or(gaps, |best: usize| best.min(gaps)));
+    }
+    best
+}
+
 /// Get command argument with pipe input support
 /// Priority: CLI argument > piped input > None
 pub fn get_command_arg(arg: Option<String>) -> Result<Option<String>> {
@@ -158,3 +343,17 @@ pub fn drain_stdin() -> Result<()> {
     // to not inherit stdin in child processes (using Stdio::null()).
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::fuzzy_score;
+
+    #[test]
+    fn fuzzy_score_prefers_tighter_matches() {
+        assert_eq!(fuzzy_score("", "anything"), Some(0));
+        assert_eq!(fuzzy_score("pay", "app/payment-retry"), Some(0));
+        assert_eq!(fuzzy_score("PR", "app/payment-retry"), Some(7));
+        assert_eq!(fuzzy_score("pay", "p-a-y"), Some(2));
+        assert_eq!(fuzzy_score("yap", "app/payment-retry"), None);
+    }
+}
@@ -0,0 +1,506 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::JoinHandle;
+
+use anyhow::{Result, bail};
+use chrono::{DateTime, Duration, Utc};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+use crate::state::get_config_dir;
+
+/// Finished jobs are kept this long so clients can still fetch their result.
+const FINISHED_JOB_RETENTION_MINUTES: i64 = 60;
+const JOB_EVENT_BUFFER: usize = 64;
+/// Jobs beyond this many wait in the queue until a slot frees up.
+const MAX_RUNNING_JOBS: usize = 4;
+
+static JOBS: Lazy<Mutex<HashMap<String, Arc<Job>>>> = Lazy::new(|| Mutex::new(load_persisted()));
+static RUNNING_SLOTS: Lazy<(Mutex<usize>, Condvar)> = Lazy::new(|| (Mutex::new(0), Condvar::new()));
+static WORKERS: Lazy<Mutex<Vec<JoinHandle<()>>>> = Lazy::new(|| Mutex::new(Vec::new()));
+/// Held while `jobs.json` is read, merged, and rewritten, so jobs finishing
+/// on different threads don't drop each other's updates.
+static PERSIST: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Succeeded,
+    Failed,
+    Cancelled,
+}
+
+impl JobStatus {
+    pub fn is_finished(self) -> bool {
+        matches!(self, Self::Succeeded | Self::Failed | Self::Cancelled)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobStep {
+    pub name: String,
+    pub started_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+/// Point-in-time view of a job, as returned by `GET /api/jobs/:id` and
+/// persisted to `~/.pigs/jobs.json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobSnapshot {
+    pub id: String,
+    pub kind: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub status: JobStatus,
+    pub steps: Vec<JobStep>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+/// Progress notification: `started` when the job leaves the queue, `step` when
+/// a new step starts, then one of `succeeded`, `failed`, or `cancelled`.
+/// Streamed to dashboard clients and printed by `--json` modes.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobEvent {
+    pub job_id: String,
+    pub kind: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub step: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl JobEvent {
+    pub fn is_terminal(&self) -> bool {
+        matches!(self.kind, "succeeded" | "failed" | "cancelled")
+    }
+}
+
+pub struct Job {
+    snapshot: Mutex<JobSnapshot>,
+    cancel_requested: AtomicBool,
+    events: broadcast::Sender<JobEvent>,
+}
+
+impl Job {
+    fn new(kind: &str, description: Option<String>) -> Self {
+        Self::from_snapshot(JobSnapshot {
+            id: Uuid::new_v4().to_string(),
+            kind: kind.to_string(),
+            description,
+            status: JobStatus::Queued,
+            steps: Vec::new(),
+            result: None,
+            error: None,
+            created_at: Utc::now(),
+            finished_at: None,
+        })
+    }
+
+    fn from_snapshot(snapshot: JobSnapshot) -> Self {
+        let (events, _) = broadcast::channel(JOB_EVENT_BUFFER);
+        Self {
+            snapshot: Mutex::new(snapshot),
+            cancel_requested: AtomicBool::new(false),
+            events,
+        }
+    }
+
+    pub fn id(&self) -> String {
+        self.lock().id.clone()
+    }
+
+    pub fn snapshot(&self) -> JobSnapshot {
+        self.lock().clone()
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<JobEvent> {
+        self.events.subscribe()
+    }
+
+    /// Ask the job to stop. Queued jobs are cancelled right away; running jobs
+    /// stop at their next cancellation check. Returns false if already finished.
+    pub fn cancel(&self) -> bool {
+        let status = self.lock().status;
+        if status.is_finished() {
+            return false;
+        }
+        self.cancel_requested.store(true, Ordering::SeqCst);
+        if status == JobStatus::Queued {
+            self.finish(JobStatus::Cancelled, None, None);
+        }
+        true
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel_requested.load(Ordering::SeqCst)
+    }
+
+    /// Cancellation point for job bodies: returns an error once cancel was requested.
+    pub fn check_cancelled(&self) -> Result<()> {
+        if self.is_cancelled() {
+            bail!("cancelled");
+        }
+        Ok(())
+    }
+
+    /// Finish the current step and start `name`.
+    pub fn step(&self, name: &str) {
+        let now = Utc::now();
+        let event = {
+            let mut snapshot = self.lock();
+            if let Some(current) = snapshot.steps.last_mut() {
+                current.finished_at.get_or_insert(now);
+            }
+            snapshot.steps.push(JobStep {
+                name: name.to_string(),
+                started_at: now,
+                finished_at: None,
+            });
+            event(&snapshot, "step", Some(name.to_string()))
+        };
+        let _ = self.events.send(event);
+        persist(self);
+    }
+
+    fn mark_running(&self) -> bool {
+        let event = {
+            let mut snapshot = self.lock();
+            if snapshot.status != JobStatus::Queued {
+                return false;
+            }
+            snapshot.status = JobStatus::Running;
+            event(&snapshot, "started", None)
+        };
+        let _ = self.events.send(event);
+        persist(self);
+        true
+    }
+
+    pub fn succeed(&self, result: Value) {
+        self.finish(JobStatus::Succeeded, Some(result), None);
+    }
+
+    pub fn fail(&self, error: String) {
+        self.finish(JobStatus::Failed, None, Some(error));
+    }
+
+    fn finish(&self, status: JobStatus, result: Option<Value>, error: Option<String>) {
+        let now = Utc::now();
+        let event = {
+            let mut snapshot = self.lock();
+            if snapshot.status.is_finished() {
+                return;
+            }
+            if let Some(current) = snapshot.steps.last_mut() {
+                current.finished_at.get_or_insert(now);
+            }
+            snapshot.status = status;
+            snapshot.result = result;
+            snapshot.error = error;
+            snapshot.finished_at = Some(now);
+            let kind = match status {
+                JobStatus::Failed => "failed",
+                JobStatus::Cancelled => "cancelled",
+                _ => "succeeded",
+            };
+            event(&snapshot, kind, None)
+        };
+        let _ = self.events.send(event);
+        persist(self);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, JobSnapshot> {
+        self.snapshot
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+fn event(snapshot: &JobSnapshot, kind: &'static str, step: Option<String>) -> JobEvent {
+    JobEvent {
+        job_id: snapshot.id.clone(),
+        kind,
+        step,
+        result: snapshot.result.clone(),
+        error: snapshot.error.clone(),
+        timestamp: Utc::now(),
+    }
+}
+
+/// Queue `work` as a job of the given kind (e.g. `create`, `sync`) and run it
+/// on a background thread once a slot is free. The body reports progress with
+/// [`Job::step`] and should call [`Job::check_cancelled`] between steps; its
+/// return value becomes the job's result. The returned receiver is subscribed
+/// before the job starts, so it sees every event.
+pub fn spawn<F>(
+    kind: &str,
+    description: Option<String>,
+    work: F,
+) -> (Arc<Job>, broadcast::Receiver<JobEvent>)
+where
+    F: FnOnce(&Job) -> Result<Value> + Send + 'static,
+{
+    let job = Arc::new(Job::new(kind, description));
+    let events = job.subscribe();
+    register(job.clone());
+    persist(&job);
+
+    let worker = job.clone();
+    let handle = std::thread::spawn(move || {
+        let _slot = RunningSlot::acquire();
+        if !worker.mark_running() {
+            return;
+        }
+        match work(&worker) {
+            Ok(result) => worker.succeed(result),
+            Err(_) if worker.is_cancelled() => worker.finish(JobStatus::Cancelled, None, None),
+            Err(err) => worker.fail(format!("{err:#}")),
+        }
+    });
+    let mut workers = WORKERS.lock().unwrap();
+    workers.retain(|worker| !worker.is_finished());
+    workers.push(handle);
+    drop(workers);
+    (job, events)
+}
+
+/// Block until every job spawned by this process has finished, so a one-shot
+/// command doesn't exit while its jobs are still running.
+pub fn wait_for_spawned() {
+    let workers = std::mem::take(&mut *WORKERS.lock().unwrap());
+    for worker in workers {
+        let _ = worker.join();
+    }
+}
+
+pub fn get(id: &str) -> Option<Arc<Job>> {
+    lock_jobs().get(id).cloned()
+}
+
+/// Snapshots of every known job, newest first.
+pub fn list() -> Vec<JobSnapshot> {
+    let mut snapshots: Vec<JobSnapshot> = lock_jobs().values().map(|job| job.snapshot()).collect();
+    snapshots.sort_by_key(|snapshot| std::cmp::Reverse(snapshot.created_at));
+    snapshots
+}
+
+fn register(job: Arc<Job>) {
+    let mut jobs = lock_jobs();
+    let cutoff = Utc::now() - Duration::minutes(FINISHED_JOB_RETENTION_MINUTES);
+    jobs.retain(|_, existing| {
+        existing
+            .snapshot()
+            .finished_at
+            .is_none_or(|finished| finished > cutoff)
+    });
+    jobs.insert(job.id(), job);
+}
+
+fn lock_jobs() -> std::sync::MutexGuard<'static, HashMap<String, Arc<Job>>> {
+    JOBS.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// One of the [`MAX_RUNNING_JOBS`] concurrency slots, released on drop.
+struct RunningSlot;
+
+impl RunningSlot {
+    fn acquire() -> Self {
+        let (count, freed) = &*RUNNING_SLOTS;
+        let mut running = count
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        while *running >= MAX_RUNNING_JOBS {
+            running = freed
+                .wait(running)
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+        }
+        *running += 1;
+        Self
+    }
+}
+
+impl Drop for RunningSlot {
+    fn drop(&mut self) {
+        let (count, freed) = &*RUNNING_SLOTS;
+        let mut running = count
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        *running -= 1;
+        freed.notify_one();
+    }
+}
+
+fn jobs_path() -> Result<PathBuf> {
+    Ok(get_config_dir()?.join("jobs.json"))
+}
+
+/// Jobs recorded by earlier processes. Anything still queued or running was
+/// interrupted when that process exited, so it is reported as failed.
+fn load_persisted() -> HashMap<String, Arc<Job>> {
+    read_persisted()
+        .into_iter()
+        .map(|snapshot| {
+            let snapshot = mark_interrupted(snapshot);
+            (snapshot.id.clone(), Arc::new(Job::from_snapshot(snapshot)))
+        })
+        .collect()
+}
+
+fn mark_interrupted(mut snapshot: JobSnapshot) -> JobSnapshot {
+    if !snapshot.status.is_finished() {
+        snapshot.status = JobStatus::Failed;
+        snapshot.error = Some("interrupted: pigs exited before the job finished".into());
+        snapshot.finished_at = Some(Utc::now());
+    }
+    snapshot
+}
+
+fn read_persisted() -> Vec<JobSnapshot> {
+    if cfg!(test) {
+        return Vec::new();
+    }
+    jobs_path()
+        .ok()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Merge this job into `~/.pigs/jobs.json`, keeping entries written by other
+/// pigs processes and dropping finished jobs past their retention.
+fn persist(job: &Job) {
+    if cfg!(test) {
+        return;
+    }
+    let _persisting = PERSIST
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    let snapshot = job.snapshot();
+    let cutoff = Utc::now() - Duration::minutes(FINISHED_JOB_RETENTION_MINUTES);
+    let mut snapshots: Vec<JobSnapshot> = read_persisted()
+        .into_iter()
+        .filter(|existing| existing.id != snapshot.id)
+        .filter(|existing| {
+            existing
+                .finished_at
+                .is_none_or(|finished| finished > cutoff)
+        })
+        .collect();
+    snapshots.push(snapshot);
+
+    let result = jobs_path().and_then(|path| {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let content = serde_json::to_string_pretty(&snapshots)?;
+        // Rename a finished sibling file into place, so another pigs process
+        // never reads a half-written file
+        let tmp_path = path.with_extension(format!("json.{}.tmp", std::process::id()));
+        std::fs::write(&tmp_path, content)?;
+        std::fs::rename(&tmp_path, &path)?;
+        Ok(())
+    });
+    if let Err(err) = result {
+        eprintln!("Warning: failed to record job state: {err}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn wait_for_finish(job: &Job) -> JobSnapshot {
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(10);
+        loop {
+            let snapshot = job.snapshot();
+            if snapshot.status.is_finished() {
+                return snapshot;
+            }
+            assert!(std::time::Instant::now() < deadline, "job never finished");
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn steps_close_when_the_next_one_starts() {
+        let (job, mut events) = spawn("create", None, |job| {
+            job.step("branching");
+            job.step("worktree-add");
+            Ok(json!({ "name": "feature" }))
+        });
+
+        let snapshot = wait_for_finish(&get(&job.id()).unwrap());
+        assert_eq!(snapshot.status, JobStatus::Succeeded);
+        assert_eq!(snapshot.steps.len(), 2);
+        assert!(snapshot.steps.iter().all(|step| step.finished_at.is_some()));
+        assert_eq!(snapshot.result, Some(json!({ "name": "feature" })));
+
+        let kinds: Vec<&str> = std::iter::from_fn(|| events.try_recv().ok())
+            .map(|event| event.kind)
+            .collect();
+        assert_eq!(kinds, ["started", "step", "step", "succeeded"]);
+    }
+
+    #[test]
+    fn running_jobs_stop_at_the_next_cancellation_check() {
+        let (release, wait) = std::sync::mpsc::channel::<()>();
+        let (job, _) = spawn("sync", None, move |job| {
+            job.step("first");
+            wait.recv().unwrap();
+            job.check_cancelled()?;
+            job.step("second");
+            Ok(json!({}))
+        });
+        assert!(job.cancel());
+        release.send(()).unwrap();
+
+        let snapshot = wait_for_finish(&job);
+        assert_eq!(snapshot.status, JobStatus::Cancelled);
+        assert!(snapshot.steps.iter().all(|step| step.name != "second"));
+        assert!(!job.cancel());
+    }
+
+    #[test]
+    fn failures_carry_the_error_message() {
+        let (job, _) = spawn("create", None, |_| bail!("boom"));
+
+        let snapshot = wait_for_finish(&job);
+        assert_eq!(snapshot.status, JobStatus::Failed);
+        assert_eq!(snapshot.error.as_deref(), Some("boom"));
+        assert!(snapshot.result.is_none());
+    }
+
+    #[test]
+    fn unfinished_jobs_from_a_previous_run_are_marked_interrupted() {
+        let mut running = Job::new("create", None).snapshot();
+        running.status = JobStatus::Running;
+        let restored = mark_interrupted(running);
+        assert_eq!(restored.status, JobStatus::Failed);
+        assert!(restored.finished_at.is_some());
+
+        let mut finished = Job::new("create", None).snapshot();
+        finished.status = JobStatus::Succeeded;
+        assert!(mark_interrupted(finished).error.is_none());
+    }
+}
@@ -0,0 +1,362 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Mutex;
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+
+use crate::git::resolve_base_ref;
+
+/// Labels already computed in this process, by worktree path, with the state
+/// they were computed for. The dashboard asks for every worktree's labels on
+/// each refresh; this skips the merge-base and diff until something changes.
+static CACHE: Lazy<Mutex<LabelCache>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+type LabelCache = HashMap<PathBuf, (String, Vec<ChangeLabel>)>;
+
+/// Automatic label derived from the set of files a worktree has changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ChangeLabel {
+    Migrations,
+    PublicApi,
+    Dependencies,
+    TestsOnly,
+    DocsOnly,
+}
+
+impl ChangeLabel {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Migrations => "migrations",
+            Self::PublicApi => "public-api",
+            Self::Dependencies => "dependencies",
+            Self::TestsOnly => "tests-only",
+            Self::DocsOnly => "docs-only",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "migrations" => Some(Self::Migrations),
+            "public-api" => Some(Self::PublicApi),
+            "dependencies" => Some(Self::Dependencies),
+            "tests-only" => Some(Self::TestsOnly),
+            "docs-only" => Some(Self::DocsOnly),
+            _ => None,
+        }
+    }
+}
+
+/// Compute labels for a worktree by diffing it (including uncommitted changes)
+/// against the merge-base with its base branch. Returns an empty list when the
+/// worktree is missing or git fails, so callers can treat labels as best-effort.
+/// Results are reused while HEAD, the base branch, and the set of uncommitted
+/// changes stay the same.
+pub fn detect_worktree_labels(worktree_path: &Path) -> Vec<ChangeLabel> {
+    if !worktree_path.exists() {
+        return Vec::new();
+    }
+    let Some(base_ref) = resolve_base_ref(worktree_path) else {
+        return Vec::new();
+    };
+
+    let key = cache_key(worktree_path, &base_ref);
+    if let Some(key) = &key
+        && let Some((cached, labels)) = lock_cache().get(worktree_path)
+        && cached == key
+    {
+        return labels.clone();
+    }
+
+    let labels = classify_paths(&changed_paths(worktree_path, &base_ref));
+    if let Some(key) = key {
+        lock_cache().insert(worktree_path.to_path_buf(), (key, labels.clone()));
+    }
+    labels
+}
+
+/// What the labels of a worktree depend on: the commits of HEAD and the base
+/// branch, and which tracked files have uncommitted changes. Much cheaper to
+/// read than the diff itself.
+fn cache_key(worktree_path: &Path, base_ref: &str) -> Option<String> {
+    let commits = run_git(worktree_path, &["rev-parse", "HEAD", base_ref])?;
+    let status = run_git(
+        worktree_path,
+        &["status", "--porcelain", "--untracked-files=no"],
+    )?;
+    Some(format!("{commits}{status}"))
+}
+
+fn lock_cache() -> std::sync::MutexGuard<'static, LabelCache> {
+    CACHE
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// List paths changed in a worktree relative to the merge-base with
+/// `base_ref`, including staged and unstaged edits.
+fn changed_paths(worktree_path: &Path, base_ref: &str) -> Vec<String> {
+    let Some(merge_base) = run_git(worktree_path, &["merge-base", base_ref, "HEAD"]) else {
+        return Vec::new();
+    };
+
+    run_git(worktree_path, &["diff", "--numstat", merge_base.trim()])
+        .map(|output| parse_numstat(&output))
+        .unwrap_or_default()
+}
+
+fn run_git(worktree_path: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git")
+        .current_dir(worktree_path)
+        .args(args)
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    Some(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Extract file paths from `git diff --numstat` output. Renames are reported
+/// as `old => new` (optionally inside braces); the new path is kept.
+pub fn parse_numstat(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.splitn(3, '\t');
+            let _added = parts.next()?;
+            let _removed = parts.next()?;
+            let path = parts.next()?.trim();
+            if path.is_empty() {
+                return None;
+            }
+            Some(resolve_rename(path))
+        })
+        .collect()
+}
+
+fn resolve_rename(path: &str) -> String {
+    if let (Some(open), Some(close)) = (path.find('{'), path.find('}'))
+        && open < close
+        && let Some((_, new)) = path[open + 1..close].split_once(" => ")
+    {
+        let joined = format!("{}{}{}", &path[..open], new, &path[close + 1..]);
+        return joined.replace("//", "/");
+    }
+
+    match path.split_once(" => ") {
+        Some((_, new)) => new.to_string(),
+        None => path.to_string(),
+    }
+}
+
+/// Classify a set of changed paths into labels. Labels are returned sorted and
+/// deduplicated. `tests-only` and `docs-only` require every path to match.
+pub fn classify_paths(paths: &[String]) -> Vec<ChangeLabel> {
+    let mut labels = Vec::new();
+    if paths.is_empty() {
+        return labels;
+    }
+
+    if paths.iter().any(|p| is_migration_path(p)) {
+        labels.push(ChangeLabel::Migrations);
+    }
+    if paths.iter().any(|p| is_public_api_path(p)) {
+        labels.push(ChangeLabel::PublicApi);
+    }
+    if paths.iter().any(|p| is_dependency_path(p)) {
+        labels.push(ChangeLabel::Dependencies);
+    }
+    if paths.iter().all(|p| is_test_path(p)) {
+        labels.push(ChangeLabel::TestsOnly);
+    }
+    if paths.iter().all(|p| is_doc_path(p)) {
+        labels.push(ChangeLabel::DocsOnly);
+    }
+
+    labels.sort();
+    labels.dedup();
+    labels
+}
+
+fn components(path: &str) -> impl Iterator<Item = String> + '_ {
+    path.split('/').map(str::to_ascii_lowercase)
+}
+
+fn dir_components(path: &str) -> Vec<String> {
+    let mut parts: Vec<String> = components(path).collect();
+    parts.pop();
+    parts
+}
+
+fn file_name(path: &str) -> String {
+    path.rsplit('/').next().unwrap_or(path).to_ascii_lowercase()
+}
+
+fn is_migration_path(path: &str) -> bool {
+    components(path).any(|c| c == "migrations" || c == "migrate" || c == "migration")
+}
+
+fn is_public_api_path(path: &str) -> bool {
+    let name = file_name(path);
+    if path == "src/lib.rs" || name.ends_with(".proto") || name.ends_with(".d.ts") {
+        return true;
+    }
+    if name.starts_with("openapi.") || name.starts_with("swagger.") || name == "schema.graphql" {
+        return true;
+    }
+    components(path).any(|c| c == "api" || c == "include")
+}
+
+fn is_dependency_path(path: &str) -> bool {
+    matches!(
+        file_name(path).as_str(),
+        "cargo.toml"
+            | "cargo.lock"
+            | "package.json"
+            | "package-lock.json"
+            | "yarn.lock"
+            | "pnpm-lock.yaml"
+            | "go.mod"
+            | "go.sum"
+            | "requirements.txt"
+            | "pyproject.toml"
+            | "poetry.lock"
+            | "gemfile"
+            | "gemfile.lock"
+    )
+}
+
+fn is_test_path(path: &str) -> bool {
+    let name = file_name(path);
+    let dir_matches = dir_components(path).iter().any(|c| {
+        matches!(
+            c.as_str(),
+            "test" | "tests" | "spec" | "__tests__" | "snapshots"
+        )
+    });
+    dir_matches
+        || name.starts_with("test_")
+        || name.contains("_test.")
+        || name.contains(".test.")
+        || name.contains(".spec.")
+        || name.ends_with(".snap")
+}
+
+fn is_doc_path(path: &str) -> bool {
+    let name = file_name(path);
+    if dir_components(path)
+        .iter()
+        .any(|c| c == "docs" || c == "doc")
+    {
+        return true;
+    }
+    name.ends_with(".md")
+        || name.ends_with(".mdx")
+        || name.ends_with(".rst")
+        || name.ends_with(".adoc")
+        || name.starts_with("license")
+        || name.starts_with("changelog")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paths(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn classifies_docs_only_changes() {
+        let labels = classify_paths(&paths(&["README.md", "docs/setup.txt"]));
+        assert_eq!(labels, vec![ChangeLabel::DocsOnly]);
+    }
+
+    #[test]
+    fn classifies_tests_only_changes() {
+        let labels = classify_paths(&paths(&[
+            "tests/integration.rs",
+            "src/utils_test.go",
+            "web/app.spec.ts",
+        ]));
+        assert_eq!(labels, vec![ChangeLabel::TestsOnly]);
+    }
+
+    #[test]
+    fn mixed_changes_get_any_match_labels_only() {
+        let labels = classify_paths(&paths(&[
+            "db/migrations/0001_init.sql",
+            "src/lib.rs",
+            "Cargo.toml",
+            "tests/integration.rs",
+        ]));
+        assert_eq!(
+            labels,
+            vec![
+                ChangeLabel::Migrations,
+                ChangeLabel::PublicApi,
+                ChangeLabel::Dependencies
+            ]
+        );
+    }
+
+    #[test]
+    fn empty_diff_has_no_labels() {
+        assert!(classify_paths(&[]).is_empty());
+    }
+
+    #[test]
+    fn labels_follow_new_commits_and_uncommitted_changes() {
+        let repo = tempfile::tempdir().unwrap();
+        let git = |args: &[&str]| {
+            assert!(run_git(repo.path(), args).is_some(), "git {args:?} failed");
+        };
+        git(&["init", "-q", "-b", "main"]);
+        git(&["config", "user.name", "Dev"]);
+        git(&["config", "user.email", "dev@example.com"]);
+        std::fs::write(repo.path().join("README.md"), "# Repo").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-q", "-m", "Initial commit"]);
+        git(&["checkout", "-q", "-b", "feature"]);
+        assert!(detect_worktree_labels(repo.path()).is_empty());
+
+        std::fs::write(repo.path().join("README.md"), "# Changed").unwrap();
+        assert_eq!(
+            detect_worktree_labels(repo.path()),
+            vec![ChangeLabel::DocsOnly]
+        );
+
+        git(&["commit", "-q", "-am", "Docs"]);
+        std::fs::write(repo.path().join("Cargo.toml"), "[package]").unwrap();
+        git(&["add", "Cargo.toml"]);
+        assert_eq!(
+            detect_worktree_labels(repo.path()),
+            vec![ChangeLabel::Dependencies]
+        );
+        // Unchanged since the last call, so served from the cache
+        assert!(lock_cache().contains_key(repo.path()));
+        assert_eq!(
+            detect_worktree_labels(repo.path()),
+            vec![ChangeLabel::Dependencies]
+        );
+    }
+
+    #[test]
+    fn parses_numstat_with_renames() {
+        let output = "3\t1\tsrc/main.rs\n-\t-\tassets/logo.png\n0\t0\tsrc/{old => new}/mod.rs\n1\t1\ta.md => b.md\n";
+        assert_eq!(
+            parse_numstat(output),
+            vec![
+                "src/main.rs".to_string(),
+                "assets/logo.png".to_string(),
+                "src/new/mod.rs".to_string(),
+                "b.md".to_string(),
+            ]
+        );
+    }
+}
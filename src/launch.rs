@@ -0,0 +1,54 @@
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::{Result, bail};
+
+use crate::devenv;
+use crate::state::WorktreeInfo;
+
+/// The editor command: the configured one, then `PIGS_DASHBOARD_EDITOR`, then
+/// `EDITOR`, then VS Code.
+pub fn editor_command(override_cmd: Option<String>) -> String {
+    override_cmd
+        .filter(|s| !s.trim().is_empty())
+        .or_else(|| std::env::var("PIGS_DASHBOARD_EDITOR").ok())
+        .or_else(|| std::env::var("EDITOR").ok())
+        .unwrap_or_else(|| "code".to_string())
+}
+
+/// The shell command: the configured one, then `PIGS_DASHBOARD_SHELL`, then
+/// `SHELL`, then zsh.
+pub fn shell_command(override_cmd: Option<String>) -> String {
+    override_cmd
+        .filter(|s| !s.trim().is_empty())
+        .or_else(|| std::env::var("PIGS_DASHBOARD_SHELL").ok())
+        .or_else(|| std::env::var("SHELL").ok())
+        .unwrap_or_else(|| "/bin/zsh".to_string())
+}
+
+/// A shell started in the worktree with its direnv/mise environment.
+pub fn shell(info: &WorktreeInfo, shell_override: Option<String>) -> Result<Command> {
+    let mut cmd = split_command(&shell_command(shell_override), "Shell")?;
+    cmd.current_dir(&info.path);
+    devenv::apply(&mut cmd, &info.path);
+    Ok(cmd)
+}
+
+/// The editor, opening `path`.
+pub fn editor(path: &Path, editor_override: Option<String>) -> Result<Command> {
+    let mut cmd = split_command(&editor_command(editor_override), "Editor")?;
+    cmd.arg(path);
+    Ok(cmd)
+}
+
+fn split_command(command: &str, what: &str) -> Result<Command> {
+    let Ok(mut parts) = shell_words::split(command) else {
+        bail!("Failed to parse {} command", what.to_lowercase());
+    };
+    if parts.is_empty() {
+        bail!("{what} command is empty");
+    }
+    let mut cmd = Command::new(parts.remove(0));
+    cmd.args(parts);
+    Ok(cmd)
+}
@@ -1,5 +1,5 @@
 use anyhow::{Context, Result};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 const LINEAR_API_URL: &str = "https://api.linear.app/graphql";
 
@@ -9,7 +9,7 @@ pub struct LinearIssue {
     pub branch_name: String,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize)]
 pub struct LinearIssueSummary {
     pub identifier: String,
     pub title: String,
@@ -26,6 +26,7 @@ pub fn is_linear_task_id(s: &str) -> bool {
 }
 
 pub fn fetch_issue(identifier: &str) -> Result<LinearIssue> {
+    crate::network::ensure_online("Linear")?;
     let api_key = std::env::var("LINEAR_API_KEY")
         .context("LINEAR_API_KEY environment variable is not set")?;
 
@@ -97,6 +98,7 @@ struct IssueState {
 }
 
 pub fn start_issue(identifier: &str) -> Result<()> {
+    crate::network::ensure_online("Linear")?;
     let api_key = std::env::var("LINEAR_API_KEY")
         .context("LINEAR_API_KEY environment variable is not set")?;
 
@@ -185,6 +187,7 @@ pub fn start_issue(identifier: &str) -> Result<()> {
 }
 
 pub fn fetch_my_issues() -> Result<Vec<LinearIssueSummary>> {
+    crate::network::ensure_online("Linear")?;
     let api_key = std::env::var("LINEAR_API_KEY")
         .context("LINEAR_API_KEY environment variable is not set")?;
 
@@ -1,28 +1,71 @@
 use anyhow::Result;
-use clap::{Parser, Subcommand};
+use clap::{CommandFactory, Parser, Subcommand};
 use clap_complete::Shell;
 
+mod activity;
+mod artifacts;
 mod claude;
 mod codex;
 mod commands;
 mod completions;
+mod container;
 mod dashboard;
+mod detect;
+mod devenv;
+mod external;
+mod faults;
 mod git;
+mod github;
+mod hooks;
 mod input;
+mod jobs;
+mod labels;
+mod launch;
 mod linear;
+mod names;
+mod network;
+mod plain_text;
+mod pool;
+mod problems;
+mod prompts;
+mod recorder;
+mod relink;
+mod reminders;
+mod repo_lock;
+mod session_tail;
+mod stack;
 mod state;
+mod transcripts;
+mod trust;
 mod utils;
+mod webhooks;
 
 use commands::{
-    handle_add, handle_checkout, handle_clean, handle_complete_agents, handle_complete_from,
-    handle_complete_linear, handle_config, handle_create, handle_dashboard, handle_delete,
-    handle_dir, handle_linear, handle_list, handle_open, handle_rename, handle_review,
+    CopyOverride, DiffOptions, DirtyChanges, InitShare, OpenWith, RemoteBranch, TidyOptions,
+    handle_add, handle_api, handle_archive, handle_artifacts, handle_bench, handle_checkout,
+    handle_cherry_pick, handle_clean, handle_complete_agents, handle_complete_from,
+    handle_complete_linear, handle_complete_tasks, handle_config, handle_config_get,
+    handle_config_list, handle_config_set, handle_config_unset, handle_create, handle_dashboard,
+    handle_delete, handle_diff, handle_diff_snapshots, handle_dir, handle_doctor, handle_du,
+    handle_export, handle_focus, handle_gc, handle_github, handle_import, handle_init,
+    handle_linear, handle_list, handle_log, handle_merge, handle_note, handle_open_with,
+    handle_pool, handle_pr, handle_recent, handle_remind, handle_rename, handle_replay_prompts,
+    handle_report, handle_resume, handle_review, handle_sessions, handle_shell_init, handle_stats,
+    handle_switch, handle_sync, handle_tag, handle_tidy, handle_token_create, handle_token_list,
+    handle_token_revoke, handle_trace, handle_trust, handle_tui, handle_watch,
 };
+use state::TokenScope;
 
 #[derive(Parser)]
 #[command(name = "pigs")]
 #[command(about = "Manage AI agent sessions with git worktrees", long_about = None)]
 struct Cli {
+    /// Skip network integrations (Linear, GitHub, git fetches)
+    #[arg(long, global = true, env = "PIGS_OFFLINE", value_parser = clap::builder::BoolishValueParser::new())]
+    offline: bool,
+    /// Skip lifecycle hooks (post_create, pre_open, pre_sync, post_sync, pre_delete, post_delete)
+    #[arg(long, global = true, env = "PIGS_NO_HOOKS", value_parser = clap::builder::BoolishValueParser::new())]
+    no_hooks: bool,
     #[command(subcommand)]
     command: Commands,
 }
@@ -46,16 +89,64 @@ enum Commands {
         #[arg(last = true)]
         agent_args: Vec<String>,
     },
+    /// Create a new git worktree from a GitHub issue
+    Github {
+        /// GitHub issue number or URL (interactive selection of your issues if not provided)
+        issue: Option<String>,
+        /// Create from an existing worktree or branch instead of the current branch
+        #[arg(long)]
+        from: Option<String>,
+        /// Automatically open the worktree after creation
+        #[arg(short = 'y')]
+        yes: bool,
+        /// Select agent at runtime by configured agent name
+        #[arg(short = 'a', long)]
+        agent: Option<String>,
+        /// Extra arguments passed to the agent command
+        #[arg(last = true)]
+        agent_args: Vec<String>,
+    },
     /// Create a new git worktree
     Create {
-        /// Name for the worktree (random BIP39 word if not provided)
-        name: Option<String>,
+        /// Name for the worktree (random BIP39 word if not provided); several
+        /// names create one worktree each
+        names: Vec<String>,
+        /// Create this many worktrees with generated names
+        #[arg(long, conflicts_with_all = ["names", "json", "track", "yes"])]
+        count: Option<usize>,
         /// Create from an existing worktree or branch instead of the current branch
         #[arg(long)]
         from: Option<String>,
+        /// Stack the new branch on another worktree's branch (restack with `pigs sync`)
+        #[arg(long, conflicts_with = "from")]
+        stack_on: Option<String>,
+        /// Check out a remote branch (e.g. origin/feature-x) with upstream tracking, fetching it if needed
+        #[arg(long, value_name = "REMOTE/BRANCH", conflicts_with_all = ["from", "stack_on", "json"])]
+        track: Option<String>,
         /// Automatically open the worktree after creation
         #[arg(short = 'y')]
         yes: bool,
+        /// Print progress as JSON lines instead of prompting to open the worktree
+        #[arg(long, conflicts_with = "yes")]
+        json: bool,
+        /// Pull the repository's devcontainer prebuild image and run agents in it
+        #[arg(long, conflicts_with = "json")]
+        container: bool,
+        /// Claim a ready-made worktree from the pool instead of creating one
+        #[arg(long, conflicts_with_all = ["json", "from", "stack_on", "track"])]
+        fast: bool,
+        /// Apply this patch to the new worktree as its starting point (`-` reads stdin)
+        #[arg(long, value_name = "FILE", conflicts_with = "json")]
+        apply_patch: Option<std::path::PathBuf>,
+        /// Don't copy the repository's configured `copy_files`
+        #[arg(long, conflicts_with = "json")]
+        no_copy: bool,
+        /// Also copy this file from the repository root (repeatable)
+        #[arg(long, value_name = "FILE", conflicts_with = "json")]
+        copy: Vec<String>,
+        /// Create the worktree at this directory instead of next to the repository
+        #[arg(long, value_name = "DIR", conflicts_with_all = ["json", "count", "fast"])]
+        path: Option<std::path::PathBuf>,
         /// Select agent at runtime by configured agent name
         #[arg(short = 'a', long)]
         agent: Option<String>,
@@ -65,11 +156,14 @@ enum Commands {
     },
     /// Checkout a branch or pull request into a worktree
     Checkout {
-        /// Branch name or pull request number
+        /// Branch name, pull request number, or pull/merge request URL
         target: Option<String>,
         /// Automatically open the worktree after creation
         #[arg(short = 'y')]
         yes: bool,
+        /// Only check out the files the pull request touches (requires `gh`)
+        #[arg(long)]
+        sparse: bool,
         /// Select agent at runtime by configured agent name
         #[arg(short = 'a', long)]
         agent: Option<String>,
@@ -92,6 +186,15 @@ enum Commands {
         /// Select agent at runtime by configured agent name
         #[arg(short = 'a', long)]
         agent: Option<String>,
+        /// Open a shell in the worktree instead of the agent
+        #[arg(long, conflicts_with_all = ["editor", "agent", "agent_args"])]
+        shell: bool,
+        /// Open the worktree in your editor instead of the agent
+        #[arg(long, conflicts_with_all = ["agent", "agent_args"])]
+        editor: bool,
+        /// Save the agent's terminal transcript, viewable from the dashboard API
+        #[arg(long, conflicts_with_all = ["shell", "editor"])]
+        record: bool,
         /// Extra arguments passed to the agent command
         #[arg(last = true)]
         agent_args: Vec<String>,
@@ -101,13 +204,80 @@ enum Commands {
         /// Name of the worktree to delete (current if not provided)
         name: Option<String>,
         /// Delete all managed worktrees
-        #[arg(long)]
+        #[arg(long, conflicts_with_all = ["name", "select"])]
         all: bool,
+        /// Only consider worktrees of this repository (with --all or --select)
+        #[arg(long)]
+        repo: Option<String>,
+        /// Choose several worktrees to delete from a checkbox list
+        #[arg(short = 's', long, conflicts_with = "name")]
+        select: bool,
+        /// Delete even if the worktree has uncommitted changes, discarding them
+        #[arg(short, long, conflicts_with_all = ["stash", "patch", "archive"])]
+        force: bool,
+        /// Save uncommitted changes to a named git stash before deleting
+        #[arg(long, conflicts_with_all = ["patch", "archive"])]
+        stash: bool,
+        /// Save uncommitted changes to a patch file under ~/.pigs/patches before deleting
+        #[arg(long, conflicts_with = "archive")]
+        patch: bool,
+        /// Archive the branch and any uncommitted changes to ~/.pigs/archives before deleting
+        #[arg(long)]
+        archive: bool,
+        /// Also delete the branch from origin once it is merged
+        #[arg(long, conflicts_with_all = ["all", "select"])]
+        remote: bool,
+        /// Also delete the branch from origin even if it isn't merged
+        #[arg(long, conflicts_with_all = ["all", "select", "remote"])]
+        remote_force: bool,
+    },
+    /// Save a worktree's branch and uncommitted changes to ~/.pigs/archives
+    Archive {
+        /// Name of the worktree (interactive selection if not provided)
+        name: Option<String>,
+    },
+    /// Show changes in a worktree
+    Diff {
+        /// Name of the worktree (interactive selection if not provided)
+        name: Option<String>,
+        /// Diff against the merge-base with the base branch
+        #[arg(long, conflicts_with_all = ["staged", "since_session"])]
+        base: bool,
+        /// Only show staged changes
+        #[arg(long, conflicts_with = "since_session")]
+        staged: bool,
+        /// Show changes made since the given agent session started
+        #[arg(long, value_name = "SESSION_ID")]
+        since_session: Option<String>,
+        /// Show a diffstat instead of the full patch
+        #[arg(long)]
+        stat: bool,
+        /// Print directly instead of piping through git's pager
+        #[arg(long)]
+        no_pager: bool,
+    },
+    /// Show what changed in a worktree between two session snapshots
+    DiffSnapshots {
+        /// Name of the worktree
+        name: String,
+        /// Earlier checkpoint: a session id (or unique prefix), or "now"
+        from: String,
+        /// Later checkpoint: a session id (or unique prefix), or "now"
+        to: String,
+        /// Show a diffstat instead of the full patch
+        #[arg(long)]
+        stat: bool,
+        /// Print directly instead of piping through git's pager
+        #[arg(long)]
+        no_pager: bool,
     },
     /// Add current worktree to pigs management
     Add {
         /// Name for the worktree (defaults to current branch name)
         name: Option<String>,
+        /// Find this repository's untracked worktrees and pick ones to adopt
+        #[arg(long, conflicts_with = "name")]
+        scan: bool,
     },
     /// Rename a worktree
     Rename {
@@ -115,25 +285,250 @@ enum Commands {
         old_name: String,
         /// New name for the worktree
         new_name: String,
+        /// Also rename the git branch to the new name
+        #[arg(long)]
+        rename_branch: bool,
+        /// Also move the directory to where the new name would be created
+        #[arg(long)]
+        move_dir: bool,
     },
     /// List all active agent sessions
     List {
         /// Output as JSON
         #[arg(long)]
         json: bool,
+        /// Only show worktrees with this automatic change label
+        /// (migrations, public-api, dependencies, tests-only, docs-only)
+        #[arg(long)]
+        label: Option<String>,
+        /// Only show worktrees with this tag (set with `pigs tag`)
+        #[arg(long)]
+        tag: Option<String>,
+    },
+    /// List worktrees by last activity, most recent first
+    Recent {
+        /// Show at most this many worktrees
+        #[arg(short = 'n', long, default_value_t = 10)]
+        limit: usize,
+    },
+    /// Show a live table of all worktrees with git status, agents, and problems
+    Watch {
+        /// Seconds between refreshes
+        #[arg(short, long, default_value_t = 2)]
+        interval: u64,
+        /// Print the table once and exit
+        #[arg(long)]
+        once: bool,
     },
+    /// Browse worktrees full-screen and open, sync, or delete them with keys
+    Tui,
     /// Clean up invalid worktrees from state
-    Clean,
+    Clean {
+        /// Remove worktrees whose branch is merged into the base branch, or whose PR was merged
+        #[arg(long)]
+        merged: bool,
+        /// Base branch to check against (default: the repository's base branch)
+        #[arg(long, requires = "merged")]
+        base: Option<String>,
+        /// Remove merged worktrees or re-link moved repositories without asking
+        #[arg(short = 'y')]
+        yes: bool,
+        /// Print what would be removed and why, without changing anything
+        #[arg(long)]
+        dry_run: bool,
+        /// Also run `git worktree prune` in each repository
+        #[arg(long, conflicts_with = "merged")]
+        prune_git: bool,
+    },
+    /// Remove old session logs and report the disk space reclaimed
+    Gc {
+        /// Remove logs last written longer ago than this, e.g. 30d, 2w
+        #[arg(long, default_value = "30d")]
+        older_than: String,
+        /// Also remove Claude and Codex session files of tracked worktrees
+        #[arg(long)]
+        agent_logs: bool,
+        /// Print what would be removed, without removing anything
+        #[arg(long)]
+        dry_run: bool,
+        /// Delete Claude and Codex session files without asking
+        #[arg(short = 'y')]
+        yes: bool,
+    },
+    /// Show the disk space each worktree uses, largest first
+    Du {
+        /// Print sizes as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Apply commits from one worktree's branch onto another worktree or the base
+    CherryPick {
+        /// Worktree the commits come from
+        source: String,
+        /// A commit or range on the source branch, e.g. HEAD~2..HEAD
+        commits: String,
+        /// Worktree to apply them to (default: the main checkout)
+        dest: Option<String>,
+    },
+    /// Merge a worktree's branch into its base branch in the main checkout
+    Merge {
+        /// Name of the worktree (interactive selection if not provided)
+        name: Option<String>,
+        /// Squash the branch into a single commit
+        #[arg(long)]
+        squash: bool,
+        /// Delete the worktree (and its branch) once merged
+        #[arg(short = 'd', long)]
+        delete: bool,
+    },
+    /// Push a worktree's branch and open a GitHub pull request for it
+    Pr {
+        /// Name of the worktree (interactive selection if not provided)
+        name: Option<String>,
+        /// Pull request title (default: from the Linear issue or the branch name)
+        #[arg(long)]
+        title: Option<String>,
+        /// Use the latest agent session's final reply as the description
+        #[arg(long)]
+        session_summary: bool,
+        /// Open the pull request as a draft
+        #[arg(long)]
+        draft: bool,
+    },
+    /// Update a worktree onto its base branch, and restack stacked worktrees
+    Sync {
+        /// Update this worktree onto its base branch, then restack the worktrees
+        /// stacked on it (default: restack every stack)
+        name: Option<String>,
+        /// Merge the base branch in instead of rebasing onto it
+        #[arg(long, requires = "name")]
+        merge: bool,
+    },
+    /// Squash a worktree's commits into a clean history before opening a PR
+    Tidy {
+        /// Name of the worktree (interactive selection if not provided)
+        name: Option<String>,
+        /// Branch or ref the worktree forked from (default: the repository's base branch)
+        #[arg(long)]
+        base: Option<String>,
+        /// Ask the agent to write the squashed commit messages
+        #[arg(long)]
+        agent_message: bool,
+        /// Agent to ask for commit messages, by configured name
+        #[arg(short = 'a', long, requires = "agent_message")]
+        agent: Option<String>,
+        /// Print the squash plan without rewriting the branch
+        #[arg(long)]
+        dry_run: bool,
+        /// Rewrite the branch without asking for confirmation
+        #[arg(short = 'y')]
+        yes: bool,
+    },
+    /// Re-send the prompts recorded in one worktree to a fresh agent in another
+    ReplayPrompts {
+        /// Worktree whose recorded prompts are replayed
+        source: String,
+        /// Worktree that receives the prompts
+        target: String,
+        /// Select agent at runtime by configured agent name
+        #[arg(short = 'a', long)]
+        agent: Option<String>,
+        /// Seconds of agent silence to wait before sending the next prompt
+        #[arg(long, default_value_t = 10)]
+        idle_secs: u64,
+        /// Print the prompt sequence without launching an agent
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Compare agents, models, and outcomes across a group of worktrees
+    Report {
+        /// Group name: matches the worktree named <group> and every <group>-* worktree
+        group: String,
+        /// Render an HTML page instead of Markdown
+        #[arg(long)]
+        html: bool,
+        /// Write the report to a file instead of stdout
+        #[arg(short = 'o', long)]
+        output: Option<std::path::PathBuf>,
+    },
+    /// Summarize worktrees created and deleted, lifetimes, and agent sessions
+    Stats {
+        /// Weeks of created/deleted counts to show
+        #[arg(short = 'w', long, default_value_t = 8)]
+        weeks: usize,
+        /// Flag worktrees with no commits or sessions for this many days
+        #[arg(long, default_value_t = 14)]
+        stale_days: u32,
+        /// Print the summary as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Collect worktrees, sessions, prompts, PRs, and verification runs for an issue
+    Trace {
+        /// Issue id, e.g. ENG-123, as it appears in worktree or branch names
+        issue: String,
+        /// Print JSON instead of Markdown
+        #[arg(long)]
+        json: bool,
+        /// Write the trace to a file instead of stdout
+        #[arg(short = 'o', long)]
+        output: Option<std::path::PathBuf>,
+    },
+    /// Print pigs state as JSON, to move it to another machine with `pigs import`
+    Export {
+        /// Only export worktrees of this repository
+        #[arg(long)]
+        repo: Option<String>,
+    },
+    /// Merge state written by `pigs export` into this machine's state
+    Import {
+        /// Export file, or `-` to read it from stdin
+        file: std::path::PathBuf,
+        /// Move imported paths under one prefix to another, e.g. /home/me:/Users/me
+        #[arg(long, value_name = "OLD:NEW")]
+        rewrite_prefix: Option<String>,
+    },
+    /// Show, fill, or empty the pool of ready-made worktrees used by `create --fast`
+    Pool {
+        /// Top up every configured pool now instead of waiting for the dashboard
+        #[arg(long, conflicts_with = "drain")]
+        fill: bool,
+        /// Remove pooled worktrees and their branches
+        #[arg(long)]
+        drain: bool,
+    },
+    /// Time create/open/status/delete steps on a throwaway worktree
+    Bench {
+        /// Number of create/delete cycles to run
+        #[arg(short = 'n', long, default_value_t = 3)]
+        iterations: usize,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
     /// Get the directory path of a worktree
     Dir {
         /// Name of the worktree (interactive selection if not provided)
         name: Option<String>,
     },
+    /// Change into a worktree (needs the `shell-init` wrapper)
+    Switch {
+        /// Name of the worktree (interactive selection if not provided)
+        name: Option<String>,
+    },
+    /// Print a shell function that lets `pigs switch` change directory
+    ShellInit {
+        /// Shell to print the function for
+        #[arg(value_enum)]
+        shell: Shell,
+    },
+    /// Check tools, the state file, and tracked worktrees, and suggest fixes
+    Doctor,
     /// Generate shell completions
     Completions {
         /// Shell to generate completions for
         #[arg(value_enum)]
-        shell: Shell,
+        shell: completions::CompletionShell,
     },
     /// Output worktree info for shell completions (hidden)
     #[command(hide = true)]
@@ -148,11 +543,122 @@ enum Commands {
     /// Output configured agent names for --agent completion (hidden)
     #[command(hide = true)]
     CompleteAgents,
+    /// Output configured task names and commands for completions (hidden)
+    #[command(hide = true)]
+    CompleteTasks,
     /// Output Linear issues for shell completions (hidden)
     #[command(hide = true)]
     CompleteLinear,
-    /// Open the pigs state file in $EDITOR
-    Config,
+    /// Get or change settings, or open the pigs state file in $EDITOR
+    Config {
+        #[command(subcommand)]
+        action: Option<ConfigAction>,
+    },
+    /// Detect agent files and test commands and save them to .pigs/settings.json
+    Init {
+        /// Write the detected settings without asking for confirmation
+        #[arg(short = 'y')]
+        yes: bool,
+        /// Add .pigs/settings.json to .gitignore, keeping the settings personal
+        #[arg(long, conflicts_with = "commit")]
+        gitignore: bool,
+        /// Commit .pigs/settings.json so everyone on the repository shares it
+        #[arg(long)]
+        commit: bool,
+    },
+    /// Show the saved outputs of a worktree's verification runs
+    Artifacts {
+        /// Name of the worktree (interactive selection if not provided)
+        name: Option<String>,
+        /// Output as JSON
+        #[arg(long, conflicts_with = "markdown")]
+        json: bool,
+        /// Print a Markdown summary of the latest run of each task, for PR descriptions
+        #[arg(long)]
+        markdown: bool,
+        /// Only show runs of this task
+        #[arg(long)]
+        task: Option<String>,
+    },
+    /// List a worktree's Claude and Codex sessions, newest first
+    Sessions {
+        /// Name of the worktree (interactive selection if not provided)
+        name: Option<String>,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Resume a past Claude or Codex session in a worktree
+    Resume {
+        /// Name of the worktree (interactive selection if not provided)
+        name: Option<String>,
+        /// Id (or unique prefix) of the session to resume, instead of picking one
+        #[arg(long)]
+        session: Option<String>,
+    },
+    /// Show a worktree's activity, including the exact command each session ran
+    Log {
+        /// Name of the worktree (interactive selection if not provided)
+        name: Option<String>,
+        /// Show at most this many of the latest entries
+        #[arg(short = 'n', long, default_value_t = 20)]
+        limit: usize,
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Make a worktree the default for commands run without a worktree name
+    Focus {
+        /// Name of the worktree to focus (shows the current focus if omitted)
+        #[arg(conflicts_with_all = ["clear", "prompt"])]
+        name: Option<String>,
+        /// Stop focusing any worktree
+        #[arg(long, conflicts_with = "prompt")]
+        clear: bool,
+        /// Print the focused worktree for a shell prompt (prefixed with * when inside it)
+        #[arg(long)]
+        prompt: bool,
+    },
+    /// Add or remove tags on a worktree, e.g. `pigs tag feature +bug -wip`
+    Tag {
+        /// Name of the worktree
+        name: String,
+        /// Tags to add (`+tag`) or remove (`-tag`); shows the tags if omitted
+        #[arg(allow_hyphen_values = true)]
+        changes: Vec<String>,
+    },
+    /// Show or set a free-form note on a worktree
+    Note {
+        /// Name of the worktree (interactive selection if not provided)
+        name: Option<String>,
+        /// The note; replaces any existing one (shows the note if omitted)
+        text: Option<String>,
+        /// Remove the worktree's note
+        #[arg(long, conflicts_with = "text")]
+        clear: bool,
+    },
+    /// Attach a due date to a worktree; the dashboard notifies when it passes
+    Remind {
+        /// Name of the worktree (interactive selection if not provided)
+        name: Option<String>,
+        /// What to be reminded about
+        note: Option<String>,
+        /// Due after this long, e.g. 30m, 3h, 2d, 1w
+        #[arg(long = "in", value_name = "DURATION", conflicts_with = "at")]
+        offset: Option<String>,
+        /// Due at this local time: YYYY-MM-DD or "YYYY-MM-DD HH:MM"
+        #[arg(long)]
+        at: Option<String>,
+        /// Remove the worktree's reminder
+        #[arg(long, conflicts_with_all = ["offset", "at", "note"])]
+        clear: bool,
+    },
+    /// Allow this repository's .pigs/settings.json to set agent, shell, editor, or setup commands
+    Trust {
+        /// Forget a previous trust decision instead
+        #[arg(long)]
+        revoke: bool,
+    },
     /// Launch the embedded dashboard
     Dashboard {
         /// Bind address (default 127.0.0.1:5710)
@@ -161,11 +667,99 @@ enum Commands {
         /// Do not open the browser automatically
         #[arg(long)]
         no_browser: bool,
+        /// Show desktop notifications when agents finish or fail
+        #[arg(long)]
+        notify: bool,
+        /// Serve dashboard files from this directory instead of the built-in ones
+        #[arg(long, env = "PIGS_DASHBOARD_ASSETS")]
+        assets_dir: Option<std::path::PathBuf>,
+        /// Log filter, e.g. `debug` or `pigs=debug,info` (default: info)
+        #[arg(long, env = "PIGS_LOG", default_value = "info")]
+        log_level: String,
+        /// Write logs as JSON lines
+        #[arg(long)]
+        log_json: bool,
+        #[command(subcommand)]
+        action: Option<DashboardAction>,
+    },
+    /// Call a dashboard API endpoint without a server and print its JSON response
+    Api {
+        /// Endpoint path, e.g. `worktrees` or `/api/worktrees/<repo>/<name>/actions`
+        endpoint: String,
+        /// HTTP method (default: POST with --json, else GET)
+        #[arg(short = 'X', long)]
+        method: Option<String>,
+        /// Request body as JSON, or `-` to read it from stdin
+        #[arg(long)]
+        json: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum DashboardAction {
+    /// Manage scoped dashboard tokens
+    Token {
+        #[command(subcommand)]
+        action: TokenAction,
+    },
+}
+
+#[derive(Subcommand)]
+enum TokenAction {
+    /// Mint a token and print it
+    Create {
+        /// What the token may do
+        #[arg(long, value_enum, default_value = "read")]
+        scope: TokenScope,
+        /// Name requests made with the token are attributed to
+        #[arg(long)]
+        user: Option<String>,
+    },
+    /// List tokens with their scopes
+    List,
+    /// Revoke a token, given in full or by a unique prefix
+    Revoke { token: String },
+}
+
+#[derive(Subcommand)]
+enum ConfigAction {
+    /// Print a setting's value
+    Get {
+        key: String,
+        /// Read the repository's .pigs/settings.json
+        #[arg(long)]
+        local: bool,
+    },
+    /// Set a setting (the value is parsed as JSON when possible, else as a string)
+    Set {
+        key: String,
+        value: String,
+        /// Write to the repository's .pigs/settings.json
+        #[arg(long)]
+        local: bool,
+    },
+    /// Remove a setting
+    Unset {
+        key: String,
+        /// Write to the repository's .pigs/settings.json
+        #[arg(long)]
+        local: bool,
+    },
+    /// List the settings that are set
+    List {
+        /// Read the repository's .pigs/settings.json
+        #[arg(long)]
+        local: bool,
     },
 }
 
 fn main() -> Result<()> {
+    clap_complete::CompleteEnv::with_factory(|| completions::with_dynamic_values(Cli::command()))
+        .var(completions::COMPLETE_VAR)
+        .complete();
     let cli = Cli::parse();
+    network::set_offline(cli.offline || state::PigsState::load().is_ok_and(|state| state.offline));
+    hooks::set_disabled(cli.no_hooks);
 
     match cli.command {
         Commands::Linear {
@@ -175,37 +769,269 @@ fn main() -> Result<()> {
             agent,
             agent_args,
         } => handle_linear(identifier, from, yes, agent, agent_args),
+        Commands::Github {
+            issue,
+            from,
+            yes,
+            agent,
+            agent_args,
+        } => handle_github(issue, from, yes, agent, agent_args),
         Commands::Create {
-            name,
+            names,
+            count,
+            from,
+            stack_on,
+            track,
+            yes,
+            json,
+            container,
+            fast,
+            apply_patch,
+            no_copy,
+            copy,
+            path,
+            agent,
+            agent_args,
+        } => handle_create(
+            names,
+            count,
             from,
+            stack_on,
+            track,
             yes,
+            json,
+            container,
+            fast,
             agent,
             agent_args,
-        } => handle_create(name, from, yes, agent, agent_args),
+            apply_patch,
+            CopyOverride {
+                skip_configured: no_copy,
+                extra: copy,
+            },
+            path,
+        ),
         Commands::Checkout {
             target,
             yes,
+            sparse,
             agent,
             agent_args,
-        } => handle_checkout(target, yes, agent, agent_args),
+        } => handle_checkout(target, yes, sparse, agent, agent_args),
         Commands::Review { target, base } => handle_review(target, base),
+        Commands::Diff {
+            name,
+            base,
+            staged,
+            since_session,
+            stat,
+            no_pager,
+        } => handle_diff(
+            name,
+            DiffOptions {
+                base,
+                staged,
+                since_session,
+                stat,
+                no_pager,
+            },
+        ),
+        Commands::DiffSnapshots {
+            name,
+            from,
+            to,
+            stat,
+            no_pager,
+        } => handle_diff_snapshots(name, from, to, stat, no_pager),
         Commands::Open {
             name,
             agent,
+            shell,
+            editor,
+            record,
             agent_args,
-        } => handle_open(name, agent, agent_args),
-        Commands::Delete { name, all } => handle_delete(name, all),
-        Commands::Add { name } => handle_add(name),
-        Commands::Rename { old_name, new_name } => handle_rename(old_name, new_name),
-        Commands::List { json } => handle_list(json),
-        Commands::Clean => handle_clean(),
+        } => handle_open_with(
+            name,
+            agent,
+            agent_args,
+            OpenWith::from_flags(shell, editor),
+            record,
+        ),
+        Commands::Delete {
+            name,
+            all,
+            repo,
+            select,
+            force,
+            stash,
+            patch,
+            archive,
+            remote,
+            remote_force,
+        } => handle_delete(
+            name,
+            all,
+            repo,
+            select,
+            DirtyChanges::from_flags(force, stash, patch, archive),
+            RemoteBranch::from_flags(remote, remote_force),
+        ),
+        Commands::Archive { name } => handle_archive(name),
+        Commands::Add { name, scan } => handle_add(name, scan),
+        Commands::Rename {
+            old_name,
+            new_name,
+            rename_branch,
+            move_dir,
+        } => handle_rename(old_name, new_name, rename_branch, move_dir),
+        Commands::List { json, label, tag } => handle_list(json, label, tag),
+        Commands::Recent { limit } => handle_recent(limit),
+        Commands::Watch { interval, once } => handle_watch(interval, once),
+        Commands::Tui => handle_tui(),
+        Commands::Gc {
+            older_than,
+            agent_logs,
+            dry_run,
+            yes,
+        } => handle_gc(older_than, agent_logs, dry_run, yes),
+        Commands::Du { json } => handle_du(json),
+        Commands::Clean {
+            merged,
+            base,
+            yes,
+            dry_run,
+            prune_git,
+        } => handle_clean(merged, base, yes, dry_run, prune_git),
+        Commands::CherryPick {
+            source,
+            commits,
+            dest,
+        } => handle_cherry_pick(source, commits, dest),
+        Commands::Merge {
+            name,
+            squash,
+            delete,
+        } => handle_merge(name, squash, delete),
+        Commands::Pr {
+            name,
+            title,
+            session_summary,
+            draft,
+        } => handle_pr(name, title, session_summary, draft),
+        Commands::Sync { name, merge } => handle_sync(name, merge),
+        Commands::Tidy {
+            name,
+            base,
+            agent_message,
+            agent,
+            dry_run,
+            yes,
+        } => handle_tidy(
+            name,
+            TidyOptions {
+                base,
+                agent_message,
+                agent,
+                dry_run,
+                yes,
+            },
+        ),
+        Commands::ReplayPrompts {
+            source,
+            target,
+            agent,
+            idle_secs,
+            dry_run,
+        } => handle_replay_prompts(source, target, agent, idle_secs, dry_run),
+        Commands::Report {
+            group,
+            html,
+            output,
+        } => handle_report(group, html, output),
+        Commands::Stats {
+            weeks,
+            stale_days,
+            json,
+        } => handle_stats(weeks, stale_days, json),
+        Commands::Trace {
+            issue,
+            json,
+            output,
+        } => handle_trace(issue, json, output),
+        Commands::Export { repo } => handle_export(repo),
+        Commands::Import {
+            file,
+            rewrite_prefix,
+        } => handle_import(file, rewrite_prefix),
+        Commands::Pool { fill, drain } => handle_pool(fill, drain),
+        Commands::Bench { iterations, json } => handle_bench(iterations, json),
         Commands::Dir { name } => handle_dir(name),
-        Commands::Completions { shell } => completions::handle_completions(shell),
+        Commands::Switch { name } => handle_switch(name),
+        Commands::ShellInit { shell } => handle_shell_init(shell),
+        Commands::Doctor => handle_doctor(),
+        Commands::Completions { shell } => completions::handle_completions(shell, Cli::command()),
         Commands::CompleteWorktrees { format } => commands::handle_complete_worktrees(&format),
         Commands::CompleteFrom => handle_complete_from(),
         Commands::CompleteAgents => handle_complete_agents(),
+        Commands::CompleteTasks => handle_complete_tasks(),
         Commands::CompleteLinear => handle_complete_linear(),
-        Commands::Config => handle_config(),
-        Commands::Dashboard { addr, no_browser } => handle_dashboard(addr, no_browser),
+        Commands::Config { action } => match action {
+            None => handle_config(),
+            Some(ConfigAction::Get { key, local }) => handle_config_get(key, local),
+            Some(ConfigAction::Set { key, value, local }) => handle_config_set(key, value, local),
+            Some(ConfigAction::Unset { key, local }) => handle_config_unset(key, local),
+            Some(ConfigAction::List { local }) => handle_config_list(local),
+        },
+        Commands::Init {
+            yes,
+            gitignore,
+            commit,
+        } => handle_init(yes, InitShare::from_flags(gitignore, commit)),
+        Commands::Artifacts {
+            name,
+            json,
+            markdown,
+            task,
+        } => handle_artifacts(name, task, json, markdown),
+        Commands::Log { name, limit, json } => handle_log(name, limit, json),
+        Commands::Sessions { name, json } => handle_sessions(name, json),
+        Commands::Resume { name, session } => handle_resume(name, session),
+        Commands::Focus {
+            name,
+            clear,
+            prompt,
+        } => handle_focus(name, clear, prompt),
+        Commands::Note { name, text, clear } => handle_note(name, text, clear),
+        Commands::Tag { name, changes } => handle_tag(name, changes),
+        Commands::Remind {
+            name,
+            note,
+            offset,
+            at,
+            clear,
+        } => handle_remind(name, note, offset, at, clear),
+        Commands::Trust { revoke } => handle_trust(revoke),
+        Commands::Dashboard {
+            action: Some(DashboardAction::Token { action }),
+            ..
+        } => match action {
+            TokenAction::Create { scope, user } => handle_token_create(scope, user),
+            TokenAction::List => handle_token_list(),
+            TokenAction::Revoke { token } => handle_token_revoke(token),
+        },
+        Commands::Dashboard {
+            addr,
+            no_browser,
+            notify,
+            assets_dir,
+            log_level,
+            log_json,
+            action: None,
+        } => handle_dashboard(addr, no_browser, notify, assets_dir, log_level, log_json),
+        Commands::Api {
+            endpoint,
+            method,
+            json,
+        } => handle_api(endpoint, method, json),
     }
 }
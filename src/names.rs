@@ -0,0 +1,433 @@
+use std::path::Path;
+
+use anyhow::{Context, Result, bail};
+use colored::Colorize;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand::seq::IndexedRandom;
+
+use crate::commands::focus::focused_worktree;
+use crate::git::execute_git;
+use crate::input::{explicit_confirm, fuzzy_score, smart_select};
+use crate::state::{PigsState, WorktreeInfo};
+
+/// Suffixed variants tried before giving up on a taken name.
+const MAX_SUFFIX: usize = 100;
+
+/// How `pigs create` names a worktree when no name is given, set with
+/// `"name_scheme"` in `~/.pigs/settings.json`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NameScheme {
+    /// `word` (the default) or `words:<n>`: random BIP39 words joined by `-`
+    Words(usize),
+    /// `numbered` or `numbered:<prefix>`: the first free `<prefix>-<n>`
+    Numbered(String),
+}
+
+impl Default for NameScheme {
+    fn default() -> Self {
+        Self::Words(1)
+    }
+}
+
+impl NameScheme {
+    pub fn parse(value: &str) -> Result<Self> {
+        let (kind, arg) = match value.split_once(':') {
+            Some((kind, arg)) => (kind, Some(arg)),
+            None => (value, None),
+        };
+        match (kind, arg) {
+            ("word", None) => Ok(Self::Words(1)),
+            ("words", None) => Ok(Self::Words(2)),
+            ("words", Some(count)) => match count.parse() {
+                Ok(count @ 1..=6) => Ok(Self::Words(count)),
+                _ => bail!("Invalid word count in name scheme '{value}': expected 1 to 6"),
+            },
+            ("numbered", None) => Ok(Self::Numbered("wt".to_string())),
+            ("numbered", Some(prefix)) if !prefix.is_empty() && !prefix.contains('/') => {
+                Ok(Self::Numbered(prefix.to_string()))
+            }
+            _ => {
+                bail!("Unknown name scheme '{value}'. Use word, words:<n>, or numbered[:<prefix>]")
+            }
+        }
+    }
+}
+
+/// Generate a name for a new worktree in `repo_name` that isn't already a
+/// tracked worktree, a local branch, or a worktree directory. Draws from a
+/// single RNG seeded by `PIGS_TEST_SEED` when set, so names are reproducible.
+pub fn generate(repo_name: &str, repo_path: Option<&Path>) -> Result<String> {
+    let state = PigsState::load()?;
+    let scheme = match state.name_scheme.as_deref() {
+        Some(value) => NameScheme::parse(value)?,
+        None => NameScheme::default(),
+    };
+    let root_path = match repo_path {
+        Some(path) => path.to_path_buf(),
+        None => std::env::current_dir()?,
+    };
+    let root = root_path
+        .to_str()
+        .context("Repository path contains invalid UTF-8")?;
+
+    let is_taken = |name: &str| {
+        state
+            .worktrees
+            .contains_key(&PigsState::make_key(repo_name, name))
+            || state
+                .worktree_dir(&root_path, repo_name, name)
+                .is_ok_and(|dir| dir.exists())
+            || execute_git(&[
+                "-C",
+                root,
+                "show-ref",
+                "--verify",
+                "--quiet",
+                &format!("refs/heads/{name}"),
+            ])
+            .is_ok()
+    };
+    pick(&scheme, &mut seeded_rng(), is_taken)
+}
+
+fn seeded_rng() -> StdRng {
+    match std::env::var("PIGS_TEST_SEED") {
+        Ok(seed) => StdRng::seed_from_u64(seed.parse().unwrap_or(42)),
+        Err(_) => StdRng::from_rng(&mut rand::rng()),
+    }
+}
+
+fn pick(scheme: &NameScheme, rng: &mut StdRng, is_taken: impl Fn(&str) -> bool) -> Result<String> {
+    let base = match scheme {
+        NameScheme::Numbered(prefix) => {
+            return (1..)
+                .map(|n| format!("{prefix}-{n}"))
+                .find(|name| !is_taken(name))
+                .context("Failed to generate a name");
+        }
+        NameScheme::Words(count) => {
+            let words = bip39::Language::English.word_list();
+            (0..*count)
+                .map(|_| *words.choose(rng).expect("BIP39 word list is not empty"))
+                .collect::<Vec<_>>()
+                .join("-")
+        }
+    };
+
+    std::iter::once(base.clone())
+        .chain((2..=MAX_SUFFIX).map(|n| format!("{base}-{n}")))
+        .find(|name| !is_taken(name))
+        .with_context(|| {
+            format!("Failed to generate a free name: '{base}' and its variants are taken")
+        })
+}
+
+/// How a name typed on the command line matched the tracked worktrees.
+#[derive(Debug, PartialEq, Eq)]
+enum NameMatch {
+    /// Exactly one worktree, by index
+    Unique(usize),
+    /// Several worktrees matched equally well, best first
+    Ambiguous(Vec<usize>),
+    /// Nothing matched; the worktrees with similar names, closest first
+    Missing(Vec<usize>),
+}
+
+/// Suggestions listed when a name is ambiguous or missing.
+const MAX_SUGGESTIONS: usize = 3;
+
+/// Match `query` against worktree `(name, branch)` pairs, trying in turn: an
+/// exact name, an exact branch, a prefix of either, then a fuzzy match of
+/// either. The first of those that any worktree passes decides the result.
+fn match_name(query: &str, worktrees: &[(&str, &str)]) -> NameMatch {
+    if let Some(exact) = worktrees.iter().position(|(name, _)| *name == query) {
+        return NameMatch::Unique(exact);
+    }
+
+    for tier in [Tier::Branch, Tier::Prefix, Tier::Fuzzy] {
+        let mut matches: Vec<(usize, usize)> = worktrees
+            .iter()
+            .enumerate()
+            .filter_map(|(i, (name, branch))| {
+                let branch_score = tier.score(query, branch);
+                // The name was already tried exactly above
+                let name_score = match tier {
+                    Tier::Branch => None,
+                    _ => tier.score(query, name),
+                };
+                name_score
+                    .into_iter()
+                    .chain(branch_score)
+                    .min()
+                    .map(|s| (i, s))
+            })
+            .collect();
+        matches.sort_by_key(|&(i, score)| (score, worktrees[i].0));
+        match matches.as_slice() {
+            [] => continue,
+            [(only, _)] => return NameMatch::Unique(*only),
+            _ => {
+                return NameMatch::Ambiguous(
+                    matches
+                        .into_iter()
+                        .map(|(i, _)| i)
+                        .take(MAX_SUGGESTIONS)
+                        .collect(),
+                );
+            }
+        }
+    }
+
+    // Catch typos, which the fuzzy match misses when letters are swapped or dropped
+    let mut similar: Vec<(usize, usize)> = worktrees
+        .iter()
+        .enumerate()
+        .map(|(i, (name, _))| (i, edit_distance(query, name)))
+        .filter(|&(i, distance)| distance <= (worktrees[i].0.chars().count() / 3).max(1))
+        .collect();
+    similar.sort_by_key(|&(i, distance)| (distance, worktrees[i].0));
+    NameMatch::Missing(
+        similar
+            .into_iter()
+            .map(|(i, _)| i)
+            .take(MAX_SUGGESTIONS)
+            .collect(),
+    )
+}
+
+#[derive(Clone, Copy)]
+enum Tier {
+    Branch,
+    Prefix,
+    Fuzzy,
+}
+
+impl Tier {
+    /// How well `query` matches `text` in this tier, lower being better.
+    fn score(self, query: &str, text: &str) -> Option<usize> {
+        match self {
+            Self::Branch => (text == query).then_some(0),
+            Self::Prefix => text.starts_with(query).then_some(0),
+            Self::Fuzzy => fuzzy_score(query, text),
+        }
+    }
+}
+
+/// Levenshtein distance between `a` and `b`, ignoring case.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.to_lowercase().chars().collect();
+    let b: Vec<char> = b.to_lowercase().chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// The worktree `name` names exactly: a `<repo>/<name>` key, or a worktree
+/// name used in a single repository. `None` when no worktree has that name;
+/// an error listing the candidates when several repositories do.
+pub fn exact_worktree(state: &PigsState, name: &str) -> Result<Option<(String, WorktreeInfo)>> {
+    if let Some(info) = state.worktrees.get(name) {
+        return Ok(Some((name.to_string(), info.clone())));
+    }
+    let mut matches: Vec<(&String, &WorktreeInfo)> = state
+        .worktrees
+        .iter()
+        .filter(|(_, info)| info.name == name)
+        .collect();
+    matches.sort_by(|a, b| a.0.cmp(b.0));
+    match matches.as_slice() {
+        [] => Ok(None),
+        [(key, info)] => Ok(Some(((*key).clone(), (*info).clone()))),
+        _ => {
+            let keys: Vec<&str> = matches.iter().map(|(key, _)| key.as_str()).collect();
+            bail!(
+                "Worktree '{name}' exists in several repositories ({}); use <repo>/<name>",
+                keys.join(", ")
+            )
+        }
+    }
+}
+
+/// Find the worktree `name` refers to across all repositories: an exact
+/// worktree name or `<repo>/<name>`, else the one worktree whose name or
+/// branch equals, starts with, or fuzzily matches it (noted on stderr). Fails
+/// with a "did you mean" when several match or none do.
+pub fn find_worktree(state: &PigsState, name: &str) -> Result<(String, WorktreeInfo)> {
+    if let Some(found) = exact_worktree(state, name)? {
+        return Ok(found);
+    }
+    let (key, info) = closest_worktree(state, name)?;
+    eprintln!(
+        "{} Using worktree {}/{} for '{}'",
+        "🔎".blue(),
+        info.repo_name,
+        info.name.cyan(),
+        name
+    );
+    Ok((key, info))
+}
+
+/// Like `find_worktree`, for commands that destroy work: anything but an
+/// exact name or `<repo>/<name>` has to be confirmed at a prompt (or with
+/// piped `y`), which `PIGS_YES` doesn't answer.
+pub fn find_worktree_exactly(state: &PigsState, name: &str) -> Result<(String, WorktreeInfo)> {
+    if let Some(found) = exact_worktree(state, name)? {
+        return Ok(found);
+    }
+    let (key, info) = closest_worktree(state, name)?;
+    let prompt = format!(
+        "No worktree is named '{name}'. Use {}/{} instead?",
+        info.repo_name, info.name
+    );
+    if !explicit_confirm(&prompt)? {
+        bail!(
+            "Worktree '{name}' not found; did you mean '{}'? Pass its exact name",
+            info.name
+        );
+    }
+    Ok((key, info))
+}
+
+/// The worktree a command acts on: `name` when given, matched exactly like
+/// `exact_worktree`, else the focused worktree, else one picked interactively.
+pub fn resolve_worktree(state: &PigsState, name: Option<String>) -> Result<WorktreeInfo> {
+    if let Some(name) = name {
+        return exact_worktree(state, &name)?
+            .map(|(_, info)| info)
+            .with_context(|| format!("Worktree '{name}' not found"));
+    }
+    if let Some(info) = focused_worktree(state) {
+        return Ok(info);
+    }
+
+    let worktree_list: Vec<WorktreeInfo> = state.worktrees.values().cloned().collect();
+    let selection = smart_select("Select a worktree", &worktree_list, |info| {
+        format!("{}/{}", info.repo_name, info.name)
+    })?;
+
+    match selection {
+        Some(idx) => Ok(worktree_list[idx].clone()),
+        None => bail!(
+            "Interactive selection not available in non-interactive mode. Please specify a worktree name."
+        ),
+    }
+}
+
+/// The one worktree whose name or branch matches `name` without being exact.
+fn closest_worktree(state: &PigsState, name: &str) -> Result<(String, WorktreeInfo)> {
+    let mut entries: Vec<(&String, &WorktreeInfo)> = state.worktrees.iter().collect();
+    // Sorted so ties are listed the same way every time
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+    let pairs: Vec<(&str, &str)> = entries
+        .iter()
+        .map(|(_, info)| (info.name.as_str(), info.branch.as_str()))
+        .collect();
+    let suggest = |indices: &[usize]| {
+        let names: Vec<String> = indices
+            .iter()
+            .map(|&i| format!("'{}'", entries[i].1.name))
+            .collect();
+        match names.split_last() {
+            Some((last, [])) => last.clone(),
+            Some((last, rest)) => format!("{} or {last}", rest.join(", ")),
+            None => String::new(),
+        }
+    };
+
+    match match_name(name, &pairs) {
+        NameMatch::Unique(i) => {
+            let (key, info) = entries[i];
+            Ok((key.clone(), info.clone()))
+        }
+        NameMatch::Ambiguous(indices) => bail!(
+            "Worktree '{name}' is ambiguous; did you mean {}?",
+            suggest(&indices)
+        ),
+        NameMatch::Missing(indices) if indices.is_empty() => {
+            bail!("Worktree '{name}' not found")
+        }
+        NameMatch::Missing(indices) => bail!(
+            "Worktree '{name}' not found; did you mean {}?",
+            suggest(&indices)
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_schemes() {
+        assert_eq!(NameScheme::parse("word").unwrap(), NameScheme::Words(1));
+        assert_eq!(NameScheme::parse("words:3").unwrap(), NameScheme::Words(3));
+        assert_eq!(
+            NameScheme::parse("numbered:task").unwrap(),
+            NameScheme::Numbered("task".to_string())
+        );
+        assert!(NameScheme::parse("words:0").is_err());
+        assert!(NameScheme::parse("emoji").is_err());
+    }
+
+    #[test]
+    fn same_seed_gives_same_name() {
+        let scheme = NameScheme::Words(2);
+        let first = pick(&scheme, &mut StdRng::seed_from_u64(7), |_| false).unwrap();
+        let second = pick(&scheme, &mut StdRng::seed_from_u64(7), |_| false).unwrap();
+        assert_eq!(first, second);
+        assert_eq!(first.split('-').count(), 2);
+    }
+
+    #[test]
+    fn taken_names_get_suffixes() {
+        let scheme = NameScheme::Words(1);
+        let free = pick(&scheme, &mut StdRng::seed_from_u64(7), |_| false).unwrap();
+        let taken = [free.clone(), format!("{free}-2")];
+        let name = pick(&scheme, &mut StdRng::seed_from_u64(7), |name| {
+            taken.iter().any(|taken| taken == name)
+        })
+        .unwrap();
+        assert_eq!(name, format!("{free}-3"));
+
+        let numbered = NameScheme::Numbered("wt".to_string());
+        let name = pick(&numbered, &mut StdRng::seed_from_u64(7), |name| {
+            name == "wt-1" || name == "wt-2"
+        })
+        .unwrap();
+        assert_eq!(name, "wt-3");
+    }
+
+    #[test]
+    fn matches_names_then_branches_then_prefixes_then_fuzzy() {
+        let worktrees = [
+            ("payment-retry", "payment-retry"),
+            ("payment-ui", "feature/checkout"),
+            ("docs", "docs"),
+        ];
+        assert_eq!(match_name("docs", &worktrees), NameMatch::Unique(2));
+        assert_eq!(
+            match_name("feature/checkout", &worktrees),
+            NameMatch::Unique(1)
+        );
+        assert_eq!(match_name("payment-r", &worktrees), NameMatch::Unique(0));
+        assert_eq!(match_name("feat", &worktrees), NameMatch::Unique(1));
+        assert_eq!(
+            match_name("pay", &worktrees),
+            NameMatch::Ambiguous(vec![0, 1])
+        );
+        assert_eq!(match_name("pmtui", &worktrees), NameMatch::Unique(1));
+        assert_eq!(
+            match_name("paymnet-retry", &worktrees),
+            NameMatch::Missing(vec![0])
+        );
+        assert_eq!(match_name("zzz", &worktrees), NameMatch::Missing(vec![]));
+    }
+}
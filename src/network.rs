@@ -0,0 +1,24 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use anyhow::Result;
+
+/// Process-wide offline switch, set once at startup from `--offline`,
+/// `PIGS_OFFLINE`, or the `offline` setting.
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+
+pub fn set_offline(offline: bool) {
+    OFFLINE.store(offline, Ordering::Relaxed);
+}
+
+/// Whether network integrations (Linear, `gh`, `git fetch`) should be skipped.
+pub fn is_offline() -> bool {
+    OFFLINE.load(Ordering::Relaxed)
+}
+
+/// Fail fast with a clear message when an integration has no offline fallback.
+pub fn ensure_online(service: &str) -> Result<()> {
+    if is_offline() {
+        anyhow::bail!("{service} is unavailable in offline mode");
+    }
+    Ok(())
+}
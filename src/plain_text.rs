@@ -0,0 +1,113 @@
+/// Remove terminal escape sequences and control characters from `text`.
+/// Drops CSI (`ESC [ ... m`), OSC (`ESC ] ... BEL`), and other escapes, keeps
+/// newlines and tabs, and treats a carriage return as starting the line over,
+/// so a redrawn progress line keeps only its final state.
+pub fn strip_ansi(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut line_start = 0;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\x1b' => match chars.next() {
+                // CSI: parameters and intermediates, then one final byte
+                Some('[') => {
+                    for c in chars.by_ref() {
+                        if ('\x40'..='\x7e').contains(&c) {
+                            break;
+                        }
+                    }
+                }
+                // OSC, DCS, and friends: terminated by BEL or ESC \
+                Some(']' | 'P' | '_' | '^' | 'X') => {
+                    while let Some(c) = chars.next() {
+                        if c == '\x07' {
+                            break;
+                        }
+                        if c == '\x1b' && chars.peek() == Some(&'\\') {
+                            chars.next();
+                            break;
+                        }
+                    }
+                }
+                // Character set selection takes one more byte
+                Some('(' | ')' | '*' | '+') => {
+                    chars.next();
+                }
+                _ => {}
+            },
+            '\n' => {
+                out.push('\n');
+                line_start = out.len();
+            }
+            '\r' => {
+                if chars.peek() != Some(&'\n') {
+                    out.truncate(line_start);
+                }
+            }
+            '\x08' => {
+                if out.len() > line_start {
+                    out.pop();
+                }
+            }
+            '\t' => out.push('\t'),
+            c if c.is_control() => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Collects raw terminal chunks and hands back complete, ANSI-stripped lines.
+/// Escape sequences never span a newline, so holding back the unfinished tail
+/// also holds back any escape sequence split across chunks.
+#[derive(Default)]
+pub struct LineBuffer {
+    pending: String,
+}
+
+impl LineBuffer {
+    /// Add a chunk and return the lines it completed.
+    pub fn push(&mut self, chunk: &str) -> Vec<String> {
+        self.pending.push_str(chunk);
+        let Some(end) = self.pending.rfind('\n') else {
+            return Vec::new();
+        };
+        let complete: String = self.pending.drain(..=end).collect();
+        strip_ansi(&complete).lines().map(str::to_string).collect()
+    }
+
+    /// Return the unfinished last line, if it has any text.
+    pub fn flush(&mut self) -> Option<String> {
+        let line = strip_ansi(&std::mem::take(&mut self.pending));
+        (!line.trim().is_empty()).then_some(line)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strips_escapes_and_redrawn_lines() {
+        assert_eq!(
+            strip_ansi("\x1b[1;32mok\x1b[0m done\r\n"),
+            "ok done\n".to_string()
+        );
+        assert_eq!(
+            strip_ansi("\x1b]0;title\x0750%\r100%\n"),
+            "100%\n".to_string()
+        );
+        assert_eq!(strip_ansi("ab\x08c\x1b(B"), "ac".to_string());
+    }
+
+    #[test]
+    fn buffers_partial_lines_and_split_escapes() {
+        let mut buffer = LineBuffer::default();
+        assert!(buffer.push("hello \x1b[3").is_empty());
+        assert_eq!(buffer.push("1mworld\x1b[0m\nnext"), vec!["hello world"]);
+        assert_eq!(buffer.push(" line\n\n"), vec!["next line", ""]);
+        assert!(buffer.push("tail").is_empty());
+        assert_eq!(buffer.flush(), Some("tail".to_string()));
+        assert_eq!(buffer.flush(), None);
+    }
+}
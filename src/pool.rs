@@ -0,0 +1,155 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::commands::create::resolve_repo_name;
+use crate::git::{
+    copy_files_to_worktree, execute_git, main_checkout, run_setup_commands, update_submodules,
+};
+use crate::repo_lock::lock_repo;
+use crate::state::{PigsState, RepoConfig};
+
+/// Prefix of the placeholder branches pooled worktrees sit on until claimed.
+const POOL_BRANCH_PREFIX: &str = "pigs-pool/";
+
+/// A worktree created ahead of time, with submodules, copied files, and setup
+/// commands done, waiting for `pigs create --fast` to claim it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PooledWorktree {
+    pub repo_name: String,
+    pub path: PathBuf,
+    /// Placeholder branch, renamed when the worktree is claimed
+    pub branch: String,
+    /// Branch the worktree was created from; only creates on it may claim it
+    pub base_branch: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Top up the pool for the repository at `repo_root` to `size` worktrees on
+/// its default branch. Returns how many were created.
+pub fn fill(repo_root: &Path, size: usize) -> Result<usize> {
+    let root = repo_root
+        .to_str()
+        .context("Repository path is not valid UTF-8")?;
+    let repo_name = resolve_repo_name(Some(&repo_root.to_path_buf())).or_else(|_| {
+        repo_root
+            .file_name()
+            .and_then(|name| name.to_str())
+            .map(String::from)
+            .context("Failed to get repository name")
+    })?;
+    let base_branch = execute_git(&["-C", root, "symbolic-ref", "refs/remotes/origin/HEAD"])
+        .ok()
+        .and_then(|head| head.strip_prefix("refs/remotes/origin/").map(String::from))
+        .unwrap_or_else(|| "main".to_string());
+
+    let state = PigsState::load()?;
+    let pooled = state
+        .pooled_worktrees
+        .iter()
+        .filter(|pooled| pooled.repo_name == repo_name && pooled.base_branch == base_branch)
+        .count();
+
+    let mut created = 0;
+    for _ in pooled..size {
+        let pooled = create_pooled(repo_root, &repo_name, &base_branch)?;
+        let mut state = PigsState::load()?;
+        state.pooled_worktrees.push(pooled);
+        state.save()?;
+        created += 1;
+    }
+    Ok(created)
+}
+
+fn create_pooled(repo_root: &Path, repo_name: &str, base_branch: &str) -> Result<PooledWorktree> {
+    let root = repo_root.to_str().unwrap_or_default();
+    let id = uuid::Uuid::new_v4().simple().to_string()[..8].to_string();
+    let branch = format!("{POOL_BRANCH_PREFIX}{id}");
+    // Pooled worktrees live where claimed ones end up, so claiming is a rename
+    let path = PigsState::load()?.worktree_dir(repo_root, repo_name, &format!("pool-{id}"))?;
+    let path_str = path.to_string_lossy().to_string();
+
+    {
+        let _guard = lock_repo(repo_name, "fill the worktree pool")?;
+        execute_git(&[
+            "-C",
+            root,
+            "worktree",
+            "add",
+            "-b",
+            &branch,
+            &path_str,
+            base_branch,
+        ])
+        .context("Failed to create pooled worktree")?;
+    }
+
+    let prepared = update_submodules(&path).and_then(|()| {
+        let repo_config = RepoConfig::load(repo_root)?;
+        copy_files_to_worktree(repo_root, &path, &repo_config.copy_files, true)?;
+        run_setup_commands(&path, &repo_config.setup_commands, true)
+    });
+    if let Err(err) = prepared {
+        remove(root, repo_name, &path_str, &branch);
+        return Err(err);
+    }
+
+    Ok(PooledWorktree {
+        repo_name: repo_name.to_string(),
+        path,
+        branch,
+        base_branch: base_branch.to_string(),
+        created_at: Utc::now(),
+    })
+}
+
+/// Take the oldest pooled worktree of `repo_name` created from `base_branch`
+/// out of the pool, if there is one whose directory still exists.
+pub fn claim(repo_name: &str, base_branch: &str) -> Result<Option<PooledWorktree>> {
+    let mut state = PigsState::load()?;
+    let Some(index) = state.pooled_worktrees.iter().position(|pooled| {
+        pooled.repo_name == repo_name && pooled.base_branch == base_branch && pooled.path.exists()
+    }) else {
+        return Ok(None);
+    };
+    let pooled = state.pooled_worktrees.remove(index);
+    state.save()?;
+    Ok(Some(pooled))
+}
+
+/// Put a claimed worktree back in the pool after the create claiming it
+/// failed, with its directory and branch as they were.
+pub fn unclaim(pooled: PooledWorktree) -> Result<()> {
+    let mut state = PigsState::load()?;
+    state.pooled_worktrees.push(pooled);
+    state.save()
+}
+
+/// Remove every pooled worktree and its branch. Returns how many were removed.
+pub fn drain() -> Result<usize> {
+    let mut state = PigsState::load()?;
+    let drained = std::mem::take(&mut state.pooled_worktrees);
+    state.save()?;
+
+    for pooled in &drained {
+        let Some(root) = main_checkout(&pooled.path) else {
+            continue;
+        };
+        remove(
+            &root.to_string_lossy(),
+            &pooled.repo_name,
+            &pooled.path.to_string_lossy(),
+            &pooled.branch,
+        );
+    }
+    Ok(drained.len())
+}
+
+fn remove(root: &str, repo_name: &str, path: &str, branch: &str) {
+    let Ok(_guard) = lock_repo(repo_name, "remove a pooled worktree") else {
+        return;
+    };
+    let _ = execute_git(&["-C", root, "worktree", "remove", "--force", path]);
+    let _ = execute_git(&["-C", root, "branch", "-D", branch]);
+}
@@ -0,0 +1,126 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use serde::{Deserialize, Serialize};
+
+/// A git state that blocks normal work in a worktree until it is resolved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum WorktreeProblem {
+    Conflicts,
+    RebaseInProgress,
+    MergeInProgress,
+    CherryPickInProgress,
+    RevertInProgress,
+    DetachedHead,
+}
+
+impl WorktreeProblem {
+    /// Short human-readable description for list and dashboard output.
+    pub fn describe(self) -> &'static str {
+        match self {
+            Self::Conflicts => "unresolved merge conflicts",
+            Self::RebaseInProgress => "rebase in progress",
+            Self::MergeInProgress => "merge in progress",
+            Self::CherryPickInProgress => "cherry-pick in progress",
+            Self::RevertInProgress => "revert in progress",
+            Self::DetachedHead => "detached HEAD",
+        }
+    }
+
+    /// Command to run inside the worktree to get out of this state.
+    pub fn hint(self, branch: &str) -> String {
+        match self {
+            Self::Conflicts => {
+                "git diff --name-only --diff-filter=U, fix the files, then git add them".to_string()
+            }
+            Self::RebaseInProgress => "git rebase --continue (or --abort)".to_string(),
+            Self::MergeInProgress => "git merge --continue (or --abort)".to_string(),
+            Self::CherryPickInProgress => "git cherry-pick --continue (or --abort)".to_string(),
+            Self::RevertInProgress => "git revert --continue (or --abort)".to_string(),
+            Self::DetachedHead => format!("git switch {branch}"),
+        }
+    }
+}
+
+/// Problem together with its resolution hint, as reported by `pigs list --json`
+/// and the dashboard.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProblemReport {
+    pub kind: WorktreeProblem,
+    pub message: String,
+    pub hint: String,
+}
+
+impl ProblemReport {
+    pub fn new(kind: WorktreeProblem, branch: &str) -> Self {
+        Self {
+            kind,
+            message: kind.describe().to_string(),
+            hint: kind.hint(branch),
+        }
+    }
+}
+
+/// Inspect a worktree's sequencer files and status for states that need
+/// attention. Returns an empty list when the worktree is missing or git fails.
+pub fn detect_worktree_problems(worktree_path: &Path) -> Vec<WorktreeProblem> {
+    if !worktree_path.exists() {
+        return Vec::new();
+    }
+    let Some(git_dir) = git_dir(worktree_path) else {
+        return Vec::new();
+    };
+
+    let mut problems = Vec::new();
+    let unmerged = run_git(worktree_path, &["diff", "--name-only", "--diff-filter=U"]);
+    if unmerged.is_some_and(|files| !files.trim().is_empty()) {
+        problems.push(WorktreeProblem::Conflicts);
+    }
+
+    let rebasing = git_dir.join("rebase-merge").exists() || git_dir.join("rebase-apply").exists();
+    if rebasing {
+        problems.push(WorktreeProblem::RebaseInProgress);
+    }
+    if git_dir.join("MERGE_HEAD").exists() {
+        problems.push(WorktreeProblem::MergeInProgress);
+    }
+    if git_dir.join("CHERRY_PICK_HEAD").exists() {
+        problems.push(WorktreeProblem::CherryPickInProgress);
+    }
+    if git_dir.join("REVERT_HEAD").exists() {
+        problems.push(WorktreeProblem::RevertInProgress);
+    }
+
+    // A rebase detaches HEAD on purpose; only flag it outside of one
+    if !rebasing && run_git(worktree_path, &["symbolic-ref", "-q", "HEAD"]).is_none() {
+        problems.push(WorktreeProblem::DetachedHead);
+    }
+
+    problems
+}
+
+/// Resolve the worktree's private git directory. For linked worktrees this is
+/// `<repo>/.git/worktrees/<name>`, where the sequencer files live.
+fn git_dir(worktree_path: &Path) -> Option<PathBuf> {
+    let dir = PathBuf::from(run_git(worktree_path, &["rev-parse", "--git-dir"])?.trim());
+    Some(if dir.is_absolute() {
+        dir
+    } else {
+        worktree_path.join(dir)
+    })
+}
+
+fn run_git(worktree_path: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git")
+        .current_dir(worktree_path)
+        .args(args)
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    Some(String::from_utf8_lossy(&output.stdout).to_string())
+}
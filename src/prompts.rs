@@ -0,0 +1,78 @@
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::state::get_config_dir;
+
+/// One prompt sent to an agent in a worktree, appended to
+/// `~/.pigs/prompts.jsonl` so the sequence can be replayed elsewhere.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptRecord {
+    pub timestamp: DateTime<Utc>,
+    pub repo_name: String,
+    pub worktree: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    pub prompt: String,
+}
+
+fn prompts_path() -> Result<PathBuf> {
+    Ok(get_config_dir()?.join("prompts.jsonl"))
+}
+
+/// Append a prompt to the log. Failures are reported but never interrupt the
+/// command that sent the prompt.
+pub fn record(repo_name: &str, worktree: &str, session_id: Option<&str>, prompt: &str) {
+    let entry = PromptRecord {
+        timestamp: Utc::now(),
+        repo_name: repo_name.to_string(),
+        worktree: worktree.to_string(),
+        session_id: session_id.map(str::to_string),
+        prompt: prompt.to_string(),
+    };
+    if let Err(err) = append(&entry) {
+        eprintln!("Warning: failed to record prompt: {err}");
+    }
+}
+
+fn append(entry: &PromptRecord) -> Result<()> {
+    let path = prompts_path()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).context("Failed to create config directory")?;
+    }
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .context("Failed to open prompt log")?;
+    writeln!(file, "{}", serde_json::to_string(entry)?).context("Failed to write prompt log")?;
+    Ok(())
+}
+
+/// Read every prompt recorded for one worktree, oldest first. Malformed lines
+/// are skipped.
+pub fn read_for_worktree(repo_name: &str, worktree: &str) -> Result<Vec<PromptRecord>> {
+    Ok(read_all()?
+        .into_iter()
+        .filter(|entry| entry.repo_name == repo_name && entry.worktree == worktree)
+        .collect())
+}
+
+/// Read every recorded prompt, oldest first. Malformed lines are skipped.
+pub fn read_all() -> Result<Vec<PromptRecord>> {
+    let path = prompts_path()?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let file = fs::File::open(&path).context("Failed to open prompt log")?;
+    Ok(BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str::<PromptRecord>(&line).ok())
+        .collect())
+}
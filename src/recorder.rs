@@ -0,0 +1,318 @@
+use std::io::{self, IsTerminal, Read, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use console::Term;
+use portable_pty::{CommandBuilder, MasterPty, PtySize, native_pty_system};
+
+use crate::devenv::EnvChange;
+use crate::state::WorktreeInfo;
+use crate::transcripts::TranscriptWriter;
+
+/// Size used when stdout isn't a terminal, matching dashboard sessions.
+const DEFAULT_SIZE: (u16, u16) = (40, 120);
+/// How often the terminal is checked for a new size to pass on to the agent.
+const RESIZE_POLL: Duration = Duration::from_millis(250);
+
+/// Run `program` in a PTY that pigs proxies to this terminal, so it stays fully
+/// interactive, while saving its output and each line typed into it as the
+/// transcript of `session_id`. Returns the exit code.
+pub fn run(
+    info: &WorktreeInfo,
+    program: &str,
+    args: &[String],
+    env_changes: &[EnvChange],
+    session_id: &str,
+) -> Result<u32> {
+    let term = Term::stdout();
+    let (rows, cols) = term.size_checked().unwrap_or(DEFAULT_SIZE);
+    let pair = native_pty_system().openpty(PtySize {
+        rows,
+        cols,
+        pixel_width: 0,
+        pixel_height: 0,
+    })?;
+
+    let mut builder = CommandBuilder::new(program);
+    builder.args(args);
+    builder.cwd(info.path.clone());
+    builder.env_clear();
+    for (key, value) in std::env::vars() {
+        builder.env(&key, value);
+    }
+    for (key, value) in env_changes {
+        match value {
+            Some(value) => builder.env(key, value),
+            None => builder.env_remove(key),
+        }
+    }
+
+    let mut child = pair
+        .slave
+        .spawn_command(builder)
+        .with_context(|| format!("Failed to spawn {program}"))?;
+    drop(pair.slave);
+
+    // The agent is reaped even when proxying fails, so it never outlives pigs
+    // as a zombie.
+    let done = Arc::new(AtomicBool::new(false));
+    let proxied = proxy(
+        pair.master,
+        term,
+        (rows, cols),
+        &done,
+        program,
+        args,
+        session_id,
+    );
+    if proxied.is_err() {
+        let _ = child.kill();
+    }
+    let status = child.wait().context("Failed to wait for agent");
+    done.store(true, Ordering::Relaxed);
+    let transcript = proxied?;
+    let status = status?;
+    transcript
+        .lock()
+        .unwrap()
+        .status("stopped", Some(format!("exit code {}", status.exit_code())))?;
+    Ok(status.exit_code())
+}
+
+/// Proxy the agent's PTY to this terminal until the agent closes it, saving
+/// the transcript of `session_id` as it goes.
+fn proxy(
+    master: Box<dyn MasterPty + Send>,
+    term: Term,
+    (rows, cols): (u16, u16),
+    done: &Arc<AtomicBool>,
+    program: &str,
+    args: &[String],
+    session_id: &str,
+) -> Result<Arc<Mutex<TranscriptWriter>>> {
+    let mut reader = master
+        .try_clone_reader()
+        .context("Failed to clone PTY reader")?;
+    let mut writer = master
+        .take_writer()
+        .context("Failed to capture PTY writer")?;
+
+    let transcript = Arc::new(Mutex::new(TranscriptWriter::create(session_id)?));
+    transcript.lock().unwrap().status(
+        "running",
+        Some(shell_words::join(
+            std::iter::once(program).chain(args.iter().map(String::as_str)),
+        )),
+    )?;
+    let _raw = RawMode::enable();
+
+    // Keystrokes go straight to the agent. This thread is left blocked on
+    // stdin when the agent exits; pigs exits right after anyway. Piped input
+    // was already drained, so the agent just sees end of input instead.
+    if io::stdin().is_terminal() {
+        let input_transcript = transcript.clone();
+        std::thread::spawn(move || {
+            let mut stdin = io::stdin();
+            let mut lines = LineTracker::default();
+            let mut buf = [0u8; 1024];
+            while let Ok(n) = stdin.read(&mut buf) {
+                if n == 0 || writer.write_all(&buf[..n]).is_err() {
+                    break;
+                }
+                let _ = writer.flush();
+                for line in lines.push(&buf[..n]) {
+                    let _ = input_transcript
+                        .lock()
+                        .unwrap()
+                        .message("user", "stdin", line);
+                }
+            }
+        });
+    } else {
+        writer.write_all(b"\x04")?;
+        writer.flush()?;
+    }
+
+    let resize_done = done.clone();
+    std::thread::spawn(move || {
+        let mut size = (rows, cols);
+        while !resize_done.load(Ordering::Relaxed) {
+            std::thread::sleep(RESIZE_POLL);
+            if let Some(current) = term.size_checked()
+                && current != size
+            {
+                size = current;
+                let _ = master.resize(PtySize {
+                    rows: size.0,
+                    cols: size.1,
+                    pixel_width: 0,
+                    pixel_height: 0,
+                });
+            }
+        }
+    });
+
+    let mut stdout = io::stdout();
+    let mut decoder = Utf8Decoder::default();
+    let mut buf = [0u8; 4096];
+    loop {
+        match reader.read(&mut buf) {
+            Ok(0) | Err(_) => break,
+            Ok(n) => {
+                stdout.write_all(&buf[..n])?;
+                stdout.flush()?;
+                let chunk = decoder.push(&buf[..n]);
+                if chunk.is_empty() {
+                    continue;
+                }
+                transcript
+                    .lock()
+                    .unwrap()
+                    .message("assistant", "stdout", chunk)?;
+            }
+        }
+    }
+    Ok(transcript)
+}
+
+/// Puts the terminal on stdin into raw mode, so keys reach the agent as they
+/// are typed, and restores it when dropped.
+struct RawMode {
+    original: Option<libc::termios>,
+}
+
+impl RawMode {
+    fn enable() -> Self {
+        if !io::stdin().is_terminal() {
+            return Self { original: None };
+        }
+        // SAFETY: termios is plain data, and both calls only read or write the
+        // struct passed to them for a descriptor that is a terminal.
+        unsafe {
+            let mut original: libc::termios = std::mem::zeroed();
+            if libc::tcgetattr(libc::STDIN_FILENO, &mut original) != 0 {
+                return Self { original: None };
+            }
+            let mut raw = original;
+            libc::cfmakeraw(&mut raw);
+            libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &raw);
+            Self {
+                original: Some(original),
+            }
+        }
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        if let Some(original) = &self.original {
+            // SAFETY: restores the settings read in `enable`.
+            unsafe {
+                libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, original);
+            }
+        }
+    }
+}
+
+/// Decodes UTF-8 that arrives in arbitrary chunks, holding back a character
+/// split across two reads until the rest of it arrives.
+#[derive(Default)]
+struct Utf8Decoder {
+    pending: Vec<u8>,
+}
+
+impl Utf8Decoder {
+    /// Feed read bytes, returning the text they complete.
+    fn push(&mut self, bytes: &[u8]) -> String {
+        self.pending.extend_from_slice(bytes);
+        let incomplete = self
+            .pending
+            .utf8_chunks()
+            .last()
+            .map(|chunk| chunk.invalid())
+            .filter(|invalid| {
+                std::str::from_utf8(invalid).is_err_and(|err| err.error_len().is_none())
+            })
+            .map_or(0, <[u8]>::len);
+        let rest = self.pending.split_off(self.pending.len() - incomplete);
+        let text = String::from_utf8_lossy(&self.pending).into_owned();
+        self.pending = rest;
+        text
+    }
+}
+
+/// Rebuilds the lines typed into a raw terminal from keystrokes: applies
+/// backspaces and drops escape sequences such as arrow keys.
+#[derive(Default)]
+struct LineTracker {
+    line: String,
+    escape: Escape,
+    decoder: Utf8Decoder,
+}
+
+#[derive(Default, PartialEq)]
+enum Escape {
+    #[default]
+    None,
+    Started,
+    Sequence,
+}
+
+impl LineTracker {
+    /// Feed typed bytes, returning each non-empty line completed by Enter.
+    fn push(&mut self, bytes: &[u8]) -> Vec<String> {
+        let mut lines = Vec::new();
+        for c in self.decoder.push(bytes).chars() {
+            match (&self.escape, c) {
+                (Escape::Started, '[' | 'O') => self.escape = Escape::Sequence,
+                (Escape::Started, _) => self.escape = Escape::None,
+                (Escape::Sequence, '\u{40}'..='\u{7e}') => self.escape = Escape::None,
+                (Escape::Sequence, _) => {}
+                (Escape::None, '\u{1b}') => self.escape = Escape::Started,
+                (Escape::None, '\r' | '\n') => {
+                    let line = std::mem::take(&mut self.line);
+                    if !line.trim().is_empty() {
+                        lines.push(line);
+                    }
+                }
+                (Escape::None, '\u{7f}' | '\u{8}') => {
+                    self.line.pop();
+                }
+                (Escape::None, c) if !c.is_control() => self.line.push(c),
+                (Escape::None, _) => {}
+            }
+        }
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LineTracker, Utf8Decoder};
+
+    #[test]
+    fn line_tracker_rebuilds_typed_lines() {
+        let mut tracker = LineTracker::default();
+        assert!(tracker.push(b"fix the tset").is_empty());
+        assert_eq!(tracker.push(b"\x7f\x7f\x7fest\r"), vec!["fix the test"]);
+        // Arrow keys and a bare Enter leave no trace
+        assert!(tracker.push(b"\x1b[A\r").is_empty());
+        assert_eq!(tracker.push(b"y\x1bOBes\n"), vec!["yes"]);
+        // A character split across reads isn't mangled
+        assert!(tracker.push(b"caf\xc3").is_empty());
+        assert_eq!(tracker.push(b"\xa9\r"), vec!["café"]);
+    }
+
+    #[test]
+    fn utf8_decoder_holds_back_split_characters() {
+        let mut decoder = Utf8Decoder::default();
+        let bytes = "✓ done".as_bytes();
+        assert_eq!(decoder.push(&bytes[..1]), "");
+        assert_eq!(decoder.push(&bytes[1..2]), "");
+        assert_eq!(decoder.push(&bytes[2..]), "✓ done");
+        // Bytes that can never be valid are still replaced right away
+        assert_eq!(decoder.push(b"a\xffb"), "a\u{fffd}b");
+    }
+}
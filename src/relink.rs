@@ -0,0 +1,143 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+use crate::git::{execute_git, extract_repo_name_from_url};
+use crate::state::PigsState;
+
+/// A tracked repository whose main checkout is gone from where pigs last saw
+/// it, and the checkout with the same origin found under a workspace root.
+pub struct MovedRepo {
+    pub repo_name: String,
+    pub old_root: PathBuf,
+    pub new_root: PathBuf,
+    /// Keys of the repository's worktrees in state
+    pub worktrees: Vec<String>,
+}
+
+/// Find repositories whose main checkout no longer exists and that can be
+/// found again under `workspace_roots` (default: the parent of the current
+/// directory), by the repository name in their origin URL.
+pub fn find_moved_repos(state: &PigsState) -> Vec<MovedRepo> {
+    let mut missing: BTreeMap<(String, PathBuf), Vec<String>> = BTreeMap::new();
+    for (key, info) in &state.worktrees {
+        let Ok(root) = info.main_repo_path() else {
+            continue;
+        };
+        if !root.exists() {
+            missing
+                .entry((info.repo_name.clone(), root))
+                .or_default()
+                .push(key.clone());
+        }
+    }
+    if missing.is_empty() {
+        return Vec::new();
+    }
+
+    let candidates = find_checkouts(&workspace_roots(state));
+    missing
+        .into_iter()
+        .filter_map(|((repo_name, old_root), mut worktrees)| {
+            let new_root = candidates
+                .iter()
+                .find(|(name, _)| *name == repo_name)
+                .map(|(_, path)| path.clone())?;
+            worktrees.sort();
+            Some(MovedRepo {
+                repo_name,
+                old_root,
+                new_root,
+                worktrees,
+            })
+        })
+        .collect()
+}
+
+/// Point the repository's worktrees at their new location and let git repair
+/// the links between the main checkout and the worktrees. A worktree that sat
+/// next to the old checkout is looked for next to the new one; one kept
+/// elsewhere stays where it is. Returns how many worktrees were re-linked.
+pub fn relink(state: &mut PigsState, moved: &MovedRepo) -> Result<usize> {
+    let new_parent = moved
+        .new_root
+        .parent()
+        .context("Repository has no parent directory")?;
+    let mut relinked = Vec::new();
+    for key in &moved.worktrees {
+        let Some(info) = state.worktrees.get_mut(key) else {
+            continue;
+        };
+        let moved_along = info.path.parent() == moved.old_root.parent();
+        let new_path = match info.path.file_name() {
+            Some(dir_name) if moved_along => new_parent.join(dir_name),
+            _ => info.path.clone(),
+        };
+        if new_path.exists() {
+            info.path = new_path.clone();
+            info.repo_root = Some(moved.new_root.clone());
+            relinked.push(new_path);
+        }
+    }
+
+    let root = moved
+        .new_root
+        .to_str()
+        .context("Repository path contains invalid UTF-8")?;
+    let mut args = vec!["-C", root, "worktree", "repair"];
+    for path in &relinked {
+        args.push(
+            path.to_str()
+                .context("Worktree path contains invalid UTF-8")?,
+        );
+    }
+    execute_git(&args).context("Failed to repair worktree links")?;
+    Ok(relinked.len())
+}
+
+fn workspace_roots(state: &PigsState) -> Vec<PathBuf> {
+    if !state.workspace_roots.is_empty() {
+        return state.workspace_roots.clone();
+    }
+    std::env::current_dir()
+        .ok()
+        .and_then(|dir| dir.parent().map(Path::to_path_buf))
+        .into_iter()
+        .collect()
+}
+
+/// Main checkouts up to two levels below each root (`root/repo` and
+/// `root/org/repo`), with the repository name from their origin URL.
+fn find_checkouts(roots: &[PathBuf]) -> Vec<(String, PathBuf)> {
+    let mut checkouts = Vec::new();
+    let mut dirs: Vec<(PathBuf, usize)> = roots.iter().map(|root| (root.clone(), 0)).collect();
+    while let Some((dir, depth)) = dirs.pop() {
+        if dir.join(".git").is_dir() {
+            if let Some(name) = dir
+                .to_str()
+                .and_then(|path| execute_git(&["-C", path, "remote", "get-url", "origin"]).ok())
+                .and_then(|url| extract_repo_name_from_url(&url))
+            {
+                checkouts.push((name, dir));
+            }
+            continue;
+        }
+        if depth == 2 {
+            continue;
+        }
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let hidden = entry.file_name().to_string_lossy().starts_with('.');
+            if path.is_dir() && !hidden {
+                dirs.push((path, depth + 1));
+            }
+        }
+    }
+    checkouts.sort();
+    checkouts
+}
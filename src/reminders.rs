@@ -0,0 +1,162 @@
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::state::{PigsState, WorktreeInfo};
+
+/// Due date attached to a worktree with `pigs remind`. The dashboard sends a
+/// notification once it passes and marks it `notified` so it only fires once.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Reminder {
+    pub due_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    #[serde(default)]
+    pub notified: bool,
+}
+
+impl Reminder {
+    pub fn is_overdue(&self, now: DateTime<Utc>) -> bool {
+        self.due_at <= now
+    }
+
+    /// e.g. "due in 2d", "overdue by 3h", followed by the note if any.
+    pub fn describe(&self, now: DateTime<Utc>) -> String {
+        let when = if self.is_overdue(now) {
+            format!("overdue by {}", format_span(now - self.due_at))
+        } else {
+            format!("due in {}", format_span(self.due_at - now))
+        };
+        match &self.note {
+            Some(note) => format!("{when} — {note}"),
+            None => when,
+        }
+    }
+}
+
+/// Reminder as reported by `pigs list --json` and the dashboard.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReminderReport {
+    pub due: DateTime<Utc>,
+    pub note: Option<String>,
+    pub overdue: bool,
+}
+
+impl ReminderReport {
+    pub fn new(reminder: &Reminder, now: DateTime<Utc>) -> Self {
+        Self {
+            due: reminder.due_at,
+            note: reminder.note.clone(),
+            overdue: reminder.is_overdue(now),
+        }
+    }
+}
+
+/// Parse an offset such as `30m`, `3h`, `2d`, or `1w`.
+pub fn parse_offset(text: &str) -> Result<Duration> {
+    let text = text.trim();
+    let split = text
+        .find(|c: char| !c.is_ascii_digit())
+        .with_context(|| format!("Missing unit in '{text}' (use m, h, d, or w)"))?;
+    let (amount, unit) = text.split_at(split);
+    let amount: i64 = amount
+        .parse()
+        .with_context(|| format!("Invalid duration '{text}'"))?;
+    let duration = match unit {
+        "m" | "min" => Duration::minutes(amount),
+        "h" => Duration::hours(amount),
+        "d" => Duration::days(amount),
+        "w" => Duration::weeks(amount),
+        _ => bail!("Unknown unit '{unit}' in '{text}' (use m, h, d, or w)"),
+    };
+    if duration <= Duration::zero() {
+        bail!("Duration '{text}' must be positive");
+    }
+    Ok(duration)
+}
+
+/// Parse an absolute due date in local time: `2024-06-01`, `2024-06-01 14:30`,
+/// or an RFC 3339 timestamp. A bare date means 9am that day.
+pub fn parse_due(text: &str) -> Result<DateTime<Utc>> {
+    let text = text.trim();
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(text) {
+        return Ok(timestamp.with_timezone(&Utc));
+    }
+    let naive = NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M")
+        .or_else(|_| NaiveDateTime::parse_from_str(text, "%Y-%m-%dT%H:%M"))
+        .or_else(|_| {
+            NaiveDate::parse_from_str(text, "%Y-%m-%d")
+                .map(|date| date.and_hms_opt(9, 0, 0).expect("9am is a valid time"))
+        })
+        .with_context(|| {
+            format!("Invalid date '{text}' (use YYYY-MM-DD, 'YYYY-MM-DD HH:MM', or RFC 3339)")
+        })?;
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .map(|local| local.with_timezone(&Utc))
+        .with_context(|| format!("'{text}' does not exist in the local timezone"))
+}
+
+/// Worktrees whose reminder has passed but hasn't been sent yet.
+pub fn due_unnotified(state: &PigsState, now: DateTime<Utc>) -> Vec<(String, WorktreeInfo)> {
+    let mut due: Vec<(String, WorktreeInfo)> = state
+        .worktrees
+        .iter()
+        .filter(|(_, info)| {
+            info.reminder
+                .as_ref()
+                .is_some_and(|reminder| !reminder.notified && reminder.is_overdue(now))
+        })
+        .map(|(key, info)| (key.clone(), info.clone()))
+        .collect();
+    due.sort_by(|a, b| a.0.cmp(&b.0));
+    due
+}
+
+fn format_span(span: Duration) -> String {
+    if span.num_days() > 0 {
+        format!("{}d", span.num_days())
+    } else if span.num_hours() > 0 {
+        format!("{}h", span.num_hours())
+    } else {
+        format!("{}m", span.num_minutes().max(1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn offsets_accept_minutes_hours_days_and_weeks() {
+        assert_eq!(parse_offset("30m").unwrap(), Duration::minutes(30));
+        assert_eq!(parse_offset("3h").unwrap(), Duration::hours(3));
+        assert_eq!(parse_offset("2d").unwrap(), Duration::days(2));
+        assert_eq!(parse_offset("1w").unwrap(), Duration::weeks(1));
+        assert!(parse_offset("2").is_err());
+        assert!(parse_offset("0d").is_err());
+        assert!(parse_offset("2y").is_err());
+    }
+
+    #[test]
+    fn describe_reports_time_left_or_overdue() {
+        let now = Utc::now();
+        let upcoming = Reminder {
+            due_at: now + Duration::days(2) + Duration::minutes(1),
+            note: Some("ping reviewer".to_string()),
+            notified: false,
+        };
+        let late = Reminder {
+            due_at: now - Duration::hours(3) - Duration::minutes(1),
+            note: None,
+            notified: false,
+        };
+
+        assert_eq!(upcoming.describe(now), "due in 2d — ping reviewer");
+        assert_eq!(late.describe(now), "overdue by 3h");
+        assert!(parse_due("2024-06-01").is_ok());
+        assert!(parse_due("2024-06-01 14:30").is_ok());
+        assert!(parse_due("next tuesday").is_err());
+    }
+}
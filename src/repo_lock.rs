@@ -0,0 +1,204 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread::{self, ThreadId};
+use std::time::{Duration, Instant};
+
+use anyhow::{Result, bail};
+use once_cell::sync::Lazy;
+
+/// How long to wait for another operation on the same repository by default.
+const DEFAULT_TIMEOUT_SECS: u64 = 300;
+/// Waiting longer than this prints a note naming the operation holding the lock.
+const SLOW_WAIT_SECS: u64 = 10;
+
+static REPO_LOCKS: Lazy<Mutex<HashMap<String, Arc<RepoLock>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Default)]
+struct RepoLock {
+    holder: Mutex<Option<Holder>>,
+    released: Condvar,
+}
+
+struct Holder {
+    thread: ThreadId,
+    operation: String,
+    since: Instant,
+    depth: usize,
+}
+
+/// Exclusive access to one or more repositories for mutating git operations
+/// (branch, worktree add/remove, rebase). Released on drop.
+pub struct RepoGuard {
+    locks: Vec<Arc<RepoLock>>,
+}
+
+impl Drop for RepoGuard {
+    fn drop(&mut self) {
+        for lock in self.locks.iter().rev() {
+            let mut holder = lock_ignoring_poison(&lock.holder);
+            if let Some(current) = holder.as_mut() {
+                current.depth -= 1;
+                if current.depth == 0 {
+                    *holder = None;
+                    lock.released.notify_one();
+                }
+            }
+        }
+    }
+}
+
+/// Serialize mutating git operations on `repo` within this process, so
+/// parallel jobs don't race on its index and refs. Other repositories are
+/// unaffected. The lock is re-entrant for the thread that holds it.
+pub fn lock_repo(repo: &str, operation: &str) -> Result<RepoGuard> {
+    lock_repos(&[repo], operation)
+}
+
+/// Lock several repositories at once. Locks are always taken in sorted order,
+/// so two callers locking overlapping sets cannot deadlock.
+pub fn lock_repos(repos: &[&str], operation: &str) -> Result<RepoGuard> {
+    let mut names: Vec<&str> = repos.to_vec();
+    names.sort_unstable();
+    names.dedup();
+
+    let timeout = lock_timeout();
+    let mut guard = RepoGuard { locks: Vec::new() };
+    for name in names {
+        let lock = lock_ignoring_poison(&REPO_LOCKS)
+            .entry(name.to_string())
+            .or_default()
+            .clone();
+        acquire(&lock, name, operation, timeout)?;
+        guard.locks.push(lock);
+    }
+    Ok(guard)
+}
+
+fn acquire(lock: &RepoLock, repo: &str, operation: &str, timeout: Duration) -> Result<()> {
+    let me = thread::current().id();
+    let started = Instant::now();
+    let mut warned = false;
+    let mut holder = lock_ignoring_poison(&lock.holder);
+    loop {
+        match holder.as_mut() {
+            None => {
+                *holder = Some(Holder {
+                    thread: me,
+                    operation: operation.to_string(),
+                    since: Instant::now(),
+                    depth: 1,
+                });
+                return Ok(());
+            }
+            Some(current) if current.thread == me => {
+                current.depth += 1;
+                return Ok(());
+            }
+            Some(current) => {
+                let waited = started.elapsed();
+                if waited >= timeout {
+                    bail!(
+                        "Timed out after {}s waiting to {operation} in '{repo}': '{}' has held the repository for {}s",
+                        waited.as_secs(),
+                        current.operation,
+                        current.since.elapsed().as_secs()
+                    );
+                }
+                if !warned && waited >= Duration::from_secs(SLOW_WAIT_SECS) {
+                    eprintln!(
+                        "⏳ Waiting to {operation} in '{repo}' while '{}' finishes",
+                        current.operation
+                    );
+                    warned = true;
+                }
+                let wait = (timeout - waited).min(Duration::from_secs(SLOW_WAIT_SECS));
+                holder = lock
+                    .released
+                    .wait_timeout(holder, wait)
+                    .map(|(guard, _)| guard)
+                    .unwrap_or_else(|poisoned| poisoned.into_inner().0);
+            }
+        }
+    }
+}
+
+/// `PIGS_REPO_LOCK_TIMEOUT_SECS` overrides the default wait.
+fn lock_timeout() -> Duration {
+    let secs = std::env::var("PIGS_REPO_LOCK_TIMEOUT_SECS")
+        .ok()
+        .and_then(|value| value.trim().parse().ok())
+        .unwrap_or(DEFAULT_TIMEOUT_SECS);
+    Duration::from_secs(secs)
+}
+
+fn lock_ignoring_poison<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    #[test]
+    fn the_holding_thread_can_lock_again() {
+        let outer = lock_repo("reentrant-repo", "create").unwrap();
+        let inner = lock_repo("reentrant-repo", "sync").unwrap();
+        drop(inner);
+        drop(outer);
+
+        let (done, finished) = mpsc::channel();
+        thread::spawn(move || {
+            let _guard = lock_repo("reentrant-repo", "delete").unwrap();
+            done.send(()).unwrap();
+        });
+        finished.recv_timeout(Duration::from_secs(5)).unwrap();
+    }
+
+    #[test]
+    fn other_threads_wait_for_the_same_repo_only() {
+        let guard = lock_repo("busy-repo", "create feature").unwrap();
+
+        let (done, finished) = mpsc::channel();
+        let other = done.clone();
+        thread::spawn(move || {
+            let _guard = lock_repo("idle-repo", "create").unwrap();
+            other.send("idle").unwrap();
+        });
+        thread::spawn(move || {
+            let _guard = lock_repos(&["busy-repo", "idle-repo"], "sync").unwrap();
+            done.send("busy").unwrap();
+        });
+
+        assert_eq!(
+            finished.recv_timeout(Duration::from_secs(5)).unwrap(),
+            "idle"
+        );
+        assert!(finished.recv_timeout(Duration::from_millis(200)).is_err());
+        drop(guard);
+        assert_eq!(
+            finished.recv_timeout(Duration::from_secs(5)).unwrap(),
+            "busy"
+        );
+    }
+
+    #[test]
+    fn timeouts_name_the_operation_holding_the_lock() {
+        let lock = RepoLock::default();
+        *lock.holder.lock().unwrap() = Some(Holder {
+            thread: thread::spawn(|| thread::current().id()).join().unwrap(),
+            operation: "rebase child".to_string(),
+            since: Instant::now(),
+            depth: 1,
+        });
+
+        let err = acquire(&lock, "repo", "create", Duration::from_millis(50)).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("'rebase child' has held the repository")
+        );
+    }
+}
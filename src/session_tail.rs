@@ -0,0 +1,427 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use once_cell::sync::Lazy;
+use serde_json::Value;
+
+use crate::claude::{self, SessionInfo};
+use crate::codex::{self, CodexSession};
+
+/// Transcripts followed by the dashboard, shared by payload builds and the
+/// preview watcher so each file is only ever read once.
+static TAILER: Lazy<Mutex<SessionTailer>> = Lazy::new(|| Mutex::new(SessionTailer::default()));
+
+/// Follows Claude and Codex session transcripts, reading only what was
+/// appended since the last look, and keeps a running summary of each.
+#[derive(Default)]
+pub struct SessionTailer {
+    files: HashMap<PathBuf, TailedFile>,
+}
+
+struct TailedFile {
+    offset: u64,
+    /// Bytes after the last complete line, held until the line is finished
+    partial: Vec<u8>,
+    session: Tailed,
+    /// Whether the preview changed since the watcher last asked
+    unreported: bool,
+}
+
+enum Tailed {
+    Claude(SessionInfo),
+    /// `None` until the `session_meta` line has been read
+    Codex(Option<CodexSession>),
+    /// A Codex file that doesn't start with `session_meta`
+    Skipped,
+}
+
+#[derive(Clone, Copy)]
+enum Provider {
+    Claude,
+    Codex,
+}
+
+impl TailedFile {
+    fn new(provider: Provider) -> Self {
+        Self {
+            offset: 0,
+            partial: Vec::new(),
+            session: match provider {
+                Provider::Claude => Tailed::Claude(SessionInfo::default()),
+                Provider::Codex => Tailed::Codex(None),
+            },
+            unreported: false,
+        }
+    }
+
+    /// The latest user message and its timestamp, as shown in previews.
+    fn preview(&self) -> (Option<&str>, Option<DateTime<Utc>>) {
+        match &self.session {
+            Tailed::Claude(session) => (
+                Some(session.last_user_message.as_str()).filter(|message| !message.is_empty()),
+                session.last_timestamp,
+            ),
+            Tailed::Codex(Some(session)) => {
+                (session.last_user_message.as_deref(), session.last_timestamp)
+            }
+            Tailed::Codex(None) | Tailed::Skipped => (None, None),
+        }
+    }
+
+    fn apply(&mut self, path: &Path, line: &[u8]) {
+        let Ok(value) = serde_json::from_slice::<Value>(line) else {
+            return;
+        };
+        match &mut self.session {
+            Tailed::Claude(session) => session.apply(&value),
+            Tailed::Codex(Some(session)) => session.apply(&value),
+            Tailed::Codex(None) => {
+                self.session = match CodexSession::from_meta(&value, path) {
+                    Ok(Some(session)) => Tailed::Codex(Some(session)),
+                    _ => Tailed::Skipped,
+                };
+            }
+            Tailed::Skipped => {}
+        }
+    }
+}
+
+impl SessionTailer {
+    /// Read whatever was appended to `path` since the last call. A file that
+    /// shrank was rewritten, so it is read again from the start.
+    fn follow(&mut self, path: &Path, provider: Provider) {
+        let Ok(len) = fs::metadata(path).map(|meta| meta.len()) else {
+            return;
+        };
+        let file = self
+            .files
+            .entry(path.to_path_buf())
+            .or_insert_with(|| TailedFile::new(provider));
+        if len < file.offset {
+            *file = TailedFile::new(provider);
+        }
+        if len == file.offset || matches!(file.session, Tailed::Skipped) {
+            return;
+        }
+
+        let mut appended = Vec::new();
+        let read = File::open(path).and_then(|mut handle| {
+            handle.seek(SeekFrom::Start(file.offset))?;
+            handle.read_to_end(&mut appended)
+        });
+        let Ok(read) = read else {
+            return;
+        };
+        file.offset += read as u64;
+        file.partial.extend_from_slice(&appended);
+        let Some(end) = file.partial.iter().rposition(|byte| *byte == b'\n') else {
+            return;
+        };
+
+        let before = preview_key(file.preview());
+        let complete: Vec<u8> = file.partial.drain(..=end).collect();
+        for line in complete.split(|byte| *byte == b'\n') {
+            if !line.is_empty() {
+                file.apply(path, line);
+            }
+        }
+        if preview_key(file.preview()) != before {
+            file.unreported = true;
+        }
+    }
+
+    /// Claude sessions for a worktree, newest first, as `get_claude_sessions`
+    /// returns them.
+    fn claude_sessions(&mut self, worktree: &Path) -> Vec<SessionInfo> {
+        let files = claude::session_files(worktree);
+        if let Some(dir) = claude::project_dir(worktree) {
+            let listed: HashSet<&PathBuf> = files.iter().collect();
+            self.files.retain(|path, file| {
+                !matches!(file.session, Tailed::Claude(_))
+                    || path.parent() != Some(dir.as_path())
+                    || listed.contains(path)
+            });
+        }
+
+        let mut sessions = Vec::new();
+        for path in files {
+            self.follow(&path, Provider::Claude);
+            if let Some(TailedFile {
+                session: Tailed::Claude(session),
+                ..
+            }) = self.files.get(&path)
+                && !session.last_user_message.is_empty()
+            {
+                sessions.push(session.clone());
+            }
+        }
+        claude::sort_sessions(&mut sessions);
+        sessions
+    }
+
+    /// Up to `limit` Codex sessions for each worktree, newest first, keyed by
+    /// normalized worktree path.
+    fn codex_sessions(
+        &mut self,
+        worktree_paths: &[PathBuf],
+        limit: usize,
+    ) -> Result<HashMap<PathBuf, Vec<CodexSession>>> {
+        let files = codex::iterate_session_files(true)?;
+        let listed: HashSet<&PathBuf> = files.iter().collect();
+        self.files.retain(|path, file| {
+            !matches!(file.session, Tailed::Codex(_) | Tailed::Skipped) || listed.contains(path)
+        });
+
+        let targets: HashSet<PathBuf> = worktree_paths
+            .iter()
+            .map(|path| codex::normalized_worktree_path(path))
+            .collect();
+        let mut map: HashMap<PathBuf, Vec<CodexSession>> = HashMap::new();
+        for path in &files {
+            self.follow(path, Provider::Codex);
+            let Some(TailedFile {
+                session: Tailed::Codex(Some(session)),
+                ..
+            }) = self.files.get(path)
+            else {
+                continue;
+            };
+            let cwd = codex::normalized_worktree_path(&session.cwd);
+            if !targets.contains(&cwd) {
+                continue;
+            }
+            let entry = map.entry(cwd).or_default();
+            if entry.len() < limit {
+                entry.push(session.clone());
+            }
+        }
+        Ok(map)
+    }
+
+    /// Catch up on every transcript of `worktrees` (`(key, path)` pairs) and
+    /// return the keys whose latest preview changed since the last call, with
+    /// the new message.
+    fn changed_previews(&mut self, worktrees: &[(String, PathBuf)]) -> Vec<(String, String)> {
+        let paths: Vec<PathBuf> = worktrees.iter().map(|(_, path)| path.clone()).collect();
+        let _ = self.codex_sessions(&paths, 0);
+        let by_cwd: HashMap<PathBuf, &str> = worktrees
+            .iter()
+            .map(|(key, path)| (codex::normalized_worktree_path(path), key.as_str()))
+            .collect();
+
+        let mut latest: HashMap<String, (Option<DateTime<Utc>>, String)> = HashMap::new();
+        let mut note = |key: &str, file: &mut TailedFile| {
+            if !std::mem::take(&mut file.unreported) {
+                return;
+            }
+            let (Some(message), timestamp) = file.preview() else {
+                return;
+            };
+            let entry = latest
+                .entry(key.to_string())
+                .or_insert((None, String::new()));
+            if entry.1.is_empty() || timestamp > entry.0 {
+                *entry = (timestamp, message.to_string());
+            }
+        };
+
+        let mut claude_files = HashSet::new();
+        for (key, path) in worktrees {
+            for file in claude::session_files(path) {
+                self.follow(&file, Provider::Claude);
+                if let Some(tailed) = self.files.get_mut(&file) {
+                    note(key, tailed);
+                }
+                claude_files.insert(file);
+            }
+        }
+        // Forget transcripts that were deleted or belong to worktrees that are
+        // gone, like Codex ones above
+        self.files.retain(|path, file| {
+            !matches!(file.session, Tailed::Claude(_)) || claude_files.contains(path)
+        });
+        for tailed in self.files.values_mut() {
+            let cwd = match &tailed.session {
+                Tailed::Codex(Some(session)) => codex::normalized_worktree_path(&session.cwd),
+                _ => continue,
+            };
+            if let Some(key) = by_cwd.get(&cwd) {
+                note(key, tailed);
+            }
+        }
+
+        let mut changed: Vec<(String, String)> = latest
+            .into_iter()
+            .map(|(key, (_, message))| (key, message))
+            .collect();
+        changed.sort();
+        changed
+    }
+}
+
+fn preview_key(
+    preview: (Option<&str>, Option<DateTime<Utc>>),
+) -> (Option<String>, Option<DateTime<Utc>>) {
+    (preview.0.map(str::to_string), preview.1)
+}
+
+/// Claude sessions for a worktree from the shared tailer, newest first.
+pub fn claude_sessions(worktree: &Path) -> Vec<SessionInfo> {
+    TAILER.lock().unwrap().claude_sessions(worktree)
+}
+
+/// Up to `limit` recent Codex sessions for each worktree from the shared
+/// tailer, keyed by normalized worktree path.
+pub fn codex_sessions(
+    worktree_paths: &[PathBuf],
+    limit: usize,
+) -> Result<HashMap<PathBuf, Vec<CodexSession>>> {
+    TAILER.lock().unwrap().codex_sessions(worktree_paths, limit)
+}
+
+/// Worktrees whose latest session message changed since the last call.
+pub fn changed_previews(worktrees: &[(String, PathBuf)]) -> Vec<(String, String)> {
+    TAILER.lock().unwrap().changed_previews(worktrees)
+}
+
+/// Call `on_change` whenever something is written under the Claude or Codex
+/// transcript directories. Returns `None` when neither can be watched, e.g.
+/// because no agent has run on this machine yet; callers then poll. Events
+/// stop once the returned watcher is dropped.
+pub fn watch_transcripts(on_change: impl Fn() + Send + 'static) -> Option<RecommendedWatcher> {
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        if event.is_ok_and(|event| !event.kind.is_access()) {
+            on_change();
+        }
+    })
+    .ok()?;
+    let mut watching = false;
+    for dir in [claude::projects_dir(), codex::sessions_root()]
+        .into_iter()
+        .flatten()
+    {
+        watching |= watcher.watch(&dir, RecursiveMode::Recursive).is_ok();
+    }
+    watching.then_some(watcher)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    fn user_line(text: &str, timestamp: &str) -> String {
+        format!(
+            "{}\n",
+            json!({ "type": "user", "timestamp": timestamp, "message": { "content": text } })
+        )
+    }
+
+    fn message(tailer: &SessionTailer, path: &Path) -> Option<String> {
+        let (message, _) = tailer.files.get(path)?.preview();
+        message.map(str::to_string)
+    }
+
+    #[test]
+    fn follows_appended_lines_and_holds_partial_ones() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("session.jsonl");
+        let mut tailer = SessionTailer::default();
+
+        fs::write(&path, user_line("first", "2024-01-01T00:00:00Z")).unwrap();
+        tailer.follow(&path, Provider::Claude);
+        assert_eq!(message(&tailer, &path).as_deref(), Some("first"));
+
+        // Half a line is held back until the rest arrives
+        let second = user_line("second", "2024-01-01T00:01:00Z");
+        let (head, tail) = second.split_at(20);
+        let mut file = fs::OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(head.as_bytes()).unwrap();
+        tailer.follow(&path, Provider::Claude);
+        assert_eq!(message(&tailer, &path).as_deref(), Some("first"));
+        file.write_all(tail.as_bytes()).unwrap();
+        tailer.follow(&path, Provider::Claude);
+        assert_eq!(message(&tailer, &path).as_deref(), Some("second"));
+        assert!(tailer.files[&path].unreported);
+
+        // A rewritten, shorter file is read again from the start
+        fs::write(&path, user_line("new", "2024-01-02T00:00:00Z")).unwrap();
+        tailer.follow(&path, Provider::Claude);
+        assert_eq!(message(&tailer, &path).as_deref(), Some("new"));
+    }
+
+    #[test]
+    fn forgets_claude_transcripts_that_are_gone() {
+        let home = TempDir::new().unwrap();
+        let worktree = home.path().join("worktree");
+        fs::create_dir_all(&worktree).unwrap();
+        temp_env::with_var("HOME", Some(home.path()), || {
+            let dir = claude::project_dir(&worktree).unwrap();
+            fs::create_dir_all(&dir).unwrap();
+            let first = dir.join("first.jsonl");
+            let second = dir.join("second.jsonl");
+            fs::write(&first, user_line("one", "2024-01-01T00:00:00Z")).unwrap();
+            fs::write(&second, user_line("two", "2024-01-01T00:01:00Z")).unwrap();
+            let worktrees = [("repo/wt".to_string(), worktree.clone())];
+
+            let mut tailer = SessionTailer::default();
+            tailer.changed_previews(&worktrees);
+            assert_eq!(tailer.files.len(), 2);
+
+            fs::remove_file(&first).unwrap();
+            assert_eq!(tailer.claude_sessions(&worktree).len(), 1);
+            assert!(!tailer.files.contains_key(&first));
+
+            // Once the worktree is no longer tracked, its transcripts go too
+            tailer.changed_previews(&[]);
+            assert!(tailer.files.is_empty());
+        });
+    }
+
+    #[test]
+    fn watcher_reports_transcript_writes() {
+        let home = TempDir::new().unwrap();
+        let projects = home.path().join(".claude/projects");
+        fs::create_dir_all(projects.join("project")).unwrap();
+        let (tx, rx) = std::sync::mpsc::channel();
+        let _watcher = temp_env::with_vars(
+            [
+                ("HOME", Some(home.path().as_os_str())),
+                ("PIGS_CODEX_SESSIONS_DIR", None),
+            ],
+            || {
+                watch_transcripts(move || {
+                    let _ = tx.send(());
+                })
+            },
+        )
+        .expect("the Claude projects directory can be watched");
+
+        fs::write(
+            projects.join("project/session.jsonl"),
+            user_line("hi", "2024-01-01T00:00:00Z"),
+        )
+        .unwrap();
+        rx.recv_timeout(std::time::Duration::from_secs(5))
+            .expect("a write is reported");
+    }
+
+    #[test]
+    fn skips_codex_files_without_session_meta() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("rollout.jsonl");
+        let mut tailer = SessionTailer::default();
+
+        fs::write(&path, "{\"type\":\"event_msg\"}\n").unwrap();
+        tailer.follow(&path, Provider::Codex);
+        assert!(matches!(tailer.files[&path].session, Tailed::Skipped));
+    }
+}
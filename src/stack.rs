@@ -0,0 +1,112 @@
+use serde::{Deserialize, Serialize};
+
+/// Records that a worktree's branch is stacked on another worktree's branch.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StackLink {
+    /// Name of the parent worktree in the same repository
+    pub parent: String,
+    /// Parent commit the branch was last based on. `pigs sync` rebases
+    /// everything after this commit onto the parent's new head.
+    pub base: String,
+}
+
+/// One node of the stack forest: repository, worktree name, and parent name.
+pub struct StackNode<'a> {
+    pub repo_name: &'a str,
+    pub name: &'a str,
+    pub parent: Option<&'a str>,
+}
+
+/// Order nodes so every parent comes directly before its children, returning
+/// `(index, depth)` pairs. Roots and siblings keep their input order, so
+/// callers sort first. Nodes whose parent is missing are treated as roots.
+pub fn stack_order(nodes: &[StackNode]) -> Vec<(usize, usize)> {
+    let has_parent = |node: &StackNode| {
+        node.parent.is_some_and(|parent| {
+            nodes
+                .iter()
+                .any(|other| other.repo_name == node.repo_name && other.name == parent)
+        })
+    };
+
+    let mut ordered = Vec::with_capacity(nodes.len());
+    let mut visited = vec![false; nodes.len()];
+    for (index, node) in nodes.iter().enumerate() {
+        if !has_parent(node) {
+            visit(nodes, index, 0, &mut visited, &mut ordered);
+        }
+    }
+    // Anything left is part of a cycle; list it flat rather than dropping it
+    for index in 0..nodes.len() {
+        if !visited[index] {
+            visit(nodes, index, 0, &mut visited, &mut ordered);
+        }
+    }
+    ordered
+}
+
+fn visit(
+    nodes: &[StackNode],
+    index: usize,
+    depth: usize,
+    visited: &mut [bool],
+    ordered: &mut Vec<(usize, usize)>,
+) {
+    if visited[index] {
+        return;
+    }
+    visited[index] = true;
+    ordered.push((index, depth));
+
+    let node = &nodes[index];
+    for (child, candidate) in nodes.iter().enumerate() {
+        if candidate.repo_name == node.repo_name && candidate.parent == Some(node.name) {
+            visit(nodes, child, depth + 1, visited, ordered);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node<'a>(repo_name: &'a str, name: &'a str, parent: Option<&'a str>) -> StackNode<'a> {
+        StackNode {
+            repo_name,
+            name,
+            parent,
+        }
+    }
+
+    #[test]
+    fn children_follow_their_parent() {
+        let nodes = [
+            node("repo", "a-child", Some("b-parent")),
+            node("repo", "b-parent", None),
+            node("repo", "c-grandchild", Some("a-child")),
+            node("repo", "d-solo", None),
+        ];
+
+        assert_eq!(stack_order(&nodes), [(1, 0), (0, 1), (2, 2), (3, 0)]);
+    }
+
+    #[test]
+    fn missing_parents_and_other_repos_are_roots() {
+        let nodes = [
+            node("api", "child", Some("parent")),
+            node("web", "parent", None),
+            node("web", "orphan", Some("gone")),
+        ];
+
+        assert_eq!(stack_order(&nodes), [(0, 0), (1, 0), (2, 0)]);
+    }
+
+    #[test]
+    fn cycles_are_listed_once() {
+        let nodes = [node("repo", "a", Some("b")), node("repo", "b", Some("a"))];
+
+        let ordered = stack_order(&nodes);
+        assert_eq!(ordered.len(), 2);
+        assert_eq!(ordered[0], (0, 0));
+    }
+}
@@ -1,10 +1,17 @@
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::path::{Path, PathBuf};
 
+use crate::activity::AgentUse;
+use crate::artifacts::ArtifactRun;
+use crate::container::ContainerImage;
+use crate::pool::PooledWorktree;
+use crate::reminders::Reminder;
+use crate::stack::StackLink;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WorktreeInfo {
     pub name: String,
@@ -12,6 +19,68 @@ pub struct WorktreeInfo {
     pub path: PathBuf,
     pub repo_name: String,
     pub created_at: DateTime<Utc>,
+    /// Branch the worktree was created from; `pigs sync <name>` updates onto it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_branch: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stack: Option<StackLink>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reminder: Option<Reminder>,
+    /// Prebuilt container the worktree's agents run in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub container: Option<ContainerImage>,
+    /// Saved outputs of recent verification runs, oldest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub artifacts: Vec<ArtifactRun>,
+    /// Free-form note on what the worktree is for, set with `pigs note`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    /// Tags set with `pigs tag`, sorted
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Recent agent launches, oldest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub agent_history: Vec<AgentUse>,
+    /// Main checkout the worktree belongs to; older entries without it ask git
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repo_root: Option<PathBuf>,
+}
+
+impl WorktreeInfo {
+    /// The main checkout of the repository this worktree belongs to.
+    pub fn main_repo_path(&self) -> Result<PathBuf> {
+        if let Some(root) = &self.repo_root {
+            return Ok(root.clone());
+        }
+        crate::git::main_checkout(&self.path).with_context(|| {
+            format!(
+                "Cannot locate the main checkout of worktree '{}'",
+                self.name
+            )
+        })
+    }
+
+    /// The agent launched in this worktree most recently.
+    pub fn last_agent(&self) -> Option<&AgentUse> {
+        self.agent_history.last()
+    }
+}
+
+/// Days a deleted worktree's tombstone is kept unless `tombstone_days` is set.
+const DEFAULT_TOMBSTONE_DAYS: u32 = 30;
+
+/// Record of a deleted worktree, kept so deletions can be told apart from
+/// worktrees that were never seen.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Tombstone {
+    pub deleted_at: DateTime<Utc>,
+    /// When the worktree was created, for `pigs stats` lifetimes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<DateTime<Utc>>,
+    pub branch: String,
+    /// Commit the branch pointed at when the worktree was deleted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub head: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,6 +89,52 @@ pub struct AgentOption {
     pub command: String,
 }
 
+/// Endpoint that receives a JSON POST for worktree and session events.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookConfig {
+    pub url: String,
+    /// Event names to send, e.g. `worktree.created` or `session.*` (default: all)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub events: Vec<String>,
+    /// Extra request headers, e.g. an `Authorization` token
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+}
+
+/// What a dashboard token may do. Each scope includes the ones before it.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, clap::ValueEnum,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum TokenScope {
+    /// View worktrees, sessions, and events
+    Read,
+    /// Also start agent sessions, type into them, and bookmark them
+    Sessions,
+    /// Everything, including shells, tasks, settings, and deleting worktrees
+    Admin,
+}
+
+impl TokenScope {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Read => "read",
+            Self::Sessions => "sessions",
+            Self::Admin => "admin",
+        }
+    }
+}
+
+/// Dashboard bearer token minted with `pigs dashboard token create`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DashboardToken {
+    pub scope: TokenScope,
+    /// Name requests made with the token are attributed to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Default)]
 pub struct PigsState {
     // Key format: "{repo_name}/{worktree_name}"
@@ -34,6 +149,75 @@ pub struct PigsState {
     // Preferred interactive shell command
     #[serde(skip_serializing_if = "Option::is_none")]
     pub shell: Option<String>,
+    // Dashboard bearer tokens mapped to the user name they act as
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub dashboard_users: HashMap<String, String>,
+    // Scoped dashboard bearer tokens; once any exist, every API request needs one
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub dashboard_tokens: HashMap<String, DashboardToken>,
+    // Disable network integrations, same as passing --offline
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub offline: bool,
+    // Record `pigs open` agent sessions as transcripts, same as passing --record
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub record_sessions: bool,
+    // Repo-level settings files allowed to set agent/shell/editor/hooks:
+    // hash of the file path -> hash of the settings that were trusted
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub trusted_repos: HashMap<String, String>,
+    // Webhooks notified of worktree and session events
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub webhooks: Vec<WebhookConfig>,
+    // Lifecycle hooks run for every repository, before the repository's own
+    #[serde(flatten)]
+    pub hooks: HookConfig,
+    // Worktree key set with `pigs focus`; bare commands default to it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub focus: Option<String>,
+    // How unnamed worktrees are named: "word", "words:<n>", or "numbered[:<prefix>]"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name_scheme: Option<String>,
+    // Deleted worktrees by key, dropped by `pigs clean` after `tombstone_days`
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub tombstones: HashMap<String, Tombstone>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tombstone_days: Option<u32>,
+    // Directory new worktrees are created in, instead of next to their repository
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub worktree_root: Option<PathBuf>,
+    // Directory of a new worktree, relative to `worktree_root`, from `{repo}`
+    // and `{name}`; defaults to "{repo}-{name}"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub worktree_dir_template: Option<String>,
+    // Directories `pigs clean` searches for repositories that moved
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub workspace_roots: Vec<PathBuf>,
+    // Repositories the dashboard keeps ready-made worktrees for: repo path -> count
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub worktree_pool: BTreeMap<PathBuf, usize>,
+    // Ready-made worktrees waiting for `pigs create --fast`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pooled_worktrees: Vec<PooledWorktree>,
+}
+
+/// Fill in a `worktree_dir_template`. The result must name the worktree and
+/// stay inside the directory it is relative to.
+fn render_worktree_dir(template: &str, repo_name: &str, worktree_name: &str) -> Result<PathBuf> {
+    if !template.contains("{name}") {
+        anyhow::bail!("worktree_dir_template '{template}' must contain {{name}}");
+    }
+    let rendered = PathBuf::from(
+        template
+            .replace("{repo}", repo_name)
+            .replace("{name}", worktree_name),
+    );
+    let relative = rendered
+        .components()
+        .all(|component| matches!(component, std::path::Component::Normal(_)));
+    if !relative {
+        anyhow::bail!("worktree_dir_template '{template}' must be a relative path without '..'");
+    }
+    Ok(rendered)
 }
 
 impl PigsState {
@@ -41,13 +225,83 @@ impl PigsState {
         format!("{repo_name}/{worktree_name}")
     }
 
+    /// Where a new worktree goes: `worktree_dir_template` (`<repo>-<name>` by
+    /// default) inside `worktree_root` when that is set, else next to the main
+    /// checkout of the repository containing `repo_path`.
+    pub fn worktree_dir(
+        &self,
+        repo_path: &Path,
+        repo_name: &str,
+        worktree_name: &str,
+    ) -> Result<PathBuf> {
+        let template = self
+            .worktree_dir_template
+            .as_deref()
+            .unwrap_or("{repo}-{name}");
+        let rendered = render_worktree_dir(template, repo_name, worktree_name)?;
+        if let Some(root) = &self.worktree_root {
+            return Ok(root.join(rendered));
+        }
+
+        let repo_root =
+            crate::git::main_checkout(repo_path).unwrap_or_else(|| repo_path.to_path_buf());
+        // Next to the checkout, a first component named like the checkout
+        // would put the worktree inside it
+        if rendered.components().next().map(|c| c.as_os_str()) == repo_root.file_name() {
+            anyhow::bail!(
+                "worktree_dir_template '{template}' would create worktrees inside the repository \
+                 at {}; set worktree_root to use it",
+                repo_root.display()
+            );
+        }
+        let parent = repo_root
+            .parent()
+            .context("Repository root has no parent directory for worktrees")?;
+        Ok(parent.join(rendered))
+    }
+
+    /// The worktree set with `pigs focus`, if it still exists.
+    pub fn focused(&self) -> Option<&WorktreeInfo> {
+        self.focus.as_ref().and_then(|key| self.worktrees.get(key))
+    }
+
+    /// Remove a worktree from state, leaving a tombstone with the commit its
+    /// branch was at (`head`).
+    pub fn remove_worktree(&mut self, key: &str, head: Option<String>) -> Option<WorktreeInfo> {
+        let info = self.worktrees.remove(key)?;
+        self.tombstones.insert(
+            key.to_string(),
+            Tombstone {
+                deleted_at: Utc::now(),
+                created_at: Some(info.created_at),
+                branch: info.branch.clone(),
+                head,
+            },
+        );
+        Some(info)
+    }
+
+    /// Drop tombstones older than `tombstone_days`, returning how many.
+    pub fn compact_tombstones(&mut self) -> usize {
+        let days = self.tombstone_days.unwrap_or(DEFAULT_TOMBSTONE_DAYS);
+        let cutoff = Utc::now() - chrono::Duration::days(days.into());
+        let before = self.tombstones.len();
+        self.tombstones
+            .retain(|_, tombstone| tombstone.deleted_at > cutoff);
+        before - self.tombstones.len()
+    }
+
     /// Load global settings then overlay any local `.pigs/settings.json` found
     /// by walking up from the current directory. Local settings override global
-    /// ones for `agent`, `editor`, and `shell`.
+    /// ones for `agent`, `editor`, and `shell`, once the user trusts them.
     pub fn load_with_local_overrides() -> Result<Self> {
         let mut state = Self::load()?;
 
-        if let Some(local) = Self::find_local_settings()? {
+        if let Some((path, settings)) = Self::find_local_settings()?
+            && crate::trust::is_trusted(&path, &settings)?
+        {
+            let local: Self = serde_json::from_value(settings)
+                .with_context(|| format!("Failed to parse {}", path.display()))?;
             if local.agent.is_some() {
                 state.agent = local.agent;
             }
@@ -63,9 +317,10 @@ impl PigsState {
     }
 
     /// Search for a `.pigs/settings.json` in the current directory or any
-    /// ancestor. Returns `Ok(None)` when no local file is found.
+    /// ancestor, returning its path and contents. Returns `Ok(None)` when no
+    /// local file is found.
     /// Skips repo-level config files that don't contain pigs state fields.
-    fn find_local_settings() -> Result<Option<Self>> {
+    pub fn find_local_settings() -> Result<Option<(PathBuf, serde_json::Value)>> {
         let global_path = get_config_path()?;
         let mut dir = std::env::current_dir().ok();
         while let Some(d) = dir {
@@ -80,9 +335,11 @@ impl PigsState {
                     .with_context(|| format!("Failed to read {}", candidate.display()))?;
                 // Try to parse as PigsState; skip files that don't match
                 // (e.g. repo-level RepoConfig files with copy_files)
-                match serde_json::from_str::<Self>(&content) {
-                    Ok(local) => return Ok(Some(local)),
-                    Err(_) => {
+                match serde_json::from_str::<serde_json::Value>(&content) {
+                    Ok(settings) if serde_json::from_value::<Self>(settings.clone()).is_ok() => {
+                        return Ok(Some((candidate, settings)));
+                    }
+                    _ => {
                         // Not a pigs state file, keep walking up
                         dir = d.parent().map(Path::to_path_buf);
                         continue;
@@ -148,7 +405,16 @@ impl PigsState {
             fs::create_dir_all(parent).context("Failed to create config directory")?;
         }
         let content = serde_json::to_string_pretty(self).context("Failed to serialize state")?;
-        fs::write(&config_path, content).context("Failed to write config file")?;
+        // Write a sibling file and rename it over the old one, so an
+        // interrupted write never leaves a truncated state file behind
+        let tmp_path = config_path.with_extension("json.tmp");
+        if let Some(cutoff) = crate::faults::state_write_cutoff(content.len()) {
+            fs::write(&tmp_path, &content.as_bytes()[..cutoff])
+                .context("Failed to write config file")?;
+            anyhow::bail!("Failed to write config file: injected partial write");
+        }
+        fs::write(&tmp_path, content).context("Failed to write config file")?;
+        fs::rename(&tmp_path, &config_path).context("Failed to write config file")?;
         Ok(())
     }
 }
@@ -177,19 +443,86 @@ pub struct RepoConfig {
     pub copy_files: Vec<String>,
     #[serde(default)]
     pub setup_commands: Vec<String>,
+    // Lifecycle hooks, run after the global ones
+    #[serde(flatten)]
+    pub hooks: HookConfig,
+    // Named shell commands the dashboard can run in a worktree (e.g. "test": "cargo test")
+    #[serde(default)]
+    pub tasks: BTreeMap<String, String>,
+    // Files or directories saved after each task run (e.g. "target/junit.xml")
+    #[serde(default)]
+    pub artifacts: Vec<String>,
+    // Prebuilt image for `pigs create --container` (default: the devcontainer's image)
+    #[serde(default)]
+    pub container_image: Option<String>,
 }
 
 impl RepoConfig {
+    /// Setup commands, hooks, tasks, artifacts, and the container image are
+    /// dropped unless the user trusts the file.
     pub fn load(repo_root: &Path) -> Result<Self> {
         let config_path = repo_root.join(".pigs/settings.json");
         if config_path.exists() {
             let content = fs::read_to_string(&config_path)
                 .context("Failed to read repo-level .pigs/settings.json")?;
-            Ok(serde_json::from_str(&content)?)
+            let settings: serde_json::Value = serde_json::from_str(&content)?;
+            let mut config: Self = serde_json::from_value(settings.clone())?;
+            if config.is_privileged() && !crate::trust::is_trusted(&config_path, &settings)? {
+                config.setup_commands.clear();
+                config.hooks = HookConfig::default();
+                config.tasks.clear();
+                config.artifacts.clear();
+                config.container_image = None;
+            }
+            Ok(config)
         } else {
             Ok(Self::default())
         }
     }
+
+    /// Whether the file sets anything that runs commands, reads files outside
+    /// git, or picks the image agents run in.
+    fn is_privileged(&self) -> bool {
+        !(self.setup_commands.is_empty()
+            && self.hooks.is_empty()
+            && self.tasks.is_empty()
+            && self.artifacts.is_empty()
+            && self.container_image.is_none())
+    }
+}
+
+/// Commands run at points in a worktree's life (see `hooks::Hook`), set for
+/// every repository in `~/.pigs/settings.json` or for one in its
+/// `.pigs/settings.json`.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct HookConfig {
+    // In each new worktree once it is created and recorded
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub post_create: Vec<String>,
+    // In a worktree before an agent is launched there
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pre_open: Vec<String>,
+    // In a worktree before and after `pigs sync` updates it
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pre_sync: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub post_sync: Vec<String>,
+    // Before a worktree is removed (in it) and after (in the main repository)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pre_delete: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub post_delete: Vec<String>,
+}
+
+impl HookConfig {
+    pub fn is_empty(&self) -> bool {
+        self.post_create.is_empty()
+            && self.pre_open.is_empty()
+            && self.pre_sync.is_empty()
+            && self.post_sync.is_empty()
+            && self.pre_delete.is_empty()
+            && self.post_delete.is_empty()
+    }
 }
 
 /// Resolve default agent option when no config is present.
@@ -199,3 +532,28 @@ pub fn get_default_agent() -> AgentOption {
         command: "claude --dangerously-skip-permissions".to_string(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::render_worktree_dir;
+    use std::path::PathBuf;
+
+    #[test]
+    fn worktree_dir_templates() {
+        assert_eq!(
+            render_worktree_dir("{repo}-{name}", "app", "fix").unwrap(),
+            PathBuf::from("app-fix")
+        );
+        assert_eq!(
+            render_worktree_dir("{repo}/{name}", "app", "fix").unwrap(),
+            PathBuf::from("app/fix")
+        );
+        assert_eq!(
+            render_worktree_dir("wt-{name}", "app", "fix").unwrap(),
+            PathBuf::from("wt-fix")
+        );
+        assert!(render_worktree_dir("{repo}", "app", "fix").is_err());
+        assert!(render_worktree_dir("../{name}", "app", "fix").is_err());
+        assert!(render_worktree_dir("/tmp/{name}", "app", "fix").is_err());
+    }
+}
@@ -0,0 +1,173 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::state::get_config_dir;
+
+/// One entry of a saved session transcript, with the same fields as the
+/// dashboard's live session events.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TranscriptEvent {
+    pub sequence: u64,
+    pub timestamp: DateTime<Utc>,
+    /// Either `message` or `status`
+    pub kind: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub channel: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+/// A note or bare bookmark attached to one event of a session.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Annotation {
+    pub id: String,
+    pub sequence: u64,
+    #[serde(default)]
+    pub note: Option<String>,
+    pub author: String,
+    pub created_at: DateTime<Utc>,
+}
+
+fn transcript_path(session_id: &str) -> Result<PathBuf> {
+    // Ids are uuids; anything else could escape the directory
+    if session_id.is_empty()
+        || !session_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-')
+    {
+        bail!("Invalid session id '{session_id}'");
+    }
+    Ok(transcripts_dir()?.join(format!("{session_id}.jsonl")))
+}
+
+/// Where saved session transcripts live: `~/.pigs/transcripts`.
+pub fn transcripts_dir() -> Result<PathBuf> {
+    Ok(get_config_dir()?.join("transcripts"))
+}
+
+/// Appends the events of one session to `~/.pigs/transcripts/<id>.jsonl`.
+pub struct TranscriptWriter {
+    file: File,
+    sequence: u64,
+}
+
+impl TranscriptWriter {
+    pub fn create(session_id: &str) -> Result<Self> {
+        let path = transcript_path(session_id)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).context("Failed to create transcripts directory")?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("Failed to open {}", path.display()))?;
+        Ok(Self { file, sequence: 0 })
+    }
+
+    pub fn message(&mut self, role: &str, channel: &str, text: String) -> Result<()> {
+        let event = TranscriptEvent {
+            kind: "message".to_string(),
+            role: Some(role.to_string()),
+            channel: Some(channel.to_string()),
+            text: Some(text),
+            ..self.next_event()
+        };
+        self.append(event)
+    }
+
+    pub fn status(&mut self, status: &str, detail: Option<String>) -> Result<()> {
+        let event = TranscriptEvent {
+            kind: "status".to_string(),
+            status: Some(status.to_string()),
+            detail,
+            ..self.next_event()
+        };
+        self.append(event)
+    }
+
+    fn next_event(&mut self) -> TranscriptEvent {
+        let sequence = self.sequence;
+        self.sequence += 1;
+        TranscriptEvent {
+            sequence,
+            timestamp: Utc::now(),
+            kind: String::new(),
+            role: None,
+            channel: None,
+            text: None,
+            status: None,
+            detail: None,
+        }
+    }
+
+    fn append(&mut self, event: TranscriptEvent) -> Result<()> {
+        writeln!(self.file, "{}", serde_json::to_string(&event)?)
+            .context("Failed to write transcript")
+    }
+}
+
+/// Read a saved transcript, or `None` if no session with that id was saved.
+/// Malformed lines are skipped.
+pub fn read(session_id: &str) -> Result<Option<Vec<TranscriptEvent>>> {
+    let Ok(path) = transcript_path(session_id) else {
+        return Ok(None);
+    };
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let file = File::open(&path).context("Failed to open transcript")?;
+    Ok(Some(
+        BufReader::new(file)
+            .lines()
+            .map_while(Result::ok)
+            .filter_map(|line| serde_json::from_str(&line).ok())
+            .collect(),
+    ))
+}
+
+/// Annotations live next to the session's transcript, in
+/// `~/.pigs/transcripts/<id>.annotations.jsonl`, so they outlive the dashboard
+/// process that took them.
+fn annotations_path(session_id: &str) -> Result<PathBuf> {
+    Ok(transcript_path(session_id)?.with_extension("annotations.jsonl"))
+}
+
+pub fn append_annotation(session_id: &str, annotation: &Annotation) -> Result<()> {
+    let path = annotations_path(session_id)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).context("Failed to create transcripts directory")?;
+    }
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    writeln!(file, "{}", serde_json::to_string(annotation)?).context("Failed to save annotation")
+}
+
+/// A session's saved annotations, oldest first. Malformed lines are skipped.
+pub fn read_annotations(session_id: &str) -> Vec<Annotation> {
+    let Ok(file) = annotations_path(session_id).and_then(|path| Ok(File::open(path)?)) else {
+        return Vec::new();
+    };
+    BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str(&line).ok())
+        .collect()
+}
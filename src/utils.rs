@@ -1,39 +1,6 @@
 use anyhow::{Context, Result};
-use rand::seq::IndexedRandom;
-use rand::{RngCore, SeedableRng};
 use std::path::Path;
 
-pub fn generate_random_name() -> Result<String> {
-    // Allow setting seed for testing
-    let mut rng = if let Ok(seed_str) = std::env::var("PIGS_TEST_SEED") {
-        let seed: u64 = seed_str.parse().unwrap_or(42);
-        Box::new(rand::rngs::StdRng::seed_from_u64(seed)) as Box<dyn RngCore>
-    } else {
-        Box::new(rand::rng()) as Box<dyn RngCore>
-    };
-
-    // Generate 128 bits of entropy for a 12-word mnemonic
-    let mut entropy = [0u8; 16];
-    rng.fill_bytes(&mut entropy);
-
-    let mnemonic = bip39::Mnemonic::from_entropy(&entropy)?;
-    let words: Vec<&str> = mnemonic.words().collect();
-
-    // Use the same RNG for choosing the word
-    let mut chooser_rng = if let Ok(seed_str) = std::env::var("PIGS_TEST_SEED") {
-        let seed: u64 = seed_str.parse().unwrap_or(42);
-        rand::rngs::StdRng::seed_from_u64(seed)
-    } else {
-        let mut entropy_rng = rand::rng();
-        rand::rngs::StdRng::from_rng(&mut entropy_rng)
-    };
-
-    words
-        .choose(&mut chooser_rng)
-        .map(|&word| word.to_string())
-        .context("Failed to generate random name")
-}
-
 /// Sanitize a branch name for use in directory names
 /// Replaces forward slashes with hyphens to avoid creating subdirectories
 pub fn sanitize_branch_name(branch: &str) -> String {
@@ -56,6 +23,21 @@ where
     result
 }
 
+/// Human-readable size, e.g. `512 B`, `3.2 MiB`.
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{size:.1} {}", UNITS[unit])
+}
+
 /// Resolve agent command from state or default, and split into program + args.
 pub fn resolve_agent_command(selected_agent: Option<&str>) -> Result<(String, Vec<String>)> {
     let state = crate::state::PigsState::load_with_local_overrides()?;
@@ -194,6 +176,32 @@ pub fn prepare_agent_command(
     Ok((program, new_args))
 }
 
+/// The command that resumes session `session_id` of `provider` (`claude` or
+/// `codex`), keeping the flags of the first configured agent that runs that
+/// program, e.g. `claude --dangerously-skip-permissions --resume <id>`.
+pub fn resume_agent_command(provider: &str, session_id: &str) -> Result<(String, Vec<String>)> {
+    let state = crate::state::PigsState::load_with_local_overrides()?;
+    let configured = state
+        .agent
+        .unwrap_or_else(|| vec![crate::state::get_default_agent()])
+        .iter()
+        .filter_map(|option| split_agent_command(&option.command).ok())
+        .find(|(program, _)| {
+            Path::new(program)
+                .file_name()
+                .is_some_and(|name| name.eq_ignore_ascii_case(provider))
+        });
+    let (program, mut args) = configured.unwrap_or_else(|| (provider.to_string(), Vec::new()));
+
+    if provider.eq_ignore_ascii_case("codex") {
+        args.push("resume".to_string());
+    } else {
+        args.push("--resume".to_string());
+    }
+    args.push(session_id.to_string());
+    Ok((program, args))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -260,6 +268,39 @@ mod tests {
         });
     }
 
+    #[test]
+    fn resume_agent_command_keeps_configured_flags() {
+        let _guard = ENV_MUTEX.get_or_init(|| Mutex::new(())).lock().unwrap();
+
+        let config_dir = TempDir::new().unwrap();
+        let state = json!({
+            "worktrees": {},
+            "agent": [
+                { "name": "fast", "command": "/usr/local/bin/codex --profile fast" },
+                { "name": "claude", "command": "claude --dangerously-skip-permissions" }
+            ]
+        });
+        fs::write(
+            config_dir.path().join("settings.json"),
+            serde_json::to_string_pretty(&state).unwrap(),
+        )
+        .unwrap();
+
+        let config_dir_str = config_dir.path().to_string_lossy().to_string();
+        temp_env::with_vars([("PIGS_CONFIG_DIR", Some(config_dir_str.as_str()))], || {
+            let (program, args) = resume_agent_command("codex", "abc").unwrap();
+            assert_eq!(program, "/usr/local/bin/codex");
+            assert_eq!(args, vec!["--profile", "fast", "resume", "abc"]);
+
+            let (program, args) = resume_agent_command("claude", "def").unwrap();
+            assert_eq!(program, "claude");
+            assert_eq!(
+                args,
+                vec!["--dangerously-skip-permissions", "--resume", "def"]
+            );
+        });
+    }
+
     #[test]
     fn prepare_agent_command_resumes_latest_codex_session() {
         let _guard = ENV_MUTEX.get_or_init(|| Mutex::new(())).lock().unwrap();
@@ -337,4 +378,11 @@ mod tests {
             },
         );
     }
+
+    #[test]
+    fn format_bytes_picks_a_readable_unit() {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(1536), "1.5 KiB");
+        assert_eq!(format_bytes(3 * 1024 * 1024 * 1024), "3.0 GiB");
+    }
 }
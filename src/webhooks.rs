@@ -0,0 +1,128 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::activity;
+use crate::state::{PigsState, WebhookConfig, WorktreeInfo};
+
+/// Each delivery gives up after this long so a dead endpoint can't stall a command.
+const DELIVERY_TIMEOUT_SECS: u64 = 5;
+
+/// JSON body POSTed to every webhook subscribed to `event`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookEvent {
+    /// e.g. `worktree.created`, `worktree.deleted`, `session.started`, `session.exited`
+    pub event: &'static str,
+    pub timestamp: DateTime<Utc>,
+    pub repo: String,
+    pub worktree: String,
+    pub branch: String,
+    pub path: PathBuf,
+    pub user: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    /// For dashboard task sessions, the repo task being run
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub task: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+impl WebhookEvent {
+    pub fn new(event: &'static str, info: &WorktreeInfo) -> Self {
+        Self {
+            event,
+            timestamp: Utc::now(),
+            repo: info.repo_name.clone(),
+            worktree: info.name.clone(),
+            branch: info.branch.clone(),
+            path: info.path.clone(),
+            user: activity::local_user(),
+            session_id: None,
+            task: None,
+            exit_code: None,
+            detail: None,
+        }
+    }
+}
+
+/// Deliver `event` to the configured webhooks, waiting for each. Failures are
+/// reported but never interrupt the command that triggered them.
+pub fn emit(event: WebhookEvent) {
+    let hooks = match subscribers(event.event) {
+        Ok(hooks) => hooks,
+        Err(err) => {
+            eprintln!("Warning: failed to load webhooks: {err}");
+            return;
+        }
+    };
+    for hook in hooks {
+        if let Err(err) = deliver(&hook, &event) {
+            eprintln!(
+                "Warning: webhook {} for {} failed: {err}",
+                hook.url, event.event
+            );
+        }
+    }
+}
+
+/// Like `emit`, but on its own thread, for callers that can't block.
+pub fn emit_in_background(event: WebhookEvent) {
+    std::thread::spawn(move || emit(event));
+}
+
+fn subscribers(event: &str) -> Result<Vec<WebhookConfig>> {
+    if crate::network::is_offline() {
+        return Ok(Vec::new());
+    }
+    let state = PigsState::load()?;
+    Ok(state
+        .webhooks
+        .into_iter()
+        .filter(|hook| hook.events.is_empty() || hook.events.iter().any(|p| matches(p, event)))
+        .collect())
+}
+
+/// `*` matches everything and `worktree.*` every worktree event.
+fn matches(pattern: &str, event: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => event.starts_with(prefix),
+        None => pattern == event,
+    }
+}
+
+fn deliver(hook: &WebhookConfig, event: &WebhookEvent) -> Result<()> {
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .timeout_global(Some(Duration::from_secs(DELIVERY_TIMEOUT_SECS)))
+        .build()
+        .into();
+    let mut request = agent
+        .post(&hook.url)
+        .header("User-Agent", concat!("pigs/", env!("CARGO_PKG_VERSION")))
+        .header("X-Pigs-Event", event.event);
+    for (name, value) in &hook.headers {
+        request = request.header(name, value);
+    }
+    request.send_json(event).context("request failed")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn event_filters_support_trailing_wildcards() {
+        assert!(matches("*", "session.exited"));
+        assert!(matches("worktree.*", "worktree.created"));
+        assert!(matches("session.started", "session.started"));
+        assert!(!matches("worktree.*", "session.started"));
+        assert!(!matches("session.started", "session.exited"));
+    }
+}
@@ -0,0 +1,591 @@
+//! End-to-end tests for the dashboard. Each test runs a real
+//! `pigs dashboard` against a throwaway repo whose agent is a small scripted
+//! program, then drives it over HTTP and the session WebSocket.
+
+use serde_json::{Value, json};
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::net::TcpStream;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+use tungstenite::{Message, WebSocket};
+
+const TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Fake agent: reports its pid and terminal size, asks the terminal for the
+/// cursor position like real TUIs do, then echoes each line it reads until
+/// `exit`. `size` reports the terminal size again.
+const FAKE_AGENT: &str = r#"stty -echo -icanon
+echo "pid $$"
+echo "size $(stty size)"
+printf '\033[6n'
+IFS= read -r -s -d R reply
+echo "cursor ${reply#*[}"
+stty icanon
+echo "prompt>"
+while IFS= read -r line; do
+    if [ "$line" = exit ]; then
+        echo "bye"
+        exit 3
+    fi
+    if [ "$line" = size ]; then
+        echo "size $(stty size)"
+    fi
+    echo "echo:$line"
+    echo "prompt>"
+done
+"#;
+
+struct DashboardContext {
+    temp_dir: TempDir,
+    repo_dir: PathBuf,
+    config_dir: PathBuf,
+    server: Option<Child>,
+    addr: String,
+}
+
+impl DashboardContext {
+    /// A repo with one worktree, `feature`, and a dashboard serving it.
+    /// `env` is passed to the dashboard process.
+    fn new(env: &[(&str, &str)]) -> Self {
+        let temp_dir = TempDir::new().unwrap();
+        let repo_dir = temp_dir.path().join("test-repo");
+        let config_dir = temp_dir.path().join(".config/pigs");
+        fs::create_dir_all(&repo_dir).unwrap();
+        fs::create_dir_all(&config_dir).unwrap();
+
+        let agent = temp_dir.path().join("agent.sh");
+        fs::write(&agent, FAKE_AGENT).unwrap();
+        fs::write(
+            config_dir.join("settings.json"),
+            serde_json::to_string_pretty(&json!({
+                "worktrees": {},
+                "agent": [{ "name": "fake", "command": format!("bash {}", agent.display()) }]
+            }))
+            .unwrap(),
+        )
+        .unwrap();
+
+        for args in [
+            &["init", "-b", "main"][..],
+            &["config", "user.email", "test@example.com"],
+            &["config", "user.name", "Test User"],
+            &["config", "commit.gpgsign", "false"],
+        ] {
+            git(&repo_dir, args);
+        }
+        fs::write(repo_dir.join("README.md"), "# Test Repo").unwrap();
+        git(&repo_dir, &["add", "."]);
+        git(&repo_dir, &["commit", "-m", "Initial commit"]);
+
+        let mut ctx = Self {
+            temp_dir,
+            repo_dir,
+            config_dir,
+            server: None,
+            addr: String::new(),
+        };
+        let status = ctx.pigs().args(["create", "feature"]).status().unwrap();
+        assert!(status.success(), "pigs create failed");
+        ctx.start_server(env);
+        ctx
+    }
+
+    fn pigs(&self) -> Command {
+        let mut cmd = Command::new(env!("CARGO_BIN_EXE_pigs"));
+        cmd.current_dir(&self.repo_dir)
+            .env("HOME", self.temp_dir.path())
+            .env("PIGS_CONFIG_DIR", &self.config_dir)
+            .env("PIGS_TEST_MODE", "1")
+            .env("NO_COLOR", "1")
+            .env("PIGS_NON_INTERACTIVE", "1")
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+        cmd
+    }
+
+    fn start_server(&mut self, env: &[(&str, &str)]) {
+        let mut cmd = self.pigs();
+        cmd.args(["dashboard", "--addr", "127.0.0.1:0", "--no-browser"])
+            .envs(env.iter().copied())
+            .stdout(Stdio::piped());
+        let mut server = cmd.spawn().unwrap();
+
+        let mut stdout = BufReader::new(server.stdout.take().unwrap());
+        let mut line = String::new();
+        stdout.read_line(&mut line).unwrap();
+        let addr = line
+            .split("http://")
+            .nth(1)
+            .and_then(|rest| rest.split_whitespace().next())
+            .unwrap_or_else(|| panic!("no dashboard address in {line:?}"));
+        self.addr = addr.to_string();
+        // Keep draining so later output never hits a closed pipe
+        std::thread::spawn(move || std::io::copy(&mut stdout, &mut std::io::sink()));
+        self.server = Some(server);
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("http://{}{path}", self.addr)
+    }
+
+    fn post(&self, path: &str, body: Value) -> Value {
+        ureq::post(&self.url(path))
+            .send_json(body)
+            .unwrap()
+            .body_mut()
+            .read_json()
+            .unwrap()
+    }
+
+    /// Status code and JSON body (or `Null`) of a GET.
+    fn get(&self, path: &str) -> (u16, Value) {
+        let response = ureq::get(&self.url(path))
+            .config()
+            .http_status_as_error(false)
+            .build()
+            .call()
+            .unwrap();
+        let status = response.status().as_u16();
+        let body = response.into_body().read_json().unwrap_or(Value::Null);
+        (status, body)
+    }
+
+    fn start_session(&self, size: Value) -> String {
+        let started = self.post("/api/worktrees/test-repo/feature/live-session", size);
+        started["sessionId"].as_str().unwrap().to_string()
+    }
+
+    /// The `/api/events` WebSocket, with a short read timeout so tests can
+    /// also check that nothing more arrives.
+    fn connect_events(&self) -> WebSocket<TcpStream> {
+        let stream = TcpStream::connect(&self.addr).unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(1)))
+            .unwrap();
+        let url = format!("ws://{}/api/events", self.addr);
+        tungstenite::client(url, stream).unwrap().0
+    }
+
+    fn connect(&self, session_id: &str) -> Stream {
+        let stream = TcpStream::connect(&self.addr).unwrap();
+        stream.set_read_timeout(Some(TIMEOUT)).unwrap();
+        let url = format!(
+            "ws://{}/api/sessions/{session_id}/stream?protocolVersion=1",
+            self.addr
+        );
+        let (socket, _) = tungstenite::client(url, stream).unwrap();
+        let mut stream = Stream {
+            socket,
+            output: String::new(),
+            statuses: Vec::new(),
+        };
+        let hello = stream.next_frame();
+        assert_eq!(hello["kind"], "hello", "{hello}");
+        assert_eq!(hello["sessionId"], session_id);
+        stream
+    }
+}
+
+impl Drop for DashboardContext {
+    fn drop(&mut self) {
+        if let Some(server) = &mut self.server {
+            let _ = server.kill();
+            let _ = server.wait();
+        }
+    }
+}
+
+/// A session's WebSocket stream, collecting what the agent printed and the
+/// statuses it went through.
+struct Stream {
+    socket: WebSocket<TcpStream>,
+    output: String,
+    statuses: Vec<(String, Option<String>)>,
+}
+
+impl Stream {
+    fn next_frame(&mut self) -> Value {
+        loop {
+            match self.socket.read().expect("session stream ended") {
+                Message::Text(text) => return serde_json::from_str(&text).unwrap(),
+                Message::Close(_) => panic!("session stream closed"),
+                _ => continue,
+            }
+        }
+    }
+
+    /// Read events until `done` holds for the stream so far.
+    fn wait_for(&mut self, what: &str, done: impl Fn(&Self) -> bool) {
+        let deadline = Instant::now() + TIMEOUT;
+        while !done(self) {
+            assert!(
+                Instant::now() < deadline,
+                "timed out waiting for {what}; output: {:?}, statuses: {:?}",
+                self.output,
+                self.statuses
+            );
+            let event = self.next_frame();
+            match event["kind"].as_str() {
+                Some("message") if event["channel"] == "stdout" => {
+                    self.output
+                        .push_str(event["text"].as_str().unwrap_or_default());
+                }
+                Some("status") => self.statuses.push((
+                    event["status"].as_str().unwrap_or_default().to_string(),
+                    event["detail"].as_str().map(str::to_string),
+                )),
+                _ => {}
+            }
+        }
+    }
+
+    fn wait_for_output(&mut self, text: &str) {
+        self.wait_for(text, |stream| stream.output.contains(text));
+    }
+
+    fn wait_for_status(&mut self, status: &str) {
+        self.wait_for(status, |stream| {
+            stream.statuses.iter().any(|(seen, _)| seen == status)
+        });
+    }
+
+    fn detail(&self, status: &str) -> Option<&str> {
+        self.statuses
+            .iter()
+            .find(|(seen, _)| seen == status)
+            .and_then(|(_, detail)| detail.as_deref())
+    }
+}
+
+fn git(dir: &Path, args: &[&str]) {
+    let status = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .unwrap();
+    assert!(status.success(), "git {args:?} failed");
+}
+
+#[test]
+fn test_live_session_streams_output_and_answers_terminal_queries() {
+    let ctx = DashboardContext::new(&[]);
+    let id = ctx.start_session(json!({ "rows": 30, "cols": 100 }));
+    let mut stream = ctx.connect(&id);
+
+    stream.wait_for_output("size 30 100");
+    // The dashboard answers the cursor query itself and hides it from clients
+    stream.wait_for_output("cursor 30;100");
+    stream.wait_for_output("prompt>");
+    assert!(!stream.output.contains("\x1b[6n"), "{:?}", stream.output);
+
+    // Starting again reattaches to the running session
+    assert_eq!(ctx.start_session(json!({})), id);
+}
+
+#[test]
+fn test_live_session_clamps_the_requested_size() {
+    let ctx = DashboardContext::new(&[]);
+    let id = ctx.start_session(json!({ "rows": 2, "cols": 5000 }));
+    let mut stream = ctx.connect(&id);
+
+    stream.wait_for_output("size 10 1000");
+    stream.wait_for_output("cursor 10;1000");
+}
+
+#[test]
+fn test_live_session_send_writes_to_agent_stdin() {
+    let ctx = DashboardContext::new(&[]);
+    let id = ctx.start_session(json!({}));
+    let mut stream = ctx.connect(&id);
+    stream.wait_for_output("prompt>");
+
+    let sent = ctx.post(
+        &format!("/api/sessions/{id}/send"),
+        json!({ "message": "  fix the tests  " }),
+    );
+    assert_eq!(sent["status"], "ok");
+    stream.wait_for_output("echo:fix the tests");
+
+    // Sent lines are logged as user input alongside the agent's output
+    let (status, logs) = ctx.get(&format!("/api/sessions/{id}/logs"));
+    assert_eq!(status, 200);
+    let events = logs["events"].as_array().unwrap();
+    assert!(events.iter().any(|event| {
+        event["role"] == "user" && event["channel"] == "stdin" && event["text"] == "fix the tests"
+    }));
+
+    // Late subscribers get the backlog before live events
+    let mut late = ctx.connect(&id);
+    late.wait_for_output("echo:fix the tests");
+}
+
+#[test]
+fn test_annotations_survive_a_dashboard_restart() {
+    let mut ctx = DashboardContext::new(&[]);
+    let id = ctx.start_session(json!({}));
+    let mut stream = ctx.connect(&id);
+    stream.wait_for_output("prompt>");
+
+    let (_, logs) = ctx.get(&format!("/api/sessions/{id}/logs"));
+    let sequence = logs["events"][0]["sequence"].as_u64().unwrap();
+    let annotation = ctx.post(
+        &format!("/api/sessions/{id}/annotations"),
+        json!({ "sequence": sequence, "note": "  agent started  " }),
+    );
+    assert_eq!(annotation["note"], "agent started");
+
+    // The new process has no live session, but the annotation was saved
+    drop(stream);
+    if let Some(mut server) = ctx.server.take() {
+        server.kill().unwrap();
+        server.wait().unwrap();
+    }
+    ctx.start_server(&[]);
+    let (status, saved) = ctx.get(&format!("/api/sessions/{id}/annotations"));
+    assert_eq!(status, 200);
+    assert_eq!(saved["annotations"][0]["id"], annotation["id"]);
+    assert_eq!(saved["annotations"][0]["sequence"], sequence);
+    assert_eq!(saved["annotations"][0]["note"], "agent started");
+    assert_eq!(ctx.get("/api/sessions/unknown/annotations").0, 404);
+}
+
+/// Dashboard events received until `deadline`, or until one has `kind`.
+fn read_events(events: &mut WebSocket<TcpStream>, kind: &str, deadline: Instant) -> Vec<Value> {
+    let mut seen = Vec::new();
+    while Instant::now() < deadline {
+        match events.read() {
+            Ok(Message::Text(text)) => {
+                let event: Value = serde_json::from_str(&text).unwrap();
+                let done = event["kind"] == kind;
+                seen.push(event);
+                if done {
+                    break;
+                }
+            }
+            Ok(_) => {}
+            Err(tungstenite::Error::Io(err))
+                if matches!(
+                    err.kind(),
+                    std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+                ) => {}
+            Err(err) => panic!("event stream failed: {err}"),
+        }
+    }
+    seen
+}
+
+#[test]
+fn test_events_stream_publishes_session_exits() {
+    let ctx = DashboardContext::new(&[]);
+    let mut events = ctx.connect_events();
+    let id = ctx.start_session(json!({}));
+    let mut stream = ctx.connect(&id);
+    stream.wait_for_output("prompt>");
+    ctx.post(
+        &format!("/api/sessions/{id}/send"),
+        json!({ "message": "exit" }),
+    );
+
+    let seen = read_events(&mut events, "agent-errored", Instant::now() + TIMEOUT);
+    let exited = seen.last().unwrap();
+    assert_eq!(exited["kind"], "agent-errored", "{seen:?}");
+    assert_eq!(exited["sessionId"], id.as_str());
+    assert_eq!(exited["worktreeKey"], "test-repo/feature");
+}
+
+#[test]
+fn test_worktree_going_dirty_publishes_one_event() {
+    let ctx = DashboardContext::new(&[("PIGS_DIRTY_POLL_SECS", "1")]);
+    let mut events = ctx.connect_events();
+    // Let the watcher see the worktree clean first
+    std::thread::sleep(Duration::from_secs(3));
+
+    let worktree = ctx.temp_dir.path().join("test-repo-feature");
+    fs::write(worktree.join("README.md"), "# Changed").unwrap();
+    let seen = read_events(&mut events, "worktree-dirty", Instant::now() + TIMEOUT);
+    assert_eq!(seen.last().unwrap()["kind"], "worktree-dirty", "{seen:?}");
+    assert_eq!(seen.last().unwrap()["worktreeKey"], "test-repo/feature");
+
+    // Staying dirty over later polls doesn't publish it again
+    fs::write(worktree.join("other.txt"), "more").unwrap();
+    let later = read_events(
+        &mut events,
+        "worktree-dirty",
+        Instant::now() + Duration::from_secs(4),
+    );
+    assert!(
+        later.iter().all(|event| event["kind"] != "worktree-dirty"),
+        "{later:?}"
+    );
+}
+
+#[test]
+fn test_deleting_the_worktree_kills_its_session() {
+    let ctx = DashboardContext::new(&[]);
+    let id = ctx.start_session(json!({}));
+    let mut stream = ctx.connect(&id);
+    stream.wait_for_output("prompt>");
+
+    let response: Value = ureq::delete(&ctx.url("/api/worktrees/test-repo/feature?force=true"))
+        .call()
+        .unwrap()
+        .body_mut()
+        .read_json()
+        .unwrap();
+    assert!(
+        response["message"]
+            .as_str()
+            .unwrap()
+            .contains("stopped 1 session(s)"),
+        "{response}"
+    );
+
+    stream.wait_for_status("stopped");
+    assert_eq!(stream.detail("terminated"), Some("worktree deleted"));
+    assert!(!ctx.temp_dir.path().join("test-repo-feature").exists());
+    // A terminated session leaves the registry right away
+    assert_eq!(ctx.get(&format!("/api/sessions/{id}/logs")).0, 404);
+}
+
+#[test]
+fn test_resizing_a_session_resizes_the_agent_terminal() {
+    let ctx = DashboardContext::new(&[]);
+    let id = ctx.start_session(json!({ "rows": 30, "cols": 100 }));
+    let mut stream = ctx.connect(&id);
+    stream.wait_for_output("prompt>");
+
+    let resized = ctx.post(
+        &format!("/api/sessions/{id}/resize"),
+        json!({ "rows": 40, "cols": 120 }),
+    );
+    assert_eq!(resized, json!({ "rows": 40, "cols": 120 }));
+    ctx.post(
+        &format!("/api/sessions/{id}/send"),
+        json!({ "message": "size" }),
+    );
+    stream.wait_for_output("size 40 120");
+}
+
+#[test]
+fn test_killing_a_session_stops_the_agent_and_forgets_the_session() {
+    let ctx = DashboardContext::new(&[]);
+    let id = ctx.start_session(json!({}));
+    let mut stream = ctx.connect(&id);
+    stream.wait_for_output("prompt>");
+    let pid = stream
+        .output
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("pid "))
+        .unwrap()
+        .to_string();
+
+    let response: Value = ureq::delete(&ctx.url(&format!("/api/sessions/{id}")))
+        .call()
+        .unwrap()
+        .body_mut()
+        .read_json()
+        .unwrap();
+    assert_eq!(response["status"], "ok");
+
+    stream.wait_for_status("stopped");
+    assert_eq!(
+        stream.detail("terminated"),
+        Some("killed from the dashboard")
+    );
+    let alive = Command::new("kill")
+        .args(["-0", &pid])
+        .stderr(Stdio::null())
+        .status()
+        .unwrap();
+    assert!(!alive.success(), "agent {pid} is still running");
+    assert_eq!(ctx.get(&format!("/api/sessions/{id}/logs")).0, 404);
+    assert_ne!(ctx.start_session(json!({})), id);
+}
+
+#[test]
+fn test_finished_sessions_are_kept_until_retention_expires() {
+    let ctx = DashboardContext::new(&[("PIGS_SESSION_RETENTION_SECS", "2")]);
+    let id = ctx.start_session(json!({}));
+    let mut stream = ctx.connect(&id);
+    stream.wait_for_output("prompt>");
+
+    ctx.post(
+        &format!("/api/sessions/{id}/send"),
+        json!({ "message": "exit" }),
+    );
+    // The last output may be streamed after the exit status
+    stream.wait_for_status("stopped");
+    stream.wait_for_output("bye");
+    assert_eq!(stream.detail("stopped"), Some("exit code 3 (failed)"));
+
+    // Logs stay readable for a while after the agent exits...
+    let (status, logs) = ctx.get(&format!("/api/sessions/{id}/logs"));
+    assert_eq!(status, 200);
+    assert!(
+        logs["events"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|event| event["status"] == "stopped")
+    );
+
+    // ...then the session is dropped and a new one can start
+    let deadline = Instant::now() + TIMEOUT;
+    while ctx.get(&format!("/api/sessions/{id}/logs")).0 != 404 {
+        assert!(Instant::now() < deadline, "session was never cleaned up");
+        std::thread::sleep(Duration::from_millis(200));
+    }
+    assert_ne!(ctx.start_session(json!({})), id);
+}
+
+#[test]
+fn test_pull_request_status_is_polled_into_the_worktree_payload() {
+    use std::os::unix::fs::PermissionsExt;
+
+    // Fake `gh` that counts its calls and reports one PR with failing checks
+    let bin = TempDir::new().unwrap();
+    let calls = bin.path().join("calls");
+    fs::write(
+        bin.path().join("gh"),
+        format!(
+            "#!/bin/sh\necho call >> {}\necho '[{{\"number\":7,\"url\":\"https://example.test/pr/7\",\"state\":\"OPEN\",\"headRefName\":\"feature\",\"statusCheckRollup\":[{{\"status\":\"COMPLETED\",\"conclusion\":\"FAILURE\"}}]}}]'\n",
+            calls.display()
+        ),
+    )
+    .unwrap();
+    fs::set_permissions(bin.path().join("gh"), fs::Permissions::from_mode(0o755)).unwrap();
+    let path = format!(
+        "{}:{}",
+        bin.path().display(),
+        std::env::var("PATH").unwrap_or_default()
+    );
+    let ctx = DashboardContext::new(&[("PATH", &path)]);
+
+    let deadline = Instant::now() + TIMEOUT;
+    let pull_request = loop {
+        let (status, payload) = ctx.get("/api/worktrees");
+        assert_eq!(status, 200);
+        let tree = &payload["worktrees"][0];
+        if !tree["pullRequest"]["value"].is_null() {
+            break tree["pullRequest"].clone();
+        }
+        assert!(Instant::now() < deadline, "PR status never showed up");
+        std::thread::sleep(Duration::from_millis(200));
+    };
+    assert_eq!(pull_request["value"]["number"], 7);
+    assert_eq!(pull_request["value"]["checks"], "failing");
+    assert_eq!(pull_request["stale"], false);
+    assert!(pull_request["fetchedAt"].is_string());
+
+    // Further reads are served from the cache until the refresh interval
+    for _ in 0..5 {
+        ctx.get("/api/worktrees");
+    }
+    assert_eq!(fs::read_to_string(&calls).unwrap().lines().count(), 1);
+}
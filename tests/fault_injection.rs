@@ -0,0 +1,184 @@
+use assert_cmd::Command;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+/// A git repository plus an isolated pigs config directory
+struct FaultRepo {
+    _temp_dir: TempDir,
+    repo: PathBuf,
+    config_dir: PathBuf,
+}
+
+impl FaultRepo {
+    fn new() -> Self {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = temp_dir.path().join("test-repo");
+        fs::create_dir(&repo).unwrap();
+        let config_dir = temp_dir.path().join(".config/pigs");
+        fs::create_dir_all(&config_dir).unwrap();
+        fs::write(
+            config_dir.join("settings.json"),
+            serde_json::to_string_pretty(&serde_json::json!({
+                "worktrees": {},
+                "agent": [{ "name": "test", "command": "true" }]
+            }))
+            .unwrap(),
+        )
+        .unwrap();
+
+        let fault_repo = Self {
+            _temp_dir: temp_dir,
+            repo,
+            config_dir,
+        };
+        fault_repo.git(&fault_repo.repo, &["init", "-b", "main"]);
+        fault_repo.git(
+            &fault_repo.repo,
+            &["config", "user.email", "test@example.com"],
+        );
+        fault_repo.git(&fault_repo.repo, &["config", "user.name", "Test User"]);
+        fault_repo.git(&fault_repo.repo, &["config", "commit.gpgsign", "false"]);
+        fs::write(fault_repo.repo.join("README.md"), "# Test Repo").unwrap();
+        fault_repo.git(&fault_repo.repo, &["add", "."]);
+        fault_repo.git(&fault_repo.repo, &["commit", "-m", "Initial commit"]);
+        fault_repo
+    }
+
+    fn git(&self, dir: &Path, args: &[&str]) -> String {
+        let output = std::process::Command::new("git")
+            .current_dir(dir)
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "git {args:?} failed");
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    }
+
+    /// Run pigs in the repository, injecting `faults` when given
+    fn pigs(&self, args: &[&str], faults: Option<&str>) -> assert_cmd::assert::Assert {
+        let mut cmd = Command::new(env!("CARGO_BIN_EXE_pigs"));
+        cmd.current_dir(&self.repo)
+            .env("PIGS_CONFIG_DIR", &self.config_dir)
+            .env("PIGS_NON_INTERACTIVE", "1")
+            .env("PIGS_TEST_MODE", "1")
+            .env_remove("PIGS_FAULT_INJECT")
+            .args(args);
+        if let Some(faults) = faults {
+            cmd.env("PIGS_FAULT_INJECT", faults);
+        }
+        cmd.assert()
+    }
+
+    fn state(&self) -> serde_json::Value {
+        let content = fs::read_to_string(self.config_dir.join("settings.json")).unwrap();
+        serde_json::from_str(&content).expect("state file should stay valid JSON")
+    }
+
+    fn worktree_path(&self, name: &str) -> PathBuf {
+        self.repo
+            .parent()
+            .unwrap()
+            .join(format!("test-repo-{name}"))
+    }
+
+    fn branch_exists(&self, branch: &str) -> bool {
+        std::process::Command::new("git")
+            .current_dir(&self.repo)
+            .args(["show-ref", "--verify", &format!("refs/heads/{branch}")])
+            .output()
+            .unwrap()
+            .status
+            .success()
+    }
+}
+
+#[test]
+fn test_failed_create_leaves_nothing_behind() {
+    let repo = FaultRepo::new();
+
+    // Fail after the branch exists but before the worktree is added
+    repo.pigs(&["create", "doomed"], Some("git_fail_on=worktree add"))
+        .failure();
+    assert!(repo.state()["worktrees"].as_object().unwrap().is_empty());
+    assert!(!repo.worktree_path("doomed").exists());
+    assert!(!repo.branch_exists("doomed"));
+
+    // Fail after the worktree is added but before state is saved
+    repo.pigs(&["create", "doomed"], Some("partial_write=1"))
+        .failure();
+    assert!(repo.state()["worktrees"].as_object().unwrap().is_empty());
+    assert!(!repo.worktree_path("doomed").exists());
+    assert!(!repo.branch_exists("doomed"));
+    assert!(
+        !repo
+            .git(&repo.repo, &["worktree", "list"])
+            .contains("doomed")
+    );
+
+    // The same name works once the faults are gone
+    repo.pigs(&["create", "doomed"], None).success();
+    assert!(repo.worktree_path("doomed").exists());
+    assert!(repo.state()["worktrees"]["test-repo/doomed"].is_object());
+}
+
+#[test]
+fn test_failed_delete_keeps_worktree_in_state() {
+    let repo = FaultRepo::new();
+    repo.pigs(&["create", "keeper"], None).success();
+
+    repo.pigs(&["delete", "keeper"], Some("git_fail_on=worktree remove"))
+        .failure();
+    assert!(repo.worktree_path("keeper").exists());
+    assert!(repo.state()["worktrees"]["test-repo/keeper"].is_object());
+
+    // A worktree that is gone leaves state even if its branch can't be deleted
+    repo.pigs(&["delete", "keeper"], Some("git_fail_on=branch -"))
+        .success();
+    assert!(!repo.worktree_path("keeper").exists());
+    assert!(repo.state()["worktrees"]["test-repo/keeper"].is_null());
+}
+
+#[test]
+fn test_failed_sync_keeps_stack_base() {
+    let repo = FaultRepo::new();
+    repo.pigs(&["create", "parent"], None).success();
+    repo.pigs(&["create", "child", "--stack-on", "parent"], None)
+        .success();
+    let base = repo.state()["worktrees"]["test-repo/child"]["stack"]["base"].clone();
+
+    let parent_path = repo.worktree_path("parent");
+    fs::write(parent_path.join("parent.txt"), "parent change").unwrap();
+    repo.git(&parent_path, &["add", "."]);
+    repo.git(&parent_path, &["commit", "-m", "Parent change"]);
+
+    repo.pigs(&["sync"], Some("git_fail_on=rebase")).failure();
+    assert_eq!(
+        repo.state()["worktrees"]["test-repo/child"]["stack"]["base"],
+        base
+    );
+
+    repo.pigs(&["sync"], None).success();
+    assert_ne!(
+        repo.state()["worktrees"]["test-repo/child"]["stack"]["base"],
+        base
+    );
+}
+
+#[test]
+fn test_random_faults_never_leave_half_created_worktrees() {
+    let repo = FaultRepo::new();
+    for seed in 0..8 {
+        let name = format!("chaos-{seed}");
+        let faults = format!("git_fail=0.15,partial_write=0.3,seed={seed}");
+        let created = repo
+            .pigs(&["create", &name], Some(&faults))
+            .get_output()
+            .status
+            .success();
+        let tracked = repo.state()["worktrees"][format!("test-repo/{name}")].is_object();
+        assert_eq!(tracked, created, "seed {seed}");
+        assert_eq!(repo.worktree_path(&name).exists(), created, "seed {seed}");
+        assert_eq!(repo.branch_exists(&name), created, "seed {seed}");
+    }
+}
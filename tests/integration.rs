@@ -208,6 +208,9 @@ fn test_create_with_name() {
             if let Some(path) = worktree["path"].as_str() {
                 worktree["path"] = json!(ctx.redact_paths(path));
             }
+            if let Some(root) = worktree["repo_root"].as_str() {
+                worktree["repo_root"] = json!(ctx.redact_paths(root));
+            }
         }
     }
     assert_json_snapshot!(state);
@@ -216,6 +219,419 @@ fn test_create_with_name() {
     assert!(ctx.worktree_exists("feature-x"));
 }
 
+#[test]
+fn test_create_json_reports_progress() {
+    let ctx = TestContext::new("test-repo");
+
+    let output = ctx
+        .pigs(&["create", "json-progress", "--json"])
+        .assert()
+        .success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    let events: Vec<serde_json::Value> = stdout
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+
+    let steps: Vec<&str> = events
+        .iter()
+        .filter(|event| event["kind"] == "step")
+        .filter_map(|event| event["step"].as_str())
+        .collect();
+    assert_eq!(
+        steps,
+        ["branching", "worktree-add", "submodules", "copy", "hooks"]
+    );
+
+    let last = events.last().unwrap();
+    assert_eq!(last["kind"], "succeeded");
+    assert_eq!(last["result"]["key"], "test-repo/json-progress");
+    assert!(ctx.worktree_exists("json-progress"));
+
+    let output = ctx
+        .pigs(&["create", "json-progress", "--json"])
+        .assert()
+        .failure();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    let last: serde_json::Value = serde_json::from_str(stdout.lines().last().unwrap()).unwrap();
+    assert_eq!(last["kind"], "failed");
+    assert!(last["error"].as_str().unwrap().contains("already exists"));
+}
+
+#[test]
+fn test_create_generated_names_are_seeded_and_unique() {
+    let ctx = TestContext::new("test-repo");
+
+    for _ in 0..2 {
+        ctx.pigs(&["create"])
+            .env("PIGS_TEST_SEED", "7")
+            .assert()
+            .success();
+    }
+    let state = ctx.read_state();
+    let mut names: Vec<&str> = state["worktrees"]
+        .as_object()
+        .unwrap()
+        .values()
+        .map(|info| info["name"].as_str().unwrap())
+        .collect();
+    names.sort();
+    assert_eq!(names.len(), 2);
+    assert_eq!(names[1], format!("{}-2", names[0]));
+
+    // Numbered names skip the ones in use
+    let mut settings = ctx.read_state();
+    settings["name_scheme"] = json!("numbered:task");
+    ctx.write_state(&settings);
+    ctx.git(&["branch", "task-1"]);
+    ctx.pigs(&["create"]).assert().success();
+    assert!(ctx.worktree_exists("task-2"));
+}
+
+#[test]
+fn test_create_batch_creates_several_worktrees() {
+    let ctx = TestContext::new("test-repo");
+
+    ctx.pigs(&["create", "try-a", "try-b"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Created 2/2 worktrees"));
+    assert!(ctx.worktree_exists("try-a"));
+    assert!(ctx.worktree_exists("try-b"));
+
+    let mut settings = ctx.read_state();
+    settings["name_scheme"] = json!("numbered:agent");
+    ctx.write_state(&settings);
+    ctx.pigs(&["create", "--count", "2"]).assert().success();
+    assert!(ctx.worktree_exists("agent-1"));
+    assert!(ctx.worktree_exists("agent-2"));
+
+    // One failure doesn't stop the others, but fails the command
+    ctx.pigs(&["create", "try-a", "try-c"])
+        .assert()
+        .failure()
+        .stdout(predicates::str::contains("Created 1/2 worktrees"))
+        .stderr(predicates::str::contains(
+            "Failed to create 1 of 2 worktrees: try-a",
+        ));
+    assert!(ctx.worktree_exists("try-c"));
+    assert_eq!(ctx.read_state()["worktrees"].as_object().unwrap().len(), 5);
+
+    ctx.pigs(&["create", "x", "x"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("given more than once"));
+    ctx.pigs(&["create", "-y", "x", "y"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains(
+            "only work when creating a single",
+        ));
+}
+
+#[test]
+fn test_create_copy_flags_override_copy_files() {
+    let ctx = TestContext::new("test-repo");
+    ctx.pigs(&["config", "set", "--local", "copy_files", r#"["big.env"]"#])
+        .assert()
+        .success();
+    fs::write(ctx.repo_dir.join("big.env"), "SECRET=1\n").unwrap();
+    fs::write(ctx.repo_dir.join("extra.txt"), "extra\n").unwrap();
+
+    ctx.pigs(&["create", "full"]).assert().success();
+    let full = ctx.temp_dir.path().join("test-repo-full");
+    assert!(full.join("big.env").exists());
+    assert!(!full.join("extra.txt").exists());
+
+    ctx.pigs(&["create", "lean", "--no-copy", "--copy", "extra.txt"])
+        .assert()
+        .success();
+    let lean = ctx.temp_dir.path().join("test-repo-lean");
+    assert!(!lean.join("big.env").exists());
+    assert_eq!(
+        fs::read_to_string(lean.join("extra.txt")).unwrap(),
+        "extra\n"
+    );
+
+    ctx.pigs(&["create", "broken", "--copy", "missing.txt"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains(
+            "--copy 'missing.txt' is not a file",
+        ));
+    assert!(!ctx.worktree_exists("broken"));
+}
+
+#[test]
+fn test_create_in_custom_location() {
+    let ctx = TestContext::new("test-repo");
+
+    let scratch = ctx.temp_dir.path().join("scratch/wt/exact");
+    ctx.pigs(&["create", "pinned", "--path", scratch.to_str().unwrap()])
+        .assert()
+        .success();
+    assert!(scratch.join("README.md").exists());
+    assert_eq!(
+        ctx.read_state()["worktrees"]["test-repo/pinned"]["path"],
+        scratch.to_str().unwrap()
+    );
+
+    // worktree_root moves every new worktree, keeping the usual directory name
+    let root = ctx.temp_dir.path().join("other-disk");
+    ctx.pigs(&["config", "set", "worktree_root", root.to_str().unwrap()])
+        .assert()
+        .success();
+    ctx.pigs(&["create", "elsewhere"]).assert().success();
+    assert!(root.join("test-repo-elsewhere/README.md").exists());
+    assert!(!ctx.temp_dir.path().join("test-repo-elsewhere").exists());
+    ctx.git(&["branch", "existing"]);
+    ctx.pigs(&["checkout", "existing"]).assert().success();
+    assert!(root.join("test-repo-existing").exists());
+
+    ctx.pigs(&["create", "taken", "--path", scratch.to_str().unwrap()])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("already exists"));
+}
+
+#[test]
+fn test_worktree_dir_template() {
+    let ctx = TestContext::new("test-repo");
+    let root = ctx.temp_dir.path().join("worktrees");
+    let mut settings = ctx.read_state();
+    settings["worktree_root"] = json!(root);
+    settings["worktree_dir_template"] = json!("{repo}/{name}");
+    ctx.write_state(&settings);
+
+    ctx.pigs(&["create", "first"]).assert().success();
+    assert!(root.join("test-repo/first/README.md").exists());
+    ctx.git(&["branch", "topic"]);
+    ctx.pigs(&["checkout", "topic"]).assert().success();
+    assert!(root.join("test-repo/topic").exists());
+
+    ctx.pigs(&["rename", "first", "second", "--move-dir"])
+        .assert()
+        .success();
+    let moved = root.join("test-repo/second");
+    assert!(moved.join("README.md").exists());
+    assert!(!root.join("test-repo/first").exists());
+    ctx.pigs(&["dir", "second"])
+        .assert()
+        .success()
+        .stdout(format!("{}\n", moved.display()));
+
+    let mut settings = ctx.read_state();
+    settings["worktree_dir_template"] = json!("../{name}");
+    ctx.write_state(&settings);
+    ctx.pigs(&["create", "escape"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("without '..'"));
+
+    // Without worktree_root, `{repo}/{name}` would nest worktrees inside the
+    // checkout itself, even when run from a subdirectory
+    settings["worktree_dir_template"] = json!("{repo}/{name}");
+    settings.as_object_mut().unwrap().remove("worktree_root");
+    ctx.write_state(&settings);
+    let subdir = ctx.repo_dir.join("src");
+    fs::create_dir_all(&subdir).unwrap();
+    ctx.pigs(&["create", "nested"])
+        .current_dir(&subdir)
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("set worktree_root"));
+    assert!(!ctx.repo_dir.join("nested").exists());
+
+    // Other templates go next to the main checkout, not the subdirectory
+    settings["worktree_dir_template"] = json!("wt-{name}");
+    ctx.write_state(&settings);
+    ctx.pigs(&["create", "sibling"])
+        .current_dir(&subdir)
+        .assert()
+        .success();
+    assert!(ctx.temp_dir.path().join("wt-sibling/README.md").exists());
+}
+
+#[test]
+fn test_create_runs_post_create_hooks_until_one_fails() {
+    let ctx = TestContext::new("test-repo");
+    fs::create_dir_all(ctx.repo_dir.join(".pigs")).unwrap();
+    fs::write(
+        ctx.repo_dir.join(".pigs/settings.json"),
+        r#"{"post_create": ["echo \"$PWD\" > hook.log", "exit 3", "echo never > never.log"]}"#,
+    )
+    .unwrap();
+
+    // Untrusted hooks don't run
+    ctx.pigs(&["create", "untrusted"]).assert().success();
+    assert!(
+        !ctx.temp_dir
+            .path()
+            .join("test-repo-untrusted/hook.log")
+            .exists()
+    );
+
+    ctx.pigs(&["trust"]).assert().success();
+    let output = ctx
+        .pigs(&["create", "hooked"])
+        .assert()
+        .failure()
+        .stdout(predicates::str::contains("Running post_create hook 1/3"))
+        .stderr(predicates::str::contains(
+            "post_create hook failed (exit 3): exit 3; skipped the 1 after it",
+        ));
+    let stderr = String::from_utf8_lossy(&output.get_output().stderr);
+    assert!(stderr.contains("Worktree 'hooked' was created"), "{stderr}");
+
+    // The worktree is kept, and the hooks ran inside it in order
+    let path = ctx.temp_dir.path().join("test-repo-hooked");
+    let ran_in = fs::read_to_string(path.join("hook.log")).unwrap();
+    assert_eq!(
+        fs::canonicalize(ran_in.trim()).unwrap(),
+        fs::canonicalize(&path).unwrap()
+    );
+    assert!(!path.join("never.log").exists());
+    assert!(ctx.read_state()["worktrees"]["test-repo/hooked"].is_object());
+}
+
+#[test]
+fn test_create_fast_claims_a_pooled_worktree() {
+    let ctx = TestContext::new("test-repo");
+    fs::create_dir_all(ctx.repo_dir.join(".pigs")).unwrap();
+    fs::write(
+        ctx.repo_dir.join(".pigs/settings.json"),
+        r#"{"setup_commands": ["echo ran >> setup.log"], "post_create": ["echo hook >> post.log"]}"#,
+    )
+    .unwrap();
+    ctx.pigs(&["trust"]).assert().success();
+    let mut state = ctx.read_state();
+    state["worktree_pool"] = json!({ ctx.repo_dir.to_str().unwrap(): 1 });
+    ctx.write_state(&state);
+
+    ctx.pigs(&["pool", "--fill"]).assert().success();
+    let pooled = ctx.read_state()["pooled_worktrees"].clone();
+    assert_eq!(pooled.as_array().unwrap().len(), 1);
+    let pool_branch = pooled[0]["branch"].as_str().unwrap().to_string();
+
+    let output = ctx.pigs(&["create", "quick", "--fast"]).assert().success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(stdout.contains("Claiming a pooled worktree"), "{stdout}");
+
+    let path = ctx.temp_dir.path().join("test-repo-quick");
+    let branch = ctx.git(&["-C", path.to_str().unwrap(), "branch", "--show-current"]);
+    assert_eq!(String::from_utf8_lossy(&branch.stdout).trim(), "quick");
+    // Setup ran once, when the pool was filled, and post_create when claimed
+    assert_eq!(fs::read_to_string(path.join("setup.log")).unwrap(), "ran\n");
+    assert_eq!(fs::read_to_string(path.join("post.log")).unwrap(), "hook\n");
+    let state = ctx.read_state();
+    assert!(state.get("pooled_worktrees").is_none());
+    assert_eq!(state["worktrees"]["test-repo/quick"]["base_branch"], "main");
+    let branches = ctx.git(&["branch", "--list", &pool_branch]);
+    assert!(branches.stdout.is_empty());
+
+    // An empty pool falls back to a regular create
+    let output = ctx.pigs(&["create", "slow", "--fast"]).assert().success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(stdout.contains("No pooled worktree ready"), "{stdout}");
+    assert!(ctx.worktree_exists("slow"));
+
+    // A claim that fails puts the worktree back in the pool as it was
+    ctx.pigs(&["pool", "--fill"]).assert().success();
+    let pooled = ctx.read_state()["pooled_worktrees"][0].clone();
+    let pooled_path = PathBuf::from(pooled["path"].as_str().unwrap());
+    let pool_branch = pooled["branch"].as_str().unwrap();
+    for dir in [&pooled_path, &ctx.repo_dir] {
+        let dir = dir.to_str().unwrap();
+        ctx.git(&["-C", dir, "commit", "-q", "--allow-empty", "-m", dir]);
+    }
+    ctx.pigs(&["create", "stuck", "--fast"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("Failed to fast-forward"));
+    assert!(!ctx.worktree_exists("stuck"));
+    assert!(pooled_path.exists());
+    assert_eq!(ctx.read_state()["pooled_worktrees"][0], pooled);
+    let branch = ctx.git(&[
+        "-C",
+        pooled_path.to_str().unwrap(),
+        "branch",
+        "--show-current",
+    ]);
+    assert_eq!(String::from_utf8_lossy(&branch.stdout).trim(), pool_branch);
+    assert!(ctx.git(&["branch", "--list", "stuck"]).stdout.is_empty());
+
+    // Pooled worktrees aren't offered for adoption
+    ctx.pigs(&["add", "--scan"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("already tracked"));
+
+    ctx.pigs(&["pool", "--drain"]).assert().success();
+    assert!(ctx.read_state().get("pooled_worktrees").is_none());
+    let branches = ctx.git(&["branch", "--list", "pigs-pool/*"]);
+    assert!(branches.stdout.is_empty());
+
+    // Nor reported as stale by clean once their directory is gone
+    ctx.pigs(&["pool", "--fill"]).assert().success();
+    let pooled = ctx.read_state()["pooled_worktrees"][0].clone();
+    fs::remove_dir_all(pooled["path"].as_str().unwrap()).unwrap();
+    let output = ctx.pigs(&["clean"]).assert().success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(
+        !stdout.contains("Stale git worktree registration"),
+        "{stdout}"
+    );
+}
+
+#[test]
+fn test_create_tracks_remote_branches() {
+    let ctx = TestContext::new("test-repo");
+    let origin = ctx.temp_dir.path().join("remote/test-repo.git");
+    ctx.git(&["init", "--bare", origin.to_str().unwrap()]);
+    ctx.git(&["remote", "add", "origin", origin.to_str().unwrap()]);
+    for branch in ["feature-x", "teammate"] {
+        ctx.git(&["branch", branch]);
+        ctx.git(&["push", "origin", branch]);
+        ctx.git(&["branch", "-D", branch]);
+    }
+    // Not fetched yet: --track has to fetch it
+    ctx.git(&["update-ref", "-d", "refs/remotes/origin/teammate"]);
+
+    let upstream = |worktree: &str, branch: &str| {
+        let output = std::process::Command::new("git")
+            .args(["-C", worktree, "rev-parse", "--abbrev-ref"])
+            .arg(format!("{branch}@{{u}}"))
+            .output()
+            .unwrap();
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    };
+
+    ctx.pigs(&["create", "--track", "origin/teammate"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Fetching 'teammate'"));
+    let worktree = ctx.temp_dir.path().join("test-repo-teammate");
+    assert_eq!(
+        upstream(worktree.to_str().unwrap(), "teammate"),
+        "origin/teammate"
+    );
+
+    // A remote-only --from branch checked out under its own name tracks it too
+    ctx.pigs(&["create", "feature-x", "--from", "feature-x"])
+        .assert()
+        .success();
+    let worktree = ctx.temp_dir.path().join("test-repo-feature-x");
+    assert_eq!(
+        upstream(worktree.to_str().unwrap(), "feature-x"),
+        "origin/feature-x"
+    );
+
+    ctx.pigs(&["create", "--track", "feature-x"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("expected <remote>/<branch>"));
+}
+
 #[test]
 fn test_create_random_name() {
     let ctx = TestContext::new("test-repo");
@@ -252,6 +668,227 @@ fn test_create_on_wrong_branch() {
     assert_snapshot!(redacted);
 }
 
+#[cfg(unix)]
+#[test]
+fn test_replay_prompts_into_another_worktree() {
+    let ctx = TestContext::new("test-repo");
+    ctx.pigs(&["create", "first-try"]).assert().success();
+    ctx.pigs(&["create", "second-try"]).assert().success();
+
+    let prompts: String = ["add a parser", "now add tests", "ship it"]
+        .iter()
+        .map(|prompt| {
+            json!({
+                "timestamp": "2025-01-01T00:00:00Z",
+                "repo_name": "test-repo",
+                "worktree": "first-try",
+                "prompt": prompt,
+            })
+            .to_string()
+                + "\n"
+        })
+        .collect();
+    fs::write(ctx.config_dir.join("prompts.jsonl"), prompts).unwrap();
+
+    // Fake agent: log its initial argument, then every line typed into it
+    let log = ctx.temp_dir.path().join("agent.log");
+    let agent = ctx.temp_dir.path().join("agent.sh");
+    fs::write(
+        &agent,
+        "#!/bin/sh\necho \"arg:$1\" >> \"$AGENT_LOG\"\nwhile IFS= read -r line; do echo \"stdin:$line\" >> \"$AGENT_LOG\"; done\n",
+    )
+    .unwrap();
+    let mut state = ctx.read_state();
+    state["agent"] = json!([{ "name": "fake", "command": format!("sh {}", agent.display()) }]);
+    fs::write(
+        ctx.config_dir.join("settings.json"),
+        serde_json::to_string_pretty(&state).unwrap(),
+    )
+    .unwrap();
+
+    let output = ctx
+        .pigs(&["replay-prompts", "first-try", "second-try", "--dry-run"])
+        .assert()
+        .success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(stdout.contains("2. now add tests"), "stdout: {stdout}");
+    assert!(!log.exists());
+
+    ctx.pigs(&[
+        "replay-prompts",
+        "first-try",
+        "second-try",
+        "--idle-secs",
+        "1",
+    ])
+    .env("AGENT_LOG", &log)
+    .assert()
+    .success();
+    assert_eq!(
+        fs::read_to_string(&log).unwrap(),
+        "arg:add a parser\nstdin:now add tests\nstdin:ship it\n"
+    );
+
+    let recorded = fs::read_to_string(ctx.config_dir.join("prompts.jsonl")).unwrap();
+    assert_eq!(recorded.matches("\"worktree\":\"second-try\"").count(), 3);
+
+    // A name used in several repositories needs <repo>/<name>
+    let mut state = ctx.read_state();
+    let mut other = state["worktrees"]["test-repo/second-try"].clone();
+    other["repo_name"] = json!("other-repo");
+    state["worktrees"]["other-repo/second-try"] = other;
+    ctx.write_state(&state);
+    ctx.pigs(&["replay-prompts", "first-try", "second-try", "--dry-run"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("use <repo>/<name>"));
+    ctx.pigs(&[
+        "replay-prompts",
+        "first-try",
+        "test-repo/second-try",
+        "--dry-run",
+    ])
+    .assert()
+    .success();
+}
+
+#[test]
+fn test_bench_reports_every_step_and_cleans_up() {
+    let ctx = TestContext::new("test-repo");
+
+    let output = ctx
+        .pigs(&["bench", "--iterations", "2", "--json"])
+        .assert()
+        .success();
+    let report: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+
+    assert_eq!(report["iterations"], 2);
+    let steps: Vec<&str> = report["steps"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|step| step["step"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        steps,
+        [
+            "worktree add",
+            "submodules",
+            "copy files",
+            "setup hooks",
+            "open",
+            "status",
+            "delete"
+        ]
+    );
+
+    let branches =
+        String::from_utf8_lossy(&ctx.git(&["branch", "--list", "pigs-bench-*"]).stdout).to_string();
+    assert!(branches.trim().is_empty(), "leftover branches: {branches}");
+    let worktrees = String::from_utf8_lossy(&ctx.git(&["worktree", "list"]).stdout).to_string();
+    assert_eq!(
+        worktrees.lines().count(),
+        1,
+        "leftover worktrees: {worktrees}"
+    );
+    assert!(
+        ctx.read_state()["worktrees"]
+            .as_object()
+            .is_none_or(|w| w.is_empty())
+    );
+}
+
+#[test]
+fn test_report_compares_worktree_group() {
+    let ctx = TestContext::new("test-repo");
+
+    ctx.pigs(&["create", "exp-a"]).assert().success();
+    ctx.pigs(&["create", "exp-b"]).assert().success();
+    ctx.pigs(&["create", "other"]).assert().success();
+
+    let output = ctx.pigs(&["report", "exp"]).assert().success();
+    let markdown = String::from_utf8_lossy(&output.get_output().stdout).to_string();
+    assert!(markdown.contains("| Worktree | Branch | Agent |"));
+    assert!(markdown.contains("| exp-a | exp-a |"));
+    assert!(markdown.contains("| exp-b | exp-b |"));
+    assert!(!markdown.contains("| other |"));
+
+    let html_path = ctx.temp_dir.path().join("report.html");
+    ctx.pigs(&["report", "exp", "--html", "-o", html_path.to_str().unwrap()])
+        .assert()
+        .success();
+    let html = std::fs::read_to_string(&html_path).unwrap();
+    assert!(html.contains("<table>"));
+    assert!(html.contains("<td>exp-b</td>"));
+
+    ctx.pigs(&["report", "missing"]).assert().failure();
+}
+
+#[test]
+fn test_trace_collects_worktrees_for_an_issue() {
+    let ctx = TestContext::new("test-repo");
+
+    ctx.pigs(&["create", "eng-42-login"]).assert().success();
+    ctx.pigs(&["create", "eng-420-other"]).assert().success();
+    let worktree_path = ctx.temp_dir.path().join("test-repo-eng-42-login");
+    fs::write(worktree_path.join("login.rs"), "fn login() {}").unwrap();
+    ctx.git(&["-C", worktree_path.to_str().unwrap(), "add", "login.rs"]);
+    ctx.git(&[
+        "-C",
+        worktree_path.to_str().unwrap(),
+        "commit",
+        "-m",
+        "Add login",
+    ]);
+    let prompts: String = [
+        ("eng-42-login", "fix the login"),
+        ("eng-42-spike", "try it"),
+    ]
+    .iter()
+    .map(|(worktree, prompt)| {
+        json!({
+            "timestamp": "2025-01-01T00:00:00Z",
+            "repo_name": "test-repo",
+            "worktree": worktree,
+            "prompt": prompt,
+        })
+        .to_string()
+            + "\n"
+    })
+    .collect();
+    fs::write(ctx.config_dir.join("prompts.jsonl"), prompts).unwrap();
+
+    let output = ctx
+        .pigs(&["--offline", "trace", "ENG-42", "--json"])
+        .assert()
+        .success();
+    let trace: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    let worktrees = trace["worktrees"].as_array().unwrap();
+    assert_eq!(worktrees.len(), 2, "{trace}");
+    assert_eq!(worktrees[0]["name"], "eng-42-login");
+    assert_eq!(worktrees[0]["tracked"], true);
+    assert!(
+        worktrees[0]["commits"][0]
+            .as_str()
+            .unwrap()
+            .ends_with("Add login")
+    );
+    assert_eq!(worktrees[0]["prompts"][0]["prompt"], "fix the login");
+    assert_eq!(worktrees[1]["name"], "eng-42-spike");
+    assert_eq!(worktrees[1]["tracked"], false);
+
+    let output = ctx
+        .pigs(&["--offline", "trace", "eng-42"])
+        .assert()
+        .success();
+    let markdown = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(markdown.starts_with("# Trace: eng-42"), "{markdown}");
+    assert!(markdown.contains("## test-repo/eng-42-login"));
+    assert!(markdown.contains("> fix the login"));
+
+    ctx.pigs(&["trace", "ENG-7"]).assert().failure();
+}
+
 // Checkout command tests
 #[test]
 fn test_checkout_branch_creates_worktree() {
@@ -280,6 +917,9 @@ fn test_checkout_branch_creates_worktree() {
             if let Some(path) = worktree["path"].as_str() {
                 worktree["path"] = json!(ctx.redact_paths(path));
             }
+            if let Some(root) = worktree["repo_root"].as_str() {
+                worktree["repo_root"] = json!(ctx.redact_paths(root));
+            }
         }
     }
     assert_json_snapshot!(state);
@@ -369,6 +1009,9 @@ fn test_checkout_pull_request_creates_worktree() {
             if let Some(path) = worktree["path"].as_str() {
                 worktree["path"] = json!(ctx.redact_paths(path));
             }
+            if let Some(root) = worktree["repo_root"].as_str() {
+                worktree["repo_root"] = json!(ctx.redact_paths(root));
+            }
         }
     }
     assert_json_snapshot!(state);
@@ -377,293 +1020,3219 @@ fn test_checkout_pull_request_creates_worktree() {
     assert!(pr_worktree.exists());
 }
 
-// List command tests
 #[test]
-fn test_list_empty() {
+fn test_checkout_pull_request_url() {
     let ctx = TestContext::new("test-repo");
 
-    let output = ctx.pigs(&["list"]).assert().success();
+    let remote_dir = ctx.temp_dir.path().join("remote.git");
+    ctx.git(&["init", "--bare", remote_dir.to_str().unwrap()]);
+    // origin looks like GitHub but fetches from the local bare repository,
+    // which also names the worktrees
+    let origin = "https://github.com/acme/test-repo.git";
+    ctx.git(&["remote", "add", "origin", origin]);
+    ctx.git(&[
+        "config",
+        &format!("url.{}.insteadOf", remote_dir.display()),
+        origin,
+    ]);
+    ctx.git(&["push", "-u", "origin", "main"]);
 
-    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
-    let redacted = ctx.redact_paths(&stdout);
-    assert_snapshot!(redacted);
+    ctx.git(&["checkout", "-b", "pr-source"]);
+    fs::write(ctx.repo_dir.join("FEATURE.txt"), "feature change").unwrap();
+    ctx.git(&["add", "FEATURE.txt"]);
+    ctx.git(&["commit", "--no-gpg-sign", "-m", "Add PR change"]);
+    ctx.git(&["checkout", "main"]);
+    ctx.git(&[
+        "push",
+        "origin",
+        "pr-source:refs/pull/5/head",
+        "pr-source:refs/merge-requests/6/head",
+    ]);
+    ctx.git(&["branch", "-D", "pr-source"]);
+
+    ctx.pigs(&[
+        "--offline",
+        "checkout",
+        "https://github.com/acme/test-repo/pull/5",
+    ])
+    .assert()
+    .failure()
+    .stderr(predicates::str::contains(
+        "Cannot check out pull request #5",
+    ));
+    ctx.pigs(&["checkout", "https://github.com/Acme/test-repo/pull/5/files"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Fetching pull request #5"));
+    assert!(ctx.temp_dir.path().join("remote-pr-5/FEATURE.txt").exists());
+
+    // GitLab merge request links resolve the same way
+    ctx.pigs(&[
+        "checkout",
+        "https://github.com/acme/test-repo/-/merge_requests/6",
+    ])
+    .assert()
+    .success()
+    .stdout(predicates::str::contains("merge request !6"));
+    assert!(ctx.temp_dir.path().join("remote-mr-6").exists());
+
+    ctx.pigs(&["checkout", "https://github.com/other/repo/pull/5"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains(
+            "github.com/other/repo is not this repository",
+        ));
 }
 
 #[test]
-fn test_list_with_worktrees() {
+fn test_offline_mode_skips_network_integrations() {
     let ctx = TestContext::new("test-repo");
 
-    // Create a few worktrees
-    ctx.pigs(&["create", "feature-a"]).assert().success();
-    ctx.pigs(&["create", "feature-b"]).assert().success();
-
-    let output = ctx.pigs(&["list"]).assert().success();
+    let remote_dir = ctx.temp_dir.path().join("remote.git");
+    assert!(
+        std::process::Command::new("git")
+            .args(["init", "--bare", "remote.git"])
+            .current_dir(ctx.temp_dir.path())
+            .status()
+            .unwrap()
+            .success()
+    );
+    ctx.git(&["remote", "add", "origin", remote_dir.to_str().unwrap()]);
+    ctx.git(&["checkout", "-b", "remote-only"]);
+    ctx.git(&["push", "origin", "remote-only"]);
+    ctx.git(&["checkout", "main"]);
+    ctx.git(&["branch", "-D", "remote-only"]);
 
-    // Manually redact timestamps and paths in output for consistent snapshots
-    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
-    let redacted_stdout = ctx.redact_output(&stdout);
-    assert_snapshot!(redacted_stdout);
-}
+    let assert = ctx
+        .pigs(&["--offline", "checkout", "remote-only"])
+        .assert()
+        .failure();
+    let stderr = String::from_utf8_lossy(&assert.get_output().stderr);
+    assert!(stderr.contains("offline mode"), "stderr: {stderr}");
 
-// Delete command tests
-#[test]
+    let assert = ctx
+        .pigs(&["linear", "ENG-42"])
+        .env("PIGS_OFFLINE", "1")
+        .env_remove("LINEAR_API_KEY")
+        .assert()
+        .success();
+    let stdout = String::from_utf8_lossy(&assert.get_output().stdout);
+    assert!(
+        stdout.contains("skipping Linear lookup"),
+        "stdout: {stdout}"
+    );
+    // The repo name comes from the origin remote once one is configured
+    assert!(ctx.temp_dir.path().join("remote-eng-42").exists());
+}
+
+#[test]
+fn test_linear_api_reports_offline_and_fetch_errors() {
+    let ctx = TestContext::new("test-repo");
+    let api_error = |args: &[&str]| {
+        let assert = ctx
+            .pigs(args)
+            .env_remove("LINEAR_API_KEY")
+            .assert()
+            .failure();
+        String::from_utf8_lossy(&assert.get_output().stderr).to_string()
+    };
+
+    let stderr = api_error(&["--offline", "api", "linear/issues"]);
+    assert!(stderr.contains("503"), "stderr: {stderr}");
+    assert!(stderr.contains("offline mode"), "stderr: {stderr}");
+
+    let stderr = api_error(&["api", "linear/issues"]);
+    assert!(stderr.contains("502"), "stderr: {stderr}");
+    assert!(stderr.contains("LINEAR_API_KEY"), "stderr: {stderr}");
+
+    let stderr = api_error(&["api", "linear/not-an-id/worktree", "--json", "{}"]);
+    assert!(stderr.contains("400"), "stderr: {stderr}");
+    assert!(
+        stderr.contains("not a valid Linear task ID"),
+        "stderr: {stderr}"
+    );
+
+    let stderr = api_error(&["--offline", "api", "linear/ENG-1/worktree", "--json", "{}"]);
+    assert!(stderr.contains("503"), "stderr: {stderr}");
+    assert!(stderr.contains("offline mode"), "stderr: {stderr}");
+
+    let stderr = api_error(&[
+        "api",
+        "linear/ENG-1/worktree",
+        "--json",
+        r#"{"repo": "unknown"}"#,
+    ]);
+    assert!(stderr.contains("400"), "stderr: {stderr}");
+    assert!(
+        stderr.contains("Cannot locate the main checkout for repository 'unknown'"),
+        "stderr: {stderr}"
+    );
+
+    let stderr = api_error(&["api", "linear/ENG-1/worktree", "--json", "{}"]);
+    assert!(stderr.contains("502"), "stderr: {stderr}");
+    assert!(stderr.contains("LINEAR_API_KEY"), "stderr: {stderr}");
+    assert!(
+        ctx.read_state()["worktrees"]
+            .as_object()
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_checkout_pull_request_sparse() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let ctx = TestContext::new("test-repo");
+
+    let remote_dir = ctx.temp_dir.path().join("remote.git");
+    assert!(
+        std::process::Command::new("git")
+            .args(["init", "--bare", "remote.git"])
+            .current_dir(ctx.temp_dir.path())
+            .status()
+            .unwrap()
+            .success()
+    );
+    ctx.git(&["remote", "add", "origin", remote_dir.to_str().unwrap()]);
+
+    fs::create_dir_all(ctx.repo_dir.join("src/touched")).unwrap();
+    fs::create_dir_all(ctx.repo_dir.join("vendor")).unwrap();
+    fs::write(ctx.repo_dir.join("src/touched/lib.rs"), "// before").unwrap();
+    fs::write(ctx.repo_dir.join("vendor/big.txt"), "untouched").unwrap();
+    ctx.git(&["add", "."]);
+    ctx.git(&["commit", "--no-gpg-sign", "-m", "Add nested files"]);
+    ctx.git(&["push", "-u", "origin", "main"]);
+
+    ctx.git(&["checkout", "-b", "pr-source"]);
+    fs::write(ctx.repo_dir.join("src/touched/lib.rs"), "// after").unwrap();
+    ctx.git(&["commit", "--no-gpg-sign", "-am", "Touch lib"]);
+    let hash = String::from_utf8_lossy(&ctx.git(&["rev-parse", "pr-source"]).stdout)
+        .trim()
+        .to_string();
+    ctx.git(&["push", "origin", "pr-source"]);
+    assert!(
+        std::process::Command::new("git")
+            .args([
+                "--git-dir",
+                remote_dir.to_str().unwrap(),
+                "update-ref",
+                "refs/pull/7/head",
+                &hash,
+            ])
+            .status()
+            .unwrap()
+            .success()
+    );
+    ctx.git(&["checkout", "main"]);
+
+    // Fake `gh`: `pr view` fails (forcing the pr/N fallback), `pr diff` lists the touched file
+    let bin_dir = ctx.temp_dir.path().join("bin");
+    fs::create_dir_all(&bin_dir).unwrap();
+    let gh = bin_dir.join("gh");
+    fs::write(
+        &gh,
+        "#!/bin/sh\nif [ \"$2\" = \"diff\" ]; then echo src/touched/lib.rs; exit 0; fi\nexit 1\n",
+    )
+    .unwrap();
+    fs::set_permissions(&gh, fs::Permissions::from_mode(0o755)).unwrap();
+    let path = format!(
+        "{}:{}",
+        bin_dir.display(),
+        std::env::var("PATH").unwrap_or_default()
+    );
+
+    ctx.pigs(&["checkout", "7", "--sparse"])
+        .env("PATH", &path)
+        .assert()
+        .success();
+
+    let worktree = ctx.temp_dir.path().join("remote-pr-7");
+    assert_eq!(
+        fs::read_to_string(worktree.join("src/touched/lib.rs")).unwrap(),
+        "// after"
+    );
+    assert!(worktree.join("README.md").exists());
+    assert!(!worktree.join("vendor/big.txt").exists());
+
+    ctx.pigs(&["checkout", "some-branch", "--sparse"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_github_creates_worktree_from_issue() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let ctx = TestContext::new("test-repo");
+
+    // Fake `gh issue view <n>` that reports issue 42 and records the lookup
+    let bin_dir = ctx.temp_dir.path().join("bin");
+    fs::create_dir_all(&bin_dir).unwrap();
+    let gh = bin_dir.join("gh");
+    fs::write(
+        &gh,
+        format!(
+            "#!/bin/sh\necho \"$@\" >> {}\nif [ \"$1 $2\" = \"issue view\" ]; then\n  echo '{{\"number\":42,\"title\":\"Fix the login bug\",\"body\":\"Safari drops the cookie.\",\"url\":\"https://github.com/acme/test-repo/issues/42\"}}'\n  exit 0\nfi\nexit 1\n",
+            bin_dir.join("gh.log").display()
+        ),
+    )
+    .unwrap();
+    fs::set_permissions(&gh, fs::Permissions::from_mode(0o755)).unwrap();
+    let path = format!(
+        "{}:{}",
+        bin_dir.display(),
+        std::env::var("PATH").unwrap_or_default()
+    );
+
+    ctx.pigs(&["github", "https://github.com/acme/test-repo/issues/42"])
+        .env("PATH", &path)
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Found GitHub issue #42"));
+    let log = fs::read_to_string(bin_dir.join("gh.log")).unwrap();
+    assert!(
+        log.contains("issue view https://github.com/acme/test-repo/issues/42"),
+        "{log}"
+    );
+
+    let state = ctx.read_state();
+    let info = &state["worktrees"]["test-repo/42-fix-the-login-bug"];
+    assert_eq!(info["branch"], "42-fix-the-login-bug");
+    assert!(
+        ctx.temp_dir
+            .path()
+            .join("test-repo-42-fix-the-login-bug")
+            .exists()
+    );
+
+    // The issue becomes the agent's first prompt
+    let recorded = fs::read_to_string(ctx.config_dir.join("prompts.jsonl")).unwrap();
+    assert!(
+        recorded.contains("Fix the login bug\\n\\nSafari drops the cookie."),
+        "{recorded}"
+    );
+
+    ctx.pigs(&["--offline", "github", "#7"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("using branch 'issue-7'"));
+    ctx.pigs(&["github", "ENG-7"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("is not a GitHub issue"));
+}
+
+// List command tests
+#[test]
+fn test_clean_merged_removes_merged_worktrees() {
+    let ctx = TestContext::new("test-repo");
+
+    ctx.pigs(&["create", "landed"]).assert().success();
+    ctx.pigs(&["create", "untouched"]).assert().success();
+    ctx.pigs(&["create", "in-progress"]).assert().success();
+
+    let landed = ctx.temp_dir.path().join("test-repo-landed");
+    fs::write(landed.join("landed.txt"), "done\n").unwrap();
+    let in_progress = ctx.temp_dir.path().join("test-repo-in-progress");
+    fs::write(in_progress.join("wip.txt"), "wip\n").unwrap();
+    for (dir, message) in [(&landed, "Land feature"), (&in_progress, "WIP")] {
+        for args in [vec!["add", "."], vec!["commit", "-m", message]] {
+            std::process::Command::new("git")
+                .args(&args)
+                .current_dir(dir)
+                .output()
+                .unwrap();
+        }
+    }
+    ctx.git(&["merge", "--no-ff", "-m", "Merge landed", "landed"]);
+
+    let output = ctx
+        .pigs(&["--offline", "clean", "--merged", "-y"])
+        .assert()
+        .success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(stdout.contains("Removed 1/1 merged worktrees"), "{stdout}");
+
+    assert!(!landed.exists());
+    assert!(ctx.worktree_exists("untouched"));
+    assert!(ctx.worktree_exists("in-progress"));
+    let branches = ctx.git(&["branch", "--list", "landed"]);
+    assert!(branches.stdout.is_empty());
+}
+
+#[test]
+fn test_archive_and_clean_merged_run_as_api_jobs() {
+    let ctx = TestContext::new("test-repo");
+    ctx.pigs(&["create", "landed"]).assert().success();
+    let landed = ctx.temp_dir.path().join("test-repo-landed");
+    fs::write(landed.join("landed.txt"), "done\n").unwrap();
+    for args in [vec!["add", "."], vec!["commit", "-m", "Land feature"]] {
+        std::process::Command::new("git")
+            .args(&args)
+            .current_dir(&landed)
+            .output()
+            .unwrap();
+    }
+    ctx.git(&["merge", "--no-ff", "-m", "Merge landed", "landed"]);
+
+    let job_result = |endpoint: &str| {
+        let output = ctx
+            .pigs(&["--offline", "api", endpoint, "--method", "POST"])
+            .assert()
+            .success();
+        let started: serde_json::Value =
+            serde_json::from_slice(&output.get_output().stdout).unwrap();
+        let job_id = started["jobId"].as_str().unwrap().to_string();
+        let output = ctx
+            .pigs(&["api", &format!("jobs/{job_id}")])
+            .assert()
+            .success();
+        serde_json::from_slice::<serde_json::Value>(&output.get_output().stdout).unwrap()
+    };
+
+    let job = job_result("worktrees/test-repo/landed/archive");
+    assert_eq!(job["kind"], "archive");
+    assert_eq!(job["status"], "succeeded");
+    assert_eq!(job["steps"][0]["name"], "bundle");
+    let archive = std::path::PathBuf::from(job["result"]["path"].as_str().unwrap());
+    assert!(archive.join("branch.bundle").exists());
+
+    let job = job_result("clean");
+    assert_eq!(job["kind"], "clean");
+    assert_eq!(job["status"], "succeeded");
+    assert_eq!(job["result"]["removed"], json!(["test-repo/landed"]));
+    assert_eq!(job["steps"][1]["name"], "remove test-repo/landed");
+    assert!(!landed.exists());
+
+    ctx.pigs(&[
+        "api",
+        "worktrees/test-repo/landed/archive",
+        "--method",
+        "POST",
+    ])
+    .assert()
+    .failure()
+    .stderr(predicates::str::contains("404"));
+}
+
+#[test]
+fn test_cherry_pick_applies_commits_across_worktrees() {
+    let ctx = TestContext::new("test-repo");
+
+    ctx.pigs(&["create", "attempt-1"]).assert().success();
+    ctx.pigs(&["create", "attempt-2"]).assert().success();
+
+    let commit = |dir: &std::path::Path, file: &str, content: &str| {
+        fs::write(dir.join(file), content).unwrap();
+        for args in [vec!["add", "."], vec!["commit", "-m", file]] {
+            std::process::Command::new("git")
+                .args(&args)
+                .current_dir(dir)
+                .output()
+                .unwrap();
+        }
+    };
+    let first = ctx.temp_dir.path().join("test-repo-attempt-1");
+    let second = ctx.temp_dir.path().join("test-repo-attempt-2");
+    commit(&second, "skip.txt", "skip\n");
+    commit(&second, "keep.txt", "keep\n");
+    commit(&second, "also-keep.txt", "keep\n");
+
+    let output = ctx
+        .pigs(&["cherry-pick", "attempt-2", "HEAD~2..HEAD", "attempt-1"])
+        .assert()
+        .success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(stdout.contains("Applied 2 commit(s)"), "{stdout}");
+    assert!(first.join("keep.txt").exists());
+    assert!(first.join("also-keep.txt").exists());
+    assert!(!first.join("skip.txt").exists());
+
+    // Conflicting changes leave the cherry-pick in progress
+    commit(&first, "shared.txt", "one\n");
+    commit(&second, "shared.txt", "two\n");
+    let output = ctx
+        .pigs(&["cherry-pick", "attempt-2", "HEAD", "attempt-1"])
+        .assert()
+        .failure();
+    let stderr = String::from_utf8_lossy(&output.get_output().stderr);
+    assert!(stderr.contains("shared.txt"), "{stderr}");
+    assert!(stderr.contains("git cherry-pick --continue"), "{stderr}");
+
+    ctx.pigs(&["cherry-pick", "attempt-2", "HEAD", "attempt-2"])
+        .assert()
+        .failure();
+
+    // A name used in several repositories needs <repo>/<name>
+    let mut state = ctx.read_state();
+    let mut other = state["worktrees"]["test-repo/attempt-2"].clone();
+    other["repo_name"] = json!("other-repo");
+    state["worktrees"]["other-repo/attempt-2"] = other;
+    ctx.write_state(&state);
+    ctx.pigs(&["cherry-pick", "attempt-2", "HEAD", "attempt-1"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("use <repo>/<name>"));
+}
+
+#[test]
+fn test_merge_lands_a_worktree_branch() {
+    let ctx = TestContext::new("test-repo");
+
+    ctx.pigs(&["create", "plain"]).assert().success();
+    ctx.pigs(&["create", "squashed"]).assert().success();
+
+    let commit = |dir: &std::path::Path, file: &str| {
+        fs::write(dir.join(file), file).unwrap();
+        for args in [vec!["add", "."], vec!["commit", "-m", file]] {
+            std::process::Command::new("git")
+                .args(&args)
+                .current_dir(dir)
+                .output()
+                .unwrap();
+        }
+    };
+    let plain = ctx.temp_dir.path().join("test-repo-plain");
+    let squashed = ctx.temp_dir.path().join("test-repo-squashed");
+    commit(&plain, "plain.txt");
+    commit(&squashed, "one.txt");
+    commit(&squashed, "two.txt");
+
+    // The main checkout is switched back to the base branch first
+    ctx.git(&["switch", "-c", "elsewhere"]);
+
+    let output = ctx.pigs(&["merge", "plain"]).assert().success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(stdout.contains("Merged 'plain' into 'main'"), "{stdout}");
+    assert!(stdout.contains("pigs delete plain"), "{stdout}");
+    assert!(ctx.repo_dir.join("plain.txt").exists());
+    assert!(ctx.worktree_exists("plain"));
+
+    let before = String::from_utf8_lossy(&ctx.git(&["rev-list", "--count", "HEAD"]).stdout)
+        .trim()
+        .to_string();
+    ctx.pigs(&["merge", "squashed", "--squash", "--delete"])
+        .assert()
+        .success();
+    let after = String::from_utf8_lossy(&ctx.git(&["rev-list", "--count", "HEAD"]).stdout)
+        .trim()
+        .to_string();
+    assert_eq!(
+        after.parse::<u32>().unwrap(),
+        before.parse::<u32>().unwrap() + 1
+    );
+    assert!(ctx.repo_dir.join("two.txt").exists());
+    assert!(!ctx.worktree_exists("squashed"));
+    assert!(ctx.read_state()["worktrees"]["test-repo/squashed"].is_null());
+    let branches =
+        String::from_utf8_lossy(&ctx.git(&["branch", "--list", "squashed"]).stdout).to_string();
+    assert!(branches.trim().is_empty());
+}
+
+#[test]
+fn test_init_saves_detected_settings() {
+    let ctx = TestContext::new("test-repo");
+
+    fs::write(ctx.repo_dir.join("AGENTS.md"), "# Agents\n").unwrap();
+    fs::write(
+        ctx.repo_dir.join("Cargo.toml"),
+        "[package]\nname = \"demo\"\n",
+    )
+    .unwrap();
+    fs::write(ctx.repo_dir.join(".gitignore"), ".env\n").unwrap();
+    fs::write(ctx.repo_dir.join(".env"), "TOKEN=1\n").unwrap();
+
+    let output = ctx.pigs(&["create", "feature"]).assert().success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(stdout.contains("Run pigs init"), "{stdout}");
+
+    ctx.pigs(&["init", "-y"]).assert().success();
+
+    let settings: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(ctx.repo_dir.join(".pigs/settings.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(settings["copy_files"], json!([".env"]));
+    assert_eq!(settings["tasks"], json!({ "test": "cargo test" }));
+    assert_eq!(settings["agent"][0]["name"], "codex");
+    // Approved while writing, so the agent override applies without a prompt
+    assert_eq!(
+        ctx.read_state()["trusted_repos"].as_object().unwrap().len(),
+        1
+    );
+
+    let output = ctx.pigs(&["init", "-y"]).assert().success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(stdout.contains("Nothing to add"), "{stdout}");
+}
+
+#[test]
+fn test_init_scaffolds_settings_and_commits_or_ignores_them() {
+    let ctx = TestContext::new("test-repo");
+
+    let output = ctx.pigs(&["init", "-y", "--commit"]).assert().success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(stdout.contains("What each setting does"), "{stdout}");
+    let settings: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(ctx.repo_dir.join(".pigs/settings.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        settings,
+        json!({ "copy_files": [], "setup_commands": [], "tasks": {}, "artifacts": [] })
+    );
+    let log = ctx.git(&["log", "-1", "--format=%s", "--name-only"]);
+    assert_eq!(
+        String::from_utf8_lossy(&log.stdout).trim(),
+        "Add pigs settings\n\n.pigs/settings.json"
+    );
+
+    ctx.pigs(&["init", "-y", "--gitignore"]).assert().success();
+    ctx.pigs(&["init", "-y", "--gitignore"]).assert().success();
+    assert_eq!(
+        fs::read_to_string(ctx.repo_dir.join(".gitignore")).unwrap(),
+        ".pigs/settings.json\n"
+    );
+}
+
+#[test]
+fn test_doctor_reports_missing_agents_and_worktrees() {
+    let ctx = TestContext::new("test-repo");
+    ctx.pigs(&["create", "healthy"]).assert().success();
+
+    let output = ctx.pigs(&["doctor"]).assert().success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(stdout.contains("agent 'test'"), "{stdout}");
+    assert!(stdout.contains("test-repo/healthy"), "{stdout}");
+    assert!(stdout.contains("No problems found"), "{stdout}");
+
+    let mut state = ctx.read_state();
+    state["agent"] = json!([{ "name": "ghost", "command": "pigs-no-such-agent --flag" }]);
+    state["worktrees"]["test-repo/gone"] = json!({
+        "name": "gone",
+        "branch": "gone",
+        "repo_name": "test-repo",
+        "path": "/non/existent/path",
+        "created_at": "2024-01-01T00:00:00Z"
+    });
+    ctx.write_state(&state);
+
+    let output = ctx
+        .pigs(&["doctor"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("found 2 problems"));
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(
+        stdout.contains("`pigs-no-such-agent` not found on PATH"),
+        "{stdout}"
+    );
+    assert!(stdout.contains("/non/existent/path is missing"), "{stdout}");
+    assert!(stdout.contains("Run `pigs clean`"), "{stdout}");
+}
+
+#[test]
+fn test_list_empty() {
+    let ctx = TestContext::new("test-repo");
+
+    let output = ctx.pigs(&["list"]).assert().success();
+
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    let redacted = ctx.redact_paths(&stdout);
+    assert_snapshot!(redacted);
+}
+
+#[test]
+fn test_list_with_worktrees() {
+    let ctx = TestContext::new("test-repo");
+
+    // Create a few worktrees
+    ctx.pigs(&["create", "feature-a"]).assert().success();
+    ctx.pigs(&["create", "feature-b"]).assert().success();
+
+    let output = ctx.pigs(&["list"]).assert().success();
+
+    // Manually redact timestamps and paths in output for consistent snapshots
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    let redacted_stdout = ctx.redact_output(&stdout);
+    assert_snapshot!(redacted_stdout);
+}
+
+#[test]
+fn test_recent_orders_worktrees_by_last_activity() {
+    let ctx = TestContext::new("test-repo");
+
+    for name in ["alpha", "beta", "gamma"] {
+        ctx.pigs(&["create", name]).assert().success();
+    }
+    // A commit in beta dated ahead of everything else, and gamma created later still
+    let beta_path = ctx.read_state()["worktrees"]["test-repo/beta"]["path"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let commit = std::process::Command::new("git")
+        .args(["-C", &beta_path, "commit", "--allow-empty", "-m", "later"])
+        .env("GIT_COMMITTER_DATE", "2090-01-01T00:00:00Z")
+        .output()
+        .unwrap();
+    assert!(commit.status.success());
+    let mut state = ctx.read_state();
+    state["worktrees"]["test-repo/gamma"]["created_at"] = json!("2095-01-01T00:00:00Z");
+    ctx.write_state(&state);
+
+    let output = ctx.pigs(&["recent"]).assert().success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    let order: Vec<usize> = ["test-repo/gamma", "test-repo/beta", "test-repo/alpha"]
+        .iter()
+        .map(|key| {
+            stdout
+                .find(key)
+                .unwrap_or_else(|| panic!("{key}: {stdout}"))
+        })
+        .collect();
+    assert!(order.is_sorted(), "{stdout}");
+
+    let output = ctx.pigs(&["recent", "-n", "2"]).assert().success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(stdout.contains("test-repo/beta"), "{stdout}");
+    assert!(!stdout.contains("test-repo/alpha"), "{stdout}");
+}
+
+#[test]
+fn test_watch_once_prints_worktree_table() {
+    let ctx = TestContext::new("test-repo");
+
+    ctx.pigs(&["create", "quiet"]).assert().success();
+    ctx.pigs(&["create", "busy"]).assert().success();
+    let busy = ctx.temp_dir.path().join("test-repo-busy");
+    fs::write(busy.join("new.txt"), "new\n").unwrap();
+    fs::write(busy.join("README.md"), "changed\n").unwrap();
+    let detach = std::process::Command::new("git")
+        .args(["checkout", "--detach"])
+        .current_dir(&busy)
+        .output()
+        .unwrap();
+    assert!(detach.status.success());
+
+    let output = ctx.pigs(&["watch", "--once"]).assert().success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(
+        stdout.contains("2 worktree(s), 0 with active agents, 1 needing attention"),
+        "{stdout}"
+    );
+    let row = |key: &str| {
+        stdout
+            .lines()
+            .find(|line| line.starts_with(key))
+            .unwrap_or_else(|| panic!("{key}: {stdout}"))
+    };
+    assert!(row("test-repo/quiet").contains("clean"), "{stdout}");
+    let busy_row = row("test-repo/busy");
+    assert!(busy_row.contains("~1 ?1"), "{stdout}");
+    assert!(busy_row.contains("detached HEAD"), "{stdout}");
+
+    ctx.pigs(&["watch", "--interval", "0"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("at least 1 second"));
+}
+
+#[test]
+fn test_tui_requires_a_terminal() {
+    let ctx = TestContext::new("test-repo");
+
+    ctx.pigs(&["tui"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains(
+            "pigs tui needs an interactive terminal",
+        ));
+}
+
+/// Record a Claude session from 2024-01-01 and a later Codex session, plus a
+/// Codex subagent session, for the worktree at `worktree`.
+fn write_agent_sessions(ctx: &TestContext, worktree: &Path) {
+    let worktree = worktree.canonicalize().unwrap();
+    let project = ctx
+        .temp_dir
+        .path()
+        .join(".claude/projects")
+        .join(worktree.to_string_lossy().replace('/', "-"));
+    fs::create_dir_all(&project).unwrap();
+    fs::write(
+        project.join("0b6c7f0e-claude.jsonl"),
+        json!({ "type": "user", "timestamp": "2024-01-01T00:00:00Z", "message": { "content": "fix the login bug" } })
+            .to_string(),
+    )
+    .unwrap();
+
+    let day = ctx.temp_dir.path().join(".codex/sessions/2024/01/02");
+    fs::create_dir_all(&day).unwrap();
+    for (file, id, source, prompt) in [
+        ("rollout-1.jsonl", "codex-main", json!("cli"), "add tests"),
+        (
+            "rollout-2.jsonl",
+            "codex-sub",
+            json!({ "subagent": "review" }),
+            "review it",
+        ),
+    ] {
+        let lines = [
+            json!({ "type": "session_meta", "payload": { "id": id, "cwd": worktree, "source": source, "timestamp": "2024-01-02T00:00:00Z" } }),
+            json!({ "type": "response_item", "timestamp": "2024-01-02T00:01:00Z", "payload": { "type": "message", "role": "user", "content": [{ "type": "input_text", "text": prompt }] } }),
+        ];
+        fs::write(
+            day.join(file),
+            lines.map(|line| line.to_string()).join("\n"),
+        )
+        .unwrap();
+    }
+}
+
+#[test]
+fn test_gc_removes_old_transcripts_and_agent_logs() {
+    let ctx = TestContext::new("test-repo");
+    ctx.pigs(&["create", "feature"]).assert().success();
+    let worktree = ctx.temp_dir.path().join("test-repo-feature");
+    write_agent_sessions(&ctx, &worktree);
+    let elsewhere = ctx
+        .temp_dir
+        .path()
+        .join(".codex/sessions/2024/01/02/rollout-3.jsonl");
+    fs::write(
+        &elsewhere,
+        json!({ "type": "session_meta", "payload": { "id": "other", "cwd": "/somewhere/else" } })
+            .to_string(),
+    )
+    .unwrap();
+    let transcripts = ctx.config_dir.join("transcripts");
+    fs::create_dir_all(&transcripts).unwrap();
+    fs::write(transcripts.join("old.jsonl"), "{}\n").unwrap();
+    fs::write(transcripts.join("new.jsonl"), "{}\n").unwrap();
+
+    let age = |path: &Path| {
+        let long_ago = std::time::SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1 << 30);
+        fs::File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(long_ago)
+            .unwrap();
+    };
+    age(&transcripts.join("old.jsonl"));
+    age(&elsewhere);
+    let claude_dir = ctx.temp_dir.path().join(".claude/projects").join(
+        worktree
+            .canonicalize()
+            .unwrap()
+            .to_string_lossy()
+            .replace('/', "-"),
+    );
+    let claude_file = claude_dir.join("0b6c7f0e-claude.jsonl");
+    age(&claude_file);
+    let codex_day = ctx.temp_dir.path().join(".codex/sessions/2024/01/02");
+    for file in ["rollout-1.jsonl", "rollout-2.jsonl"] {
+        age(&codex_day.join(file));
+    }
+
+    // Session snapshots: an old one from before snapshots kept a reflog, and
+    // one of an old commit taken just now
+    let tree = String::from_utf8(ctx.git(&["rev-parse", "HEAD^{tree}"]).stdout).unwrap();
+    let old_commit = std::process::Command::new("git")
+        .args(["commit-tree", tree.trim(), "-m", "snapshot"])
+        .current_dir(&ctx.repo_dir)
+        .env("GIT_COMMITTER_DATE", "2001-01-01T00:00:00Z")
+        .output()
+        .unwrap();
+    let old_commit = String::from_utf8(old_commit.stdout).unwrap();
+    ctx.git(&["update-ref", "refs/pigs/sessions/old", old_commit.trim()]);
+    ctx.git(&[
+        "update-ref",
+        "--create-reflog",
+        "refs/pigs/sessions/new",
+        old_commit.trim(),
+    ]);
+
+    let output = ctx.pigs(&["gc", "--dry-run"]).assert().success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(
+        stdout.contains("Would remove 1 file(s) older than 30d, reclaiming 3 B"),
+        "{stdout}"
+    );
+    assert!(stdout.contains("session snapshots: 1 ref(s)"), "{stdout}");
+    assert!(transcripts.join("old.jsonl").exists());
+
+    let output = ctx.pigs(&["gc"]).assert().success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(
+        stdout.contains("Removed 1 session snapshot ref(s)"),
+        "{stdout}"
+    );
+    assert!(
+        stdout.contains("Removed 1 file(s) older than 30d"),
+        "{stdout}"
+    );
+    assert!(!transcripts.join("old.jsonl").exists());
+    assert!(transcripts.join("new.jsonl").exists());
+    let refs = String::from_utf8(ctx.git(&["for-each-ref", "refs/pigs/sessions/"]).stdout).unwrap();
+    assert!(!refs.contains("sessions/old"), "{refs}");
+    assert!(refs.contains("sessions/new"), "{refs}");
+
+    let output = ctx.pigs(&["gc", "--agent-logs", "-y"]).assert().success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(stdout.contains("Claude sessions: 1 file(s)"), "{stdout}");
+    assert!(stdout.contains("Codex sessions: 2 file(s)"), "{stdout}");
+    assert!(stdout.contains("Removed 3 file(s)"), "{stdout}");
+    assert!(!claude_file.exists());
+    assert!(!codex_day.join("rollout-1.jsonl").exists());
+    // Sessions of directories pigs doesn't track are left alone
+    assert!(elsewhere.exists());
+}
+
+#[test]
+fn test_du_sorts_worktrees_by_size_with_largest_directories() {
+    let ctx = TestContext::new("test-repo");
+    ctx.pigs(&["create", "small"]).assert().success();
+    ctx.pigs(&["create", "big"]).assert().success();
+    let big = ctx.temp_dir.path().join("test-repo-big");
+    fs::create_dir_all(big.join("packages/app/node_modules/dep")).unwrap();
+    fs::write(
+        big.join("packages/app/node_modules/dep/index.js"),
+        vec![b'x'; 200_000],
+    )
+    .unwrap();
+    fs::create_dir_all(big.join("target")).unwrap();
+    fs::write(big.join("target/out.bin"), vec![0u8; 50_000]).unwrap();
+
+    let output = ctx.pigs(&["du", "--json"]).assert().success();
+    let json: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    assert_eq!(json[0]["name"], "big");
+    assert_eq!(json[1]["name"], "small");
+    assert!(json[0]["bytes"].as_u64().unwrap() > 250_000);
+    let largest: Vec<&str> = json[0]["largest"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|dir| dir["path"].as_str().unwrap())
+        .collect();
+    assert_eq!(largest, ["packages", "packages/app/node_modules", "target"]);
+
+    let output = ctx.pigs(&["du"]).assert().success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(stdout.contains("across 2 worktree(s)"), "{stdout}");
+    assert!(stdout.contains("test-repo/big (active"), "{stdout}");
+    assert!(
+        stdout.contains("195.3 KiB    packages/app/node_modules"),
+        "{stdout}"
+    );
+}
+
+#[test]
+fn test_sessions_lists_claude_and_codex_sessions() {
+    let ctx = TestContext::new("test-repo");
+    ctx.pigs(&["create", "feature"]).assert().success();
+    let path = ctx.read_state()["worktrees"]["test-repo/feature"]["path"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    ctx.pigs(&["sessions", "feature"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("No agent sessions found"));
+
+    write_agent_sessions(&ctx, Path::new(&path));
+    let output = ctx
+        .pigs(&["sessions", "feature", "--json"])
+        .assert()
+        .success();
+    let payload: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    let sessions: Vec<(&str, &str, &str)> = payload["sessions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|session| {
+            (
+                session["provider"].as_str().unwrap(),
+                session["id"].as_str().unwrap(),
+                session["last_user_message"].as_str().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        sessions,
+        vec![
+            ("codex", "codex-main", "add tests"),
+            ("claude", "0b6c7f0e-claude", "fix the login bug"),
+        ]
+    );
+
+    let output = ctx.pigs(&["sessions", "feature"]).assert().success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(stdout.contains("codex-main"), "{stdout}");
+    assert!(stdout.contains("fix the login bug"), "{stdout}");
+}
+
+#[test]
+fn test_resume_launches_the_chosen_session() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let ctx = TestContext::new("test-repo");
+    ctx.pigs(&["create", "feature"]).assert().success();
+    let path = ctx.read_state()["worktrees"]["test-repo/feature"]["path"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    write_agent_sessions(&ctx, Path::new(&path));
+
+    // Agents that record how they were launched
+    let bin_dir = ctx.temp_dir.path().join("bin");
+    fs::create_dir_all(&bin_dir).unwrap();
+    let log = ctx.temp_dir.path().join("agent.log");
+    for agent in ["claude", "codex"] {
+        let script = bin_dir.join(agent);
+        fs::write(
+            &script,
+            format!(
+                "#!/bin/sh
+echo \"{agent} $*\" >> {}\n",
+                log.display()
+            ),
+        )
+        .unwrap();
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+    }
+    let mut state = ctx.read_state();
+    state["agent"] = json!([{
+        "name": "yolo",
+        "command": format!("{} --dangerously-skip-permissions", bin_dir.join("claude").display())
+    }]);
+    ctx.write_state(&state);
+    let path_env = format!(
+        "{}:{}",
+        bin_dir.display(),
+        std::env::var("PATH").unwrap_or_default()
+    );
+
+    ctx.pigs(&["resume", "feature"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("Pass --session"));
+    ctx.pigs(&["resume", "feature", "--session", "missing"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("pigs sessions feature"));
+
+    ctx.pigs(&["resume", "feature", "--session", "0b6c"])
+        .env("PATH", &path_env)
+        .assert()
+        .success();
+    ctx.pigs(&["resume", "feature", "--session", "codex-main"])
+        .env("PATH", &path_env)
+        .assert()
+        .success();
+    assert_eq!(
+        fs::read_to_string(&log).unwrap(),
+        "claude --dangerously-skip-permissions --resume 0b6c7f0e-claude\ncodex resume codex-main\n"
+    );
+
+    let output = ctx.pigs(&["log", "feature", "--json"]).assert().success();
+    let records: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    assert_eq!(
+        records.as_array().unwrap().last().unwrap()["action"],
+        "resume"
+    );
+}
+
+#[test]
+fn test_list_json_reports_paths_and_session_counts() {
+    let ctx = TestContext::new("test-repo");
+
+    ctx.pigs(&["create", "feature-a"]).assert().success();
+
+    let output = ctx.pigs(&["list", "--json"]).assert().success();
+    let json: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    let worktree = &json["worktrees"][0];
+    assert_eq!(worktree["name"], "feature-a");
+    assert_eq!(worktree["repo_name"], "test-repo");
+    assert!(
+        worktree["path"]
+            .as_str()
+            .unwrap()
+            .ends_with("test-repo-feature-a")
+    );
+    assert!(worktree["created_at"].is_string());
+    assert_eq!(worktree["session_count"], 0);
+    assert_eq!(worktree["codex_session_count"], 0);
+    assert!(worktree.get("reminder").is_none());
+}
+
+#[test]
+fn test_list_filters_by_label() {
+    let ctx = TestContext::new("test-repo");
+
+    ctx.pigs(&["create", "docs-change"]).assert().success();
+    ctx.pigs(&["create", "code-change"]).assert().success();
+
+    let docs_path = ctx.temp_dir.path().join("test-repo-docs-change");
+    fs::write(docs_path.join("README.md"), "# Updated docs").unwrap();
+
+    let code_path = ctx.temp_dir.path().join("test-repo-code-change");
+    fs::write(code_path.join("main.rs"), "fn main() {}").unwrap();
+    std::process::Command::new("git")
+        .args(["add", "main.rs"])
+        .current_dir(&code_path)
+        .output()
+        .unwrap();
+
+    let output = ctx
+        .pigs(&["list", "--json", "--label", "docs-only"])
+        .assert()
+        .success();
+
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let worktrees = json["worktrees"].as_array().unwrap();
+    assert_eq!(worktrees.len(), 1);
+    assert_eq!(worktrees[0]["name"], "docs-change");
+    assert_eq!(worktrees[0]["labels"], json!(["docs-only"]));
+
+    ctx.pigs(&["list", "--label", "nonsense"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_remind_sets_and_clears_reminders() {
+    let ctx = TestContext::new("test-repo");
+
+    ctx.pigs(&["create", "feature"]).assert().success();
+    ctx.pigs(&["remind", "feature", "--in", "2d", "ping reviewer"])
+        .assert()
+        .success();
+
+    let output = ctx.pigs(&["list", "--json"]).assert().success();
+    let json: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    let reminder = &json["worktrees"][0]["reminder"];
+    assert_eq!(reminder["note"], "ping reviewer");
+    assert_eq!(reminder["overdue"], false);
+
+    ctx.pigs(&["remind", "feature", "--at", "2020-01-01"])
+        .assert()
+        .success();
+    let output = ctx.pigs(&["list"]).assert().success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(stdout.contains("overdue by"), "{stdout}");
+
+    ctx.pigs(&["remind", "feature", "--clear"])
+        .assert()
+        .success();
+    let state = ctx.read_state();
+    assert!(
+        state["worktrees"]["test-repo/feature"]
+            .get("reminder")
+            .is_none()
+    );
+
+    ctx.pigs(&["remind", "feature", "--in", "soon"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_completions_for_powershell_and_nushell() {
+    let ctx = TestContext::new("test-repo");
+
+    let output = ctx.pigs(&["completions", "nushell"]).assert().success();
+    let script = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(
+        script.contains("def \"nu-complete pigs worktrees\" []"),
+        "{script}"
+    );
+    let open = script
+        .split("export extern ")
+        .find(|block| block.starts_with("\"pigs open\""))
+        .unwrap();
+    assert!(
+        open.contains("name?: string@\"nu-complete pigs worktrees\""),
+        "{open}"
+    );
+    assert!(
+        open.contains("--agent(-a): string@\"nu-complete pigs agents\""),
+        "{open}"
+    );
+    assert!(
+        script.contains("identifier?: string@\"nu-complete pigs linear\""),
+        "{script}"
+    );
+    assert!(
+        script.contains("--task: string@\"nu-complete pigs tasks\""),
+        "{script}"
+    );
+    // Positional arguments that aren't worktrees keep plain completion
+    assert!(script.contains("    commits: string "), "{script}");
+
+    let output = ctx.pigs(&["completions", "powershell"]).assert().success();
+    let script = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(
+        script.contains("Register-ArgumentCompleter -Native -CommandName pigs"),
+        "{script}"
+    );
+    assert!(script.contains("PIGS_COMPLETE"), "{script}");
+}
+
+#[test]
+fn test_completions_call_back_into_pigs() {
+    let ctx = TestContext::new("test-repo");
+    ctx.pigs(&["create", "feature"]).assert().success();
+
+    let complete = |words: &[&str]| {
+        let mut args = vec!["--", "pigs"];
+        args.extend_from_slice(words);
+        let output = ctx
+            .pigs(&args)
+            .env("PIGS_COMPLETE", "fish")
+            .assert()
+            .success();
+        String::from_utf8_lossy(&output.get_output().stdout).to_string()
+    };
+
+    let subcommands = complete(&["wat"]);
+    assert!(subcommands.starts_with("watch\t"), "{subcommands}");
+    let worktrees = complete(&["open", ""]);
+    assert!(
+        worktrees.contains("feature\t[test-repo] no sessions"),
+        "{worktrees}"
+    );
+    assert!(worktrees.contains("--agent"), "{worktrees}");
+    let stack_on = complete(&["create", "--stack-on", "fea"]);
+    assert!(stack_on.starts_with("feature\t"), "{stack_on}");
+    let agents = complete(&["open", "--agent", ""]);
+    assert_eq!(agents.trim(), "test");
+    // Only worktree arguments get worktree names
+    let tags = complete(&["tag", "feature", ""]);
+    assert!(!tags.contains("feature"), "{tags}");
+
+    fs::create_dir_all(ctx.repo_dir.join(".pigs")).unwrap();
+    fs::write(
+        ctx.repo_dir.join(".pigs/settings.json"),
+        r#"{"tasks": {"test": "cargo test", "lint": "cargo clippy"}}"#,
+    )
+    .unwrap();
+    ctx.pigs(&["trust"]).assert().success();
+    let tasks = complete(&["artifacts", "feature", "--task", "l"]);
+    assert_eq!(tasks, "lint\tcargo clippy\n");
+}
+
+#[test]
+fn test_note_is_shown_in_list_and_completions() {
+    let ctx = TestContext::new("test-repo");
+
+    ctx.pigs(&["create", "feature"]).assert().success();
+    ctx.pigs(&["note", "feature", "why this exists\tand more"])
+        .assert()
+        .success();
+
+    let output = ctx.pigs(&["list", "--json"]).assert().success();
+    let json: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    assert_eq!(json["worktrees"][0]["note"], "why this exists\tand more");
+    let output = ctx.pigs(&["list"]).assert().success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(stdout.contains("Note: why this exists"), "{stdout}");
+
+    // The note is the last field, squeezed onto one line
+    let output = ctx
+        .pigs(&["complete-worktrees", "--format=detailed"])
+        .assert()
+        .success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    let fields: Vec<&str> = stdout.trim_end().split('\t').collect();
+    assert_eq!(fields[0], "feature");
+    assert_eq!(fields.last(), Some(&"why this exists and more"));
+
+    let output = ctx.pigs(&["api", "worktrees"]).assert().success();
+    let payload: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    assert_eq!(payload["worktrees"][0]["note"], "why this exists\tand more");
+
+    ctx.pigs(&["note", "feature"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("why this exists"));
+    ctx.pigs(&["note", "feature", "--clear"]).assert().success();
+    assert!(
+        ctx.read_state()["worktrees"]["test-repo/feature"]
+            .get("note")
+            .is_none()
+    );
+    ctx.pigs(&["note", "feature", "  "]).assert().failure();
+}
+
+#[test]
+fn test_worktree_names_shared_across_repositories_need_the_repo() {
+    let ctx = TestContext::new("test-repo");
+    ctx.pigs(&["create", "feature"]).assert().success();
+    let mut state = ctx.read_state();
+    let mut other = state["worktrees"]["test-repo/feature"].clone();
+    other["repo_name"] = json!("other-repo");
+    state["worktrees"]["other-repo/feature"] = other;
+    ctx.write_state(&state);
+
+    ctx.pigs(&["note", "feature", "ambiguous"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains(
+            "Worktree 'feature' exists in several repositories (other-repo/feature, test-repo/feature); use <repo>/<name>",
+        ));
+
+    ctx.pigs(&["note", "test-repo/feature", "picked"])
+        .assert()
+        .success();
+    let state = ctx.read_state();
+    assert_eq!(state["worktrees"]["test-repo/feature"]["note"], "picked");
+    assert!(state["worktrees"]["other-repo/feature"]["note"].is_null());
+}
+
+#[test]
+fn test_tags_filter_list_and_dashboard_api() {
+    let ctx = TestContext::new("test-repo");
+
+    ctx.pigs(&["create", "tagged"]).assert().success();
+    ctx.pigs(&["create", "plain"]).assert().success();
+    ctx.pigs(&["tag", "tagged", "+urgent", "bug", "+wip"])
+        .assert()
+        .success();
+    ctx.pigs(&["tag", "tagged", "-wip", "+bug"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("bug, urgent"));
+    assert_eq!(
+        ctx.read_state()["worktrees"]["test-repo/tagged"]["tags"],
+        json!(["bug", "urgent"])
+    );
+    ctx.pigs(&["tag", "tagged", "+two words"])
+        .assert()
+        .failure();
+    ctx.pigs(&["tag", "test-repo/plain", "+later"])
+        .assert()
+        .success();
+    ctx.pigs(&["tag", "test-repo/plain", "-later"])
+        .assert()
+        .success();
+
+    let output = ctx
+        .pigs(&["list", "--json", "--tag", "bug"])
+        .assert()
+        .success();
+    let json: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    let worktrees = json["worktrees"].as_array().unwrap();
+    assert_eq!(worktrees.len(), 1);
+    assert_eq!(worktrees[0]["name"], "tagged");
+    assert_eq!(worktrees[0]["tags"], json!(["bug", "urgent"]));
+
+    let output = ctx.pigs(&["list", "--tag", "none"]).assert().success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(stdout.contains("No active worktrees"), "{stdout}");
+
+    let output = ctx
+        .pigs(&["api", "worktrees?tag=urgent"])
+        .assert()
+        .success();
+    let payload: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    let names: Vec<&str> = payload["worktrees"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|worktree| worktree["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, vec!["tagged"]);
+    let output = ctx.pigs(&["api", "worktrees"]).assert().success();
+    let payload: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    assert_eq!(payload["worktrees"].as_array().unwrap().len(), 2);
+}
+
+#[test]
+fn test_focus_sets_the_default_worktree() {
+    let ctx = TestContext::new("test-repo");
+
+    ctx.pigs(&["create", "first"]).assert().success();
+    ctx.pigs(&["create", "second"]).assert().success();
+    ctx.pigs(&["focus", "second"]).assert().success();
+    assert_eq!(ctx.read_state()["focus"], "test-repo/second");
+
+    let output = ctx.pigs(&["dir"]).assert().success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(stdout.trim().ends_with("second"), "{stdout}");
+
+    ctx.pigs(&["focus", "--prompt"])
+        .assert()
+        .success()
+        .stdout("second\n");
+
+    ctx.pigs(&["rename", "second", "renamed"])
+        .assert()
+        .success();
+    assert_eq!(ctx.read_state()["focus"], "test-repo/renamed");
+
+    ctx.pigs(&["focus", "--clear"]).assert().success();
+    assert!(ctx.read_state().get("focus").is_none());
+    ctx.pigs(&["focus", "--prompt"])
+        .assert()
+        .success()
+        .stdout("");
+    ctx.pigs(&["focus", "missing"]).assert().failure();
+
+    ctx.pigs(&["focus", "test-repo/first"]).assert().success();
+    assert_eq!(ctx.read_state()["focus"], "test-repo/first");
+}
+
+#[test]
+fn test_artifacts_lists_runs_and_are_removed_with_the_worktree() {
+    let ctx = TestContext::new("test-repo");
+
+    ctx.pigs(&["create", "feature"]).assert().success();
+    let mut state = ctx.read_state();
+    state["worktrees"]["test-repo/feature"]["artifacts"] = json!([{
+        "id": "20240601T120000000Z-test",
+        "task": "test",
+        "finished_at": "2024-06-01T12:00:00Z",
+        "exit_code": 1,
+        "success": false,
+        "files": [
+            { "name": "output.log", "kind": "log", "size": 5 },
+            { "name": "reports/junit.xml", "kind": "junit", "size": 60 }
+        ]
+    }]);
+    ctx.write_state(&state);
+
+    let run_dir = ctx
+        .config_dir
+        .join("artifacts/test-repo/feature/20240601T120000000Z-test");
+    fs::create_dir_all(run_dir.join("reports")).unwrap();
+    fs::write(run_dir.join("output.log"), "boom\n").unwrap();
+    fs::write(
+        run_dir.join("reports/junit.xml"),
+        r#"<testsuites tests="3" failures="1"></testsuites>"#,
+    )
+    .unwrap();
+
+    let output = ctx
+        .pigs(&["artifacts", "feature", "--markdown"])
+        .assert()
+        .success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(stdout.contains("`test` ❌ failed"), "{stdout}");
+    assert!(
+        stdout.contains("junit `reports/junit.xml`: 3 tests, 1 failed"),
+        "{stdout}"
+    );
+
+    let output = ctx
+        .pigs(&["artifacts", "feature", "--json"])
+        .assert()
+        .success();
+    let json: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    assert_eq!(json["runs"][0]["task"], "test");
+    assert!(
+        json["runs"][0]["path"]
+            .as_str()
+            .unwrap()
+            .ends_with("20240601T120000000Z-test")
+    );
+    let output = ctx
+        .pigs(&["artifacts", "feature", "--json", "--task", "lint"])
+        .assert()
+        .success();
+    let json: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    assert_eq!(json["runs"], json!([]));
+
+    ctx.pigs(&["delete", "feature"]).assert().success();
+    assert!(!ctx.config_dir.join("artifacts/test-repo/feature").exists());
+}
+
+#[test]
+fn test_list_flags_conflicts_and_detached_head() {
+    let ctx = TestContext::new("test-repo");
+
+    ctx.pigs(&["create", "conflicted"]).assert().success();
+    ctx.pigs(&["create", "detached"]).assert().success();
+
+    let git_in = |path: &std::path::Path, args: &[&str]| {
+        std::process::Command::new("git")
+            .args(args)
+            .current_dir(path)
+            .output()
+            .unwrap()
+    };
+
+    ctx.git(&["checkout", "-b", "other"]);
+    fs::write(ctx.repo_dir.join("README.md"), "theirs\n").unwrap();
+    ctx.git(&["commit", "-am", "theirs"]);
+    ctx.git(&["checkout", "main"]);
+
+    let conflicted = ctx.temp_dir.path().join("test-repo-conflicted");
+    fs::write(conflicted.join("README.md"), "ours\n").unwrap();
+    git_in(&conflicted, &["commit", "-am", "ours"]);
+    git_in(&conflicted, &["merge", "other"]);
+
+    let detached = ctx.temp_dir.path().join("test-repo-detached");
+    git_in(&detached, &["checkout", "--detach"]);
+
+    let output = ctx.pigs(&["list", "--json"]).assert().success();
+    let json: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    let problems = |name: &str| -> Vec<String> {
+        json["worktrees"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|w| w["name"] == name)
+            .unwrap()["problems"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|p| p["kind"].as_str().unwrap().to_string())
+            .collect()
+    };
+    assert_eq!(problems("conflicted"), ["conflicts", "merge-in-progress"]);
+    assert_eq!(problems("detached"), ["detached-head"]);
+
+    let output = ctx.pigs(&["list"]).assert().success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(stdout.contains("MERGE IN PROGRESS"));
+    assert!(stdout.contains("git merge --continue (or --abort)"));
+    assert!(stdout.contains("git switch detached"));
+}
+
+#[test]
+fn test_stacked_worktrees_restack_with_sync() {
+    let ctx = TestContext::new("test-repo");
+
+    ctx.pigs(&["create", "parent"]).assert().success();
+    ctx.pigs(&["create", "child", "--stack-on", "parent"])
+        .assert()
+        .success();
+
+    let state = ctx.read_state();
+    assert_eq!(
+        state["worktrees"]["test-repo/child"]["stack"]["parent"],
+        "parent"
+    );
+
+    let commit = |path: &std::path::Path, file: &str| {
+        fs::write(path.join(file), file).unwrap();
+        for args in [vec!["add", file], vec!["commit", "-m", file]] {
+            std::process::Command::new("git")
+                .args(&args)
+                .current_dir(path)
+                .output()
+                .unwrap();
+        }
+    };
+    let parent_path = ctx.temp_dir.path().join("test-repo-parent");
+    let child_path = ctx.temp_dir.path().join("test-repo-child");
+    commit(&child_path, "child.txt");
+    commit(&parent_path, "parent.txt");
+    assert!(!child_path.join("parent.txt").exists());
+
+    let output = ctx.pigs(&["list"]).assert().success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    let parent_line = stdout.find("• parent").unwrap();
+    let child_line = stdout.find("└─ child").unwrap();
+    assert!(parent_line < child_line);
+    assert!(stdout.contains("Stacked on: parent"));
+
+    ctx.pigs(&["sync", "parent"]).assert().success();
+    assert!(child_path.join("parent.txt").exists());
+    assert!(child_path.join("child.txt").exists());
+
+    let output = ctx.pigs(&["sync"]).assert().success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(stdout.contains("'child' is up to date with 'parent'"));
+
+    ctx.pigs(&["create", "bad", "--stack-on", "missing"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_sync_updates_a_worktree_onto_its_base_branch() {
+    let ctx = TestContext::new("test-repo");
+    let origin = ctx.temp_dir.path().join("remote/test-repo.git");
+    let upstream = ctx.temp_dir.path().join("upstream");
+    let origin_str = origin.to_str().unwrap();
+    ctx.git(&["clone", "--bare", ".", origin_str]);
+    ctx.git(&["remote", "add", "origin", origin_str]);
+    ctx.git(&["fetch", "origin"]);
+    ctx.git(&["clone", origin_str, upstream.to_str().unwrap()]);
+
+    ctx.pigs(&["create", "feature"]).assert().success();
+    assert_eq!(
+        ctx.read_state()["worktrees"]["test-repo/feature"]["base_branch"],
+        "main"
+    );
+
+    let commit = |path: &std::path::Path, file: &str, content: &str| {
+        fs::write(path.join(file), content).unwrap();
+        for args in [
+            vec!["add", file],
+            vec![
+                "-c",
+                "user.name=Test User",
+                "-c",
+                "user.email=test@example.com",
+                "commit",
+                "--no-gpg-sign",
+                "-m",
+                file,
+            ],
+        ] {
+            let output = std::process::Command::new("git")
+                .args(&args)
+                .current_dir(path)
+                .output()
+                .unwrap();
+            assert!(output.status.success());
+        }
+    };
+    let feature_path = ctx.temp_dir.path().join("test-repo-feature");
+    commit(&feature_path, "feature.txt", "feature");
+
+    // Land a change upstream that the local main doesn't have yet
+    commit(&upstream, "upstream.txt", "upstream");
+    let output = std::process::Command::new("git")
+        .args(["push", "origin", "HEAD:main"])
+        .current_dir(&upstream)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let output = ctx.pigs(&["sync", "feature"]).assert().success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(stdout.contains("Rebased 'feature' onto 'origin/main'"));
+    assert!(feature_path.join("upstream.txt").exists());
+    assert!(feature_path.join("feature.txt").exists());
+
+    let output = ctx.pigs(&["sync", "test-repo/feature"]).assert().success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(stdout.contains("'feature' is up to date with 'origin/main'"));
+
+    // Conflicting changes stop the merge and name the files
+    commit(&feature_path, "README.md", "feature readme");
+    commit(&upstream, "README.md", "upstream readme");
+    let output = std::process::Command::new("git")
+        .args(["push", "origin", "HEAD:main"])
+        .current_dir(&upstream)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let output = ctx.pigs(&["sync", "feature", "--merge"]).assert().failure();
+    let stderr = String::from_utf8_lossy(&output.get_output().stderr);
+    assert!(stderr.contains("conflicts in README.md"));
+    assert!(stderr.contains("git merge --continue"));
+}
+
+#[test]
+fn test_global_hooks_get_worktree_context() {
+    let ctx = TestContext::new("test-repo");
+    let log = ctx.temp_dir.path().join("hooks.log");
+    let record = format!(
+        "echo \"global $PIGS_HOOK $PIGS_REPO $PIGS_WORKTREE_NAME $PIGS_BRANCH $(basename \"$PIGS_WORKTREE_PATH\")\" >> {}",
+        log.display()
+    );
+    let mut state = ctx.read_state();
+    for hook in ["post_create", "pre_sync", "post_sync"] {
+        state[hook] = json!([record]);
+    }
+    ctx.write_state(&state);
+    fs::create_dir_all(ctx.repo_dir.join(".pigs")).unwrap();
+    fs::write(
+        ctx.repo_dir.join(".pigs/settings.json"),
+        json!({ "post_create": [format!("echo repo >> {}", log.display())] }).to_string(),
+    )
+    .unwrap();
+    ctx.pigs(&["trust"]).assert().success();
+
+    // Global hooks run before the repository's
+    ctx.pigs(&["create", "feature"]).assert().success();
+    ctx.pigs(&["create", "skipped", "--no-hooks"])
+        .assert()
+        .success();
+
+    // Sync hooks run around an update that changes the worktree
+    fs::write(ctx.repo_dir.join("main.txt"), "main").unwrap();
+    ctx.git(&["add", "main.txt"]);
+    ctx.git(&["commit", "--no-gpg-sign", "-qm", "main"]);
+    ctx.pigs(&["sync", "feature"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Rebased 'feature' onto 'main'"));
+    ctx.pigs(&["sync", "feature"]).assert().success();
+
+    assert_eq!(
+        fs::read_to_string(&log).unwrap(),
+        "global post_create test-repo feature feature test-repo-feature\n\
+         repo\n\
+         global pre_sync test-repo feature feature test-repo-feature\n\
+         global post_sync test-repo feature feature test-repo-feature\n\
+         global pre_sync test-repo feature feature test-repo-feature\n"
+    );
+    // Saving state keeps the global hooks in place
+    assert_eq!(ctx.read_state()["post_create"], json!([record]));
+}
+
+#[test]
+fn test_tidy_squashes_commits_touching_the_same_files() {
+    let ctx = TestContext::new("test-repo");
+
+    ctx.pigs(&["create", "messy"]).assert().success();
+    let worktree_path = ctx.temp_dir.path().join("test-repo-messy");
+    let commit = |file: &str, content: &str, message: &str| {
+        fs::write(worktree_path.join(file), content).unwrap();
+        for args in [vec!["add", file], vec!["commit", "-m", message]] {
+            std::process::Command::new("git")
+                .args(&args)
+                .current_dir(&worktree_path)
+                .output()
+                .unwrap();
+        }
+    };
+    commit("api.txt", "v1", "Add api");
+    commit("docs.txt", "docs", "Write docs");
+    commit("api.txt", "v2", "Fix api typo");
+
+    let output = ctx.pigs(&["tidy", "messy", "--dry-run"]).assert().success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(stdout.contains("3 commits since main → 2"));
+
+    ctx.pigs(&["tidy", "messy", "-y"]).assert().success();
+
+    let log = std::process::Command::new("git")
+        .args(["log", "--format=%s", "main..HEAD"])
+        .current_dir(&worktree_path)
+        .output()
+        .unwrap();
+    let subjects = String::from_utf8_lossy(&log.stdout);
+    assert_eq!(
+        subjects.lines().collect::<Vec<_>>(),
+        ["Write docs", "Add api"]
+    );
+    assert_eq!(
+        fs::read_to_string(worktree_path.join("api.txt")).unwrap(),
+        "v2"
+    );
+
+    let output = ctx.pigs(&["tidy", "messy", "-y"]).assert().success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(stdout.contains("Nothing to tidy"));
+}
+
+#[test]
+fn test_webhooks_receive_worktree_events() {
+    use std::io::{BufRead, BufReader, Read, Write};
+
+    let ctx = TestContext::new("test-repo");
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    ctx.write_state(&json!({
+        "webhooks": [{
+            "url": format!("http://{}/hook", listener.local_addr().unwrap()),
+            "events": ["worktree.*"],
+            "headers": { "Authorization": "Bearer hook-token" }
+        }]
+    }));
+
+    // Accept two deliveries, recording the auth header and JSON body of each
+    let server = std::thread::spawn(move || {
+        (0..2)
+            .map(|_| {
+                let (stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream);
+                let mut length = 0;
+                let mut authorization = String::new();
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    let line = line.trim_end();
+                    if line.is_empty() {
+                        break;
+                    }
+                    let (name, value) = line.split_once(": ").unwrap_or((line, ""));
+                    match name.to_ascii_lowercase().as_str() {
+                        "content-length" => length = value.parse().unwrap(),
+                        "authorization" => authorization = value.to_string(),
+                        _ => {}
+                    }
+                }
+                let mut body = vec![0; length];
+                reader.read_exact(&mut body).unwrap();
+                reader
+                    .get_mut()
+                    .write_all(b"HTTP/1.1 204 No Content\r\nConnection: close\r\n\r\n")
+                    .unwrap();
+                let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+                (authorization, body)
+            })
+            .collect::<Vec<_>>()
+    });
+
+    ctx.pigs(&["create", "hooked"]).assert().success();
+    ctx.pigs(&["delete", "hooked"]).assert().success();
+
+    let deliveries = server.join().unwrap();
+    assert_eq!(deliveries[0].0, "Bearer hook-token");
+    assert_eq!(deliveries[0].1["event"], "worktree.created");
+    assert_eq!(deliveries[0].1["repo"], "test-repo");
+    assert_eq!(deliveries[0].1["worktree"], "hooked");
+    assert_eq!(deliveries[1].1["event"], "worktree.deleted");
+}
+
+#[test]
+fn test_diff_modes() {
+    let ctx = TestContext::new("test-repo");
+
+    ctx.pigs(&["create", "diffy"]).assert().success();
+    let worktree_path = ctx.temp_dir.path().join("test-repo-diffy");
+
+    fs::write(worktree_path.join("staged.txt"), "staged").unwrap();
+    std::process::Command::new("git")
+        .args(["add", "staged.txt"])
+        .current_dir(&worktree_path)
+        .output()
+        .unwrap();
+    fs::write(worktree_path.join("README.md"), "# Changed").unwrap();
+
+    let output = ctx
+        .pigs(&["diff", "diffy", "--stat", "--no-pager"])
+        .assert()
+        .success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(stdout.contains("README.md"));
+    assert!(stdout.contains("staged.txt"));
+
+    let output = ctx
+        .pigs(&["diff", "diffy", "--staged", "--stat", "--no-pager"])
+        .assert()
+        .success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(stdout.contains("staged.txt"));
+    assert!(!stdout.contains("README.md"));
+
+    ctx.pigs(&["diff", "diffy", "--since-session", "missing"])
+        .assert()
+        .failure();
+    ctx.pigs(&["diff", "diffy", "--base", "--staged"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_diff_snapshots_between_sessions() {
+    let ctx = TestContext::new("test-repo");
+    ctx.pigs(&["create", "stages"]).assert().success();
+    let worktree_path = ctx.temp_dir.path().join("test-repo-stages");
+    let git = |args: &[&str]| {
+        std::process::Command::new("git")
+            .args(args)
+            .current_dir(&worktree_path)
+            .output()
+            .unwrap()
+    };
+
+    git(&["update-ref", "refs/pigs/sessions/aaaa1111", "HEAD"]);
+    fs::write(worktree_path.join("first.txt"), "first").unwrap();
+    git(&["add", "first.txt"]);
+    git(&["commit", "-m", "First stage"]);
+    git(&["update-ref", "refs/pigs/sessions/bbbb2222", "HEAD"]);
+    fs::write(worktree_path.join("README.md"), "# Second stage").unwrap();
+
+    let output = ctx
+        .pigs(&[
+            "diff-snapshots",
+            "stages",
+            "aaaa",
+            "bbbb",
+            "--stat",
+            "--no-pager",
+        ])
+        .assert()
+        .success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(stdout.contains("first.txt"), "{stdout}");
+    assert!(!stdout.contains("README.md"), "{stdout}");
+
+    let output = ctx
+        .pigs(&[
+            "diff-snapshots",
+            "stages",
+            "bbbb",
+            "now",
+            "--stat",
+            "--no-pager",
+        ])
+        .assert()
+        .success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(stdout.contains("README.md"), "{stdout}");
+    assert!(!stdout.contains("first.txt"), "{stdout}");
+
+    let output = ctx
+        .pigs(&[
+            "api",
+            "/api/worktrees/test-repo/stages/snapshots/diff?from=aaaa&to=bbbb",
+        ])
+        .assert()
+        .success();
+    let body: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    assert!(body["diff"].as_str().unwrap().contains("+first"));
+
+    ctx.pigs(&["diff-snapshots", "stages", "missing", "now"])
+        .assert()
+        .failure();
+    ctx.pigs(&["diff-snapshots", "stages", "now", "now"])
+        .assert()
+        .failure();
+}
+
+// Delete command tests
+#[test]
 fn test_delete_clean_worktree() {
     let ctx = TestContext::new("test-repo");
 
-    // Create worktree
-    ctx.pigs(&["create", "to-delete"]).assert().success();
+    // Create worktree
+    ctx.pigs(&["create", "to-delete"]).assert().success();
+
+    // Delete worktree (in non-interactive mode, clean worktree will be deleted automatically)
+    let output = ctx.pigs(&["delete", "to-delete"]).assert().success();
+
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    let redacted = ctx.redact_paths(&stdout);
+    assert_snapshot!(redacted);
+
+    // Verify worktree was deleted
+    assert!(!ctx.worktree_exists("to-delete"));
+
+    // Verify state was updated
+    let state = ctx.read_state();
+    assert_eq!(state["worktrees"].as_object().unwrap().len(), 0);
+}
+
+#[test]
+fn test_delete_worktree_created_with_path() {
+    let ctx = TestContext::new("test-repo");
+
+    // Nowhere near the main checkout, so only the recorded root finds it
+    let scratch = ctx.temp_dir.path().join("scratch/wt/exact");
+    ctx.pigs(&["create", "pinned", "--path", scratch.to_str().unwrap()])
+        .assert()
+        .success();
+    assert_eq!(
+        ctx.read_state()["worktrees"]["test-repo/pinned"]["repo_root"],
+        ctx.repo_dir.to_str().unwrap()
+    );
+
+    ctx.pigs(&["delete", "pinned"]).assert().success();
+    assert!(!scratch.exists());
+    assert!(ctx.read_state()["worktrees"]["test-repo/pinned"].is_null());
+
+    // Entries saved before the root was recorded ask git for it
+    ctx.pigs(&["create", "legacy", "--path", scratch.to_str().unwrap()])
+        .assert()
+        .success();
+    let mut state = ctx.read_state();
+    state["worktrees"]["test-repo/legacy"]
+        .as_object_mut()
+        .unwrap()
+        .remove("repo_root");
+    ctx.write_state(&state);
+    ctx.pigs(&["delete", "legacy"]).assert().success();
+    assert!(!scratch.exists());
+}
+
+#[test]
+fn test_delete_with_changes() {
+    let ctx = TestContext::new("test-repo");
+
+    // Create worktree
+    ctx.pigs(&["create", "with-changes"]).assert().success();
+
+    // Create uncommitted changes in worktree
+    let worktree_path = ctx.temp_dir.path().join("test-repo-with-changes");
+    fs::write(worktree_path.join("new-file.txt"), "content").unwrap();
+
+    // Deleting refuses without --force, --stash, or --patch
+    let output = ctx.pigs(&["delete", "with-changes"]).assert().failure();
+
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    let stderr = String::from_utf8_lossy(&output.get_output().stderr);
+    assert!(stdout.contains("uncommitted changes"));
+    assert!(stderr.contains("--stash"), "{stderr}");
+
+    // Verify worktree was not deleted
+    assert!(worktree_path.exists());
+
+    ctx.pigs(&["delete", "with-changes", "--force"])
+        .assert()
+        .success();
+    assert!(!worktree_path.exists());
+}
+
+#[test]
+fn test_delete_remote_removes_merged_branch_from_origin() {
+    let ctx = TestContext::new("test-repo");
+    let origin = ctx.temp_dir.path().join("remote/test-repo.git");
+    ctx.git(&["init", "--bare", origin.to_str().unwrap()]);
+    ctx.git(&["remote", "add", "origin", origin.to_str().unwrap()]);
+
+    ctx.pigs(&["create", "shipped"]).assert().success();
+    ctx.pigs(&["create", "wip"]).assert().success();
+    let wip = ctx.temp_dir.path().join("test-repo-wip");
+    fs::write(wip.join("wip.txt"), "unfinished").unwrap();
+    for args in [&["add", "wip.txt"][..], &["commit", "-m", "wip"]] {
+        let status = std::process::Command::new("git")
+            .args(args)
+            .current_dir(&wip)
+            .status()
+            .unwrap();
+        assert!(status.success());
+    }
+    ctx.git(&["push", "origin", "shipped", "wip"]);
+    let remote_branches = || {
+        let output = ctx.git(&["ls-remote", "--heads", "origin"]);
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
+
+    // Merged: deleted locally and on origin
+    ctx.pigs(&["delete", "shipped", "--remote"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Remote branch deleted"));
+    assert!(!remote_branches().contains("refs/heads/shipped"));
+
+    // Unmerged: refused before anything is removed, even with --force, which
+    // only covers local changes
+    for args in [
+        &["delete", "wip", "--remote"][..],
+        &["delete", "wip", "--remote", "--force"],
+    ] {
+        ctx.pigs(args)
+            .assert()
+            .failure()
+            .stderr(predicates::str::contains("Use --remote-force"));
+        assert!(wip.exists());
+        assert!(remote_branches().contains("refs/heads/wip"));
+    }
+
+    ctx.pigs(&["delete", "wip", "--remote-force"])
+        .assert()
+        .success();
+    assert!(!wip.exists());
+    assert!(!remote_branches().contains("refs/heads/wip"));
+
+    // Merged locally, but someone pushed more to origin since: kept unless forced
+    ctx.pigs(&["create", "drifted"]).assert().success();
+    ctx.git(&["push", "origin", "drifted"]);
+    let output = ctx.git(&[
+        "commit-tree",
+        "drifted^{tree}",
+        "-p",
+        "drifted",
+        "-m",
+        "more",
+    ]);
+    let pushed = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    ctx.git(&["push", "origin", &format!("{pushed}:refs/heads/drifted")]);
+    ctx.pigs(&["delete", "drifted", "--remote"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("aren't in the local branch"));
+    assert!(remote_branches().contains(&pushed));
+    ctx.pigs(&["delete", "drifted", "--remote-force"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Remote branch deleted"));
+    assert!(!remote_branches().contains("refs/heads/drifted"));
+}
+
+#[test]
+fn test_delete_runs_pre_and_post_delete_hooks() {
+    let ctx = TestContext::new("test-repo");
+    let log = ctx.temp_dir.path().join("hooks.log");
+    let configure = |name: &str, pre_delete: &str, commit: bool| {
+        ctx.pigs(&["create", name]).assert().success();
+        let path = ctx.temp_dir.path().join(format!("test-repo-{name}"));
+        fs::create_dir_all(path.join(".pigs")).unwrap();
+        let settings = json!({
+            "pre_delete": [pre_delete],
+            "post_delete": [format!("echo post {name} >> {}", log.display())],
+        });
+        fs::write(path.join(".pigs/settings.json"), settings.to_string()).unwrap();
+        if commit {
+            for args in [&["add", "."][..], &["commit", "-qm", "settings"]] {
+                let status = std::process::Command::new("git")
+                    .args(args)
+                    .current_dir(&path)
+                    .status()
+                    .unwrap();
+                assert!(status.success());
+            }
+        }
+        ctx.pigs(&["trust"]).current_dir(&path).assert().success();
+        path
+    };
+
+    // A failing pre_delete hook keeps the worktree
+    let kept = configure("kept", "exit 2", false);
+    ctx.pigs(&["delete", "kept", "--force"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains(
+            "its pre_delete hooks didn't finish",
+        ));
+    assert!(kept.exists());
+
+    let pre = format!("echo pre $(basename \"$PWD\") >> {}", log.display());
+    configure("cli", &pre, false);
+    ctx.pigs(&["delete", "cli", "--force"]).assert().success();
+    assert!(!ctx.worktree_exists("cli"));
+
+    // The dashboard, `merge --delete`, and `clean` share the unattended path
+    configure("merged", &pre, true);
+    ctx.pigs(&["merge", "merged", "--delete"])
+        .assert()
+        .success();
+    assert!(!ctx.worktree_exists("merged"));
+
+    assert_eq!(
+        fs::read_to_string(&log).unwrap(),
+        "pre test-repo-cli\npost cli\npre test-repo-merged\npost merged\n"
+    );
+}
+
+#[test]
+fn test_delete_saves_changes_to_stash_or_patch() {
+    let ctx = TestContext::new("test-repo");
+
+    ctx.pigs(&["create", "stashed"]).assert().success();
+    ctx.pigs(&["create", "patched"]).assert().success();
+    for name in ["stashed", "patched"] {
+        let path = ctx.temp_dir.path().join(format!("test-repo-{name}"));
+        fs::write(path.join("notes.txt"), "keep me\n").unwrap();
+    }
+
+    ctx.pigs(&["delete", "stashed", "--stash"])
+        .assert()
+        .success();
+    assert!(!ctx.worktree_exists("stashed"));
+    let stashes = ctx.git(&["stash", "list"]);
+    let stashes = String::from_utf8_lossy(&stashes.stdout);
+    assert!(
+        stashes.contains("pigs delete test-repo/stashed"),
+        "{stashes}"
+    );
+
+    ctx.pigs(&["delete", "patched", "--patch"])
+        .assert()
+        .success();
+    assert!(!ctx.worktree_exists("patched"));
+    let patches: Vec<_> = fs::read_dir(ctx.config_dir.join("patches/test-repo"))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    assert_eq!(patches.len(), 1);
+    let patch = fs::read_to_string(&patches[0]).unwrap();
+    assert!(patch.contains("+keep me"), "{patch}");
+}
+
+#[test]
+fn test_archive_bundles_branch_and_changes() {
+    let ctx = TestContext::new("test-repo");
+
+    ctx.pigs(&["create", "experiment"]).assert().success();
+    let path = ctx.temp_dir.path().join("test-repo-experiment");
+    fs::write(path.join("idea.txt"), "committed\n").unwrap();
+    for args in [vec!["add", "."], vec!["commit", "-m", "Try an idea"]] {
+        std::process::Command::new("git")
+            .args(&args)
+            .current_dir(&path)
+            .output()
+            .unwrap();
+    }
+    fs::write(path.join("draft.txt"), "not committed\n").unwrap();
+
+    ctx.pigs(&["archive", "experiment"]).assert().success();
+    let archives_dir = ctx.config_dir.join("archives/test-repo");
+    let archives: Vec<_> = fs::read_dir(&archives_dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    assert_eq!(archives.len(), 1);
+    let archive = &archives[0];
+    let patch = fs::read_to_string(archive.join("changes.patch")).unwrap();
+    assert!(patch.contains("+not committed"), "{patch}");
+    let bundle = archive.join("branch.bundle");
+    ctx.git(&["bundle", "verify", bundle.to_str().unwrap()]);
+    let metadata: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(archive.join("archive.json")).unwrap()).unwrap();
+    assert_eq!(metadata["branch"], "experiment");
+    // Archiving leaves the worktree untouched
+    assert!(path.join("draft.txt").exists());
+
+    ctx.pigs(&["delete", "experiment", "--archive"])
+        .assert()
+        .success();
+    assert!(!path.exists());
+    assert_eq!(fs::read_dir(&archives_dir).unwrap().count(), 2);
+}
+
+#[test]
+fn test_delete_current_worktree() {
+    let ctx = TestContext::new("test-repo");
+
+    // Create worktree
+    ctx.pigs(&["create", "current"]).assert().success();
+
+    // Switch to the worktree directory
+    let worktree_path = ctx.temp_dir.path().join("test-repo-current");
+
+    // Delete from within the worktree (no name specified)
+    let output = ctx
+        .pigs_in_dir(&worktree_path, &["delete"])
+        .assert()
+        .success();
+
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    // Just check that the command succeeded and output something reasonable
+    assert!(stdout.contains("Checking worktree") || stdout.contains("deleted"));
+}
+
+// Add command tests
+#[test]
+fn test_add_existing_worktree() {
+    let ctx = TestContext::new("test-repo");
+
+    // Manually create worktree
+    std::process::Command::new("git")
+        .args([
+            "worktree",
+            "add",
+            "../test-repo-manual",
+            "-b",
+            "manual-branch",
+        ])
+        .current_dir(&ctx.repo_dir)
+        .output()
+        .unwrap();
+
+    // Switch to manually created worktree
+    let manual_worktree = ctx.temp_dir.path().join("test-repo-manual");
+
+    let output = ctx
+        .pigs_in_dir(&manual_worktree, &["add", "manual"])
+        .assert()
+        .success();
+
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    let redacted = ctx.redact_paths(&stdout);
+    assert_snapshot!(redacted);
+
+    // Verify state with manual redactions
+    let mut state = ctx.read_state();
+    if let Some(obj) = state.as_object_mut() {
+        obj.remove("agent");
+    }
+    if let Some(worktrees) = state["worktrees"].as_object_mut() {
+        for (_, worktree) in worktrees {
+            worktree["created_at"] = json!("[TIMESTAMP]");
+            if let Some(path) = worktree["path"].as_str() {
+                worktree["path"] = json!(ctx.redact_paths(path));
+            }
+            if let Some(root) = worktree["repo_root"].as_str() {
+                worktree["repo_root"] = json!(ctx.redact_paths(root));
+            }
+        }
+    }
+    assert_json_snapshot!(state);
+}
+
+#[test]
+fn test_add_duplicate_path_is_rejected() {
+    let ctx = TestContext::new("test-repo");
+
+    // Manually create worktree
+    std::process::Command::new("git")
+        .args(["worktree", "add", "../test-repo-dup", "-b", "dup-branch"])
+        .current_dir(&ctx.repo_dir)
+        .output()
+        .unwrap();
+
+    let manual_worktree = ctx.temp_dir.path().join("test-repo-dup");
+
+    ctx.pigs_in_dir(&manual_worktree, &["add", "primary"])
+        .assert()
+        .success();
+
+    let assert = ctx
+        .pigs_in_dir(&manual_worktree, &["add", "secondary"])
+        .assert()
+        .failure();
+    let stderr = String::from_utf8_lossy(&assert.get_output().stderr);
+    assert!(
+        stderr.contains("already managed"),
+        "expected duplicate path error, got: {}",
+        stderr
+    );
+}
+
+#[test]
+fn test_add_without_name() {
+    let ctx = TestContext::new("test-repo");
+
+    // Manually create worktree
+    std::process::Command::new("git")
+        .args(["worktree", "add", "../test-repo-auto", "-b", "auto-branch"])
+        .current_dir(&ctx.repo_dir)
+        .output()
+        .unwrap();
+
+    let auto_worktree = ctx.temp_dir.path().join("test-repo-auto");
+
+    // Add without specifying name (should use branch name)
+    let output = ctx.pigs_in_dir(&auto_worktree, &["add"]).assert().success();
+
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(stdout.contains("Adding worktree") || stdout.contains("added successfully"));
+
+    // Verify it was added with branch name
+    let state = ctx.read_state();
+    let worktrees = state["worktrees"].as_object().unwrap();
+    // Key format is now "repo_name/worktree_name"
+    // The repo name is correctly detected from the main repository
+    assert!(worktrees.contains_key("test-repo/auto-branch"));
+}
+
+// Clean command tests
+#[test]
+fn test_clean_invalid_worktrees() {
+    let ctx = TestContext::new("test-repo");
+
+    // Create a valid worktree
+    ctx.pigs(&["create", "valid"]).assert().success();
+
+    // Manually corrupt state file by adding invalid worktree
+    let state = ctx.read_state();
+    // Convert to proper structure for pigs state format
+    let worktrees_obj = state["worktrees"].as_object().cloned().unwrap_or_default();
+    let mut new_worktrees = serde_json::Map::new();
+
+    // Copy existing worktrees
+    for (k, v) in worktrees_obj {
+        new_worktrees.insert(k, v);
+    }
+
+    // Add invalid worktree with new key format
+    new_worktrees.insert(
+        "test-repo/invalid".to_string(),
+        json!({
+            "name": "invalid",
+            "branch": "invalid",
+            "repo_name": "test-repo",
+            "path": "/non/existent/path",
+            "repo_root": "/non/existent/test-repo",
+            "created_at": "2024-01-01T00:00:00Z"
+        }),
+    );
+
+    let mut new_state = serde_json::Map::new();
+    new_state.insert("worktrees".to_string(), json!(new_worktrees));
+    ctx.write_state(&json!(new_state));
 
-    // Delete worktree (in non-interactive mode, clean worktree will be deleted automatically)
-    let output = ctx.pigs(&["delete", "to-delete"]).assert().success();
+    // Run clean
+    let output = ctx.pigs(&["clean"]).assert().success();
 
     let stdout = String::from_utf8_lossy(&output.get_output().stdout);
     let redacted = ctx.redact_paths(&stdout);
     assert_snapshot!(redacted);
 
-    // Verify worktree was deleted
-    assert!(!ctx.worktree_exists("to-delete"));
+    // Verify state was cleaned
+    let cleaned_state = ctx.read_state();
+    let worktrees = cleaned_state["worktrees"].as_object().unwrap();
+    assert_eq!(worktrees.len(), 1);
+    // Key format is now "repo_name/worktree_name"
+    assert!(worktrees.contains_key("test-repo/valid"));
+}
+
+#[test]
+fn test_clean_dry_run_reports_and_prune_git_drops_stale_registrations() {
+    let ctx = TestContext::new("test-repo");
+
+    ctx.pigs(&["create", "valid"]).assert().success();
+    ctx.pigs(&["create", "gone"]).assert().success();
+    let gone_path = ctx.read_state()["worktrees"]["test-repo/gone"]["path"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    std::fs::remove_dir_all(&gone_path).unwrap();
+
+    let mut state = ctx.read_state();
+    state["worktrees"]["test-repo/invalid"] = json!({
+        "name": "invalid",
+        "branch": "invalid",
+        "repo_name": "test-repo",
+        "path": "/non/existent/path",
+        "repo_root": "/non/existent/test-repo",
+        "created_at": "2024-01-01T00:00:00Z"
+    });
+    ctx.write_state(&state);
+
+    // Git still has the deleted directory registered, so only the entry
+    // pointing nowhere is invalid until the registration is pruned
+    let output = ctx.pigs(&["clean", "--dry-run"]).assert().success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(
+        stdout.contains(
+            "Found invalid worktree: test-repo/invalid (/non/existent/path): repository /non/existent/test-repo is missing"
+        ),
+        "{stdout}"
+    );
+    assert!(!stdout.contains("test-repo/gone"), "{stdout}");
+    assert!(
+        stdout.contains("Stale git worktree registration"),
+        "{stdout}"
+    );
+    assert!(stdout.contains("Pass --prune-git"), "{stdout}");
+    assert!(
+        stdout.contains("Dry run: would remove 1 invalid worktree from state"),
+        "{stdout}"
+    );
+    assert_eq!(ctx.read_state(), state);
+
+    let output = ctx
+        .pigs(&["clean", "--dry-run", "--prune-git"])
+        .assert()
+        .success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(
+        stdout.contains("test-repo/gone") && stdout.contains("directory is missing"),
+        "{stdout}"
+    );
+    assert!(
+        stdout.contains("Would run 'git worktree prune'"),
+        "{stdout}"
+    );
+    assert!(
+        stdout.contains("would remove 2 invalid worktrees"),
+        "{stdout}"
+    );
+    let listed = ctx.git(&["worktree", "list", "--porcelain"]);
+    assert!(String::from_utf8_lossy(&listed.stdout).contains("prunable"));
+
+    ctx.pigs(&["clean", "--prune-git"]).assert().success();
+    let worktrees = ctx.read_state()["worktrees"].as_object().unwrap().clone();
+    assert_eq!(
+        worktrees.keys().collect::<Vec<_>>(),
+        vec!["test-repo/valid"]
+    );
+    let listed = ctx.git(&["worktree", "list", "--porcelain"]);
+    assert!(!String::from_utf8_lossy(&listed.stdout).contains(&gone_path));
+}
+
+#[test]
+fn test_delete_leaves_tombstone_until_clean_compacts_it() {
+    let ctx = TestContext::new("test-repo");
+
+    ctx.pigs(&["create", "gone"]).assert().success();
+    let head = ctx.git(&["rev-parse", "gone"]);
+    let head = String::from_utf8_lossy(&head.stdout).trim().to_string();
+    ctx.pigs(&["delete", "gone"]).assert().success();
 
-    // Verify state was updated
     let state = ctx.read_state();
-    assert_eq!(state["worktrees"].as_object().unwrap().len(), 0);
+    let tombstone = &state["tombstones"]["test-repo/gone"];
+    assert_eq!(tombstone["branch"], "gone");
+    assert_eq!(tombstone["head"], head.as_str());
+
+    // Recreating the worktree clears its tombstone
+    ctx.pigs(&["create", "gone"]).assert().success();
+    assert!(ctx.read_state().get("tombstones").is_none());
+    ctx.pigs(&["delete", "gone"]).assert().success();
+    let mut state = ctx.read_state();
+
+    // Fresh tombstones survive a clean; expired ones are compacted
+    ctx.pigs(&["clean"]).assert().success();
+    assert!(ctx.read_state()["tombstones"]["test-repo/gone"].is_object());
+
+    state["tombstones"]["test-repo/gone"]["deleted_at"] = json!("2024-01-01T00:00:00Z");
+    ctx.write_state(&state);
+    let output = ctx.pigs(&["clean"]).assert().success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(stdout.contains("Compacted 1 expired tombstone"), "{stdout}");
+    assert!(ctx.read_state().get("tombstones").is_none());
 }
 
 #[test]
-fn test_delete_with_changes() {
+fn test_api_calls_dashboard_endpoints_in_process() {
     let ctx = TestContext::new("test-repo");
+    let origin = ctx.temp_dir.path().join("remote/test-repo.git");
+    ctx.git(&["init", "--bare", origin.to_str().unwrap()]);
+    ctx.git(&["remote", "add", "origin", origin.to_str().unwrap()]);
+    ctx.git(&["push", "origin", "main"]);
 
-    // Create worktree
-    ctx.pigs(&["create", "with-changes"]).assert().success();
+    // Creation runs as a job; the command waits for it before exiting
+    let output = ctx
+        .pigs(&["api", "worktrees", "--json", r#"{"name": "from-api"}"#])
+        .assert()
+        .success();
+    let created: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    let job_id = created["jobId"].as_str().unwrap();
+    assert!(ctx.worktree_exists("from-api"));
 
-    // Create uncommitted changes in worktree
-    let worktree_path = ctx.temp_dir.path().join("test-repo-with-changes");
-    fs::write(worktree_path.join("new-file.txt"), "content").unwrap();
+    let output = ctx
+        .pigs(&["api", &format!("/api/jobs/{job_id}")])
+        .assert()
+        .success();
+    let job: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    assert_eq!(job["status"], "succeeded");
+
+    let output = ctx.pigs(&["api", "worktrees"]).assert().success();
+    let payload: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    let names: Vec<&str> = payload["worktrees"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|worktree| worktree["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, vec!["from-api"]);
+
+    ctx.pigs(&["api", "jobs/missing"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("404"));
+    ctx.pigs(&["api", "events"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("pigs dashboard"));
+}
 
-    // Try to delete, in non-interactive mode it will be cancelled automatically
-    let output = ctx.pigs(&["delete", "with-changes"]).assert().success();
+#[test]
+fn test_clean_relinks_moved_repository() {
+    let ctx = TestContext::new("test-repo");
+    let origin = ctx.temp_dir.path().join("remote/test-repo.git");
+    ctx.git(&["init", "--bare", origin.to_str().unwrap()]);
+    ctx.git(&["remote", "add", "origin", origin.to_str().unwrap()]);
+    ctx.pigs(&["create", "moved"]).assert().success();
+
+    // Reorganize: move the checkout and its worktree into a workspace folder
+    let workspace = ctx.temp_dir.path().join("workspace");
+    fs::create_dir_all(&workspace).unwrap();
+    for dir in ["test-repo", "test-repo-moved"] {
+        fs::rename(ctx.temp_dir.path().join(dir), workspace.join(dir)).unwrap();
+    }
+    let mut state = ctx.read_state();
+    state["workspace_roots"] = json!([workspace]);
+    ctx.write_state(&state);
 
-    // Check that output mentions uncommitted changes and cancellation
+    let new_root = workspace.join("test-repo");
+    let output = ctx.pigs_in_dir(&new_root, &["clean"]).assert().success();
     let stdout = String::from_utf8_lossy(&output.get_output().stdout);
-    assert!(stdout.contains("uncommitted changes"));
-    assert!(stdout.contains("Cancelled"));
+    assert!(stdout.contains("Re-linked 1/1 worktrees"), "{stdout}");
 
-    // Verify worktree was not deleted
-    assert!(worktree_path.exists());
+    let state = ctx.read_state();
+    let info = &state["worktrees"]["test-repo/moved"];
+    let new_path = workspace.join("test-repo-moved");
+    assert_eq!(info["path"], new_path.to_str().unwrap());
+    assert_eq!(info["repo_root"], new_root.to_str().unwrap());
+
+    // git agrees on both ends of the link
+    let list = std::process::Command::new("git")
+        .args(["-C", new_root.to_str().unwrap(), "worktree", "list"])
+        .output()
+        .unwrap();
+    assert!(String::from_utf8_lossy(&list.stdout).contains(new_path.to_str().unwrap()));
+    let status = std::process::Command::new("git")
+        .args(["-C", new_path.to_str().unwrap(), "status"])
+        .output()
+        .unwrap();
+    assert!(status.status.success());
 }
 
 #[test]
-fn test_delete_current_worktree() {
+fn test_config_get_set_unset_list() {
     let ctx = TestContext::new("test-repo");
 
-    // Create worktree
-    ctx.pigs(&["create", "current"]).assert().success();
+    ctx.pigs(&["config", "set", "editor", "code --wait"])
+        .assert()
+        .success();
+    ctx.pigs(&["config", "set", "tombstone_days", "7"])
+        .assert()
+        .success();
+    let state = ctx.read_state();
+    assert_eq!(state["editor"], "code --wait");
+    assert_eq!(state["tombstone_days"], 7);
+    ctx.pigs(&["config", "get", "editor"])
+        .assert()
+        .success()
+        .stdout("code --wait\n");
 
-    // Switch to the worktree directory
-    let worktree_path = ctx.temp_dir.path().join("test-repo-current");
+    // A command line adds an agent and makes it the default; a name reorders
+    ctx.pigs(&["config", "set", "agent", "codex --profile fast"])
+        .assert()
+        .success();
+    ctx.pigs(&["config", "set", "agent", "test"])
+        .assert()
+        .success();
+    assert_eq!(
+        ctx.read_state()["agent"],
+        json!([
+            { "name": "test", "command": "true" },
+            { "name": "codex", "command": "codex --profile fast" }
+        ])
+    );
 
-    // Delete from within the worktree (no name specified)
-    let output = ctx
-        .pigs_in_dir(&worktree_path, &["delete"])
+    ctx.pigs(&["config", "set", "tombstone_days", "soon"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("Invalid value"));
+    ctx.pigs(&["config", "set", "worktrees", "{}"])
+        .assert()
+        .failure();
+
+    ctx.pigs(&["config", "unset", "editor"]).assert().success();
+    let output = ctx.pigs(&["config", "list"]).assert().success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(stdout.contains("tombstone_days = 7"), "{stdout}");
+    assert!(!stdout.contains("editor"), "{stdout}");
+    assert!(!stdout.contains("worktrees"), "{stdout}");
+
+    // --local writes the repository's settings and trusts them
+    ctx.pigs(&["config", "set", "--local", "shell", "zsh"])
         .assert()
         .success();
+    let local: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(ctx.repo_dir.join(".pigs/settings.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(local, json!({ "shell": "zsh" }));
+    assert_eq!(
+        ctx.read_state()["trusted_repos"].as_object().unwrap().len(),
+        1
+    );
+    ctx.pigs(&["config", "get", "--local", "shell"])
+        .assert()
+        .success()
+        .stdout("zsh\n");
+}
+
+#[test]
+fn test_clean_with_no_invalid() {
+    let ctx = TestContext::new("test-repo");
+
+    // Create valid worktrees
+    ctx.pigs(&["create", "valid1"]).assert().success();
+    ctx.pigs(&["create", "valid2"]).assert().success();
+
+    // Run clean (should find no invalid worktrees)
+    let output = ctx.pigs(&["clean"]).assert().success();
 
     let stdout = String::from_utf8_lossy(&output.get_output().stdout);
-    // Just check that the command succeeded and output something reasonable
-    assert!(stdout.contains("Checking worktree") || stdout.contains("deleted"));
+    assert!(
+        stdout.contains("No invalid worktrees found") || stdout.contains("All worktrees are valid")
+    );
 }
 
-// Add command tests
+// Open command tests (basic, since we can't actually launch Claude)
 #[test]
-fn test_add_existing_worktree() {
+fn test_open_specific_worktree() {
     let ctx = TestContext::new("test-repo");
 
-    // Manually create worktree
-    std::process::Command::new("git")
-        .args([
-            "worktree",
-            "add",
-            "../test-repo-manual",
-            "-b",
-            "manual-branch",
-        ])
-        .current_dir(&ctx.repo_dir)
-        .output()
+    // Create worktree
+    ctx.pigs(&["create", "to-open"]).assert().success();
+
+    // Mock claude command to verify it would be called
+    let output = ctx.pigs(&["open", "to-open"]).assert().success();
+
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(stdout.contains("Opening worktree"));
+}
+
+#[test]
+fn test_open_records_agent_history() {
+    let ctx = TestContext::new("test-repo");
+    ctx.pigs(&["create", "feature"]).assert().success();
+
+    ctx.pigs(&["open", "feature", "--", "--model", "opus"])
+        .assert()
+        .success();
+    ctx.pigs(&["open", "feature"]).assert().success();
+
+    let state = ctx.read_state();
+    let history = state["worktrees"]["test-repo/feature"]["agent_history"]
+        .as_array()
+        .unwrap();
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[0]["agent"], "true");
+    assert_eq!(history[0]["args"], json!(["--model", "opus"]));
+
+    let output = ctx.pigs(&["list", "--json"]).assert().success();
+    let json: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    assert_eq!(json["worktrees"][0]["last_agent"]["agent"], "true");
+    assert!(json["worktrees"][0]["last_agent"].get("args").is_none());
+    ctx.pigs(&["list"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Last agent: true (0m ago)"));
+}
+
+#[test]
+fn test_open_runs_pre_open_hooks_before_the_agent() {
+    let ctx = TestContext::new("test-repo");
+    ctx.pigs(&["create", "feature"]).assert().success();
+    let path = ctx.temp_dir.path().join("test-repo-feature");
+    fs::create_dir_all(path.join(".pigs")).unwrap();
+    let configure = |pre_open: serde_json::Value| {
+        fs::write(
+            path.join(".pigs/settings.json"),
+            json!({ "pre_open": pre_open }).to_string(),
+        )
         .unwrap();
+        ctx.pigs(&["trust"]).current_dir(&path).assert().success();
+    };
 
-    // Switch to manually created worktree
-    let manual_worktree = ctx.temp_dir.path().join("test-repo-manual");
+    configure(json!(["echo up >> services.log"]));
+    ctx.pigs(&["open", "feature"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Running pre_open hook 1/1"));
+    assert_eq!(
+        fs::read_to_string(path.join("services.log")).unwrap(),
+        "up\n"
+    );
 
-    let output = ctx
-        .pigs_in_dir(&manual_worktree, &["add", "manual"])
+    // A failing hook stops the launch
+    configure(json!(["exit 1"]));
+    ctx.pigs(&["open", "feature"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("Not launching the agent"));
+    let history = &ctx.read_state()["worktrees"]["test-repo/feature"]["agent_history"];
+    assert_eq!(history.as_array().unwrap().len(), 1);
+}
+
+#[test]
+fn test_open_loads_direnv_environment() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let ctx = TestContext::new("test-repo");
+    fs::write(ctx.repo_dir.join(".envrc"), "export NODE_VERSION=22\n").unwrap();
+    ctx.git(&["add", ".envrc"]);
+    ctx.git(&["commit", "-m", "Add envrc"]);
+    ctx.pigs(&["create", "envy"]).assert().success();
+
+    // Fake `direnv export json` and an agent that records what it was given
+    let bin_dir = ctx.temp_dir.path().join("bin");
+    fs::create_dir_all(&bin_dir).unwrap();
+    let direnv = bin_dir.join("direnv");
+    fs::write(
+        &direnv,
+        "#!/bin/sh\necho '{\"NODE_VERSION\": \"22\", \"STALE_VAR\": null}'\n",
+    )
+    .unwrap();
+    fs::set_permissions(&direnv, fs::Permissions::from_mode(0o755)).unwrap();
+    let log = ctx.temp_dir.path().join("agent-env.log");
+    let mut state = ctx.read_state();
+    state["agent"] = json!([{
+        "name": "env",
+        "command": format!("sh -c 'echo \"$NODE_VERSION:${{STALE_VAR:-unset}}\" > {}'", log.display())
+    }]);
+    ctx.write_state(&state);
+    let path = format!(
+        "{}:{}",
+        bin_dir.display(),
+        std::env::var("PATH").unwrap_or_default()
+    );
+
+    ctx.pigs(&["open", "envy"])
+        .env("PATH", &path)
+        .env("STALE_VAR", "from-parent")
         .assert()
         .success();
+    assert_eq!(fs::read_to_string(&log).unwrap().trim(), "22:unset");
+}
 
-    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
-    let redacted = ctx.redact_paths(&stdout);
-    assert_snapshot!(redacted);
+#[test]
+fn test_open_shell_and_editor_use_configured_commands() {
+    let ctx = TestContext::new("test-repo");
+    ctx.pigs(&["create", "hands-on"]).assert().success();
+    let log = ctx.temp_dir.path().join("launch.log");
+    let mut state = ctx.read_state();
+    state["shell"] = json!(format!("sh -c 'pwd > {}'", log.display()));
+    state["editor"] = json!(format!("sh -c 'echo \"$0\" >> {}'", log.display()));
+    ctx.write_state(&state);
+    let worktree = ctx.temp_dir.path().join("test-repo-hands-on");
 
-    // Verify state with manual redactions
+    ctx.pigs(&["open", "hands-on", "--shell"])
+        .assert()
+        .success();
+    assert_eq!(
+        fs::canonicalize(fs::read_to_string(&log).unwrap().trim()).unwrap(),
+        fs::canonicalize(&worktree).unwrap()
+    );
+
+    fs::remove_file(&log).unwrap();
+    ctx.pigs(&["open", "hands-on", "--editor"])
+        .assert()
+        .success();
+    assert_eq!(
+        fs::read_to_string(&log).unwrap().trim(),
+        worktree.to_str().unwrap()
+    );
+
+    ctx.pigs(&["open", "hands-on", "--shell", "--editor"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_open_record_saves_a_transcript() {
+    let ctx = TestContext::new("test-repo");
+    ctx.pigs(&["create", "taped"]).assert().success();
     let mut state = ctx.read_state();
-    if let Some(obj) = state.as_object_mut() {
-        obj.remove("agent");
-    }
-    if let Some(worktrees) = state["worktrees"].as_object_mut() {
-        for (_, worktree) in worktrees {
-            worktree["created_at"] = json!("[TIMESTAMP]");
-            if let Some(path) = worktree["path"].as_str() {
-                worktree["path"] = json!(ctx.redact_paths(path));
-            }
-        }
-    }
-    assert_json_snapshot!(state);
+    state["agent"] = json!([{ "name": "echo", "command": "sh -c 'echo hello from the agent'" }]);
+    ctx.write_state(&state);
+
+    ctx.pigs(&["open", "taped", "--record"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("hello from the agent"));
+
+    let transcripts: Vec<_> = fs::read_dir(ctx.config_dir.join("transcripts"))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    assert_eq!(transcripts.len(), 1);
+    let session_id = transcripts[0].file_stem().unwrap().to_str().unwrap();
+
+    let output = ctx
+        .pigs(&["api", &format!("sessions/{session_id}/logs")])
+        .assert()
+        .success();
+    let logs: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    let events = logs["events"].as_array().unwrap();
+    assert_eq!(events.first().unwrap()["status"], "running");
+    assert_eq!(events.last().unwrap()["detail"], "exit code 0");
+    assert!(
+        events
+            .iter()
+            .filter_map(|event| event["text"].as_str())
+            .any(|text| text.contains("hello from the agent")),
+        "{logs}"
+    );
+    assert_eq!(logs["command"]["program"], "sh");
+
+    let output = ctx
+        .pigs(&["api", &format!("sessions/{session_id}/export")])
+        .assert()
+        .success();
+    let export = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(export.contains("test-repo/taped"), "{export}");
 }
 
 #[test]
-fn test_add_duplicate_path_is_rejected() {
+fn test_switch_changes_directory_through_shell_init() {
     let ctx = TestContext::new("test-repo");
+    ctx.pigs(&["create", "hop"]).assert().success();
+    let worktree = ctx.temp_dir.path().join("test-repo-hop");
 
-    // Manually create worktree
-    std::process::Command::new("git")
-        .args(["worktree", "add", "../test-repo-dup", "-b", "dup-branch"])
+    // Without the wrapper, the path is printed along with how to set it up
+    ctx.pigs(&["switch", "hop"])
+        .assert()
+        .success()
+        .stdout(format!("{}\n", worktree.display()))
+        .stderr(predicates::str::contains("pigs shell-init"));
+
+    let output = ctx.pigs(&["shell-init", "bash"]).assert().success();
+    let init = String::from_utf8_lossy(&output.get_output().stdout).to_string();
+    let bin_dir = Path::new(env!("CARGO_BIN_EXE_pigs")).parent().unwrap();
+    let output = std::process::Command::new("bash")
+        .arg("-c")
+        .arg(format!("{init}\npigs switch hop && pwd"))
         .current_dir(&ctx.repo_dir)
+        .env(
+            "PATH",
+            format!("{}:{}", bin_dir.display(), std::env::var("PATH").unwrap()),
+        )
+        .env("HOME", ctx.temp_dir.path())
+        .env("PIGS_CONFIG_DIR", &ctx.config_dir)
+        .env("PIGS_NON_INTERACTIVE", "1")
         .output()
         .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    assert!(output.stderr.is_empty(), "{output:?}");
+    assert_eq!(
+        fs::canonicalize(String::from_utf8_lossy(&output.stdout).trim()).unwrap(),
+        fs::canonicalize(&worktree).unwrap()
+    );
 
-    let manual_worktree = ctx.temp_dir.path().join("test-repo-dup");
+    ctx.pigs(&["shell-init", "elvish"]).assert().failure();
+}
 
-    ctx.pigs_in_dir(&manual_worktree, &["add", "primary"])
+#[test]
+fn test_dashboard_tokens_can_be_created_listed_and_revoked() {
+    let ctx = TestContext::new("test-repo");
+
+    let output = ctx
+        .pigs(&[
+            "dashboard",
+            "token",
+            "create",
+            "--scope",
+            "read",
+            "--user",
+            "wallboard",
+        ])
         .assert()
         .success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout).to_string();
+    let token = stdout.lines().nth(1).unwrap().trim().to_string();
+    let state = ctx.read_state();
+    assert_eq!(state["dashboard_tokens"][&token]["scope"], "read");
+    assert_eq!(state["dashboard_tokens"][&token]["user"], "wallboard");
 
-    let assert = ctx
-        .pigs_in_dir(&manual_worktree, &["add", "secondary"])
+    ctx.pigs(&["dashboard", "token", "create", "--scope", "root"])
         .assert()
         .failure();
-    let stderr = String::from_utf8_lossy(&assert.get_output().stderr);
+
+    let output = ctx.pigs(&["dashboard", "token", "list"]).assert().success();
+    let listed = String::from_utf8_lossy(&output.get_output().stdout);
     assert!(
-        stderr.contains("already managed"),
-        "expected duplicate path error, got: {}",
-        stderr
+        listed.contains(&token[..8]) && listed.contains("as wallboard"),
+        "{listed}"
     );
+    // The full token is only shown when it is created
+    assert!(!listed.contains(&token), "{listed}");
+
+    ctx.pigs(&["dashboard", "token", "revoke", "nope"])
+        .assert()
+        .failure();
+    ctx.pigs(&["dashboard", "token", "revoke", &token[..6]])
+        .assert()
+        .success();
+    assert!(ctx.read_state().get("dashboard_tokens").is_none());
 }
 
 #[test]
-fn test_add_without_name() {
+fn test_create_with_container_pins_the_prebuild_image() {
+    use std::os::unix::fs::PermissionsExt;
+
     let ctx = TestContext::new("test-repo");
+    fs::create_dir_all(ctx.repo_dir.join(".devcontainer")).unwrap();
+    fs::write(
+        ctx.repo_dir.join(".devcontainer/devcontainer.json"),
+        "{\n  // Codespaces prebuild\n  \"image\": \"ghcr.io/acme/app:latest\"\n}\n",
+    )
+    .unwrap();
+
+    // Fake container CLI: log every call and report a fixed digest
+    let log = ctx.temp_dir.path().join("runtime.log");
+    let runtime = ctx.temp_dir.path().join("fake-docker");
+    fs::write(
+        &runtime,
+        format!(
+            "#!/bin/sh\necho \"$@\" >> {}\nif [ \"$1\" = image ]; then echo ghcr.io/acme/app@sha256:abc; fi\n",
+            log.display()
+        ),
+    )
+    .unwrap();
+    fs::set_permissions(&runtime, fs::Permissions::from_mode(0o755)).unwrap();
+
+    ctx.pigs(&["create", "boxed", "--container"])
+        .env("PIGS_CONTAINER_RUNTIME", &runtime)
+        .assert()
+        .success();
+    let container = &ctx.read_state()["worktrees"]["test-repo/boxed"]["container"];
+    assert_eq!(container["image"], "ghcr.io/acme/app:latest");
+    assert_eq!(container["digest"], "ghcr.io/acme/app@sha256:abc");
 
-    // Manually create worktree
-    std::process::Command::new("git")
-        .args(["worktree", "add", "../test-repo-auto", "-b", "auto-branch"])
-        .current_dir(&ctx.repo_dir)
-        .output()
-        .unwrap();
+    ctx.pigs(&["open", "boxed"])
+        .env("PIGS_CONTAINER_RUNTIME", &runtime)
+        .assert()
+        .success();
+    let calls = fs::read_to_string(&log).unwrap();
+    assert!(
+        calls.starts_with("pull --quiet ghcr.io/acme/app:latest\n"),
+        "{calls}"
+    );
+    let run = calls.lines().last().unwrap();
+    let worktree = ctx.temp_dir.path().join("test-repo-boxed");
+    assert!(run.starts_with("run --rm -i -v "), "{run}");
+    assert!(run.contains(&format!("-w {}", worktree.display())), "{run}");
+    assert!(run.ends_with("ghcr.io/acme/app@sha256:abc true"), "{run}");
+
+    // Without a prebuild image, nothing is created
+    fs::remove_dir_all(ctx.repo_dir.join(".devcontainer")).unwrap();
+    ctx.pigs(&["create", "unboxed", "--container"])
+        .env("PIGS_CONTAINER_RUNTIME", &runtime)
+        .assert()
+        .failure();
+    assert!(!ctx.worktree_exists("unboxed"));
+}
 
-    let auto_worktree = ctx.temp_dir.path().join("test-repo-auto");
+#[test]
+fn test_pr_pushes_the_branch_and_opens_a_pull_request() {
+    use std::os::unix::fs::PermissionsExt;
 
-    // Add without specifying name (should use branch name)
-    let output = ctx.pigs_in_dir(&auto_worktree, &["add"]).assert().success();
+    let ctx = TestContext::new("test-repo");
+    let origin = ctx.temp_dir.path().join("remote/test-repo.git");
+    ctx.git(&["init", "--bare", origin.to_str().unwrap()]);
+    ctx.git(&["remote", "add", "origin", origin.to_str().unwrap()]);
+    ctx.pigs(&["create", "eng-42-fix-login"]).assert().success();
+
+    // Fake gh: log its arguments and print a pull request URL
+    let bin_dir = ctx.temp_dir.path().join("bin");
+    fs::create_dir_all(&bin_dir).unwrap();
+    let log = ctx.temp_dir.path().join("gh.log");
+    let gh = bin_dir.join("gh");
+    fs::write(
+        &gh,
+        format!(
+            "#!/bin/sh\nfor arg in \"$@\"; do echo \"$arg\" >> {}; done\necho https://github.com/acme/test-repo/pull/7\n",
+            log.display()
+        ),
+    )
+    .unwrap();
+    fs::set_permissions(&gh, fs::Permissions::from_mode(0o755)).unwrap();
+    let path = format!(
+        "{}:{}",
+        bin_dir.display(),
+        std::env::var("PATH").unwrap_or_default()
+    );
+
+    // A Claude session whose last reply summarizes the work
+    let worktree = ctx
+        .temp_dir
+        .path()
+        .join("test-repo-eng-42-fix-login")
+        .canonicalize()
+        .unwrap();
+    let project = ctx
+        .temp_dir
+        .path()
+        .join(".claude/projects")
+        .join(worktree.to_string_lossy().replace('/', "-"));
+    fs::create_dir_all(&project).unwrap();
+    fs::write(
+        project.join("session.jsonl"),
+        [
+            json!({ "type": "user", "timestamp": "2024-01-01T00:00:00Z", "message": { "content": "fix the login bug" } }),
+            json!({ "type": "assistant", "timestamp": "2024-01-01T00:01:00Z", "message": { "content": [{ "type": "text", "text": "Fixed the login redirect." }] } }),
+        ]
+        .iter()
+        .map(|line| line.to_string())
+        .collect::<Vec<_>>()
+        .join("\n"),
+    )
+    .unwrap();
 
+    let output = ctx
+        .pigs(&["pr", "eng-42-fix-login", "--session-summary", "--draft"])
+        .env("PATH", &path)
+        .env_remove("LINEAR_API_KEY")
+        .assert()
+        .success();
     let stdout = String::from_utf8_lossy(&output.get_output().stdout);
-    assert!(stdout.contains("Adding worktree") || stdout.contains("added successfully"));
+    assert!(
+        stdout.contains("https://github.com/acme/test-repo/pull/7"),
+        "{stdout}"
+    );
 
-    // Verify it was added with branch name
-    let state = ctx.read_state();
-    let worktrees = state["worktrees"].as_object().unwrap();
-    // Key format is now "repo_name/worktree_name"
-    // The repo name is correctly detected from the main repository
-    assert!(worktrees.contains_key("test-repo/auto-branch"));
+    let args: Vec<String> = fs::read_to_string(&log)
+        .unwrap()
+        .lines()
+        .map(String::from)
+        .collect();
+    let value_of = |flag: &str| {
+        let index = args.iter().position(|arg| arg == flag).unwrap();
+        args[index + 1].clone()
+    };
+    assert_eq!(value_of("--title"), "[ENG-42] Fix login");
+    assert_eq!(value_of("--body"), "Fixed the login redirect.");
+    assert_eq!(value_of("--base"), "main");
+    assert!(args.contains(&"--draft".to_string()));
+
+    let pushed = ctx.git(&[
+        "--git-dir",
+        origin.to_str().unwrap(),
+        "branch",
+        "--list",
+        "eng-42-fix-login",
+    ]);
+    assert!(!String::from_utf8_lossy(&pushed.stdout).trim().is_empty());
 }
 
-// Clean command tests
 #[test]
-fn test_clean_invalid_worktrees() {
+fn test_log_shows_the_command_each_session_ran() {
     let ctx = TestContext::new("test-repo");
+    ctx.pigs(&["create", "feature"]).assert().success();
+    let mut state = ctx.read_state();
+    state["agent"] = json!([{ "name": "test", "command": "true --model opus 'fix it'" }]);
+    ctx.write_state(&state);
 
-    // Create a valid worktree
-    ctx.pigs(&["create", "valid"]).assert().success();
-
-    // Manually corrupt state file by adding invalid worktree
-    let state = ctx.read_state();
-    // Convert to proper structure for pigs state format
-    let worktrees_obj = state["worktrees"].as_object().cloned().unwrap_or_default();
-    let mut new_worktrees = serde_json::Map::new();
-
-    // Copy existing worktrees
-    for (k, v) in worktrees_obj {
-        new_worktrees.insert(k, v);
-    }
+    ctx.pigs(&["open", "feature"])
+        .env("AGENT_MODEL", "fast")
+        .assert()
+        .success();
 
-    // Add invalid worktree with new key format
-    new_worktrees.insert(
-        "test-repo/invalid".to_string(),
-        json!({
-            "name": "invalid",
-            "branch": "invalid",
-            "repo_name": "test-repo",
-            "path": "/non/existent/path",
-            "created_at": "2024-01-01T00:00:00Z"
-        }),
+    let output = ctx.pigs(&["log", "feature", "--json"]).assert().success();
+    let records: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    let launch = records.as_array().unwrap().last().unwrap();
+    assert_eq!(launch["action"], "open");
+    assert_eq!(launch["command"]["program"], "true");
+    assert_eq!(
+        launch["command"]["args"],
+        json!(["--model", "opus", "fix it"])
+    );
+    assert!(
+        launch["command"]["env"]
+            .as_array()
+            .unwrap()
+            .contains(&json!("AGENT_MODEL=fast"))
     );
 
-    let mut new_state = serde_json::Map::new();
-    new_state.insert("worktrees".to_string(), json!(new_worktrees));
-    ctx.write_state(&json!(new_state));
-
-    // Run clean
-    let output = ctx.pigs(&["clean"]).assert().success();
-
+    let output = ctx.pigs(&["log", "feature"]).assert().success();
     let stdout = String::from_utf8_lossy(&output.get_output().stdout);
-    let redacted = ctx.redact_paths(&stdout);
-    assert_snapshot!(redacted);
-
-    // Verify state was cleaned
-    let cleaned_state = ctx.read_state();
-    let worktrees = cleaned_state["worktrees"].as_object().unwrap();
-    assert_eq!(worktrees.len(), 1);
-    // Key format is now "repo_name/worktree_name"
-    assert!(worktrees.contains_key("test-repo/valid"));
+    assert!(stdout.contains("true --model opus 'fix it'"), "{stdout}");
 }
 
 #[test]
-fn test_clean_with_no_invalid() {
+fn test_open_nonexistent_worktree() {
     let ctx = TestContext::new("test-repo");
 
-    // Create valid worktrees
-    ctx.pigs(&["create", "valid1"]).assert().success();
-    ctx.pigs(&["create", "valid2"]).assert().success();
+    let output = ctx.pigs(&["open", "nonexistent"]).assert().failure();
 
-    // Run clean (should find no invalid worktrees)
-    let output = ctx.pigs(&["clean"]).assert().success();
+    let stderr = String::from_utf8_lossy(&output.get_output().stderr);
+    assert!(stderr.contains("not found") || stderr.contains("No worktree"));
+}
+
+#[test]
+fn test_worktree_names_match_prefixes_and_suggest_typos() {
+    let ctx = TestContext::new("test-repo");
+    ctx.pigs(&["create", "payment-retry"]).assert().success();
+    ctx.pigs(&["create", "payment-ui"]).assert().success();
+    ctx.pigs(&["create", "docs"]).assert().success();
 
+    let output = ctx.pigs(&["dir", "payment-r"]).assert().success();
     let stdout = String::from_utf8_lossy(&output.get_output().stdout);
     assert!(
-        stdout.contains("No invalid worktrees found") || stdout.contains("All worktrees are valid")
+        stdout.trim().ends_with("test-repo-payment-retry"),
+        "{stdout}"
+    );
+    let stderr = String::from_utf8_lossy(&output.get_output().stderr);
+    assert!(
+        stderr.contains("Using worktree test-repo/payment-retry for 'payment-r'"),
+        "{stderr}"
     );
+
+    ctx.pigs(&["dir", "pay"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains(
+            "Worktree 'pay' is ambiguous; did you mean 'payment-retry' or 'payment-ui'?",
+        ));
+    ctx.pigs(&["open", "paymnet-retry"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains(
+            "Worktree 'paymnet-retry' not found; did you mean 'payment-retry'?",
+        ));
+    ctx.pigs(&["delete", "zzz"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("Worktree 'zzz' not found"));
 }
 
-// Open command tests (basic, since we can't actually launch Claude)
 #[test]
-fn test_open_specific_worktree() {
+fn test_delete_only_guesses_names_when_confirmed() {
     let ctx = TestContext::new("test-repo");
+    ctx.pigs(&["create", "feature-2"]).assert().success();
 
-    // Create worktree
-    ctx.pigs(&["create", "to-open"]).assert().success();
+    // A stale name isn't taken as a prefix, even with PIGS_YES
+    ctx.pigs(&["delete", "feature"])
+        .env("PIGS_YES", "1")
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains(
+            "Worktree 'feature' not found; did you mean 'feature-2'? Pass its exact name",
+        ));
+    assert!(ctx.worktree_exists("feature-2"));
 
-    // Mock claude command to verify it would be called
-    let output = ctx.pigs(&["open", "to-open"]).assert().success();
+    // Answering the prompt accepts the guess
+    ctx.pigs(&["delete", "feature"])
+        .write_stdin("y\ny\n")
+        .assert()
+        .success();
+    assert!(!ctx.worktree_exists("feature-2"));
 
-    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
-    assert!(stdout.contains("Opening worktree"));
+    // The same name in two repositories is never resolved by picking one
+    ctx.pigs(&["create", "shared"]).assert().success();
+    let mut state = ctx.read_state();
+    let mut other = state["worktrees"]["test-repo/shared"].clone();
+    other["repo_name"] = json!("other-repo");
+    state["worktrees"]["other-repo/shared"] = other;
+    ctx.write_state(&state);
+    ctx.pigs(&["delete", "shared"])
+        .env("PIGS_YES", "1")
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains(
+            "Worktree 'shared' exists in several repositories (other-repo/shared, test-repo/shared)",
+        ));
+    ctx.pigs(&["dir", "shared"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("use <repo>/<name>"));
+    ctx.pigs(&["delete", "test-repo/shared"])
+        .env("PIGS_YES", "1")
+        .assert()
+        .success();
+    assert!(!ctx.worktree_exists("shared"));
 }
 
 #[test]
-fn test_open_nonexistent_worktree() {
+fn test_stats_counts_created_deleted_and_stale_worktrees() {
     let ctx = TestContext::new("test-repo");
-
-    let output = ctx.pigs(&["open", "nonexistent"]).assert().failure();
-
-    let stderr = String::from_utf8_lossy(&output.get_output().stderr);
-    assert!(stderr.contains("not found") || stderr.contains("No worktree"));
+    ctx.pigs(&["create", "kept"]).assert().success();
+    ctx.pigs(&["create", "gone"]).assert().success();
+    ctx.pigs(&["delete", "gone"]).assert().success();
+    ctx.pigs(&["open", "kept"]).assert().success();
+
+    let output = ctx.pigs(&["stats", "--json"]).assert().success();
+    let json: serde_json::Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+    assert_eq!(json["totals"]["active"], 1);
+    assert_eq!(json["totals"]["created"], 2);
+    assert_eq!(json["totals"]["deleted"], 1);
+    assert!(json["totals"]["average_lifetime_hours"].is_number());
+    assert_eq!(json["repos"][0]["repo_name"], "test-repo");
+    assert_eq!(json["agent_launches"]["true"], 1);
+    let weeks = json["weeks"].as_array().unwrap();
+    assert_eq!(weeks.len(), 8);
+    assert_eq!(weeks[7]["created"], 2);
+    assert_eq!(weeks[7]["deleted"], 1);
+    assert_eq!(json["stale"], json!([]));
+
+    // Everything older than now is stale with --stale-days 0
+    let output = ctx
+        .pigs(&["stats", "--weeks", "2", "--stale-days", "0"])
+        .assert()
+        .success();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(
+        stdout.contains("1 active worktree(s) in 1 repo(s); 2 created and 1 deleted"),
+        "{stdout}"
+    );
+    assert!(stdout.contains("Agent launches: true 1"), "{stdout}");
+    assert!(
+        stdout.contains("test-repo/kept (kept) last active"),
+        "{stdout}"
+    );
 }
 
 // Migration test
@@ -908,6 +4477,177 @@ fn test_rename_command() {
         .stderr(predicates::str::contains("already exists"));
 }
 
+#[test]
+fn test_rename_branch_moves_branch_and_upstream() {
+    let ctx = TestContext::new("test-repo");
+    // Named like the repo, since pigs takes the repo name from the remote URL
+    let origin = ctx.temp_dir.path().join("remote/test-repo.git");
+    ctx.git(&["init", "--bare", origin.to_str().unwrap()]);
+    ctx.git(&["remote", "add", "origin", origin.to_str().unwrap()]);
+    ctx.pigs(&["create", "first"]).assert().success();
+    ctx.pigs(&["create", "second"]).assert().success();
+    let first = ctx.temp_dir.path().join("test-repo-first");
+    let second = ctx.temp_dir.path().join("test-repo-second");
+    let git_in = |dir: &Path, args: &[&str]| {
+        let output = std::process::Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "git {args:?}: {output:?}");
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    };
+    git_in(&first, &["push", "-u", "origin", "first"]);
+    git_in(&second, &["push", "-u", "origin", "second"]);
+    // The new name already exists on the remote for `second` only
+    git_in(&second, &["push", "origin", "second:renamed-second"]);
+    let mut state = ctx.read_state();
+    state["worktrees"]["test-repo/second"]["base_branch"] = json!("first");
+    ctx.write_state(&state);
+
+    ctx.pigs(&["rename", "first", "renamed-first", "--rename-branch"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(
+            "git push -u origin renamed-first",
+        ));
+    assert_eq!(
+        git_in(&first, &["branch", "--show-current"]),
+        "renamed-first"
+    );
+    assert!(
+        std::process::Command::new("git")
+            .args(["rev-parse", "--abbrev-ref", "@{upstream}"])
+            .current_dir(&first)
+            .output()
+            .unwrap()
+            .stdout
+            .is_empty()
+    );
+
+    ctx.pigs(&["rename", "second", "renamed-second", "--rename-branch"])
+        .assert()
+        .success();
+    assert_eq!(
+        git_in(&second, &["rev-parse", "--abbrev-ref", "@{upstream}"]),
+        "origin/renamed-second"
+    );
+
+    let state = ctx.read_state();
+    let renamed = &state["worktrees"]["test-repo/renamed-second"];
+    assert_eq!(
+        state["worktrees"]["test-repo/renamed-first"]["branch"],
+        "renamed-first"
+    );
+    assert_eq!(renamed["branch"], "renamed-second");
+    assert_eq!(renamed["base_branch"], "renamed-first");
+}
+
+#[test]
+fn test_add_scan_adopts_untracked_worktrees() {
+    let ctx = TestContext::new("test-repo");
+    ctx.pigs(&["create", "tracked"]).assert().success();
+    let manual = ctx.temp_dir.path().join("by-hand");
+    let other = ctx.temp_dir.path().join("elsewhere");
+    ctx.git(&[
+        "worktree",
+        "add",
+        "-b",
+        "feature/by-hand",
+        manual.to_str().unwrap(),
+    ]);
+    ctx.git(&["worktree", "add", "-b", "other", other.to_str().unwrap()]);
+
+    // Without a terminal or piped choice there is nothing to pick with
+    let output = ctx.pigs(&["add", "--scan"]).assert().failure();
+    let stdout = String::from_utf8_lossy(&output.get_output().stdout);
+    assert!(stdout.contains("Found 2 untracked worktrees"), "{stdout}");
+    assert!(stdout.contains("feature-by-hand"), "{stdout}");
+    assert!(!stdout.contains("test-repo-tracked"), "{stdout}");
+
+    ctx.pigs(&["add", "--scan"])
+        .write_stdin("feature-by-hand\n")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Adopted 'feature-by-hand'"));
+    let state = ctx.read_state();
+    let adopted = &state["worktrees"]["test-repo/feature-by-hand"];
+    assert_eq!(adopted["branch"], "feature/by-hand");
+    assert_eq!(
+        fs::canonicalize(adopted["path"].as_str().unwrap()).unwrap(),
+        fs::canonicalize(&manual).unwrap()
+    );
+    assert!(state["worktrees"].get("test-repo/other").is_none());
+
+    ctx.pigs(&["add", "--scan"])
+        .write_stdin("other\n")
+        .assert()
+        .success();
+    ctx.pigs(&["add", "--scan"])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("already tracked"));
+    ctx.pigs(&["add", "name", "--scan"]).assert().failure();
+}
+
+#[test]
+fn test_create_apply_patch_seeds_the_worktree() {
+    let ctx = TestContext::new("test-repo");
+    fs::write(ctx.repo_dir.join("README.md"), "# Test Repo\nseeded line\n").unwrap();
+    let patch = ctx.git(&["diff"]).stdout;
+    ctx.git(&["checkout", "--", "README.md"]);
+    let patch_file = ctx.temp_dir.path().join("seed.diff");
+    fs::write(&patch_file, &patch).unwrap();
+
+    ctx.pigs(&[
+        "create",
+        "seeded",
+        "--apply-patch",
+        patch_file.to_str().unwrap(),
+    ])
+    .assert()
+    .success()
+    .stdout(predicates::str::contains("Applied the patch"));
+    let worktree = ctx.temp_dir.path().join("test-repo-seeded");
+    assert!(
+        fs::read_to_string(worktree.join("README.md"))
+            .unwrap()
+            .contains("seeded line")
+    );
+
+    ctx.pigs(&["create", "piped", "--apply-patch", "-"])
+        .write_stdin(patch.clone())
+        .assert()
+        .success();
+    assert!(
+        fs::read_to_string(ctx.temp_dir.path().join("test-repo-piped/README.md"))
+            .unwrap()
+            .contains("seeded line")
+    );
+
+    // A patch that doesn't apply leaves nothing behind
+    fs::write(
+        &patch_file,
+        String::from_utf8_lossy(&patch).replace("Test Repo", "Other"),
+    )
+    .unwrap();
+    ctx.pigs(&[
+        "create",
+        "broken",
+        "--apply-patch",
+        patch_file.to_str().unwrap(),
+    ])
+    .assert()
+    .failure()
+    .stderr(predicates::str::contains("Failed to apply the patch"));
+    assert!(!ctx.temp_dir.path().join("test-repo-broken").exists());
+    assert!(
+        ctx.read_state()["worktrees"]
+            .get("test-repo/broken")
+            .is_none()
+    );
+}
+
 #[test]
 fn test_create_duplicate_name() {
     let ctx = TestContext::new("test-repo");
@@ -1070,6 +4810,27 @@ fn test_create_with_slash_in_branch_name() {
     assert!(String::from_utf8_lossy(&branch_output.stdout).contains("fix/bug"));
 }
 
+#[test]
+fn test_complete_tasks_lists_configured_tasks() {
+    let ctx = TestContext::new("test-repo");
+    ctx.pigs(&["complete-tasks"]).assert().success().stdout("");
+
+    fs::create_dir_all(ctx.repo_dir.join(".pigs")).unwrap();
+    fs::write(
+        ctx.repo_dir.join(".pigs/settings.json"),
+        r#"{"tasks": {"test": "cargo test", "lint": "cargo clippy"}}"#,
+    )
+    .unwrap();
+    // Tasks run commands, so they are left out until the settings are trusted
+    ctx.pigs(&["complete-tasks"]).assert().success().stdout("");
+
+    ctx.pigs(&["trust"]).assert().success();
+    ctx.pigs(&["complete-tasks"])
+        .assert()
+        .success()
+        .stdout("lint\tcargo clippy\ntest\tcargo test\n");
+}
+
 #[test]
 fn test_delete_with_slash_in_branch_name() {
     let ctx = TestContext::new("test-repo");
@@ -1102,3 +4863,66 @@ fn test_delete_with_slash_in_branch_name() {
     let key = "test-repo/feature-awesome".to_string();
     assert!(!state["worktrees"].as_object().unwrap().contains_key(&key));
 }
+
+#[test]
+fn test_export_import_moves_state_between_machines() {
+    let old = TestContext::new("test-repo");
+    old.pigs(&["create", "feature"]).assert().success();
+    old.pigs(&["dashboard", "token", "create"])
+        .assert()
+        .success();
+    let old_path = old.read_state()["worktrees"]["test-repo/feature"]["path"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    let export = old.pigs(&["export"]).assert().success();
+    let exported = String::from_utf8_lossy(&export.get_output().stdout).to_string();
+    assert!(exported.contains("test-repo/feature"), "{exported}");
+    // Dashboard tokens are secrets and stay behind
+    assert!(!exported.contains("dashboard_tokens"), "{exported}");
+    old.pigs(&["export", "--repo", "other-repo"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("No worktrees found"));
+
+    let new = TestContext::new("test-repo");
+    let file = new.temp_dir.path().join("pigs.json");
+    fs::write(&file, &exported).unwrap();
+    let old_root = Path::new(&old_path).parent().unwrap().to_str().unwrap();
+    let new_root = new.temp_dir.path().to_str().unwrap();
+    new.pigs(&[
+        "import",
+        file.to_str().unwrap(),
+        "--rewrite-prefix",
+        &format!("{old_root}:{new_root}"),
+    ])
+    .assert()
+    .success()
+    .stdout(predicates::str::contains("Imported 1 worktree(s)"))
+    .stdout(predicates::str::contains("don't exist on this machine"));
+
+    let state = new.read_state();
+    assert_eq!(
+        state["worktrees"]["test-repo/feature"]["path"],
+        new.temp_dir
+            .path()
+            .join("test-repo-feature")
+            .to_str()
+            .unwrap()
+    );
+    // The local agent setting wins over the imported one
+    assert_eq!(state["agent"][0]["name"], "test");
+
+    // Importing again keeps what is already tracked
+    new.pigs(&["import", "-"])
+        .write_stdin(exported)
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Imported 0 worktree(s)"))
+        .stdout(predicates::str::contains("Kept 1 worktree(s)"));
+    assert_eq!(
+        new.read_state()["worktrees"]["test-repo/feature"]["path"],
+        state["worktrees"]["test-repo/feature"]["path"]
+    );
+}
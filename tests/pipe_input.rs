@@ -153,6 +153,36 @@ fn test_delete_with_auto_confirm() {
         .stdout(predicate::str::contains("deleted successfully"));
 }
 
+#[test]
+fn test_delete_select_with_piped_selection() {
+    let (temp_dir, repo_path, config_dir) = setup_test_repo();
+
+    for name in ["keep", "drop-a", "drop-b"] {
+        Command::new(env!("CARGO_BIN_EXE_pigs"))
+            .current_dir(&repo_path)
+            .env("PIGS_CONFIG_DIR", &config_dir)
+            .env("PIGS_NON_INTERACTIVE", "1")
+            .env("PIGS_TEST_MODE", "1")
+            .args(["create", name])
+            .assert()
+            .success();
+    }
+
+    // One line picks the worktrees, the next confirms once for all of them
+    Command::new(env!("CARGO_BIN_EXE_pigs"))
+        .current_dir(&repo_path)
+        .env("PIGS_CONFIG_DIR", &config_dir)
+        .args(["delete", "--select", "--repo", "test-repo"])
+        .write_stdin("test-repo/drop-a, test-repo/drop-b\ny\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Deleted 2/2 worktrees"));
+
+    assert!(temp_dir.path().join("test-repo-keep").exists());
+    assert!(!temp_dir.path().join("test-repo-drop-a").exists());
+    assert!(!temp_dir.path().join("test-repo-drop-b").exists());
+}
+
 #[test]
 fn test_delete_with_env_yes() {
     // This test primarily demonstrates that PIGS_YES environment variable